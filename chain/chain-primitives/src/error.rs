@@ -185,6 +185,9 @@ pub enum Error {
     /// Invalid Balance Burnt
     #[error("Invalid Balance Burnt")]
     InvalidBalanceBurnt,
+    /// Invalid Congestion Info
+    #[error("Invalid Congestion Info")]
+    InvalidCongestionInfo,
     /// Invalid shard id
     #[error("Shard id {0} does not exist")]
     InvalidShardId(ShardId),
@@ -306,6 +309,7 @@ impl Error {
             | Error::InvalidGasPrice
             | Error::InvalidGasUsed
             | Error::InvalidBalanceBurnt
+            | Error::InvalidCongestionInfo
             | Error::InvalidShardId(_)
             | Error::InvalidStateRequest(_)
             | Error::InvalidRandomnessBeaconOutput
@@ -380,6 +384,7 @@ impl Error {
             Error::InvalidGasPrice => "invalid_gas_price",
             Error::InvalidGasUsed => "invalid_gas_used",
             Error::InvalidBalanceBurnt => "invalid_balance_burnt",
+            Error::InvalidCongestionInfo => "invalid_congestion_info",
             Error::InvalidShardId(_) => "invalid_shard_id",
             Error::InvalidStateRequest(_) => "invalid_state_request",
             Error::InvalidRandomnessBeaconOutput => "invalid_randomness_beacon_output",
@@ -58,6 +58,7 @@ use unc_primitives::challenge::{
     MaybeEncodedShardChunk, PartialState, SlashedValidator,
 };
 use unc_primitives::checked_feature;
+use unc_primitives::congestion_info::BlockCongestionInfo;
 #[cfg(feature = "new_epoch_sync")]
 use unc_primitives::epoch_manager::epoch_sync::EpochSyncInfo;
 #[cfg(feature = "new_epoch_sync")]
@@ -466,6 +467,7 @@ impl Chain {
                             0,
                             chain_genesis.gas_limit,
                             0,
+                            None,
                         ),
                     );
                 }
@@ -2902,6 +2904,7 @@ impl Chain {
         block_header: &BlockHeader,
         prev_block_header: &BlockHeader,
         is_new_chunk: bool,
+        congestion_info: BlockCongestionInfo,
     ) -> Result<ApplyChunkBlockContext, Error> {
         let epoch_id = block_header.epoch_id();
         let protocol_version = self.epoch_manager.get_epoch_protocol_version(epoch_id)?;
@@ -2915,7 +2918,7 @@ impl Chain {
             prev_block_header.next_gas_price()
         };
 
-        Ok(ApplyChunkBlockContext::from_header(block_header, gas_price))
+        Ok(ApplyChunkBlockContext::from_header(block_header, gas_price, congestion_info))
     }
 
     fn block_catch_up_postprocess(
@@ -3376,6 +3379,7 @@ impl Chain {
                 block.header(),
                 prev_block.header(),
                 is_new_chunk,
+                block.block_congestion_info(),
             )?;
             let storage_context =
                 StorageContext { storage_data_source: StorageDataSource::Db, state_patch };
@@ -3523,7 +3527,12 @@ impl Chain {
                 )));
             }
             execution_contexts.push((
-                self.get_apply_chunk_block_context(&block_header, &prev_block_header, false)?,
+                self.get_apply_chunk_block_context(
+                    &block_header,
+                    &prev_block_header,
+                    false,
+                    BlockCongestionInfo::default(),
+                )?,
                 shard_context,
             ));
         }
@@ -3680,8 +3689,14 @@ impl Chain {
             if shard_context.need_to_reshard {
                 return Ok(None);
             }
+            let congestion_info = self.get_block(&prev_chunk_block_hash)?.block_congestion_info();
             (
-                self.get_apply_chunk_block_context(&block_header, &prev_block_header, true)?,
+                self.get_apply_chunk_block_context(
+                    &block_header,
+                    &prev_block_header,
+                    true,
+                    congestion_info,
+                )?,
                 shard_context,
             )
         };
@@ -3752,6 +3767,7 @@ impl Chain {
                     apply_result.total_gas_burnt,
                     gas_limit,
                     apply_result.total_balance_burnt,
+                    apply_result.congestion_info,
                 );
                 result.push((
                     prev_chunk_block_context.block_hash,
@@ -3780,17 +3796,7 @@ impl Chain {
         }
         if let Some(snapshot_callbacks) = &self.snapshot_callbacks {
             if make_snapshot {
-                let head = self.head()?;
-                let epoch_height =
-                    self.epoch_manager.get_epoch_height_from_prev_block(&head.prev_block_hash)?;
-                let shard_uids = self
-                    .epoch_manager
-                    .get_shard_layout_from_prev_block(&head.prev_block_hash)?
-                    .shard_uids()
-                    .collect();
-                let last_block = self.get_block(&head.last_block_hash)?;
-                let make_snapshot_callback = &snapshot_callbacks.make_snapshot_callback;
-                make_snapshot_callback(head.prev_block_hash, epoch_height, shard_uids, last_block);
+                self.request_state_snapshot(snapshot_callbacks)?;
             } else if delete_snapshot {
                 let delete_snapshot_callback = &snapshot_callbacks.delete_snapshot_callback;
                 delete_snapshot_callback();
@@ -3799,6 +3805,30 @@ impl Chain {
         Ok(())
     }
 
+    /// Makes a state snapshot at the current head outside of the regular schedule,
+    /// on request of the node operator.
+    pub fn make_state_snapshot(&self) -> Result<(), Error> {
+        let Some(snapshot_callbacks) = &self.snapshot_callbacks else {
+            return Err(Error::Other("State snapshots are disabled on this node".to_string()));
+        };
+        self.request_state_snapshot(snapshot_callbacks)
+    }
+
+    fn request_state_snapshot(&self, snapshot_callbacks: &SnapshotCallbacks) -> Result<(), Error> {
+        let head = self.head()?;
+        let epoch_height =
+            self.epoch_manager.get_epoch_height_from_prev_block(&head.prev_block_hash)?;
+        let shard_uids = self
+            .epoch_manager
+            .get_shard_layout_from_prev_block(&head.prev_block_hash)?
+            .shard_uids()
+            .collect();
+        let last_block = self.get_block(&head.last_block_hash)?;
+        let make_snapshot_callback = &snapshot_callbacks.make_snapshot_callback;
+        make_snapshot_callback(head.prev_block_hash, epoch_height, shard_uids, last_block);
+        Ok(())
+    }
+
     /// Function to check whether we need to create a new snapshot while processing the current block
     /// Note that this functions is called as a part of block preprocesing, so the head is not updated to current block
     fn should_make_or_delete_snapshot(&mut self) -> Result<(bool, bool), Error> {
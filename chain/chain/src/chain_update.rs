@@ -23,6 +23,7 @@ use unc_epoch_manager::types::BlockHeaderInfo;
 use unc_epoch_manager::EpochManagerAdapter;
 use unc_primitives::block::{Block, Tip};
 use unc_primitives::block_header::BlockHeader;
+use unc_primitives::congestion_info::BlockCongestionInfo;
 #[cfg(feature = "new_epoch_sync")]
 use unc_primitives::epoch_manager::{block_info::BlockInfo, epoch_sync::EpochSyncInfo};
 use unc_primitives::hash::CryptoHash;
@@ -291,6 +292,7 @@ impl<'a> ChainUpdate<'a> {
                         gas_burnt,
                         gas_limit,
                         balance_burnt,
+                        None,
                     );
                     sum_gas_used += gas_burnt;
                     sum_balance_burnt += balance_burnt;
@@ -362,6 +364,7 @@ impl<'a> ChainUpdate<'a> {
                         apply_result.total_gas_burnt,
                         gas_limit,
                         apply_result.total_balance_burnt,
+                        apply_result.congestion_info,
                     ),
                 );
 
@@ -749,6 +752,14 @@ impl<'a> ChainUpdate<'a> {
         // during protocol version RestoreReceiptsAfterFixApplyChunks.
         // TODO(nikurt): Determine the value correctly.
         let is_first_block_with_chunk_of_version = false;
+        // The block may be missing during state sync, in which case only the
+        // congestion of this shard is known from the chunk header.
+        let congestion_info = match self.chain_store_update.get_block(block_header.hash()) {
+            Ok(block) => block.block_congestion_info(),
+            Err(_) => BlockCongestionInfo::new(
+                chunk_header.congestion_info().map(|info| (shard_id, info)).into_iter().collect(),
+            ),
+        };
 
         let apply_result = self.runtime_adapter.apply_chunk(
             RuntimeStorageConfig::new(chunk_header.prev_state_root(), true),
@@ -768,6 +779,7 @@ impl<'a> ChainUpdate<'a> {
                 gas_price,
                 challenges_result: block_header.challenges_result().clone(),
                 random_seed: *block_header.random_value(),
+                congestion_info,
             },
             &receipts,
             chunk.transactions(),
@@ -798,6 +810,7 @@ impl<'a> ChainUpdate<'a> {
             apply_result.total_gas_burnt,
             gas_limit,
             apply_result.total_balance_burnt,
+            apply_result.congestion_info,
         );
         self.chain_store_update.save_chunk_extra(block_header.hash(), &shard_uid, chunk_extra);
 
@@ -859,7 +872,11 @@ impl<'a> ChainUpdate<'a> {
                 is_new_chunk: false,
                 is_first_block_with_chunk_of_version: false,
             },
-            ApplyChunkBlockContext::from_header(&block_header, prev_block_header.next_gas_price()),
+            ApplyChunkBlockContext::from_header(
+                &block_header,
+                prev_block_header.next_gas_price(),
+                BlockCongestionInfo::default(),
+            ),
             &[],
             &[],
         )?;
@@ -978,6 +978,7 @@ impl<'a> ChainStoreUpdate<'a> {
             | DBCol::BlockHeight  // block sync needs it + genesis should be accessible
             | DBCol::_Peers
             | DBCol::RecentOutboundConnections
+            | DBCol::PeerReputations
             | DBCol::BlockMerkleTree
             | DBCol::AccountAnnouncements
             | DBCol::EpochLightClientBlocks
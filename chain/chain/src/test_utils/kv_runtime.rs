@@ -18,6 +18,7 @@ use unc_epoch_manager::{EpochManagerAdapter, RngSeed};
 use unc_pool::types::PoolIterator;
 use unc_primitives::account::{AccessKey, Account};
 use unc_primitives::block_header::{Approval, ApprovalInner};
+use unc_primitives::congestion_info::BlockCongestionInfo;
 use unc_primitives::epoch_manager::block_info::BlockInfo;
 use unc_primitives::epoch_manager::epoch_info::EpochInfo;
 use unc_primitives::epoch_manager::EpochConfig;
@@ -44,7 +45,8 @@ use unc_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 use unc_primitives::views::AllMinersView;
 use unc_primitives::views::{
     AccessKeyInfoView, AccessKeyList, CallResult, ChipView, ChipsList, ContractCodeView,
-    EpochValidatorInfo, QueryRequest, QueryResponse, QueryResponseKind, ViewStateResult,
+    DebugCallResult, EpochValidatorInfo, QueryRequest, QueryResponse, QueryResponseKind,
+    ViewStateResult,
 };
 use unc_store::test_utils::TestTriesBuilder;
 use unc_store::{
@@ -1056,6 +1058,7 @@ impl RuntimeAdapter for KeyValueRuntime {
         _shard_id: ShardId,
         _state_root: StateRoot,
         _next_block_height: BlockHeight,
+        _congestion_info: &BlockCongestionInfo,
         transactions: &mut dyn PoolIterator,
         _chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         _current_protocol_version: ProtocolVersion,
@@ -1227,6 +1230,7 @@ impl RuntimeAdapter for KeyValueRuntime {
             total_balance_burnt: 0,
             proof: None,
             processed_delayed_receipts: vec![],
+            congestion_info: None,
         })
     }
 
@@ -1309,6 +1313,18 @@ impl RuntimeAdapter for KeyValueRuntime {
                 kind: QueryResponseKind::CallResult(CallResult {
                     result: Default::default(),
                     logs: Default::default(),
+                    profile: None,
+                }),
+                block_height,
+                block_hash: *block_hash,
+            }),
+            QueryRequest::DebugCallFunction { .. } => Ok(QueryResponse {
+                kind: QueryResponseKind::DebugCallResult(DebugCallResult {
+                    result: Default::default(),
+                    logs: Default::default(),
+                    burnt_gas: 0,
+                    error: None,
+                    trace: Default::default(),
                 }),
                 block_height,
                 block_hash: *block_hash,
@@ -1411,6 +1427,8 @@ impl RuntimeAdapter for KeyValueRuntime {
         }
     }
 
+    fn set_gc_num_epochs_to_keep(&self, _gc_num_epochs_to_keep: u64) {}
+
     fn get_protocol_config(&self, _epoch_id: &EpochId) -> Result<ProtocolConfig, Error> {
         unreachable!("get_protocol_config should not be called in KeyValueRuntime");
     }
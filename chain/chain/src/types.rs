@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::Duration;
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -16,6 +17,7 @@ use unc_chain_primitives::Error;
 use unc_pool::types::PoolIterator;
 use unc_primitives::challenge::ChallengesResult;
 use unc_primitives::checked_feature;
+use unc_primitives::congestion_info::{BlockCongestionInfo, CongestionInfo};
 use unc_primitives::errors::InvalidTxError;
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::merkle::{merklize, MerklePath};
@@ -112,6 +114,9 @@ pub struct ApplyChunkResult {
     pub total_balance_burnt: Balance,
     pub proof: Option<PartialStorage>,
     pub processed_delayed_receipts: Vec<Receipt>,
+    /// Congestion of the shard after applying the chunk, `None` before the
+    /// congestion control is enabled.
+    pub congestion_info: Option<CongestionInfo>,
 }
 
 impl ApplyChunkResult {
@@ -258,6 +263,10 @@ pub enum StorageDataSource {
     /// State data is supplied from state witness, there is no state data
     /// stored on disk.
     Recorded(PartialStorage),
+    /// Trie is present in DB, extended with trie nodes and values which only
+    /// exist in memory. Flat storage is not used. Lets tools apply chunks on
+    /// top of modified state without writing it to disk.
+    DbTrieWithOverlay(Arc<HashMap<CryptoHash, Arc<[u8]>>>),
 }
 
 pub struct RuntimeStorageConfig {
@@ -289,10 +298,15 @@ pub struct ApplyChunkBlockContext {
     pub gas_price: Balance,
     pub challenges_result: ChallengesResult,
     pub random_seed: CryptoHash,
+    pub congestion_info: BlockCongestionInfo,
 }
 
 impl ApplyChunkBlockContext {
-    pub fn from_header(header: &BlockHeader, gas_price: Balance) -> Self {
+    pub fn from_header(
+        header: &BlockHeader,
+        gas_price: Balance,
+        congestion_info: BlockCongestionInfo,
+    ) -> Self {
         Self {
             height: header.height(),
             block_hash: *header.hash(),
@@ -301,6 +315,7 @@ impl ApplyChunkBlockContext {
             gas_price,
             challenges_result: header.challenges_result().clone(),
             random_seed: *header.random_value(),
+            congestion_info,
         }
     }
 }
@@ -367,6 +382,8 @@ pub trait RuntimeAdapter: Send + Sync {
     /// against the given `chain_validate` closure and runtime's transaction verifier.
     /// If the transaction is valid for both, it's added to the result and the temporary state
     /// update is preserved for validation of next transactions.
+    /// Transactions to the shards which are too congested according to `congestion_info` are
+    /// skipped.
     /// Throws an `Error` with `ErrorKind::StorageError` in case the runtime throws
     /// `RuntimeError::StorageError`.
     fn prepare_transactions(
@@ -377,6 +394,7 @@ pub trait RuntimeAdapter: Send + Sync {
         shard_id: ShardId,
         state_root: StateRoot,
         next_block_height: BlockHeight,
+        congestion_info: &BlockCongestionInfo,
         pool_iterator: &mut dyn PoolIterator,
         chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         current_protocol_version: ProtocolVersion,
@@ -390,6 +408,9 @@ pub trait RuntimeAdapter: Send + Sync {
     /// Get the block height for which garbage collection should not go over
     fn get_gc_stop_height(&self, block_hash: &CryptoHash) -> BlockHeight;
 
+    /// Changes the number of epochs for which the data is kept, used by `get_gc_stop_height`.
+    fn set_gc_num_epochs_to_keep(&self, gc_num_epochs_to_keep: u64);
+
     /// Apply transactions and receipts to given state root and return store update
     /// and new state root.
     /// Also returns transaction result for each transaction and new receipts.
@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use actix::Message;
 
+use unc_chain_configs::TransactionPoolEvictionPolicy;
 use unc_pool::types::PoolIterator;
 use unc_pool::{InsertTransactionResult, PoolIteratorWrapper, TransactionPool};
 use unc_primitives::shard_layout::{account_id_to_shard_uid, ShardLayout, ShardUId};
@@ -39,14 +40,36 @@ pub struct ShardedTransactionPool {
     /// while keeping the security of randomization of transactions in pool
     rng_seed: RngSeed,
 
-    /// If set, new transactions that bring the size of the pool over this limit will be rejected.
+    /// If set, new transactions that bring the size of the pool over this limit will either be
+    /// rejected or evict other transactions, depending on `eviction_policy`.
     /// The size is tracked and enforced separately for each shard.
     pool_size_limit: Option<u64>,
+
+    /// What to do when a new transaction doesn't fit into `pool_size_limit`.
+    eviction_policy: TransactionPoolEvictionPolicy,
 }
 
 impl ShardedTransactionPool {
-    pub fn new(rng_seed: RngSeed, pool_size_limit: Option<u64>) -> Self {
-        Self { tx_pools: HashMap::new(), rng_seed, pool_size_limit }
+    pub fn new(
+        rng_seed: RngSeed,
+        pool_size_limit: Option<u64>,
+        eviction_policy: TransactionPoolEvictionPolicy,
+    ) -> Self {
+        Self { tx_pools: HashMap::new(), rng_seed, pool_size_limit, eviction_policy }
+    }
+
+    /// Changes the size limit of all the pools, including the ones created later.
+    pub fn set_pool_size_limit(&mut self, pool_size_limit: Option<u64>) {
+        self.pool_size_limit = pool_size_limit;
+        for pool in self.tx_pools.values_mut() {
+            pool.set_total_transaction_size_limit(pool_size_limit);
+        }
+    }
+
+    /// Removes all transactions from the pools of all shards.
+    /// Returns the number of removed transactions.
+    pub fn clear(&mut self) -> usize {
+        self.tx_pools.values_mut().map(|pool| pool.clear()).sum()
     }
 
     pub fn get_pool_iterator(&mut self, shard_uid: ShardUId) -> Option<PoolIteratorWrapper<'_>> {
@@ -84,6 +107,7 @@ impl ShardedTransactionPool {
             TransactionPool::new(
                 Self::random_seed(&self.rng_seed, shard_uid.shard_id()),
                 self.pool_size_limit,
+                self.eviction_policy,
                 &shard_uid.to_string(),
             )
         })
@@ -100,8 +124,10 @@ impl ShardedTransactionPool {
         let pool = self.pool_for_shard(shard_uid);
         for tx in transactions {
             reintroduced_count += match pool.insert_transaction(tx.clone()) {
-                InsertTransactionResult::Success | InsertTransactionResult::Duplicate => 1,
-                InsertTransactionResult::NoSpaceLeft => 0,
+                InsertTransactionResult::Success
+                | InsertTransactionResult::Replaced
+                | InsertTransactionResult::Duplicate => 1,
+                InsertTransactionResult::Cancelled | InsertTransactionResult::NoSpaceLeft => 0,
             }
         }
         reintroduced_count
@@ -185,7 +211,8 @@ mod tests {
         let old_shard_layout = ShardLayout::get_simple_nightshade_layout();
         let new_shard_layout = ShardLayout::get_simple_nightshade_layout_v2();
 
-        let mut pool = ShardedTransactionPool::new(TEST_SEED, None);
+        let mut pool =
+            ShardedTransactionPool::new(TEST_SEED, None, TransactionPoolEvictionPolicy::Reject);
 
         let mut shard_id_to_accounts = HashMap::new();
         shard_id_to_accounts.insert(0, vec!["aaa", "abcd", "a-a-a-a-a"]);
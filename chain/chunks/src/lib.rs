@@ -110,6 +110,7 @@ use unc_network::types::{
 };
 use unc_network::types::{NetworkRequests, PeerManagerMessageRequest};
 use unc_primitives::block::Tip;
+use unc_primitives::congestion_info::CongestionInfo;
 use unc_primitives::errors::EpochError;
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::merkle::{verify_path, MerklePath};
@@ -1875,6 +1876,7 @@ impl ShardsManager {
         prev_outgoing_receipts: &[Receipt],
         prev_outgoing_receipts_root: CryptoHash,
         tx_root: CryptoHash,
+        congestion_info: Option<CongestionInfo>,
         signer: &dyn ValidatorSigner,
         rs: &mut ReedSolomonWrapper,
         protocol_version: ProtocolVersion,
@@ -1895,6 +1897,7 @@ impl ShardsManager {
             transactions,
             prev_outgoing_receipts,
             prev_outgoing_receipts_root,
+            congestion_info,
             signer,
             protocol_version,
         )
@@ -144,6 +144,7 @@ impl ChunkTestFixture {
             &receipts,
             receipts_root,
             MerkleHash::default(),
+            None,
             &signer,
             &mut rs,
             PROTOCOL_VERSION,
@@ -642,6 +642,14 @@ pub enum QueryError {
         block_height: unc_primitives::types::BlockHeight,
         block_hash: unc_primitives::hash::CryptoHash,
     },
+    #[error(
+        "History of account {requested_account_id} at block #{block_height} is not retained on this partial archival node"
+    )]
+    AccountNotRetained {
+        requested_account_id: unc_primitives::types::AccountId,
+        block_height: unc_primitives::types::BlockHeight,
+        block_hash: unc_primitives::hash::CryptoHash,
+    },
     #[error("Block either has never been observed on the node or has been garbage collected: {block_reference:?}")]
     UnknownBlock { block_reference: unc_primitives::types::BlockReference },
     // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
@@ -823,6 +831,12 @@ pub struct TxStatus {
 pub enum TxStatusError {
     ChainError(unc_chain_primitives::Error),
     MissingTransaction(CryptoHash),
+    /// The transaction is known, but its outcome was dropped from cold
+    /// storage because the signer isn't retained on this partial archival node.
+    AccountNotRetained {
+        transaction_hash: CryptoHash,
+        signer_account_id: AccountId,
+    },
     InternalError(String),
     TimeoutError,
 }
@@ -922,6 +936,14 @@ pub enum GetStateChangesError {
     UnknownBlock { error_message: String },
     #[error("There are no fully synchronized blocks yet")]
     NotSyncedYet,
+    #[error(
+        "History of account {requested_account_id} at block #{block_height} is not retained on this partial archival node"
+    )]
+    AccountNotRetained {
+        requested_account_id: unc_primitives::types::AccountId,
+        block_height: unc_primitives::types::BlockHeight,
+        block_hash: unc_primitives::hash::CryptoHash,
+    },
     // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
     // expected cases, we cannot statically guarantee that no other errors will be returned
     // in the future.
@@ -999,6 +1021,11 @@ pub enum GetExecutionOutcomeError {
         transaction_or_receipt_id: unc_primitives::hash::CryptoHash,
         shard_id: unc_primitives::types::ShardId,
     },
+    #[error("{transaction_or_receipt_id} is not known and history of account {requested_account_id} is not retained on this partial archival node")]
+    AccountNotRetained {
+        transaction_or_receipt_id: unc_primitives::hash::CryptoHash,
+        requested_account_id: unc_primitives::types::AccountId,
+    },
     #[error("Internal error: {error_message}")]
     InternalError { error_message: String },
     // NOTE: Currently, the underlying errors are too broad, and while we tried to handle
@@ -1246,6 +1273,42 @@ impl From<std::io::Error> for GetSplitStorageInfoError {
     }
 }
 
+/// Operator actions performed by `ClientActor` on request of the admin API.
+#[derive(Debug)]
+pub enum AdminMessage {
+    /// Makes a state snapshot at the current head.
+    MakeStateSnapshot,
+    /// Removes all transactions from the transaction pool.
+    DrainTransactionPool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AdminResponse {
+    StateSnapshotRequested,
+    TransactionPoolDrained { num_transactions: usize },
+}
+
+impl Message for AdminMessage {
+    type Result = Result<AdminResponse, AdminError>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AdminError {
+    #[error("IO Error: {0}")]
+    IOError(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<unc_chain_primitives::Error> for AdminError {
+    fn from(error: unc_chain_primitives::Error) -> Self {
+        match error {
+            unc_chain_primitives::Error::IOErr(error) => Self::IOError(error.to_string()),
+            _ => Self::Other(error.to_string()),
+        }
+    }
+}
+
 #[cfg(feature = "sandbox")]
 #[derive(Debug)]
 pub enum SandboxMessage {
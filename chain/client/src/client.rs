@@ -56,7 +56,7 @@ use unc_epoch_manager::shard_tracker::ShardTracker;
 use unc_epoch_manager::EpochManagerAdapter;
 use unc_network::types::{AccountKeys, ChainInfo, PeerManagerMessageRequest, SetChainInfo};
 use unc_network::types::{
-    HighestHeightPeerInfo, NetworkRequests, PeerManagerAdapter, ReasonForBan,
+    HighestHeightPeerInfo, NetworkRequests, PeerBehaviour, PeerManagerAdapter, ReasonForBan,
 };
 use unc_o11y::log_assert;
 use unc_o11y::WithSpanContextExt;
@@ -65,7 +65,7 @@ use unc_primitives::block::{Approval, ApprovalInner, ApprovalMessage, Block, Blo
 use unc_primitives::block_header::ApprovalType;
 use unc_primitives::challenge::{Challenge, ChallengeBody};
 use unc_primitives::epoch_manager::RngSeed;
-use unc_primitives::errors::EpochError;
+use unc_primitives::errors::{EpochError, InvalidTxError};
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::merkle::{merklize, MerklePath, PartialMerkleTree};
 use unc_primitives::network::PeerId;
@@ -192,12 +192,40 @@ pub struct Client {
 }
 
 impl Client {
-    pub(crate) fn update_client_config(&self, update_client_config: UpdateableClientConfig) {
+    pub(crate) fn update_client_config(&mut self, update_client_config: UpdateableClientConfig) {
+        if let Err(err) = update_client_config.validate() {
+            warn!(target: "config", %err, "Ignoring the invalid ClientConfig update");
+            return;
+        }
+        let current = UpdateableClientConfig {
+            expected_shutdown: self.config.expected_shutdown.get(),
+            resharding_config: self.config.resharding_config.get(),
+            produce_chunk_add_transactions_time_limit: self
+                .config
+                .produce_chunk_add_transactions_time_limit
+                .get(),
+            gc: self.config.gc.clone(),
+            tracked_shards: self.config.tracked_shards.clone(),
+            transaction_pool_size_limit: self.config.transaction_pool_size_limit,
+        };
+        self.config.config_updates.record(unc_chain_configs::config_diff(
+            "client_config",
+            &current,
+            &update_client_config,
+        ));
+
         self.config.expected_shutdown.update(update_client_config.expected_shutdown);
         self.config.resharding_config.update(update_client_config.resharding_config);
         self.config
             .produce_chunk_add_transactions_time_limit
             .update(update_client_config.produce_chunk_add_transactions_time_limit);
+        self.runtime_adapter
+            .set_gc_num_epochs_to_keep(update_client_config.gc.gc_num_epochs_to_keep());
+        self.config.gc = update_client_config.gc;
+        self.shard_tracker.set_tracked_shards(&update_client_config.tracked_shards);
+        self.config.tracked_shards = update_client_config.tracked_shards;
+        self.config.transaction_pool_size_limit = update_client_config.transaction_pool_size_limit;
+        self.sharded_tx_pool.set_pool_size_limit(update_client_config.transaction_pool_size_limit);
     }
 }
 
@@ -266,8 +294,11 @@ impl Client {
             chain.chain_store(),
             chain_config.background_migration_threads,
         )?;
-        let sharded_tx_pool =
-            ShardedTransactionPool::new(rng_seed, config.transaction_pool_size_limit);
+        let sharded_tx_pool = ShardedTransactionPool::new(
+            rng_seed,
+            config.transaction_pool_size_limit,
+            config.transaction_pool_eviction_policy,
+        );
         let sync_status = SyncStatus::AwaitingPeers;
         let genesis_block = chain.genesis_block();
         let epoch_sync = EpochSync::new(
@@ -898,6 +929,7 @@ impl Client {
             &outgoing_receipts,
             outgoing_receipts_root,
             tx_root,
+            chunk_extra.congestion_info(),
             &*validator_signer,
             &mut self.rs_for_chunk_production,
             protocol_version,
@@ -982,6 +1014,8 @@ impl Client {
 
         let transactions = if let Some(mut iter) = sharded_tx_pool.get_pool_iterator(shard_uid) {
             let transaction_validity_period = chain.transaction_validity_period;
+            let congestion_info =
+                chain.get_block(prev_block_header.hash())?.block_congestion_info();
             runtime.prepare_transactions(
                 prev_block_header.next_gas_price(),
                 gas_limit,
@@ -992,6 +1026,7 @@ impl Client {
                 // passing it will result in a more conservative check and will not accidentally allow
                 // invalid transactions to be included.
                 prev_block_header.height() + 1,
+                &congestion_info,
                 &mut iter,
                 &mut |tx: &SignedTransaction| -> bool {
                     chain
@@ -1129,6 +1164,20 @@ impl Client {
                     self.request_block(prev_hash, peer_id)
                 }
             }
+            Err(err) if err.is_bad_data() => {
+                debug!(target: "chain", ?err, "invalid block");
+                let behaviour = match err {
+                    unc_chain::Error::InvalidChunk
+                    | unc_chain::Error::InvalidChunkProofs(_)
+                    | unc_chain::Error::InvalidChunkState(_)
+                    | unc_chain::Error::InvalidChunkMask
+                    | unc_chain::Error::InvalidChunkReceiptsRoot
+                    | unc_chain::Error::InvalidChunkHeadersRoot
+                    | unc_chain::Error::InvalidChunkTxRoot => PeerBehaviour::InvalidChunk,
+                    _ => PeerBehaviour::InvalidBlock,
+                };
+                self.report_peer_behaviour(peer_id, behaviour);
+            }
             err => {
                 debug!(target: "chain", ?err, "some other error");
             }
@@ -2181,6 +2230,27 @@ impl Client {
 
         let shard_id =
             self.epoch_manager.account_id_to_shard_id(&tx.transaction.signer_id, &epoch_id)?;
+
+        // Transactions to a congested shard wouldn't be included into chunks until the shard
+        // accepts transactions again, so they are rejected right away.
+        let receiver_shard_id =
+            self.epoch_manager.account_id_to_shard_id(&tx.transaction.receiver_id, &epoch_id)?;
+        if receiver_shard_id != shard_id {
+            let block_congestion_info =
+                self.chain.get_block(&head.last_block_hash)?.block_congestion_info();
+            let config = self.runtime_adapter.get_protocol_config(&epoch_id)?;
+            if let Some(congestion_info) = block_congestion_info.get(receiver_shard_id) {
+                if !congestion_info
+                    .shard_accepts_transactions(&config.runtime_config.congestion_control_config)
+                {
+                    debug!(target: "client", tx_hash = ?tx.get_hash(), receiver_shard_id, "Invalid tx: receiver shard is congested");
+                    return Ok(ProcessTxResponse::InvalidTx(InvalidTxError::ShardCongested {
+                        shard_id: receiver_shard_id,
+                    }));
+                }
+            }
+        }
+
         let care_about_shard =
             self.shard_tracker.care_about_shard(me, &head.last_block_hash, shard_id, true);
         let will_care_about_shard =
@@ -2218,6 +2288,12 @@ impl Client {
                         InsertTransactionResult::Success => {
                             trace!(target: "client", ?shard_uid, tx_hash = ?tx.get_hash(), "Recorded a transaction.");
                         }
+                        InsertTransactionResult::Replaced => {
+                            trace!(target: "client", ?shard_uid, tx_hash = ?tx.get_hash(), "Replaced a pending transaction with the same nonce.");
+                        }
+                        InsertTransactionResult::Cancelled => {
+                            trace!(target: "client", ?shard_uid, tx_hash = ?tx.get_hash(), "Cancelled a pending transaction with the same nonce.");
+                        }
                         InsertTransactionResult::Duplicate => {
                             trace!(target: "client", ?shard_uid, tx_hash = ?tx.get_hash(), "Duplicate transaction, not forwarding it.");
                             return Ok(ProcessTxResponse::ValidTx);
@@ -2543,6 +2619,12 @@ impl Client {
             NetworkRequests::BanPeer { peer_id, ban_reason },
         ));
     }
+
+    pub fn report_peer_behaviour(&self, peer_id: PeerId, behaviour: PeerBehaviour) {
+        self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
+            NetworkRequests::ReportPeerBehaviour { peer_id, behaviour },
+        ));
+    }
 }
 
 impl Client {
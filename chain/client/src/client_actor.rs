@@ -53,14 +53,15 @@ use unc_chunks::adapter::ShardsManagerRequestFromClient;
 use unc_chunks::client::ShardsManagerResponse;
 use unc_chunks::logic::cares_about_shard_this_or_next_epoch;
 use unc_client_primitives::types::{
-    Error, GetClientConfig, GetClientConfigError, GetNetworkInfo, NetworkInfoResponse,
-    StateSyncStatus, Status, StatusError, StatusSyncInfo, SyncStatus,
+    AdminError, AdminMessage, AdminResponse, Error, GetClientConfig, GetClientConfigError,
+    GetNetworkInfo, NetworkInfoResponse, StateSyncStatus, Status, StatusError, StatusSyncInfo,
+    SyncStatus,
 };
 use unc_epoch_manager::shard_tracker::ShardTracker;
 use unc_epoch_manager::EpochManagerAdapter;
 use unc_network::types::ReasonForBan;
 use unc_network::types::{
-    NetworkInfo, NetworkRequests, PeerManagerAdapter, PeerManagerMessageRequest,
+    NetworkInfo, NetworkRequests, PeerBehaviour, PeerManagerAdapter, PeerManagerMessageRequest,
 };
 use unc_o11y::{handler_debug_span, OpenTelemetrySpanExt, WithSpanContext, WithSpanContextExt};
 use unc_performance_metrics;
@@ -1111,7 +1112,7 @@ impl ClientActor {
     fn check_triggers(&mut self, ctx: &mut Context<ClientActor>) -> Duration {
         let _span = tracing::debug_span!(target: "client", "check_triggers").entered();
         if let Some(config_updater) = &mut self.config_updater {
-            config_updater.try_update(&|updateable_client_config| {
+            config_updater.try_update(&mut |updateable_client_config| {
                 self.client.update_client_config(updateable_client_config)
             });
         }
@@ -1385,8 +1386,15 @@ impl ClientActor {
             info!(target: "client", "Received an empty set of block headers");
             return true;
         }
+        let prev_header_height = self.client.chain.header_head().map(|tip| tip.height).ok();
         match self.client.sync_block_headers(headers) {
-            Ok(_) => true,
+            Ok(_) => {
+                let header_height = self.client.chain.header_head().map(|tip| tip.height).ok();
+                if header_height > prev_header_height {
+                    self.client.report_peer_behaviour(peer_id, PeerBehaviour::UsefulHeaderSync);
+                }
+                true
+            }
             Err(err) => {
                 if err.is_bad_data() {
                     error!(target: "client", ?err, "Error processing sync blocks");
@@ -1983,6 +1991,30 @@ impl Handler<WithSpanContext<GetClientConfig>> for ClientActor {
     }
 }
 
+impl Handler<WithSpanContext<AdminMessage>> for ClientActor {
+    type Result = Result<AdminResponse, AdminError>;
+
+    #[perf]
+    fn handle(
+        &mut self,
+        msg: WithSpanContext<AdminMessage>,
+        _: &mut Context<Self>,
+    ) -> Self::Result {
+        let (_span, msg) = handler_debug_span!(target: "client", msg);
+        tracing::info!(target: "client", ?msg, "Admin request");
+        match msg {
+            AdminMessage::MakeStateSnapshot => {
+                self.client.chain.make_state_snapshot()?;
+                Ok(AdminResponse::StateSnapshotRequested)
+            }
+            AdminMessage::DrainTransactionPool => {
+                let num_transactions = self.client.sharded_tx_pool.clear();
+                Ok(AdminResponse::TransactionPoolDrained { num_transactions })
+            }
+        }
+    }
+}
+
 impl Handler<WithSpanContext<SyncMessage>> for ClientActor {
     type Result = ();
 
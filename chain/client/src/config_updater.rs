@@ -22,15 +22,22 @@ impl ConfigUpdater {
         Self { rx_config_update, updateable_configs_error: None }
     }
 
+    /// Returns a new receiver of the config updates, for the components which
+    /// apply the non-client parts of `UpdateableConfigs`.
+    pub fn subscribe(
+        &self,
+    ) -> Receiver<Result<UpdateableConfigs, Arc<UpdateableConfigLoaderError>>> {
+        self.rx_config_update.resubscribe()
+    }
+
     /// Check if any of the configs were updated.
     /// If they did, the receiver (rx_config_update) will contain a clone of the new configs.
-    pub fn try_update(&mut self, update_client_config_fn: &dyn Fn(UpdateableClientConfig)) {
+    pub fn try_update(&mut self, update_client_config_fn: &mut dyn FnMut(UpdateableClientConfig)) {
         while let Ok(maybe_updateable_configs) = self.rx_config_update.try_recv() {
             match maybe_updateable_configs {
                 Ok(updateable_configs) => {
                     if let Some(client_config) = updateable_configs.client_config {
                         update_client_config_fn(client_config);
-                        tracing::info!(target: "config", "Updated ClientConfig");
                     }
                     self.updateable_configs_error = None;
                 }
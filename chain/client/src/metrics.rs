@@ -473,6 +473,15 @@ pub(crate) static STATE_SYNC_EXTERNAL_PARTS_FAILED: Lazy<IntCounterVec> = Lazy::
     .unwrap()
 });
 
+pub(crate) static STATE_SYNC_PARTS_BY_SOURCE: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "unc_state_sync_parts_by_source_total",
+        "Number of attempts to retrieve state parts, by source and result",
+        &["shard_id", "source", "result"],
+    )
+    .unwrap()
+});
+
 pub(crate) static STATE_SYNC_EXTERNAL_PARTS_REQUEST_DELAY: Lazy<HistogramVec> = Lazy::new(|| {
     try_create_histogram_vec(
         "unc_state_sync_external_parts_request_delay_sec",
@@ -355,6 +355,7 @@ mod test {
                 highest_block_hash: Default::default(),
                 tracked_shards: vec![],
                 archival: false,
+                reputation: 0,
             })
             .collect()
     }
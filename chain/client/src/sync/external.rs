@@ -1,10 +1,13 @@
 use crate::metrics;
 use futures::TryStreamExt;
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
 use std::io::{Read, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
+use unc_primitives::hash::{hash, CryptoHash};
 use unc_primitives::types::{EpochId, ShardId};
 
 /// Connection to the external storage.
@@ -24,12 +27,51 @@ pub enum ExternalConnection {
         reqwest_client: Arc<reqwest::Client>,
         bucket: String,
     },
+    Http {
+        client: Arc<reqwest::Client>,
+        // URL without a trailing slash.
+        base_url: String,
+        // Sent with every request.
+        headers: reqwest::header::HeaderMap,
+        // Whether parts without a checksum file are accepted.
+        allow_missing_checksums: bool,
+    },
 }
 
+/// Number of attempts of a single request to an HTTP server before giving up.
+const HTTP_MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first retry of a request to an HTTP server, doubled on every retry.
+const HTTP_INITIAL_RETRY_DELAY: Duration = Duration::from_millis(200);
+/// Suffix of the file that holds the checksum of a state part stored on an HTTP server.
+const HTTP_CHECKSUM_SUFFIX: &str = ".sha256";
+
 const GCS_ENCODE_SET: &percent_encoding::AsciiSet =
     &percent_encoding::NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_');
 
 impl ExternalConnection {
+    /// Creates a connection to an HTTP(S) server.
+    pub fn new_http(
+        base_url: &str,
+        headers: &BTreeMap<String, String>,
+        allow_missing_checksums: bool,
+        timeout: Duration,
+    ) -> Result<Self, anyhow::Error> {
+        let mut header_map = reqwest::header::HeaderMap::new();
+        for (name, value) in headers {
+            header_map.insert(
+                reqwest::header::HeaderName::from_bytes(name.as_bytes())?,
+                reqwest::header::HeaderValue::from_str(value)?,
+            );
+        }
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(ExternalConnection::Http {
+            client: Arc::new(client),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            headers: header_map,
+            allow_missing_checksums,
+        })
+    }
+
     pub async fn get_part(
         &self,
         shard_id: ShardId,
@@ -75,6 +117,14 @@ impl ExternalConnection {
                     }
                 }
             }
+            ExternalConnection::Http { client, base_url, headers, allow_missing_checksums } => {
+                let url = format!("{}/{}", base_url, location);
+                with_retries(|| http_get_verified(client, headers, *allow_missing_checksums, &url))
+                    .await.map_err(|err| {
+                    tracing::debug!(target: "sync", %shard_id, location, ?err, "HTTP state_part request failed");
+                    err
+                })
+            }
         }
     }
 
@@ -131,6 +181,20 @@ impl ExternalConnection {
                 tracing::debug!(target: "state_sync_dump", shard_id, part_length = state_part.len(), ?location, "Wrote a state part to GCS");
                 Ok(())
             }
+            ExternalConnection::Http { client, base_url, headers, .. } => {
+                let url = format!("{}/{}", base_url, location);
+                // The checksum is written after the part, so that readers never
+                // see a checksum without the corresponding part.
+                let checksum = hash(state_part).to_string();
+                with_retries(|| http_put(client, headers, base_url, location, state_part)).await?;
+                let checksum_location = format!("{}{}", location, HTTP_CHECKSUM_SUFFIX);
+                with_retries(|| {
+                    http_put(client, headers, base_url, &checksum_location, checksum.as_bytes())
+                })
+                .await?;
+                tracing::debug!(target: "state_sync_dump", shard_id, part_length = state_part.len(), ?url, "Wrote a state part to an HTTP server");
+                Ok(())
+            }
         }
     }
 
@@ -201,10 +265,163 @@ impl ExternalConnection {
                     .flatten()
                     .collect())
             }
+            ExternalConnection::Http { client, base_url, headers, .. } => {
+                let url = format!("{}/{}/", base_url, directory_path);
+                tracing::debug!(target: "state_sync_dump", shard_id, ?url, "List state parts on an HTTP server");
+                let file_names = with_retries(|| http_list(client, headers, &url)).await?;
+                Ok(file_names
+                    .into_iter()
+                    .filter(|file_name| !file_name.ends_with(HTTP_CHECKSUM_SUFFIX))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Runs the given request up to `HTTP_MAX_ATTEMPTS` times with an exponential backoff.
+/// Only transient failures are retried, see `is_transient_http_error`.
+async fn with_retries<T, F, Fut>(mut request: F) -> Result<T, anyhow::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, anyhow::Error>>,
+{
+    let mut delay = HTTP_INITIAL_RETRY_DELAY;
+    let mut attempt = 1;
+    loop {
+        match request().await {
+            Ok(result) => return Ok(result),
+            Err(err) if attempt < HTTP_MAX_ATTEMPTS && is_transient_http_error(&err) => {
+                tracing::debug!(target: "sync", attempt, ?err, "HTTP request failed, will retry");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
         }
     }
 }
 
+/// Returns whether the failed HTTP request may succeed if repeated: the
+/// request failed on the way to or from the server, or the server responded
+/// with a `5xx` status. Client errors such as `404 Not Found`, checksum
+/// mismatches and malformed responses won't go away on their own.
+fn is_transient_http_error(err: &anyhow::Error) -> bool {
+    if let Some(err) = err.downcast_ref::<reqwest::Error>() {
+        return match err.status() {
+            Some(status) => status.is_server_error(),
+            None => err.is_timeout() || err.is_connect() || err.is_request() || err.is_body(),
+        };
+    }
+    err.downcast_ref::<std::io::Error>().is_some()
+}
+
+/// Downloads the file at the given URL and verifies it against the checksum
+/// stored next to it. A missing checksum is an error unless
+/// `allow_missing_checksums` is set.
+async fn http_get_verified(
+    client: &reqwest::Client,
+    headers: &reqwest::header::HeaderMap,
+    allow_missing_checksums: bool,
+    url: &str,
+) -> Result<Vec<u8>, anyhow::Error> {
+    let data =
+        client.get(url).headers(headers.clone()).send().await?.error_for_status()?.bytes().await?;
+    let checksum_url = format!("{}{}", url, HTTP_CHECKSUM_SUFFIX);
+    let response = client.get(&checksum_url).headers(headers.clone()).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        if allow_missing_checksums {
+            return Ok(data.to_vec());
+        }
+        anyhow::bail!("Missing checksum for {url} at {checksum_url}");
+    }
+    let expected = response.error_for_status()?.text().await?;
+    let expected: CryptoHash = expected
+        .trim()
+        .parse()
+        .map_err(|err| anyhow::anyhow!("Malformed checksum at {checksum_url}: {err}"))?;
+    let actual = hash(&data);
+    if actual != expected {
+        anyhow::bail!("Checksum mismatch for {url}: expected {expected}, got {actual}");
+    }
+    Ok(data.to_vec())
+}
+
+/// Uploads a file with a PUT request. WebDAV servers reject uploads into
+/// missing directories with `409 Conflict`, in which case the directories are
+/// created with MKCOL requests and the upload is repeated.
+async fn http_put(
+    client: &reqwest::Client,
+    headers: &reqwest::header::HeaderMap,
+    base_url: &str,
+    location: &str,
+    data: &[u8],
+) -> Result<(), anyhow::Error> {
+    let url = format!("{}/{}", base_url, location);
+    let put = || client.put(&url).headers(headers.clone()).body(data.to_vec()).send();
+    let response = put().await?;
+    if response.status() != reqwest::StatusCode::CONFLICT {
+        response.error_for_status()?;
+        return Ok(());
+    }
+    let mkcol = reqwest::Method::from_bytes(b"MKCOL")?;
+    let mut directory = base_url.to_string();
+    let segments: Vec<&str> = location.split('/').collect();
+    for segment in &segments[..segments.len() - 1] {
+        directory = format!("{}/{}", directory, segment);
+        let response = client
+            .request(mkcol.clone(), format!("{}/", directory))
+            .headers(headers.clone())
+            .send()
+            .await?;
+        // `405 Method Not Allowed` means that the directory already exists.
+        if response.status() != reqwest::StatusCode::METHOD_NOT_ALLOWED {
+            response.error_for_status()?;
+        }
+    }
+    put().await?.error_for_status()?;
+    Ok(())
+}
+
+/// Lists the files in a directory with a WebDAV PROPFIND request.
+/// A missing directory is treated as an empty one.
+async fn http_list(
+    client: &reqwest::Client,
+    headers: &reqwest::header::HeaderMap,
+    url: &str,
+) -> Result<Vec<String>, anyhow::Error> {
+    let response = client
+        .request(reqwest::Method::from_bytes(b"PROPFIND")?, url)
+        .headers(headers.clone())
+        .header("Depth", "1")
+        .send()
+        .await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(vec![]);
+    }
+    let body = response.error_for_status()?.text().await?;
+    Ok(parse_propfind_file_names(&body))
+}
+
+/// Extracts the file names from the `href` elements of a PROPFIND response,
+/// skipping directories.
+fn parse_propfind_file_names(body: &str) -> Vec<String> {
+    static HREF_RE: Lazy<regex::Regex> = Lazy::new(|| {
+        regex::Regex::new(r"(?i)<(?:[a-z0-9]+:)?href>([^<]*)</(?:[a-z0-9]+:)?href>").unwrap()
+    });
+    HREF_RE
+        .captures_iter(body)
+        .filter_map(|captures| {
+            let href = captures.get(1)?.as_str().trim();
+            if href.ends_with('/') {
+                return None;
+            }
+            let file_name = href.rsplit('/').next()?;
+            let file_name = percent_encoding::percent_decode_str(file_name).decode_utf8().ok()?;
+            Some(file_name.to_string())
+        })
+        .collect()
+}
+
 /// Construct a location on the external storage.
 pub fn external_storage_location(
     chain_id: &str,
@@ -332,10 +549,15 @@ fn create_bucket(
 #[cfg(test)]
 mod test {
     use crate::sync::external::{
-        get_num_parts_from_filename, get_part_id_from_filename, is_part_filename, part_filename,
-        ExternalConnection,
+        get_num_parts_from_filename, get_part_id_from_filename, is_part_filename,
+        is_transient_http_error, parse_propfind_file_names, part_filename, ExternalConnection,
     };
     use rand::distributions::{Alphanumeric, DistString};
+    use std::collections::{BTreeMap, HashMap};
+    use std::io::{BufRead, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
     use unc_o11y::testonly::init_test_logger;
 
     fn random_string(rand_len: usize) -> String {
@@ -409,4 +631,165 @@ mod test {
         let download_data = rt.block_on(async { connection.get_part(0, &full_filename).await });
         assert!(download_data.is_err(), "{:?}", download_data);
     }
+
+    type HttpFiles = Arc<Mutex<HashMap<String, Vec<u8>>>>;
+
+    /// Starts a minimal WebDAV-like HTTP server that keeps files in memory.
+    /// Returns its URL and the files it serves.
+    fn start_http_server() -> (String, HttpFiles) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let files = HttpFiles::default();
+        let server_files = files.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if let Ok(mut stream) = stream {
+                    handle_http_request(&mut stream, &server_files);
+                }
+            }
+        });
+        (url, files)
+    }
+
+    fn handle_http_request(stream: &mut TcpStream, files: &HttpFiles) {
+        let mut reader = std::io::BufReader::new(stream.try_clone().unwrap());
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut request_line = request_line.split_whitespace();
+        let method = request_line.next().unwrap_or_default().to_string();
+        let path = request_line.next().unwrap_or_default().to_string();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+
+        let mut files = files.lock().unwrap();
+        let (status, response) = match method.as_str() {
+            "GET" => match files.get(&path) {
+                Some(data) => ("200 OK", data.clone()),
+                None => ("404 Not Found", vec![]),
+            },
+            "PUT" => {
+                files.insert(path, body);
+                ("201 Created", vec![])
+            }
+            "PROPFIND" => {
+                let hrefs: String = files
+                    .keys()
+                    .filter(|name| name.starts_with(&path))
+                    .map(|name| format!("<D:response><D:href>{}</D:href></D:response>", name))
+                    .collect();
+                let response = format!("<?xml version=\"1.0\"?><D:multistatus xmlns:D=\"DAV:\"><D:response><D:href>{}</D:href></D:response>{}</D:multistatus>", path, hrefs);
+                ("207 Multi-Status", response.into_bytes())
+            }
+            _ => ("405 Method Not Allowed", vec![]),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            response.len()
+        )
+        .unwrap();
+        stream.write_all(&response).unwrap();
+    }
+
+    #[test]
+    fn test_parse_propfind_file_names() {
+        let body = r#"<d:multistatus xmlns:d="DAV:">
+            <d:response><d:href>/parts/shard_id=0/</d:href></d:response>
+            <d:response><d:href>/parts/shard_id=0/state_part_000000_of_000002</d:href></d:response>
+            <d:response><d:href>/parts/shard_id%3D0/state_part_000001_of_000002</d:href></d:response>
+        </d:multistatus>"#;
+        assert_eq!(
+            parse_propfind_file_names(body),
+            vec!["state_part_000000_of_000002", "state_part_000001_of_000002"]
+        );
+    }
+
+    #[test]
+    fn test_transient_http_errors() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (url, _files) = start_http_server();
+        let client = reqwest::Client::new();
+
+        // Not found.
+        let err = rt
+            .block_on(async {
+                client.get(format!("{}/missing", url)).send().await?.error_for_status()
+            })
+            .unwrap_err();
+        assert!(!is_transient_http_error(&err.into()));
+
+        // Nothing listens on the port any more.
+        let closed_url = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let err = rt.block_on(client.get(closed_url).send()).unwrap_err();
+        assert!(is_transient_http_error(&err.into()));
+
+        let err = anyhow::anyhow!("Checksum mismatch");
+        assert!(!is_transient_http_error(&err));
+    }
+
+    #[test]
+    fn test_http_upload_list_download() {
+        init_test_logger();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let (url, files) = start_http_server();
+        let connection =
+            ExternalConnection::new_http(&url, &BTreeMap::new(), false, Duration::from_secs(5))
+                .unwrap();
+
+        let dir = "chain_id=test/epoch_height=1/epoch_id=test/shard_id=0".to_string();
+        let filename = part_filename(0, 1);
+        let full_filename = format!("{}/{}", dir, filename);
+        let data: Vec<u8> = random_string(1000).into();
+
+        let files_before = rt.block_on(connection.list_state_parts(0, &dir)).unwrap();
+        assert!(files_before.is_empty(), "{:?}", files_before);
+
+        rt.block_on(connection.put_state_part(&data, 0, &full_filename)).unwrap();
+
+        // The checksum is stored next to the part, but isn't listed as a part.
+        let files_after = rt.block_on(connection.list_state_parts(0, &dir)).unwrap();
+        assert_eq!(files_after, vec![filename]);
+        assert!(files.lock().unwrap().contains_key(&format!("/{}.sha256", full_filename)));
+
+        let downloaded = rt.block_on(connection.get_part(0, &full_filename)).unwrap();
+        assert_eq!(downloaded, data);
+
+        // A corrupted part fails the checksum verification.
+        files.lock().unwrap().insert(format!("/{}", full_filename), b"corrupted".to_vec());
+        let downloaded = rt.block_on(connection.get_part(0, &full_filename));
+        assert!(downloaded.is_err(), "{:?}", downloaded);
+
+        // A part without a checksum is rejected unless missing checksums are allowed.
+        files.lock().unwrap().insert(format!("/{}", full_filename), data.clone());
+        files.lock().unwrap().remove(&format!("/{}.sha256", full_filename));
+        let downloaded = rt.block_on(connection.get_part(0, &full_filename));
+        assert!(downloaded.is_err(), "{:?}", downloaded);
+        let lenient_connection =
+            ExternalConnection::new_http(&url, &BTreeMap::new(), true, Duration::from_secs(5))
+                .unwrap();
+        let downloaded = rt.block_on(lenient_connection.get_part(0, &full_filename)).unwrap();
+        assert_eq!(downloaded, data);
+
+        // A missing part can't be downloaded.
+        let missing = format!("{}/{}", dir, part_filename(1, 2));
+        let downloaded = rt.block_on(connection.get_part(0, &missing));
+        assert!(downloaded.is_err(), "{:?}", downloaded);
+    }
 }
@@ -651,6 +651,7 @@ mod test {
                 highest_block_hash: Default::default(),
                 tracked_shards: vec![],
                 archival: false,
+                reputation: 0,
             });
             header_sync.syncing_peer.as_mut().unwrap().highest_block_height = highest_height;
         };
@@ -37,25 +37,27 @@ use std::sync::Arc;
 use std::time::Duration as TimeDuration;
 use tokio::sync::{Semaphore, TryAcquireError};
 use tracing::info;
-use unc_async::messaging::CanSendAsync;
+use unc_async::messaging::{CanSend, CanSendAsync};
 use unc_chain::chain::ApplyStatePartsRequest;
 use unc_chain::resharding::ReshardingRequest;
 use unc_chain::types::RuntimeAdapter;
 use unc_chain::unc_chain_primitives;
 use unc_chain::Chain;
-use unc_chain_configs::{ExternalStorageConfig, ExternalStorageLocation, SyncConfig};
+use unc_chain_configs::{
+    ExternalStorageConfig, ExternalStorageLocation, HybridSyncConfig, StateSyncSource, SyncConfig,
+};
 use unc_client_primitives::types::{
     format_shard_sync_phase, DownloadStatus, ShardSyncDownload, ShardSyncStatus,
 };
 use unc_epoch_manager::EpochManagerAdapter;
 use unc_network::types::PeerManagerMessageRequest;
 use unc_network::types::{
-    HighestHeightPeerInfo, NetworkRequests, NetworkResponses, PeerManagerAdapter,
+    HighestHeightPeerInfo, NetworkRequests, NetworkResponses, PeerBehaviour, PeerManagerAdapter,
 };
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::network::PeerId;
 use unc_primitives::shard_layout::ShardUId;
-use unc_primitives::state_part::PartId;
+use unc_primitives::state_part::{unpack_state_part, PartId};
 use unc_primitives::state_sync::{ShardStateSyncResponse, StatePartKey};
 use unc_primitives::static_clock::StaticClock;
 use unc_primitives::types::{AccountId, EpochHeight, EpochId, ShardId, StateRoot};
@@ -69,6 +71,9 @@ pub const MAX_PENDING_PART: u64 = MAX_STATE_PART_REQUEST * 10000;
 /// Time limit per state dump iteration.
 /// A node must check external storage for parts to dump again once time is up.
 pub const STATE_DUMP_ITERATION_TIME_LIMIT_SECS: u64 = 300;
+/// Peers with a reputation below this value are not asked for state parts,
+/// unless no other peers are available.
+pub const MIN_STATE_SYNC_PEER_REPUTATION: i32 = -100;
 
 pub enum StateSyncResult {
     /// State sync still in progress. No action needed by the caller.
@@ -122,6 +127,37 @@ enum StateSyncInner {
         /// Connection to the external storage.
         external: ExternalConnection,
     },
+    /// Requests the state header from peers and every state part from the
+    /// sources ranked in `config`, falling back to the next source if the
+    /// part can't be fetched from the current one.
+    Hybrid {
+        /// Which parts were requested from which peer and when.
+        last_part_id_requested: HashMap<(PeerId, ShardId), PendingRequestStatus>,
+        /// Map from which part we requested to whom.
+        requested_target: lru::LruCache<(u64, CryptoHash), PeerId>,
+        /// Chain ID.
+        chain_id: String,
+        /// This semaphore imposes a restriction on the maximum number of simultaneous downloads
+        semaphore: Arc<tokio::sync::Semaphore>,
+        /// Connection to the external storage.
+        external: ExternalConnection,
+        /// Ranking of the sources.
+        config: HybridSyncConfig,
+    },
+}
+
+impl StateSyncInner {
+    /// Returns the source to request the given part from.
+    fn part_source(&self, download: &DownloadStatus) -> StateSyncSource {
+        match self {
+            StateSyncInner::Peers { .. } => StateSyncSource::Peers,
+            StateSyncInner::PartsFromExternal { .. } => StateSyncSource::ExternalStorage,
+            StateSyncInner::Hybrid { config, .. } => {
+                // `state_requests_count` is the number of previous attempts.
+                config.source_for_attempt(download.state_requests_count as u32)
+            }
+        }
+    }
 }
 
 /// Helper to track state sync.
@@ -161,39 +197,27 @@ impl StateSync {
                 last_part_id_requested: Default::default(),
                 requested_target: lru::LruCache::new(MAX_PENDING_PART as usize),
             },
-            SyncConfig::ExternalStorage(ExternalStorageConfig {
-                location,
-                num_concurrent_requests,
-                num_concurrent_requests_during_catchup,
-            }) => {
-                let external = match location {
-                    ExternalStorageLocation::S3 { bucket, region, .. } => {
-                        let bucket = create_bucket_readonly(&bucket, &region, timeout);
-                        if let Err(err) = bucket {
-                            panic!("Failed to create an S3 bucket: {}", err);
-                        }
-                        ExternalConnection::S3 { bucket: Arc::new(bucket.unwrap()) }
-                    }
-                    ExternalStorageLocation::Filesystem { root_dir } => {
-                        ExternalConnection::Filesystem { root_dir: root_dir.clone() }
-                    }
-                    ExternalStorageLocation::GCS { bucket, .. } => ExternalConnection::GCS {
-                        gcs_client: Arc::new(cloud_storage::Client::default()),
-                        reqwest_client: Arc::new(reqwest::Client::default()),
-                        bucket: bucket.clone(),
-                    },
-                };
-                let num_permits = if catchup {
-                    *num_concurrent_requests_during_catchup
-                } else {
-                    *num_concurrent_requests
-                } as usize;
+            SyncConfig::ExternalStorage(external_config) => {
+                let (external, num_permits) =
+                    create_external_connection(external_config, timeout, catchup);
                 StateSyncInner::PartsFromExternal {
                     chain_id: chain_id.to_string(),
                     semaphore: Arc::new(tokio::sync::Semaphore::new(num_permits)),
                     external,
                 }
             }
+            SyncConfig::Hybrid(hybrid_config) => {
+                let (external, num_permits) =
+                    create_external_connection(&hybrid_config.external, timeout, catchup);
+                StateSyncInner::Hybrid {
+                    last_part_id_requested: Default::default(),
+                    requested_target: lru::LruCache::new(MAX_PENDING_PART as usize),
+                    chain_id: chain_id.to_string(),
+                    semaphore: Arc::new(tokio::sync::Semaphore::new(num_permits)),
+                    external,
+                    config: hybrid_config.clone(),
+                }
+            }
         };
         let timeout = Duration::from_std(timeout).unwrap();
         let (tx, rx) = channel::<StateSyncGetPartResult>();
@@ -445,7 +469,8 @@ impl StateSync {
         sync_hash: CryptoHash,
     ) {
         match &mut self.inner {
-            StateSyncInner::Peers { last_part_id_requested, requested_target } => {
+            StateSyncInner::Peers { last_part_id_requested, requested_target }
+            | StateSyncInner::Hybrid { last_part_id_requested, requested_target, .. } => {
                 let key = (part_id, sync_hash);
                 // Check that it came from the target that we requested it from.
                 if let Some(target) = requested_target.get(&key) {
@@ -467,15 +492,25 @@ impl StateSync {
     }
 
     /// Avoids peers that already have outstanding requests for parts.
+    /// Prefers peers with a good enough reputation, but falls back to all
+    /// peers if none of them qualifies.
     fn select_peers(
         &mut self,
         highest_height_peers: &[HighestHeightPeerInfo],
         shard_id: ShardId,
     ) -> Result<Vec<PeerId>, unc_chain::Error> {
-        let peers: Vec<PeerId> =
-            highest_height_peers.iter().map(|peer| peer.peer_info.id.clone()).collect();
+        let reputable_peers: Vec<&HighestHeightPeerInfo> = highest_height_peers
+            .iter()
+            .filter(|peer| peer.reputation >= MIN_STATE_SYNC_PEER_REPUTATION)
+            .collect();
+        let peers: Vec<PeerId> = if reputable_peers.is_empty() {
+            highest_height_peers.iter().map(|peer| peer.peer_info.id.clone()).collect()
+        } else {
+            reputable_peers.into_iter().map(|peer| peer.peer_info.id.clone()).collect()
+        };
         let res = match &mut self.inner {
-            StateSyncInner::Peers { last_part_id_requested, .. } => {
+            StateSyncInner::Peers { last_part_id_requested, .. }
+            | StateSyncInner::Hybrid { last_part_id_requested, .. } => {
                 last_part_id_requested.retain(|_, request| !request.expired());
                 peers
                     .into_iter()
@@ -503,7 +538,11 @@ impl StateSync {
     ) -> Result<(), unc_chain::Error> {
         let possible_targets = self.select_peers(highest_height_peers, shard_id)?;
 
-        if possible_targets.is_empty() {
+        // In hybrid mode the parts may still be fetched from external storage.
+        let needs_peers =
+            matches!(shard_sync_download.status, ShardSyncStatus::StateDownloadHeader)
+                || !matches!(self.inner, StateSyncInner::Hybrid { .. });
+        if possible_targets.is_empty() && needs_peers {
             tracing::debug!(target: "sync", "Can't request a state header: No possible targets");
             // In most cases it means that all the targets are currently busy (that we have a pending request with them).
             return Ok(());
@@ -584,69 +623,66 @@ impl StateSync {
         // Parts are ordered such that its index match its part_id.
         match &mut self.inner {
             StateSyncInner::Peers { last_part_id_requested, requested_target } => {
-                // We'll select all the 'highest' peers + validators as candidates (excluding those that gave us timeout in the past).
-                // And for each one of them, we'll ask for up to 16 (MAX_STATE_PART_REQUEST) parts.
-                let possible_targets_sampler =
-                    SamplerLimited::new(possible_targets, MAX_STATE_PART_REQUEST);
-
-                // For every part that needs to be requested it is selected one
-                // peer (target) randomly to request the part from.
-                // IMPORTANT: here we use 'zip' with possible_target_sampler -
-                // which is limited. So at any moment we'll not request more
-                // than possible_targets.len() * MAX_STATE_PART_REQUEST parts.
-                for ((part_id, download), target) in
-                    parts_to_fetch(new_shard_sync_download).zip(possible_targets_sampler)
-                {
-                    sent_request_part(
-                        target.clone(),
-                        part_id,
-                        shard_id,
-                        sync_hash,
-                        last_part_id_requested,
-                        requested_target,
-                        self.timeout,
-                    );
-                    request_part_from_peers(
-                        part_id,
-                        target,
-                        download,
-                        shard_id,
-                        sync_hash,
-                        &self.network_adapter,
-                    );
-                }
+                request_parts_from_peers(
+                    parts_to_fetch(new_shard_sync_download),
+                    possible_targets,
+                    shard_id,
+                    sync_hash,
+                    last_part_id_requested,
+                    requested_target,
+                    self.timeout,
+                    &self.network_adapter,
+                );
             }
             StateSyncInner::PartsFromExternal { chain_id, semaphore, external } => {
-                let sync_block_header = chain.get_block_header(&sync_hash).unwrap();
-                let epoch_id = sync_block_header.epoch_id();
-                let epoch_info = chain.epoch_manager.get_epoch_info(epoch_id).unwrap();
-                let epoch_height = epoch_info.epoch_height();
-
-                let shard_state_header = chain.get_state_header(shard_id, sync_hash).unwrap();
-                let state_root = shard_state_header.chunk_prev_state_root();
-                let state_num_parts = shard_state_header.num_state_parts();
-
-                for (part_id, download) in parts_to_fetch(new_shard_sync_download) {
-                    request_part_from_external_storage(
-                        part_id,
-                        download,
-                        shard_id,
-                        sync_hash,
-                        epoch_id,
-                        epoch_height,
-                        state_num_parts,
-                        &chain_id.clone(),
-                        state_root,
-                        semaphore.clone(),
-                        external.clone(),
-                        runtime_adapter.clone(),
-                        state_parts_arbiter_handle,
-                        self.state_parts_mpsc_tx.clone(),
-                    );
-                    if semaphore.available_permits() == 0 {
-                        break;
-                    }
-                }
+                request_parts_from_external_storage(
+                    parts_to_fetch(new_shard_sync_download),
+                    shard_id,
+                    sync_hash,
+                    chain,
+                    chain_id,
+                    semaphore,
+                    external,
+                    runtime_adapter,
+                    state_parts_arbiter_handle,
+                    &self.state_parts_mpsc_tx,
+                );
+            }
+            StateSyncInner::Hybrid {
+                last_part_id_requested,
+                requested_target,
+                chain_id,
+                semaphore,
+                external,
+                config,
+            } => {
+                let (external_parts, peer_parts) = partition_hybrid_parts(
+                    config,
+                    parts_to_fetch(new_shard_sync_download),
+                    !possible_targets.is_empty(),
+                );
+                request_parts_from_peers(
+                    peer_parts.into_iter(),
+                    possible_targets,
+                    shard_id,
+                    sync_hash,
+                    last_part_id_requested,
+                    requested_target,
+                    self.timeout,
+                    &self.network_adapter,
+                );
+                request_parts_from_external_storage(
+                    external_parts.into_iter(),
+                    shard_id,
+                    sync_hash,
+                    chain,
+                    chain_id,
+                    semaphore,
+                    external,
+                    runtime_adapter,
+                    state_parts_arbiter_handle,
+                    &self.state_parts_mpsc_tx,
+                );
             }
         }
     }
@@ -759,9 +795,23 @@ impl StateSync {
                             &data,
                         ) {
                             Ok(()) => {
+                                metrics::STATE_SYNC_PARTS_BY_SOURCE
+                                    .with_label_values(&[
+                                        &shard_id.to_string(),
+                                        StateSyncSource::Peers.as_str(),
+                                        "done",
+                                    ])
+                                    .inc();
                                 shard_sync_download.downloads[part_id as usize].done = true;
                             }
                             Err(err) => {
+                                metrics::STATE_SYNC_PARTS_BY_SOURCE
+                                    .with_label_values(&[
+                                        &shard_id.to_string(),
+                                        StateSyncSource::Peers.as_str(),
+                                        "failed",
+                                    ])
+                                    .inc();
                                 tracing::error!(target: "sync", %shard_id, %hash, part_id, ?err, "State sync set_state_part error");
                                 shard_sync_download.downloads[part_id as usize].error = true;
                             }
@@ -845,11 +895,32 @@ impl StateSync {
                 let part_timeout = now - prev > self.timeout; // Retry parts that failed.
                 if part_timeout || part_download.error {
                     download_timeout |= part_timeout;
-                    if part_timeout || part_download.last_target.is_some() {
+                    if part_timeout {
+                        if let Some(peer_id) = part_download.last_target.clone() {
+                            metrics::STATE_SYNC_PARTS_BY_SOURCE
+                                .with_label_values(&[
+                                    &shard_id.to_string(),
+                                    StateSyncSource::Peers.as_str(),
+                                    "failed",
+                                ])
+                                .inc();
+                            self.network_adapter.send(PeerManagerMessageRequest::NetworkRequests(
+                                NetworkRequests::ReportPeerBehaviour {
+                                    peer_id,
+                                    behaviour: PeerBehaviour::SlowStatePart,
+                                },
+                            ));
+                        }
+                    }
+                    if part_timeout
+                        || part_download.last_target.is_some()
+                        || self.inner.part_source(part_download) == StateSyncSource::Peers
+                    {
                         // Don't immediately retry failed requests from external
                         // storage. Most often error is a state part not
                         // available. That error doesn't get fixed by retrying,
-                        // but rather by waiting.
+                        // but rather by waiting. In hybrid mode the part is
+                        // retried right away if it falls back to the peers.
                         metrics::STATE_SYNC_RETRY_PART
                             .with_label_values(&[&shard_id.to_string()])
                             .inc();
@@ -1019,6 +1090,42 @@ impl StateSync {
     }
 }
 
+/// Creates a connection to the configured external storage and returns it
+/// along with the number of concurrent requests allowed.
+fn create_external_connection(
+    config: &ExternalStorageConfig,
+    timeout: TimeDuration,
+    catchup: bool,
+) -> (ExternalConnection, usize) {
+    let external = match &config.location {
+        ExternalStorageLocation::S3 { bucket, region, .. } => {
+            let bucket = create_bucket_readonly(&bucket, &region, timeout);
+            if let Err(err) = bucket {
+                panic!("Failed to create an S3 bucket: {}", err);
+            }
+            ExternalConnection::S3 { bucket: Arc::new(bucket.unwrap()) }
+        }
+        ExternalStorageLocation::Filesystem { root_dir } => {
+            ExternalConnection::Filesystem { root_dir: root_dir.clone() }
+        }
+        ExternalStorageLocation::GCS { bucket, .. } => ExternalConnection::GCS {
+            gcs_client: Arc::new(cloud_storage::Client::default()),
+            reqwest_client: Arc::new(reqwest::Client::default()),
+            bucket: bucket.clone(),
+        },
+        ExternalStorageLocation::Http { base_url, headers, allow_missing_checksums } => {
+            ExternalConnection::new_http(base_url, headers, *allow_missing_checksums, timeout)
+                .unwrap_or_else(|err| panic!("Failed to create an HTTP client: {}", err))
+        }
+    };
+    let num_permits = if catchup {
+        config.num_concurrent_requests_during_catchup
+    } else {
+        config.num_concurrent_requests
+    } as usize;
+    (external, num_permits)
+}
+
 /// Returns parts that still need to be fetched.
 fn parts_to_fetch(
     new_shard_sync_download: &mut ShardSyncDownload,
@@ -1031,6 +1138,116 @@ fn parts_to_fetch(
         .map(|(part_id, download)| (part_id as u64, download))
 }
 
+/// Splits the parts between external storage and the peers according to the
+/// ranking of the sources. Returns `(external_parts, peer_parts)`.
+///
+/// Parts are only requested from the peers if there are peers to request them
+/// from. Otherwise the attempts that would go to the peers are skipped, as
+/// nothing would ever count them as failed and the parts would never fall back
+/// to external storage.
+fn partition_hybrid_parts<'a>(
+    config: &HybridSyncConfig,
+    parts: impl Iterator<Item = (u64, &'a mut DownloadStatus)>,
+    has_peers: bool,
+) -> (Vec<(u64, &'a mut DownloadStatus)>, Vec<(u64, &'a mut DownloadStatus)>) {
+    parts
+        .map(|(part_id, download)| {
+            if !has_peers {
+                let attempt = download.state_requests_count as u32;
+                if let Some(attempt) =
+                    config.next_attempt_with_source(attempt, StateSyncSource::ExternalStorage)
+                {
+                    download.state_requests_count = attempt as u64;
+                }
+            }
+            (part_id, download)
+        })
+        .partition(|(_, download)| {
+            config.source_for_attempt(download.state_requests_count as u32)
+                == StateSyncSource::ExternalStorage
+        })
+}
+
+/// Requests the given parts from the peers.
+fn request_parts_from_peers<'a>(
+    parts: impl Iterator<Item = (u64, &'a mut DownloadStatus)>,
+    possible_targets: Vec<PeerId>,
+    shard_id: ShardId,
+    sync_hash: CryptoHash,
+    last_part_id_requested: &mut HashMap<(PeerId, ShardId), PendingRequestStatus>,
+    requested_target: &mut lru::LruCache<(u64, CryptoHash), PeerId>,
+    timeout: Duration,
+    network_adapter: &PeerManagerAdapter,
+) {
+    // We'll select all the 'highest' peers + validators as candidates (excluding those that gave us timeout in the past).
+    // And for each one of them, we'll ask for up to 16 (MAX_STATE_PART_REQUEST) parts.
+    let possible_targets_sampler = SamplerLimited::new(possible_targets, MAX_STATE_PART_REQUEST);
+
+    // For every part that needs to be requested it is selected one
+    // peer (target) randomly to request the part from.
+    // IMPORTANT: here we use 'zip' with possible_target_sampler -
+    // which is limited. So at any moment we'll not request more
+    // than possible_targets.len() * MAX_STATE_PART_REQUEST parts.
+    for ((part_id, download), target) in parts.zip(possible_targets_sampler) {
+        sent_request_part(
+            target.clone(),
+            part_id,
+            shard_id,
+            sync_hash,
+            last_part_id_requested,
+            requested_target,
+            timeout,
+        );
+        request_part_from_peers(part_id, target, download, shard_id, sync_hash, network_adapter);
+    }
+}
+
+/// Requests the given parts from external storage, as long as the number of
+/// concurrent requests allows.
+fn request_parts_from_external_storage<'a>(
+    parts: impl Iterator<Item = (u64, &'a mut DownloadStatus)>,
+    shard_id: ShardId,
+    sync_hash: CryptoHash,
+    chain: &Chain,
+    chain_id: &str,
+    semaphore: &Arc<Semaphore>,
+    external: &ExternalConnection,
+    runtime_adapter: Arc<dyn RuntimeAdapter>,
+    state_parts_arbiter_handle: &ArbiterHandle,
+    state_parts_mpsc_tx: &Sender<StateSyncGetPartResult>,
+) {
+    let sync_block_header = chain.get_block_header(&sync_hash).unwrap();
+    let epoch_id = sync_block_header.epoch_id();
+    let epoch_info = chain.epoch_manager.get_epoch_info(epoch_id).unwrap();
+    let epoch_height = epoch_info.epoch_height();
+
+    let shard_state_header = chain.get_state_header(shard_id, sync_hash).unwrap();
+    let state_root = shard_state_header.chunk_prev_state_root();
+    let state_num_parts = shard_state_header.num_state_parts();
+
+    for (part_id, download) in parts {
+        request_part_from_external_storage(
+            part_id,
+            download,
+            shard_id,
+            sync_hash,
+            epoch_id,
+            epoch_height,
+            state_num_parts,
+            chain_id,
+            state_root,
+            semaphore.clone(),
+            external.clone(),
+            runtime_adapter.clone(),
+            state_parts_arbiter_handle,
+            state_parts_mpsc_tx.clone(),
+        );
+        if semaphore.available_permits() == 0 {
+            break;
+        }
+    }
+}
+
 /// Starts an asynchronous network request to external storage to fetch the given state part.
 fn request_part_from_external_storage(
     part_id: u64,
@@ -1058,12 +1275,20 @@ fn request_part_from_external_storage(
     let location =
         external_storage_location(chain_id, epoch_id, epoch_height, shard_id, part_id, num_parts);
 
+    let epoch_id = epoch_id.clone();
+
     match semaphore.try_acquire_owned() {
         Ok(permit) => {
             if state_parts_arbiter_handle.spawn({
                 async move {
                     let result = external.get_part(shard_id, &location).await;
                     let part_id = PartId{ idx: part_id, total: num_parts };
+                    let result = result.and_then(|data| {
+                        // Parts dumped as a `StatePartContainer` are checked against their header
+                        // and content hash before the more expensive trie validation.
+                        unpack_state_part(data, shard_id, &epoch_id, part_id, &state_root)
+                            .map_err(anyhow::Error::from)
+                    });
                     let part_result = match result {
                         Ok(data) => {
                             info!(target: "sync", ?shard_id, ?part_id, "downloaded state part");
@@ -1097,10 +1322,10 @@ fn request_part_from_external_storage(
             {
                 tracing::error!(target: "sync", %shard_id, part_id, "Unable to spawn download. state_parts_arbiter has died.");
             }
-        },
+        }
         Err(TryAcquireError::NoPermits) => {
             download.run_me.store(true, Ordering::SeqCst);
-        },
+        }
         Err(TryAcquireError::Closed) => {
             download.run_me.store(true, Ordering::SeqCst);
             tracing::warn!(target: "sync", %shard_id, part_id, "Failed to schedule download. Semaphore closed.");
@@ -1180,6 +1405,13 @@ fn process_part_response(
             metrics::STATE_SYNC_EXTERNAL_PARTS_SIZE_DOWNLOADED
                 .with_label_values(&[&shard_id.to_string()])
                 .inc_by(data_len);
+            metrics::STATE_SYNC_PARTS_BY_SOURCE
+                .with_label_values(&[
+                    &shard_id.to_string(),
+                    StateSyncSource::ExternalStorage.as_str(),
+                    "done",
+                ])
+                .inc();
             part_download.done = true;
         }
         // The request failed without reaching the external storage.
@@ -1187,6 +1419,13 @@ fn process_part_response(
             metrics::STATE_SYNC_EXTERNAL_PARTS_FAILED
                 .with_label_values(&[&shard_id.to_string()])
                 .inc();
+            metrics::STATE_SYNC_PARTS_BY_SOURCE
+                .with_label_values(&[
+                    &shard_id.to_string(),
+                    StateSyncSource::ExternalStorage.as_str(),
+                    "failed",
+                ])
+                .inc();
             tracing::debug!(target: "sync", ?err, %shard_id, %sync_hash, part_id, "Failed to get a part from external storage, will retry");
             part_download.error = true;
         }
@@ -1330,6 +1569,7 @@ mod test {
             highest_block_hash: Default::default(),
             tracked_shards: vec![0],
             archival: false,
+            reputation: 0,
         };
 
         run_actix(async {
@@ -1403,4 +1643,167 @@ mod test {
             System::current().stop()
         });
     }
+
+    fn hybrid_state_sync(sources: Vec<StateSyncSource>, attempts_per_source: u32) -> StateSync {
+        let mock_peer_manager = Arc::new(MockPeerManagerAdapter::default());
+        let config = HybridSyncConfig {
+            external: ExternalStorageConfig {
+                location: ExternalStorageLocation::Filesystem {
+                    root_dir: std::env::temp_dir().join("hybrid_state_sync"),
+                },
+                num_concurrent_requests: 1,
+                num_concurrent_requests_during_catchup: 1,
+            },
+            sources,
+            attempts_per_source,
+        };
+        StateSync::new(
+            mock_peer_manager.into(),
+            TimeDuration::from_secs(1),
+            "chain_id",
+            &SyncConfig::Hybrid(config),
+            false,
+        )
+    }
+
+    #[test]
+    // Every part is requested from the sources in order, moving on to the next
+    // source after `attempts_per_source` attempts and starting over after the last one.
+    fn test_hybrid_part_source_fallback() {
+        let state_sync =
+            hybrid_state_sync(vec![StateSyncSource::ExternalStorage, StateSyncSource::Peers], 2);
+        let mut download = DownloadStatus::new(StaticClock::utc());
+        let sources: Vec<_> = (0..6)
+            .map(|attempt| {
+                download.state_requests_count = attempt;
+                state_sync.inner.part_source(&download)
+            })
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                StateSyncSource::ExternalStorage,
+                StateSyncSource::ExternalStorage,
+                StateSyncSource::Peers,
+                StateSyncSource::Peers,
+                StateSyncSource::ExternalStorage,
+                StateSyncSource::ExternalStorage,
+            ]
+        );
+
+        // Without sources all the parts are requested from the peers.
+        let state_sync = hybrid_state_sync(vec![], 1);
+        download.state_requests_count = 0;
+        assert_eq!(state_sync.inner.part_source(&download), StateSyncSource::Peers);
+    }
+
+    #[test]
+    // Without peers, the parts that would be requested from the peers are
+    // requested from external storage instead of waiting for the peers forever.
+    fn test_hybrid_parts_without_peers() {
+        let config = HybridSyncConfig {
+            external: ExternalStorageConfig {
+                location: ExternalStorageLocation::Filesystem {
+                    root_dir: std::env::temp_dir().join("hybrid_state_sync"),
+                },
+                num_concurrent_requests: 1,
+                num_concurrent_requests_during_catchup: 1,
+            },
+            sources: vec![StateSyncSource::ExternalStorage, StateSyncSource::Peers],
+            attempts_per_source: 2,
+        };
+        let now = StaticClock::utc();
+        let mut shard_sync_download = ShardSyncDownload::new_download_state_parts(now, 3);
+        for (part_id, download) in shard_sync_download.downloads.iter_mut().enumerate() {
+            download.state_requests_count = part_id as u64 * 2;
+        }
+
+        let (external_parts, peer_parts) =
+            partition_hybrid_parts(&config, parts_to_fetch(&mut shard_sync_download), true);
+        let external_parts: Vec<_> = external_parts.into_iter().map(|(id, _)| id).collect();
+        let peer_parts: Vec<_> = peer_parts.into_iter().map(|(id, _)| id).collect();
+        assert_eq!(external_parts, vec![0, 2]);
+        assert_eq!(peer_parts, vec![1]);
+
+        let (external_parts, peer_parts) =
+            partition_hybrid_parts(&config, parts_to_fetch(&mut shard_sync_download), false);
+        let external_parts: Vec<_> = external_parts.into_iter().map(|(id, _)| id).collect();
+        assert_eq!(external_parts, vec![0, 1, 2]);
+        assert!(peer_parts.is_empty());
+        let attempts: Vec<_> = shard_sync_download
+            .downloads
+            .iter()
+            .map(|download| download.state_requests_count)
+            .collect();
+        assert_eq!(attempts, vec![0, 4, 4]);
+
+        // Without external storage among the sources the parts keep waiting for the peers.
+        let config = HybridSyncConfig { sources: vec![StateSyncSource::Peers], ..config };
+        let (external_parts, peer_parts) =
+            partition_hybrid_parts(&config, parts_to_fetch(&mut shard_sync_download), false);
+        assert!(external_parts.is_empty());
+        assert_eq!(peer_parts.len(), 3);
+    }
+
+    #[test]
+    // A part that failed to download from external storage is retried right
+    // away only if the next attempt falls back to the peers.
+    fn test_hybrid_retry_failed_external_part() {
+        let shard_id = 11;
+        let mut state_sync =
+            hybrid_state_sync(vec![StateSyncSource::ExternalStorage, StateSyncSource::Peers], 1);
+        let now = StaticClock::utc();
+        let mut shard_sync_download = ShardSyncDownload::new_download_state_parts(now, 2);
+        for (part_id, download) in shard_sync_download.downloads.iter_mut().enumerate() {
+            download.run_me.store(false, Ordering::SeqCst);
+            download.error = true;
+            // The first part was requested once and falls back to the peers,
+            // the second part was requested twice and goes back to external storage.
+            download.state_requests_count = part_id as u64 + 1;
+        }
+
+        let (download_timeout, run_shard_state_download) =
+            state_sync.sync_shards_download_parts_status(shard_id, &mut shard_sync_download, now);
+        assert!(!download_timeout);
+        assert!(run_shard_state_download);
+
+        let fallback = &shard_sync_download.downloads[0];
+        assert!(fallback.run_me.load(Ordering::SeqCst));
+        assert!(!fallback.error);
+        let external = &shard_sync_download.downloads[1];
+        assert!(!external.run_me.load(Ordering::SeqCst));
+        assert!(external.error);
+    }
+
+    #[test]
+    // A part request that timed out is counted as a failure of the peers.
+    fn test_hybrid_peer_part_timeout_counted_as_failure() {
+        let shard_id = 12;
+        let mut state_sync =
+            hybrid_state_sync(vec![StateSyncSource::ExternalStorage, StateSyncSource::Peers], 1);
+        let start = StaticClock::utc();
+        let mut shard_sync_download = ShardSyncDownload::new_download_state_parts(start, 1);
+        let download = &mut shard_sync_download.downloads[0];
+        download.run_me.store(false, Ordering::SeqCst);
+        download.state_requests_count = 2;
+        download.last_target =
+            Some(PeerId::new(SecretKey::from_random(unc_crypto::KeyType::ED25519).public_key()));
+
+        let failed = metrics::STATE_SYNC_PARTS_BY_SOURCE.with_label_values(&[
+            &shard_id.to_string(),
+            StateSyncSource::Peers.as_str(),
+            "failed",
+        ]);
+        let failed_before = failed.get();
+        let (download_timeout, run_shard_state_download) = state_sync
+            .sync_shards_download_parts_status(
+                shard_id,
+                &mut shard_sync_download,
+                start + Duration::seconds(2),
+            );
+        assert!(download_timeout);
+        assert!(run_shard_state_download);
+        assert_eq!(failed.get(), failed_before + 1);
+        assert!(shard_sync_download.downloads[0].run_me.load(Ordering::SeqCst));
+    }
 }
@@ -524,6 +524,7 @@ pub fn setup_mock_all_validators(
                                 connection_established_time: unc_async::time::Instant::now(),
                                 peer_type: PeerType::Outbound,
                                 nonce: 3,
+                                reputation: 0,
                             })
                             .collect();
                         let peers2 = peers
@@ -824,6 +825,8 @@ pub fn setup_mock_all_validators(
                         }
                         NetworkRequests::ForwardTx(_, _)
                         | NetworkRequests::BanPeer { .. }
+                        | NetworkRequests::UnbanPeer { .. }
+                        | NetworkRequests::ReportPeerBehaviour { .. }
                         | NetworkRequests::TxStatus(_, _, _)
                         | NetworkRequests::SnapshotHostInfo { .. }
                         | NetworkRequests::Challenge(_) => {}
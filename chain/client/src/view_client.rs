@@ -56,7 +56,7 @@ use unc_primitives::static_clock::StaticClock;
 use unc_primitives::transaction::SignedTransaction;
 use unc_primitives::types::{
     AccountId, BlockHeight, BlockId, BlockReference, EpochReference, Finality, MaybeBlockId,
-    ShardId, SyncCheckpoint, TransactionOrReceiptId, ValidatorInfoIdentifier,
+    ShardId, StateChangesRequest, SyncCheckpoint, TransactionOrReceiptId, ValidatorInfoIdentifier,
 };
 use unc_primitives::views::validator_power_and_pledge_view::ValidatorPowerAndPledgeView;
 use unc_primitives::views::{
@@ -309,6 +309,30 @@ impl ViewClientActor {
         Ok(windows)
     }
 
+    /// Whether this node has the history of `account_id` at the given height.
+    ///
+    /// A partial archival node only keeps history of the retained accounts in
+    /// cold storage, so other accounts can't be looked up below the hot tail.
+    fn is_retained_at(
+        &self,
+        account_id: &AccountId,
+        height: BlockHeight,
+    ) -> Result<bool, unc_chain::Error> {
+        if self.is_retained(account_id) {
+            return Ok(true);
+        }
+        Ok(height >= self.chain.tail()?)
+    }
+
+    /// Whether the history of `account_id` is kept in cold storage. Always
+    /// true unless this is a partial archival node.
+    fn is_retained(&self, account_id: &AccountId) -> bool {
+        self.config
+            .archive_retained_accounts
+            .as_ref()
+            .map_or(true, |retained_accounts| retained_accounts.contains(account_id))
+    }
+
     fn handle_query(&mut self, msg: Query) -> Result<QueryResponse, QueryError> {
         let header = self.get_block_header_by_reference(&msg.block_reference);
         let header = match header {
@@ -331,7 +355,19 @@ impl ViewClientActor {
             QueryRequest::ViewChipList { account_id, .. } => account_id,
             QueryRequest::CallFunction { account_id, .. } => account_id,
             QueryRequest::ViewCode { account_id, .. } => account_id,
+            QueryRequest::DebugCallFunction { account_id, .. } => account_id,
         };
+
+        if !self
+            .is_retained_at(account_id, header.height())
+            .map_err(|err| QueryError::InternalError { error_message: err.to_string() })?
+        {
+            return Err(QueryError::AccountNotRetained {
+                requested_account_id: account_id.clone(),
+                block_height: header.height(),
+                block_hash: *header.hash(),
+            });
+        }
         let shard_id = self
             .epoch_manager
             .account_id_to_shard_id(account_id, header.epoch_id())
@@ -539,6 +575,13 @@ impl ViewClientActor {
                                 execution_outcome: Some(outcome),
                                 status: TxExecutionStatus::Included,
                             })
+                        } else if !self.is_retained(&signer_account_id) {
+                            // The outcome is saved together with the transaction, so
+                            // it was dropped on the way to cold storage.
+                            Err(TxStatusError::AccountNotRetained {
+                                transaction_hash: tx_hash,
+                                signer_account_id,
+                            })
                         } else {
                             Ok(TxStatusView {
                                 execution_outcome: None,
@@ -709,7 +752,15 @@ impl Handler<WithSpanContext<GetBlock>> for ViewClientActor {
             .epoch_manager
             .get_block_producer(block.header().epoch_id(), block.header().height())
             .into_chain_error()?;
-        Ok(BlockView::from_author_block(block_author, block))
+        let config = self.runtime.get_protocol_config(block.header().epoch_id())?;
+        let mut block_view = BlockView::from_author_block(block_author, block);
+        for chunk in &mut block_view.chunks {
+            if let Some(congestion_info) = &mut chunk.congestion_info {
+                congestion_info
+                    .set_congestion_level(&config.runtime_config.congestion_control_config);
+            }
+        }
+        Ok(block_view)
     }
 }
 
@@ -790,7 +841,12 @@ impl Handler<WithSpanContext<GetChunk>> for ViewClientActor {
             .get_chunk_producer(&epoch_id, chunk_inner.height_created(), chunk_inner.shard_id())
             .into_chain_error()?;
 
-        Ok(ChunkView::from_author_chunk(author, chunk))
+        let config = self.runtime.get_protocol_config(&epoch_id)?;
+        let mut chunk_view = ChunkView::from_author_chunk(author, chunk);
+        if let Some(congestion_info) = &mut chunk_view.header.congestion_info {
+            congestion_info.set_congestion_level(&config.runtime_config.congestion_control_config);
+        }
+        Ok(chunk_view)
     }
 }
 
@@ -920,10 +976,34 @@ impl Handler<WithSpanContext<GetStateChanges>> for ViewClientActor {
         tracing::debug!(target: "client", ?msg);
         let _timer =
             metrics::VIEW_CLIENT_MESSAGE_TIME.with_label_values(&["GetStateChanges"]).start_timer();
+        let state_changes_request: StateChangesRequest = msg.state_changes_request.into();
+        if self.config.archive_retained_accounts.is_some() {
+            let header = self.chain.get_block_header(&msg.block_hash)?;
+            let account_ids = match &state_changes_request {
+                StateChangesRequest::AccountChanges { account_ids }
+                | StateChangesRequest::AllAccessKeyChanges { account_ids }
+                | StateChangesRequest::ContractCodeChanges { account_ids }
+                | StateChangesRequest::DataChanges { account_ids, .. } => {
+                    account_ids.iter().collect::<Vec<_>>()
+                }
+                StateChangesRequest::SingleAccessKeyChanges { keys } => {
+                    keys.iter().map(|key| &key.account_id).collect()
+                }
+            };
+            for account_id in account_ids {
+                if !self.is_retained_at(account_id, header.height())? {
+                    return Err(GetStateChangesError::AccountNotRetained {
+                        requested_account_id: account_id.clone(),
+                        block_height: header.height(),
+                        block_hash: *header.hash(),
+                    });
+                }
+            }
+        }
         Ok(self
             .chain
             .chain_store()
-            .get_state_changes(&msg.block_hash, &msg.state_changes_request.into())?
+            .get_state_changes(&msg.block_hash, &state_changes_request)?
             .into_iter()
             .map(Into::into)
             .collect())
@@ -1120,7 +1200,12 @@ impl Handler<WithSpanContext<GetExecutionOutcome>> for ViewClientActor {
                     .epoch_manager
                     .account_id_to_shard_id(&account_id, &head.epoch_id)
                     .into_chain_error()?;
-                if self.shard_tracker.care_about_shard(
+                if !self.is_retained(&account_id) {
+                    Err(GetExecutionOutcomeError::AccountNotRetained {
+                        transaction_or_receipt_id: id,
+                        requested_account_id: account_id,
+                    })
+                } else if self.shard_tracker.care_about_shard(
                     self.validator_account_id.as_ref(),
                     &head.last_block_hash,
                     target_shard_id,
@@ -1,24 +1,50 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use crate::EpochManagerAdapter;
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::types::{AccountId, ShardId};
 
-/// TrackedConfig::AllShards: track all shards
+/// Tracks either all shards, or only the shards the node has validation duties in.
 #[derive(Clone)]
 pub struct ShardTracker {
     epoch_manager: Arc<dyn EpochManagerAdapter>,
+    /// Whether all shards are tracked. Shared by the clones of the tracker, so that
+    /// `set_tracked_shards` affects every component holding one.
+    track_all_shards: Arc<AtomicBool>,
 }
 
 impl ShardTracker {
+    /// Creates a tracker tracking all shards.
     pub fn new(epoch_manager: Arc<dyn EpochManagerAdapter>) -> Self {
-        ShardTracker { epoch_manager }
+        ShardTracker { epoch_manager, track_all_shards: Arc::new(AtomicBool::new(true)) }
     }
 
     pub fn new_empty(epoch_manager: Arc<dyn EpochManagerAdapter>) -> Self {
         Self::new(epoch_manager)
     }
 
+    /// Creates a tracker configured by `tracked_shards` of the node config, see
+    /// `set_tracked_shards`.
+    pub fn new_with_tracked_shards(
+        epoch_manager: Arc<dyn EpochManagerAdapter>,
+        tracked_shards: &[ShardId],
+    ) -> Self {
+        let tracker = Self::new(epoch_manager);
+        tracker.set_tracked_shards(tracked_shards);
+        tracker
+    }
+
+    /// Applies `tracked_shards` of the node config: any non-empty list tracks all shards,
+    /// while an empty one only tracks the shards the node has validation duties in.
+    pub fn set_tracked_shards(&self, tracked_shards: &[ShardId]) {
+        self.track_all_shards.store(!tracked_shards.is_empty(), Ordering::Relaxed);
+    }
+
+    fn tracks_all_shards(&self) -> bool {
+        self.track_all_shards.load(Ordering::Relaxed)
+    }
+
     /// Whether the client cares about some shard right now.
     /// * If `account_id` is None, `is_me` is not checked and the
     /// result indicates whether the client is tracking the shard
@@ -51,7 +77,7 @@ impl ShardTracker {
                 // We have access to the node config. Use the config to find a definite answer.
             }
         }
-        true
+        self.tracks_all_shards()
     }
 
     /// Whether the client cares about some shard in the next epoch.
@@ -89,7 +115,7 @@ impl ShardTracker {
             }
         }
 
-        true
+        self.tracks_all_shards()
     }
 }
 
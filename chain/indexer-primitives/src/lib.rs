@@ -1,6 +1,9 @@
 pub use unc_primitives::hash::CryptoHash;
 pub use unc_primitives::{self, types, views};
 
+/// Prefix of the logs carrying standardized JSON events.
+pub const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+
 /// Resulting struct represents block with chunks
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct StreamerMessage {
@@ -32,6 +35,90 @@ pub struct IndexerExecutionOutcomeWithOptionalReceipt {
 pub struct IndexerExecutionOutcomeWithReceipt {
     pub execution_outcome: views::ExecutionOutcomeWithIdView,
     pub receipt: views::ReceiptView,
+    /// Events emitted by the receipt execution through `EVENT_JSON:` logs
+    #[serde(default)]
+    pub events: Vec<IndexerEvent>,
+    /// Logs with the `EVENT_JSON:` prefix which couldn't be parsed as events
+    #[serde(default)]
+    pub invalid_events: Vec<IndexerInvalidEvent>,
+}
+
+impl IndexerExecutionOutcomeWithReceipt {
+    /// Bundles the outcome with its receipt and extracts the events from the outcome logs
+    pub fn new(
+        execution_outcome: views::ExecutionOutcomeWithIdView,
+        receipt: views::ReceiptView,
+    ) -> Self {
+        let (events, invalid_events) =
+            IndexerEvent::from_logs(execution_outcome.id, &execution_outcome.outcome.logs);
+        Self { execution_outcome, receipt, events, invalid_events }
+    }
+}
+
+/// Standardized event emitted by a contract as `EVENT_JSON:{"standard":..,"version":..,"event":..,"data":..}`
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IndexerEvent {
+    pub standard: String,
+    pub version: String,
+    pub event: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    /// Receipt whose execution emitted the event
+    pub receipt_id: CryptoHash,
+    /// Index of the log in the execution outcome
+    pub log_index: usize,
+}
+
+/// Log with the `EVENT_JSON:` prefix which isn't a valid event
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct IndexerInvalidEvent {
+    pub receipt_id: CryptoHash,
+    pub log_index: usize,
+    pub log: String,
+    pub error: String,
+}
+
+#[derive(serde::Deserialize)]
+struct EventLog {
+    standard: String,
+    version: String,
+    event: String,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+impl IndexerEvent {
+    /// Parses the events out of the logs of an execution outcome.
+    /// Logs without the `EVENT_JSON:` prefix are not events and are skipped.
+    pub fn from_logs(
+        receipt_id: CryptoHash,
+        logs: &[String],
+    ) -> (Vec<IndexerEvent>, Vec<IndexerInvalidEvent>) {
+        let mut events = vec![];
+        let mut invalid_events = vec![];
+        for (log_index, log) in logs.iter().enumerate() {
+            let Some(json) = log.strip_prefix(EVENT_JSON_PREFIX) else {
+                continue;
+            };
+            match serde_json::from_str::<EventLog>(json) {
+                Ok(EventLog { standard, version, event, data }) => events.push(IndexerEvent {
+                    standard,
+                    version,
+                    event,
+                    data,
+                    receipt_id,
+                    log_index,
+                }),
+                Err(err) => invalid_events.push(IndexerInvalidEvent {
+                    receipt_id,
+                    log_index,
+                    log: log.clone(),
+                    error: err.to_string(),
+                }),
+            }
+        }
+        (events, invalid_events)
+    }
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -41,3 +128,28 @@ pub struct IndexerShard {
     pub receipt_execution_outcomes: Vec<IndexerExecutionOutcomeWithReceipt>,
     pub state_changes: views::StateChangesView,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CryptoHash, IndexerEvent};
+
+    #[test]
+    fn test_events_from_logs() {
+        let logs = vec![
+            "plain log".to_string(),
+            r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[{"owner_id":"alice.unc"}]}"#.to_string(),
+            r#"EVENT_JSON:{"standard":"nep141","event":"ft_transfer"}"#.to_string(),
+            "EVENT_JSON:not json".to_string(),
+        ];
+        let (events, invalid_events) = IndexerEvent::from_logs(CryptoHash::default(), &logs);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].standard, "nep171");
+        assert_eq!(events[0].event, "nft_mint");
+        assert_eq!(events[0].log_index, 1);
+        assert_eq!(events[0].data, Some(serde_json::json!([{ "owner_id": "alice.unc" }])));
+        assert_eq!(
+            invalid_events.iter().map(|event| event.log_index).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+    }
+}
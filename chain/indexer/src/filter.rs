@@ -0,0 +1,246 @@
+use std::str::FromStr;
+
+use unc_indexer_primitives::{IndexerChunkView, IndexerShard};
+use unc_primitives::account::id::ParseAccountError;
+use unc_primitives::types::{AccountId, ShardId};
+use unc_primitives::views;
+
+/// Account matcher used by [`IndexerFilter`].
+///
+/// Parsed from either an exact account id (`app.unc`) or a wildcard suffix
+/// (`*.app.unc`). The wildcard matches every sub-account of the suffix, but
+/// not the suffix account itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountPattern {
+    Exact(AccountId),
+    Suffix(AccountId),
+}
+
+impl AccountPattern {
+    pub fn matches(&self, account_id: &AccountId) -> bool {
+        match self {
+            AccountPattern::Exact(expected) => expected == account_id,
+            AccountPattern::Suffix(suffix) => account_id
+                .as_str()
+                .strip_suffix(suffix.as_str())
+                .map_or(false, |prefix| prefix.len() > 1 && prefix.ends_with('.')),
+        }
+    }
+}
+
+impl FromStr for AccountPattern {
+    type Err = ParseAccountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("*.") {
+            Some(suffix) => Ok(AccountPattern::Suffix(suffix.parse()?)),
+            None => Ok(AccountPattern::Exact(s.parse()?)),
+        }
+    }
+}
+
+/// Kind of an action, used to filter transactions and receipts by what they do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumString, strum::IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum ActionKind {
+    CreateAccount,
+    DeployContract,
+    FunctionCall,
+    Transfer,
+    Pledge,
+    AddKey,
+    DeleteKey,
+    DeleteAccount,
+    Delegate,
+    RegisterRsa2048Keys,
+    CreateRsa2048Challenge,
+    DeployGlobalContract,
+    UseGlobalContract,
+}
+
+impl From<&views::ActionView> for ActionKind {
+    fn from(action: &views::ActionView) -> Self {
+        match action {
+            views::ActionView::CreateAccount => ActionKind::CreateAccount,
+            views::ActionView::DeployContract { .. } => ActionKind::DeployContract,
+            views::ActionView::FunctionCall { .. } => ActionKind::FunctionCall,
+            views::ActionView::Transfer { .. } => ActionKind::Transfer,
+            views::ActionView::Pledge { .. } => ActionKind::Pledge,
+            views::ActionView::AddKey { .. } => ActionKind::AddKey,
+            views::ActionView::DeleteKey { .. } => ActionKind::DeleteKey,
+            views::ActionView::DeleteAccount { .. } => ActionKind::DeleteAccount,
+            views::ActionView::Delegate { .. } => ActionKind::Delegate,
+            views::ActionView::RegisterRsa2048Keys { .. } => ActionKind::RegisterRsa2048Keys,
+            views::ActionView::CreateRsa2048Challenge { .. } => ActionKind::CreateRsa2048Challenge,
+            views::ActionView::DeployGlobalContract { .. } => ActionKind::DeployGlobalContract,
+            views::ActionView::UseGlobalContract { .. } => ActionKind::UseGlobalContract,
+        }
+    }
+}
+
+/// Filter applied by the streamer to every block before the `StreamerMessage` is emitted.
+///
+/// Every criterion left empty matches anything. Non-empty criteria must all match:
+/// * transactions are kept if their signer, receiver and at least one action match;
+/// * receipts (and their execution outcomes) are kept if their receiver, signer
+///   (predecessor for data receipts) and at least one action match. Data receipts
+///   have no actions, so they are dropped whenever `action_kinds` is set;
+/// * state changes are kept if the changed account matches any of the receiver or
+///   signer patterns;
+/// * shards not listed in `shard_ids` are not processed at all.
+///
+/// The block header is always emitted, even if nothing inside it matched.
+#[derive(Debug, Clone, Default)]
+pub struct IndexerFilter {
+    pub receiver_ids: Vec<AccountPattern>,
+    pub signer_ids: Vec<AccountPattern>,
+    pub action_kinds: Vec<ActionKind>,
+    pub shard_ids: Vec<ShardId>,
+}
+
+fn matches_any(patterns: &[AccountPattern], account_id: &AccountId) -> bool {
+    patterns.is_empty() || patterns.iter().any(|pattern| pattern.matches(account_id))
+}
+
+impl IndexerFilter {
+    /// Whether the filter lets everything through.
+    pub fn is_empty(&self) -> bool {
+        self.receiver_ids.is_empty()
+            && self.signer_ids.is_empty()
+            && self.action_kinds.is_empty()
+            && self.shard_ids.is_empty()
+    }
+
+    pub fn matches_shard(&self, shard_id: ShardId) -> bool {
+        self.shard_ids.is_empty() || self.shard_ids.contains(&shard_id)
+    }
+
+    /// Whether receipts executed by `account_id` may match the filter.
+    pub fn matches_receiver(&self, account_id: &AccountId) -> bool {
+        matches_any(&self.receiver_ids, account_id)
+    }
+
+    fn matches_actions(&self, actions: &[views::ActionView]) -> bool {
+        self.action_kinds.is_empty()
+            || actions.iter().any(|action| self.action_kinds.contains(&ActionKind::from(action)))
+    }
+
+    pub fn matches_transaction(&self, transaction: &views::SignedTransactionView) -> bool {
+        matches_any(&self.receiver_ids, &transaction.receiver_id)
+            && matches_any(&self.signer_ids, &transaction.signer_id)
+            && self.matches_actions(&transaction.actions)
+    }
+
+    pub fn matches_receipt(&self, receipt: &views::ReceiptView) -> bool {
+        if !matches_any(&self.receiver_ids, &receipt.receiver_id) {
+            return false;
+        }
+        match &receipt.receipt {
+            views::ReceiptEnumView::Action { signer_id, actions, .. } => {
+                matches_any(&self.signer_ids, signer_id) && self.matches_actions(actions)
+            }
+            views::ReceiptEnumView::Data { .. }
+            | views::ReceiptEnumView::GlobalContractDistribution { .. } => {
+                matches_any(&self.signer_ids, &receipt.predecessor_id)
+                    && self.action_kinds.is_empty()
+            }
+        }
+    }
+
+    pub fn matches_state_change(&self, state_change: &views::StateChangeWithCauseView) -> bool {
+        if self.receiver_ids.is_empty() && self.signer_ids.is_empty() {
+            return true;
+        }
+        let account_id = match &state_change.value {
+            views::StateChangeValueView::AccountUpdate { account_id, .. }
+            | views::StateChangeValueView::AccountDeletion { account_id }
+            | views::StateChangeValueView::AccessKeyUpdate { account_id, .. }
+            | views::StateChangeValueView::AccessKeyDeletion { account_id, .. }
+            | views::StateChangeValueView::DataUpdate { account_id, .. }
+            | views::StateChangeValueView::DataDeletion { account_id, .. }
+            | views::StateChangeValueView::ContractCodeUpdate { account_id, .. }
+            | views::StateChangeValueView::ContractCodeDeletion { account_id }
+            | views::StateChangeValueView::RsaKeyUpdate { account_id, .. }
+            | views::StateChangeValueView::RsaKeyDeletion { account_id, .. } => account_id,
+        };
+        self.receiver_ids.iter().chain(self.signer_ids.iter()).any(|p| p.matches(account_id))
+    }
+
+    /// Drops everything in the shard that doesn't match the filter.
+    pub(crate) fn apply(&self, shard: &mut IndexerShard) {
+        if let Some(IndexerChunkView { transactions, receipts, .. }) = &mut shard.chunk {
+            transactions.retain(|tx| self.matches_transaction(&tx.transaction));
+            receipts.retain(|receipt| self.matches_receipt(receipt));
+        }
+        shard.receipt_execution_outcomes.retain(|outcome| self.matches_receipt(&outcome.receipt));
+        shard.state_changes.retain(|state_change| self.matches_state_change(state_change));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccountPattern, ActionKind, IndexerFilter};
+    use unc_primitives::types::AccountId;
+    use unc_primitives::views;
+
+    fn account(s: &str) -> AccountId {
+        s.parse().unwrap()
+    }
+
+    fn receipt(
+        predecessor: &str,
+        receiver: &str,
+        actions: Vec<views::ActionView>,
+    ) -> views::ReceiptView {
+        views::ReceiptView {
+            predecessor_id: account(predecessor),
+            receiver_id: account(receiver),
+            receipt_id: Default::default(),
+            receipt: views::ReceiptEnumView::Action {
+                signer_id: account(predecessor),
+                signer_public_key: unc_crypto::PublicKey::empty(unc_crypto::KeyType::ED25519),
+                gas_price: 0,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions,
+            },
+        }
+    }
+
+    #[test]
+    fn test_account_pattern() {
+        let exact: AccountPattern = "app.unc".parse().unwrap();
+        assert!(exact.matches(&account("app.unc")));
+        assert!(!exact.matches(&account("a.app.unc")));
+
+        let suffix: AccountPattern = "*.app.unc".parse().unwrap();
+        assert!(suffix.matches(&account("a.app.unc")));
+        assert!(suffix.matches(&account("b.a.app.unc")));
+        assert!(!suffix.matches(&account("app.unc")));
+        assert!(!suffix.matches(&account("myapp.unc")));
+
+        assert!("*.".parse::<AccountPattern>().is_err());
+    }
+
+    #[test]
+    fn test_receipt_filter() {
+        let filter = IndexerFilter {
+            receiver_ids: vec!["*.app.unc".parse().unwrap()],
+            action_kinds: vec![ActionKind::Transfer],
+            ..Default::default()
+        };
+        let transfer = views::ActionView::Transfer { deposit: 1 };
+        assert!(filter.matches_receipt(&receipt("alice.unc", "a.app.unc", vec![transfer.clone()])));
+        assert!(!filter.matches_receipt(&receipt("alice.unc", "bob.unc", vec![transfer])));
+        assert!(!filter.matches_receipt(&receipt(
+            "alice.unc",
+            "a.app.unc",
+            vec![views::ActionView::CreateAccount]
+        )));
+        assert!(filter.matches_receiver(&account("a.app.unc")));
+        assert!(!filter.matches_receiver(&account("bob.unc")));
+        assert!(IndexerFilter::default().is_empty());
+        assert!(IndexerFilter::default().matches_receiver(&account("bob.unc")));
+        assert!(IndexerFilter::default().matches_shard(3));
+    }
+}
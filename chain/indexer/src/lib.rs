@@ -3,17 +3,23 @@
 use anyhow::Context;
 use tokio::sync::mpsc;
 
+pub use filter::{AccountPattern, ActionKind, IndexerFilter};
 pub use framework::{get_default_home, init_configs, UncConfig};
+#[cfg(unix)]
+pub use sink::UnixSocketSink;
+pub use sink::{IndexerSink, JsonLinesFileSink, WebhookSink};
 use unc_chain_configs::GenesisValidationMode;
 pub use unc_primitives;
 use unc_primitives::types::Gas;
 
 pub use unc_indexer_primitives::{
-    IndexerChunkView, IndexerExecutionOutcomeWithOptionalReceipt,
-    IndexerExecutionOutcomeWithReceipt, IndexerShard, IndexerTransactionWithOutcome,
-    StreamerMessage,
+    IndexerChunkView, IndexerEvent, IndexerExecutionOutcomeWithOptionalReceipt,
+    IndexerExecutionOutcomeWithReceipt, IndexerInvalidEvent, IndexerShard,
+    IndexerTransactionWithOutcome, StreamerMessage,
 };
 
+mod filter;
+mod sink;
 mod streamer;
 
 pub const INDEXER: &str = "indexer";
@@ -59,6 +65,9 @@ pub enum SyncModeEnum {
     FromInterruption,
     /// Specific block height to start syncing from
     BlockHeight(u64),
+    /// Streams the blocks in the inclusive range and stops once the `end` height
+    /// has been streamed. Useful for backfills.
+    BlockRange { start: u64, end: u64 },
 }
 
 /// Enum to define whether await for node to be fully synced or stream while syncing (useful for indexing from genesis)
@@ -70,6 +79,33 @@ pub enum AwaitForNodeSyncedEnum {
     StreamWhileSyncing,
 }
 
+/// Order in which the blocks fetched concurrently are sent to the stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackfillOrdering {
+    /// Messages are sent in the block height order
+    Ordered,
+    /// Messages are sent as soon as they are built, the consumer must not rely on
+    /// the block height order
+    Unordered,
+}
+
+/// Controls how the blocks are fetched while the indexer is behind the chain head,
+/// e.g. when re-indexing the history of an archival node
+#[derive(Debug, Clone)]
+pub struct BackfillConfig {
+    /// Maximum number of blocks fetched and assembled at the same time
+    pub concurrency: usize,
+    /// Order of the emitted messages
+    pub ordering: BackfillOrdering,
+}
+
+impl Default for BackfillConfig {
+    /// Fetches one block at a time, which is the same as streaming the latest blocks
+    fn default() -> Self {
+        Self { concurrency: 1, ordering: BackfillOrdering::Ordered }
+    }
+}
+
 /// UNC Indexer configuration to be provided to `Indexer::new(IndexerConfig)`
 #[derive(Debug, Clone)]
 pub struct IndexerConfig {
@@ -81,6 +117,11 @@ pub struct IndexerConfig {
     pub await_for_node_synced: AwaitForNodeSyncedEnum,
     /// Tells whether to validate the genesis file before starting
     pub validate_genesis: bool,
+    /// Which parts of the blocks to include in the streamed messages.
+    /// `IndexerFilter::default()` streams everything.
+    pub filter: IndexerFilter,
+    /// How to fetch the blocks the indexer is lagging behind on
+    pub backfill: BackfillConfig,
 }
 
 /// This is the core component, which handles `framework` and internal `streamer`.
@@ -100,6 +141,14 @@ impl Indexer {
             indexer_config.home_dir.display()
         );
 
+        if let SyncModeEnum::BlockRange { start, end } = indexer_config.sync_mode {
+            anyhow::ensure!(start <= end, "Invalid block range: {} > {}", start, end);
+        }
+        anyhow::ensure!(
+            indexer_config.backfill.concurrency > 0,
+            "Backfill concurrency must be positive"
+        );
+
         let genesis_validation_mode = if indexer_config.validate_genesis {
             GenesisValidationMode::Full
         } else {
@@ -116,11 +165,15 @@ impl Indexer {
 
     /// Boots up `unc_indexer::streamer`, so it monitors the new blocks with chunks, transactions, receipts, and execution outcomes inside. The returned stream handler should be drained and handled on the user side.
     pub fn streamer(&self) -> mpsc::Receiver<StreamerMessage> {
+        self.start_streamer(self.indexer_config.clone())
+    }
+
+    fn start_streamer(&self, indexer_config: IndexerConfig) -> mpsc::Receiver<StreamerMessage> {
         let (sender, receiver) = mpsc::channel(100);
         actix::spawn(streamer::start(
             self.view_client.clone(),
             self.client.clone(),
-            self.indexer_config.clone(),
+            indexer_config,
             self.unc_config.config.store.clone(),
             self.unc_config.config.archive,
             sender,
@@ -128,6 +181,43 @@ impl Indexer {
         receiver
     }
 
+    /// Boots up the streamer and delivers every message to all the `sinks` with at-least-once
+    /// semantics. The last block height acknowledged by every sink is checkpointed in
+    /// `<home_dir>/indexer_checkpoints`, and once any checkpoint exists the streaming resumes
+    /// right after the lowest one instead of the configured `sync_mode`.
+    /// Resolves once the stream ends, e.g. after the last block of `SyncModeEnum::BlockRange`.
+    pub async fn run_sinks(&self, sinks: Vec<Box<dyn IndexerSink>>) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.indexer_config.backfill.ordering == BackfillOrdering::Ordered,
+            "Sinks require the ordered backfill"
+        );
+        sink::validate_sink_names(&sinks)?;
+        let checkpoints =
+            sink::SinkCheckpoints::open(self.indexer_config.home_dir.join("indexer_checkpoints"))?;
+        let mut lowest_checkpoint = None;
+        for sink in &sinks {
+            if let Some(height) = checkpoints.load(sink.name())? {
+                lowest_checkpoint = Some(lowest_checkpoint.map_or(height, |h: u64| h.min(height)));
+            }
+        }
+
+        let mut indexer_config = self.indexer_config.clone();
+        if let Some(height) = lowest_checkpoint {
+            indexer_config.sync_mode = match indexer_config.sync_mode {
+                SyncModeEnum::BlockRange { end, .. } if height >= end => {
+                    tracing::info!(target: INDEXER, "All sinks are past the end of the block range");
+                    return Ok(());
+                }
+                SyncModeEnum::BlockRange { end, .. } => {
+                    SyncModeEnum::BlockRange { start: height + 1, end }
+                }
+                _ => SyncModeEnum::BlockHeight(height + 1),
+            };
+            tracing::info!(target: INDEXER, "Resuming the sinks from block #{}", height + 1);
+        }
+        sink::run(self.start_streamer(indexer_config), sinks, checkpoints).await
+    }
+
     /// Expose unc-node config
     pub fn unc_config(&self) -> &framework::UncConfig {
         &self.unc_config
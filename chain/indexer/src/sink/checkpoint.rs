@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use unc_primitives::types::BlockHeight;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Checkpoint {
+    block_height: BlockHeight,
+}
+
+/// Durable record of the last block height acknowledged by every sink.
+///
+/// Every sink has its own `<name>.json` file in the checkpoint directory.
+/// The files are replaced atomically, so a crash never leaves a torn checkpoint.
+pub(crate) struct SinkCheckpoints {
+    dir: PathBuf,
+}
+
+impl SinkCheckpoints {
+    pub(crate) fn open(dir: PathBuf) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, sink_name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", sink_name))
+    }
+
+    pub(crate) fn load(&self, sink_name: &str) -> anyhow::Result<Option<BlockHeight>> {
+        let path = self.path(sink_name);
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()))
+            }
+        };
+        let checkpoint: Checkpoint = serde_json::from_slice(&data)
+            .with_context(|| format!("failed to parse {}", path.display()))?;
+        Ok(Some(checkpoint.block_height))
+    }
+
+    pub(crate) fn save(&self, sink_name: &str, block_height: BlockHeight) -> anyhow::Result<()> {
+        let path = self.path(sink_name);
+        let tmp_path = path.with_extension("json.tmp");
+        let data = serde_json::to_vec(&Checkpoint { block_height })?;
+        {
+            let file = std::fs::File::create(&tmp_path)
+                .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+            std::io::Write::write_all(&mut &file, &data)?;
+            file.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &path)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SinkCheckpoints;
+
+    #[test]
+    fn test_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = SinkCheckpoints::open(dir.path().join("checkpoints")).unwrap();
+        assert_eq!(checkpoints.load("files").unwrap(), None);
+        checkpoints.save("files", 10).unwrap();
+        checkpoints.save("files", 11).unwrap();
+        checkpoints.save("webhook", 5).unwrap();
+
+        let checkpoints = SinkCheckpoints::open(dir.path().join("checkpoints")).unwrap();
+        assert_eq!(checkpoints.load("files").unwrap(), Some(11));
+        assert_eq!(checkpoints.load("webhook").unwrap(), Some(5));
+    }
+}
@@ -0,0 +1,113 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use unc_indexer_primitives::StreamerMessage;
+
+use super::IndexerSink;
+
+/// Writes the messages as newline-delimited JSON into files in a directory.
+///
+/// A new file named `<name>-<first block height>.jsonl` is started once the
+/// current one reaches `max_file_size` bytes, and on every restart. A message is
+/// acknowledged once it's fsynced.
+pub struct JsonLinesFileSink {
+    name: String,
+    dir: PathBuf,
+    max_file_size: u64,
+    file: Option<(std::fs::File, u64)>,
+}
+
+impl JsonLinesFileSink {
+    pub fn new(name: &str, dir: PathBuf, max_file_size: u64) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create {}", dir.display()))?;
+        Ok(Self { name: name.to_string(), dir, max_file_size, file: None })
+    }
+
+    fn open_file(&self, block_height: u64) -> anyhow::Result<std::fs::File> {
+        let path = self.dir.join(format!("{}-{:012}.jsonl", self.name, block_height));
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))
+    }
+}
+
+#[async_trait::async_trait]
+impl IndexerSink for JsonLinesFileSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&mut self, message: &StreamerMessage) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        if self.file.as_ref().map_or(true, |(_, size)| *size >= self.max_file_size) {
+            let file = self.open_file(message.block.header.height)?;
+            let size = file.metadata()?.len();
+            self.file = Some((file, size));
+        }
+        let (file, size) = self.file.as_mut().unwrap();
+        if let Err(err) = file.write_all(&line).and_then(|()| file.sync_data()) {
+            // The file may end with a partial line now, continue in a new one.
+            self.file = None;
+            return Err(err.into());
+        }
+        *size += line.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::message;
+    use super::{IndexerSink, JsonLinesFileSink};
+
+    fn read_dir(dir: &std::path::Path) -> Vec<(String, usize)> {
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let lines = std::fs::read_to_string(entry.path()).unwrap().lines().count();
+                (entry.file_name().into_string().unwrap(), lines)
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn test_file_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let line_size = serde_json::to_vec(&message(1)).unwrap().len() as u64 + 1;
+
+        // A file is rotated once it holds at least two messages.
+        let mut sink =
+            JsonLinesFileSink::new("files", dir.path().to_path_buf(), line_size + 1).unwrap();
+        for height in 1..=3 {
+            sink.deliver(&message(height)).await.unwrap();
+        }
+        assert_eq!(
+            read_dir(dir.path()),
+            vec![
+                ("files-000000000001.jsonl".to_string(), 2),
+                ("files-000000000003.jsonl".to_string(), 1),
+            ]
+        );
+
+        // A restart starts a new file.
+        let mut sink =
+            JsonLinesFileSink::new("files", dir.path().to_path_buf(), line_size + 1).unwrap();
+        sink.deliver(&message(4)).await.unwrap();
+        assert_eq!(
+            read_dir(dir.path()).last().unwrap(),
+            &("files-000000000004.jsonl".to_string(), 1)
+        );
+
+        let line = std::fs::read_to_string(dir.path().join("files-000000000004.jsonl")).unwrap();
+        let parsed: unc_indexer_primitives::StreamerMessage = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.block.header.height, 4);
+    }
+}
@@ -0,0 +1,22 @@
+use once_cell::sync::Lazy;
+use unc_o11y::metrics::{
+    try_create_int_counter_vec, try_create_int_gauge_vec, IntCounterVec, IntGaugeVec,
+};
+
+pub(crate) static SINK_ACKNOWLEDGED_BLOCK_HEIGHT: Lazy<IntGaugeVec> = Lazy::new(|| {
+    try_create_int_gauge_vec(
+        "unc_indexer_sink_acknowledged_block_height",
+        "Height of the last block acknowledged by the sink",
+        &["sink"],
+    )
+    .unwrap()
+});
+
+pub(crate) static SINK_DELIVERY_FAILURES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "unc_indexer_sink_delivery_failures_total",
+        "Number of failed attempts to deliver a message to the sink",
+        &["sink"],
+    )
+    .unwrap()
+});
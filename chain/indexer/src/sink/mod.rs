@@ -0,0 +1,266 @@
+//! Sinks consuming the `StreamerMessage`s with at-least-once delivery.
+//!
+//! Every sink has a durable checkpoint of the last block height it acknowledged.
+//! A message is redelivered until the sink acknowledges it, and after a restart
+//! the streaming resumes right after the lowest checkpoint, so a message may be
+//! delivered more than once but is never skipped.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use unc_indexer_primitives::StreamerMessage;
+use unc_primitives::types::BlockHeight;
+
+use crate::INDEXER;
+
+pub(crate) use self::checkpoint::SinkCheckpoints;
+pub use self::file::JsonLinesFileSink;
+#[cfg(unix)]
+pub use self::socket::UnixSocketSink;
+pub use self::webhook::WebhookSink;
+
+mod checkpoint;
+mod file;
+mod metrics;
+#[cfg(unix)]
+mod socket;
+mod webhook;
+
+/// Number of messages queued for a single sink before the streaming waits for it.
+const SINK_QUEUE_CAPACITY: usize = 100;
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Destination of the `StreamerMessage`s.
+#[async_trait::async_trait]
+pub trait IndexerSink: Send {
+    /// Unique name of the sink, used as the name of its checkpoint.
+    fn name(&self) -> &str;
+
+    /// Delivers the message. Returning `Ok` acknowledges the message, so it's
+    /// not delivered to this sink again. Errors are retried.
+    async fn deliver(&mut self, message: &StreamerMessage) -> anyhow::Result<()>;
+}
+
+pub(crate) fn validate_sink_names(sinks: &[Box<dyn IndexerSink>]) -> anyhow::Result<()> {
+    let mut names = std::collections::HashSet::new();
+    for sink in sinks {
+        let name = sink.name();
+        anyhow::ensure!(
+            !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "Invalid sink name {:?}, only [A-Za-z0-9_-] are allowed",
+            name
+        );
+        anyhow::ensure!(names.insert(name), "Duplicate sink name {:?}", name);
+    }
+    Ok(())
+}
+
+async fn deliver_with_retry(sink: &mut dyn IndexerSink, message: &StreamerMessage) {
+    let mut delay = MIN_RETRY_DELAY;
+    loop {
+        match sink.deliver(message).await {
+            Ok(()) => return,
+            Err(err) => {
+                metrics::SINK_DELIVERY_FAILURES.with_label_values(&[sink.name()]).inc();
+                tracing::warn!(
+                    target: INDEXER,
+                    sink = sink.name(),
+                    block_height = message.block.header.height,
+                    ?err,
+                    "Failed to deliver the message, retrying in {:?}",
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+    }
+}
+
+/// Delivers the messages from its own queue to a single sink, skipping the
+/// ones it has already acknowledged.
+async fn run_sink(
+    mut sink: Box<dyn IndexerSink>,
+    mut messages: mpsc::Receiver<Arc<StreamerMessage>>,
+    checkpoints: Arc<SinkCheckpoints>,
+) -> anyhow::Result<()> {
+    let mut acknowledged = checkpoints.load(sink.name())?;
+    while let Some(message) = messages.recv().await {
+        let block_height = message.block.header.height;
+        if acknowledged.map_or(false, |height| height >= block_height) {
+            continue;
+        }
+        deliver_with_retry(sink.as_mut(), &message).await;
+        checkpoints.save(sink.name(), block_height)?;
+        acknowledged = Some(block_height);
+        metrics::SINK_ACKNOWLEDGED_BLOCK_HEIGHT
+            .with_label_values(&[sink.name()])
+            .set(block_height as i64);
+    }
+    Ok(())
+}
+
+/// Delivers every message to all the sinks which haven't acknowledged it yet.
+///
+/// Every sink is driven by its own task with a queue of up to
+/// `SINK_QUEUE_CAPACITY` messages, so a failing sink doesn't hold back the
+/// others until its queue is full.
+pub(crate) async fn run(
+    mut messages: mpsc::Receiver<StreamerMessage>,
+    sinks: Vec<Box<dyn IndexerSink>>,
+    checkpoints: SinkCheckpoints,
+) -> anyhow::Result<()> {
+    let checkpoints = Arc::new(checkpoints);
+    let mut queues = Vec::with_capacity(sinks.len());
+    let mut tasks = Vec::with_capacity(sinks.len());
+    for sink in sinks {
+        let (sender, receiver) = mpsc::channel(SINK_QUEUE_CAPACITY);
+        queues.push(sender);
+        tasks.push(tokio::spawn(run_sink(sink, receiver, checkpoints.clone())));
+    }
+    while let Some(message) = messages.recv().await {
+        let message = Arc::new(message);
+        for queue in &queues {
+            // The task only stops early on an error, which is returned below.
+            if queue.send(message.clone()).await.is_err() {
+                break;
+            }
+        }
+        if queues.iter().any(|queue| queue.is_closed()) {
+            break;
+        }
+    }
+    drop(queues);
+    for task in tasks {
+        task.await??;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::sync::mpsc;
+    use unc_indexer_primitives::StreamerMessage;
+    use unc_primitives::types::BlockHeight;
+
+    use super::{run, validate_sink_names, IndexerSink, JsonLinesFileSink, SinkCheckpoints};
+
+    pub(super) fn message(block_height: BlockHeight) -> StreamerMessage {
+        let block = unc_primitives::block::Block::genesis(
+            unc_primitives::version::PROTOCOL_VERSION,
+            vec![],
+            unc_primitives::static_clock::StaticClock::utc(),
+            block_height,
+            0,
+            0,
+            Default::default(),
+        );
+        StreamerMessage {
+            block: unc_primitives::views::BlockView::from_author_block(
+                "test".parse().unwrap(),
+                block,
+            ),
+            shards: vec![],
+        }
+    }
+
+    /// Records the delivered block heights, failing the first `failures` deliveries.
+    struct TestSink {
+        name: String,
+        failures: usize,
+        delivered: Arc<Mutex<Vec<BlockHeight>>>,
+    }
+
+    impl TestSink {
+        fn new(
+            name: &str,
+            failures: usize,
+        ) -> (Box<dyn IndexerSink>, Arc<Mutex<Vec<BlockHeight>>>) {
+            let delivered = Arc::new(Mutex::new(vec![]));
+            let sink = TestSink { name: name.to_string(), failures, delivered: delivered.clone() };
+            (Box::new(sink), delivered)
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl IndexerSink for TestSink {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn deliver(&mut self, message: &StreamerMessage) -> anyhow::Result<()> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                anyhow::bail!("failure injected by the test");
+            }
+            self.delivered.lock().unwrap().push(message.block.header.height);
+            Ok(())
+        }
+    }
+
+    async fn send_messages(
+        heights: std::ops::RangeInclusive<BlockHeight>,
+    ) -> mpsc::Receiver<StreamerMessage> {
+        let (sender, receiver) = mpsc::channel(100);
+        for height in heights {
+            sender.send(message(height)).await.unwrap();
+        }
+        receiver
+    }
+
+    #[test]
+    fn test_validate_sink_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = |name: &str| -> Box<dyn IndexerSink> {
+            Box::new(JsonLinesFileSink::new(name, dir.path().to_path_buf(), 1024).unwrap())
+        };
+        assert!(validate_sink_names(&[sink("files"), sink("files-2")]).is_ok());
+        assert!(validate_sink_names(&[sink("files"), sink("files")]).is_err());
+        assert!(validate_sink_names(&[sink("../files")]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_redelivery() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = || SinkCheckpoints::open(dir.path().to_path_buf()).unwrap();
+
+        // Failed deliveries are retried until the sink acknowledges the message.
+        let (sink, delivered) = TestSink::new("flaky", 2);
+        run(send_messages(1..=3).await, vec![sink], checkpoints()).await.unwrap();
+        assert_eq!(*delivered.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(checkpoints().load("flaky").unwrap(), Some(3));
+
+        // Messages acknowledged before a restart aren't delivered again.
+        let (sink, delivered) = TestSink::new("flaky", 0);
+        let (new_sink, new_delivered) = TestSink::new("new", 0);
+        run(send_messages(2..=4).await, vec![sink, new_sink], checkpoints()).await.unwrap();
+        assert_eq!(*delivered.lock().unwrap(), vec![4]);
+        assert_eq!(*new_delivered.lock().unwrap(), vec![2, 3, 4]);
+        assert_eq!(checkpoints().load("flaky").unwrap(), Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_failing_sink_does_not_block_others() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = SinkCheckpoints::open(dir.path().to_path_buf()).unwrap();
+        let (failing, failing_delivered) = TestSink::new("failing", usize::MAX);
+        let (healthy, healthy_delivered) = TestSink::new("healthy", 0);
+        let run =
+            tokio::spawn(run(send_messages(1..=3).await, vec![failing, healthy], checkpoints));
+
+        let wait = async {
+            while healthy_delivered.lock().unwrap().len() < 3 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(10), wait).await.unwrap();
+        assert_eq!(*healthy_delivered.lock().unwrap(), vec![1, 2, 3]);
+        assert!(failing_delivered.lock().unwrap().is_empty());
+        run.abort();
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use tokio::net::UnixStream;
+use unc_indexer_primitives::StreamerMessage;
+
+use super::IndexerSink;
+
+/// Writes the messages as newline-delimited JSON into a local Unix socket.
+///
+/// The connection is (re)established lazily. A message is acknowledged once it's
+/// fully written into the socket.
+pub struct UnixSocketSink {
+    name: String,
+    path: PathBuf,
+    stream: Option<UnixStream>,
+}
+
+impl UnixSocketSink {
+    pub fn new(name: &str, path: PathBuf) -> Self {
+        Self { name: name.to_string(), path, stream: None }
+    }
+}
+
+async fn write_all(stream: &UnixStream, mut buf: &[u8]) -> std::io::Result<()> {
+    while !buf.is_empty() {
+        stream.writable().await?;
+        match stream.try_write(buf) {
+            Ok(n) => buf = &buf[n..],
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl IndexerSink for UnixSocketSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&mut self, message: &StreamerMessage) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        if self.stream.is_none() {
+            let stream = UnixStream::connect(&self.path)
+                .await
+                .with_context(|| format!("failed to connect to {}", self.path.display()))?;
+            self.stream = Some(stream);
+        }
+        if let Err(err) = write_all(self.stream.as_ref().unwrap(), &line).await {
+            self.stream = None;
+            return Err(err.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncBufReadExt;
+
+    use super::super::tests::message;
+    use super::{IndexerSink, UnixSocketSink};
+
+    #[tokio::test]
+    async fn test_socket_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("indexer.sock");
+        let mut sink = UnixSocketSink::new("socket", path.clone());
+
+        // Nobody listens on the socket yet.
+        assert!(sink.deliver(&message(1)).await.is_err());
+
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        sink.deliver(&message(1)).await.unwrap();
+        sink.deliver(&message(2)).await.unwrap();
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut lines = tokio::io::BufReader::new(stream).lines();
+        for height in 1..=2 {
+            let line = lines.next_line().await.unwrap().unwrap();
+            let parsed: unc_indexer_primitives::StreamerMessage =
+                serde_json::from_str(&line).unwrap();
+            assert_eq!(parsed.block.header.height, height);
+        }
+    }
+}
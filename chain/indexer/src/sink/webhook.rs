@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use unc_indexer_primitives::StreamerMessage;
+
+use super::IndexerSink;
+
+/// POSTs every message as a JSON body to the given URL.
+///
+/// A message is acknowledged once the endpoint responds with a 2xx status.
+pub struct WebhookSink {
+    name: String,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(name: &str, url: &str, timeout: Duration) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        Ok(Self { name: name.to_string(), url: url.to_string(), client })
+    }
+}
+
+#[async_trait::async_trait]
+impl IndexerSink for WebhookSink {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn deliver(&mut self, message: &StreamerMessage) -> anyhow::Result<()> {
+        self.client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(message)?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::super::tests::message;
+    use super::{IndexerSink, WebhookSink};
+
+    /// Reads a single HTTP request and returns its body.
+    async fn read_request_body(stream: &mut tokio::net::TcpStream) -> Vec<u8> {
+        let mut buf = vec![];
+        let header_end = loop {
+            let mut chunk = [0u8; 4096];
+            let n = stream.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "connection closed before the end of the headers");
+            buf.extend_from_slice(&chunk[..n]);
+            if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&buf[..header_end]).to_lowercase();
+        let content_length: usize = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        while buf.len() < header_end + content_length {
+            let mut chunk = [0u8; 4096];
+            let n = stream.read(&mut chunk).await.unwrap();
+            assert!(n > 0, "connection closed before the end of the body");
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        buf[header_end..header_end + content_length].to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_webhook_sink() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/messages", listener.local_addr().unwrap());
+        // Rejects the first request and accepts the following ones.
+        let server = tokio::spawn(async move {
+            let mut bodies = vec![];
+            for status in ["500 Internal Server Error", "200 OK"] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                bodies.push(read_request_body(&mut stream).await);
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+            bodies
+        });
+
+        let mut sink = WebhookSink::new("webhook", &url, Duration::from_secs(10)).unwrap();
+        assert!(sink.deliver(&message(7)).await.is_err());
+        sink.deliver(&message(7)).await.unwrap();
+
+        let bodies = server.await.unwrap();
+        assert_eq!(bodies.len(), 2);
+        for body in bodies {
+            let parsed: unc_indexer_primitives::StreamerMessage =
+                serde_json::from_slice(&body).unwrap();
+            assert_eq!(parsed.block.header.height, 7);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::BTreeSet;
+
+use unc_primitives::types::BlockHeight;
+
+/// Tracks which heights of a backfill are done when they complete out of order.
+///
+/// Only the highest height below which every block is done can be persisted
+/// as `last_synced_block_height`, otherwise an interrupted backfill would skip
+/// the blocks that were still in flight.
+pub(crate) struct BackfillProgress {
+    /// Lowest height that is not done yet.
+    next: BlockHeight,
+    /// Heights above `next` that are already done.
+    done: BTreeSet<BlockHeight>,
+}
+
+impl BackfillProgress {
+    pub(crate) fn new(start: BlockHeight) -> Self {
+        Self { next: start, done: BTreeSet::new() }
+    }
+
+    /// Marks the height as done. Returns the new highest height below which
+    /// everything is done, if it moved.
+    pub(crate) fn complete(&mut self, height: BlockHeight) -> Option<BlockHeight> {
+        if height < self.next {
+            return None;
+        }
+        self.done.insert(height);
+        let prev = self.next;
+        while self.done.remove(&self.next) {
+            self.next += 1;
+        }
+        (self.next > prev).then(|| self.next - 1)
+    }
+
+    /// Number of heights done ahead of the persisted one.
+    pub(crate) fn pending(&self) -> usize {
+        self.done.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BackfillProgress;
+
+    #[test]
+    fn test_backfill_progress() {
+        let mut progress = BackfillProgress::new(10);
+        assert_eq!(progress.complete(11), None);
+        assert_eq!(progress.complete(13), None);
+        assert_eq!(progress.pending(), 2);
+        assert_eq!(progress.complete(10), Some(11));
+        assert_eq!(progress.complete(12), Some(13));
+        assert_eq!(progress.pending(), 0);
+        assert_eq!(progress.complete(12), None);
+        assert_eq!(progress.complete(14), Some(14));
+    }
+}
@@ -13,6 +13,7 @@ use unc_primitives::{types, views};
 
 use super::errors::FailedToFetchData;
 use super::INDEXER;
+use crate::IndexerFilter;
 
 pub(crate) async fn fetch_status(
     client: &Addr<unc_client::ClientActor>,
@@ -83,9 +84,12 @@ pub(crate) async fn fetch_state_changes(
 
 /// Fetch all ExecutionOutcomeWithId for current block
 /// Returns a HashMap where the key is shard id IndexerExecutionOutcomeWithOptionalReceipt
+/// Outcomes of shards that don't match the `filter` are skipped, and receipts are only
+/// fetched for outcomes executed by accounts that may match it.
 pub(crate) async fn fetch_outcomes(
     client: &Addr<unc_client::ViewClientActor>,
     block_hash: CryptoHash,
+    filter: &IndexerFilter,
 ) -> Result<
     HashMap<unc_primitives::types::ShardId, Vec<IndexerExecutionOutcomeWithOptionalReceipt>>,
     FailedToFetchData,
@@ -100,8 +104,18 @@ pub(crate) async fn fetch_outcomes(
         Vec<IndexerExecutionOutcomeWithOptionalReceipt>,
     > = HashMap::new();
     for (shard_id, shard_outcomes) in outcomes {
+        if !filter.matches_shard(shard_id) {
+            continue;
+        }
         let mut outcomes_with_receipts: Vec<IndexerExecutionOutcomeWithOptionalReceipt> = vec![];
         for outcome in shard_outcomes {
+            if !filter.matches_receiver(&outcome.outcome.executor_id) {
+                outcomes_with_receipts.push(IndexerExecutionOutcomeWithOptionalReceipt {
+                    execution_outcome: outcome,
+                    receipt: None,
+                });
+                continue;
+            }
             let receipt = match fetch_receipt_by_id(&client, outcome.id).await {
                 Ok(res) => res,
                 Err(e) => {
@@ -147,16 +161,18 @@ async fn fetch_single_chunk(
         .map_err(|err| FailedToFetchData::String(err.to_string()))
 }
 
-/// Fetches all chunks belonging to given block.
+/// Fetches all chunks belonging to given block and shards matching the `filter`.
 /// Includes transactions and receipts in custom struct (to provide more info).
 pub(crate) async fn fetch_block_chunks(
     client: &Addr<unc_client::ViewClientActor>,
     block: &views::BlockView,
+    filter: &IndexerFilter,
 ) -> Result<Vec<views::ChunkView>, FailedToFetchData> {
     let mut futures: futures::stream::FuturesUnordered<_> = block
         .chunks
         .iter()
         .filter(|chunk| chunk.height_included == block.header.height)
+        .filter(|chunk| filter.matches_shard(chunk.shard_id))
         .map(|chunk| fetch_single_chunk(&client, chunk.chunk_hash))
         .collect();
     let mut chunks = Vec::<views::ChunkView>::with_capacity(futures.len());
@@ -43,3 +43,52 @@ pub(crate) static BUILD_STREAMER_MESSAGE_TIME: Lazy<Histogram> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub(crate) static NUM_BLOCKS_BUILT: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "unc_indexer_num_blocks_built_total",
+        "Number of block heights processed by the streamer, including the missing blocks",
+    )
+    .unwrap()
+});
+
+pub(crate) static BLOCKS_IN_FLIGHT: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "unc_indexer_blocks_in_flight",
+        "Number of blocks being fetched and assembled concurrently",
+    )
+    .unwrap()
+});
+
+/// Counts a block in `BLOCKS_IN_FLIGHT` for as long as the guard is alive, so
+/// that the blocks whose fetching is cancelled are uncounted too.
+pub(crate) struct BlockInFlightGuard(());
+
+impl BlockInFlightGuard {
+    pub(crate) fn new() -> Self {
+        BLOCKS_IN_FLIGHT.inc();
+        Self(())
+    }
+}
+
+impl Drop for BlockInFlightGuard {
+    fn drop(&mut self) {
+        BLOCKS_IN_FLIGHT.dec();
+    }
+}
+
+pub(crate) static BLOCKS_DONE_OUT_OF_ORDER: Lazy<IntGauge> = Lazy::new(|| {
+    try_create_int_gauge(
+        "unc_indexer_blocks_done_out_of_order",
+        "Number of blocks already sent but not yet persisted as synced because a lower block is still in flight",
+    )
+    .unwrap()
+});
+
+pub(crate) static NUM_INVALID_EVENTS: Lazy<IntCounter> = Lazy::new(|| {
+    try_create_int_counter(
+        "unc_indexer_num_invalid_events_total",
+        "Number of logs with the EVENT_JSON: prefix which couldn't be parsed as events",
+    )
+    .unwrap()
+});
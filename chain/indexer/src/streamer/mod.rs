@@ -1,3 +1,4 @@
+use self::backfill::BackfillProgress;
 use self::errors::FailedToFetchData;
 use self::fetchers::{
     fetch_block, fetch_block_by_height, fetch_block_chunks, fetch_latest_block, fetch_outcomes,
@@ -6,14 +7,15 @@ use self::fetchers::{
 use self::utils::convert_transactions_sir_into_local_receipts;
 use crate::streamer::fetchers::fetch_protocol_config;
 use crate::INDEXER;
-use crate::{AwaitForNodeSyncedEnum, IndexerConfig};
+use crate::{AwaitForNodeSyncedEnum, BackfillOrdering, IndexerConfig, IndexerFilter};
 use actix::Addr;
 use async_recursion::async_recursion;
+use futures::StreamExt;
 use rocksdb::DB;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 use unc_indexer_primitives::{
     IndexerChunkView, IndexerExecutionOutcomeWithOptionalReceipt,
     IndexerExecutionOutcomeWithReceipt, IndexerShard, IndexerTransactionWithOutcome,
@@ -23,6 +25,7 @@ use unc_parameters::RuntimeConfig;
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::views;
 
+mod backfill;
 mod errors;
 mod fetchers;
 mod metrics;
@@ -62,14 +65,17 @@ fn test_problematic_blocks_hash() {
 
 /// This function supposed to return the entire `StreamerMessage`.
 /// It fetches the block and all related parts (chunks, outcomes, state changes etc.)
-/// and returns everything together in one struct
+/// and returns everything together in one struct.
+/// Only the parts matching the `filter` are kept in the message. Shards that don't
+/// match it aren't fetched at all.
 #[async_recursion]
 async fn build_streamer_message(
     client: &Addr<unc_client::ViewClientActor>,
     block: views::BlockView,
+    filter: &IndexerFilter,
 ) -> Result<StreamerMessage, FailedToFetchData> {
     let _timer = metrics::BUILD_STREAMER_MESSAGE_TIME.start_timer();
-    let chunks = fetch_block_chunks(&client, &block).await?;
+    let chunks = fetch_block_chunks(&client, &block, filter).await?;
 
     let protocol_config_view = fetch_protocol_config(&client, block.header.hash).await?;
     let num_shards = protocol_config_view.num_block_producer_seats_per_shard.len()
@@ -78,13 +84,14 @@ async fn build_streamer_message(
     let runtime_config_store = unc_parameters::RuntimeConfigStore::new(None);
     let runtime_config = runtime_config_store.get_config(protocol_config_view.protocol_version);
 
-    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash).await?;
+    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash, filter).await?;
     let mut state_changes = fetch_state_changes(
         &client,
         block.header.hash,
         unc_primitives::types::EpochId(block.header.epoch_id),
     )
     .await?;
+    state_changes.retain(|shard_id, _| filter.matches_shard(*shard_id));
     let mut indexer_shards = (0..num_shards)
         .map(|shard_id| IndexerShard {
             shard_id,
@@ -125,6 +132,7 @@ async fn build_streamer_message(
             indexer_transactions
                 .iter()
                 .filter(|tx| tx.transaction.signer_id == tx.transaction.receiver_id)
+                .filter(|tx| filter.matches_receiver(&tx.transaction.receiver_id))
                 .collect::<Vec<&IndexerTransactionWithOutcome>>(),
             &block,
         )
@@ -146,6 +154,10 @@ async fn build_streamer_message(
         let mut receipt_execution_outcomes: Vec<IndexerExecutionOutcomeWithReceipt> = vec![];
         for outcome in receipt_outcomes {
             let IndexerExecutionOutcomeWithOptionalReceipt { execution_outcome, receipt } = outcome;
+            if !filter.matches_receiver(&execution_outcome.outcome.executor_id) {
+                // The receipt wasn't fetched as it's dropped by the filter anyway.
+                continue;
+            }
             let receipt = if let Some(receipt) = receipt {
                 receipt
             } else {
@@ -180,7 +192,7 @@ async fn build_streamer_message(
                 }
             };
             receipt_execution_outcomes
-                .push(IndexerExecutionOutcomeWithReceipt { execution_outcome, receipt });
+                .push(IndexerExecutionOutcomeWithReceipt::new(execution_outcome, receipt));
         }
 
         // Blocks #47317863 and #47317864
@@ -223,16 +235,65 @@ async fn build_streamer_message(
     // That might happen before the fix https://github.com/utnet-org/utility/pull/4228
     for (shard_id, outcomes) in shards_outcomes {
         indexer_shards[shard_id as usize].receipt_execution_outcomes.extend(
-            outcomes.into_iter().map(|outcome| IndexerExecutionOutcomeWithReceipt {
-                execution_outcome: outcome.execution_outcome,
-                receipt: outcome.receipt.expect("`receipt` must be present at this moment"),
-            }),
+            outcomes
+                .into_iter()
+                .filter(|outcome| {
+                    filter.matches_receiver(&outcome.execution_outcome.outcome.executor_id)
+                })
+                .map(|outcome| {
+                    IndexerExecutionOutcomeWithReceipt::new(
+                        outcome.execution_outcome,
+                        outcome.receipt.expect("`receipt` must be present at this moment"),
+                    )
+                }),
         )
     }
 
+    for outcome in indexer_shards.iter().flat_map(|shard| &shard.receipt_execution_outcomes) {
+        for invalid_event in &outcome.invalid_events {
+            warn!(
+                target: INDEXER,
+                receipt_id = %invalid_event.receipt_id,
+                log_index = invalid_event.log_index,
+                error = %invalid_event.error,
+                "Invalid EVENT_JSON log"
+            );
+        }
+        metrics::NUM_INVALID_EVENTS.inc_by(outcome.invalid_events.len() as u64);
+    }
+
+    if !filter.is_empty() {
+        indexer_shards.retain(|shard| filter.matches_shard(shard.shard_id));
+        for shard in &mut indexer_shards {
+            filter.apply(shard);
+        }
+    }
+
     Ok(StreamerMessage { block, shards: indexer_shards })
 }
 
+/// Fetches the block of the given height and builds the `StreamerMessage` for it.
+/// Returns `None` if the block or some of its data is missing.
+async fn fetch_streamer_message(
+    client: &Addr<unc_client::ViewClientActor>,
+    block_height: unc_primitives::types::BlockHeight,
+    filter: &IndexerFilter,
+) -> (unc_primitives::types::BlockHeight, Option<StreamerMessage>) {
+    let _in_flight = metrics::BlockInFlightGuard::new();
+    let streamer_message = match fetch_block_by_height(&client, block_height).await {
+        Ok(block) => match build_streamer_message(&client, block, filter).await {
+            Ok(streamer_message) => Some(streamer_message),
+            Err(err) => {
+                debug!(target: INDEXER, "Missing data, skipping block #{}...", block_height);
+                debug!(target: INDEXER, "{:#?}", err);
+                None
+            }
+        },
+        Err(_) => None,
+    };
+    (block_height, streamer_message)
+}
+
 /// Function that tries to find specific local receipt by it's ID and returns it
 /// otherwise returns None
 async fn find_local_receipt_by_id_in_block(
@@ -241,9 +302,11 @@ async fn find_local_receipt_by_id_in_block(
     block: views::BlockView,
     receipt_id: unc_primitives::hash::CryptoHash,
 ) -> Result<Option<views::ReceiptView>, FailedToFetchData> {
-    let chunks = fetch_block_chunks(&client, &block).await?;
+    // The receipt may belong to any shard or account, so nothing is filtered out.
+    let filter = IndexerFilter::default();
+    let chunks = fetch_block_chunks(&client, &block, &filter).await?;
 
-    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash).await?;
+    let mut shards_outcomes = fetch_outcomes(&client, block.header.hash, &filter).await?;
 
     for chunk in chunks {
         let views::ChunkView { header, transactions, .. } = chunk;
@@ -321,6 +384,8 @@ pub(crate) async fn start(
         };
 
         let latest_block_height = block.header.height;
+        let end_syncing_block_height =
+            end_syncing_block_height(&indexer_config.sync_mode, latest_block_height);
         let start_syncing_block_height = if let Some(last_synced_block_height) =
             last_synced_block_height
         {
@@ -335,6 +400,7 @@ pub(crate) async fn start(
                 }
                 crate::SyncModeEnum::LatestSynced => latest_block_height,
                 crate::SyncModeEnum::BlockHeight(height) => height,
+                crate::SyncModeEnum::BlockRange { start, .. } => start,
             }
         };
 
@@ -346,35 +412,91 @@ pub(crate) async fn start(
         );
         metrics::START_BLOCK_HEIGHT.set(start_syncing_block_height as i64);
         metrics::LATEST_BLOCK_HEIGHT.set(latest_block_height as i64);
-        for block_height in start_syncing_block_height..=latest_block_height {
-            metrics::CURRENT_BLOCK_HEIGHT.set(block_height as i64);
-            if let Ok(block) = fetch_block_by_height(&view_client, block_height).await {
-                let response = build_streamer_message(&view_client, block).await;
-
-                match response {
-                    Ok(streamer_message) => {
-                        debug!(target: INDEXER, "{:#?}", &streamer_message);
-                        if blocks_sink.send(streamer_message).await.is_err() {
-                            info!(
-                                target: INDEXER,
-                                "Unable to send StreamerMessage to listener, listener doesn't listen. terminating..."
-                            );
-                            break 'main;
-                        } else {
-                            metrics::NUM_STREAMER_MESSAGES_SENT.inc();
-                        }
-                    }
-                    Err(err) => {
-                        debug!(
-                            target: INDEXER,
-                            "Missing data, skipping block #{}...", block_height
-                        );
-                        debug!(target: INDEXER, "{:#?}", err);
-                    }
+        let backfill = &indexer_config.backfill;
+        let heights = futures::stream::iter(start_syncing_block_height..=end_syncing_block_height)
+            .map(|block_height| {
+                fetch_streamer_message(&view_client, block_height, &indexer_config.filter)
+            });
+        // Blocks are fetched and assembled concurrently, but the synced height is
+        // only persisted once all the blocks below it have been sent.
+        let mut streamer_messages = match backfill.ordering {
+            BackfillOrdering::Ordered => heights.buffered(backfill.concurrency).boxed_local(),
+            BackfillOrdering::Unordered => {
+                heights.buffer_unordered(backfill.concurrency).boxed_local()
+            }
+        };
+        let mut progress = BackfillProgress::new(start_syncing_block_height);
+        while let Some((block_height, streamer_message)) = streamer_messages.next().await {
+            metrics::NUM_BLOCKS_BUILT.inc();
+            if let Some(streamer_message) = streamer_message {
+                debug!(target: INDEXER, "{:#?}", &streamer_message);
+                if blocks_sink.send(streamer_message).await.is_err() {
+                    info!(
+                        target: INDEXER,
+                        "Unable to send StreamerMessage to listener, listener doesn't listen. terminating..."
+                    );
+                    break 'main;
+                } else {
+                    metrics::NUM_STREAMER_MESSAGES_SENT.inc();
                 }
             }
-            db.put(b"last_synced_block_height", &block_height.to_string()).unwrap();
-            last_synced_block_height = Some(block_height);
+            if let Some(synced_block_height) = progress.complete(block_height) {
+                metrics::CURRENT_BLOCK_HEIGHT.set(synced_block_height as i64);
+                db.put(b"last_synced_block_height", &synced_block_height.to_string()).unwrap();
+                last_synced_block_height = Some(synced_block_height);
+            }
+            metrics::BLOCKS_DONE_OUT_OF_ORDER.set(progress.pending() as i64);
+        }
+
+        if is_block_range_done(&indexer_config.sync_mode, last_synced_block_height) {
+            info!(target: INDEXER, "Reached the end of the block range, stopping...");
+            break 'main;
+        }
+    }
+}
+
+/// Returns the last height to stream in this iteration of the streamer loop:
+/// the latest block, but never past the end of `SyncModeEnum::BlockRange`.
+fn end_syncing_block_height(
+    sync_mode: &crate::SyncModeEnum,
+    latest_block_height: unc_primitives::types::BlockHeight,
+) -> unc_primitives::types::BlockHeight {
+    match sync_mode {
+        crate::SyncModeEnum::BlockRange { end, .. } => latest_block_height.min(*end),
+        _ => latest_block_height,
+    }
+}
+
+/// Returns whether the streamer has synced every block of `SyncModeEnum::BlockRange`
+/// and should stop. The other sync modes never stop.
+fn is_block_range_done(
+    sync_mode: &crate::SyncModeEnum,
+    last_synced_block_height: Option<unc_primitives::types::BlockHeight>,
+) -> bool {
+    match sync_mode {
+        crate::SyncModeEnum::BlockRange { end, .. } => {
+            last_synced_block_height.map_or(false, |height| height >= *end)
         }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{end_syncing_block_height, is_block_range_done};
+    use crate::SyncModeEnum;
+
+    #[test]
+    fn test_block_range_end() {
+        let range = SyncModeEnum::BlockRange { start: 5, end: 10 };
+        assert_eq!(end_syncing_block_height(&range, 7), 7);
+        assert_eq!(end_syncing_block_height(&range, 20), 10);
+        assert!(!is_block_range_done(&range, None));
+        assert!(!is_block_range_done(&range, Some(9)));
+        assert!(is_block_range_done(&range, Some(10)));
+
+        let latest = SyncModeEnum::LatestSynced;
+        assert_eq!(end_syncing_block_height(&latest, 20), 20);
+        assert!(!is_block_range_done(&latest, Some(u64::MAX)));
     }
 }
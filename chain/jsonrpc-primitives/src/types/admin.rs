@@ -0,0 +1,59 @@
+use unc_primitives::network::PeerId;
+use unc_primitives::types::BlockHeight;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcAdminSetExpectedShutdownRequest {
+    /// Block height to shut down at, `None` clears the scheduled shutdown.
+    pub block_height: Option<BlockHeight>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcAdminPeerRequest {
+    pub peer_id: PeerId,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcAdminResponse {}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+pub struct RpcAdminDrainTransactionPoolResponse {
+    pub num_transactions: usize,
+}
+
+#[derive(thiserror::Error, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "name", content = "info", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RpcAdminError {
+    #[error("The admin action failed: {error_message}")]
+    ActionFailed { error_message: String },
+    #[error("The node reached its limits. Try again later. More details: {error_message}")]
+    InternalError { error_message: String },
+}
+
+impl From<RpcAdminError> for crate::errors::RpcError {
+    fn from(error: RpcAdminError) -> Self {
+        let error_data = match serde_json::to_value(error) {
+            Ok(value) => value,
+            Err(err) => {
+                return Self::new_internal_error(
+                    None,
+                    format!("Failed to serialize RpcAdminError: {:?}", err),
+                )
+            }
+        };
+        Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
+    }
+}
+
+/// Admin actions which are performed outside of the actors.
+/// We use a trait for this, because jsonrpc does not have access to the
+/// storage and the config files of the node.
+pub trait AdminHandler: Sync + Send {
+    /// Rereads the dynamically updateable configs, same as on SIGHUP.
+    fn reload_config(&self) -> Result<(), RpcAdminError>;
+
+    /// Schedules or clears the graceful shutdown at the given block height.
+    fn set_expected_shutdown(&self, block_height: Option<BlockHeight>);
+
+    /// Runs a full compaction of the hot database. Blocks until it's done.
+    fn compact_database(&self) -> Result<(), RpcAdminError>;
+}
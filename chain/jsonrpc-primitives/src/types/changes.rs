@@ -34,6 +34,14 @@ pub enum RpcStateChangesError {
     },
     #[error("There are no fully synchronized blocks yet")]
     NotSyncedYet,
+    #[error(
+        "History of account {requested_account_id} at block #{block_height} is not retained on this partial archival node"
+    )]
+    AccountNotRetained {
+        requested_account_id: unc_primitives::types::AccountId,
+        block_height: unc_primitives::types::BlockHeight,
+        block_hash: unc_primitives::hash::CryptoHash,
+    },
     #[error("The node reached its limits. Try again later. More details: {error_message}")]
     InternalError { error_message: String },
 }
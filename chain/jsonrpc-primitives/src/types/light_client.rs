@@ -49,6 +49,11 @@ pub enum RpcLightClientProofError {
         transaction_or_receipt_id: unc_primitives::hash::CryptoHash,
         shard_id: unc_primitives::types::ShardId,
     },
+    #[error("{transaction_or_receipt_id} is not known and history of account {requested_account_id} is not retained on this partial archival node")]
+    AccountNotRetained {
+        transaction_or_receipt_id: unc_primitives::hash::CryptoHash,
+        requested_account_id: unc_primitives::types::AccountId,
+    },
     #[error("Internal error: {error_message}")]
     InternalError { error_message: String },
 }
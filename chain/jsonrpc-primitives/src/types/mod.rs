@@ -1,3 +1,4 @@
+pub mod admin;
 pub mod all_miners;
 pub mod blocks;
 pub mod changes;
@@ -20,6 +20,14 @@ pub enum RpcQueryError {
         block_height: unc_primitives::types::BlockHeight,
         block_hash: unc_primitives::hash::CryptoHash,
     },
+    #[error(
+        "History of account {requested_account_id} at block #{block_height} is not retained on this partial archival node"
+    )]
+    AccountNotRetained {
+        requested_account_id: unc_primitives::types::AccountId,
+        block_height: unc_primitives::types::BlockHeight,
+        block_hash: unc_primitives::hash::CryptoHash,
+    },
     #[error("Block either has never been observed on the node or has been garbage collected: {block_reference:?}")]
     UnknownBlock { block_reference: unc_primitives::types::BlockReference },
     #[error("Account ID {requested_account_id} is invalid")]
@@ -84,6 +92,8 @@ pub enum QueryResponseKind {
     ViewAccount(unc_primitives::views::AccountView),
     ViewCode(unc_primitives::views::ContractCodeView),
     ViewState(unc_primitives::views::ViewStateResult),
+    // Goes before `CallResult`, whose fields are a subset of its own.
+    DebugCallResult(unc_primitives::views::DebugCallResult),
     CallResult(unc_primitives::views::CallResult),
     AccessKey(unc_primitives::views::AccessKeyView),
     AccessKeyList(unc_primitives::views::AccessKeyList),
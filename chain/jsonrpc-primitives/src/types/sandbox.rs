@@ -1,5 +1,9 @@
+use unc_primitives::hash::CryptoHash;
 use unc_primitives::state_record::StateRecord;
-use unc_primitives::types::BlockHeightDelta;
+use unc_primitives::types::{
+    AccountId, BlockHeight, BlockHeightDelta, BlockReference, FunctionArgs,
+};
+use unc_primitives::views::{DebugCallResult, DebugOptions};
 
 #[derive(serde::Deserialize, serde::Serialize, Debug)]
 pub struct RpcSandboxPatchStateRequest {
@@ -60,3 +64,26 @@ impl From<RpcSandboxFastForwardError> for crate::errors::RpcError {
         Self::new_internal_or_handler_error(Some(error_data.clone()), error_data)
     }
 }
+
+/// Run a contract method with the debugger on top of the state at `block_reference`.
+///
+/// Failures of the called method are reported in the response along with the trace leading up to
+/// them, only failures to set up the call are returned as errors.
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct RpcSandboxDebugCallRequest {
+    #[serde(flatten)]
+    pub block_reference: BlockReference,
+    pub account_id: AccountId,
+    pub method_name: String,
+    pub args_base64: FunctionArgs,
+    #[serde(default)]
+    pub options: DebugOptions,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct RpcSandboxDebugCallResponse {
+    #[serde(flatten)]
+    pub result: DebugCallResult,
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+}
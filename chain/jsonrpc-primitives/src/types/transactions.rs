@@ -45,6 +45,11 @@ pub enum RpcTransactionError {
     RequestRouted { transaction_hash: unc_primitives::hash::CryptoHash },
     #[error("Transaction {requested_transaction_hash} doesn't exist")]
     UnknownTransaction { requested_transaction_hash: unc_primitives::hash::CryptoHash },
+    #[error("Outcome of transaction {requested_transaction_hash} is not retained on this partial archival node for account {requested_account_id}")]
+    AccountNotRetained {
+        requested_transaction_hash: unc_primitives::hash::CryptoHash,
+        requested_account_id: unc_primitives::types::AccountId,
+    },
     #[error("The node reached its limits. Try again later. More details: {debug_info}")]
     InternalError { debug_info: String },
     #[error("Timeout")]
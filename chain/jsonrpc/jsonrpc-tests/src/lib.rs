@@ -4,7 +4,7 @@ use actix::Addr;
 use futures::{future, future::LocalBoxFuture, FutureExt, TryFutureExt};
 use once_cell::sync::Lazy;
 use serde_json::json;
-use unc_chain_configs::GenesisConfig;
+use unc_chain_configs::{GenesisConfig, MutableConfigValue};
 use unc_client::test_utils::setup_no_network_with_validity_period_and_no_epoch_sync;
 use unc_client::ViewClientActor;
 use unc_jsonrpc::{start_http, RpcConfig};
@@ -46,13 +46,19 @@ pub fn start_all_with_validity_period_and_no_epoch_sync(
         );
 
     let addr = tcp::ListenerAddr::reserve_for_test();
+    let rpc_config = RpcConfig::new(addr);
+    let json_payload_max_size = MutableConfigValue::new(
+        rpc_config.limits_config.json_payload_max_size,
+        "json_payload_max_size",
+    );
     start_http(
-        RpcConfig::new(addr),
+        rpc_config,
         TEST_GENESIS_CONFIG.clone(),
         actor_handles.client_actor,
         actor_handles.view_client_actor.clone(),
         None,
         Arc::new(DummyEntityDebugHandler {}),
+        json_payload_max_size,
     );
     (actor_handles.view_client_actor, addr)
 }
@@ -324,6 +324,7 @@ fn test_query_call_function() {
                     account_id: "test".parse().unwrap(),
                     method_name: "method".to_string(),
                     args: vec![].into(),
+                    profile: false,
                 },
             })
             .await
@@ -546,6 +547,26 @@ fn test_invalid_methods() {
     });
 }
 
+#[test]
+fn test_request_without_json_content_type() {
+    test_with_client!(test_utils::NodeType::NonValidator, client, async move {
+        let json = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "dontcare",
+            "method": "status",
+            "params": serde_json::json!([]),
+        });
+        let response = client
+            .client
+            .post(&client.server_addr)
+            .insert_header(("Content-Type", "text/plain"))
+            .send_body(json.to_string())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), awc::http::StatusCode::BAD_REQUEST);
+    });
+}
+
 #[test]
 fn test_get_chunk_with_object_in_params() {
     test_with_client!(test_utils::NodeType::NonValidator, client, async move {
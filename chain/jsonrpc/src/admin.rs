@@ -0,0 +1,239 @@
+//! Admin API for node operators, served on a separate listener.
+//!
+//! Every request has to carry the `Authorization: Bearer <token>` header.
+//! All the requests, including the rejected ones, are logged with the `admin`
+//! target and counted by the `unc_rpc_admin_requests_total` metric.
+
+use actix::Addr;
+use actix_web::{middleware, web, App, Error as HttpError, HttpRequest, HttpResponse, HttpServer};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{error, info};
+use unc_client::{AdminMessage, AdminResponse, ClientActor};
+use unc_jsonrpc_primitives::errors::RpcError;
+use unc_jsonrpc_primitives::message::{Message, Request};
+use unc_jsonrpc_primitives::types::admin::{
+    AdminHandler, RpcAdminDrainTransactionPoolResponse, RpcAdminError, RpcAdminPeerRequest,
+    RpcAdminResponse, RpcAdminSetExpectedShutdownRequest,
+};
+use unc_network::types::{
+    NetworkRequests, NetworkResponses, PeerManagerMessageRequest, PeerManagerMessageResponse,
+    ReasonForBan,
+};
+use unc_network::PeerManagerActor;
+use unc_o11y::WithSpanContextExt;
+
+use crate::{metrics, process_method_call, AdminRpcConfig, RpcFrom};
+
+struct AdminRpcHandler {
+    client_addr: Addr<ClientActor>,
+    peer_manager_addr: Option<Addr<PeerManagerActor>>,
+    admin_handler: Arc<dyn AdminHandler>,
+    auth_token: String,
+}
+
+/// Checks that the request carries the `Authorization: Bearer <auth_token>` header.
+fn is_authorized(request: &HttpRequest, auth_token: &str) -> bool {
+    let Some(token) = request
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    // Compare in constant time to not leak the token through the response time.
+    token.len() == auth_token.len()
+        && token.bytes().zip(auth_token.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+impl AdminRpcHandler {
+    async fn process_request(&self, request: Request) -> Result<Value, RpcError> {
+        match request.method.as_ref() {
+            "admin_reload_config" => {
+                process_method_call(request, |()| async {
+                    self.admin_handler.reload_config().map(|()| RpcAdminResponse {})
+                })
+                .await
+            }
+            "admin_set_expected_shutdown" => {
+                process_method_call(request, |params: RpcAdminSetExpectedShutdownRequest| async {
+                    self.admin_handler.set_expected_shutdown(params.block_height);
+                    Ok::<_, RpcAdminError>(RpcAdminResponse {})
+                })
+                .await
+            }
+            "admin_make_state_snapshot" => {
+                process_method_call(request, |()| async {
+                    self.client_send(AdminMessage::MakeStateSnapshot)
+                        .await
+                        .map(|_| RpcAdminResponse {})
+                })
+                .await
+            }
+            "admin_compact_database" => {
+                process_method_call(request, |()| async {
+                    let admin_handler = self.admin_handler.clone();
+                    tokio::task::spawn_blocking(move || admin_handler.compact_database())
+                        .await
+                        .map_err(|err| RpcAdminError::InternalError {
+                            error_message: err.to_string(),
+                        })?
+                        .map(|()| RpcAdminResponse {})
+                })
+                .await
+            }
+            "admin_ban_peer" => {
+                process_method_call(request, |params: RpcAdminPeerRequest| async {
+                    self.peer_manager_send(NetworkRequests::BanPeer {
+                        peer_id: params.peer_id,
+                        ban_reason: ReasonForBan::Abusive,
+                    })
+                    .await
+                })
+                .await
+            }
+            "admin_unban_peer" => {
+                process_method_call(request, |params: RpcAdminPeerRequest| async {
+                    self.peer_manager_send(NetworkRequests::UnbanPeer { peer_id: params.peer_id })
+                        .await
+                })
+                .await
+            }
+            "admin_drain_tx_pool" => {
+                process_method_call(request, |()| async {
+                    match self.client_send(AdminMessage::DrainTransactionPool).await? {
+                        AdminResponse::TransactionPoolDrained { num_transactions } => {
+                            Ok(RpcAdminDrainTransactionPoolResponse { num_transactions })
+                        }
+                        response => Err(RpcAdminError::InternalError {
+                            error_message: format!("Unexpected response {:?}", response),
+                        }),
+                    }
+                })
+                .await
+            }
+            _ => Err(RpcError::method_not_found(request.method)),
+        }
+    }
+
+    async fn client_send(&self, msg: AdminMessage) -> Result<AdminResponse, RpcAdminError> {
+        self.client_addr
+            .send(msg.with_span_context())
+            .await
+            .map_err(RpcFrom::rpc_from)?
+            .map_err(RpcFrom::rpc_from)
+    }
+
+    async fn peer_manager_send(
+        &self,
+        request: NetworkRequests,
+    ) -> Result<RpcAdminResponse, RpcAdminError> {
+        let Some(peer_manager_addr) = &self.peer_manager_addr else {
+            return Err(RpcFrom::rpc_from(actix::MailboxError::Closed));
+        };
+        match peer_manager_addr
+            .send(PeerManagerMessageRequest::NetworkRequests(request).with_span_context())
+            .await
+            .map_err(RpcFrom::rpc_from)?
+        {
+            PeerManagerMessageResponse::NetworkResponses(NetworkResponses::UnbanPeerFailed(
+                error_message,
+            )) => Err(RpcAdminError::ActionFailed { error_message }),
+            PeerManagerMessageResponse::NetworkResponses(_) => Ok(RpcAdminResponse {}),
+            response => Err(RpcAdminError::InternalError {
+                error_message: format!("Unexpected response {:?}", response),
+            }),
+        }
+    }
+}
+
+async fn admin_rpc_handler(
+    http_request: HttpRequest,
+    message: web::Json<Message>,
+    handler: web::Data<AdminRpcHandler>,
+) -> Result<HttpResponse, HttpError> {
+    let peer_addr = http_request.peer_addr();
+    if !is_authorized(&http_request, &handler.auth_token) {
+        metrics::ADMIN_REQUEST_COUNT.with_label_values(&["", "unauthorized"]).inc();
+        tracing::warn!(target: "admin", ?peer_addr, "Rejected unauthorized admin request");
+        return Ok(HttpResponse::Unauthorized().finish());
+    }
+    let id = message.id();
+    let Message::Request(request) = message.0 else {
+        return Ok(HttpResponse::Ok().json(&Message::error(RpcError::parse_error(
+            "JSON RPC Request format was expected".to_owned(),
+        ))));
+    };
+    let method = request.method.clone();
+    info!(target: "admin", ?peer_addr, %method, params = %request.params, "Admin request");
+    let response = handler.process_request(request).await;
+    match &response {
+        Ok(_) => {
+            metrics::ADMIN_REQUEST_COUNT.with_label_values(&[&method, "ok"]).inc();
+            info!(target: "admin", ?peer_addr, %method, "Admin request succeeded");
+        }
+        Err(err) => {
+            metrics::ADMIN_REQUEST_COUNT.with_label_values(&[&method, "error"]).inc();
+            tracing::warn!(target: "admin", ?peer_addr, %method, ?err, "Admin request failed");
+        }
+    }
+    Ok(HttpResponse::Ok().json(&Message::response(id, response)))
+}
+
+/// Starts the HTTP server of the admin API.
+///
+/// Returns a vector with the started server, same as `start_http`.
+pub fn start_admin_http(
+    config: AdminRpcConfig,
+    auth_token: String,
+    client_addr: Addr<ClientActor>,
+    peer_manager_addr: Option<Addr<PeerManagerActor>>,
+    admin_handler: Arc<dyn AdminHandler>,
+) -> Vec<(&'static str, actix_web::dev::ServerHandle)> {
+    let addr = config.addr;
+    info!(target: "admin", "Starting admin http server at {}", addr);
+    let mut servers = Vec::new();
+    let listener = HttpServer::new(move || {
+        App::new()
+            .app_data(web::Data::new(AdminRpcHandler {
+                client_addr: client_addr.clone(),
+                peer_manager_addr: peer_manager_addr.clone(),
+                admin_handler: admin_handler.clone(),
+                auth_token: auth_token.clone(),
+            }))
+            .wrap(middleware::Logger::default())
+            .service(web::resource("/").route(web::post().to(admin_rpc_handler)))
+    });
+    match listener.listen(addr.std_listener().unwrap()) {
+        Ok(server) => {
+            let server = server.workers(1).shutdown_timeout(5).disable_signals().run();
+            servers.push(("Admin RPC", server.handle()));
+            tokio::spawn(server);
+        }
+        Err(err) => {
+            error!(target: "admin", "Could not start admin http server at {} due to {:?}", addr, err)
+        }
+    }
+    servers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_authorized;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_is_authorized() {
+        let token = "secret-token";
+        let request =
+            |header: &str| TestRequest::default().insert_header(("Authorization", header));
+        assert!(is_authorized(&request("Bearer secret-token").to_http_request(), token));
+        assert!(!is_authorized(&request("Bearer secret-tokem").to_http_request(), token));
+        assert!(!is_authorized(&request("Bearer secret").to_http_request(), token));
+        assert!(!is_authorized(&request("Bearer secret-token2").to_http_request(), token));
+        assert!(!is_authorized(&request("Basic secret-token").to_http_request(), token));
+        assert!(!is_authorized(&request("secret-token").to_http_request(), token));
+        assert!(!is_authorized(&TestRequest::default().to_http_request(), token));
+    }
+}
@@ -0,0 +1,36 @@
+use serde_json::Value;
+
+use unc_client_primitives::types::AdminError;
+use unc_jsonrpc_primitives::errors::RpcParseError;
+use unc_jsonrpc_primitives::types::admin::{
+    RpcAdminError, RpcAdminPeerRequest, RpcAdminSetExpectedShutdownRequest,
+};
+
+use super::{Params, RpcFrom, RpcRequest};
+
+impl RpcRequest for RpcAdminSetExpectedShutdownRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcRequest for RpcAdminPeerRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
+impl RpcFrom<actix::MailboxError> for RpcAdminError {
+    fn rpc_from(error: actix::MailboxError) -> Self {
+        Self::InternalError { error_message: error.to_string() }
+    }
+}
+
+impl RpcFrom<AdminError> for RpcAdminError {
+    fn rpc_from(error: AdminError) -> Self {
+        match error {
+            AdminError::IOError(error_message) => Self::InternalError { error_message },
+            AdminError::Other(error_message) => Self::ActionFailed { error_message },
+        }
+    }
+}
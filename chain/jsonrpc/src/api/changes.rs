@@ -53,6 +53,11 @@ impl RpcFrom<GetStateChangesError> for RpcStateChangesError {
                 Self::UnknownBlock { error_message }
             }
             GetStateChangesError::NotSyncedYet => Self::NotSyncedYet,
+            GetStateChangesError::AccountNotRetained {
+                requested_account_id,
+                block_height,
+                block_hash,
+            } => Self::AccountNotRetained { requested_account_id, block_height, block_hash },
             GetStateChangesError::Unreachable { ref error_message } => {
                 tracing::warn!(target: "jsonrpc", "Unreachable error occurred: {}", error_message);
                 crate::metrics::RPC_UNREACHABLE_ERROR_COUNT
@@ -53,6 +53,10 @@ impl RpcFrom<GetExecutionOutcomeError> for RpcLightClientProofError {
             GetExecutionOutcomeError::UnavailableShard { transaction_or_receipt_id, shard_id } => {
                 Self::UnavailableShard { transaction_or_receipt_id, shard_id }
             }
+            GetExecutionOutcomeError::AccountNotRetained {
+                transaction_or_receipt_id,
+                requested_account_id,
+            } => Self::AccountNotRetained { transaction_or_receipt_id, requested_account_id },
             GetExecutionOutcomeError::InternalError { error_message } => {
                 Self::InternalError { error_message }
             }
@@ -3,6 +3,7 @@ use serde_json::Value;
 use unc_jsonrpc_primitives::errors::RpcParseError;
 use unc_jsonrpc_primitives::errors::{RpcError, ServerError};
 
+mod admin;
 mod all_miners;
 mod blocks;
 mod changes;
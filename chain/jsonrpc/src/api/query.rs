@@ -78,6 +78,7 @@ fn parse_path_data(path: String, data: String) -> Result<RpcQueryRequest, RpcPar
                 account_id,
                 method_name: method_name.to_string(),
                 args: parse_data()?.into(),
+                profile: false,
             },
             None => return Err(RpcParseError("Method name is missing".to_string())),
         },
@@ -105,6 +106,9 @@ impl RpcFrom<QueryError> for RpcQueryError {
             QueryError::GarbageCollectedBlock { block_height, block_hash } => {
                 Self::GarbageCollectedBlock { block_height, block_hash }
             }
+            QueryError::AccountNotRetained { requested_account_id, block_height, block_hash } => {
+                Self::AccountNotRetained { requested_account_id, block_height, block_hash }
+            }
             QueryError::InvalidAccount { requested_account_id, block_height, block_hash } => {
                 Self::InvalidAccount { requested_account_id, block_height, block_hash }
             }
@@ -173,6 +177,9 @@ impl RpcFrom<unc_primitives::views::QueryResponseKind>
             unc_primitives::views::QueryResponseKind::ChipList(chip_list) => {
                 Self::ChipList(chip_list)
             }
+            unc_primitives::views::QueryResponseKind::DebugCallResult(debug_call_result) => {
+                Self::DebugCallResult(debug_call_result)
+            }
         }
     }
 }
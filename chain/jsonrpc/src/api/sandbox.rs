@@ -2,8 +2,8 @@ use serde_json::Value;
 
 use unc_jsonrpc_primitives::errors::RpcParseError;
 use unc_jsonrpc_primitives::types::sandbox::{
-    RpcSandboxFastForwardError, RpcSandboxFastForwardRequest, RpcSandboxPatchStateError,
-    RpcSandboxPatchStateRequest,
+    RpcSandboxDebugCallRequest, RpcSandboxFastForwardError, RpcSandboxFastForwardRequest,
+    RpcSandboxPatchStateError, RpcSandboxPatchStateRequest,
 };
 
 use super::{Params, RpcFrom, RpcRequest};
@@ -20,6 +20,12 @@ impl RpcRequest for RpcSandboxFastForwardRequest {
     }
 }
 
+impl RpcRequest for RpcSandboxDebugCallRequest {
+    fn parse(value: Value) -> Result<Self, RpcParseError> {
+        Params::parse(value)
+    }
+}
+
 impl RpcFrom<actix::MailboxError> for RpcSandboxPatchStateError {
     fn rpc_from(error: actix::MailboxError) -> Self {
         Self::InternalError { error_message: error.to_string() }
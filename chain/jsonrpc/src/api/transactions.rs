@@ -67,6 +67,12 @@ impl RpcFrom<TxStatusError> for RpcTransactionError {
             TxStatusError::MissingTransaction(requested_transaction_hash) => {
                 Self::UnknownTransaction { requested_transaction_hash }
             }
+            TxStatusError::AccountNotRetained { transaction_hash, signer_account_id } => {
+                Self::AccountNotRetained {
+                    requested_transaction_hash: transaction_hash,
+                    requested_account_id: signer_account_id,
+                }
+            }
             TxStatusError::InternalError(debug_info) => Self::InternalError { debug_info },
             TxStatusError::TimeoutError => Self::TimeoutError,
         }
@@ -5,17 +5,19 @@ use actix_cors::Cors;
 use actix_web::http::header;
 use actix_web::HttpRequest;
 use actix_web::{get, http, middleware, web, App, Error as HttpError, HttpResponse, HttpServer};
+pub use admin::start_admin_http;
 use api::RpcRequest;
 pub use api::{RpcFrom, RpcInto};
 use futures::Future;
 use futures::FutureExt;
+use futures::StreamExt;
 use serde_json::{json, Value};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::time::{sleep, timeout};
 use tracing::{error, info};
-use unc_chain_configs::GenesisConfig;
+use unc_chain_configs::{GenesisConfig, MutableConfigValue};
 use unc_client::{
     ClientActor, DebugStatus, GetBlock, GetBlockProof, GetChunk, GetClientConfig,
     GetExecutionOutcome, GetGasPrice, GetMaintenanceWindows, GetNetworkInfo,
@@ -45,6 +47,7 @@ use unc_primitives::transaction::SignedTransaction;
 use unc_primitives::types::{AccountId, BlockHeight};
 use unc_primitives::views::{QueryRequest, TxExecutionStatus};
 
+mod admin;
 mod api;
 mod metrics;
 
@@ -96,6 +99,18 @@ pub struct RpcConfig {
     // be read from this directory, instead of the contents compiled into the binary. This allows
     // for quick iterative development.
     pub experimental_debug_pages_src_path: Option<String>,
+    /// If provided, the admin API is served on a separate listener.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin: Option<AdminRpcConfig>,
+}
+
+/// Configuration of the authenticated admin API.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct AdminRpcConfig {
+    pub addr: tcp::ListenerAddr,
+    /// File with the token every admin request has to present as
+    /// `Authorization: Bearer <token>`. Relative to the home dir of the node.
+    pub auth_token_file: PathBuf,
 }
 
 impl Default for RpcConfig {
@@ -108,6 +123,7 @@ impl Default for RpcConfig {
             limits_config: Default::default(),
             enable_debug_rpc: false,
             experimental_debug_pages_src_path: None,
+            admin: None,
         }
     }
 }
@@ -228,6 +244,7 @@ struct JsonRpcHandler {
     enable_debug_rpc: bool,
     debug_pages_src_path: Option<PathBuf>,
     entity_debug_handler: Arc<dyn EntityDebugHandler>,
+    json_payload_max_size: MutableConfigValue<usize>,
 }
 
 impl JsonRpcHandler {
@@ -301,6 +318,7 @@ impl JsonRpcHandler {
                     QueryRequest::ViewAccessKeyList { .. } => "query_view_access_key_list",
                     QueryRequest::ViewChipList { .. } => "query_view_chip_list",
                     QueryRequest::CallFunction { .. } => "query_call_function",
+                    QueryRequest::DebugCallFunction { .. } => "query_debug_call_function",
                 };
                 (metrics_name.to_string(), process_query_response(self.query(params).await))
             }
@@ -393,6 +411,10 @@ impl JsonRpcHandler {
             "sandbox_fast_forward" => {
                 process_method_call(request, |params| self.sandbox_fast_forward(params)).await
             }
+            #[cfg(feature = "sandbox")]
+            "sandbox_debug_call" => {
+                process_method_call(request, |params| self.sandbox_debug_call(params)).await
+            }
             "provider" => process_method_call(request, |params| self.get_provider(params)).await,
             "all_miners" => {
                 process_method_call(request, |params| self.get_all_miners(params)).await
@@ -1206,6 +1228,35 @@ impl JsonRpcHandler {
 
         Ok(unc_jsonrpc_primitives::types::sandbox::RpcSandboxFastForwardResponse {})
     }
+
+    async fn sandbox_debug_call(
+        &self,
+        debug_call_request: unc_jsonrpc_primitives::types::sandbox::RpcSandboxDebugCallRequest,
+    ) -> Result<
+        unc_jsonrpc_primitives::types::sandbox::RpcSandboxDebugCallResponse,
+        unc_jsonrpc_primitives::types::query::RpcQueryError,
+    > {
+        let request = QueryRequest::DebugCallFunction {
+            account_id: debug_call_request.account_id,
+            method_name: debug_call_request.method_name,
+            args: debug_call_request.args_base64,
+            options: debug_call_request.options,
+        };
+        let query_response: unc_primitives::views::QueryResponse =
+            self.view_client_send(Query::new(debug_call_request.block_reference, request)).await?;
+        match query_response.kind {
+            unc_primitives::views::QueryResponseKind::DebugCallResult(result) => {
+                Ok(unc_jsonrpc_primitives::types::sandbox::RpcSandboxDebugCallResponse {
+                    result,
+                    block_height: query_response.block_height,
+                    block_hash: query_response.block_hash,
+                })
+            }
+            kind => Err(unc_jsonrpc_primitives::types::query::RpcQueryError::InternalError {
+                error_message: format!("unexpected response to a debug call: {:?}", kind),
+            }),
+        }
+    }
 }
 
 #[cfg(feature = "test_features")]
@@ -1308,15 +1359,39 @@ impl JsonRpcHandler {
     }
 }
 
-fn rpc_handler(
-    message: web::Json<Message>,
+async fn rpc_handler(
+    req: HttpRequest,
+    mut payload: web::Payload,
     handler: web::Data<JsonRpcHandler>,
-) -> impl Future<Output = Result<HttpResponse, HttpError>> {
-    let response = async move {
-        let message = handler.process(message.0).await?;
-        Ok(HttpResponse::Ok().json(&message))
+) -> Result<HttpResponse, HttpError> {
+    // The payload is read manually instead of using `web::Json`, so that the
+    // limit can be changed while the server is running. The content type is
+    // checked the same way as `web::Json` does.
+    let is_json = match actix_web::HttpMessage::mime_type(&req) {
+        Ok(Some(mime)) => {
+            mime.subtype() == "json" || mime.suffix().map_or(false, |suffix| suffix == "json")
+        }
+        _ => false,
     };
-    response.boxed()
+    if !is_json {
+        return Err(actix_web::error::ErrorBadRequest("Content type error"));
+    }
+    let limit = handler.json_payload_max_size.get();
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk?;
+        if body.len() + chunk.len() > limit {
+            return Err(actix_web::error::ErrorPayloadTooLarge(format!(
+                "JSON payload is larger than {} bytes",
+                limit
+            )));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let message: Message =
+        serde_json::from_slice(&body).map_err(actix_web::error::ErrorBadRequest)?;
+    let message = handler.process(message).await?;
+    Ok(HttpResponse::Ok().json(&message))
 }
 
 fn status_handler(
@@ -1518,6 +1593,10 @@ fn tx_execution_status_meets_expectations(
 /// configuration may also start another HTTP server just for providing
 /// Prometheus metrics (i.e. covering the `/metrics` path).
 ///
+/// `json_payload_max_size` limits the size of the JSON RPC requests instead of
+/// `config.limits_config`, so that the limit can be changed while the server
+/// is running.
+///
 /// Returns a vector of servers that have been started.  Each server is returned
 /// as a tuple containing a name of the server (e.g. `"JSON RPC"`) which can be
 /// used in diagnostic messages and a [`actix_web::dev::Server`] object which
@@ -1529,15 +1608,17 @@ pub fn start_http(
     view_client_addr: Addr<ViewClientActor>,
     peer_manager_addr: Option<Addr<PeerManagerActor>>,
     entity_debug_handler: Arc<dyn EntityDebugHandler>,
+    json_payload_max_size: MutableConfigValue<usize>,
 ) -> Vec<(&'static str, actix_web::dev::ServerHandle)> {
     let RpcConfig {
         addr,
         prometheus_addr,
         cors_allowed_origins,
         polling_config,
-        limits_config,
+        limits_config: _,
         enable_debug_rpc,
         experimental_debug_pages_src_path: debug_pages_src_path,
+        admin: _,
     } = config;
     let prometheus_addr = prometheus_addr.filter(|it| it != &addr.to_string());
     let cors_allowed_origins_clone = cors_allowed_origins.clone();
@@ -1555,8 +1636,8 @@ pub fn start_http(
                 enable_debug_rpc,
                 debug_pages_src_path: debug_pages_src_path.clone().map(Into::into),
                 entity_debug_handler: entity_debug_handler.clone(),
+                json_payload_max_size: json_payload_max_size.clone(),
             }))
-            .app_data(web::JsonConfig::default().limit(limits_config.json_payload_max_size))
             .wrap(middleware::Logger::default())
             .service(web::resource("/").route(web::post().to(rpc_handler)))
             .service(
@@ -55,3 +55,11 @@ pub static RPC_UNREACHABLE_ERROR_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static ADMIN_REQUEST_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    unc_o11y::metrics::try_create_int_counter_vec(
+        "unc_rpc_admin_requests_total",
+        "Total count of admin RPC requests received, by method and result",
+        &["method", "result"],
+    )
+    .unwrap()
+});
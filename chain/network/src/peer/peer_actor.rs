@@ -1139,7 +1139,8 @@ impl PeerActor {
                 if let Some(max_peers) = max_peers {
                     num_peers = min(num_peers, max_peers);
                 }
-                let peers = self.network_state.peer_store.healthy_peers(num_peers as usize);
+                let peers =
+                    self.network_state.peer_store.healthy_peers(&self.clock, num_peers as usize);
 
                 let mut direct_peers = self.network_state.get_direct_peers();
                 if let Some(max_direct_peers) = max_direct_peers {
@@ -27,7 +27,7 @@ use anyhow::Context;
 use arc_swap::ArcSwap;
 use parking_lot::Mutex;
 use std::net::SocketAddr;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tracing::Instrument as _;
 use unc_async::messaging::Sender;
@@ -142,6 +142,10 @@ pub(crate) struct NetworkState {
     /// messages sincce last block.
     pub txns_since_last_block: AtomicUsize,
 
+    /// Maximum number of TIER2 peers. Initialized with `config.max_num_peers`,
+    /// but can be changed while the node is running.
+    pub max_num_peers: AtomicU32,
+
     /// Whitelisted nodes, which are allowed to connect even if the connection limit has been
     /// reached.
     whitelist_nodes: Vec<WhitelistNode>,
@@ -203,6 +207,7 @@ impl NetworkState {
                 RECENT_ROUTED_MESSAGES_CACHE_SIZE,
             )),
             txns_since_last_block: AtomicUsize::new(0),
+            max_num_peers: AtomicU32::new(config.max_num_peers),
             whitelist_nodes,
             add_edges_demux: demux::Demux::new(config.routing_table_update_rate_limit),
             update_routes_demux: demux::Demux::new(config.routing_table_update_rate_limit),
@@ -261,7 +266,8 @@ impl NetworkState {
     fn is_inbound_allowed(&self, peer_info: &PeerInfo) -> bool {
         // Check if we have spare inbound connections capacity.
         let tier2 = self.tier2.load();
-        if tier2.ready.len() + tier2.outbound_handshakes.len() < self.config.max_num_peers as usize
+        if tier2.ready.len() + tier2.outbound_handshakes.len()
+            < self.max_num_peers.load(Ordering::Relaxed) as usize
             && !self.config.inbound_disabled
         {
             return true;
@@ -325,7 +331,7 @@ impl NetworkState {
                             let tier2 = this.tier2.load();
                             tracing::debug!(target: "network",
                                 tier2 = tier2.ready.len(), outgoing_peers = tier2.outbound_handshakes.len(),
-                                max_num_peers = this.config.max_num_peers,
+                                max_num_peers = this.max_num_peers.load(Ordering::Relaxed),
                                 "Dropping handshake (network at max capacity)."
                             );
                             return Err(RegisterPeerError::ConnectionLimitExceeded);
@@ -15,7 +15,7 @@ use crate::tcp;
 use crate::types::{
     ConnectedPeerInfo, HighestHeightPeerInfo, KnownProducer, NetworkInfo, NetworkRequests,
     NetworkResponses, PeerInfo, PeerManagerMessageRequest, PeerManagerMessageResponse, PeerType,
-    SetChainInfo, SnapshotHostInfo,
+    SetChainInfo, SnapshotHostInfo, UpdateNetworkConfig,
 };
 use crate::{client, network_protocol};
 use actix::fut::future::wrap_future;
@@ -209,8 +209,9 @@ impl PeerManagerActor {
     ) -> anyhow::Result<actix::Addr<Self>> {
         let config = config.verify().context("config")?;
         let store = store::Store::from(store);
-        let peer_store = peer_store::PeerStore::new(&clock, config.peer_store.clone())
-            .context("PeerStore::new")?;
+        let peer_store =
+            peer_store::PeerStore::new(&clock, config.peer_store.clone(), store.clone())
+                .context("PeerStore::new")?;
         tracing::debug!(target: "network",
                len = peer_store.len(),
                boot_nodes = config.peer_store.boot_nodes.len(),
@@ -394,7 +395,7 @@ impl PeerManagerActor {
                 + tier2.outbound_handshakes.len();
 
         (total_connections < self.state.config.ideal_connections_lo as usize
-            || (total_connections < self.state.config.max_num_peers as usize
+            || (total_connections < self.state.max_num_peers.load(Ordering::Relaxed) as usize
                 && potential_outbound_connections
                     < self.state.config.minimum_outbound_peers as usize))
             && !self.state.config.outbound_disabled
@@ -423,6 +424,10 @@ impl PeerManagerActor {
                 i.highest_block_height.saturating_add(self.state.config.highest_peer_horizon)
                     >= max_height
             })
+            .map(|mut i| {
+                i.reputation = self.state.peer_store.reputation(&self.clock, &i.peer_info.id);
+                i
+            })
             .collect()
     }
 
@@ -533,8 +538,14 @@ impl PeerManagerActor {
         }
 
         // Build valid candidate list to choose the peer to be removed. All peers outside the safe set.
-        let candidates = tier2.ready.values().filter(|p| !safe_set.contains(&p.peer_info.id));
-        if let Some(p) = candidates.choose(&mut rand::thread_rng()) {
+        // The peer with the lowest reputation is removed, ties are broken randomly.
+        let mut candidates: Vec<_> =
+            tier2.ready.values().filter(|p| !safe_set.contains(&p.peer_info.id)).collect();
+        candidates.shuffle(&mut rand::thread_rng());
+        if let Some(p) = candidates
+            .into_iter()
+            .min_by_key(|p| self.state.peer_store.reputation(&self.clock, &p.peer_info.id))
+        {
             tracing::debug!(target: "network", id = ?p.peer_info.id,
                 tier2_len = tier2.ready.len(),
                 ideal_connections_hi = self.state.config.ideal_connections_hi,
@@ -574,6 +585,7 @@ impl PeerManagerActor {
             let prefer_previously_connected_peer =
                 thread_rng().gen_bool(PREFER_PREVIOUSLY_CONNECTED_PEER);
             if let Some(peer_info) = self.state.peer_store.unconnected_peer(
+                &self.clock,
                 |peer_state| {
                     // Ignore connecting to ourself
                     self.my_peer_id == peer_state.peer_info.id
@@ -664,12 +676,13 @@ impl PeerManagerActor {
                 Some(e) => e.nonce(),
                 None => 0,
             },
+            reputation: self.state.peer_store.reputation(&self.clock, &cp.peer_info.id),
         };
         NetworkInfo {
             connected_peers: tier2.ready.values().map(connected_peer).collect(),
             tier1_connections: tier1.ready.values().map(connected_peer).collect(),
             num_connected_peers: tier2.ready.len(),
-            peer_max_count: self.state.config.max_num_peers,
+            peer_max_count: self.state.max_num_peers.load(Ordering::Relaxed),
             highest_height_peers: self.highest_height_peers(),
             sent_bytes_per_sec: tier2
                 .ready
@@ -822,6 +835,19 @@ impl PeerManagerActor {
                 self.state.disconnect_and_ban(&self.clock, &peer_id, ban_reason);
                 NetworkResponses::NoResponse
             }
+            NetworkRequests::UnbanPeer { peer_id } => {
+                match self.state.peer_store.peer_unban(&peer_id) {
+                    Ok(()) => NetworkResponses::NoResponse,
+                    Err(err) => {
+                        tracing::warn!(target: "network", ?err, "Failed to unban a peer");
+                        NetworkResponses::UnbanPeerFailed(err.to_string())
+                    }
+                }
+            }
+            NetworkRequests::ReportPeerBehaviour { peer_id, behaviour } => {
+                self.state.peer_store.report_behaviour(&self.clock, &peer_id, behaviour);
+                NetworkResponses::NoResponse
+            }
             NetworkRequests::AnnounceAccount(announce_account) => {
                 let state = self.state.clone();
                 ctx.spawn(wrap_future(async move {
@@ -1042,6 +1068,22 @@ impl actix::Handler<WithSpanContext<SetChainInfo>> for PeerManagerActor {
     }
 }
 
+impl actix::Handler<WithSpanContext<UpdateNetworkConfig>> for PeerManagerActor {
+    type Result = ();
+    #[perf]
+    fn handle(&mut self, msg: WithSpanContext<UpdateNetworkConfig>, _ctx: &mut Self::Context) {
+        let (_span, msg) = handler_debug_span!(target: "network", msg);
+        let _timer = metrics::PEER_MANAGER_MESSAGES_TIME
+            .with_label_values(&["UpdateNetworkConfig"])
+            .start_timer();
+        // Blacklisted peers which are already connected are not disconnected,
+        // the blacklist only applies to the new connections.
+        self.state.peer_store.set_blacklist(msg.blacklist);
+        self.state.max_num_peers.store(msg.max_num_peers, Ordering::Relaxed);
+        tracing::info!(target: "network", max_num_peers = msg.max_num_peers, "Updated network config");
+    }
+}
+
 impl actix::Handler<WithSpanContext<PeerManagerMessageRequest>> for PeerManagerActor {
     type Result = PeerManagerMessageResponse;
     #[perf]
@@ -1,12 +1,14 @@
 use crate::blacklist;
 use crate::network_protocol::PeerInfo;
-use crate::types::{KnownPeerState, KnownPeerStatus, ReasonForBan};
+use crate::stats::metrics;
+use crate::store;
+use crate::types::{KnownPeerState, KnownPeerStatus, PeerBehaviour, PeerReputation, ReasonForBan};
 use anyhow::bail;
 use im::hashmap::Entry;
 use im::{HashMap, HashSet};
 use lru::LruCache;
 use parking_lot::Mutex;
-use rand::seq::IteratorRandom;
+use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::net::SocketAddr;
 use std::ops::Not;
@@ -26,6 +28,37 @@ mod tests;
 ///
 /// Contents of the PeerStore are not persisted to the database. Upon starting a node,
 /// the PeerStore is initialized from the boot nodes in its config.
+/// The only exception are the reputation scores of the peers, which are saved to
+/// DBCol::PeerReputations by PeerStore::update and loaded back on start.
+
+/// Reputation scores are clamped to [-MAX_REPUTATION, MAX_REPUTATION].
+pub(crate) const MAX_REPUTATION: i32 = 1000;
+/// Reputation penalty applied when a peer gets banned.
+const BAN_REPUTATION_PENALTY: i32 = -200;
+/// Peers with a reputation below this threshold are not advertised as healthy.
+pub(crate) const MIN_HEALTHY_REPUTATION: i32 = -300;
+/// Every REPUTATION_HALF_LIFE the score of a peer gets twice closer to zero,
+/// so that peers can recover from past misbehaviour.
+const REPUTATION_HALF_LIFE: time::Duration = time::Duration::hours(6);
+
+impl PeerReputation {
+    fn new(now: time::Utc) -> Self {
+        Self { score: 0, last_updated: now }
+    }
+
+    /// Returns the score decayed up to `now`.
+    pub fn score_at(&self, now: time::Utc) -> i32 {
+        let elapsed = (now - self.last_updated).max(time::Duration::ZERO);
+        let half_lives = elapsed / REPUTATION_HALF_LIFE;
+        (self.score as f64 * 0.5f64.powf(half_lives)).round() as i32
+    }
+
+    fn add(&mut self, delta: i32, now: time::Utc) {
+        self.score =
+            self.score_at(now).saturating_add(delta).clamp(-MAX_REPUTATION, MAX_REPUTATION);
+        self.last_updated = now;
+    }
+}
 
 /// Level of trust we have about a new (PeerId, Addr) pair.
 #[derive(Eq, PartialEq, Debug, Clone, Copy)]
@@ -84,6 +117,12 @@ struct Inner {
     // It can happens that some peers don't have known address, so
     // they will not be present in this list, otherwise they will be present.
     addr_peers: HashMap<SocketAddr, VerifiedPeer>,
+    // Reputation of the peers. Kept separately from peer_states, so that it
+    // is not lost when a peer is evicted from the cache.
+    reputations: HashMap<PeerId, PeerReputation>,
+    // Whether reputations changed since they were last saved to the store.
+    reputations_dirty: bool,
+    store: store::Store,
 }
 
 impl Inner {
@@ -167,17 +206,52 @@ impl Inner {
         }
     }
 
-    /// Find a random subset of peers based on filter.
-    fn find_peers<F>(&self, filter: F, count: usize) -> Vec<PeerInfo>
+    /// Find up to `count` peers based on filter, with the highest reputation first.
+    /// Peers with equal reputation are ordered randomly.
+    fn find_best_peers<F>(&self, now: time::Utc, filter: F, count: usize) -> Vec<PeerInfo>
     where
         F: FnMut(&&KnownPeerState) -> bool,
     {
-        (self.peer_states.iter().map(|(_, v)| v))
-            .filter(filter)
-            .choose_multiple(&mut thread_rng(), count)
-            .into_iter()
-            .map(|kps| kps.peer_info.clone())
-            .collect()
+        let mut peers: Vec<_> = (self.peer_states.iter().map(|(_, v)| v)).filter(filter).collect();
+        peers.shuffle(&mut thread_rng());
+        // Stable sort keeps the random order among peers with the same reputation.
+        peers.sort_by_key(|p| std::cmp::Reverse(self.reputation(&p.peer_info.id, now)));
+        peers.into_iter().take(count).map(|kps| kps.peer_info.clone()).collect()
+    }
+
+    fn reputation(&self, peer_id: &PeerId, now: time::Utc) -> i32 {
+        self.reputations.get(peer_id).map_or(0, |r| r.score_at(now))
+    }
+
+    fn add_reputation(&mut self, peer_id: &PeerId, delta: i32, now: time::Utc) {
+        self.reputations
+            .entry(peer_id.clone())
+            .or_insert_with(|| PeerReputation::new(now))
+            .add(delta, now);
+        self.reputations_dirty = true;
+    }
+
+    /// Drops the reputations which decayed to zero, keeps at most
+    /// config.peer_states_cache_size of the most significant ones
+    /// and saves them to the store.
+    fn save_reputations(&mut self, now: time::Utc) {
+        if !self.reputations_dirty {
+            return;
+        }
+        let mut reputations: Vec<_> = self
+            .reputations
+            .iter()
+            .filter(|(_, r)| r.score_at(now) != 0)
+            .map(|(peer_id, r)| (peer_id.clone(), *r))
+            .collect();
+        reputations.sort_by_key(|(_, r)| std::cmp::Reverse(r.score_at(now).abs()));
+        reputations.truncate(self.config.peer_states_cache_size as usize);
+        if let Err(err) = self.store.set_peer_reputations(&reputations) {
+            tracing::error!(target: "network", ?err, "Failed to save peer reputations");
+            return;
+        }
+        self.reputations = reputations.into_iter().collect();
+        self.reputations_dirty = false;
     }
 
     /// Create new pair between peer_info.id and peer_addr removing
@@ -275,19 +349,21 @@ impl Inner {
     /// * it unbans a peer if config.ban_window has passed
     /// * it updates KnownPeerStatus.last_seen of the connected peers
     /// * it removes peers which were not seen for config.peer_expiration_duration
+    /// * it saves the peer reputations to the store
     /// This function should be called periodically.
     pub fn update(&mut self, clock: &time::Clock) {
         let now = clock.now_utc();
         self.unban(now);
         self.update_last_seen(now);
         self.remove_expired(now);
+        self.save_reputations(now);
     }
 }
 
 pub(crate) struct PeerStore(Mutex<Inner>);
 
 impl PeerStore {
-    pub fn new(clock: &time::Clock, config: Config) -> anyhow::Result<Self> {
+    pub fn new(clock: &time::Clock, config: Config, store: store::Store) -> anyhow::Result<Self> {
         let boot_nodes: HashSet<_> = config.boot_nodes.iter().map(|p| p.id.clone()).collect();
         // A mapping from `PeerId` to `KnownPeerState`.
         let mut peerid_2_state = LruCache::new(config.peer_states_cache_size as usize);
@@ -334,11 +410,23 @@ impl PeerStore {
             }
         }
 
-        let inner =
-            Inner { config, boot_nodes, peer_states: peerid_2_state, addr_peers: addr_2_peer };
+        let reputations = store.get_peer_reputations().into_iter().collect();
+        let inner = Inner {
+            config,
+            boot_nodes,
+            peer_states: peerid_2_state,
+            addr_peers: addr_2_peer,
+            reputations,
+            reputations_dirty: false,
+            store,
+        };
         Ok(PeerStore(Mutex::new(inner)))
     }
 
+    pub fn set_blacklist(&self, blacklist: blacklist::Blacklist) {
+        self.0.lock().config.blacklist = blacklist;
+    }
+
     pub fn is_blacklisted(&self, addr: &SocketAddr) -> bool {
         self.0.lock().config.blacklist.contains(*addr)
     }
@@ -416,8 +504,9 @@ impl PeerStore {
     ) -> anyhow::Result<()> {
         tracing::warn!(target: "network", "Banning peer {} for {:?}", peer_id, ban_reason);
         let mut inner = self.0.lock();
+        let now = clock.now_utc();
+        inner.add_reputation(peer_id, BAN_REPUTATION_PENALTY, now);
         if let Some(peer_state) = inner.peer_states.get_mut(peer_id) {
-            let now = clock.now_utc();
             peer_state.last_seen = now;
             peer_state.status = KnownPeerStatus::Banned(ban_reason, now);
         } else {
@@ -426,16 +515,48 @@ impl PeerStore {
         Ok(())
     }
 
+    /// Lifts the ban of the peer. The reputation penalty of the ban is kept.
+    pub fn peer_unban(&self, peer_id: &PeerId) -> anyhow::Result<()> {
+        tracing::info!(target: "network", ?peer_id, "Unbanning peer");
+        let mut inner = self.0.lock();
+        match inner.peer_states.peek(peer_id) {
+            Some(peer_state) if peer_state.status.is_banned() => inner.peer_unban(peer_id),
+            Some(_) => bail!("Peer {} is not banned", peer_id),
+            None => bail!("Peer {} is missing in the peer store", peer_id),
+        }
+    }
+
+    /// Updates the reputation of the peer based on its observed behaviour.
+    pub fn report_behaviour(
+        &self,
+        clock: &time::Clock,
+        peer_id: &PeerId,
+        behaviour: PeerBehaviour,
+    ) {
+        tracing::debug!(target: "network", ?peer_id, ?behaviour, "Peer behaviour reported");
+        metrics::PEER_BEHAVIOUR_REPORTS.with_label_values(&[behaviour.into()]).inc();
+        self.0.lock().add_reputation(peer_id, behaviour.score_delta(), clock.now_utc());
+    }
+
+    /// Returns the current reputation score of the peer.
+    pub fn reputation(&self, clock: &time::Clock, peer_id: &PeerId) -> i32 {
+        self.0.lock().reputation(peer_id, clock.now_utc())
+    }
+
     /// Return unconnected or peers with unknown status that we can try to connect to.
     /// Peers with unknown addresses are filtered out.
+    /// Among the matching peers the one with the highest reputation is returned.
     pub fn unconnected_peer(
         &self,
+        clock: &time::Clock,
         ignore_fn: impl Fn(&KnownPeerState) -> bool,
         prefer_previously_connected_peer: bool,
     ) -> Option<PeerInfo> {
         let inner = self.0.lock();
+        let now = clock.now_utc();
         if prefer_previously_connected_peer {
-            let preferred_peer = inner.find_peers(
+            let preferred_peer = inner.find_best_peers(
+                now,
                 |p| {
                     (p.status == KnownPeerStatus::NotConnected)
                         && !ignore_fn(p)
@@ -453,7 +574,8 @@ impl PeerStore {
             };
             // otherwise, pick a peer from the wider pool below.
         }
-        inner.find_peers(
+        inner.find_best_peers(
+            now,
             |p| {
                 (p.status == KnownPeerStatus::NotConnected || p.status == KnownPeerStatus::Unknown)
                     && !ignore_fn(p)
@@ -467,11 +589,19 @@ impl PeerStore {
         .cloned()
     }
 
-    /// Return healthy known peers up to given amount.
-    pub fn healthy_peers(&self, max_count: usize) -> Vec<PeerInfo> {
-        self.0
-            .lock()
-            .find_peers(|p| matches!(p.status, KnownPeerStatus::Banned(_, _)).not(), max_count)
+    /// Return healthy known peers up to given amount, with the highest reputation first.
+    /// Peers with a reputation below MIN_HEALTHY_REPUTATION are not considered healthy.
+    pub fn healthy_peers(&self, clock: &time::Clock, max_count: usize) -> Vec<PeerInfo> {
+        let inner = self.0.lock();
+        let now = clock.now_utc();
+        inner.find_best_peers(
+            now,
+            |p| {
+                matches!(p.status, KnownPeerStatus::Banned(_, _)).not()
+                    && inner.reputation(&p.peer_info.id, now) >= MIN_HEALTHY_REPUTATION
+            },
+            max_count,
+        )
     }
 
     /// Adds peers we’ve learned about from other peers.
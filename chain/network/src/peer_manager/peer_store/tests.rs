@@ -25,6 +25,10 @@ fn gen_peer_info(port: u16) -> PeerInfo {
     }
 }
 
+fn make_store() -> store::Store {
+    store::Store::from(unc_store::db::TestDB::new())
+}
+
 fn make_config(
     boot_nodes: &[PeerInfo],
     blacklist: blacklist::Blacklist,
@@ -47,12 +51,36 @@ fn ban_store() {
     let peer_info_to_ban = gen_peer_info(1);
     let boot_nodes = vec![peer_info_a, peer_info_to_ban.clone()];
 
-    let peer_store =
-        PeerStore::new(&clock.clock(), make_config(&boot_nodes, Blacklist::default(), false))
-            .unwrap();
-    assert_eq!(peer_store.healthy_peers(3).len(), 2);
+    let peer_store = PeerStore::new(
+        &clock.clock(),
+        make_config(&boot_nodes, Blacklist::default(), false),
+        make_store(),
+    )
+    .unwrap();
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 2);
+    peer_store.peer_ban(&clock.clock(), &peer_info_to_ban.id, ReasonForBan::Abusive).unwrap();
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 1);
+}
+
+#[test]
+fn unban_store() {
+    let clock = time::FakeClock::default();
+    let peer_info_a = gen_peer_info(0);
+    let peer_info_to_ban = gen_peer_info(1);
+    let boot_nodes = vec![peer_info_a.clone(), peer_info_to_ban.clone()];
+
+    let peer_store = PeerStore::new(
+        &clock.clock(),
+        make_config(&boot_nodes, Blacklist::default(), false),
+        make_store(),
+    )
+    .unwrap();
+    assert!(peer_store.peer_unban(&peer_info_to_ban.id).is_err());
     peer_store.peer_ban(&clock.clock(), &peer_info_to_ban.id, ReasonForBan::Abusive).unwrap();
-    assert_eq!(peer_store.healthy_peers(3).len(), 1);
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 1);
+    peer_store.peer_unban(&peer_info_to_ban.id).unwrap();
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 3).len(), 2);
+    assert!(peer_store.peer_unban(&get_peer_id("unknown".to_string())).is_err());
 }
 
 #[test]
@@ -62,12 +90,15 @@ fn test_unconnected_peer() {
     let peer_info_to_ban = gen_peer_info(1);
     let boot_nodes = vec![peer_info_a, peer_info_to_ban];
 
-    let peer_store =
-        PeerStore::new(&clock.clock(), make_config(&boot_nodes, Blacklist::default(), false))
-            .unwrap();
+    let peer_store = PeerStore::new(
+        &clock.clock(),
+        make_config(&boot_nodes, Blacklist::default(), false),
+        make_store(),
+    )
+    .unwrap();
 
-    assert!(peer_store.unconnected_peer(|_| false, false).is_some());
-    assert!(peer_store.unconnected_peer(|_| true, false).is_none());
+    assert!(peer_store.unconnected_peer(&clock.clock(), |_| false, false).is_some());
+    assert!(peer_store.unconnected_peer(&clock.clock(), |_| true, false).is_none());
 }
 
 #[test]
@@ -85,9 +116,12 @@ fn test_unknown_vs_not_connected() {
         nodes.map(|peer| peer_store.get_peer_state(&peer.id).map(|known_state| known_state.status))
     };
 
-    let peer_store =
-        PeerStore::new(&clock.clock(), make_config(&boot_nodes, Blacklist::default(), false))
-            .unwrap();
+    let peer_store = PeerStore::new(
+        &clock.clock(),
+        make_config(&boot_nodes, Blacklist::default(), false),
+        make_store(),
+    )
+    .unwrap();
 
     // Check the status of the in-memory store.
     // Boot node should be marked as not-connected, as we've verified it.
@@ -120,7 +154,7 @@ fn test_unknown_vs_not_connected() {
     // if we prefer 'previously connected' peers - we should keep picking 'b'.
     assert_eq!(
         (0..10)
-            .map(|_| peer_store.unconnected_peer(|_| false, true).unwrap().id)
+            .map(|_| peer_store.unconnected_peer(&clock.clock(), |_| false, true).unwrap().id)
             .collect::<HashSet<PeerId>>(),
         [peer_info_b.id.clone()].into_iter().collect::<HashSet<_>>()
     );
@@ -128,7 +162,7 @@ fn test_unknown_vs_not_connected() {
     // if we don't care, we should pick either 'b' or 'boot'.
     assert_eq!(
         (0..100)
-            .map(|_| peer_store.unconnected_peer(|_| false, false).unwrap().id)
+            .map(|_| peer_store.unconnected_peer(&clock.clock(), |_| false, false).unwrap().id)
             .collect::<HashSet<PeerId>>(),
         [peer_info_b.id.clone(), peer_info_boot_node.id.clone()]
             .into_iter()
@@ -159,24 +193,33 @@ fn test_unconnected_peer_only_boot_nodes() {
     // 1 non-boot (peer_in_store) node peer that is in the store.
     // we should connect to peer_in_store
     {
-        let peer_store =
-            PeerStore::new(&clock.clock(), make_config(&boot_nodes, Blacklist::default(), false))
-                .unwrap();
+        let peer_store = PeerStore::new(
+            &clock.clock(),
+            make_config(&boot_nodes, Blacklist::default(), false),
+            make_store(),
+        )
+        .unwrap();
         peer_store.add_direct_peer(&clock.clock(), peer_in_store.clone());
         peer_store.peer_connected(&clock.clock(), &peer_info_a);
-        assert_eq!(peer_store.unconnected_peer(|_| false, false), Some(peer_in_store.clone()));
+        assert_eq!(
+            peer_store.unconnected_peer(&clock.clock(), |_| false, false),
+            Some(peer_in_store.clone())
+        );
     }
 
     // 1 boot node (peer_info_a) that we're already connected to.
     // 1 non-boot (peer_in_store) node peer that is in the store.
     // connect to only boot nodes is enabled - we should not find any peer to connect to.
     {
-        let peer_store =
-            PeerStore::new(&clock.clock(), make_config(&boot_nodes, Default::default(), true))
-                .unwrap();
+        let peer_store = PeerStore::new(
+            &clock.clock(),
+            make_config(&boot_nodes, Default::default(), true),
+            make_store(),
+        )
+        .unwrap();
         peer_store.add_direct_peer(&clock.clock(), peer_in_store);
         peer_store.peer_connected(&clock.clock(), &peer_info_a);
-        assert_eq!(peer_store.unconnected_peer(|_| false, false), None);
+        assert_eq!(peer_store.unconnected_peer(&clock.clock(), |_| false, false), None);
     }
 
     // 1 boot node (peer_info_a) is in the store.
@@ -185,10 +228,14 @@ fn test_unconnected_peer_only_boot_nodes() {
         let peer_store = PeerStore::new(
             &clock.clock(),
             make_config(&boot_nodes, Default::default(), connect_to_boot_nodes),
+            make_store(),
         )
         .unwrap();
         peer_store.add_direct_peer(&clock.clock(), peer_info_a.clone());
-        assert_eq!(peer_store.unconnected_peer(|_| false, false), Some(peer_info_a.clone()));
+        assert_eq!(
+            peer_store.unconnected_peer(&clock.clock(), |_| false, false),
+            Some(peer_info_a.clone())
+        );
     }
 }
 
@@ -237,7 +284,8 @@ fn check_integrity(peer_store: &PeerStore) -> bool {
 fn handle_peer_id_change() {
     let clock = time::FakeClock::default();
     let peer_store =
-        PeerStore::new(&clock.clock(), make_config(&[], Default::default(), false)).unwrap();
+        PeerStore::new(&clock.clock(), make_config(&[], Default::default(), false), make_store())
+            .unwrap();
 
     let peers_id = (0..2).map(|ix| get_peer_id(format!("node{}", ix))).collect::<Vec<_>>();
     let addr = get_addr(0);
@@ -261,7 +309,8 @@ fn handle_peer_id_change() {
 fn dont_handle_address_change() {
     let clock = time::FakeClock::default();
     let peer_store =
-        PeerStore::new(&clock.clock(), make_config(&[], Default::default(), false)).unwrap();
+        PeerStore::new(&clock.clock(), make_config(&[], Default::default(), false), make_store())
+            .unwrap();
 
     let peers_id = (0..1).map(|ix| get_peer_id(format!("node{}", ix))).collect::<Vec<_>>();
     let addrs = (0..2).map(get_addr).collect::<Vec<_>>();
@@ -280,7 +329,8 @@ fn dont_handle_address_change() {
 fn check_add_peers_overriding() {
     let clock = time::FakeClock::default();
     let peer_store =
-        PeerStore::new(&clock.clock(), make_config(&[], Default::default(), false)).unwrap();
+        PeerStore::new(&clock.clock(), make_config(&[], Default::default(), false), make_store())
+            .unwrap();
 
     // Five peers: A, B, C, D, X, T
     let peers_id = (0..6).map(|ix| get_peer_id(format!("node{}", ix))).collect::<Vec<_>>();
@@ -372,7 +422,8 @@ fn check_ignore_blacklisted_peers() {
     let blacklist: blacklist::Blacklist =
         ["127.0.0.1:1"].iter().map(|e| e.parse().unwrap()).collect();
 
-    let peer_store = PeerStore::new(&clock.clock(), make_config(&[], blacklist, false)).unwrap();
+    let peer_store =
+        PeerStore::new(&clock.clock(), make_config(&[], blacklist, false), make_store()).unwrap();
 
     peer_store.add_indirect_peers(
         &clock.clock(),
@@ -417,7 +468,8 @@ fn test_delete_peers() {
     let peer_addresses = peer_infos.iter().map(|info| info.addr.unwrap()).collect::<Vec<_>>();
 
     let peer_store =
-        PeerStore::new(&clock.clock(), make_config(&[], Default::default(), false)).unwrap();
+        PeerStore::new(&clock.clock(), make_config(&[], Default::default(), false), make_store())
+            .unwrap();
 
     peer_store.add_indirect_peers(&clock.clock(), peer_infos.into_iter());
     assert_peers_in_cache(&peer_store, &peer_ids, &peer_addresses);
@@ -431,7 +483,7 @@ fn test_lru_eviction() {
     let clock = time::FakeClock::default();
     let mut config = make_config(&[], Default::default(), false);
     config.peer_states_cache_size = 10;
-    let peer_store = PeerStore::new(&clock.clock(), config).unwrap();
+    let peer_store = PeerStore::new(&clock.clock(), config, make_store()).unwrap();
 
     let (peer_ids, peer_infos): (Vec<_>, Vec<_>) = (0..15)
         .map(|i| {
@@ -462,7 +514,7 @@ fn test_lru_ignore_duplicate_peers() {
     let clock = time::FakeClock::default();
     let mut config = make_config(&[], Default::default(), false);
     config.peer_states_cache_size = 10;
-    let peer_store = PeerStore::new(&clock.clock(), config).unwrap();
+    let peer_store = PeerStore::new(&clock.clock(), config, make_store()).unwrap();
 
     let (peer_ids, peer_infos): (Vec<_>, Vec<_>) = (0..15)
         .map(|i| {
@@ -485,3 +537,77 @@ fn test_lru_ignore_duplicate_peers() {
     peer_store.add_indirect_peers(&clock.clock(), peer_infos[10..].iter().cloned());
     assert_peers_in_cache(&peer_store, &peer_ids[5..], &peer_addresses[5..]);
 }
+
+#[test]
+fn test_reputation_ranking() {
+    let clock = time::FakeClock::default();
+    let good_peer = gen_peer_info(0);
+    let bad_peer = gen_peer_info(1);
+    let boot_nodes = vec![good_peer.clone(), bad_peer.clone()];
+    let peer_store = PeerStore::new(
+        &clock.clock(),
+        make_config(&boot_nodes, Default::default(), false),
+        make_store(),
+    )
+    .unwrap();
+
+    peer_store.report_behaviour(&clock.clock(), &good_peer.id, PeerBehaviour::UsefulHeaderSync);
+    peer_store.report_behaviour(&clock.clock(), &bad_peer.id, PeerBehaviour::SlowStatePart);
+    assert_eq!(
+        peer_store.reputation(&clock.clock(), &good_peer.id),
+        PeerBehaviour::UsefulHeaderSync.score_delta()
+    );
+    for _ in 0..10 {
+        assert_eq!(
+            peer_store.unconnected_peer(&clock.clock(), |_| false, false),
+            Some(good_peer.clone())
+        );
+        assert_eq!(
+            peer_store.healthy_peers(&clock.clock(), 2),
+            vec![good_peer.clone(), bad_peer.clone()]
+        );
+    }
+
+    // Peers with a very low reputation are not advertised.
+    for _ in 0..10 {
+        peer_store.report_behaviour(&clock.clock(), &bad_peer.id, PeerBehaviour::InvalidBlock);
+    }
+    assert!(peer_store.reputation(&clock.clock(), &bad_peer.id) < MIN_HEALTHY_REPUTATION);
+    assert_eq!(peer_store.healthy_peers(&clock.clock(), 2), vec![good_peer]);
+}
+
+#[test]
+fn test_reputation_decay() {
+    let clock = time::FakeClock::default();
+    let peer = gen_peer_info(0);
+    let peer_store = PeerStore::new(
+        &clock.clock(),
+        make_config(&[peer.clone()], Default::default(), false),
+        make_store(),
+    )
+    .unwrap();
+
+    peer_store.report_behaviour(&clock.clock(), &peer.id, PeerBehaviour::InvalidBlock);
+    assert_eq!(peer_store.reputation(&clock.clock(), &peer.id), -100);
+    clock.advance(REPUTATION_HALF_LIFE);
+    assert_eq!(peer_store.reputation(&clock.clock(), &peer.id), -50);
+    clock.advance(REPUTATION_HALF_LIFE * 20);
+    assert_eq!(peer_store.reputation(&clock.clock(), &peer.id), 0);
+}
+
+#[test]
+fn test_reputation_persisted() {
+    let clock = time::FakeClock::default();
+    let peer = gen_peer_info(0);
+    let store = make_store();
+    let config = make_config(&[peer.clone()], Default::default(), false);
+
+    let peer_store = PeerStore::new(&clock.clock(), config.clone(), store.clone()).unwrap();
+    peer_store.peer_ban(&clock.clock(), &peer.id, ReasonForBan::Abusive).unwrap();
+    assert_eq!(peer_store.reputation(&clock.clock(), &peer.id), BAN_REPUTATION_PENALTY);
+    peer_store.update(&clock.clock());
+
+    // A new PeerStore, e.g. after a restart, picks up the saved reputation.
+    let peer_store = PeerStore::new(&clock.clock(), config, store).unwrap();
+    assert_eq!(peer_store.reputation(&clock.clock(), &peer.id), BAN_REPUTATION_PENALTY);
+}
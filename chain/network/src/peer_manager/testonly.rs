@@ -17,8 +17,8 @@ use crate::test_utils;
 use crate::testonly::actix::ActixSystem;
 use crate::testonly::fake_client;
 use crate::types::{
-    AccountKeys, ChainInfo, KnownPeerStatus, NetworkRequests, PeerManagerMessageRequest,
-    ReasonForBan,
+    AccountKeys, ChainInfo, KnownPeerStatus, NetworkRequests, NetworkResponses,
+    PeerManagerMessageRequest, ReasonForBan,
 };
 use crate::PeerManagerActor;
 use std::collections::HashSet;
@@ -394,6 +394,15 @@ impl ActorHandler {
         .await;
     }
 
+    pub async fn send_network_request(&self, request: NetworkRequests) -> NetworkResponses {
+        self.actix
+            .addr
+            .send(PeerManagerMessageRequest::NetworkRequests(request).with_span_context())
+            .await
+            .unwrap()
+            .as_network_response()
+    }
+
     pub async fn announce_account(&self, aa: AnnounceAccount) {
         self.actix
             .addr
@@ -16,7 +16,7 @@ use crate::store;
 use crate::tcp;
 use crate::testonly::{abort_on_panic, make_rng, Rng};
 use crate::types::PeerMessage;
-use crate::types::{PeerInfo, ReasonForBan};
+use crate::types::{NetworkRequests, NetworkResponses, PeerInfo, ReasonForBan};
 use pretty_assertions::assert_eq;
 use rand::seq::IteratorRandom;
 use rand::Rng as _;
@@ -1385,6 +1385,58 @@ async fn connect_to_unbanned_peer() {
     drop(pm1);
 }
 
+/// Check that the ban and unban requests are dispatched to the peer store, and that a failed
+/// unban is reported back.
+#[tokio::test]
+async fn ban_and_unban_peer_requests() {
+    abort_on_panic();
+    let mut rng = make_rng(921853233);
+    let rng = &mut rng;
+    let mut clock = time::FakeClock::default();
+    let chain = Arc::new(data::Chain::make(&mut clock, rng, 10));
+
+    let mut pm0 =
+        start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let mut pm1 =
+        start_pm(clock.clock(), TestDB::new(), chain.make_config(rng), chain.clone()).await;
+    let pm0_id = pm0.cfg.node_id();
+
+    tracing::info!(target:"test", "unbanning a peer which isn't banned fails");
+    let unban = || NetworkRequests::UnbanPeer { peer_id: pm0_id.clone() };
+    assert!(matches!(
+        pm1.send_network_request(unban()).await,
+        NetworkResponses::UnbanPeerFailed(_)
+    ));
+
+    let stream_id = pm0.connect_to(&pm1.peer_info(), tcp::Tier::T2).await;
+
+    tracing::info!(target:"test", "pm1 bans pm0 on request");
+    let ban_reason = ReasonForBan::Abusive;
+    assert_eq!(
+        NetworkResponses::NoResponse,
+        pm1.send_network_request(NetworkRequests::BanPeer { peer_id: pm0_id.clone(), ban_reason })
+            .await
+    );
+    wait_for_stream_closed(&mut pm0.events, stream_id).await;
+    assert_eq!(
+        ClosingReason::Ban(ban_reason),
+        wait_for_stream_closed(&mut pm1.events, stream_id).await
+    );
+
+    tracing::info!(target:"test", "pm1 unbans pm0 on request and pm0 reconnects");
+    assert_eq!(NetworkResponses::NoResponse, pm1.send_network_request(unban()).await);
+    pm0.connect_to(&pm1.peer_info(), tcp::Tier::T2).await;
+
+    tracing::info!(target:"test", "unbanning a peer twice fails");
+    assert!(matches!(
+        pm1.send_network_request(unban()).await,
+        NetworkResponses::UnbanPeerFailed(_)
+    ));
+
+    drop(pm0);
+    drop(pm1);
+}
+
 /// Awaits a DistanceVector message from a given `peer_id`
 async fn wait_for_distance_vector(events: &mut broadcast::Receiver<Event>, peer_id: PeerId) {
     events
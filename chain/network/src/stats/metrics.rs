@@ -218,6 +218,15 @@ pub(crate) static REQUEST_COUNT_BY_TYPE_TOTAL: Lazy<IntCounterVec> = Lazy::new(|
     .unwrap()
 });
 
+pub(crate) static PEER_BEHAVIOUR_REPORTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "unc_peer_behaviour_reports_total",
+        "Number of reports of peer behaviour affecting peer reputation, by behaviour",
+        &["behaviour"],
+    )
+    .unwrap()
+});
+
 // Routing table metrics
 pub(crate) static ROUTING_TABLE_RECALCULATIONS: Lazy<IntCounter> = Lazy::new(|| {
     try_create_int_counter(
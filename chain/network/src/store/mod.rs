@@ -2,7 +2,7 @@
 /// All transactions should be implemented within this module,
 /// in particular schema::StoreUpdate is not exported.
 use crate::network_protocol::Edge;
-use crate::types::ConnectionInfo;
+use crate::types::{ConnectionInfo, PeerReputation};
 use std::collections::HashSet;
 use std::sync::Arc;
 use tracing::debug;
@@ -137,6 +137,22 @@ impl Store {
     }
 }
 
+// PeerStore storage.
+impl Store {
+    pub fn set_peer_reputations(
+        &mut self,
+        peer_reputations: &Vec<(PeerId, PeerReputation)>,
+    ) -> Result<(), Error> {
+        let mut update = self.0.new_update();
+        update.set::<schema::PeerReputations>(&(), &peer_reputations);
+        self.0.commit(update).map_err(Error)
+    }
+
+    pub fn get_peer_reputations(&self) -> Vec<(PeerId, PeerReputation)> {
+        self.0.get::<schema::PeerReputations>(&()).unwrap_or(Some(vec![])).unwrap_or(vec![])
+    }
+}
+
 impl From<Arc<dyn unc_store::db::Database>> for Store {
     fn from(store: Arc<dyn unc_store::db::Database>) -> Self {
         Self(schema::Store::from(store))
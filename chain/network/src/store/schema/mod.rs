@@ -59,6 +59,37 @@ impl BorshRepr for ConnectionInfoRepr {
     }
 }
 
+/// A Borsh representation of a (PeerId, primitives::PeerReputation) pair.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub(super) struct PeerReputationRepr {
+    peer_id: PeerId,
+    score: i32,
+    /// UNIX timestamp in nanos.
+    last_updated: u64,
+}
+
+impl BorshRepr for PeerReputationRepr {
+    type T = (PeerId, primitives::PeerReputation);
+    fn to_repr(s: &Self::T) -> Self {
+        Self {
+            peer_id: s.0.clone(),
+            score: s.1.score,
+            last_updated: s.1.last_updated.unix_timestamp_nanos() as u64,
+        }
+    }
+
+    fn from_repr(s: Self) -> Result<Self::T, Error> {
+        Ok((
+            s.peer_id,
+            primitives::PeerReputation {
+                score: s.score,
+                last_updated: time::Utc::from_unix_timestamp_nanos(s.last_updated as i128)
+                    .map_err(invalid_data)?,
+            },
+        ))
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub(super) struct EdgeRepr {
     key: (PeerId, PeerId),
@@ -103,6 +134,13 @@ impl Column for RecentOutboundConnections {
     type Value = Vec<ConnectionInfoRepr>;
 }
 
+pub(super) struct PeerReputations;
+impl Column for PeerReputations {
+    const COL: DBCol = DBCol::PeerReputations;
+    type Key = Borsh<()>;
+    type Value = Vec<PeerReputationRepr>;
+}
+
 pub(super) struct PeerComponent;
 impl Column for PeerComponent {
     const COL: DBCol = DBCol::PeerComponent;
@@ -71,6 +71,39 @@ pub enum ReasonForBan {
     ProvidedNotEnoughHeaders = 15,
 }
 
+/// Observed behaviour of a peer which changes its reputation score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::IntoStaticStr)]
+pub enum PeerBehaviour {
+    /// The peer sent us a block which turned out to be invalid.
+    InvalidBlock,
+    /// The peer sent us a block with an invalid chunk.
+    InvalidChunk,
+    /// The peer didn't respond to a state part request in time.
+    SlowStatePart,
+    /// The peer sent us block headers which advanced our header head.
+    UsefulHeaderSync,
+}
+
+impl PeerBehaviour {
+    /// How much the behaviour changes the reputation score of the peer.
+    pub fn score_delta(&self) -> i32 {
+        match self {
+            PeerBehaviour::InvalidBlock => -100,
+            PeerBehaviour::InvalidChunk => -50,
+            PeerBehaviour::SlowStatePart => -10,
+            PeerBehaviour::UsefulHeaderSync => 5,
+        }
+    }
+}
+
+/// Reputation of a peer, see `PeerStore::report_behaviour`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerReputation {
+    /// Score at the time of `last_updated`. The score decays towards zero over time.
+    pub score: i32,
+    pub last_updated: time::Utc,
+}
+
 /// Banning signal sent from Peer instance to PeerManager
 /// just before Peer instance is stopped.
 #[derive(actix::Message, Debug)]
@@ -156,6 +189,14 @@ pub struct ChainInfo {
 #[rtype(result = "()")]
 pub struct SetChainInfo(pub ChainInfo);
 
+/// Network settings which can be changed while the node is running.
+#[derive(Debug, actix::Message)]
+#[rtype(result = "()")]
+pub struct UpdateNetworkConfig {
+    pub blacklist: crate::blacklist::Blacklist,
+    pub max_num_peers: u32,
+}
+
 /// Public actix interface of `PeerManagerActor`.
 #[derive(actix::Message, Debug, strum::IntoStaticStr)]
 #[rtype(result = "PeerManagerMessageResponse")]
@@ -231,6 +272,10 @@ pub enum NetworkRequests {
     StateRequestPart { shard_id: ShardId, sync_hash: CryptoHash, part_id: u64, peer_id: PeerId },
     /// Ban given peer.
     BanPeer { peer_id: PeerId, ban_reason: ReasonForBan },
+    /// Lift the ban of the given peer before the ban window expires.
+    UnbanPeer { peer_id: PeerId },
+    /// Update the reputation of the given peer based on its observed behaviour.
+    ReportPeerBehaviour { peer_id: PeerId, behaviour: PeerBehaviour },
     /// Announce account
     AnnounceAccount(AnnounceAccount),
     /// Broadcast information about a hosted snapshot.
@@ -285,6 +330,8 @@ pub struct HighestHeightPeerInfo {
     pub tracked_shards: Vec<ShardId>,
     /// Denote if a node is running in archival mode or not.
     pub archival: bool,
+    /// Reputation score of the peer in the PeerStore.
+    pub reputation: i32,
 }
 
 impl From<FullPeerInfo> for Option<HighestHeightPeerInfo> {
@@ -297,6 +344,7 @@ impl From<FullPeerInfo> for Option<HighestHeightPeerInfo> {
                 highest_block_hash: p.chain_info.last_block.unwrap().hash,
                 tracked_shards: p.chain_info.tracked_shards,
                 archival: p.chain_info.archival,
+                reputation: 0,
             })
         } else {
             None
@@ -343,6 +391,8 @@ pub struct ConnectedPeerInfo {
     pub peer_type: PeerType,
     /// Nonce used for the connection with the peer.
     pub nonce: u64,
+    /// Reputation score of the peer in the PeerStore.
+    pub reputation: i32,
 }
 
 #[derive(Debug, Clone, actix::MessageResponse)]
@@ -367,6 +417,8 @@ pub struct NetworkInfo {
 pub enum NetworkResponses {
     NoResponse,
     RouteNotFound,
+    /// The peer couldn't be unbanned, e.g. because it isn't banned.
+    UnbanPeerFailed(String),
 }
 
 #[derive(Clone, derive_more::AsRef)]
@@ -1,15 +1,16 @@
 use std::collections::btree_map::Entry;
-use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 
 use crate::types::{PoolIterator, PoolKey, TransactionGroup};
 
 use std::ops::Bound;
+use unc_chain_configs::TransactionPoolEvictionPolicy;
 use unc_crypto::PublicKey;
 use unc_o11y::metrics::prometheus::core::{AtomicI64, GenericGauge};
 use unc_primitives::epoch_manager::RngSeed;
 use unc_primitives::hash::{hash, CryptoHash};
 use unc_primitives::transaction::SignedTransaction;
-use unc_primitives::types::AccountId;
+use unc_primitives::types::{AccountId, Gas};
 
 mod metrics;
 pub mod types;
@@ -18,37 +19,90 @@ pub mod types;
 pub enum InsertTransactionResult {
     /// Transaction was successfully inserted.
     Success,
-    /// Transaction is already in the pool.
+    /// Transaction replaced a pending transaction with the same signer, public key and nonce.
+    Replaced,
+    /// Transaction is a cancellation: the pending transaction with the same signer, public key
+    /// and nonce was removed and the cancellation itself was not added to the pool.
+    Cancelled,
+    /// Transaction is already in the pool, or a transaction with the same signer, public key
+    /// and nonce with a higher priority is.
     Duplicate,
     /// Not enough space to fit the transaction.
     NoSpaceLeft,
 }
 
+/// Priority of a transaction in the pool. A pending transaction can only be replaced by a
+/// transaction with the same nonce and at least the same priority, and the eviction policy
+/// `LowestPriority` evicts transactions with the lowest priority first.
+pub type TransactionPriority = Gas;
+
+/// Returns the priority of the transaction, which is the total amount of gas prepaid by its
+/// actions.
+pub fn transaction_priority(signed_transaction: &SignedTransaction) -> TransactionPriority {
+    signed_transaction
+        .transaction
+        .actions
+        .iter()
+        .fold(0, |total, action| total.saturating_add(action.get_prepaid_gas()))
+}
+
+/// Returns whether the transaction explicitly cancels a pending transaction with the same nonce.
+/// A cancellation is a transaction without actions sent by the signer to itself. It removes the
+/// pending transaction regardless of its priority and is never added to the pool itself.
+pub fn is_cancellation(signed_transaction: &SignedTransaction) -> bool {
+    let transaction = &signed_transaction.transaction;
+    transaction.actions.is_empty() && transaction.receiver_id == transaction.signer_id
+}
+
+/// Bookkeeping of a single transaction stored in the pool.
+struct PoolEntry {
+    /// The key of the group the transaction belongs to.
+    key: PoolKey,
+    /// Size of the transaction in bytes.
+    size: u64,
+    /// Position of the transaction in the eviction queue.
+    eviction_key: EvictionKey,
+}
+
+/// Ordering of transactions for eviction: (priority, insertion sequence number, hash).
+/// The priority component is zero if the eviction policy doesn't take priorities into account.
+type EvictionKey = (TransactionPriority, u64, CryptoHash);
+
 /// Transaction pool: keeps track of transactions that were not yet accepted into the block chain.
 pub struct TransactionPool {
     /// Transactions are grouped by a pair of (account ID, signer public key).
-    /// NOTE: It's more efficient on average to keep transactions unsorted and with potentially
-    /// conflicting nonce than to create a BTreeMap for every transaction.
+    /// NOTE: It's more efficient on average to keep transactions unsorted than to create a
+    /// BTreeMap for every transaction. The nonces within a group are unique.
     transactions: BTreeMap<PoolKey, Vec<SignedTransaction>>,
-    /// Set of all hashes to quickly check if the given transaction is in the pool.
-    unique_transactions: HashSet<CryptoHash>,
+    /// All hashes of transactions in the pool to quickly check if the given transaction is in
+    /// the pool, along with the bookkeeping needed to replace and evict them.
+    unique_transactions: HashMap<CryptoHash, PoolEntry>,
+    /// Transactions ordered by the eviction policy, the first one is evicted first.
+    eviction_queue: BTreeSet<EvictionKey>,
+    /// Sequence number assigned to the next inserted transaction.
+    next_insertion_seq: u64,
     /// A uniquely generated key seed to randomize PoolKey order.
     key_seed: RngSeed,
     /// The key after which the pool iterator starts. Doesn't have to be present in the pool.
     last_used_key: PoolKey,
-    /// If set, new transactions that bring the size of the pool over this limit will be rejected.
+    /// If set, new transactions that bring the size of the pool over this limit will either
+    /// be rejected or evict other transactions, depending on `eviction_policy`.
     total_transaction_size_limit: Option<u64>,
+    /// What to do when a new transaction doesn't fit into `total_transaction_size_limit`.
+    eviction_policy: TransactionPoolEvictionPolicy,
     /// Total size of transactions in the pool measured in bytes.
     total_transaction_size: u64,
     /// Metrics tracked for transaction pool.
     transaction_pool_count_metric: GenericGauge<AtomicI64>,
     transaction_pool_size_metric: GenericGauge<AtomicI64>,
+    metrics_label: String,
 }
 
 impl TransactionPool {
     pub fn new(
         key_seed: RngSeed,
         total_transaction_size_limit: Option<u64>,
+        eviction_policy: TransactionPoolEvictionPolicy,
         metrics_label: &str,
     ) -> Self {
         let transaction_pool_count_metric =
@@ -62,15 +116,25 @@ impl TransactionPool {
         Self {
             key_seed,
             transactions: BTreeMap::new(),
-            unique_transactions: HashSet::new(),
+            unique_transactions: HashMap::new(),
+            eviction_queue: BTreeSet::new(),
+            next_insertion_seq: 0,
             last_used_key: CryptoHash::default(),
             total_transaction_size_limit,
+            eviction_policy,
             total_transaction_size: 0,
             transaction_pool_count_metric,
             transaction_pool_size_metric,
+            metrics_label: metrics_label.to_string(),
         }
     }
 
+    /// Changes the size limit. Transactions already in the pool are kept even if
+    /// they exceed the new limit, it's only enforced for the new transactions.
+    pub fn set_total_transaction_size_limit(&mut self, limit: Option<u64>) {
+        self.total_transaction_size_limit = limit;
+    }
+
     fn key(&self, account_id: &AccountId, public_key: &PublicKey) -> PoolKey {
         let mut v = borsh::to_vec(&public_key).unwrap();
         v.extend_from_slice(&self.key_seed);
@@ -79,40 +143,205 @@ impl TransactionPool {
     }
 
     /// Inserts a signed transaction that passed validation into the pool.
+    ///
+    /// If a transaction with the same signer, public key and nonce is already in the pool, the
+    /// new one replaces it only if it has at least the same priority, so that transactions
+    /// without prepaid gas such as transfers can be replaced too. A cancellation removes the
+    /// pending transaction instead and isn't added to the pool.
+    /// If the pool is full, transactions are evicted according to the eviction policy.
     #[must_use]
     pub fn insert_transaction(
         &mut self,
         signed_transaction: SignedTransaction,
     ) -> InsertTransactionResult {
-        if !self.unique_transactions.insert(signed_transaction.get_hash()) {
+        let tx_hash = signed_transaction.get_hash();
+        if self.unique_transactions.contains_key(&tx_hash) {
             // The hash of this transaction was already seen, skip it.
             return InsertTransactionResult::Duplicate;
         }
+        let priority = transaction_priority(&signed_transaction);
+        let key = self.key(
+            &signed_transaction.transaction.signer_id,
+            &signed_transaction.transaction.public_key,
+        );
+
+        let replaced = self.transactions.get(&key).and_then(|group| {
+            group.iter().find(|tx| tx.transaction.nonce == signed_transaction.transaction.nonce)
+        });
+        let replaced = match replaced {
+            Some(tx) if is_cancellation(&signed_transaction) => {
+                let tx_hash = tx.get_hash();
+                self.evict_transaction(&tx_hash, "cancelled");
+                self.transaction_pool_count_metric.set(self.unique_transactions.len() as i64);
+                self.transaction_pool_size_metric.set(self.total_transaction_size as i64);
+                return InsertTransactionResult::Cancelled;
+            }
+            Some(tx) => {
+                if priority < transaction_priority(tx) {
+                    return InsertTransactionResult::Duplicate;
+                }
+                Some((tx.get_hash(), tx.get_size()))
+            }
+            None => None,
+        };
+
         // We never expect the total size to go over `u64` during real operation as that would
         // be more than 10^9 GiB of RAM consumed for transaction pool, so panicing here is intended
         // to catch a logic error in estimation of transaction size.
         let new_total_transaction_size = self
             .total_transaction_size
+            .checked_sub(replaced.map_or(0, |(_, size)| size))
+            .expect("Total transaction size dropped below zero")
             .checked_add(signed_transaction.get_size())
             .expect("Total transaction size is too large");
+        let mut evicted = vec![];
         if let Some(limit) = self.total_transaction_size_limit {
             if new_total_transaction_size > limit {
-                return InsertTransactionResult::NoSpaceLeft;
+                let replaced_hash = replaced.map(|(hash, _)| hash);
+                match self.select_for_eviction(
+                    new_total_transaction_size - limit,
+                    priority,
+                    replaced_hash,
+                ) {
+                    Some(hashes) => evicted = hashes,
+                    None => return InsertTransactionResult::NoSpaceLeft,
+                }
             }
         }
 
         // At this point transaction is accepted to the pool.
-        self.total_transaction_size = new_total_transaction_size;
-        let signer_id = &signed_transaction.transaction.signer_id;
-        let signer_public_key = &signed_transaction.transaction.public_key;
-        self.transactions
-            .entry(self.key(signer_id, signer_public_key))
-            .or_insert_with(Vec::new)
-            .push(signed_transaction);
-
-        self.transaction_pool_count_metric.inc();
+        if let Some((replaced_hash, _)) = replaced {
+            self.evict_transaction(&replaced_hash, "replaced");
+        }
+        let reason = self.eviction_policy_label();
+        for hash in evicted {
+            self.evict_transaction(&hash, reason);
+        }
+        self.total_transaction_size = self
+            .total_transaction_size
+            .checked_add(signed_transaction.get_size())
+            .expect("Total transaction size is too large");
+        let eviction_key = (self.eviction_priority(priority), self.next_insertion_seq, tx_hash);
+        self.next_insertion_seq += 1;
+        self.eviction_queue.insert(eviction_key);
+        self.unique_transactions
+            .insert(tx_hash, PoolEntry { key, size: signed_transaction.get_size(), eviction_key });
+        self.transactions.entry(key).or_insert_with(Vec::new).push(signed_transaction);
+
+        self.transaction_pool_count_metric.set(self.unique_transactions.len() as i64);
         self.transaction_pool_size_metric.set(self.total_transaction_size as i64);
-        InsertTransactionResult::Success
+        if replaced.is_some() {
+            InsertTransactionResult::Replaced
+        } else {
+            InsertTransactionResult::Success
+        }
+    }
+
+    /// Cancels the pending transaction with the given signer, public key and nonce.
+    /// Returns the cancelled transaction if it was in the pool.
+    ///
+    /// It's up to the caller to verify that the cancellation was requested by the owner of the
+    /// access key, e.g. by receiving a signed cancellation transaction.
+    pub fn cancel_transaction(
+        &mut self,
+        signer_id: &AccountId,
+        public_key: &PublicKey,
+        nonce: u64,
+    ) -> Option<SignedTransaction> {
+        let key = self.key(signer_id, public_key);
+        let tx_hash = self
+            .transactions
+            .get(&key)?
+            .iter()
+            .find(|tx| tx.transaction.nonce == nonce)
+            .map(|tx| tx.get_hash())?;
+        let cancelled = self.evict_transaction(&tx_hash, "cancelled");
+        self.transaction_pool_count_metric.set(self.unique_transactions.len() as i64);
+        self.transaction_pool_size_metric.set(self.total_transaction_size as i64);
+        cancelled
+    }
+
+    /// Returns the priority component of the eviction key for the configured policy.
+    fn eviction_priority(&self, priority: TransactionPriority) -> TransactionPriority {
+        match self.eviction_policy {
+            TransactionPoolEvictionPolicy::LowestPriority => priority,
+            TransactionPoolEvictionPolicy::Reject | TransactionPoolEvictionPolicy::Oldest => 0,
+        }
+    }
+
+    fn eviction_policy_label(&self) -> &'static str {
+        match self.eviction_policy {
+            TransactionPoolEvictionPolicy::Reject => "reject",
+            TransactionPoolEvictionPolicy::LowestPriority => "lowest_priority",
+            TransactionPoolEvictionPolicy::Oldest => "oldest",
+        }
+    }
+
+    /// Selects transactions to evict in order to free at least `required_size` bytes for a new
+    /// transaction with the given priority. Returns `None` if the policy doesn't allow freeing
+    /// enough space, in which case nothing should be evicted.
+    fn select_for_eviction(
+        &self,
+        required_size: u64,
+        priority: TransactionPriority,
+        skip: Option<CryptoHash>,
+    ) -> Option<Vec<CryptoHash>> {
+        if self.eviction_policy == TransactionPoolEvictionPolicy::Reject {
+            return None;
+        }
+        let mut freed_size = 0;
+        let mut hashes = vec![];
+        for (entry_priority, _, hash) in &self.eviction_queue {
+            if self.eviction_policy == TransactionPoolEvictionPolicy::LowestPriority
+                && *entry_priority >= priority
+            {
+                // Never evict transactions that are at least as valuable as the new one.
+                break;
+            }
+            if Some(*hash) == skip {
+                continue;
+            }
+            freed_size += self.unique_transactions[hash].size;
+            hashes.push(*hash);
+            if freed_size >= required_size {
+                return Some(hashes);
+            }
+        }
+        None
+    }
+
+    /// Removes the transaction with the given hash from the pool and records the eviction.
+    /// Metrics on the pool size are left to the caller.
+    fn evict_transaction(
+        &mut self,
+        tx_hash: &CryptoHash,
+        reason: &str,
+    ) -> Option<SignedTransaction> {
+        let entry = self.forget_transaction(tx_hash)?;
+        let Entry::Occupied(mut group) = self.transactions.entry(entry.key) else {
+            return None;
+        };
+        let index = group.get().iter().position(|tx| &tx.get_hash() == tx_hash)?;
+        let tx = group.get_mut().swap_remove(index);
+        if group.get().is_empty() {
+            group.remove_entry();
+        }
+        // See the comment in `insert_transaction` where we increase the size for reasoning why
+        // panicing here catches a logic error.
+        self.total_transaction_size = self
+            .total_transaction_size
+            .checked_sub(entry.size)
+            .expect("Total transaction size dropped below zero");
+        metrics::TRANSACTION_POOL_EVICTIONS.with_label_values(&[&self.metrics_label, reason]).inc();
+        Some(tx)
+    }
+
+    /// Removes the bookkeeping of the transaction with the given hash, but not the transaction
+    /// itself.
+    fn forget_transaction(&mut self, tx_hash: &CryptoHash) -> Option<PoolEntry> {
+        let entry = self.unique_transactions.remove(tx_hash)?;
+        self.eviction_queue.remove(&entry.eviction_key);
+        Some(entry)
     }
 
     /// Returns a pool iterator wrapper that implements an iterator-like trait to iterate over
@@ -130,14 +359,12 @@ impl TransactionPool {
         let mut grouped_transactions = HashMap::new();
         for tx in transactions {
             // If transaction is not present in the pool, skip it.
-            if !self.unique_transactions.remove(&tx.get_hash()) {
+            let Some(entry) = self.forget_transaction(&tx.get_hash()) else {
                 continue;
-            }
+            };
 
-            let signer_id = &tx.transaction.signer_id;
-            let signer_public_key = &tx.transaction.public_key;
             grouped_transactions
-                .entry(self.key(signer_id, signer_public_key))
+                .entry(entry.key)
                 .or_insert_with(HashSet::new)
                 .insert(tx.get_hash());
         }
@@ -166,6 +393,21 @@ impl TransactionPool {
         self.transaction_pool_size_metric.set(self.total_transaction_size as i64);
     }
 
+    /// Removes all transactions from the pool. Returns the number of removed transactions.
+    pub fn clear(&mut self) -> usize {
+        let num_transactions = self.unique_transactions.len();
+        self.transactions.clear();
+        self.unique_transactions.clear();
+        self.eviction_queue.clear();
+        self.total_transaction_size = 0;
+        metrics::TRANSACTION_POOL_EVICTIONS
+            .with_label_values(&[&self.metrics_label, "drained"])
+            .inc_by(num_transactions as u64);
+        self.transaction_pool_count_metric.set(0);
+        self.transaction_pool_size_metric.set(0);
+        num_transactions
+    }
+
     /// Returns the number of unique transactions in the pool.
     pub fn len(&self) -> usize {
         self.unique_transactions.len()
@@ -186,11 +428,14 @@ pub struct PoolIteratorWrapper<'a> {
 
     /// Queue of transaction groups. Each group there is sorted by nonce.
     sorted_groups: VecDeque<TransactionGroup>,
+
+    /// Postponed transactions of the exhausted groups, returned back to the pool on drop.
+    postponed_groups: Vec<(PoolKey, Vec<SignedTransaction>)>,
 }
 
 impl<'a> PoolIteratorWrapper<'a> {
     pub fn new(pool: &'a mut TransactionPool) -> Self {
-        Self { pool, sorted_groups: Default::default() }
+        Self { pool, sorted_groups: Default::default(), postponed_groups: Default::default() }
     }
 }
 
@@ -236,13 +481,14 @@ impl<'a> PoolIterator for PoolIteratorWrapper<'a> {
                 transactions,
                 removed_transaction_hashes: vec![],
                 removed_transaction_size: 0,
+                postponed_transactions: vec![],
             });
             Some(self.sorted_groups.back_mut().expect("just pushed"))
         } else {
             while let Some(sorted_group) = self.sorted_groups.pop_front() {
                 if sorted_group.transactions.is_empty() {
                     for hash in sorted_group.removed_transaction_hashes {
-                        self.pool.unique_transactions.remove(&hash);
+                        self.pool.forget_transaction(&hash);
                     }
                     // See the comment in `insert_transaction` where we increase the size for reasoning
                     // why panicing here catches a logic error.
@@ -256,6 +502,10 @@ impl<'a> PoolIterator for PoolIteratorWrapper<'a> {
                         .transaction_pool_count_metric
                         .set(self.pool.unique_transactions.len() as i64);
                     self.pool.transaction_pool_size_metric.set(self.pool.transaction_size() as i64);
+                    if !sorted_group.postponed_transactions.is_empty() {
+                        self.postponed_groups
+                            .push((sorted_group.key, sorted_group.postponed_transactions));
+                    }
                 } else {
                     self.sorted_groups.push_back(sorted_group);
                     return Some(self.sorted_groups.back_mut().expect("just pushed"));
@@ -267,13 +517,16 @@ impl<'a> PoolIterator for PoolIteratorWrapper<'a> {
 }
 
 /// When a pool iterator is dropped, all remaining non empty transaction groups from the sorted
-/// groups queue are inserted back into the pool. And removed transactions hashes from groups are
-/// removed from the pool's unique_transactions.
+/// groups queue and all postponed transactions are inserted back into the pool. And removed
+/// transactions hashes from groups are removed from the pool's unique_transactions.
 impl<'a> Drop for PoolIteratorWrapper<'a> {
     fn drop(&mut self) {
-        for group in self.sorted_groups.drain(..) {
+        for (key, transactions) in self.postponed_groups.drain(..) {
+            self.pool.transactions.insert(key, transactions);
+        }
+        for mut group in self.sorted_groups.drain(..) {
             for hash in group.removed_transaction_hashes {
-                self.pool.unique_transactions.remove(&hash);
+                self.pool.forget_transaction(&hash);
             }
             // See the comment in `insert_transaction` where we increase the size for reasoning
             // why panicing here catches a logic error.
@@ -283,6 +536,7 @@ impl<'a> Drop for PoolIteratorWrapper<'a> {
                 .checked_sub(group.removed_transaction_size)
                 .expect("Total transaction size dropped below zero");
 
+            group.transactions.append(&mut group.postponed_transactions);
             if !group.transactions.is_empty() {
                 self.pool.transactions.insert(group.key, group.transactions);
             }
@@ -335,7 +589,8 @@ mod tests {
         mut transactions: Vec<SignedTransaction>,
         expected_weight: u32,
     ) -> (Vec<u64>, TransactionPool) {
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, TransactionPoolEvictionPolicy::Reject, "");
         let mut rng = thread_rng();
         transactions.shuffle(&mut rng);
         for tx in transactions {
@@ -446,7 +701,8 @@ mod tests {
             })
             .collect::<Vec<_>>();
 
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, TransactionPoolEvictionPolicy::Reject, "");
         let mut rng = thread_rng();
         transactions.shuffle(&mut rng);
         for tx in transactions.clone() {
@@ -496,6 +752,43 @@ mod tests {
         assert_eq!(nonces, vec![1, 21, 3, 23, 25, 27, 29, 31]);
     }
 
+    /// Postpone the transaction with nonce 2 of one signer. It and the later nonces stay in the
+    /// pool and are not returned again by the same iterator, while the other signer is drained.
+    #[test]
+    fn test_pool_iterator_postpone() {
+        let mut transactions = generate_transactions("alice.unc", "alice.unc", 1, 4);
+        transactions.extend(generate_transactions("bob.unc", "bob.unc", 1, 3));
+        let alice_txs = transactions[1..4].to_vec();
+        let (_, mut pool) = process_txs_to_nonces(transactions, 0);
+        let size = pool.transaction_size();
+
+        let mut res = vec![];
+        let mut pool_iter = pool.pool_iterator();
+        while let Some(iter) = pool_iter.next() {
+            while let Some(tx) = iter.next() {
+                if tx.transaction.signer_id.as_str() == "alice.unc" && tx.transaction.nonce == 2 {
+                    iter.postpone(tx);
+                    break;
+                }
+                res.push(tx);
+            }
+        }
+        drop(pool_iter);
+        let mut nonces: Vec<_> = res
+            .iter()
+            .map(|tx| (tx.transaction.signer_id.as_str(), tx.transaction.nonce))
+            .collect();
+        nonces.sort();
+        assert_eq!(nonces, vec![("alice.unc", 1), ("bob.unc", 1), ("bob.unc", 2), ("bob.unc", 3)]);
+        assert_eq!(pool.len(), 3);
+        assert_eq!(pool.transaction_size(), size - res.iter().map(|tx| tx.get_size()).sum::<u64>());
+
+        let txs = prepare_transactions(&mut pool, 10);
+        assert_eq!(txs, alice_txs);
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.transaction_size(), 0);
+    }
+
     /// Test pool iterator updates unique transactions.
     #[test]
     fn test_pool_iterator_removes_unique() {
@@ -561,7 +854,8 @@ mod tests {
 
     #[test]
     fn test_transaction_pool_size() {
-        let mut pool = TransactionPool::new(TEST_SEED, None, "");
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, TransactionPoolEvictionPolicy::Reject, "");
         let transactions = generate_transactions("alice.unc", "alice.unc", 1, 100);
         let mut total_transaction_size = 0;
         // Adding transactions increases the size.
@@ -585,7 +879,12 @@ mod tests {
         // Each transaction is at least 1 byte in size, so the last transaction will not fit.
         let pool_size_limit =
             transactions.iter().map(|tx| tx.get_size()).sum::<u64>().checked_sub(1).unwrap();
-        let mut pool = TransactionPool::new(TEST_SEED, Some(pool_size_limit), "");
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            Some(pool_size_limit),
+            TransactionPoolEvictionPolicy::Reject,
+            "",
+        );
         for (i, tx) in transactions.iter().cloned().enumerate() {
             if i + 1 < transactions.len() {
                 assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
@@ -594,4 +893,161 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_clear() {
+        let transactions = generate_transactions("alice.unc", "alice.unc", 1, 10);
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, TransactionPoolEvictionPolicy::Reject, "");
+        for tx in transactions.iter().cloned() {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+        assert_eq!(pool.clear(), transactions.len());
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.transaction_size(), 0);
+        // The cleared transactions can be added again.
+        for tx in transactions {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+        }
+    }
+
+    fn generate_call(signer_id: &str, nonce: u64, gas: Gas) -> SignedTransaction {
+        let signer_id: AccountId = signer_id.parse().unwrap();
+        let signer =
+            InMemorySigner::from_seed(signer_id.clone(), KeyType::ED25519, signer_id.as_ref());
+        SignedTransaction::call(
+            nonce,
+            signer_id,
+            "bob.unc".parse().unwrap(),
+            &signer,
+            0,
+            "method".to_string(),
+            vec![],
+            gas,
+            CryptoHash::default(),
+        )
+    }
+
+    /// A transaction with the same nonce and at least the same priority replaces the pending one,
+    /// while a transaction with a lower priority is rejected.
+    #[test]
+    fn test_replace_by_nonce() {
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, TransactionPoolEvictionPolicy::Reject, "");
+        let original = generate_call("alice.unc", 1, 100);
+        assert_eq!(pool.insert_transaction(original.clone()), InsertTransactionResult::Success);
+        assert_eq!(
+            pool.insert_transaction(generate_call("alice.unc", 1, 50)),
+            InsertTransactionResult::Duplicate
+        );
+        assert_eq!(pool.insert_transaction(original), InsertTransactionResult::Duplicate);
+        let replacement = generate_call("alice.unc", 1, 200);
+        assert_eq!(pool.insert_transaction(replacement.clone()), InsertTransactionResult::Replaced);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.transaction_size(), replacement.get_size());
+        assert_eq!(prepare_transactions(&mut pool, 10), vec![replacement]);
+    }
+
+    /// Transfers have no prepaid gas, so they can be replaced by another transfer with the same
+    /// nonce.
+    #[test]
+    fn test_replace_transfer() {
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, TransactionPoolEvictionPolicy::Reject, "");
+        let signer_id: AccountId = "alice.unc".parse().unwrap();
+        let signer = InMemorySigner::from_seed(signer_id.clone(), KeyType::ED25519, "alice.unc");
+        let transfer = |deposit| {
+            SignedTransaction::send_money(
+                1,
+                signer_id.clone(),
+                "bob.unc".parse().unwrap(),
+                &signer,
+                deposit,
+                CryptoHash::default(),
+            )
+        };
+        assert_eq!(pool.insert_transaction(transfer(100)), InsertTransactionResult::Success);
+        let replacement = transfer(200);
+        assert_eq!(pool.insert_transaction(replacement.clone()), InsertTransactionResult::Replaced);
+        assert_eq!(prepare_transactions(&mut pool, 10), vec![replacement]);
+    }
+
+    /// An empty transaction sent by the signer to itself cancels the pending transaction with the
+    /// same nonce regardless of its priority.
+    #[test]
+    fn test_cancellation() {
+        let mut pool =
+            TransactionPool::new(TEST_SEED, None, TransactionPoolEvictionPolicy::Reject, "");
+        assert_eq!(
+            pool.insert_transaction(generate_call("alice.unc", 1, 100)),
+            InsertTransactionResult::Success
+        );
+        let signer_id: AccountId = "alice.unc".parse().unwrap();
+        let signer = InMemorySigner::from_seed(signer_id.clone(), KeyType::ED25519, "alice.unc");
+        let cancellation = SignedTransaction::from_actions(
+            1,
+            signer_id.clone(),
+            signer_id.clone(),
+            &signer,
+            vec![],
+            CryptoHash::default(),
+        );
+        assert!(is_cancellation(&cancellation));
+        assert_eq!(pool.insert_transaction(cancellation), InsertTransactionResult::Cancelled);
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.transaction_size(), 0);
+        assert!(prepare_transactions(&mut pool, 10).is_empty());
+
+        let pending = generate_call("alice.unc", 2, 100);
+        assert_eq!(pool.insert_transaction(pending.clone()), InsertTransactionResult::Success);
+        let cancelled = pool.cancel_transaction(&signer_id, &signer.public_key, 2);
+        assert_eq!(cancelled, Some(pending));
+        assert_eq!(pool.cancel_transaction(&signer_id, &signer.public_key, 2), None);
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.transaction_size(), 0);
+    }
+
+    /// With the `LowestPriority` policy a full pool evicts transactions with a lower priority
+    /// than the new one, and rejects the new transaction otherwise.
+    #[test]
+    fn test_evict_lowest_priority() {
+        let low = generate_call("alice.unc", 1, 10);
+        let high = generate_call("bob.unc", 1, 1000);
+        let limit = low.get_size() + high.get_size();
+        let mut pool = TransactionPool::new(
+            TEST_SEED,
+            Some(limit),
+            TransactionPoolEvictionPolicy::LowestPriority,
+            "",
+        );
+        assert_eq!(pool.insert_transaction(low), InsertTransactionResult::Success);
+        assert_eq!(pool.insert_transaction(high.clone()), InsertTransactionResult::Success);
+
+        // Not valuable enough to evict anything.
+        let lowest = generate_call("carol.unc", 1, 5);
+        assert_eq!(pool.insert_transaction(lowest), InsertTransactionResult::NoSpaceLeft);
+
+        let medium = generate_call("carol.unc", 1, 100);
+        assert_eq!(pool.insert_transaction(medium.clone()), InsertTransactionResult::Success);
+        assert_eq!(pool.len(), 2);
+        let mut txs = prepare_transactions(&mut pool, 10);
+        txs.sort_by_key(|tx| transaction_priority(tx));
+        assert_eq!(txs, vec![medium, high]);
+    }
+
+    /// With the `Oldest` policy a full pool evicts the transactions inserted first.
+    #[test]
+    fn test_evict_oldest() {
+        let transactions = generate_transactions("alice.unc", "alice.unc", 1, 10);
+        let limit = transactions.iter().map(|tx| tx.get_size()).max().unwrap() * 5;
+        let mut pool =
+            TransactionPool::new(TEST_SEED, Some(limit), TransactionPoolEvictionPolicy::Oldest, "");
+        for tx in transactions.iter().cloned() {
+            assert_eq!(pool.insert_transaction(tx), InsertTransactionResult::Success);
+            assert!(pool.transaction_size() <= limit);
+        }
+        let nonces: Vec<u64> =
+            prepare_transactions(&mut pool, 10).iter().map(|tx| tx.transaction.nonce).collect();
+        assert_eq!(nonces, (6..=10).collect::<Vec<u64>>());
+    }
 }
@@ -1,5 +1,5 @@
 use once_cell::sync::Lazy;
-use unc_o11y::metrics::IntGaugeVec;
+use unc_o11y::metrics::{IntCounterVec, IntGaugeVec};
 
 pub static TRANSACTION_POOL_COUNT: Lazy<IntGaugeVec> = Lazy::new(|| {
     unc_o11y::metrics::try_create_int_gauge_vec(
@@ -18,3 +18,12 @@ pub static TRANSACTION_POOL_SIZE: Lazy<IntGaugeVec> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static TRANSACTION_POOL_EVICTIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    unc_o11y::metrics::try_create_int_counter_vec(
+        "unc_transaction_pool_evictions_total",
+        "Number of transactions removed from a given shard pool before inclusion, by reason",
+        &["shard_id", "reason"],
+    )
+    .unwrap()
+});
@@ -23,6 +23,9 @@ pub struct TransactionGroup {
     pub(crate) removed_transaction_hashes: Vec<CryptoHash>,
     /// Total size of transactions that were pulled from the group using `.next()`.
     pub(crate) removed_transaction_size: u64,
+    /// Transactions that were put back using `.postpone()`. They are kept in the pool, but are
+    /// not returned again by this iteration.
+    pub(crate) postponed_transactions: Vec<SignedTransaction>,
 }
 
 impl TransactionGroup {
@@ -37,4 +40,18 @@ impl TransactionGroup {
             None
         }
     }
+
+    /// Puts back a transaction pulled using `.next()`, which can't be included yet, e.g. because
+    /// its receiver shard is congested. The transaction and all the remaining ones of the group
+    /// stay in the pool, but the group is exhausted for this iteration since the later nonces
+    /// depend on this transaction.
+    pub fn postpone(&mut self, tx: SignedTransaction) {
+        let hash = tx.get_hash();
+        if let Some(pos) = self.removed_transaction_hashes.iter().rposition(|h| *h == hash) {
+            self.removed_transaction_hashes.remove(pos);
+            self.removed_transaction_size -= tx.get_size();
+        }
+        self.transactions.push(tx);
+        self.postponed_transactions.append(&mut self.transactions);
+    }
 }
@@ -37,6 +37,13 @@ pub struct TelemetryEvent {
     content: serde_json::Value,
 }
 
+/// Replaces the endpoints the telemetry is sent to.
+#[derive(actix::Message, Debug)]
+#[rtype(result = "()")]
+pub struct UpdateTelemetryEndpoints {
+    pub endpoints: Vec<String>,
+}
+
 pub struct TelemetryActor {
     config: TelemetryConfig,
     client: Client,
@@ -118,6 +125,17 @@ impl Handler<WithSpanContext<TelemetryEvent>> for TelemetryActor {
     }
 }
 
+impl Handler<WithSpanContext<UpdateTelemetryEndpoints>> for TelemetryActor {
+    type Result = ();
+
+    #[perf]
+    fn handle(&mut self, msg: WithSpanContext<UpdateTelemetryEndpoints>, _ctx: &mut Context<Self>) {
+        let (_span, msg) = handler_debug_span!(target: "telemetry", msg);
+        tracing::info!(target: "telemetry", endpoints = ?msg.endpoints, "Updated telemetry endpoints");
+        self.config.endpoints = msg.endpoints;
+    }
+}
+
 /// Send telemetry event to all the endpoints.
 pub fn telemetry(telemetry: &Addr<TelemetryActor>, content: serde_json::Value) {
     telemetry.do_send(TelemetryEvent { content }.with_span_context());
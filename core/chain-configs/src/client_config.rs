@@ -1,12 +1,15 @@
 //! Chain Client Configuration
 use crate::ExternalStorageLocation::GCS;
-use crate::MutableConfigValue;
+use crate::{ConfigUpdateLog, MutableConfigValue};
 use std::cmp::{max, min};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::time::Duration;
-use unc_primitives::types::{BlockHeight, BlockHeightDelta, Gas, NumBlocks, NumSeats, ShardId};
+use unc_primitives::types::{
+    AccountId, BlockHeight, BlockHeightDelta, Gas, NumBlocks, NumSeats, ShardId,
+};
 use unc_primitives::version::Version;
 
 pub const TEST_STATE_SYNC_TIMEOUT: u64 = 5;
@@ -61,6 +64,65 @@ impl GCConfig {
     }
 }
 
+/// Accounts whose history is kept in cold storage by a partial archival node.
+///
+/// Every pattern is either an exact account id or a prefix terminated by `*`,
+/// e.g. `miner-*`. In the config file it is written as a plain list of strings.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
+pub struct RetainedAccounts {
+    patterns: Vec<String>,
+}
+
+impl RetainedAccounts {
+    /// Returns whether history of `account_id` is retained.
+    pub fn contains(&self, account_id: &AccountId) -> bool {
+        self.patterns.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => account_id.as_str().starts_with(prefix),
+            None => account_id.as_str() == pattern,
+        })
+    }
+
+    /// Returns the account id prefixes covered by the patterns. Exact account
+    /// ids are returned as a whole, so they also cover ids extending them.
+    pub fn account_prefixes(&self) -> impl Iterator<Item = &[u8]> {
+        self.patterns.iter().map(|pattern| pattern.strip_suffix('*').unwrap_or(pattern).as_bytes())
+    }
+}
+
+impl TryFrom<Vec<String>> for RetainedAccounts {
+    type Error = String;
+
+    fn try_from(patterns: Vec<String>) -> Result<Self, Self::Error> {
+        for pattern in &patterns {
+            match pattern.strip_suffix('*') {
+                Some("") => {
+                    return Err(
+                        "pattern `*` retains every account, use a full archival node instead"
+                            .to_string(),
+                    )
+                }
+                Some(prefix) if prefix.contains('*') => {
+                    return Err(format!("pattern {pattern} may only contain `*` at the end"))
+                }
+                Some(_) => {}
+                None => {
+                    pattern.parse::<AccountId>().map_err(|err| {
+                        format!("pattern {pattern} is not a valid account id: {err}")
+                    })?;
+                }
+            }
+        }
+        Ok(Self { patterns })
+    }
+}
+
+impl From<RetainedAccounts> for Vec<String> {
+    fn from(retained_accounts: RetainedAccounts) -> Self {
+        retained_accounts.patterns
+    }
+}
+
 fn default_num_concurrent_requests() -> u32 {
     DEFAULT_STATE_SYNC_NUM_CONCURRENT_REQUESTS_EXTERNAL
 }
@@ -97,6 +159,20 @@ pub enum ExternalStorageLocation {
     GCS {
         bucket: String,
     },
+    /// A plain HTTP(S) server or CDN. State parts are read with GET requests
+    /// and dumped with PUT requests, which also works with WebDAV servers.
+    Http {
+        /// URL under which the state parts are located, e.g. `https://example.com/state-parts`.
+        base_url: String,
+        /// Extra headers sent with every request, e.g. `Authorization`.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+        /// Accept state parts that don't have a `.sha256` checksum file next
+        /// to them, e.g. parts uploaded by other tools. By default a part
+        /// without a checksum is treated as unavailable.
+        #[serde(default)]
+        allow_missing_checksums: bool,
+    },
 }
 
 /// Configures how to dump state to external storage.
@@ -115,6 +191,83 @@ pub struct DumpConfig {
     /// Location of a json file with credentials allowing write access to the bucket.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub credentials_file: Option<PathBuf>,
+    /// Format of the dumped state parts.
+    #[serde(default)]
+    pub part_format: StatePartFormat,
+}
+
+/// How state parts are written to the external storage.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatePartFormat {
+    /// Borsh-serialized trie nodes, as returned by `obtain_state_part`.
+    /// Readable by nodes that don't know about `Container`.
+    #[default]
+    Raw,
+    /// A `StatePartContainer`: a versioned header identifying the part and
+    /// the hash of its content, followed by the zstd-compressed raw part.
+    Container,
+}
+
+/// A source of state parts during state sync.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StateSyncSource {
+    /// The peers that track the shard.
+    Peers,
+    /// The external storage configured in `HybridSyncConfig::external`.
+    ExternalStorage,
+}
+
+impl StateSyncSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Peers => "peers",
+            Self::ExternalStorage => "external",
+        }
+    }
+}
+
+fn default_hybrid_sync_sources() -> Vec<StateSyncSource> {
+    vec![StateSyncSource::ExternalStorage, StateSyncSource::Peers]
+}
+
+fn default_hybrid_sync_attempts_per_source() -> u32 {
+    1
+}
+
+/// Configures state sync that fetches every part from several sources.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+pub struct HybridSyncConfig {
+    /// External storage to fetch state parts from.
+    pub external: ExternalStorageConfig,
+    /// Sources of state parts ranked by preference. Every part is first requested from the
+    /// first source and falls back to the next one after `attempts_per_source` failed attempts.
+    /// After the last source the order starts over.
+    #[serde(default = "default_hybrid_sync_sources")]
+    pub sources: Vec<StateSyncSource>,
+    /// Number of failed or timed out attempts to fetch a part from a source before falling
+    /// back to the next source.
+    #[serde(default = "default_hybrid_sync_attempts_per_source")]
+    pub attempts_per_source: u32,
+}
+
+impl HybridSyncConfig {
+    /// Returns the source to use for the attempt number `attempt` (zero-based) to fetch a part.
+    pub fn source_for_attempt(&self, attempt: u32) -> StateSyncSource {
+        if self.sources.is_empty() {
+            return StateSyncSource::Peers;
+        }
+        let attempts_per_source = self.attempts_per_source.max(1);
+        self.sources[(attempt / attempts_per_source) as usize % self.sources.len()]
+    }
+
+    /// Returns the first attempt starting from `attempt` that uses the given source, or `None`
+    /// if the source isn't ranked at all.
+    pub fn next_attempt_with_source(&self, attempt: u32, source: StateSyncSource) -> Option<u32> {
+        let num_attempts = self.sources.len() as u32 * self.attempts_per_source.max(1);
+        (attempt..attempt.saturating_add(num_attempts))
+            .find(|attempt| self.source_for_attempt(*attempt) == source)
+    }
 }
 
 /// Configures how to fetch state parts during state sync.
@@ -124,6 +277,9 @@ pub enum SyncConfig {
     Peers,
     /// Expects parts to be available in external storage.
     ExternalStorage(ExternalStorageConfig),
+    /// Fetches every part from both external storage and the peers, falling back from one to
+    /// the other if a part can't be fetched.
+    Hybrid(HybridSyncConfig),
 }
 
 impl Default for SyncConfig {
@@ -147,6 +303,11 @@ impl SyncConfig {
     fn is_default(&self) -> bool {
         matches!(self, Self::Peers)
     }
+
+    /// Whether state parts may be requested from the peers.
+    pub fn uses_peers(&self) -> bool {
+        matches!(self, Self::Peers | Self::Hybrid(_))
+    }
 }
 
 // A handle that allows the main process to interrupt resharding if needed.
@@ -295,6 +456,20 @@ pub fn default_produce_chunk_add_transactions_time_limit() -> Option<Duration> {
     Some(Duration::from_millis(200))
 }
 
+/// What the transaction pool does when a new transaction doesn't fit into its size limit.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionPoolEvictionPolicy {
+    /// Reject the new transaction.
+    #[default]
+    Reject,
+    /// Evict transactions with a lower priority than the new one, lowest priority first.
+    /// If that doesn't free enough space, the new transaction is rejected.
+    LowestPriority,
+    /// Evict the oldest transactions in the pool first.
+    Oldest,
+}
+
 /// ClientConfig where some fields can be updated at runtime.
 #[derive(Clone, serde::Serialize)]
 pub struct ClientConfig {
@@ -358,6 +533,8 @@ pub struct ClientConfig {
     pub block_header_fetch_horizon: BlockHeightDelta,
     /// Garbage collection configuration.
     pub gc: GCConfig,
+    /// Shards the node is configured to track, see `tracked_shards` in config.json.
+    pub tracked_shards: Vec<ShardId>,
     /// Not clear old data, set `true` for archive nodes.
     pub archive: bool,
     /// save_trie_changes should be set to true iff
@@ -365,6 +542,9 @@ pub struct ClientConfig {
     /// - archive is true, cold_store is configured and migration to split_storage is finished - node
     /// working in split storage mode needs trie changes in order to do garbage collection on hot.
     pub save_trie_changes: bool,
+    /// If set, cold storage keeps history only for these accounts and queries
+    /// for other accounts at blocks no longer in hot storage are rejected.
+    pub archive_retained_accounts: Option<RetainedAccounts>,
     /// Number of threads for ViewClientActor pool.
     pub view_client_threads: usize,
     /// Number of seconds between state requests for view client.
@@ -391,6 +571,8 @@ pub struct ClientConfig {
     /// Limit of the size of per-shard transaction pool measured in bytes. If not set, the size
     /// will be unbounded.
     pub transaction_pool_size_limit: Option<u64>,
+    /// What to do when a new transaction doesn't fit into `transaction_pool_size_limit`.
+    pub transaction_pool_eviction_policy: TransactionPoolEvictionPolicy,
     // Allows more detailed logging, for example a list of orphaned blocks.
     pub enable_multiline_logging: bool,
     // Configuration for resharding.
@@ -403,6 +585,8 @@ pub struct ClientConfig {
     /// some limit is reached. This time limit ensures that adding transactions won't take
     /// longer than the specified duration, which helps to produce the chunk quickly.
     pub produce_chunk_add_transactions_time_limit: MutableConfigValue<Option<Duration>>,
+    /// Recent changes of the configs reloaded while the node is running.
+    pub config_updates: ConfigUpdateLog,
 }
 
 impl ClientConfig {
@@ -457,8 +641,10 @@ impl ClientConfig {
             doosmslug_step_period: Duration::from_millis(100),
             block_header_fetch_horizon: 50,
             gc: GCConfig { gc_blocks_limit: 100, ..GCConfig::default() },
+            tracked_shards: vec![],
             archive,
             save_trie_changes,
+            archive_retained_accounts: None,
             log_summary_style: LogSummaryStyle::Colored,
             view_client_threads: 1,
             view_client_throttle_period: Duration::from_secs(1),
@@ -471,6 +657,7 @@ impl ClientConfig {
             state_sync_enabled,
             state_sync: StateSyncConfig::default(),
             transaction_pool_size_limit: None,
+            transaction_pool_eviction_policy: TransactionPoolEvictionPolicy::default(),
             enable_multiline_logging: false,
             resharding_config: MutableConfigValue::new(
                 ReshardingConfig::default(),
@@ -481,6 +668,7 @@ impl ClientConfig {
                 default_produce_chunk_add_transactions_time_limit(),
                 "produce_chunk_add_transactions_time_limit",
             ),
+            config_updates: ConfigUpdateLog::default(),
         }
     }
 }
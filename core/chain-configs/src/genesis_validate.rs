@@ -4,7 +4,10 @@ use std::collections::{HashMap, HashSet};
 use unc_config_utils::{ValidationError, ValidationErrors};
 use unc_crypto::key_conversion::is_valid_staking_key;
 use unc_primitives::state_record::StateRecord;
-use unc_primitives::types::AccountId;
+use unc_primitives::types::{AccountId, Power};
+
+/// Account holding the RSA2048 chips which were registered but not claimed by any miner yet.
+pub const RSA2048_ROOT_ACCOUNT: &str = "unc";
 
 /// Validate genesis config and records. Returns ValidationError if semantic checks of genesis failed.
 pub fn validate_genesis(genesis: &Genesis) -> Result<(), ValidationError> {
@@ -31,6 +34,10 @@ struct GenesisValidator<'a> {
     account_ids: HashSet<AccountId>,
     access_key_account_ids: HashSet<AccountId>,
     contract_account_ids: HashSet<AccountId>,
+    account_powers: HashMap<AccountId, Power>,
+    /// Total power of the RSA2048 chips claimed by each account.
+    chip_powers: HashMap<AccountId, Power>,
+    rsa2048_root_account: AccountId,
     validation_errors: &'a mut ValidationErrors,
 }
 
@@ -46,6 +53,9 @@ impl<'a> GenesisValidator<'a> {
             account_ids: HashSet::new(),
             access_key_account_ids: HashSet::new(),
             contract_account_ids: HashSet::new(),
+            account_powers: HashMap::new(),
+            chip_powers: HashMap::new(),
+            rsa2048_root_account: RSA2048_ROOT_ACCOUNT.parse().unwrap(),
             validation_errors,
         }
     }
@@ -63,6 +73,7 @@ impl<'a> GenesisValidator<'a> {
                 if account.pledging() > 0 {
                     self.pledged_accounts.insert(account_id.clone(), account.pledging());
                 }
+                self.account_powers.insert(account_id.clone(), account.power());
             }
             StateRecord::AccessKey { account_id, .. } => {
                 self.access_key_account_ids.insert(account_id.clone());
@@ -75,6 +86,18 @@ impl<'a> GenesisValidator<'a> {
                 }
                 self.contract_account_ids.insert(account_id.clone());
             }
+            StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                let power = rsa_key.power().unwrap_or_else(|| {
+                    let error_message = format!(
+                        "rsa2048 key {} of account {} doesn't specify a valid power",
+                        public_key, account_id
+                    );
+                    self.validation_errors.push_genesis_semantics_error(error_message);
+                    0
+                });
+                let chip_power = self.chip_powers.entry(account_id.clone()).or_default();
+                *chip_power = chip_power.saturating_add(power);
+            }
             _ => {}
         }
     }
@@ -124,6 +147,22 @@ impl<'a> GenesisValidator<'a> {
             }
         }
 
+        for (account_id, chip_power) in &self.chip_powers {
+            let Some(account_power) = self.account_powers.get(account_id) else {
+                let error_message = format!("rsa2048 key account {} does not exist", account_id);
+                self.validation_errors.push_genesis_semantics_error(error_message);
+                continue;
+            };
+            // Chips held by the root account are not claimed, so they don't give it any power.
+            if account_id != &self.rsa2048_root_account && account_power != chip_power {
+                let error_message = format!(
+                    "account {} has power {} which doesn't match the power {} of its rsa2048 keys",
+                    account_id, account_power, chip_power
+                );
+                self.validation_errors.push_genesis_semantics_error(error_message)
+            }
+        }
+
         for account_id in &self.contract_account_ids {
             if !self.account_ids.contains(account_id) {
                 let error_message = format!("contract account {} does not exist,", account_id);
@@ -202,6 +241,7 @@ mod test {
     use crate::GenesisRecords;
     use unc_crypto::{KeyType, PublicKey};
     use unc_primitives::account::{AccessKey, Account};
+    use unc_primitives::action::RegisterRsa2048KeysAction;
     use unc_primitives::types::AccountInfo;
 
     const VALID_ED25519_RISTRETTO_KEY: &str = "ed25519:KuTCtARNzxZQ3YvXDeLjx83FDqxv2SdQTSbiq876zR7";
@@ -300,6 +340,60 @@ mod test {
         validate_genesis(genesis).unwrap();
     }
 
+    fn create_rsa2048_keys(account_id: &str, args: &str) -> StateRecord {
+        let public_key = PublicKey::empty(KeyType::RSA2048);
+        StateRecord::Rsa2048Keys {
+            account_id: account_id.parse().unwrap(),
+            public_key: public_key.clone(),
+            rsa_key: RegisterRsa2048KeysAction {
+                public_key,
+                operation_type: 0,
+                args: args.as_bytes().to_vec(),
+            },
+        }
+    }
+
+    fn validate_with_rsa2048_keys(rsa2048_keys: StateRecord) {
+        let mut config = GenesisConfig::default();
+        config.validators = vec![AccountInfo {
+            account_id: "test".parse().unwrap(),
+            public_key: VALID_ED25519_RISTRETTO_KEY.parse().unwrap(),
+            power: 5,
+            pledging: 10,
+        }];
+        config.total_supply = 110;
+        config.epoch_length = 10;
+        let records = GenesisRecords(vec![
+            StateRecord::Account { account_id: "test".parse().unwrap(), account: create_account() },
+            rsa2048_keys,
+        ]);
+        let genesis = &Genesis::new(config, records).unwrap();
+        validate_genesis(genesis).unwrap();
+    }
+
+    #[test]
+    fn test_rsa2048_keys_power_match() {
+        validate_with_rsa2048_keys(create_rsa2048_keys("test", r#"{"power":"5"}"#));
+    }
+
+    #[test]
+    #[should_panic(expected = "account test has power 5 which doesn't match the power 7")]
+    fn test_rsa2048_keys_power_not_match() {
+        validate_with_rsa2048_keys(create_rsa2048_keys("test", r#"{"power":"7"}"#));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't specify a valid power")]
+    fn test_rsa2048_keys_invalid_power() {
+        validate_with_rsa2048_keys(create_rsa2048_keys("test", r#"{"power":5}"#));
+    }
+
+    #[test]
+    #[should_panic(expected = "rsa2048 key account test1 does not exist")]
+    fn test_rsa2048_keys_with_nonexistent_account() {
+        validate_with_rsa2048_keys(create_rsa2048_keys("test1", r#"{"power":"5"}"#));
+    }
+
     #[test]
     #[should_panic(expected = "account test has more than one contract deployed")]
     fn test_more_than_one_contract() {
@@ -14,8 +14,9 @@ pub use client_config::{
     default_sync_height_threshold, default_sync_step_period, default_transaction_pool_size_limit,
     default_trie_viewer_state_size_limit, default_tx_routing_height_horizon,
     default_view_client_threads, default_view_client_throttle_period, ClientConfig, DumpConfig,
-    ExternalStorageConfig, ExternalStorageLocation, GCConfig, LogSummaryStyle, ReshardingConfig,
-    ReshardingHandle, StateSyncConfig, SyncConfig, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
+    ExternalStorageConfig, ExternalStorageLocation, GCConfig, HybridSyncConfig, LogSummaryStyle,
+    ReshardingConfig, ReshardingHandle, RetainedAccounts, StatePartFormat, StateSyncConfig,
+    StateSyncSource, SyncConfig, TransactionPoolEvictionPolicy, DEFAULT_GC_NUM_EPOCHS_TO_KEEP,
     DEFAULT_STATE_SYNC_NUM_CONCURRENT_REQUESTS_EXTERNAL,
     DEFAULT_STATE_SYNC_NUM_CONCURRENT_REQUESTS_ON_CATCHUP_EXTERNAL, MIN_GC_NUM_EPOCHS_TO_KEEP,
     TEST_STATE_SYNC_TIMEOUT,
@@ -24,4 +25,8 @@ pub use genesis_config::{
     get_initial_supply, stream_records_from_file, Genesis, GenesisChangeConfig, GenesisConfig,
     GenesisContents, GenesisRecords, GenesisValidationMode, ProtocolConfig, ProtocolConfigView,
 };
-pub use updateable_config::{MutableConfigValue, UpdateableClientConfig};
+pub use updateable_config::{
+    config_diff, ConfigFieldChange, ConfigUpdate, ConfigUpdateLog, MutableConfigValue,
+    UpdateableClientConfig, UpdateableNetworkConfig, UpdateableRpcConfig,
+    UpdateableTelemetryConfig,
+};
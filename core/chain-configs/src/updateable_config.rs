@@ -1,9 +1,14 @@
 use serde::{Deserialize, Serialize, Serializer};
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use std::{fmt::Debug, time::Duration};
-use unc_primitives::types::BlockHeight;
+use unc_primitives::static_clock::StaticClock;
+use unc_primitives::types::{BlockHeight, ShardId};
 
-use crate::ReshardingConfig;
+use crate::{GCConfig, ReshardingConfig};
+
+/// Number of config updates kept in `ConfigUpdateLog`.
+const CONFIG_UPDATE_LOG_SIZE: usize = 16;
 
 /// A wrapper for a config value that can be updated while the node is running.
 /// When initializing sub-objects (e.g. `ShardsManager`), please make sure to
@@ -34,38 +39,38 @@ impl<T: Serialize> Serialize for MutableConfigValue<T> {
     }
 }
 
-impl<T: Copy + PartialEq + Debug> MutableConfigValue<T> {
+impl<T: Clone + PartialEq + Debug> MutableConfigValue<T> {
     /// Initializes a value.
     /// `field_name` is needed to export the config value as a prometheus metric.
     pub fn new(val: T, field_name: &str) -> Self {
         let res = Self {
-            value: Arc::new(Mutex::new(val)),
+            value: Arc::new(Mutex::new(val.clone())),
             field_name: field_name.to_string(),
             #[cfg(feature = "metrics")]
             last_update: unc_primitives::static_clock::StaticClock::utc(),
         };
-        res.set_metric_value(val, 1);
+        res.set_metric_value(&val, 1);
         res
     }
 
     pub fn get(&self) -> T {
-        *self.value.lock().unwrap()
+        self.value.lock().unwrap().clone()
     }
 
     pub fn update(&self, val: T) {
         let mut lock = self.value.lock().unwrap();
         if *lock != val {
             tracing::info!(target: "config", "Updated config field '{}' from {:?} to {:?}", self.field_name, *lock, val);
-            self.set_metric_value(*lock, 0);
+            self.set_metric_value(&*lock, 0);
+            self.set_metric_value(&val, 1);
             *lock = val;
-            self.set_metric_value(val, 1);
         } else {
             tracing::info!(target: "config", "Mutable config field '{}' remains the same: {:?}", self.field_name, val);
         }
     }
 
     #[cfg(feature = "metrics")]
-    fn set_metric_value(&self, value: T, metric_value: i64) {
+    fn set_metric_value(&self, value: &T, metric_value: i64) {
         // Use field_name as a label to tell different mutable config values apart.
         // Use timestamp as a label to give some idea to the node operator (or
         // people helping them debug their node) when exactly and what values
@@ -82,10 +87,10 @@ impl<T: Copy + PartialEq + Debug> MutableConfigValue<T> {
     }
 
     #[cfg(not(feature = "metrics"))]
-    fn set_metric_value(&self, _value: T, _metric_value: i64) {}
+    fn set_metric_value(&self, _value: &T, _metric_value: i64) {}
 }
 
-#[derive(Default, Clone, Serialize, Deserialize)]
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 /// A subset of Config that can be updated white the node is running.
 pub struct UpdateableClientConfig {
     /// Graceful shutdown at expected block height.
@@ -96,4 +101,236 @@ pub struct UpdateableClientConfig {
 
     /// Time limit for adding transactions in produce_chunk()
     pub produce_chunk_add_transactions_time_limit: Option<Duration>,
+
+    /// Garbage collection configuration.
+    pub gc: GCConfig,
+
+    /// Shards the node is configured to track.
+    pub tracked_shards: Vec<ShardId>,
+
+    /// Limit of the size of per-shard transaction pool measured in bytes.
+    pub transaction_pool_size_limit: Option<u64>,
+}
+
+impl UpdateableClientConfig {
+    /// Checks the values which would be rejected by the config validation at startup.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.gc.gc_blocks_limit == 0
+            || self.gc.gc_fork_clean_step == 0
+            || self.gc.gc_num_epochs_to_keep == 0
+        {
+            return Err(format!("gc config values should all be greater than 0: {:?}", self.gc));
+        }
+        if self.transaction_pool_size_limit == Some(0) {
+            return Err("transaction_pool_size_limit should be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A subset of the network config that can be updated while the node is running.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateableNetworkConfig {
+    /// Addresses which are not allowed to connect, as `IP` or `IP:port`.
+    pub blacklist: Vec<String>,
+    /// Maximum number of active peers.
+    pub max_num_peers: u32,
+}
+
+impl UpdateableNetworkConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_num_peers == 0 {
+            return Err("max_num_peers should be greater than 0".to_string());
+        }
+        for entry in &self.blacklist {
+            if entry.parse::<std::net::IpAddr>().is_err()
+                && entry.parse::<std::net::SocketAddr>().is_err()
+            {
+                return Err(format!("invalid blacklist entry {:?}", entry));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A subset of the RPC config that can be updated while the node is running.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateableRpcConfig {
+    /// Maximum byte size of the json payload.
+    pub json_payload_max_size: usize,
+}
+
+impl UpdateableRpcConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.json_payload_max_size == 0 {
+            return Err("json_payload_max_size should be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A subset of the telemetry config that can be updated while the node is running.
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateableTelemetryConfig {
+    pub endpoints: Vec<String>,
+}
+
+impl UpdateableTelemetryConfig {
+    /// Checks that every endpoint is an HTTP(S) URL with a host.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.endpoints.iter().all(|endpoint| is_http_url(endpoint)) {
+            return Err(format!(
+                "all telemetry endpoints must be valid URLs: {:?}",
+                self.endpoints
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_http_url(url: &str) -> bool {
+    let Some(rest) = url.strip_prefix("http://").or_else(|| url.strip_prefix("https://")) else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    !host.is_empty() && !url.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// A single field changed by a config reload.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ConfigFieldChange {
+    pub field: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+/// Lists the top-level fields which differ between the two configs.
+/// Field names are prefixed with `section`, e.g. `client_config.gc`.
+pub fn config_diff<T: Serialize>(section: &str, old: &T, new: &T) -> Vec<ConfigFieldChange> {
+    let old = serde_json::to_value(old).unwrap_or_default();
+    let new = serde_json::to_value(new).unwrap_or_default();
+    match (old, new) {
+        (serde_json::Value::Object(old), serde_json::Value::Object(mut new)) => {
+            let mut changes: Vec<_> = old
+                .into_iter()
+                .filter_map(|(field, old)| {
+                    let new = new.remove(&field).unwrap_or_default();
+                    (old != new).then(|| ConfigFieldChange {
+                        field: format!("{}.{}", section, field),
+                        old,
+                        new,
+                    })
+                })
+                .collect();
+            changes.extend(new.into_iter().map(|(field, new)| ConfigFieldChange {
+                field: format!("{}.{}", section, field),
+                old: serde_json::Value::Null,
+                new,
+            }));
+            changes
+        }
+        (old, new) if old != new => {
+            vec![ConfigFieldChange { field: section.to_string(), old, new }]
+        }
+        _ => vec![],
+    }
+}
+
+/// Config changes applied by a single reload.
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigUpdate {
+    pub time: chrono::DateTime<chrono::Utc>,
+    pub changes: Vec<ConfigFieldChange>,
+}
+
+/// Recent config updates applied while the node is running.
+/// Clones share the log, so it can be updated by the components reloading
+/// their configs and read through `ClientConfig`.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigUpdateLog(Arc<Mutex<VecDeque<ConfigUpdate>>>);
+
+impl ConfigUpdateLog {
+    /// Logs and records the changes. Does nothing if there are none.
+    pub fn record(&self, changes: Vec<ConfigFieldChange>) {
+        if changes.is_empty() {
+            return;
+        }
+        for change in &changes {
+            tracing::info!(target: "config", field = %change.field, old = %change.old, new = %change.new, "Config field updated");
+        }
+        let mut log = self.0.lock().unwrap();
+        if log.len() >= CONFIG_UPDATE_LOG_SIZE {
+            log.pop_front();
+        }
+        log.push_back(ConfigUpdate { time: StaticClock::utc(), changes });
+    }
+
+    pub fn get(&self) -> Vec<ConfigUpdate> {
+        self.0.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+impl Serialize for ConfigUpdateLog {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.get().serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        config_diff, ConfigUpdateLog, UpdateableClientConfig, UpdateableTelemetryConfig,
+        CONFIG_UPDATE_LOG_SIZE,
+    };
+
+    #[test]
+    fn test_config_diff() {
+        let old = UpdateableClientConfig::default();
+        let mut new = UpdateableClientConfig::default();
+        assert!(config_diff("client_config", &old, &new).is_empty());
+
+        new.expected_shutdown = Some(100);
+        new.gc.gc_blocks_limit = 10;
+        let changes = config_diff("client_config", &old, &new);
+        let fields: Vec<_> = changes.iter().map(|change| change.field.as_str()).collect();
+        assert_eq!(fields, vec!["client_config.expected_shutdown", "client_config.gc"]);
+        assert_eq!(changes[0].old, serde_json::Value::Null);
+        assert_eq!(changes[0].new, serde_json::json!(100));
+    }
+
+    #[test]
+    fn test_telemetry_config_validate() {
+        let config = |endpoints: &[&str]| UpdateableTelemetryConfig {
+            endpoints: endpoints.iter().map(|endpoint| endpoint.to_string()).collect(),
+        };
+        assert!(config(&[]).validate().is_ok());
+        assert!(config(&["https://explorer.unc.org/api/nodes", "http://127.0.0.1:8080"])
+            .validate()
+            .is_ok());
+        for invalid in ["", "explorer.unc.org", "ftp://explorer.unc.org", "https://", "http:// a"] {
+            assert!(
+                config(&["https://explorer.unc.org", invalid]).validate().is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_update_log() {
+        let log = ConfigUpdateLog::default();
+        log.record(vec![]);
+        assert!(log.get().is_empty());
+
+        let old = UpdateableClientConfig::default();
+        for height in 0..CONFIG_UPDATE_LOG_SIZE as u64 + 2 {
+            let new = UpdateableClientConfig { expected_shutdown: Some(height), ..old.clone() };
+            log.clone().record(config_diff("client_config", &old, &new));
+        }
+        let updates = log.get();
+        assert_eq!(updates.len(), CONFIG_UPDATE_LOG_SIZE);
+        assert_eq!(updates[0].changes[0].new, serde_json::json!(2));
+    }
 }
@@ -4,7 +4,9 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::broadcast::Sender;
-use unc_chain_configs::UpdateableClientConfig;
+use unc_chain_configs::{
+    UpdateableClientConfig, UpdateableNetworkConfig, UpdateableRpcConfig, UpdateableTelemetryConfig,
+};
 use unc_o11y::log_config::LogConfig;
 use unc_primitives::static_clock::StaticClock;
 
@@ -17,10 +19,17 @@ pub struct UpdateableConfigs {
     pub log_config: Option<LogConfig>,
     /// Contents of the `config.json` corresponding to the mutable fields of `ClientConfig`.
     pub client_config: Option<UpdateableClientConfig>,
+    /// Contents of the `config.json` corresponding to the mutable fields of the network config.
+    pub network_config: Option<UpdateableNetworkConfig>,
+    /// Contents of the `config.json` corresponding to the mutable fields of `RpcConfig`.
+    pub rpc_config: Option<UpdateableRpcConfig>,
+    /// Contents of the `config.json` corresponding to the mutable fields of `TelemetryConfig`.
+    pub telemetry_config: Option<UpdateableTelemetryConfig>,
 }
 
 /// Pushes the updates to listeners.
-#[derive(Default)]
+/// Clones push to the same listeners.
+#[derive(Default, Clone)]
 pub struct UpdateableConfigLoader {
     /// Notifies receivers about the new config values available.
     tx: Option<Sender<Result<UpdateableConfigs, Arc<UpdateableConfigLoaderError>>>>,
@@ -46,13 +55,13 @@ impl UpdateableConfigLoader {
         updateable_configs: UpdateableConfigs,
         tx: Sender<Result<UpdateableConfigs, Arc<UpdateableConfigLoaderError>>>,
     ) -> Self {
-        let mut result = Self { tx: Some(tx) };
+        let result = Self { tx: Some(tx) };
         result.reload(Ok(updateable_configs));
         result
     }
 
     pub fn reload(
-        &mut self,
+        &self,
         updateable_configs: Result<UpdateableConfigs, UpdateableConfigLoaderError>,
     ) {
         match updateable_configs {
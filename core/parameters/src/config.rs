@@ -2,8 +2,9 @@
 use crate::config_store::INITIAL_TESTNET_CONFIG;
 use crate::cost::RuntimeFeesConfig;
 use crate::parameter_table::ParameterTable;
+use num_rational::Rational32;
 use unc_account_id::AccountId;
-use unc_primitives_core::types::Balance;
+use unc_primitives_core::types::{Balance, Gas};
 use unc_primitives_core::version::PROTOCOL_VERSION;
 
 use super::parameter_table::InvalidConfigError;
@@ -23,6 +24,8 @@ pub struct RuntimeConfig {
     pub wasm_config: crate::vm::Config,
     /// Config that defines rules for account creation.
     pub account_creation_config: AccountCreationConfig,
+    /// Limits used to apply backpressure between congested shards.
+    pub congestion_control_config: CongestionControlConfig,
 }
 
 impl RuntimeConfig {
@@ -39,24 +42,25 @@ impl RuntimeConfig {
 
     pub fn test() -> Self {
         let config_store = super::config_store::RuntimeConfigStore::new(None);
-        let wasm_config =
-            crate::vm::Config::clone(&config_store.get_config(PROTOCOL_VERSION).wasm_config);
+        let runtime_config = config_store.get_config(PROTOCOL_VERSION);
         RuntimeConfig {
             fees: RuntimeFeesConfig::test(),
-            wasm_config,
+            wasm_config: crate::vm::Config::clone(&runtime_config.wasm_config),
             account_creation_config: AccountCreationConfig::default(),
+            congestion_control_config: runtime_config.congestion_control_config.clone(),
         }
     }
 
     pub fn free() -> Self {
         let config_store = super::config_store::RuntimeConfigStore::new(None);
-        let mut wasm_config =
-            crate::vm::Config::clone(&config_store.get_config(PROTOCOL_VERSION).wasm_config);
+        let runtime_config = config_store.get_config(PROTOCOL_VERSION);
+        let mut wasm_config = crate::vm::Config::clone(&runtime_config.wasm_config);
         wasm_config.make_free();
         Self {
             fees: RuntimeFeesConfig::free(),
             wasm_config,
             account_creation_config: AccountCreationConfig::default(),
+            congestion_control_config: runtime_config.congestion_control_config.clone(),
         }
     }
 
@@ -83,3 +87,22 @@ impl Default for AccountCreationConfig {
         }
     }
 }
+
+/// The structure describes the limits of the cross-shard congestion control.
+///
+/// The congestion level of a shard is a number between 0 and 1, measuring how
+/// full its delayed receipts queue is relative to the limits below.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CongestionControlConfig {
+    /// Gas attached to the delayed receipts at which a shard is fully congested.
+    pub max_congestion_incoming_gas: Gas,
+    /// Size in bytes of the delayed receipts at which a shard is fully congested.
+    pub max_congestion_memory_consumption: u64,
+    /// Gas other shards may forward to a shard in one chunk when it is not congested.
+    pub max_outgoing_gas: Gas,
+    /// Gas other shards may forward to a shard in one chunk when it is fully congested.
+    /// Non-zero, so that a congested shard keeps receiving receipts from other shards.
+    pub min_outgoing_gas: Gas,
+    /// Congestion level from which new transactions to a shard are rejected.
+    pub reject_tx_congestion_threshold: Rational32,
+}
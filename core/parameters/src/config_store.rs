@@ -40,6 +40,18 @@ static CONFIG_DIFFS: &[(ProtocolVersion, &str)] = &[
     (129, include_config!("129.yaml")),
     // Introduce ETH-implicit accounts.
     (138, include_config!("138.yaml")),
+    // Introduce cross-shard congestion control.
+    (139, include_config!("139.yaml")),
+    // Introduce yield/resume host functions.
+    (140, include_config!("140.yaml")),
+    // Introduce global contracts.
+    (141, include_config!("141.yaml")),
+    // Introduce BLS12-381 host functions.
+    (142, include_config!("142.yaml")),
+    // Enable bulk memory, multi-value and reference types Wasm proposals.
+    (143, include_config!("143.yaml")),
+    // Enable the fixed-width SIMD Wasm proposal.
+    (144, include_config!("144.yaml")),
 ];
 
 /// Testnet parameters for versions <= 29, which (incorrectly) differed from mainnet parameters
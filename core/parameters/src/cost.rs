@@ -132,6 +132,28 @@ impl ExtCostsConfig {
             ExtCosts::alt_bn128_g1_sum_element => 5_000_000_000,
             ExtCosts::validator_power_base => SAFETY_MULTIPLIER * 3_000_000_000,
             ExtCosts::validator_total_power_base => SAFETY_MULTIPLIER * 3_000_000_000,
+            ExtCosts::yield_create_base => 153_411_779_276,
+            ExtCosts::yield_create_byte => 15_643_988,
+            ExtCosts::yield_resume_base => 1_195_627_285_210,
+            ExtCosts::yield_resume_byte => 17_212_011,
+            ExtCosts::bls12381_p1_sum_base => 16_500_000_000,
+            ExtCosts::bls12381_p1_sum_element => 6_000_000_000,
+            ExtCosts::bls12381_p2_sum_base => 18_600_000_000,
+            ExtCosts::bls12381_p2_sum_element => 15_000_000_000,
+            ExtCosts::bls12381_g1_multiexp_base => 16_500_000_000,
+            ExtCosts::bls12381_g1_multiexp_element => 930_000_000_000,
+            ExtCosts::bls12381_g2_multiexp_base => 18_600_000_000,
+            ExtCosts::bls12381_g2_multiexp_element => 1_995_000_000_000,
+            ExtCosts::bls12381_map_fp_to_g1_base => 1_500_000_000,
+            ExtCosts::bls12381_map_fp_to_g1_element => 252_000_000_000,
+            ExtCosts::bls12381_map_fp2_to_g2_base => 1_500_000_000,
+            ExtCosts::bls12381_map_fp2_to_g2_element => 900_000_000_000,
+            ExtCosts::bls12381_pairing_base => 2_130_000_000_000,
+            ExtCosts::bls12381_pairing_element => 2_130_000_000_000,
+            ExtCosts::bls12381_p1_decompress_base => 15_000_000_000,
+            ExtCosts::bls12381_p1_decompress_element => 81_000_000_000,
+            ExtCosts::bls12381_p2_decompress_base => 15_000_000_000,
+            ExtCosts::bls12381_p2_decompress_element => 165_000_000_000,
         }
         .map(|_, value| ParameterCost { gas: value, compute: value * factor });
         ExtCostsConfig { costs }
@@ -225,6 +247,28 @@ pub enum ExtCosts {
     ed25519_verify_byte = 60,
     validator_power_base = 61,
     validator_total_power_base = 62,
+    yield_create_base = 63,
+    yield_create_byte = 64,
+    yield_resume_base = 65,
+    yield_resume_byte = 66,
+    bls12381_p1_sum_base = 67,
+    bls12381_p1_sum_element = 68,
+    bls12381_p2_sum_base = 69,
+    bls12381_p2_sum_element = 70,
+    bls12381_g1_multiexp_base = 71,
+    bls12381_g1_multiexp_element = 72,
+    bls12381_g2_multiexp_base = 73,
+    bls12381_g2_multiexp_element = 74,
+    bls12381_map_fp_to_g1_base = 75,
+    bls12381_map_fp_to_g1_element = 76,
+    bls12381_map_fp2_to_g2_base = 77,
+    bls12381_map_fp2_to_g2_element = 78,
+    bls12381_pairing_base = 79,
+    bls12381_pairing_element = 80,
+    bls12381_p1_decompress_base = 81,
+    bls12381_p1_decompress_element = 82,
+    bls12381_p2_decompress_base = 83,
+    bls12381_p2_decompress_element = 84,
 }
 
 // Type of an action, used in fees logic.
@@ -261,6 +305,9 @@ pub enum ActionCosts {
     delegate = 15,
     register_rsa2048_keys = 16,
     create_rsa2048_challenge = 17,
+    deploy_global_contract_base = 18,
+    deploy_global_contract_byte = 19,
+    use_global_contract = 20,
 }
 
 impl ExtCosts {
@@ -337,6 +384,28 @@ impl ExtCosts {
             ExtCosts::alt_bn128_g1_sum_element => Parameter::WasmAltBn128G1SumElement,
             ExtCosts::validator_power_base => Parameter::WasmValidatorPledgeBase,
             ExtCosts::validator_total_power_base => Parameter::WasmValidatorTotalPledgeBase,
+            ExtCosts::yield_create_base => Parameter::WasmYieldCreateBase,
+            ExtCosts::yield_create_byte => Parameter::WasmYieldCreateByte,
+            ExtCosts::yield_resume_base => Parameter::WasmYieldResumeBase,
+            ExtCosts::yield_resume_byte => Parameter::WasmYieldResumeByte,
+            ExtCosts::bls12381_p1_sum_base => Parameter::WasmBls12381P1SumBase,
+            ExtCosts::bls12381_p1_sum_element => Parameter::WasmBls12381P1SumElement,
+            ExtCosts::bls12381_p2_sum_base => Parameter::WasmBls12381P2SumBase,
+            ExtCosts::bls12381_p2_sum_element => Parameter::WasmBls12381P2SumElement,
+            ExtCosts::bls12381_g1_multiexp_base => Parameter::WasmBls12381G1MultiexpBase,
+            ExtCosts::bls12381_g1_multiexp_element => Parameter::WasmBls12381G1MultiexpElement,
+            ExtCosts::bls12381_g2_multiexp_base => Parameter::WasmBls12381G2MultiexpBase,
+            ExtCosts::bls12381_g2_multiexp_element => Parameter::WasmBls12381G2MultiexpElement,
+            ExtCosts::bls12381_map_fp_to_g1_base => Parameter::WasmBls12381MapFpToG1Base,
+            ExtCosts::bls12381_map_fp_to_g1_element => Parameter::WasmBls12381MapFpToG1Element,
+            ExtCosts::bls12381_map_fp2_to_g2_base => Parameter::WasmBls12381MapFp2ToG2Base,
+            ExtCosts::bls12381_map_fp2_to_g2_element => Parameter::WasmBls12381MapFp2ToG2Element,
+            ExtCosts::bls12381_pairing_base => Parameter::WasmBls12381PairingBase,
+            ExtCosts::bls12381_pairing_element => Parameter::WasmBls12381PairingElement,
+            ExtCosts::bls12381_p1_decompress_base => Parameter::WasmBls12381P1DecompressBase,
+            ExtCosts::bls12381_p1_decompress_element => Parameter::WasmBls12381P1DecompressElement,
+            ExtCosts::bls12381_p2_decompress_base => Parameter::WasmBls12381P2DecompressBase,
+            ExtCosts::bls12381_p2_decompress_element => Parameter::WasmBls12381P2DecompressElement,
         }
     }
 }
@@ -470,6 +539,21 @@ impl RuntimeFeesConfig {
                     send_not_sir: 115123062500,
                     execution: 115123062500,
                 },
+                ActionCosts::deploy_global_contract_base => Fee {
+                    send_sir: 184765750000,
+                    send_not_sir: 184765750000,
+                    execution: 184765750000,
+                },
+                ActionCosts::deploy_global_contract_byte => Fee {
+                    send_sir: 6812999,
+                    send_not_sir: 6812999,
+                    execution: 6812999,
+                },
+                ActionCosts::use_global_contract => Fee {
+                    send_sir: 184765750000,
+                    send_not_sir: 184765750000,
+                    execution: 184765750000,
+                },
             },
         }
     }
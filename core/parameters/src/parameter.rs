@@ -29,6 +29,13 @@ pub enum Parameter {
     StorageNumBytesAccount,
     StorageNumExtraBytesRecord,
 
+    // Congestion control config
+    MaxCongestionIncomingGas,
+    MaxCongestionMemoryConsumption,
+    MaxOutgoingGas,
+    MinOutgoingGas,
+    RejectTxCongestionThreshold,
+
     // Static action costs
     // send_sir / send_not_sir is burned when creating a receipt on the signer shard.
     // (SIR = signer is receiver, which guarantees the receipt is local.)
@@ -53,6 +60,12 @@ pub enum Parameter {
     // Smart contract dynamic gas costs
     WasmRegularOpCost,
     WasmGrowMemCost,
+    WasmBulkMemoryByteCost,
+    WasmBulkTableElementCost,
+    WasmSimdOpCost,
+    WasmSimdMulOpCost,
+    WasmSimdFloatOpCost,
+    WasmSimdShuffleOpCost,
     /// Base cost for a host function
     WasmBase,
     WasmContractLoadingBase,
@@ -117,6 +130,28 @@ pub enum Parameter {
     WasmAltBn128PairingCheckElement,
     WasmAltBn128G1SumBase,
     WasmAltBn128G1SumElement,
+    WasmYieldCreateBase,
+    WasmYieldCreateByte,
+    WasmYieldResumeBase,
+    WasmYieldResumeByte,
+    WasmBls12381P1SumBase,
+    WasmBls12381P1SumElement,
+    WasmBls12381P2SumBase,
+    WasmBls12381P2SumElement,
+    WasmBls12381G1MultiexpBase,
+    WasmBls12381G1MultiexpElement,
+    WasmBls12381G2MultiexpBase,
+    WasmBls12381G2MultiexpElement,
+    WasmBls12381MapFpToG1Base,
+    WasmBls12381MapFpToG1Element,
+    WasmBls12381MapFp2ToG2Base,
+    WasmBls12381MapFp2ToG2Element,
+    WasmBls12381PairingBase,
+    WasmBls12381PairingElement,
+    WasmBls12381P1DecompressBase,
+    WasmBls12381P1DecompressElement,
+    WasmBls12381P2DecompressBase,
+    WasmBls12381P2DecompressElement,
 
     // Smart contract limits
     MaxGasBurnt,
@@ -145,7 +180,11 @@ pub enum Parameter {
     MaxFunctionsNumberPerContract,
     Wasmer2StackLimit,
     MaxLocalsPerContract,
+    MaxTablesPerContract,
+    MaxElementsPerContractTable,
     AccountIdValidityRulesVersion,
+    YieldTimeoutLengthInBlocks,
+    MaxYieldPayloadSize,
 
     // Contract runtime features
     #[strum(serialize = "disable_9393_fix")]
@@ -159,9 +198,14 @@ pub enum Parameter {
     FunctionCallWeight,
     VmKind,
     EthAccounts,
+    YieldResume,
+    Bls12381,
 
     ActionRegisterRSA2048Keys,
     ActionCreateRSA2048Challenge,
+    ActionDeployGlobalContract,
+    ActionDeployGlobalContractPerByte,
+    ActionUseGlobalContract,
 }
 
 #[derive(
@@ -196,6 +240,9 @@ pub enum FeeParameter {
     ActionDelegate,
     ActionRegisterRSA2048Keys,
     ActionCreateRSA2048Challenge,
+    ActionDeployGlobalContract,
+    ActionDeployGlobalContractPerByte,
+    ActionUseGlobalContract,
 }
 
 impl Parameter {
@@ -228,7 +275,11 @@ impl Parameter {
             Parameter::MaxFunctionsNumberPerContract,
             Parameter::Wasmer2StackLimit,
             Parameter::MaxLocalsPerContract,
+            Parameter::MaxTablesPerContract,
+            Parameter::MaxElementsPerContractTable,
             Parameter::AccountIdValidityRulesVersion,
+            Parameter::YieldTimeoutLengthInBlocks,
+            Parameter::MaxYieldPayloadSize,
         ]
         .iter()
     }
@@ -257,6 +308,9 @@ impl From<ActionCosts> for FeeParameter {
             ActionCosts::new_data_receipt_byte => Self::DataReceiptCreationPerByte,
             ActionCosts::register_rsa2048_keys => Self::ActionRegisterRSA2048Keys,
             ActionCosts::create_rsa2048_challenge => Self::ActionCreateRSA2048Challenge,
+            ActionCosts::deploy_global_contract_base => Self::ActionDeployGlobalContract,
+            ActionCosts::deploy_global_contract_byte => Self::ActionDeployGlobalContractPerByte,
+            ActionCosts::use_global_contract => Self::ActionUseGlobalContract,
         }
     }
 }
@@ -1,4 +1,4 @@
-use super::config::{AccountCreationConfig, RuntimeConfig};
+use super::config::{AccountCreationConfig, CongestionControlConfig, RuntimeConfig};
 use crate::cost::{
     ActionCosts, ExtCostsConfig, Fee, ParameterCost, RuntimeFeesConfig, StorageUsageConfig,
 };
@@ -310,6 +310,12 @@ impl TryFrom<&ParameterTable> for RuntimeConfig {
                 },
                 vm_kind: params.get(Parameter::VmKind)?,
                 grow_mem_cost: params.get(Parameter::WasmGrowMemCost)?,
+                bulk_memory_byte_cost: params.get(Parameter::WasmBulkMemoryByteCost)?,
+                bulk_table_element_cost: params.get(Parameter::WasmBulkTableElementCost)?,
+                simd_op_cost: params.get(Parameter::WasmSimdOpCost)?,
+                simd_mul_op_cost: params.get(Parameter::WasmSimdMulOpCost)?,
+                simd_float_op_cost: params.get(Parameter::WasmSimdFloatOpCost)?,
+                simd_shuffle_op_cost: params.get(Parameter::WasmSimdShuffleOpCost)?,
                 regular_op_cost: params.get(Parameter::WasmRegularOpCost)?,
                 disable_9393_fix: params.get(Parameter::Disable9393Fix)?,
                 limit_config: serde_yaml::from_value(params.yaml_map(Parameter::vm_limits()))
@@ -325,12 +331,23 @@ impl TryFrom<&ParameterTable> for RuntimeConfig {
                 alt_bn128: params.get(Parameter::AltBn128)?,
                 function_call_weight: params.get(Parameter::FunctionCallWeight)?,
                 eth_accounts: params.get(Parameter::EthAccounts)?,
+                yield_resume: params.get(Parameter::YieldResume)?,
+                bls12381: params.get(Parameter::Bls12381)?,
             },
             account_creation_config: AccountCreationConfig {
                 min_allowed_top_level_account_length: params
                     .get(Parameter::MinAllowedTopLevelAccountLength)?,
                 registrar_account_id: params.get(Parameter::RegistrarAccountId)?,
             },
+            congestion_control_config: CongestionControlConfig {
+                max_congestion_incoming_gas: params.get(Parameter::MaxCongestionIncomingGas)?,
+                max_congestion_memory_consumption: params
+                    .get(Parameter::MaxCongestionMemoryConsumption)?,
+                max_outgoing_gas: params.get(Parameter::MaxOutgoingGas)?,
+                min_outgoing_gas: params.get(Parameter::MinOutgoingGas)?,
+                reject_tx_congestion_threshold: params
+                    .get(Parameter::RejectTxCongestionThreshold)?,
+            },
         })
     }
 }
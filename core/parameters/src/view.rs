@@ -18,6 +18,8 @@ pub struct RuntimeConfigView {
     pub wasm_config: VMConfigView,
     /// Config that defines rules for account creation.
     pub account_creation_config: AccountCreationConfigView,
+    /// Limits used to apply backpressure between congested shards.
+    pub congestion_control_config: CongestionControlConfigView,
 }
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
@@ -52,6 +54,21 @@ pub struct AccountCreationConfigView {
     pub registrar_account_id: AccountId,
 }
 
+/// The structure describes the limits of the cross-shard congestion control.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct CongestionControlConfigView {
+    /// Gas attached to the delayed receipts at which a shard is fully congested.
+    pub max_congestion_incoming_gas: Gas,
+    /// Size in bytes of the delayed receipts at which a shard is fully congested.
+    pub max_congestion_memory_consumption: u64,
+    /// Gas other shards may forward to a shard in one chunk when it is not congested.
+    pub max_outgoing_gas: Gas,
+    /// Gas other shards may forward to a shard in one chunk when it is fully congested.
+    pub min_outgoing_gas: Gas,
+    /// Congestion level from which new transactions to a shard are rejected.
+    pub reject_tx_congestion_threshold: Rational32,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Hash, PartialEq, Eq)]
 pub struct DataReceiptCreationConfigView {
     /// Base cost of creating a data receipt.
@@ -104,6 +121,15 @@ pub struct ActionCreationConfigView {
     ///
     /// This is on top of the costs for the actions inside the delegate action.
     pub delegate_cost: Fee,
+
+    /// Base cost of publishing a contract into the global contract registry.
+    pub deploy_global_contract_cost: Fee,
+
+    /// Cost per byte of publishing a contract into the global contract registry.
+    pub deploy_global_contract_cost_per_byte: Fee,
+
+    /// Base cost of pointing an account at a contract from the global contract registry.
+    pub use_global_contract_cost: Fee,
 }
 
 /// Describes the cost of creating an access key.
@@ -170,6 +196,18 @@ impl From<crate::RuntimeConfig> for RuntimeConfigView {
                     delete_key_cost: config.fees.fee(ActionCosts::delete_key).clone(),
                     delete_account_cost: config.fees.fee(ActionCosts::delete_account).clone(),
                     delegate_cost: config.fees.fee(ActionCosts::delegate).clone(),
+                    deploy_global_contract_cost: config
+                        .fees
+                        .fee(ActionCosts::deploy_global_contract_base)
+                        .clone(),
+                    deploy_global_contract_cost_per_byte: config
+                        .fees
+                        .fee(ActionCosts::deploy_global_contract_byte)
+                        .clone(),
+                    use_global_contract_cost: config
+                        .fees
+                        .fee(ActionCosts::use_global_contract)
+                        .clone(),
                 },
                 storage_usage_config: StorageUsageConfigView {
                     num_bytes_account: config.fees.storage_usage_config.num_bytes_account,
@@ -187,6 +225,19 @@ impl From<crate::RuntimeConfig> for RuntimeConfigView {
                     .min_allowed_top_level_account_length,
                 registrar_account_id: config.account_creation_config.registrar_account_id,
             },
+            congestion_control_config: CongestionControlConfigView {
+                max_congestion_incoming_gas: config
+                    .congestion_control_config
+                    .max_congestion_incoming_gas,
+                max_congestion_memory_consumption: config
+                    .congestion_control_config
+                    .max_congestion_memory_consumption,
+                max_outgoing_gas: config.congestion_control_config.max_outgoing_gas,
+                min_outgoing_gas: config.congestion_control_config.min_outgoing_gas,
+                reject_tx_congestion_threshold: config
+                    .congestion_control_config
+                    .reject_tx_congestion_threshold,
+            },
         }
     }
 }
@@ -198,6 +249,19 @@ pub struct VMConfigView {
 
     /// Gas cost of a growing memory by single page.
     pub grow_mem_cost: u32,
+    /// Gas cost per byte copied, filled or initialized by `memory.copy`, `memory.fill` and
+    /// `memory.init`.
+    pub bulk_memory_byte_cost: u32,
+    /// Gas cost per element grown, copied, filled or initialized by the table instructions.
+    pub bulk_table_element_cost: u32,
+    /// Gas cost of a SIMD operation not covered by the more specific SIMD costs.
+    pub simd_op_cost: u32,
+    /// Gas cost of a SIMD integer multiplication.
+    pub simd_mul_op_cost: u32,
+    /// Gas cost of a SIMD floating point arithmetic, rounding or conversion operation.
+    pub simd_float_op_cost: u32,
+    /// Gas cost of a SIMD `i8x16.shuffle` or `i8x16.swizzle` operation.
+    pub simd_shuffle_op_cost: u32,
     /// Gas cost of a regular operation.
     pub regular_op_cost: u32,
 
@@ -221,6 +285,10 @@ pub struct VMConfigView {
     pub function_call_weight: bool,
     /// See [`VMConfig::eth_accounts`].
     pub eth_accounts: bool,
+    /// See [`VMConfig::yield_resume`].
+    pub yield_resume: bool,
+    /// See [`VMConfig::bls12381`].
+    pub bls12381: bool,
 
     /// Describes limits for VM and Runtime.
     ///
@@ -234,6 +302,12 @@ impl From<crate::vm::Config> for VMConfigView {
         Self {
             ext_costs: ExtCostsConfigView::from(config.ext_costs),
             grow_mem_cost: config.grow_mem_cost,
+            bulk_memory_byte_cost: config.bulk_memory_byte_cost,
+            bulk_table_element_cost: config.bulk_table_element_cost,
+            simd_op_cost: config.simd_op_cost,
+            simd_mul_op_cost: config.simd_mul_op_cost,
+            simd_float_op_cost: config.simd_float_op_cost,
+            simd_shuffle_op_cost: config.simd_shuffle_op_cost,
             regular_op_cost: config.regular_op_cost,
             disable_9393_fix: config.disable_9393_fix,
             limit_config: config.limit_config,
@@ -246,6 +320,8 @@ impl From<crate::vm::Config> for VMConfigView {
             function_call_weight: config.function_call_weight,
             vm_kind: config.vm_kind,
             eth_accounts: config.eth_accounts,
+            yield_resume: config.yield_resume,
+            bls12381: config.bls12381,
         }
     }
 }
@@ -255,6 +331,12 @@ impl From<VMConfigView> for crate::vm::Config {
         Self {
             ext_costs: crate::ExtCostsConfig::from(view.ext_costs),
             grow_mem_cost: view.grow_mem_cost,
+            bulk_memory_byte_cost: view.bulk_memory_byte_cost,
+            bulk_table_element_cost: view.bulk_table_element_cost,
+            simd_op_cost: view.simd_op_cost,
+            simd_mul_op_cost: view.simd_mul_op_cost,
+            simd_float_op_cost: view.simd_float_op_cost,
+            simd_shuffle_op_cost: view.simd_shuffle_op_cost,
             regular_op_cost: view.regular_op_cost,
             disable_9393_fix: view.disable_9393_fix,
             limit_config: view.limit_config,
@@ -267,6 +349,8 @@ impl From<VMConfigView> for crate::vm::Config {
             function_call_weight: view.function_call_weight,
             vm_kind: view.vm_kind,
             eth_accounts: view.eth_accounts,
+            yield_resume: view.yield_resume,
+            bls12381: view.bls12381,
         }
     }
 }
@@ -440,6 +524,58 @@ pub struct ExtCostsConfigView {
     pub alt_bn128_pairing_check_base: Gas,
     /// Per element cost for pairing check
     pub alt_bn128_pairing_check_element: Gas,
+
+    // ####################
+    // # Yield/resume API #
+    // ####################
+    /// Base cost for calling `promise_yield_create`
+    pub yield_create_base: Gas,
+    /// Cost for calling `promise_yield_create` per byte of the method name and arguments
+    pub yield_create_byte: Gas,
+    /// Base cost for calling `promise_yield_resume`
+    pub yield_resume_base: Gas,
+    /// Cost for calling `promise_yield_resume` per byte of the payload
+    pub yield_resume_byte: Gas,
+
+    // #############
+    // # BLS12-381 #
+    // #############
+    /// Base cost for sum of signed G1 points
+    pub bls12381_p1_sum_base: Gas,
+    /// Per element cost for sum of signed G1 points
+    pub bls12381_p1_sum_element: Gas,
+    /// Base cost for sum of signed G2 points
+    pub bls12381_p2_sum_base: Gas,
+    /// Per element cost for sum of signed G2 points
+    pub bls12381_p2_sum_element: Gas,
+    /// Base cost for G1 multiexp
+    pub bls12381_g1_multiexp_base: Gas,
+    /// Per element cost for G1 multiexp
+    pub bls12381_g1_multiexp_element: Gas,
+    /// Base cost for G2 multiexp
+    pub bls12381_g2_multiexp_base: Gas,
+    /// Per element cost for G2 multiexp
+    pub bls12381_g2_multiexp_element: Gas,
+    /// Base cost for mapping Fp elements to G1
+    pub bls12381_map_fp_to_g1_base: Gas,
+    /// Per element cost for mapping Fp elements to G1
+    pub bls12381_map_fp_to_g1_element: Gas,
+    /// Base cost for mapping Fp2 elements to G2
+    pub bls12381_map_fp2_to_g2_base: Gas,
+    /// Per element cost for mapping Fp2 elements to G2
+    pub bls12381_map_fp2_to_g2_element: Gas,
+    /// Base cost for pairing check
+    pub bls12381_pairing_base: Gas,
+    /// Per element cost for pairing check
+    pub bls12381_pairing_element: Gas,
+    /// Base cost for G1 point decompression
+    pub bls12381_p1_decompress_base: Gas,
+    /// Per element cost for G1 point decompression
+    pub bls12381_p1_decompress_element: Gas,
+    /// Base cost for G2 point decompression
+    pub bls12381_p2_decompress_base: Gas,
+    /// Per element cost for G2 point decompression
+    pub bls12381_p2_decompress_element: Gas,
 }
 
 impl From<crate::ExtCostsConfig> for ExtCostsConfigView {
@@ -512,6 +648,31 @@ impl From<crate::ExtCostsConfig> for ExtCostsConfigView {
             alt_bn128_pairing_check_base: config.gas_cost(ExtCosts::alt_bn128_pairing_check_base),
             alt_bn128_pairing_check_element: config
                 .gas_cost(ExtCosts::alt_bn128_pairing_check_element),
+            yield_create_base: config.gas_cost(ExtCosts::yield_create_base),
+            yield_create_byte: config.gas_cost(ExtCosts::yield_create_byte),
+            yield_resume_base: config.gas_cost(ExtCosts::yield_resume_base),
+            yield_resume_byte: config.gas_cost(ExtCosts::yield_resume_byte),
+            bls12381_p1_sum_base: config.gas_cost(ExtCosts::bls12381_p1_sum_base),
+            bls12381_p1_sum_element: config.gas_cost(ExtCosts::bls12381_p1_sum_element),
+            bls12381_p2_sum_base: config.gas_cost(ExtCosts::bls12381_p2_sum_base),
+            bls12381_p2_sum_element: config.gas_cost(ExtCosts::bls12381_p2_sum_element),
+            bls12381_g1_multiexp_base: config.gas_cost(ExtCosts::bls12381_g1_multiexp_base),
+            bls12381_g1_multiexp_element: config.gas_cost(ExtCosts::bls12381_g1_multiexp_element),
+            bls12381_g2_multiexp_base: config.gas_cost(ExtCosts::bls12381_g2_multiexp_base),
+            bls12381_g2_multiexp_element: config.gas_cost(ExtCosts::bls12381_g2_multiexp_element),
+            bls12381_map_fp_to_g1_base: config.gas_cost(ExtCosts::bls12381_map_fp_to_g1_base),
+            bls12381_map_fp_to_g1_element: config.gas_cost(ExtCosts::bls12381_map_fp_to_g1_element),
+            bls12381_map_fp2_to_g2_base: config.gas_cost(ExtCosts::bls12381_map_fp2_to_g2_base),
+            bls12381_map_fp2_to_g2_element: config
+                .gas_cost(ExtCosts::bls12381_map_fp2_to_g2_element),
+            bls12381_pairing_base: config.gas_cost(ExtCosts::bls12381_pairing_base),
+            bls12381_pairing_element: config.gas_cost(ExtCosts::bls12381_pairing_element),
+            bls12381_p1_decompress_base: config.gas_cost(ExtCosts::bls12381_p1_decompress_base),
+            bls12381_p1_decompress_element: config
+                .gas_cost(ExtCosts::bls12381_p1_decompress_element),
+            bls12381_p2_decompress_base: config.gas_cost(ExtCosts::bls12381_p2_decompress_base),
+            bls12381_p2_decompress_element: config
+                .gas_cost(ExtCosts::bls12381_p2_decompress_element),
             // removed parameters
             contract_compile_base: 0,
             contract_compile_bytes: 0,
@@ -585,6 +746,28 @@ impl From<ExtCostsConfigView> for crate::ExtCostsConfig {
                 ExtCosts::alt_bn128_g1_sum_element => view.alt_bn128_g1_sum_element,
                 ExtCosts::alt_bn128_pairing_check_base => view.alt_bn128_pairing_check_base,
                 ExtCosts::alt_bn128_pairing_check_element => view.alt_bn128_pairing_check_element,
+                ExtCosts::yield_create_base => view.yield_create_base,
+                ExtCosts::yield_create_byte => view.yield_create_byte,
+                ExtCosts::yield_resume_base => view.yield_resume_base,
+                ExtCosts::yield_resume_byte => view.yield_resume_byte,
+                ExtCosts::bls12381_p1_sum_base => view.bls12381_p1_sum_base,
+                ExtCosts::bls12381_p1_sum_element => view.bls12381_p1_sum_element,
+                ExtCosts::bls12381_p2_sum_base => view.bls12381_p2_sum_base,
+                ExtCosts::bls12381_p2_sum_element => view.bls12381_p2_sum_element,
+                ExtCosts::bls12381_g1_multiexp_base => view.bls12381_g1_multiexp_base,
+                ExtCosts::bls12381_g1_multiexp_element => view.bls12381_g1_multiexp_element,
+                ExtCosts::bls12381_g2_multiexp_base => view.bls12381_g2_multiexp_base,
+                ExtCosts::bls12381_g2_multiexp_element => view.bls12381_g2_multiexp_element,
+                ExtCosts::bls12381_map_fp_to_g1_base => view.bls12381_map_fp_to_g1_base,
+                ExtCosts::bls12381_map_fp_to_g1_element => view.bls12381_map_fp_to_g1_element,
+                ExtCosts::bls12381_map_fp2_to_g2_base => view.bls12381_map_fp2_to_g2_base,
+                ExtCosts::bls12381_map_fp2_to_g2_element => view.bls12381_map_fp2_to_g2_element,
+                ExtCosts::bls12381_pairing_base => view.bls12381_pairing_base,
+                ExtCosts::bls12381_pairing_element => view.bls12381_pairing_element,
+                ExtCosts::bls12381_p1_decompress_base => view.bls12381_p1_decompress_base,
+                ExtCosts::bls12381_p1_decompress_element => view.bls12381_p1_decompress_element,
+                ExtCosts::bls12381_p2_decompress_base => view.bls12381_p2_decompress_base,
+                ExtCosts::bls12381_p2_decompress_element => view.bls12381_p2_decompress_element,
         }
         .map(|_, value| ParameterCost { gas: value, compute: value });
         Self { costs }
@@ -128,10 +128,23 @@ pub struct LimitConfig {
     /// If present, stores max number of locals declared globally in one contract
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_locals_per_contract: Option<u64>,
+    /// If present, stores max number of tables declared in one contract
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tables_per_contract: Option<u32>,
+    /// If present, stores max number of elements a table of a contract may ever have, both
+    /// initially and after growing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_elements_per_contract_table: Option<u32>,
     /// Whether to enforce account_id well-formedness where it wasn't enforced
     /// historically.
     #[serde(default = "AccountIdValidityRulesVersion::v0")]
     pub account_id_validity_rules_version: AccountIdValidityRulesVersion,
+    /// Number of blocks after which a yielded promise times out and is resumed without data.
+    #[serde(default = "yield_timeout_length_in_blocks_default")]
+    pub yield_timeout_length_in_blocks: u64,
+    /// Max size of the payload which can be passed to `promise_yield_resume`.
+    #[serde(default = "max_yield_payload_size_default")]
+    pub max_yield_payload_size: u64,
 }
 
 /// Dynamic configuration parameters required for the WASM runtime to
@@ -149,6 +162,26 @@ pub struct Config {
     /// Gas cost of a growing memory by single page.
     pub grow_mem_cost: u32,
 
+    /// Gas cost per byte copied, filled or initialized by `memory.copy`, `memory.fill` and
+    /// `memory.init`.
+    pub bulk_memory_byte_cost: u32,
+
+    /// Gas cost per element grown, copied, filled or initialized by `table.grow`, `table.copy`,
+    /// `table.fill` and `table.init`.
+    pub bulk_table_element_cost: u32,
+
+    /// Gas cost of a SIMD operation not covered by the more specific SIMD costs.
+    pub simd_op_cost: u32,
+
+    /// Gas cost of a SIMD integer multiplication.
+    pub simd_mul_op_cost: u32,
+
+    /// Gas cost of a SIMD floating point arithmetic, rounding or conversion operation.
+    pub simd_float_op_cost: u32,
+
+    /// Gas cost of a SIMD `i8x16.shuffle` or `i8x16.swizzle` operation.
+    pub simd_shuffle_op_cost: u32,
+
     /// Gas cost of a regular operation.
     pub regular_op_cost: u32,
 
@@ -183,6 +216,12 @@ pub struct Config {
     /// Enable the `EthAccounts` protocol feature.
     pub eth_accounts: bool,
 
+    /// Enable the host functions added by the `YieldExecution` protocol feature.
+    pub yield_resume: bool,
+
+    /// Enable the host functions added by the `Bls12381` protocol feature.
+    pub bls12381: bool,
+
     /// Describes limits for VM and Runtime.
     pub limit_config: LimitConfig,
 }
@@ -203,6 +242,12 @@ impl Config {
             },
         };
         self.grow_mem_cost = 0;
+        self.bulk_memory_byte_cost = 0;
+        self.bulk_table_element_cost = 0;
+        self.simd_op_cost = 0;
+        self.simd_mul_op_cost = 0;
+        self.simd_float_op_cost = 0;
+        self.simd_shuffle_op_cost = 0;
         self.regular_op_cost = 0;
         self.limit_config.max_gas_burnt = u64::MAX;
     }
@@ -212,6 +257,14 @@ fn wasmer2_stack_limit_default() -> i32 {
     100 * 1024
 }
 
+fn yield_timeout_length_in_blocks_default() -> u64 {
+    200
+}
+
+fn max_yield_payload_size_default() -> u64 {
+    1024
+}
+
 /// Our original code for limiting WASM stack was buggy. We fixed that, but we
 /// still have to use old (`V0`) limiter for old protocol versions.
 ///
@@ -241,6 +294,11 @@ pub enum ContractPrepareVersion {
     V1,
     /// finite-wasm 0.3.0 based contract preparation code.
     V2,
+    /// Same as V2, but additionally accepts the bulk memory, multi-value, reference types and
+    /// non-trapping float-to-int conversion proposals.
+    V3,
+    /// Same as V3, but additionally accepts the fixed-width SIMD proposal.
+    V4,
 }
 
 impl ContractPrepareVersion {
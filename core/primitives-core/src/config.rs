@@ -30,4 +30,7 @@ impl AccountIdValidityRulesVersion {
 pub struct ViewConfig {
     /// If specified, defines max burnt gas per view method.
     pub max_gas_burnt: Gas,
+    /// Whether to break down the gas burnt on Wasm instructions by contract function.
+    #[serde(default)]
+    pub profile_functions: bool,
 }
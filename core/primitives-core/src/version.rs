@@ -121,6 +121,24 @@ pub enum ProtocolFeature {
     /// Enables chunk validation which is introduced with stateless validation.
     ChunkValidation,
     EthAccounts,
+    /// Track the congestion of the shards in the chunk headers and apply backpressure to
+    /// receipts and transactions sent to congested shards.
+    CongestionControl,
+    /// Allow contracts to pause a call until data is submitted with `promise_yield_resume` or the
+    /// yield times out.
+    YieldExecution,
+    /// Allow publishing contract code once into a registry keyed by code hash, which is copied to
+    /// every shard, and pointing any number of accounts at it.
+    GlobalContracts,
+    /// Host functions for BLS12-381 curve operations: point addition, multiexp, mapping field
+    /// elements to the curve, pairing check and point decompression.
+    Bls12381,
+    /// Accept the bulk memory, multi-value, reference types and non-trapping float-to-int Wasm
+    /// proposals in contracts by switching to `ContractPrepareVersion::V3`.
+    WasmProposals,
+    /// Accept the fixed-width SIMD Wasm proposal in contracts by switching to
+    /// `ContractPrepareVersion::V4`.
+    WasmSimd,
 }
 
 impl ProtocolFeature {
@@ -177,6 +195,12 @@ impl ProtocolFeature {
             ProtocolFeature::RejectBlocksWithOutdatedProtocolVersions => 132,
             ProtocolFeature::ChunkValidation => 137,
             ProtocolFeature::EthAccounts => 138,
+            ProtocolFeature::CongestionControl => 139,
+            ProtocolFeature::YieldExecution => 140,
+            ProtocolFeature::GlobalContracts => 141,
+            ProtocolFeature::Bls12381 => 142,
+            ProtocolFeature::WasmProposals => 143,
+            ProtocolFeature::WasmSimd => 144,
         }
     }
 }
@@ -189,7 +213,7 @@ const STABLE_PROTOCOL_VERSION: ProtocolVersion = 64;
 /// Largest protocol version supported by the current binary.
 pub const PROTOCOL_VERSION: ProtocolVersion = if cfg!(feature = "nightly_protocol") {
     // On nightly, pick big enough version to support all features.
-    139
+    144
 } else {
     // Enable all stable features.
     STABLE_PROTOCOL_VERSION
@@ -7,8 +7,9 @@ use std::fmt;
 use unc_crypto::PublicKey;
 use unc_primitives_core::{
     account::AccessKey,
+    hash::CryptoHash,
     serialize::dec_format,
-    types::{AccountId, Balance, Gas},
+    types::{AccountId, Balance, Gas, Power},
 };
 
 fn base64(s: &[u8]) -> String {
@@ -94,6 +95,43 @@ impl fmt::Debug for DeployContractAction {
     }
 }
 
+/// Publishes a Wasm code into the global contract registry under its hash, so that any number of
+/// accounts can later point at it with `UseGlobalContractAction` without storing a copy each.
+#[serde_as]
+#[derive(
+    BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone,
+)]
+pub struct DeployGlobalContractAction {
+    /// WebAssembly binary
+    #[serde_as(as = "Base64")]
+    pub code: Vec<u8>,
+}
+
+impl fmt::Debug for DeployGlobalContractAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeployGlobalContractAction")
+            .field("code", &format_args!("{}", base64(&self.code)))
+            .finish()
+    }
+}
+
+/// Sets the code of a receiver_id to a contract previously published with
+/// `DeployGlobalContractAction`.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    PartialEq,
+    Eq,
+    Clone,
+    Debug,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct UseGlobalContractAction {
+    /// Hash of the code in the global contract registry.
+    pub code_hash: CryptoHash,
+}
+
 #[serde_as]
 #[derive(
     BorshSerialize, BorshDeserialize, serde::Serialize, serde::Deserialize, PartialEq, Eq, Clone,
@@ -167,6 +205,16 @@ pub struct RegisterRsa2048KeysAction {
     pub args: Vec<u8>,
 }
 
+impl RegisterRsa2048KeysAction {
+    /// Power of the chip, taken from the `power` field of the JSON encoded `args`.
+    ///
+    /// Returns `None` if `args` doesn't hold a decimal string `power` field.
+    pub fn power(&self) -> Option<Power> {
+        let args: serde_json::Value = serde_json::from_slice(&self.args).ok()?;
+        args.get("power")?.as_str()?.parse().ok()
+    }
+}
+
 impl fmt::Debug for RegisterRsa2048KeysAction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RegisterRsa2048KeysAction")
@@ -229,6 +277,10 @@ pub enum Action {
     Delegate(Box<delegate::SignedDelegateAction>),
     RegisterRsa2048Keys(Box<RegisterRsa2048KeysAction>),
     CreateRsa2048Challenge(Box<CreateRsa2048ChallengeAction>),
+    /// Publishes a Wasm code into the global contract registry
+    DeployGlobalContract(DeployGlobalContractAction),
+    /// Sets a receiver_id code to a Wasm code from the global contract registry
+    UseGlobalContract(Box<UseGlobalContractAction>),
 }
 
 const _: () = assert!(
@@ -314,3 +366,15 @@ impl From<CreateRsa2048ChallengeAction> for Action {
         Self::CreateRsa2048Challenge(Box::new(create_rsa2048_challenge_action))
     }
 }
+
+impl From<DeployGlobalContractAction> for Action {
+    fn from(deploy_global_contract_action: DeployGlobalContractAction) -> Self {
+        Self::DeployGlobalContract(deploy_global_contract_action)
+    }
+}
+
+impl From<UseGlobalContractAction> for Action {
+    fn from(use_global_contract_action: UseGlobalContractAction) -> Self {
+        Self::UseGlobalContract(Box::new(use_global_contract_action))
+    }
+}
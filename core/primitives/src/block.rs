@@ -5,6 +5,7 @@ use crate::block::BlockValidityError::{
 pub use crate::block_header::*;
 use crate::challenge::{Challenges, ChallengesResult};
 use crate::checked_feature;
+use crate::congestion_info::BlockCongestionInfo;
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::{merklize, verify_path, MerklePath};
 use crate::num_rational::Rational32;
@@ -126,6 +127,7 @@ pub fn genesis_chunks(
                 vec![],
                 &[],
                 CryptoHash::default(),
+                None,
                 &EmptyValidatorSigner::default(),
                 genesis_protocol_version,
             )
@@ -527,6 +529,20 @@ impl Block {
         }
     }
 
+    /// Congestion of the shards, as reported by the chunk headers.
+    pub fn block_congestion_info(&self) -> BlockCongestionInfo {
+        BlockCongestionInfo::new(
+            self.chunks()
+                .iter()
+                .filter_map(|chunk| {
+                    chunk
+                        .congestion_info()
+                        .map(|congestion_info| (chunk.shard_id(), congestion_info))
+                })
+                .collect(),
+        )
+    }
+
     #[inline]
     pub fn challenges(&self) -> &Challenges {
         match self {
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use unc_parameters::CongestionControlConfig;
+use unc_primitives_core::types::{Gas, ShardId};
+
+use crate::errors::IntegerOverflowError;
+use crate::shard_layout::ShardLayout;
+
+/// Congestion levels are fixed-point numbers, with `MAX_CONGESTION_LEVEL`
+/// standing for a fully congested shard. They are part of the consensus, so
+/// they are computed with integers only.
+pub const MAX_CONGESTION_LEVEL: u64 = 1_000_000;
+
+/// Congestion of a shard after applying its last chunk.
+///
+/// It is included in the header of the next chunk of the shard, so that all
+/// the shards learn about it from the block.
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    serde::Serialize,
+    serde::Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+)]
+pub struct CongestionInfo {
+    /// Sum of the gas attached to the receipts in the delayed receipts queue.
+    #[serde(with = "crate::serialize::dec_format")]
+    pub delayed_receipts_gas: u128,
+    /// Total size of the receipts in the delayed receipts queue.
+    pub delayed_receipts_bytes: u64,
+}
+
+impl CongestionInfo {
+    pub fn add_delayed_receipt(
+        &mut self,
+        gas: Gas,
+        bytes: u64,
+    ) -> Result<(), IntegerOverflowError> {
+        self.delayed_receipts_gas =
+            self.delayed_receipts_gas.checked_add(gas as u128).ok_or(IntegerOverflowError)?;
+        self.delayed_receipts_bytes =
+            self.delayed_receipts_bytes.checked_add(bytes).ok_or(IntegerOverflowError)?;
+        Ok(())
+    }
+
+    /// Saturates at zero rather than failing, because a bootstrapped
+    /// congestion info may be an estimate below the actual queue.
+    pub fn remove_delayed_receipt(&mut self, gas: Gas, bytes: u64) {
+        self.delayed_receipts_gas = self.delayed_receipts_gas.saturating_sub(gas as u128);
+        self.delayed_receipts_bytes = self.delayed_receipts_bytes.saturating_sub(bytes);
+    }
+
+    /// Returns the congestion level between 0 (not congested) and
+    /// `MAX_CONGESTION_LEVEL` (fully congested), whichever of the gas and the
+    /// size of the delayed receipts is closer to its limit.
+    pub fn congestion_level(&self, config: &CongestionControlConfig) -> u64 {
+        let gas_congestion =
+            congestion_ratio(self.delayed_receipts_gas, config.max_congestion_incoming_gas.into());
+        let memory_congestion = congestion_ratio(
+            self.delayed_receipts_bytes.into(),
+            config.max_congestion_memory_consumption.into(),
+        );
+        gas_congestion.max(memory_congestion)
+    }
+
+    /// Returns how much gas of receipts other shards may send to this shard in
+    /// one chunk. It goes linearly from `max_outgoing_gas` for a shard which is
+    /// not congested down to `min_outgoing_gas` for a fully congested one.
+    pub fn outgoing_gas_limit(&self, config: &CongestionControlConfig) -> Gas {
+        let free_level = MAX_CONGESTION_LEVEL - self.congestion_level(config);
+        let gas_range = config.max_outgoing_gas.saturating_sub(config.min_outgoing_gas);
+        // Fits into a `Gas`, as `free_level` is at most `MAX_CONGESTION_LEVEL`.
+        let extra_gas =
+            u128::from(gas_range) * u128::from(free_level) / u128::from(MAX_CONGESTION_LEVEL);
+        config.min_outgoing_gas.saturating_add(extra_gas as Gas)
+    }
+
+    /// Whether new transactions may be sent to this shard.
+    pub fn shard_accepts_transactions(&self, config: &CongestionControlConfig) -> bool {
+        let threshold = &config.reject_tx_congestion_threshold;
+        // `Rational32` keeps the denominator positive, so the comparison can
+        // be done on the cross products.
+        i128::from(self.congestion_level(config)) * i128::from(*threshold.denom())
+            < i128::from(*threshold.numer()) * i128::from(MAX_CONGESTION_LEVEL)
+    }
+}
+
+/// Returns `value / limit` as a congestion level, capped at `MAX_CONGESTION_LEVEL`.
+fn congestion_ratio(value: u128, limit: u128) -> u64 {
+    let limit = limit.max(1);
+    // Cannot overflow, as both the capped value and the limit fit into a `u64`
+    // for all the configs, and `MAX_CONGESTION_LEVEL` is small.
+    (value.min(limit) * u128::from(MAX_CONGESTION_LEVEL) / limit) as u64
+}
+
+/// Congestion of all the shards, as reported by the chunk headers of a block.
+/// Shards are missing until their first chunk with the congestion info.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockCongestionInfo {
+    shards: BTreeMap<ShardId, CongestionInfo>,
+}
+
+impl BlockCongestionInfo {
+    pub fn new(shards: BTreeMap<ShardId, CongestionInfo>) -> Self {
+        Self { shards }
+    }
+
+    pub fn get(&self, shard_id: ShardId) -> Option<&CongestionInfo> {
+        self.shards.get(&shard_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&ShardId, &CongestionInfo)> {
+        self.shards.iter()
+    }
+}
+
+/// Everything the runtime needs to apply backpressure while applying a chunk.
+#[derive(Clone, Debug)]
+pub struct CongestionControl {
+    /// Shard of the chunk being applied.
+    pub shard_id: ShardId,
+    /// Shard layout, used to find the receiving shards of the outgoing receipts.
+    pub shard_layout: ShardLayout,
+    /// Congestion of the shards, taken from the chunk headers of the block.
+    pub block_congestion_info: BlockCongestionInfo,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num_rational::Rational32;
+
+    fn test_config() -> CongestionControlConfig {
+        CongestionControlConfig {
+            max_congestion_incoming_gas: 20_000_000_000_000_000,
+            max_congestion_memory_consumption: 1_000_000_000,
+            max_outgoing_gas: 300_000_000_000_000_000,
+            min_outgoing_gas: 1_000_000_000_000_000,
+            reject_tx_congestion_threshold: Rational32::new(1, 2),
+        }
+    }
+
+    #[test]
+    fn test_congestion_level() {
+        let config = test_config();
+        let mut info = CongestionInfo::default();
+        assert_eq!(info.congestion_level(&config), 0);
+        assert_eq!(info.outgoing_gas_limit(&config), config.max_outgoing_gas);
+        assert!(info.shard_accepts_transactions(&config));
+
+        info.add_delayed_receipt(config.max_congestion_incoming_gas / 4, 100).unwrap();
+        assert_eq!(info.congestion_level(&config), MAX_CONGESTION_LEVEL / 4);
+        assert_eq!(info.outgoing_gas_limit(&config), 225_250_000_000_000_000);
+        assert!(info.shard_accepts_transactions(&config));
+
+        info.add_delayed_receipt(config.max_congestion_incoming_gas / 4, 100).unwrap();
+        assert_eq!(info.congestion_level(&config), MAX_CONGESTION_LEVEL / 2);
+        assert!(!info.shard_accepts_transactions(&config));
+
+        // The level is capped and the other shards can still send some receipts.
+        info.add_delayed_receipt(0, config.max_congestion_memory_consumption * 2).unwrap();
+        assert_eq!(info.congestion_level(&config), MAX_CONGESTION_LEVEL);
+        assert_eq!(info.outgoing_gas_limit(&config), config.min_outgoing_gas);
+
+        info.remove_delayed_receipt(config.max_congestion_incoming_gas / 2, 200);
+        info.remove_delayed_receipt(0, config.max_congestion_memory_consumption * 2);
+        assert_eq!(info, CongestionInfo::default());
+        info.remove_delayed_receipt(1, 1);
+        assert_eq!(info, CongestionInfo::default());
+    }
+}
@@ -1,6 +1,6 @@
 use crate::hash::CryptoHash;
 use crate::serialize::dec_format;
-use crate::types::{AccountId, Balance, EpochId, Gas, Nonce};
+use crate::types::{AccountId, Balance, EpochId, Gas, Nonce, ShardId};
 use borsh::{BorshDeserialize, BorshSerialize};
 use std::fmt::{Debug, Display};
 use unc_crypto::PublicKey;
@@ -176,6 +176,8 @@ pub enum InvalidTxError {
     ActionsValidation(ActionsValidationError),
     /// The size of serialized transaction exceeded the limit.
     TransactionSizeExceeded { size: u64, limit: u64 },
+    /// The shard of the receiver is too congested to accept new transactions.
+    ShardCongested { shard_id: ShardId },
 }
 
 impl std::error::Error for InvalidTxError {}
@@ -504,6 +506,9 @@ pub enum ActionErrorKind {
 
     /// The public key used for an  not existed  rsa key
     RsaKeysNotFound { account_id: AccountId, public_key: Box<PublicKey> },
+    /// `UseGlobalContract` points at a code hash that was never published with
+    /// `DeployGlobalContract`.
+    GlobalContractDoesNotExist { code_hash: CryptoHash },
 }
 
 impl From<ActionErrorKind> for ActionError {
@@ -565,6 +570,9 @@ impl Display for InvalidTxError {
             InvalidTxError::TransactionSizeExceeded { size, limit } => {
                 write!(f, "Size of serialized transaction {} exceeded the limit {}", size, limit)
             }
+            InvalidTxError::ShardCongested { shard_id } => {
+                write!(f, "Shard {} is too congested to accept new transactions", shard_id)
+            }
         }
     }
 }
@@ -828,6 +836,9 @@ impl Display for ActionErrorKind {
                 "The public key {:?} is doesn't exist rsa key",
                 public_key
             ),
+            ActionErrorKind::GlobalContractDoesNotExist { code_hash } => {
+                write!(f, "Global contract with code hash {} doesn't exist", code_hash)
+            }
         }
     }
 }
@@ -1102,6 +1113,10 @@ pub enum PrepareError {
     TooManyFunctions,
     /// Contract contains too many locals.
     TooManyLocals,
+    /// Contract contains too many tables.
+    TooManyTables,
+    /// Contract contains a table which may have too many elements.
+    TooManyTableElements,
 }
 
 /// A kind of a trap happened during execution of a binary
@@ -1219,6 +1234,13 @@ pub enum HostError {
     /// Invalid input to ed25519 signature verification function (e.g. signature cannot be
     /// derived from bytes).
     Ed25519VerifyInvalidInput { msg: String },
+    /// Yield payload length exceeds the maximum permitted.
+    YieldPayloadLength { length: u64, limit: u64 },
+    /// Yield resumption data id is malformed.
+    DataIdMalformed,
+    /// Invalid input to bls12381 family of functions (e.g., point which isn't
+    /// on the curve or in the subgroup).
+    Bls12381InvalidInput { msg: String },
 }
 
 #[derive(
@@ -1326,6 +1348,8 @@ impl From<unc_vm_runner::logic::errors::PrepareError> for PrepareError {
             PE::Memory => Self::Memory,
             PE::TooManyFunctions => Self::TooManyFunctions,
             PE::TooManyLocals => Self::TooManyLocals,
+            PE::TooManyTables => Self::TooManyTables,
+            PE::TooManyTableElements => Self::TooManyTableElements,
         }
     }
 }
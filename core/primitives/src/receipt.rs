@@ -1,7 +1,7 @@
 use crate::hash::CryptoHash;
 use crate::serialize::dec_format;
 use crate::transaction::{Action, TransferAction};
-use crate::types::{AccountId, Balance, ShardId};
+use crate::types::{AccountId, Balance, BlockHeight, ShardId};
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde_with::base64::Base64;
 use serde_with::serde_as;
@@ -14,7 +14,8 @@ use unc_fmt::AbbrBytes;
 pub use unc_vm_runner::logic::DataReceiver;
 
 /// Receipts are used for a cross-shard communication.
-/// Receipts could be 2 types (determined by a `ReceiptEnum`): `ReceiptEnum::Action` of `ReceiptEnum::Data`.
+/// Receipts could be 3 types (determined by a `ReceiptEnum`): `ReceiptEnum::Action`, `ReceiptEnum::Data`
+/// or `ReceiptEnum::GlobalContractDistribution`.
 #[derive(
     BorshSerialize,
     BorshDeserialize,
@@ -97,7 +98,7 @@ impl Receipt {
     }
 }
 
-/// Receipt could be either ActionReceipt or DataReceipt
+/// Receipt could be either ActionReceipt, DataReceipt or GlobalContractDistributionReceipt
 #[derive(
     BorshSerialize,
     BorshDeserialize,
@@ -111,6 +112,7 @@ impl Receipt {
 pub enum ReceiptEnum {
     Action(ActionReceipt),
     Data(DataReceipt),
+    GlobalContractDistribution(GlobalContractDistributionReceipt),
 }
 
 /// ActionReceipt is derived from an Action from `Transaction or from Receipt`
@@ -172,6 +174,38 @@ impl fmt::Debug for DataReceipt {
     }
 }
 
+/// Copies a contract published with `DeployGlobalContractAction` into the global contract registry
+/// of another shard. The `receiver_id` of such a receipt only routes it to its shard, see
+/// `shard_layout::shard_routing_account_id`.
+#[serde_as]
+#[derive(
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    PartialEq,
+    Eq,
+    Clone,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct GlobalContractDistributionReceipt {
+    /// WebAssembly binary
+    #[serde_as(as = "Base64")]
+    pub code: Vec<u8>,
+    /// A gas_price at which the publisher prepaid storing the code on the receiving shard
+    #[serde(with = "dec_format")]
+    pub gas_price: Balance,
+}
+
+impl fmt::Debug for GlobalContractDistributionReceipt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GlobalContractDistributionReceipt")
+            .field("code", &format_args!("{}", AbbrBytes(&self.code)))
+            .field("gas_price", &self.gas_price)
+            .finish()
+    }
+}
+
 /// A temporary data which is created by processing of DataReceipt
 /// stored in a state trie with a key = `account_id` + `data_id` until
 /// `input_data_ids` of all incoming Receipts are satisfied
@@ -204,5 +238,31 @@ impl DelayedReceiptIndices {
     }
 }
 
+/// Stores indices for a persistent queue of yielded receipts awaiting resumption or timeout.
+#[derive(Default, BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct PromiseYieldIndices {
+    // First inclusive index in the queue.
+    pub first_index: u64,
+    // Exclusive end index of the queue
+    pub next_available_index: u64,
+}
+
+impl PromiseYieldIndices {
+    pub fn len(&self) -> u64 {
+        self.next_available_index - self.first_index
+    }
+}
+
+/// Entry of the yielded receipts timeout queue.
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct PromiseYieldTimeout {
+    /// The account on which the yielded receipt was created.
+    pub account_id: AccountId,
+    /// The data id on which the yielded receipt is waiting.
+    pub data_id: CryptoHash,
+    /// The block height at which the yield times out if it has not been resumed.
+    pub expires_at: BlockHeight,
+}
+
 /// Map of shard to list of receipts to send to it.
 pub type ReceiptResult = HashMap<ShardId, Vec<Receipt>>;
@@ -1,3 +1,4 @@
+use crate::congestion_info::CongestionControl;
 use crate::runtime::migration_data::{MigrationData, MigrationFlags};
 use crate::{
     hash::CryptoHash,
@@ -42,4 +43,7 @@ pub struct ApplyState {
     pub migration_data: Arc<MigrationData>,
     /// Flags for migrations indicating whether they can be applied at this block
     pub migration_flags: MigrationFlags,
+    /// Congestion of the shards, used to limit the outgoing receipts.
+    /// `None` if the congestion control is not enabled yet.
+    pub congestion_control: Option<CongestionControl>,
 }
@@ -231,6 +231,23 @@ pub fn account_id_to_shard_id(account_id: &AccountId, shard_layout: &ShardLayout
     }
 }
 
+/// Returns an account id which `account_id_to_shard_id` maps to `shard_id`. It is used as the
+/// receiver of receipts which are addressed to a shard rather than to an account, the account
+/// itself does not need to exist.
+pub fn shard_routing_account_id(shard_id: ShardId, shard_layout: &ShardLayout) -> AccountId {
+    match shard_layout {
+        ShardLayout::V0(_) => (0..)
+            .map(|i| format!("shard{shard_id}-{i}").parse().unwrap())
+            .find(|account_id| account_id_to_shard_id(account_id, shard_layout) == shard_id)
+            .unwrap(),
+        ShardLayout::V1(ShardLayoutV1 { boundary_accounts, .. }) => match shard_id {
+            // "00" is the smallest valid account id.
+            0 => "00".parse().unwrap(),
+            _ => boundary_accounts[shard_id as usize - 1].clone(),
+        },
+    }
+}
+
 /// Maps an account to the shard that it belongs to given a shard_layout
 pub fn account_id_to_shard_uid(account_id: &AccountId, shard_layout: &ShardLayout) -> ShardUId {
     ShardUId::from_shard_id_and_layout(
@@ -422,7 +439,9 @@ impl<'de> serde::de::Visitor<'de> for ShardUIdVisitor {
 
 #[cfg(test)]
 mod tests {
-    use crate::shard_layout::{account_id_to_shard_id, ShardLayout, ShardLayoutV1, ShardUId};
+    use crate::shard_layout::{
+        account_id_to_shard_id, shard_routing_account_id, ShardLayout, ShardLayoutV1, ShardUId,
+    };
     use rand::distributions::Alphanumeric;
     use rand::rngs::StdRng;
     use rand::{Rng, SeedableRng};
@@ -508,6 +527,20 @@ mod tests {
         assert_eq!(account_id_to_shard_id(&"zoo".parse().unwrap(), &shard_layout), 5);
     }
 
+    #[test]
+    fn test_shard_routing_account_id() {
+        let layouts = [
+            ShardLayout::v0(4, 0),
+            ShardLayout::v1(parse_account_ids(&["aurora", "bbb", "foo.goo"]), None, 1),
+        ];
+        for shard_layout in layouts {
+            for shard_id in shard_layout.shard_ids() {
+                let account_id = shard_routing_account_id(shard_id, &shard_layout);
+                assert_eq!(account_id_to_shard_id(&account_id, &shard_layout), shard_id);
+            }
+        }
+    }
+
     // check that after removing the fixed shards from the shard layout v1
     // the fixed shards are skipped in deserialization
     // this should be the default as long as serde(deny_unknown_fields) is not set
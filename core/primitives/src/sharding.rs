@@ -1,3 +1,4 @@
+use crate::congestion_info::CongestionInfo;
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::{combine_hash, merklize, verify_path, MerklePath};
 use crate::receipt::Receipt;
@@ -71,6 +72,7 @@ pub mod shard_chunk_header_inner;
 use crate::types::validator_stake::{ValidatorPledge, ValidatorPledgeIter};
 pub use shard_chunk_header_inner::{
     ShardChunkHeaderInner, ShardChunkHeaderInnerV1, ShardChunkHeaderInnerV2,
+    ShardChunkHeaderInnerV3,
 };
 
 #[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
@@ -194,24 +196,45 @@ impl ShardChunkHeaderV3 {
         tx_root: CryptoHash,
         prev_validator_power_proposals: Vec<ValidatorPower>,
         prev_validator_pledge_proposals: Vec<ValidatorPledge>,
+        congestion_info: Option<CongestionInfo>,
         signer: &dyn ValidatorSigner,
     ) -> Self {
-        let inner = ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
-            prev_block_hash,
-            prev_state_root,
-            prev_outcome_root,
-            encoded_merkle_root,
-            encoded_length,
-            height_created: height,
-            shard_id,
-            prev_gas_used,
-            gas_limit,
-            prev_balance_burnt,
-            prev_outgoing_receipts_root,
-            tx_root,
-            prev_validator_power_proposals,
-            prev_validator_pledge_proposals,
-        });
+        let inner = if let Some(congestion_info) = congestion_info {
+            ShardChunkHeaderInner::V3(ShardChunkHeaderInnerV3 {
+                prev_block_hash,
+                prev_state_root,
+                prev_outcome_root,
+                encoded_merkle_root,
+                encoded_length,
+                height_created: height,
+                shard_id,
+                prev_gas_used,
+                gas_limit,
+                prev_balance_burnt,
+                prev_outgoing_receipts_root,
+                tx_root,
+                prev_validator_power_proposals,
+                prev_validator_pledge_proposals,
+                congestion_info,
+            })
+        } else {
+            ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
+                prev_block_hash,
+                prev_state_root,
+                prev_outcome_root,
+                encoded_merkle_root,
+                encoded_length,
+                height_created: height,
+                shard_id,
+                prev_gas_used,
+                gas_limit,
+                prev_balance_burnt,
+                prev_outgoing_receipts_root,
+                tx_root,
+                prev_validator_power_proposals,
+                prev_validator_pledge_proposals,
+            })
+        };
         Self::from_inner(inner, signer)
     }
 
@@ -413,6 +436,16 @@ impl ShardChunkHeader {
         }
     }
 
+    /// Congestion of the shard after the previous chunk. Only set by the
+    /// chunks produced with the `CongestionControl` protocol feature.
+    #[inline]
+    pub fn congestion_info(&self) -> Option<CongestionInfo> {
+        match &self {
+            ShardChunkHeader::V1(_) | ShardChunkHeader::V2(_) => None,
+            ShardChunkHeader::V3(header) => header.inner.congestion_info(),
+        }
+    }
+
     #[inline]
     pub fn chunk_hash(&self) -> ChunkHash {
         match &self {
@@ -431,7 +464,11 @@ impl ShardChunkHeader {
             ShardChunkHeader::V2(_) => {
                 SHARD_CHUNK_HEADER_UPGRADE_VERSION <= version && version < BLOCK_HEADER_V3_VERSION
             }
-            ShardChunkHeader::V3(_) => BLOCK_HEADER_V3_VERSION <= version,
+            ShardChunkHeader::V3(header) => {
+                BLOCK_HEADER_V3_VERSION <= version
+                    && (header.inner.congestion_info().is_none()
+                        || ProtocolFeature::CongestionControl.protocol_version() <= version)
+            }
         }
     }
 
@@ -1065,6 +1102,7 @@ impl EncodedShardChunk {
         transactions: Vec<SignedTransaction>,
         prev_outgoing_receipts: &[Receipt],
         prev_outgoing_receipts_root: CryptoHash,
+        congestion_info: Option<CongestionInfo>,
         signer: &dyn ValidatorSigner,
         protocol_version: ProtocolVersion,
     ) -> Result<(Self, Vec<MerklePath>), std::io::Error> {
@@ -1143,6 +1181,7 @@ impl EncodedShardChunk {
                 tx_root,
                 prev_validator_power_proposals,
                 prev_validator_pledge_proposals,
+                congestion_info,
                 signer,
             );
             let chunk = EncodedShardChunkV2 { header: ShardChunkHeader::V3(header), content };
@@ -1,3 +1,4 @@
+use crate::congestion_info::CongestionInfo;
 use crate::types::validator_power::{ValidatorPower, ValidatorPowerIter, ValidatorPowerV1};
 use crate::types::validator_stake::{ValidatorPledge, ValidatorPledgeIter};
 use crate::types::{StateRoot, ValidatorPledgeV1};
@@ -9,6 +10,7 @@ use unc_primitives_core::types::{Balance, BlockHeight, Gas, ShardId};
 pub enum ShardChunkHeaderInner {
     V1(ShardChunkHeaderInnerV1),
     V2(ShardChunkHeaderInnerV2),
+    V3(ShardChunkHeaderInnerV3),
 }
 
 impl ShardChunkHeaderInner {
@@ -17,6 +19,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.prev_state_root,
             Self::V2(inner) => &inner.prev_state_root,
+            Self::V3(inner) => &inner.prev_state_root,
         }
     }
 
@@ -25,6 +28,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.prev_block_hash,
             Self::V2(inner) => &inner.prev_block_hash,
+            Self::V3(inner) => &inner.prev_block_hash,
         }
     }
 
@@ -33,6 +37,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.gas_limit,
             Self::V2(inner) => inner.gas_limit,
+            Self::V3(inner) => inner.gas_limit,
         }
     }
 
@@ -41,6 +46,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.prev_gas_used,
             Self::V2(inner) => inner.prev_gas_used,
+            Self::V3(inner) => inner.prev_gas_used,
         }
     }
 
@@ -49,6 +55,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => ValidatorPowerIter::v1(&inner.prev_validator_power_proposals),
             Self::V2(inner) => ValidatorPowerIter::new(&inner.prev_validator_power_proposals),
+            Self::V3(inner) => ValidatorPowerIter::new(&inner.prev_validator_power_proposals),
         }
     }
 
@@ -57,6 +64,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => ValidatorPledgeIter::v1(&inner.prev_validator_pledge_proposals),
             Self::V2(inner) => ValidatorPledgeIter::new(&inner.prev_validator_pledge_proposals),
+            Self::V3(inner) => ValidatorPledgeIter::new(&inner.prev_validator_pledge_proposals),
         }
     }
 
@@ -65,6 +73,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.height_created,
             Self::V2(inner) => inner.height_created,
+            Self::V3(inner) => inner.height_created,
         }
     }
 
@@ -73,6 +82,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.shard_id,
             Self::V2(inner) => inner.shard_id,
+            Self::V3(inner) => inner.shard_id,
         }
     }
 
@@ -81,6 +91,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.prev_outcome_root,
             Self::V2(inner) => &inner.prev_outcome_root,
+            Self::V3(inner) => &inner.prev_outcome_root,
         }
     }
 
@@ -89,6 +100,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.encoded_merkle_root,
             Self::V2(inner) => &inner.encoded_merkle_root,
+            Self::V3(inner) => &inner.encoded_merkle_root,
         }
     }
 
@@ -97,6 +109,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.encoded_length,
             Self::V2(inner) => inner.encoded_length,
+            Self::V3(inner) => inner.encoded_length,
         }
     }
 
@@ -105,6 +118,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => inner.prev_balance_burnt,
             Self::V2(inner) => inner.prev_balance_burnt,
+            Self::V3(inner) => inner.prev_balance_burnt,
         }
     }
 
@@ -113,6 +127,15 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.prev_outgoing_receipts_root,
             Self::V2(inner) => &inner.prev_outgoing_receipts_root,
+            Self::V3(inner) => &inner.prev_outgoing_receipts_root,
+        }
+    }
+
+    #[inline]
+    pub fn congestion_info(&self) -> Option<CongestionInfo> {
+        match self {
+            Self::V1(_) | Self::V2(_) => None,
+            Self::V3(inner) => Some(inner.congestion_info),
         }
     }
 
@@ -121,6 +144,7 @@ impl ShardChunkHeaderInner {
         match self {
             Self::V1(inner) => &inner.tx_root,
             Self::V2(inner) => &inner.tx_root,
+            Self::V3(inner) => &inner.tx_root,
         }
     }
 }
@@ -181,3 +205,34 @@ pub struct ShardChunkHeaderInnerV2 {
     /// Validator proposals from the previous chunk.
     pub prev_validator_pledge_proposals: Vec<ValidatorPledge>,
 }
+
+// V2 -> V3: Add congestion info
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ShardChunkHeaderInnerV3 {
+    /// Previous block hash.
+    pub prev_block_hash: CryptoHash,
+    pub prev_state_root: StateRoot,
+    /// Root of the outcomes from execution transactions and results of the previous chunk.
+    pub prev_outcome_root: CryptoHash,
+    pub encoded_merkle_root: CryptoHash,
+    pub encoded_length: u64,
+    pub height_created: BlockHeight,
+    /// Shard index.
+    pub shard_id: ShardId,
+    /// Gas used in the previous chunk.
+    pub prev_gas_used: Gas,
+    /// Gas limit voted by validators.
+    pub gas_limit: Gas,
+    /// Total balance burnt in the previous chunk.
+    pub prev_balance_burnt: Balance,
+    /// Previous chunk's outgoing receipts merkle root.
+    pub prev_outgoing_receipts_root: CryptoHash,
+    /// Tx merkle root.
+    pub tx_root: CryptoHash,
+    /// Validator proposals from the previous chunk.
+    pub prev_validator_power_proposals: Vec<ValidatorPower>,
+    /// Validator proposals from the previous chunk.
+    pub prev_validator_pledge_proposals: Vec<ValidatorPledge>,
+    /// Congestion of the shard after the previous chunk.
+    pub congestion_info: CongestionInfo,
+}
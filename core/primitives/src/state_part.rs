@@ -1,3 +1,7 @@
+use crate::hash::{hash, CryptoHash};
+use crate::types::{EpochId, ShardId, StateRoot};
+use borsh::{BorshDeserialize, BorshSerialize};
+
 // to specify a part we always specify both part_id and num_parts together
 #[derive(Copy, Clone, Debug)]
 pub struct PartId {
@@ -10,3 +14,256 @@ impl PartId {
         PartId { idx: part_id, total: num_parts }
     }
 }
+
+/// Magic bytes that start every `StatePartContainer`.
+///
+/// Raw state parts are a Borsh-serialized `PartialState`, whose first byte is
+/// the enum discriminant `0`, so they can never start with these bytes.
+pub const STATE_PART_CONTAINER_MAGIC: [u8; 4] = *b"UNSP";
+
+/// Upper bound on the uncompressed size of a part declared in a container header.
+/// Protects readers from allocating huge buffers for a corrupted or malicious header.
+pub const MAX_UNCOMPRESSED_STATE_PART_SIZE: u64 = 1 << 30;
+
+/// Compression level passed to zstd when packing a part.
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatePartCompression {
+    None,
+    Zstd,
+}
+
+/// Describes the part stored in a `StatePartContainer`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StatePartHeaderV1 {
+    pub shard_id: ShardId,
+    pub epoch_id: EpochId,
+    pub part_id: u64,
+    pub num_parts: u64,
+    pub state_root: StateRoot,
+    pub compression: StatePartCompression,
+    /// Size of the raw part, i.e. after decompression.
+    pub uncompressed_size: u64,
+    /// Hash of the raw part, i.e. after decompression.
+    pub content_hash: CryptoHash,
+}
+
+/// Versioned envelope for a state part stored in external storage.
+///
+/// Serialized as `STATE_PART_CONTAINER_MAGIC` followed by the Borsh
+/// representation of the container.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum StatePartContainer {
+    V1 { header: StatePartHeaderV1, data: Vec<u8> },
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StatePartContainerError {
+    #[error("state part container doesn't start with the expected magic bytes")]
+    MissingMagic,
+    #[error("failed to decode state part container: {0}")]
+    Decode(std::io::Error),
+    #[error("failed to compress state part: {0}")]
+    Compression(std::io::Error),
+    #[error("failed to decompress state part: {0}")]
+    Decompression(std::io::Error),
+    #[error("state part declares uncompressed size {0} which exceeds the limit")]
+    TooLarge(u64),
+    #[error("state part header mismatch in {field}: expected {expected}, got {actual}")]
+    HeaderMismatch { field: &'static str, expected: String, actual: String },
+    #[error("state part size mismatch: expected {expected}, got {actual}")]
+    SizeMismatch { expected: u64, actual: u64 },
+    #[error("state part content hash mismatch: expected {expected}, got {actual}")]
+    HashMismatch { expected: CryptoHash, actual: CryptoHash },
+}
+
+impl StatePartContainer {
+    /// Compresses the raw part `data` and wraps it into a container.
+    pub fn new(
+        shard_id: ShardId,
+        epoch_id: EpochId,
+        part_id: PartId,
+        state_root: StateRoot,
+        data: &[u8],
+    ) -> Result<Self, StatePartContainerError> {
+        let compressed = zstd::bulk::compress(data, ZSTD_COMPRESSION_LEVEL)
+            .map_err(StatePartContainerError::Compression)?;
+        let header = StatePartHeaderV1 {
+            shard_id,
+            epoch_id,
+            part_id: part_id.idx,
+            num_parts: part_id.total,
+            state_root,
+            compression: StatePartCompression::Zstd,
+            uncompressed_size: data.len() as u64,
+            content_hash: hash(data),
+        };
+        Ok(Self::V1 { header, data: compressed })
+    }
+
+    /// Returns true if `bytes` look like a serialized container rather than a raw part.
+    pub fn is_container(bytes: &[u8]) -> bool {
+        bytes.starts_with(&STATE_PART_CONTAINER_MAGIC)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = STATE_PART_CONTAINER_MAGIC.to_vec();
+        // Serializing into a Vec can't fail.
+        borsh::to_writer(&mut bytes, self).unwrap();
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StatePartContainerError> {
+        let body = bytes
+            .strip_prefix(&STATE_PART_CONTAINER_MAGIC)
+            .ok_or(StatePartContainerError::MissingMagic)?;
+        Self::try_from_slice(body).map_err(StatePartContainerError::Decode)
+    }
+
+    pub fn header(&self) -> &StatePartHeaderV1 {
+        match self {
+            Self::V1 { header, .. } => header,
+        }
+    }
+
+    /// Checks that the header describes the expected part.
+    pub fn verify_header(
+        &self,
+        shard_id: ShardId,
+        epoch_id: &EpochId,
+        part_id: PartId,
+        state_root: &StateRoot,
+    ) -> Result<(), StatePartContainerError> {
+        fn check<T: PartialEq + std::fmt::Debug>(
+            field: &'static str,
+            expected: &T,
+            actual: &T,
+        ) -> Result<(), StatePartContainerError> {
+            if expected == actual {
+                return Ok(());
+            }
+            Err(StatePartContainerError::HeaderMismatch {
+                field,
+                expected: format!("{expected:?}"),
+                actual: format!("{actual:?}"),
+            })
+        }
+        let header = self.header();
+        check("shard_id", &shard_id, &header.shard_id)?;
+        check("epoch_id", epoch_id, &header.epoch_id)?;
+        check("part_id", &part_id.idx, &header.part_id)?;
+        check("num_parts", &part_id.total, &header.num_parts)?;
+        check("state_root", state_root, &header.state_root)
+    }
+
+    /// Decompresses the part and checks it against the size and hash in the header.
+    pub fn into_data(self) -> Result<Vec<u8>, StatePartContainerError> {
+        let Self::V1 { header, data } = self;
+        if header.uncompressed_size > MAX_UNCOMPRESSED_STATE_PART_SIZE {
+            return Err(StatePartContainerError::TooLarge(header.uncompressed_size));
+        }
+        let data = match header.compression {
+            StatePartCompression::None => data,
+            StatePartCompression::Zstd => {
+                zstd::bulk::decompress(&data, header.uncompressed_size as usize)
+                    .map_err(StatePartContainerError::Decompression)?
+            }
+        };
+        if data.len() as u64 != header.uncompressed_size {
+            return Err(StatePartContainerError::SizeMismatch {
+                expected: header.uncompressed_size,
+                actual: data.len() as u64,
+            });
+        }
+        let actual = hash(&data);
+        if actual != header.content_hash {
+            return Err(StatePartContainerError::HashMismatch {
+                expected: header.content_hash,
+                actual,
+            });
+        }
+        Ok(data)
+    }
+}
+
+/// Returns the raw part stored in `bytes`, which is either a raw part or a
+/// `StatePartContainer` that must describe the given part.
+pub fn unpack_state_part(
+    bytes: Vec<u8>,
+    shard_id: ShardId,
+    epoch_id: &EpochId,
+    part_id: PartId,
+    state_root: &StateRoot,
+) -> Result<Vec<u8>, StatePartContainerError> {
+    if !StatePartContainer::is_container(&bytes) {
+        return Ok(bytes);
+    }
+    let container = StatePartContainer::from_bytes(&bytes)?;
+    container.verify_header(shard_id, epoch_id, part_id, state_root)?;
+    container.into_data()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_container(data: &[u8]) -> StatePartContainer {
+        StatePartContainer::new(1, EpochId(hash(b"epoch")), PartId::new(2, 5), hash(b"root"), data)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_state_part_container_roundtrip() {
+        let data = vec![7u8; 10_000];
+        let bytes = test_container(&data).to_bytes();
+        assert!(StatePartContainer::is_container(&bytes));
+        assert!(bytes.len() < data.len());
+
+        let unpacked = unpack_state_part(
+            bytes,
+            1,
+            &EpochId(hash(b"epoch")),
+            PartId::new(2, 5),
+            &hash(b"root"),
+        )
+        .unwrap();
+        assert_eq!(unpacked, data);
+
+        // Raw parts are passed through as is.
+        let raw = borsh::to_vec(&crate::challenge::PartialState::TrieValues(vec![])).unwrap();
+        assert!(!StatePartContainer::is_container(&raw));
+        let unpacked =
+            unpack_state_part(raw.clone(), 0, &EpochId::default(), PartId::new(0, 1), &hash(b""))
+                .unwrap();
+        assert_eq!(unpacked, raw);
+    }
+
+    #[test]
+    fn test_state_part_container_detects_mismatches() {
+        let container = test_container(b"some part");
+        let epoch_id = EpochId(hash(b"epoch"));
+        assert!(matches!(
+            container.verify_header(1, &epoch_id, PartId::new(3, 5), &hash(b"root")),
+            Err(StatePartContainerError::HeaderMismatch { field: "part_id", .. })
+        ));
+        assert!(matches!(
+            container.verify_header(1, &epoch_id, PartId::new(2, 5), &hash(b"other root")),
+            Err(StatePartContainerError::HeaderMismatch { field: "state_root", .. })
+        ));
+
+        let StatePartContainer::V1 { mut header, data } = container;
+        header.content_hash = hash(b"corrupted");
+        assert!(matches!(
+            StatePartContainer::V1 { header, data }.into_data(),
+            Err(StatePartContainerError::HashMismatch { .. })
+        ));
+
+        let mut bytes = test_container(b"some part").to_bytes();
+        bytes[0] = b'X';
+        assert!(matches!(
+            StatePartContainer::from_bytes(&bytes),
+            Err(StatePartContainerError::MissingMagic)
+        ));
+    }
+}
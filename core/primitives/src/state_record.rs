@@ -1,14 +1,17 @@
 use crate::account::{AccessKey, Account};
+use crate::action::RegisterRsa2048KeysAction;
 use crate::hash::{hash, CryptoHash};
 use crate::receipt::{Receipt, ReceivedData};
 use crate::trie_key::trie_key_parsers::{
     parse_account_id_from_access_key_key, parse_account_id_from_account_key,
     parse_account_id_from_contract_code_key, parse_account_id_from_contract_data_key,
-    parse_account_id_from_received_data_key, parse_data_id_from_received_data_key,
-    parse_data_key_from_contract_data_key, parse_public_key_from_access_key_key,
+    parse_account_id_from_promise_yield_receipt_key, parse_account_id_from_received_data_key,
+    parse_account_id_from_rsa_key_key, parse_data_id_from_promise_yield_receipt_key,
+    parse_data_id_from_received_data_key, parse_data_key_from_contract_data_key,
+    parse_public_key_from_access_key_key, parse_public_key_from_rsa_key_key,
 };
 use crate::trie_key::{col, TrieKey};
-use crate::types::{AccountId, StoreKey, StoreValue};
+use crate::types::{AccountId, BlockHeight, StoreKey, StoreValue};
 use borsh::BorshDeserialize;
 use serde_with::base64::Base64;
 use serde_with::serde_as;
@@ -43,6 +46,13 @@ pub enum StateRecord {
     /// Delayed Receipt.
     /// The receipt was delayed because the shard was overwhelmed.
     DelayedReceipt(Box<Receipt>),
+    /// RSA2048 chip key registered for some account. Chips held by the root account are not
+    /// claimed yet, chips held by any other account contribute to its power.
+    Rsa2048Keys { account_id: AccountId, public_key: PublicKey, rsa_key: RegisterRsa2048KeysAction },
+    /// Yield of the given account waiting for the data with the given data_id until it is resumed
+    /// or the block height `expires_at`. The yielded receipt itself is a postponed receipt, the
+    /// timeout queue is rebuilt from these records.
+    PromiseYieldReceipt { account_id: AccountId, data_id: CryptoHash, expires_at: BlockHeight },
 }
 
 impl StateRecord {
@@ -103,6 +113,21 @@ impl StateRecord {
                 let receipt = Receipt::try_from_slice(&value)?;
                 Some(StateRecord::DelayedReceipt(Box::new(receipt)))
             }
+            col::RSA2048_KEY => {
+                let rsa_key = RegisterRsa2048KeysAction::try_from_slice(&value)?;
+                let account_id = parse_account_id_from_rsa_key_key(&key)?;
+                let public_key = parse_public_key_from_rsa_key_key(&key, &account_id)?;
+                Some(StateRecord::Rsa2048Keys { account_id, public_key, rsa_key })
+            }
+            col::PROMISE_YIELD_INDICES => None,
+            col::PROMISE_YIELD_TIMEOUT => None,
+            col::PROMISE_YIELD_RECEIPT => {
+                let expires_at = BlockHeight::try_from_slice(&value)?;
+                let account_id = parse_account_id_from_promise_yield_receipt_key(&key)?;
+                let data_id = parse_data_id_from_promise_yield_receipt_key(&key, &account_id)?;
+                Some(StateRecord::PromiseYieldReceipt { account_id, data_id, expires_at })
+            }
+            col::GLOBAL_CONTRACT_CODE => None,
             _ => {
                 println!("key[0]: {} is unreachable", key[0]);
                 None
@@ -119,6 +144,8 @@ impl StateRecord {
             StateRecord::PostponedReceipt { .. } => "PostponedReceipt",
             StateRecord::ReceivedData { .. } => "ReceivedData",
             StateRecord::DelayedReceipt { .. } => "DelayedReceipt",
+            StateRecord::Rsa2048Keys { .. } => "Rsa2048Keys",
+            StateRecord::PromiseYieldReceipt { .. } => "PromiseYieldReceipt",
         }
         .to_string()
     }
@@ -152,6 +179,12 @@ impl Display for StateRecord {
             ),
             StateRecord::PostponedReceipt(receipt) => write!(f, "Postponed receipt {:?}", receipt),
             StateRecord::DelayedReceipt(receipt) => write!(f, "Delayed receipt {:?}", receipt),
+            StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                write!(f, "Rsa2048 key {:?},{:?}: {:?}", account_id, public_key, rsa_key)
+            }
+            StateRecord::PromiseYieldReceipt { account_id, data_id, expires_at } => {
+                write!(f, "Promise yield {:?},{:?}: expires at {}", account_id, data_id, expires_at)
+            }
         }
     }
 }
@@ -177,7 +210,9 @@ pub fn state_record_to_account_id(state_record: &StateRecord) -> &AccountId {
         | StateRecord::AccessKey { account_id, .. }
         | StateRecord::Contract { account_id, .. }
         | StateRecord::ReceivedData { account_id, .. }
-        | StateRecord::Data { account_id, .. } => account_id,
+        | StateRecord::Data { account_id, .. }
+        | StateRecord::Rsa2048Keys { account_id, .. }
+        | StateRecord::PromiseYieldReceipt { account_id, .. } => account_id,
         StateRecord::PostponedReceipt(receipt) | StateRecord::DelayedReceipt(receipt) => {
             &receipt.receiver_id
         }
@@ -188,3 +223,59 @@ pub fn is_contract_code_key(key: &[u8]) -> bool {
     debug_assert!(!key.is_empty());
     key[0] == col::CONTRACT_CODE
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unc_crypto::KeyType;
+
+    #[test]
+    fn test_rsa2048_keys_record_roundtrip() {
+        let account_id: AccountId = "miner.unc".parse().unwrap();
+        let public_key = PublicKey::empty(KeyType::RSA2048);
+        let rsa_key = RegisterRsa2048KeysAction {
+            public_key: public_key.clone(),
+            operation_type: 0,
+            args: br#"{"power":"100"}"#.to_vec(),
+        };
+        let key =
+            TrieKey::Rsa2048Keys { account_id: account_id.clone(), public_key: public_key.clone() };
+        let value = borsh::to_vec(&rsa_key).unwrap();
+
+        let record = StateRecord::from_raw_key_value(key.to_vec(), value).unwrap();
+        assert_eq!(
+            record,
+            StateRecord::Rsa2048Keys { account_id: account_id.clone(), public_key, rsa_key }
+        );
+        assert_eq!(state_record_to_account_id(&record), &account_id);
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(serde_json::from_str::<StateRecord>(&json).unwrap(), record);
+    }
+
+    #[test]
+    fn test_promise_yield_receipt_record_roundtrip() {
+        let account_id: AccountId = "contract.unc".parse().unwrap();
+        let data_id = hash(b"data");
+        let key = TrieKey::PromiseYieldReceipt { receiver_id: account_id.clone(), data_id };
+        let value = borsh::to_vec(&42u64).unwrap();
+
+        let record = StateRecord::from_raw_key_value(key.to_vec(), value).unwrap();
+        assert_eq!(
+            record,
+            StateRecord::PromiseYieldReceipt {
+                account_id: account_id.clone(),
+                data_id,
+                expires_at: 42
+            }
+        );
+        assert_eq!(state_record_to_account_id(&record), &account_id);
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(serde_json::from_str::<StateRecord>(&json).unwrap(), record);
+
+        // The timeout queue is not dumped, it is rebuilt from the yields.
+        let key = TrieKey::PromiseYieldTimeout { index: 0 };
+        assert_eq!(StateRecord::from_raw_key_value(key.to_vec(), vec![]), None);
+    }
+}
@@ -47,8 +47,19 @@ pub mod col {
     pub const CONTRACT_DATA: u8 = 9;
 
     pub const RSA2048_KEY: u8 = 10;
+    /// This column id is used when storing the indices of the yielded receipts timeout queue
+    /// (a singleton per shard).
+    pub const PROMISE_YIELD_INDICES: u8 = 11;
+    /// This column id is used when storing the timeout queue entries of the yielded receipts.
+    pub const PROMISE_YIELD_TIMEOUT: u8 = 12;
+    /// This column id is used when storing the marker of a pending yielded receipt for a given
+    /// `account_id` and `data_id`.
+    pub const PROMISE_YIELD_RECEIPT: u8 = 13;
+    /// This column id is used when storing contract blobs published into the global contract
+    /// registry for a given `code_hash`.
+    pub const GLOBAL_CONTRACT_CODE: u8 = 14;
     /// All columns
-    pub const NON_DELAYED_RECEIPT_COLUMNS: [(u8, &str); 8] = [
+    pub const NON_DELAYED_RECEIPT_COLUMNS: [(u8, &str); 9] = [
         (ACCOUNT, "Account"),
         (CONTRACT_CODE, "ContractCode"),
         (ACCESS_KEY, "AccessKey"),
@@ -57,6 +68,7 @@ pub mod col {
         (PENDING_DATA_COUNT, "PendingDataCount"),
         (POSTPONED_RECEIPT, "PostponedReceipt"),
         (CONTRACT_DATA, "ContractData"),
+        (PROMISE_YIELD_RECEIPT, "PromiseYieldReceipt"),
     ];
 }
 
@@ -98,6 +110,19 @@ pub enum TrieKey {
 
     ///ca rsakeys
     Rsa2048Keys { account_id: AccountId, public_key: PublicKey },
+    /// Used to store indices of the yielded receipts timeout queue
+    /// (`primitives::receipt::PromiseYieldIndices`).
+    /// NOTE: It is a singleton per shard.
+    PromiseYieldIndices,
+    /// Used to store a timeout queue entry `primitives::receipt::PromiseYieldTimeout` for a given
+    /// index `u64`. The queue is unique per shard.
+    PromiseYieldTimeout { index: u64 },
+    /// Used to mark a yielded receipt for a given receiver's `AccountId` and a given `data_id`
+    /// as pending resumption. Stores the `BlockHeight` at which the yield times out.
+    PromiseYieldReceipt { receiver_id: AccountId, data_id: CryptoHash },
+    /// Used to store `Vec<u8>` contract code published into the global contract registry for a
+    /// given `code_hash`. Every shard keeps its own copy of the registry.
+    GlobalContractCode { code_hash: CryptoHash },
 }
 
 /// Provides `len` function.
@@ -160,6 +185,19 @@ impl TrieKey {
             TrieKey::Rsa2048Keys { account_id, public_key } => {
                 col::RSA2048_KEY.len() * 2 + account_id.len() + public_key.len()
             }
+            TrieKey::PromiseYieldIndices => col::PROMISE_YIELD_INDICES.len(),
+            TrieKey::PromiseYieldTimeout { .. } => {
+                col::PROMISE_YIELD_TIMEOUT.len() + size_of::<u64>()
+            }
+            TrieKey::PromiseYieldReceipt { receiver_id, data_id } => {
+                col::PROMISE_YIELD_RECEIPT.len()
+                    + receiver_id.len()
+                    + ACCOUNT_DATA_SEPARATOR.len()
+                    + data_id.as_ref().len()
+            }
+            TrieKey::GlobalContractCode { code_hash } => {
+                col::GLOBAL_CONTRACT_CODE.len() + code_hash.as_ref().len()
+            }
         }
     }
 
@@ -225,6 +263,23 @@ impl TrieKey {
                 buf.push(RSA2048_KEY_SEPARATOR);
                 buf.extend(borsh::to_vec(&public_key).unwrap());
             }
+            TrieKey::PromiseYieldIndices => {
+                buf.push(col::PROMISE_YIELD_INDICES);
+            }
+            TrieKey::PromiseYieldTimeout { index } => {
+                buf.push(col::PROMISE_YIELD_TIMEOUT);
+                buf.extend(&index.to_le_bytes());
+            }
+            TrieKey::PromiseYieldReceipt { receiver_id, data_id } => {
+                buf.push(col::PROMISE_YIELD_RECEIPT);
+                buf.extend(receiver_id.as_bytes());
+                buf.push(ACCOUNT_DATA_SEPARATOR);
+                buf.extend(data_id.as_ref());
+            }
+            TrieKey::GlobalContractCode { code_hash } => {
+                buf.push(col::GLOBAL_CONTRACT_CODE);
+                buf.extend(code_hash.as_ref());
+            }
         };
         debug_assert_eq!(expected_len, buf.len() - start_len);
     }
@@ -249,6 +304,10 @@ impl TrieKey {
             TrieKey::DelayedReceipt { .. } => None,
             TrieKey::ContractData { account_id, .. } => Some(account_id.clone()),
             TrieKey::Rsa2048Keys { account_id, .. } => Some(account_id.clone()),
+            TrieKey::PromiseYieldIndices => None,
+            TrieKey::PromiseYieldTimeout { .. } => None,
+            TrieKey::PromiseYieldReceipt { receiver_id, .. } => Some(receiver_id.clone()),
+            TrieKey::GlobalContractCode { .. } => None,
         }
     }
 }
@@ -439,6 +498,35 @@ pub mod trie_key_parsers {
         })
     }
 
+    pub fn parse_account_id_from_promise_yield_receipt_key(
+        raw_key: &[u8],
+    ) -> Result<AccountId, std::io::Error> {
+        parse_account_id_from_trie_key_with_separator(
+            col::PROMISE_YIELD_RECEIPT,
+            raw_key,
+            "PromiseYieldReceipt",
+        )
+    }
+
+    pub fn parse_data_id_from_promise_yield_receipt_key(
+        raw_key: &[u8],
+        account_id: &AccountId,
+    ) -> Result<CryptoHash, std::io::Error> {
+        let prefix_len = col::PROMISE_YIELD_RECEIPT.len() * 2 + account_id.len();
+        if raw_key.len() < prefix_len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "raw key is too short for TrieKey::PromiseYieldReceipt",
+            ));
+        }
+        CryptoHash::try_from(&raw_key[prefix_len..]).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Can't parse CryptoHash for TrieKey::PromiseYieldReceipt",
+            )
+        })
+    }
+
     pub fn get_raw_prefix_for_rsa_keys(account_id: &AccountId) -> Vec<u8> {
         let mut res = Vec::with_capacity(col::RSA2048_KEY.len() * 2 + account_id.len());
         res.push(col::RSA2048_KEY);
@@ -706,6 +794,42 @@ mod tests {
         assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
     }
 
+    #[test]
+    fn test_key_for_promise_yield_receipt_consistency() {
+        for account_id in OK_ACCOUNT_IDS.iter().map(|x| x.parse::<AccountId>().unwrap()) {
+            let key = TrieKey::PromiseYieldReceipt {
+                receiver_id: account_id.clone(),
+                data_id: CryptoHash::default(),
+            };
+            let raw_key = key.to_vec();
+            assert_eq!(raw_key.len(), key.len());
+            assert_eq!(
+                trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().unwrap(),
+                account_id
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_for_promise_yield_timeout_consistency() {
+        let key = TrieKey::PromiseYieldIndices;
+        let raw_key = key.to_vec();
+        assert_eq!(raw_key.len(), key.len());
+        assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+        let key = TrieKey::PromiseYieldTimeout { index: 0 };
+        let raw_key = key.to_vec();
+        assert_eq!(raw_key.len(), key.len());
+        assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_key_for_global_contract_code_consistency() {
+        let key = TrieKey::GlobalContractCode { code_hash: CryptoHash::hash_bytes(b"code") };
+        let raw_key = key.to_vec();
+        assert_eq!(raw_key.len(), key.len());
+        assert!(trie_key_parsers::parse_account_id_from_raw_key(&raw_key).unwrap().is_none());
+    }
+
     #[test]
     fn test_account_id_from_trie_key() {
         for account_id_str in OK_ACCOUNT_IDS {
@@ -764,6 +888,20 @@ mod tests {
                 None
             );
             assert_eq!(TrieKey::DelayedReceiptIndices.get_account_id(), None);
+            assert_eq!(
+                TrieKey::PromiseYieldReceipt {
+                    receiver_id: account_id.clone(),
+                    data_id: Default::default()
+                }
+                .get_account_id(),
+                Some(account_id.clone())
+            );
+            assert_eq!(TrieKey::PromiseYieldTimeout { index: 0 }.get_account_id(), None);
+            assert_eq!(TrieKey::PromiseYieldIndices.get_account_id(), None);
+            assert_eq!(
+                TrieKey::GlobalContractCode { code_hash: Default::default() }.get_account_id(),
+                None
+            );
             assert_eq!(
                 TrieKey::ContractData { account_id: account_id.clone(), key: Default::default() }
                     .get_account_id(),
@@ -413,6 +413,10 @@ impl StateChanges {
                 TrieKey::PostponedReceipt { .. } => {}
                 TrieKey::DelayedReceiptIndices => {}
                 TrieKey::DelayedReceipt { .. } => {}
+                TrieKey::PromiseYieldIndices => {}
+                TrieKey::PromiseYieldTimeout { .. } => {}
+                TrieKey::PromiseYieldReceipt { .. } => {}
+                TrieKey::GlobalContractCode { .. } => {}
                 TrieKey::Rsa2048Keys { account_id, public_key } => {
                     state_changes.extend(changes.into_iter().map(
                         |RawStateChange { cause, data }| StateChangeWithCause {
@@ -1153,6 +1157,7 @@ pub struct BlockExtra {
 }
 
 pub mod chunk_extra {
+    use crate::congestion_info::CongestionInfo;
     use crate::types::validator_power::{ValidatorPower, ValidatorPowerIter};
     use crate::types::validator_stake::{ValidatorPledge, ValidatorPledgeIter};
     use crate::types::StateRoot;
@@ -1167,6 +1172,7 @@ pub mod chunk_extra {
     pub enum ChunkExtra {
         V1(ChunkExtraV1),
         V2(ChunkExtraV2),
+        V3(ChunkExtraV3),
     }
 
     #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize, Clone, Eq)]
@@ -1187,9 +1193,30 @@ pub mod chunk_extra {
         pub balance_burnt: Balance,
     }
 
+    /// V2 -> V3: add congestion info of the shard.
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize, Clone, Eq)]
+    pub struct ChunkExtraV3 {
+        /// Post state root after applying give chunk.
+        pub state_root: StateRoot,
+        /// Root of merklizing results of receipts (transactions) execution.
+        pub outcome_root: CryptoHash,
+        /// Validator proposals produced by given chunk.
+        pub validator_power_proposals: Vec<ValidatorPower>,
+        /// Validator proposals produced by given chunk.
+        pub validator_pledge_proposals: Vec<ValidatorPledge>,
+        /// Actually how much gas were used.
+        pub gas_used: Gas,
+        /// Gas limit, allows to increase or decrease limit based on expected time vs real time for computing the chunk.
+        pub gas_limit: Gas,
+        /// Total balance burnt after processing the current chunk.
+        pub balance_burnt: Balance,
+        /// Congestion of the shard after processing the current chunk.
+        pub congestion_info: CongestionInfo,
+    }
+
     impl ChunkExtra {
         pub fn new_with_only_state_root(state_root: &StateRoot) -> Self {
-            Self::new(state_root, CryptoHash::default(), vec![], vec![], 0, 0, 0)
+            Self::new(state_root, CryptoHash::default(), vec![], vec![], 0, 0, 0, None)
         }
 
         pub fn new(
@@ -1200,16 +1227,29 @@ pub mod chunk_extra {
             gas_used: Gas,
             gas_limit: Gas,
             balance_burnt: Balance,
+            congestion_info: Option<CongestionInfo>,
         ) -> Self {
-            Self::V2(ChunkExtraV2 {
-                state_root: *state_root,
-                outcome_root,
-                validator_power_proposals,
-                validator_pledge_proposals,
-                gas_used,
-                gas_limit,
-                balance_burnt,
-            })
+            match congestion_info {
+                Some(congestion_info) => Self::V3(ChunkExtraV3 {
+                    state_root: *state_root,
+                    outcome_root,
+                    validator_power_proposals,
+                    validator_pledge_proposals,
+                    gas_used,
+                    gas_limit,
+                    balance_burnt,
+                    congestion_info,
+                }),
+                None => Self::V2(ChunkExtraV2 {
+                    state_root: *state_root,
+                    outcome_root,
+                    validator_power_proposals,
+                    validator_pledge_proposals,
+                    gas_used,
+                    gas_limit,
+                    balance_burnt,
+                }),
+            }
         }
 
         #[inline]
@@ -1217,6 +1257,7 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => &v1.outcome_root,
                 Self::V2(v2) => &v2.outcome_root,
+                Self::V3(v3) => &v3.outcome_root,
             }
         }
 
@@ -1225,6 +1266,7 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => &v1.state_root,
                 Self::V2(v2) => &v2.state_root,
+                Self::V3(v3) => &v3.state_root,
             }
         }
 
@@ -1233,6 +1275,7 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => &mut v1.state_root,
                 Self::V2(v2) => &mut v2.state_root,
+                Self::V3(v3) => &mut v3.state_root,
             }
         }
 
@@ -1241,6 +1284,7 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => ValidatorPowerIter::v1(&v1.validator_power_proposals),
                 Self::V2(v2) => ValidatorPowerIter::new(&v2.validator_power_proposals),
+                Self::V3(v3) => ValidatorPowerIter::new(&v3.validator_power_proposals),
             }
         }
 
@@ -1249,6 +1293,7 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => ValidatorPledgeIter::v1(&v1.validator_pledge_proposals),
                 Self::V2(v2) => ValidatorPledgeIter::new(&v2.validator_pledge_proposals),
+                Self::V3(v3) => ValidatorPledgeIter::new(&v3.validator_pledge_proposals),
             }
         }
 
@@ -1257,6 +1302,7 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => v1.gas_limit,
                 Self::V2(v2) => v2.gas_limit,
+                Self::V3(v3) => v3.gas_limit,
             }
         }
 
@@ -1265,6 +1311,7 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => v1.gas_used,
                 Self::V2(v2) => v2.gas_used,
+                Self::V3(v3) => v3.gas_used,
             }
         }
 
@@ -1273,6 +1320,15 @@ pub mod chunk_extra {
             match self {
                 Self::V1(v1) => v1.balance_burnt,
                 Self::V2(v2) => v2.balance_burnt,
+                Self::V3(v3) => v3.balance_burnt,
+            }
+        }
+
+        #[inline]
+        pub fn congestion_info(&self) -> Option<CongestionInfo> {
+            match self {
+                Self::V1(_) | Self::V2(_) => None,
+                Self::V3(v3) => Some(v3.congestion_info),
             }
         }
     }
@@ -256,6 +256,25 @@ pub fn create_receipt_id_from_receipt(
     )
 }
 
+/// Creates a new Receipt ID for the data receipt that resolves a timed out yield, from its
+/// `data_id`, a block hash and a new receipt index.
+/// This method is backward compatible, so it takes the current protocol version.
+pub fn create_receipt_id_from_data_id(
+    protocol_version: ProtocolVersion,
+    data_id: &CryptoHash,
+    prev_block_hash: &CryptoHash,
+    block_hash: &CryptoHash,
+    receipt_index: usize,
+) -> CryptoHash {
+    create_hash_upgradable(
+        protocol_version,
+        data_id,
+        prev_block_hash,
+        block_hash,
+        receipt_index as u64,
+    )
+}
+
 /// Creates a new action_hash from a given receipt, a block hash and an action index.
 /// This method is backward compatible, so it takes the current protocol version.
 pub fn create_action_hash(
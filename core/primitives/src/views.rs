@@ -13,15 +13,19 @@ use crate::block_header::{
 use crate::block_header::{BlockHeaderInnerRestV4, BlockHeaderV4};
 use crate::challenge::{Challenge, ChallengesResult};
 use crate::checked_feature;
+use crate::congestion_info::CongestionInfo;
 use crate::errors::TxExecutionError;
 use crate::hash::{hash, CryptoHash};
 use crate::merkle::{combine_hash, MerklePath};
 use crate::network::PeerId;
-use crate::receipt::{ActionReceipt, DataReceipt, DataReceiver, Receipt, ReceiptEnum};
+use crate::receipt::{
+    ActionReceipt, DataReceipt, DataReceiver, GlobalContractDistributionReceipt, Receipt,
+    ReceiptEnum,
+};
 use crate::serialize::dec_format;
 use crate::sharding::{
     ChunkHash, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner, ShardChunkHeaderInnerV2,
-    ShardChunkHeaderV3,
+    ShardChunkHeaderInnerV3, ShardChunkHeaderV3,
 };
 use crate::transaction::{
     Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
@@ -36,7 +40,10 @@ use crate::types::{
     ValidatorKickoutReason,
 };
 
-use crate::action::{CreateRsa2048ChallengeAction, RegisterRsa2048KeysAction};
+use crate::action::{
+    CreateRsa2048ChallengeAction, DeployGlobalContractAction, RegisterRsa2048KeysAction,
+    UseGlobalContractAction,
+};
 use crate::types::validator_power_and_pledge::{
     ValidatorPowerAndPledge, ValidatorPowerAndPledgeIter,
 };
@@ -54,9 +61,10 @@ use std::sync::Arc;
 use strum::IntoEnumIterator;
 use unc_crypto::{PublicKey, Signature};
 use unc_fmt::{AbbrBytes, Slice};
-use unc_parameters::{ActionCosts, ExtCosts};
+use unc_parameters::{ActionCosts, CongestionControlConfig, ExtCosts};
+pub use unc_vm_runner::debugger::{DebugOptions, DebugTrace};
 use unc_vm_runner::logic::CompiledContractCache;
-use unc_vm_runner::ContractCode;
+use unc_vm_runner::{ContractCode, FunctionGasProfile};
 use validator_power_view::ValidatorPowerView;
 
 /// A view of the account
@@ -246,6 +254,54 @@ pub struct ViewStateResult {
 pub struct CallResult {
     pub result: Vec<u8>,
     pub logs: Vec<String>,
+    /// Gas burnt by each contract function, present only if requested with the call from a
+    /// sandbox node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<FunctionGasProfileView>,
+}
+
+/// Gas burnt on Wasm instructions by each function of the called contract.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct FunctionGasProfileView {
+    pub functions: Vec<FunctionGasView>,
+    /// The same profile in the folded stacks format understood by flamegraph tools.
+    pub folded_stacks: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct FunctionGasView {
+    /// Index of the function within the contract.
+    pub index: u32,
+    /// Name of the function from the contract's `name` section, or a placeholder with its index.
+    pub name: String,
+    #[serde(with = "dec_format")]
+    pub gas_used: Gas,
+}
+
+impl From<FunctionGasProfile> for FunctionGasProfileView {
+    fn from(profile: FunctionGasProfile) -> Self {
+        let functions = profile
+            .functions()
+            .map(|(index, gas_used)| FunctionGasView {
+                index,
+                name: profile.function_name(index),
+                gas_used,
+            })
+            .collect();
+        Self { functions, folded_stacks: profile.to_folded_stacks() }
+    }
+}
+
+/// Result of a debug call, which only sandbox nodes can run.
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DebugCallResult {
+    pub result: Vec<u8>,
+    pub logs: Vec<String>,
+    #[serde(with = "dec_format")]
+    pub burnt_gas: Gas,
+    /// Why the call failed, if it did. The trace is recorded up to the failure either way.
+    pub error: Option<String>,
+    pub trace: DebugTrace,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -327,6 +383,7 @@ pub enum QueryResponseKind {
     AccessKey(AccessKeyView),
     AccessKeyList(AccessKeyList),
     ChipList(ChipsList),
+    DebugCallResult(DebugCallResult),
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -360,6 +417,20 @@ pub enum QueryRequest {
         method_name: String,
         #[serde(rename = "args_base64")]
         args: FunctionArgs,
+        /// Whether to break down the gas burnt on Wasm instructions by contract function. Only
+        /// sandbox nodes collect the profile; other nodes run the call without it.
+        #[serde(default, skip_serializing_if = "is_false")]
+        profile: bool,
+    },
+    /// Run a method with the sandbox debugger. Unlike view calls, debug calls may modify the
+    /// state, but their changes are discarded.
+    DebugCallFunction {
+        account_id: AccountId,
+        method_name: String,
+        #[serde(rename = "args_base64")]
+        args: FunctionArgs,
+        #[serde(default)]
+        options: DebugOptions,
     },
 }
 
@@ -413,6 +484,9 @@ pub struct PeerInfoView {
     pub is_outbound_peer: bool,
     /// Connection nonce.
     pub nonce: u64,
+    /// Reputation score of the peer, as tracked by the peer store.
+    #[serde(default)]
+    pub reputation: i32,
 }
 
 /// Information about a Producer: its account name, peer_id and a list of connected peers that
@@ -1122,6 +1196,48 @@ pub struct ChunkHeaderView {
     pub validator_power_proposals: Vec<ValidatorPowerView>,
     pub validator_pledge_proposals: Vec<ValidatorPledgeView>,
     pub signature: Signature,
+    /// Congestion of the shard after the previous chunk, missing before the
+    /// congestion control is enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub congestion_info: Option<CongestionInfoView>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+pub struct CongestionInfoView {
+    #[serde(with = "dec_format")]
+    pub delayed_receipts_gas: u128,
+    pub delayed_receipts_bytes: u64,
+    /// Between 0 (not congested) and `MAX_CONGESTION_LEVEL` (fully congested),
+    /// computed with the congestion control config of the epoch. Only filled
+    /// by the nodes which know the config, see `set_congestion_level`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub congestion_level: Option<u64>,
+}
+
+impl CongestionInfoView {
+    pub fn set_congestion_level(&mut self, config: &CongestionControlConfig) {
+        let congestion_info = CongestionInfo::from(self.clone());
+        self.congestion_level = Some(congestion_info.congestion_level(config));
+    }
+}
+
+impl From<CongestionInfo> for CongestionInfoView {
+    fn from(congestion_info: CongestionInfo) -> Self {
+        Self {
+            delayed_receipts_gas: congestion_info.delayed_receipts_gas,
+            delayed_receipts_bytes: congestion_info.delayed_receipts_bytes,
+            congestion_level: None,
+        }
+    }
+}
+
+impl From<CongestionInfoView> for CongestionInfo {
+    fn from(view: CongestionInfoView) -> Self {
+        Self {
+            delayed_receipts_gas: view.delayed_receipts_gas,
+            delayed_receipts_bytes: view.delayed_receipts_bytes,
+        }
+    }
 }
 
 impl From<ShardChunkHeader> for ChunkHeaderView {
@@ -1156,14 +1272,19 @@ impl From<ShardChunkHeader> for ChunkHeaderView {
                 .map(Into::into)
                 .collect(),
             signature,
+            congestion_info: inner.congestion_info().map(Into::into),
         }
     }
 }
 
 impl From<ChunkHeaderView> for ShardChunkHeader {
     fn from(view: ChunkHeaderView) -> Self {
-        let mut header = ShardChunkHeaderV3 {
-            inner: ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
+        let prev_validator_power_proposals =
+            view.validator_power_proposals.into_iter().map(Into::into).collect();
+        let prev_validator_pledge_proposals =
+            view.validator_pledge_proposals.into_iter().map(Into::into).collect();
+        let inner = match view.congestion_info {
+            Some(congestion_info) => ShardChunkHeaderInner::V3(ShardChunkHeaderInnerV3 {
                 prev_block_hash: view.prev_block_hash,
                 prev_state_root: view.prev_state_root,
                 prev_outcome_root: view.outcome_root,
@@ -1176,17 +1297,29 @@ impl From<ChunkHeaderView> for ShardChunkHeader {
                 prev_balance_burnt: view.balance_burnt,
                 prev_outgoing_receipts_root: view.outgoing_receipts_root,
                 tx_root: view.tx_root,
-                prev_validator_power_proposals: view
-                    .validator_power_proposals
-                    .into_iter()
-                    .map(Into::into)
-                    .collect(),
-                prev_validator_pledge_proposals: view
-                    .validator_pledge_proposals
-                    .into_iter()
-                    .map(Into::into)
-                    .collect(),
+                prev_validator_power_proposals,
+                prev_validator_pledge_proposals,
+                congestion_info: congestion_info.into(),
+            }),
+            None => ShardChunkHeaderInner::V2(ShardChunkHeaderInnerV2 {
+                prev_block_hash: view.prev_block_hash,
+                prev_state_root: view.prev_state_root,
+                prev_outcome_root: view.outcome_root,
+                encoded_merkle_root: view.encoded_merkle_root,
+                encoded_length: view.encoded_length,
+                height_created: view.height_created,
+                shard_id: view.shard_id,
+                prev_gas_used: view.gas_used,
+                gas_limit: view.gas_limit,
+                prev_balance_burnt: view.balance_burnt,
+                prev_outgoing_receipts_root: view.outgoing_receipts_root,
+                tx_root: view.tx_root,
+                prev_validator_power_proposals,
+                prev_validator_pledge_proposals,
             }),
+        };
+        let mut header = ShardChunkHeaderV3 {
+            inner,
             height_included: view.height_included,
             signature: view.signature,
             hash: ChunkHash::default(),
@@ -1332,6 +1465,13 @@ pub enum ActionView {
         #[serde_as(as = "Base64")]
         args: Vec<u8>,
     },
+    DeployGlobalContract {
+        #[serde_as(as = "Base64")]
+        code: Vec<u8>,
+    },
+    UseGlobalContract {
+        code_hash: CryptoHash,
+    },
 }
 
 impl From<Action> for ActionView {
@@ -1374,6 +1514,13 @@ impl From<Action> for ActionView {
                 challenge_key: action.challenge_key,
                 args: action.args.into(),
             },
+            Action::DeployGlobalContract(action) => {
+                let code = hash(&action.code).as_ref().to_vec();
+                ActionView::DeployGlobalContract { code }
+            }
+            Action::UseGlobalContract(action) => {
+                ActionView::UseGlobalContract { code_hash: action.code_hash }
+            }
         }
     }
 }
@@ -1425,6 +1572,12 @@ impl TryFrom<ActionView> for Action {
                     args: args.into(),
                 }))
             }
+            ActionView::DeployGlobalContract { code } => {
+                Action::DeployGlobalContract(DeployGlobalContractAction { code })
+            }
+            ActionView::UseGlobalContract { code_hash } => {
+                Action::UseGlobalContract(Box::new(UseGlobalContractAction { code_hash }))
+            }
         })
     }
 }
@@ -2227,6 +2380,12 @@ pub enum ReceiptEnumView {
         #[serde_as(as = "Option<Base64>")]
         data: Option<Vec<u8>>,
     },
+    GlobalContractDistribution {
+        #[serde_as(as = "Base64")]
+        code: Vec<u8>,
+        #[serde(with = "dec_format")]
+        gas_price: Balance,
+    },
 }
 
 impl From<Receipt> for ReceiptView {
@@ -2258,6 +2417,12 @@ impl From<Receipt> for ReceiptView {
                 ReceiptEnum::Data(data_receipt) => {
                     ReceiptEnumView::Data { data_id: data_receipt.data_id, data: data_receipt.data }
                 }
+                ReceiptEnum::GlobalContractDistribution(distribution_receipt) => {
+                    ReceiptEnumView::GlobalContractDistribution {
+                        code: distribution_receipt.code,
+                        gas_price: distribution_receipt.gas_price,
+                    }
+                }
             },
         }
     }
@@ -2299,6 +2464,12 @@ impl TryFrom<ReceiptView> for Receipt {
                 ReceiptEnumView::Data { data_id, data } => {
                     ReceiptEnum::Data(DataReceipt { data_id, data })
                 }
+                ReceiptEnumView::GlobalContractDistribution { code, gas_price } => {
+                    ReceiptEnum::GlobalContractDistribution(GlobalContractDistributionReceipt {
+                        code,
+                        gas_price,
+                    })
+                }
             },
         })
     }
@@ -128,6 +128,7 @@ fn create_chunk_header(height: u64, shard_id: u64) -> ShardChunkHeader {
         CryptoHash::default(),
         vec![],
         vec![],
+        None,
         &validator_signer(),
     ))
 }
@@ -195,6 +196,7 @@ fn create_encoded_shard_chunk(
         receipts,
         Default::default(),
         Default::default(),
+        None,
         &validator_signer(),
         &mut rs,
         100,
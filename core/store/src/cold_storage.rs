@@ -1,17 +1,20 @@
 use crate::columns::DBKeyType;
 use crate::db::{ColdDB, COLD_HEAD_KEY, HEAD_KEY};
-use crate::trie::TrieRefcountAddition;
+use crate::trie::{NibbleSlice, RawTrieNode, RawTrieNodeWithSize, TrieRefcountAddition};
 use crate::{metrics, DBCol, DBTransaction, Database, Store, TrieChanges};
 
 use borsh::BorshDeserialize;
-use std::collections::{hash_map, HashMap};
+use std::collections::{hash_map, HashMap, HashSet};
 use std::io;
 use strum::IntoEnumIterator;
+use unc_chain_configs::RetainedAccounts;
 use unc_primitives::block::{Block, BlockHeader, Tip};
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::shard_layout::ShardLayout;
 use unc_primitives::sharding::ShardChunk;
-use unc_primitives::types::BlockHeight;
+use unc_primitives::transaction::ExecutionOutcomeWithProof;
+use unc_primitives::trie_key::col;
+use unc_primitives::types::{BlockHeight, RawStateChangesWithTrieKey};
 
 type StoreKey = Vec<u8>;
 type StoreValue = Option<Vec<u8>>;
@@ -34,6 +37,32 @@ struct BatchTransaction {
     threshold_transaction_size: usize,
 }
 
+/// Trie key columns whose keys start with an account id.
+const ACCOUNT_TRIE_KEY_COLUMNS: [u8; 10] = [
+    col::ACCOUNT,
+    col::CONTRACT_CODE,
+    col::ACCESS_KEY,
+    col::RECEIVED_DATA,
+    col::POSTPONED_RECEIPT_ID,
+    col::PENDING_DATA_COUNT,
+    col::POSTPONED_RECEIPT,
+    col::CONTRACT_DATA,
+    col::RSA2048_KEY,
+    col::PROMISE_YIELD_RECEIPT,
+];
+
+/// Decides what a partial archival node copies to cold storage.
+///
+/// State nodes are kept if they lie on a trie path that may lead to a key of a
+/// retained account, so that every lookup for such an account can be served
+/// from cold storage. State changes and outcomes are attributed to an account
+/// through their trie key and executor respectively.
+struct ColdStoreFilter<'a> {
+    retained_accounts: &'a RetainedAccounts,
+    /// Nibbles of the raw trie key prefixes of all retained accounts.
+    trie_key_prefixes: Vec<Vec<u8>>,
+}
+
 /// Updates provided cold database from provided hot store with information about block at `height`.
 /// Returns if the block was copied (false only if height is not present in `hot_store`).
 /// Block as `height` has to be final.
@@ -54,11 +83,15 @@ struct BatchTransaction {
 /// 1. add it to `DBCol::is_cold` list
 /// 2. define `DBCol::key_type` for it (if it isn't already defined)
 /// 3. add new clause in `get_keys_from_store` for new key types used for this column (if there are any)
+///
+/// If `retained_accounts` is set, State, StateChanges and TransactionResultForBlock
+/// are only copied for those accounts, see `ColdStoreFilter`.
 pub fn update_cold_db(
     cold_db: &ColdDB,
     hot_store: &Store,
     shard_layout: &ShardLayout,
     height: &BlockHeight,
+    retained_accounts: Option<&RetainedAccounts>,
 ) -> io::Result<bool> {
     let _span = tracing::debug_span!(target: "cold_store", "update cold db", height = height);
     let _timer = metrics::COLD_COPY_DURATION.start_timer();
@@ -73,6 +106,7 @@ pub fn update_cold_db(
     let block_hash_vec = store_with_cache.get_or_err(DBCol::BlockHeight, &height_key)?;
     let block_hash_key = block_hash_vec.as_slice();
 
+    let filter = retained_accounts.map(ColdStoreFilter::new);
     let key_type_to_keys =
         get_keys_from_store(&mut store_with_cache, shard_layout, &height_key, block_hash_key)?;
     for col in DBCol::iter() {
@@ -81,12 +115,18 @@ pub fn update_cold_db(
         }
 
         if col == DBCol::State {
-            copy_state_from_store(shard_layout, block_hash_key, cold_db, &mut store_with_cache)?;
+            copy_state_from_store(
+                shard_layout,
+                block_hash_key,
+                cold_db,
+                &mut store_with_cache,
+                filter.as_ref(),
+            )?;
             continue;
         }
 
         let keys = combine_keys(&key_type_to_keys, &col.key_type());
-        copy_from_store(cold_db, &mut store_with_cache, col, keys)?;
+        copy_from_store(cold_db, &mut store_with_cache, col, keys, filter.as_ref())?;
     }
 
     Ok(true)
@@ -117,6 +157,14 @@ fn rc_aware_set(
     };
 }
 
+fn record_retained(col: DBCol, size: usize) {
+    metrics::COLD_STORE_RETAINED_BYTES.with_label_values(&[<&str>::from(col)]).inc_by(size as u64);
+}
+
+fn record_skipped(col: DBCol, size: usize) {
+    metrics::COLD_STORE_SKIPPED_BYTES.with_label_values(&[<&str>::from(col)]).inc_by(size as u64);
+}
+
 // A specialized version of copy_from_store for the State column. Finds all the
 // State nodes that were inserted at given height by reading from the
 // TrieChanges and inserts them into the cold store.
@@ -124,11 +172,15 @@ fn rc_aware_set(
 // The generic implementation is not efficient for State because it would
 // attempt to read every node from every shard. Here we know exactly what shard
 // the node belongs to.
+//
+// With a filter only the inserted nodes reachable from the new state root
+// through paths of retained accounts are copied.
 fn copy_state_from_store(
     shard_layout: &ShardLayout,
     block_hash_key: &[u8],
     cold_db: &ColdDB,
     hot_store: &mut StoreWithCache,
+    filter: Option<&ColdStoreFilter>,
 ) -> io::Result<()> {
     let col = DBCol::State;
     let _span = tracing::debug_span!(target: "cold_store", "copy_state_from_store", %col);
@@ -147,16 +199,25 @@ fn copy_state_from_store(
             hot_store.get_ser::<TrieChanges>(DBCol::TrieChanges, &key)?;
 
         let Some(trie_changes) = trie_changes else { continue };
+        let retained_nodes =
+            filter.map(|filter| filter.retained_state_nodes(&trie_changes)).transpose()?;
         for op in trie_changes.insertions() {
+            let key = join_two_keys(&shard_uid_key, op.hash().as_bytes());
+            if retained_nodes.as_ref().is_some_and(|retained| !retained.contains(op.hash())) {
+                record_skipped(col, key.len() + op.payload().len());
+                continue;
+            }
             hot_store.insert_state_to_cache_from_op(op, &shard_uid_key);
 
-            let key = join_two_keys(&shard_uid_key, op.hash().as_bytes());
             let value = hot_store.get(DBCol::State, &key)?;
             let value =
                 value.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, hex::encode(&key)))?;
 
             tracing::trace!(target: "cold_store", pretty_key=?unc_fmt::StorageKey(&key), "copying state node to colddb");
-            rc_aware_set(&mut transaction, DBCol::State, key, value);
+            let size = rc_aware_set(&mut transaction, DBCol::State, key, value);
+            if filter.is_some() {
+                record_retained(col, size);
+            }
         }
     }
 
@@ -179,6 +240,7 @@ fn copy_from_store(
     hot_store: &mut StoreWithCache,
     col: DBCol,
     keys: Vec<StoreKey>,
+    filter: Option<&ColdStoreFilter>,
 ) -> io::Result<()> {
     debug_assert!(col.is_cold());
 
@@ -198,6 +260,14 @@ fn copy_from_store(
         // added.
         let data = hot_store.get(col, &key)?;
         if let Some(value) = data {
+            if let Some(filter) = filter.filter(|_| ColdStoreFilter::is_filtered(col)) {
+                if !filter.retains(col, &value)? {
+                    record_skipped(col, key.len() + value.len());
+                    continue;
+                }
+                record_retained(col, key.len() + value.len());
+            }
+
             // TODO: As an optimisation, we might consider breaking the
             // abstraction layer.  Since we’re always writing to cold database,
             // rather than using `cold_db: &dyn Database` argument we could have
@@ -276,15 +346,34 @@ pub enum CopyAllDataToColdStatus {
 
 /// Copies all contents of all cold columns from `hot_store` to `cold_db`.
 /// Does it column by column, and because columns can be huge, writes in batches of ~`batch_size`.
+///
+/// If `retained_accounts` is set, State, StateChanges and TransactionResultForBlock
+/// are filtered like in `update_cold_db`, see `copy_retained_state_to_cold`.
 pub fn copy_all_data_to_cold(
     cold_db: std::sync::Arc<ColdDB>,
     hot_store: &Store,
     batch_size: usize,
     keep_going: &std::sync::Arc<std::sync::atomic::AtomicBool>,
+    retained_accounts: Option<&RetainedAccounts>,
 ) -> io::Result<CopyAllDataToColdStatus> {
+    let filter = retained_accounts.map(ColdStoreFilter::new);
     for col in DBCol::iter() {
         if col.is_cold() {
             tracing::info!(target: "cold_store", ?col, "Started column migration");
+            if let Some(filter) = filter.as_ref().filter(|_| col == DBCol::State) {
+                let status = copy_retained_state_to_cold(
+                    cold_db.clone(),
+                    hot_store,
+                    batch_size,
+                    keep_going,
+                    filter,
+                )?;
+                if let CopyAllDataToColdStatus::Interrupted = status {
+                    return Ok(status);
+                }
+                tracing::info!(target: "cold_store", ?col, "Finished column migration");
+                continue;
+            }
             let mut transaction = BatchTransaction::new(cold_db.clone(), batch_size);
             for result in hot_store.iter(col) {
                 if !keep_going.load(std::sync::atomic::Ordering::Relaxed) {
@@ -292,6 +381,14 @@ pub fn copy_all_data_to_cold(
                     return Ok(CopyAllDataToColdStatus::Interrupted);
                 }
                 let (key, value) = result?;
+                if let Some(filter) = filter.as_ref().filter(|_| ColdStoreFilter::is_filtered(col))
+                {
+                    if !filter.retains(col, &value)? {
+                        record_skipped(col, key.len() + value.len());
+                        continue;
+                    }
+                    record_retained(col, key.len() + value.len());
+                }
                 transaction.set_and_write_if_full(col, key.to_vec(), value.to_vec())?;
             }
             transaction.write()?;
@@ -301,6 +398,63 @@ pub fn copy_all_data_to_cold(
     Ok(CopyAllDataToColdStatus::EverythingCopied)
 }
 
+/// Copies the State nodes on retained paths of every trie still present in
+/// `hot_store`. The roots are taken from all `TrieChanges` in the hot store, so
+/// both the states before and after each of the hot blocks are covered,
+/// including genesis as long as the first block is still there. Nodes which
+/// were already garbage collected from the hot store are skipped.
+fn copy_retained_state_to_cold(
+    cold_db: std::sync::Arc<ColdDB>,
+    hot_store: &Store,
+    batch_size: usize,
+    keep_going: &std::sync::Arc<std::sync::atomic::AtomicBool>,
+    filter: &ColdStoreFilter,
+) -> io::Result<CopyAllDataToColdStatus> {
+    let col = DBCol::State;
+    debug_assert_eq!(DBCol::TrieChanges.key_type(), &[DBKeyType::BlockHash, DBKeyType::ShardUId]);
+
+    let mut roots = HashSet::new();
+    for result in hot_store.iter(DBCol::TrieChanges) {
+        let (key, value) = result?;
+        let shard_uid_key = key[CryptoHash::LENGTH..].to_vec();
+        let trie_changes = TrieChanges::try_from_slice(&value)?;
+        roots.insert((shard_uid_key.clone(), trie_changes.old_root));
+        roots.insert((shard_uid_key, trie_changes.new_root));
+    }
+
+    let mut transaction = BatchTransaction::new(cold_db, batch_size);
+    let mut visited: HashMap<Vec<u8>, HashSet<(CryptoHash, Vec<u8>)>> = HashMap::new();
+    let mut copied = HashSet::new();
+    for (shard_uid_key, root) in roots {
+        if !keep_going.load(std::sync::atomic::Ordering::Relaxed) {
+            tracing::debug!(target: "cold_store", "stopping copy_all_data_to_cold");
+            return Ok(CopyAllDataToColdStatus::Interrupted);
+        }
+        let get_node = |hash: &CryptoHash| {
+            let key = join_two_keys(&shard_uid_key, hash.as_bytes());
+            Ok(hot_store.get(col, &key)?.map(|value| value.to_vec()))
+        };
+        let retained = filter.retained_trie_nodes(
+            root,
+            visited.entry(shard_uid_key.clone()).or_default(),
+            get_node,
+        )?;
+        for hash in retained {
+            let key = join_two_keys(&shard_uid_key, hash.as_bytes());
+            if !copied.insert(key.clone()) {
+                continue;
+            }
+            // Like the nodes in `get_node`, values of the oldest tries may
+            // already be garbage collected from the hot store.
+            let Some(value) = hot_store.get(col, &key)? else { continue };
+            record_retained(col, key.len() + value.len());
+            transaction.set_and_write_if_full(col, key, value.to_vec())?;
+        }
+    }
+    transaction.write()?;
+    Ok(CopyAllDataToColdStatus::EverythingCopied)
+}
+
 // The copy_state_from_store function depends on the state nodes to be present
 // in the trie changes. This isn't the case for genesis so instead this method
 // can be used to copy the genesis records from hot to cold.
@@ -320,6 +474,7 @@ pub fn test_cold_genesis_update(cold_db: &ColdDB, hot_store: &Store) -> io::Resu
             &mut store_with_cache,
             col,
             hot_store.iter(col).map(|x| x.unwrap().0.to_vec()).collect(),
+            None,
         )?;
     }
     Ok(())
@@ -559,6 +714,113 @@ impl StoreWithCache<'_> {
     }
 }
 
+impl<'a> ColdStoreFilter<'a> {
+    fn new(retained_accounts: &'a RetainedAccounts) -> Self {
+        let trie_key_prefixes = retained_accounts
+            .account_prefixes()
+            .flat_map(|account_prefix| {
+                ACCOUNT_TRIE_KEY_COLUMNS.iter().map(move |&col| {
+                    std::iter::once(col)
+                        .chain(account_prefix.iter().copied())
+                        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        Self { retained_accounts, trie_key_prefixes }
+    }
+
+    /// Whether the column is filtered by values in `retains`. State is
+    /// handled separately in `retained_state_nodes`.
+    fn is_filtered(col: DBCol) -> bool {
+        matches!(col, DBCol::StateChanges | DBCol::TransactionResultForBlock)
+    }
+
+    fn retains(&self, col: DBCol, value: &[u8]) -> io::Result<bool> {
+        let account_id = match col {
+            DBCol::StateChanges => {
+                RawStateChangesWithTrieKey::try_from_slice(value)?.trie_key.get_account_id()
+            }
+            DBCol::TransactionResultForBlock => {
+                Some(ExecutionOutcomeWithProof::try_from_slice(value)?.outcome.executor_id)
+            }
+            _ => return Ok(true),
+        };
+        Ok(account_id.is_some_and(|account_id| self.retained_accounts.contains(&account_id)))
+    }
+
+    /// Whether a key starting with the given nibbles may belong to a retained account.
+    fn is_retained_path(&self, nibbles: &[u8]) -> bool {
+        self.trie_key_prefixes.iter().any(|prefix| {
+            let len = prefix.len().min(nibbles.len());
+            prefix[..len] == nibbles[..len]
+        })
+    }
+
+    /// Walks the nodes inserted by `trie_changes` starting from the new root
+    /// and returns hashes of the nodes and values on retained paths. Children
+    /// which were not inserted are already in cold storage if they are needed.
+    fn retained_state_nodes(&self, trie_changes: &TrieChanges) -> io::Result<HashSet<CryptoHash>> {
+        let inserted: HashMap<&CryptoHash, &[u8]> =
+            trie_changes.insertions().iter().map(|op| (op.hash(), op.payload())).collect();
+        self.retained_trie_nodes(trie_changes.new_root, &mut HashSet::new(), |hash| {
+            Ok(inserted.get(hash).map(|payload| payload.to_vec()))
+        })
+    }
+
+    /// Walks the trie from `root` and returns hashes of the nodes and values on
+    /// retained paths. Nodes for which `get_node` returns `None` are not
+    /// descended into. Pairs of node and path in `visited` are skipped, which
+    /// lets walks of several tries share their common subtrees.
+    fn retained_trie_nodes(
+        &self,
+        root: CryptoHash,
+        visited: &mut HashSet<(CryptoHash, Vec<u8>)>,
+        mut get_node: impl FnMut(&CryptoHash) -> io::Result<Option<Vec<u8>>>,
+    ) -> io::Result<HashSet<CryptoHash>> {
+        let mut retained = HashSet::new();
+        let mut stack = vec![(root, vec![])];
+        while let Some((hash, nibbles)) = stack.pop() {
+            if !self.is_retained_path(&nibbles) {
+                continue;
+            }
+            // The same node may appear under several paths, so it is visited
+            // for each of them rather than only once.
+            if !visited.insert((hash, nibbles.clone())) {
+                continue;
+            }
+            let Some(payload) = get_node(&hash)? else { continue };
+            retained.insert(hash);
+            let extend = |key: &[u8]| {
+                let (slice, _) = NibbleSlice::from_encoded(key);
+                let mut nibbles = nibbles.clone();
+                nibbles.extend(slice.iter());
+                nibbles
+            };
+            match RawTrieNodeWithSize::try_from_slice(&payload)?.node {
+                RawTrieNode::Leaf(key, value) => {
+                    if self.is_retained_path(&extend(&key)) {
+                        retained.insert(value.hash);
+                    }
+                }
+                RawTrieNode::Extension(key, child) => stack.push((child, extend(&key))),
+                RawTrieNode::BranchNoValue(children) => {
+                    for (index, child) in children.iter() {
+                        stack.push((*child, [nibbles.as_slice(), &[index]].concat()));
+                    }
+                }
+                RawTrieNode::BranchWithValue(value, children) => {
+                    retained.insert(value.hash);
+                    for (index, child) in children.iter() {
+                        stack.push((*child, [nibbles.as_slice(), &[index]].concat()));
+                    }
+                }
+            }
+        }
+        Ok(retained)
+    }
+}
+
 impl BatchTransaction {
     pub fn new(cold_db: std::sync::Arc<ColdDB>, batch_size: usize) -> Self {
         Self {
@@ -618,9 +880,15 @@ impl BatchTransaction {
 
 #[cfg(test)]
 mod test {
-    use super::{combine_keys, StoreKey};
+    use super::{combine_keys, ColdStoreFilter, StoreKey};
     use crate::columns::DBKeyType;
+    use crate::test_utils::TestTriesBuilder;
+    use crate::trie::{PartialStorage, Trie};
+    use crate::ShardUId;
     use std::collections::{HashMap, HashSet};
+    use unc_chain_configs::RetainedAccounts;
+    use unc_primitives::challenge::PartialState;
+    use unc_primitives::trie_key::TrieKey;
 
     #[test]
     fn test_combine_keys() {
@@ -682,4 +950,50 @@ mod test {
             HashSet::<StoreKey>::from_iter(vec![vec![]])
         );
     }
+
+    #[test]
+    fn test_retained_state_nodes() {
+        let tries = TestTriesBuilder::new().build();
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard(), Trie::EMPTY_ROOT);
+        let keys = ["alice", "alicia", "bob", "miner-1", "miner-2"]
+            .into_iter()
+            .flat_map(|account_id| {
+                let account_id = account_id.parse().unwrap();
+                [
+                    TrieKey::Account { account_id: account_id.clone() }.to_vec(),
+                    TrieKey::ContractData { account_id, key: b"key".to_vec() }.to_vec(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let trie_changes = trie
+            .update(keys.iter().map(|key| (key.clone(), Some([key.as_slice(), b"value"].concat()))))
+            .unwrap();
+
+        let retained_accounts =
+            RetainedAccounts::try_from(vec!["alice".to_string(), "miner-*".to_string()]).unwrap();
+        let retained =
+            ColdStoreFilter::new(&retained_accounts).retained_state_nodes(&trie_changes).unwrap();
+        assert!(retained.len() < trie_changes.insertions().len());
+
+        // A trie built only from the retained nodes serves the retained accounts.
+        let nodes = trie_changes
+            .insertions()
+            .iter()
+            .filter(|op| retained.contains(op.hash()))
+            .map(|op| op.payload().into())
+            .collect();
+        let partial_trie = Trie::from_recorded_storage(
+            PartialStorage { nodes: PartialState::TrieValues(nodes) },
+            trie_changes.new_root,
+            false,
+        );
+        for key in &keys {
+            let value = partial_trie.get(key);
+            if key[1..].starts_with(b"alice") || key[1..].starts_with(b"miner-") {
+                assert_eq!(value.unwrap(), Some([key.as_slice(), b"value"].concat()));
+            } else {
+                assert!(value.is_err());
+            }
+        }
+    }
 }
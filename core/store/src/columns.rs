@@ -69,6 +69,10 @@ pub enum DBCol {
     /// - *Rows*: single row (empty row name)
     /// - *Content type*: Vec of [network_primitives::types::ConnectionInfo]
     RecentOutboundConnections,
+    /// Reputation scores of known peers, so that they survive node restarts.
+    /// - *Rows*: single row (empty row name)
+    /// - *Content type*: Vec of (PeerId, score, last update time)
+    PeerReputations,
     /// Mapping from EpochId to EpochInfo
     /// - *Rows*: EpochId (CryptoHash)
     /// - *Content type*: [unc_primitives::epoch_manager::epoch_info::EpochInfo]
@@ -294,7 +298,8 @@ pub enum DBCol {
 /// Currently only used in cold storage continuous migration.
 #[derive(PartialEq, Copy, Clone, Debug, Hash, Eq, strum::EnumIter)]
 pub enum DBKeyType {
-    /// Empty row name. Used in DBCol::LastComponentNonce and DBCol::RecentOutboundConnections
+    /// Empty row name. Used in DBCol::LastComponentNonce, DBCol::RecentOutboundConnections
+    /// and DBCol::PeerReputations
     Empty,
     /// Set of predetermined strings. Used, for example, in DBCol::BlockMisc
     StringLiteral,
@@ -458,6 +463,7 @@ impl DBCol {
             | DBCol::BlockHeight
             | DBCol::_Peers
             | DBCol::RecentOutboundConnections
+            | DBCol::PeerReputations
             | DBCol::BlockMerkleTree
             | DBCol::AccountAnnouncements
             | DBCol::EpochLightClientBlocks
@@ -505,6 +511,7 @@ impl DBCol {
             DBCol::IncomingReceipts => &[DBKeyType::BlockHash, DBKeyType::ShardId],
             DBCol::_Peers => &[DBKeyType::PeerId],
             DBCol::RecentOutboundConnections => &[DBKeyType::Empty],
+            DBCol::PeerReputations => &[DBKeyType::Empty],
             DBCol::EpochInfo => &[DBKeyType::EpochId],
             DBCol::BlockInfo => &[DBKeyType::BlockHash],
             DBCol::Chunks => &[DBKeyType::ChunkHash],
@@ -59,6 +59,10 @@ pub struct StoreConfig {
     pub load_mem_tries_for_shards: Vec<ShardUId>,
     /// If true, load mem tries for all shards; this has priority over `load_mem_tries_for_shards`.
     pub load_mem_tries_for_all_shards: bool,
+    /// If true, view queries on a state root still held by the loaded mem tries read from memory
+    /// instead of going through the view trie cache and the database. Queries on older state
+    /// always fall back to the database.
+    pub serve_view_queries_from_mem_tries: bool,
 
     /// Path where to create RocksDB checkpoints during database migrations or
     /// `false` to disable that feature.
@@ -267,6 +271,7 @@ impl Default for StoreConfig {
             // requires more RAM and takes several minutes on startup.
             load_mem_tries_for_shards: Default::default(),
             load_mem_tries_for_all_shards: false,
+            serve_view_queries_from_mem_tries: false,
 
             migration_snapshot: Default::default(),
 
@@ -211,6 +211,17 @@ pub trait Database: Sync + Send {
     /// want this method.
     fn iter_raw_bytes<'a>(&'a self, col: DBCol) -> DBIterator<'a>;
 
+    /// Iterate over items in given column whose keys are between [lower_bound, upper_bound)
+    /// bypassing reference count decoding if any.
+    ///
+    /// This is to [`Self::iter_raw_bytes`] what [`Self::iter_range`] is to [`Self::iter`].
+    fn iter_raw_bytes_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a>;
+
     /// Atomically apply all operations in given batch at once.
     fn write(&self, batch: DBTransaction) -> io::Result<()>;
 
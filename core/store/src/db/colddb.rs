@@ -82,6 +82,18 @@ impl Database for ColdDB {
         self.cold.iter_range(col, lower_bound, upper_bound)
     }
 
+    /// Iterate over items in given column whose keys are between [lower_bound, upper_bound)
+    /// bypassing reference count decoding if any.
+    fn iter_raw_bytes_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        Self::log_assert_is_in_colddb(col);
+        self.cold.iter_raw_bytes_range(col, lower_bound, upper_bound)
+    }
+
     /// Atomically applies operations in given transaction.
     ///
     /// If debug assertions are enabled, panics if there are any delete
@@ -324,6 +324,15 @@ impl Database for RocksDB {
         Box::new(self.iter_raw_bytes_internal(col, None, None, None))
     }
 
+    fn iter_raw_bytes_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        Box::new(self.iter_raw_bytes_internal(col, None, lower_bound, upper_bound))
+    }
+
     fn iter(&self, col: DBCol) -> DBIterator {
         refcount::iter_with_rc_logic(col, self.iter_raw_bytes_internal(col, None, None, None))
     }
@@ -170,6 +170,27 @@ impl Database for SplitDB {
         return Self::merge_iter(self.hot.iter_raw_bytes(col), self.cold.iter_raw_bytes(col));
     }
 
+    /// Iterate over items in given column whose keys are between [lower_bound, upper_bound)
+    /// bypassing reference count decoding if any.
+    ///
+    /// The returned iterator will iterate through items in both the cold store
+    /// and the hot store. The items will be unique and sorted.
+    fn iter_raw_bytes_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        if !col.is_cold() {
+            return self.hot.iter_raw_bytes_range(col, lower_bound, upper_bound);
+        }
+
+        return Self::merge_iter(
+            self.hot.iter_raw_bytes_range(col, lower_bound, upper_bound),
+            self.cold.iter_raw_bytes_range(col, lower_bound, upper_bound),
+        );
+    }
+
     /// The split db, in principle, should be read only and only used in view client.
     /// However the view client *does* write to the db in order to update cache.
     /// Hence we need to allow writing to the split db but only write to the hot db.
@@ -64,6 +64,16 @@ impl Database for TestDB {
         col: DBCol,
         lower_bound: Option<&[u8]>,
         upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        let iterator = self.iter_raw_bytes_range(col, lower_bound, upper_bound);
+        refcount::iter_with_rc_logic(col, iterator)
+    }
+
+    fn iter_raw_bytes_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
     ) -> DBIterator<'a> {
         let lower = lower_bound.map_or(Bound::Unbounded, |f| Bound::Included(f.to_vec()));
         let upper = upper_bound.map_or(Bound::Unbounded, |f| Bound::Excluded(f.to_vec()));
@@ -72,7 +82,7 @@ impl Database for TestDB {
             .range((lower, upper))
             .map(|(k, v)| Ok((k.clone().into_boxed_slice(), v.clone().into_boxed_slice())))
             .collect::<Vec<io::Result<_>>>();
-        refcount::iter_with_rc_logic(col, iterator.into_iter())
+        Box::new(iterator.into_iter())
     }
 
     fn write(&self, transaction: DBTransaction) -> io::Result<()> {
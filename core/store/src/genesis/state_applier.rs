@@ -1,7 +1,8 @@
 use crate::flat::FlatStateChanges;
 use crate::{
-    get_account, get_received_data, set, set_access_key, set_account, set_code,
-    set_delayed_receipt, set_postponed_receipt, set_received_data, ShardTries, TrieUpdate,
+    enqueue_promise_yield_timeout, get_account, get_received_data, set, set_access_key,
+    set_account, set_code, set_delayed_receipt, set_postponed_receipt, set_promise_yield_indices,
+    set_promise_yield_receipt, set_received_data, set_rsa2048_keys, ShardTries, TrieUpdate,
 };
 
 use std::collections::{HashMap, HashSet};
@@ -10,7 +11,10 @@ use unc_chain_configs::Genesis;
 use unc_crypto::PublicKey;
 use unc_parameters::StorageUsageConfig;
 use unc_primitives::account::{AccessKey, Account};
-use unc_primitives::receipt::{DelayedReceiptIndices, Receipt, ReceiptEnum, ReceivedData};
+use unc_primitives::receipt::{
+    DelayedReceiptIndices, PromiseYieldIndices, PromiseYieldTimeout, Receipt, ReceiptEnum,
+    ReceivedData,
+};
 use unc_primitives::shard_layout::ShardUId;
 use unc_primitives::state_record::{state_record_to_account_id, StateRecord};
 use unc_primitives::trie_key::TrieKey;
@@ -59,6 +63,12 @@ impl<'a> StorageComputer<'a> {
             StateRecord::PostponedReceipt(_) => None,
             StateRecord::ReceivedData { .. } => None,
             StateRecord::DelayedReceipt(_) => None,
+            StateRecord::PromiseYieldReceipt { .. } => None,
+            StateRecord::Rsa2048Keys { account_id, rsa_key, .. } => {
+                let storage_usage = self.config.num_extra_bytes_record
+                    + borsh::object_length(rsa_key).unwrap() as u64;
+                Some((account_id.clone(), storage_usage))
+            }
         };
         if let Some((account_id, storage_usage)) = account_and_storage {
             *self.result.entry(account_id).or_default() += storage_usage;
@@ -170,6 +180,7 @@ impl GenesisStateApplier {
         account_ids: HashSet<AccountId>,
     ) {
         let mut postponed_receipts: Vec<Receipt> = vec![];
+        let mut promise_yield_timeouts: Vec<PromiseYieldTimeout> = vec![];
         let mut storage_computer = StorageComputer::new(config);
         tracing::info!(
             target: "runtime",
@@ -243,6 +254,33 @@ impl GenesisStateApplier {
                 StateRecord::DelayedReceipt(receipt) => storage.modify(|state_update| {
                     set_delayed_receipt(state_update, delayed_receipts_indices, &*receipt);
                 }),
+                StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                    storage.modify(|state_update| {
+                        set_rsa2048_keys(
+                            state_update,
+                            account_id.clone(),
+                            public_key.clone(),
+                            rsa_key,
+                        );
+                    })
+                }
+                StateRecord::PromiseYieldReceipt { account_id, data_id, expires_at } => {
+                    storage.modify(|state_update| {
+                        set_promise_yield_receipt(
+                            state_update,
+                            account_id.clone(),
+                            *data_id,
+                            *expires_at,
+                        );
+                    });
+                    // Delaying the timeout queue until we know all the yields, it has to be
+                    // ordered by the expiration height.
+                    promise_yield_timeouts.push(PromiseYieldTimeout {
+                        account_id: account_id.clone(),
+                        data_id: *data_id,
+                        expires_at: *expires_at,
+                    });
+                }
             }
         });
 
@@ -310,6 +348,24 @@ impl GenesisStateApplier {
             }
         }
 
+        tracing::info!(
+            target: "runtime",
+            ?shard_uid,
+            "processing promise yield timeouts…"
+        );
+        promise_yield_timeouts.sort_by_key(|timeout| timeout.expires_at);
+        let mut promise_yield_indices = PromiseYieldIndices::default();
+        for timeout in promise_yield_timeouts {
+            storage.modify(|state_update| {
+                enqueue_promise_yield_timeout(state_update, &mut promise_yield_indices, &timeout);
+            });
+        }
+        if promise_yield_indices != PromiseYieldIndices::default() {
+            storage.modify(|state_update| {
+                set_promise_yield_indices(state_update, &promise_yield_indices);
+            });
+        }
+
         for (account_id, _, pledge, power) in validators {
             if !account_ids.contains(account_id) {
                 continue;
@@ -387,3 +443,181 @@ pub fn compute_genesis_storage_usage(
     });
     storage_computer.finalize()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::GenesisStateApplier;
+    use crate::test_utils::TestTriesBuilder;
+    use crate::{get, get_promise_yield_indices};
+    use std::collections::HashSet;
+    use std::sync::atomic;
+    use unc_chain_configs::{Genesis, GenesisConfig, GenesisContents, GenesisRecords};
+    use unc_crypto::{KeyType, PublicKey};
+    use unc_parameters::StorageUsageConfig;
+    use unc_primitives::account::Account;
+    use unc_primitives::action::RegisterRsa2048KeysAction;
+    use unc_primitives::hash::{hash, CryptoHash};
+    use unc_primitives::receipt::PromiseYieldTimeout;
+    use unc_primitives::shard_layout::ShardUId;
+    use unc_primitives::state_record::StateRecord;
+    use unc_primitives::trie_key::TrieKey;
+    use unc_primitives::types::AccountId;
+
+    fn rsa2048_keys(account_id: &AccountId, seed: &str, power: u64) -> StateRecord {
+        let public_key = PublicKey::from_seed(KeyType::ED25519, seed);
+        let args =
+            serde_json::json!({ "public_key": public_key.to_string(), "power": power.to_string() });
+        StateRecord::Rsa2048Keys {
+            account_id: account_id.clone(),
+            public_key: public_key.clone(),
+            rsa_key: RegisterRsa2048KeysAction {
+                public_key,
+                operation_type: 0,
+                args: serde_json::to_vec(&args).unwrap(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_rsa2048_keys_round_trip() {
+        let config = StorageUsageConfig {
+            storage_amount_per_byte: 1,
+            num_bytes_account: 100,
+            num_extra_bytes_record: 40,
+        };
+        let miner: AccountId = "miner".parse().unwrap();
+        let root: AccountId = "unc".parse().unwrap();
+        let miner_keys = [rsa2048_keys(&miner, "chip0", 3), rsa2048_keys(&miner, "chip1", 4)];
+        let root_keys = [rsa2048_keys(&root, "chip2", 5)];
+        let account = |account_id: &AccountId, power, keys: &[StateRecord]| {
+            let storage_usage = config.num_bytes_account
+                + keys
+                    .iter()
+                    .map(|record| match record {
+                        StateRecord::Rsa2048Keys { rsa_key, .. } => {
+                            config.num_extra_bytes_record
+                                + borsh::object_length(rsa_key).unwrap() as u64
+                        }
+                        _ => unreachable!(),
+                    })
+                    .sum::<u64>();
+            StateRecord::Account {
+                account_id: account_id.clone(),
+                account: Account::new(1_000, 0, power, CryptoHash::default(), storage_usage),
+            }
+        };
+        // The applier computes the storage usage of the accounts from their records.
+        let mut records = vec![account(&miner, 7, &[]), account(&root, 0, &[])];
+        records.extend(miner_keys.iter().cloned());
+        records.extend(root_keys.iter().cloned());
+        let genesis = Genesis {
+            config: GenesisConfig::default(),
+            contents: GenesisContents::Records { records: GenesisRecords(records) },
+        };
+        let tries = TestTriesBuilder::new().build();
+        let state_root = GenesisStateApplier::apply(
+            &atomic::AtomicUsize::new(0),
+            tries.clone(),
+            0,
+            &[],
+            &config,
+            &genesis,
+            HashSet::from([miner.clone(), root.clone()]),
+        );
+
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard(), state_root);
+        let got: Vec<_> = trie
+            .iter()
+            .unwrap()
+            .map(|item| {
+                let (key, value) = item.unwrap();
+                StateRecord::from_raw_key_value(key, value).unwrap()
+            })
+            .collect();
+        let mut wanted = vec![account(&miner, 7, &miner_keys), account(&root, 0, &root_keys)];
+        wanted.extend(miner_keys.iter().cloned());
+        wanted.extend(root_keys.iter().cloned());
+        assert_eq!(got.len(), wanted.len(), "{got:?}");
+        for record in &wanted {
+            assert!(got.contains(record), "{record} is missing from {got:?}");
+        }
+    }
+
+    #[test]
+    fn test_promise_yield_round_trip() {
+        let config = StorageUsageConfig {
+            storage_amount_per_byte: 1,
+            num_bytes_account: 100,
+            num_extra_bytes_record: 40,
+        };
+        let contract: AccountId = "contract".parse().unwrap();
+        let account = StateRecord::Account {
+            account_id: contract.clone(),
+            account: Account::new(1_000, 0, 0, CryptoHash::default(), config.num_bytes_account),
+        };
+        let yields: Vec<_> = [(hash(b"first"), 20), (hash(b"second"), 10)]
+            .into_iter()
+            .map(|(data_id, expires_at)| StateRecord::PromiseYieldReceipt {
+                account_id: contract.clone(),
+                data_id,
+                expires_at,
+            })
+            .collect();
+        let mut records = vec![account.clone()];
+        records.extend(yields.iter().cloned());
+        let genesis = Genesis {
+            config: GenesisConfig::default(),
+            contents: GenesisContents::Records { records: GenesisRecords(records) },
+        };
+        let tries = TestTriesBuilder::new().build();
+        let state_root = GenesisStateApplier::apply(
+            &atomic::AtomicUsize::new(0),
+            tries.clone(),
+            0,
+            &[],
+            &config,
+            &genesis,
+            HashSet::from([contract.clone()]),
+        );
+
+        let trie = tries.get_trie_for_shard(ShardUId::single_shard(), state_root);
+        let got: Vec<_> = trie
+            .iter()
+            .unwrap()
+            .filter_map(|item| {
+                let (key, value) = item.unwrap();
+                StateRecord::from_raw_key_value(key, value)
+            })
+            .collect();
+        assert_eq!(got.len(), 3, "{got:?}");
+        assert!(got.contains(&account));
+        for record in &yields {
+            assert!(got.contains(record), "{record} is missing from {got:?}");
+        }
+
+        // The timeout queue is rebuilt in the order of the expiration.
+        let state_update = tries.new_trie_update(ShardUId::single_shard(), state_root);
+        let indices = get_promise_yield_indices(&state_update).unwrap();
+        assert_eq!((indices.first_index, indices.next_available_index), (0, 2));
+        let timeouts: Vec<PromiseYieldTimeout> = (0..2)
+            .map(|index| {
+                get(&state_update, &TrieKey::PromiseYieldTimeout { index }).unwrap().unwrap()
+            })
+            .collect();
+        assert_eq!(
+            timeouts,
+            vec![
+                PromiseYieldTimeout {
+                    account_id: contract.clone(),
+                    data_id: hash(b"second"),
+                    expires_at: 10
+                },
+                PromiseYieldTimeout {
+                    account_id: contract,
+                    data_id: hash(b"first"),
+                    expires_at: 20
+                },
+            ]
+        );
+    }
+}
@@ -23,10 +23,12 @@ use unc_fmt::{AbbrBytes, StorageKey};
 use unc_primitives::account::{AccessKey, Account};
 pub use unc_primitives::errors::{MissingTrieValueContext, StorageError};
 use unc_primitives::hash::CryptoHash;
-use unc_primitives::receipt::{DelayedReceiptIndices, Receipt, ReceivedData};
+use unc_primitives::receipt::{
+    DelayedReceiptIndices, PromiseYieldIndices, PromiseYieldTimeout, Receipt, ReceivedData,
+};
 pub use unc_primitives::shard_layout::ShardUId;
 use unc_primitives::trie_key::{trie_key_parsers, TrieKey};
-use unc_primitives::types::{AccountId, StateRoot};
+use unc_primitives::types::{AccountId, BlockHeight, StateRoot};
 use unc_vm_runner::logic::{CompiledContract, CompiledContractCache};
 use unc_vm_runner::ContractCode;
 
@@ -314,6 +316,17 @@ impl Store {
         self.storage.iter_raw_bytes(col)
     }
 
+    /// Fetches raw key/value pairs in a range of keys from the database, see
+    /// [`Self::iter_raw_bytes`]. Upper bound key is not included.
+    pub fn iter_raw_bytes_range<'a>(
+        &'a self,
+        col: DBCol,
+        lower_bound: Option<&[u8]>,
+        upper_bound: Option<&[u8]>,
+    ) -> DBIterator<'a> {
+        self.storage.iter_raw_bytes_range(col, lower_bound, upper_bound)
+    }
+
     pub fn iter_prefix<'a>(&'a self, col: DBCol, key_prefix: &'a [u8]) -> DBIterator<'a> {
         self.storage.iter_prefix(col, key_prefix)
     }
@@ -744,6 +757,62 @@ pub fn set_delayed_receipt(
         .expect("Next available index for delayed receipt exceeded the integer limit");
 }
 
+pub fn get_promise_yield_indices(
+    trie: &dyn TrieAccess,
+) -> Result<PromiseYieldIndices, StorageError> {
+    Ok(get(trie, &TrieKey::PromiseYieldIndices)?.unwrap_or_default())
+}
+
+pub fn set_promise_yield_indices(
+    state_update: &mut TrieUpdate,
+    promise_yield_indices: &PromiseYieldIndices,
+) {
+    set(state_update, TrieKey::PromiseYieldIndices, promise_yield_indices);
+}
+
+// Adds the given timeout entry into the end of the yielded receipts timeout queue in the state.
+pub fn enqueue_promise_yield_timeout(
+    state_update: &mut TrieUpdate,
+    promise_yield_indices: &mut PromiseYieldIndices,
+    timeout: &PromiseYieldTimeout,
+) {
+    set(
+        state_update,
+        TrieKey::PromiseYieldTimeout { index: promise_yield_indices.next_available_index },
+        timeout,
+    );
+    promise_yield_indices.next_available_index = promise_yield_indices
+        .next_available_index
+        .checked_add(1)
+        .expect("Next available index for yield timeout exceeded the integer limit");
+}
+
+pub fn set_promise_yield_receipt(
+    state_update: &mut TrieUpdate,
+    receiver_id: AccountId,
+    data_id: CryptoHash,
+    expires_at: BlockHeight,
+) {
+    set(state_update, TrieKey::PromiseYieldReceipt { receiver_id, data_id }, &expires_at);
+}
+
+pub fn remove_promise_yield_receipt(
+    state_update: &mut TrieUpdate,
+    receiver_id: &AccountId,
+    data_id: CryptoHash,
+) {
+    state_update.remove(TrieKey::PromiseYieldReceipt { receiver_id: receiver_id.clone(), data_id });
+}
+
+pub fn has_promise_yield_receipt(
+    trie: &dyn TrieAccess,
+    receiver_id: &AccountId,
+    data_id: CryptoHash,
+) -> Result<bool, StorageError> {
+    let key = TrieKey::PromiseYieldReceipt { receiver_id: receiver_id.clone(), data_id };
+    Ok(trie.get(&key)?.is_some())
+}
+
 pub fn set_access_key(
     state_update: &mut TrieUpdate,
     account_id: AccountId,
@@ -826,6 +895,10 @@ pub fn set_code(state_update: &mut TrieUpdate, account_id: AccountId, code: &Con
     state_update.set(TrieKey::ContractCode { account_id }, code.code().to_vec());
 }
 
+pub fn remove_code(state_update: &mut TrieUpdate, account_id: AccountId) {
+    state_update.remove(TrieKey::ContractCode { account_id });
+}
+
 pub fn get_code(
     trie: &dyn TrieAccess,
     account_id: &AccountId,
@@ -835,6 +908,26 @@ pub fn get_code(
     trie.get(&key).map(|opt| opt.map(|code| ContractCode::new(code, code_hash)))
 }
 
+pub fn set_global_contract_code(state_update: &mut TrieUpdate, code: &ContractCode) {
+    state_update.set(TrieKey::GlobalContractCode { code_hash: *code.hash() }, code.code().to_vec());
+}
+
+pub fn get_global_contract_code(
+    trie: &dyn TrieAccess,
+    code_hash: CryptoHash,
+) -> Result<Option<ContractCode>, StorageError> {
+    let key = TrieKey::GlobalContractCode { code_hash };
+    trie.get(&key).map(|opt| opt.map(|code| ContractCode::new(code, Some(code_hash))))
+}
+
+pub fn has_global_contract_code(
+    trie: &dyn TrieAccess,
+    code_hash: CryptoHash,
+) -> Result<bool, StorageError> {
+    let key = TrieKey::GlobalContractCode { code_hash };
+    Ok(trie.get(&key)?.is_some())
+}
+
 /// Removes account, code and all access keys associated to it.
 pub fn remove_account(
     state_update: &mut TrieUpdate,
@@ -1040,6 +1133,11 @@ mod tests {
         assert_sorted(PREFIXES.len() * COUNT, collect(store.iter_raw_bytes(COLUMN)));
         assert_sorted(PREFIXES.len() * COUNT, collect(store.iter_prefix(COLUMN, b"")));
 
+        // Check that range scan produces exactly the keys of the range in proper order.
+        let keys = collect(store.iter_raw_bytes_range(COLUMN, Some(b"foo1"), Some(b"foo2")));
+        assert!(keys.iter().all(|key| key.starts_with(b"foo1")));
+        assert_sorted(COUNT, keys);
+
         // Check that prefix scan produces keys in proper order.
         for prefix in PREFIXES.iter() {
             let keys = collect(store.iter_prefix(COLUMN, prefix));
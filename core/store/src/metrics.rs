@@ -230,6 +230,22 @@ pub static COLD_COPY_DURATION: Lazy<Histogram> = Lazy::new(|| {
     )
     .unwrap()
 });
+pub static COLD_STORE_RETAINED_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "unc_cold_store_retained_bytes",
+        "Size of keys and values of the filtered columns copied to cold storage by a partial archival node.",
+        &["col"],
+    )
+    .unwrap()
+});
+pub static COLD_STORE_SKIPPED_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "unc_cold_store_skipped_bytes",
+        "Size of keys and values not copied to cold storage because they belong to accounts which are not retained.",
+        &["col"],
+    )
+    .unwrap()
+});
 
 pub(crate) static HAS_STATE_SNAPSHOT: Lazy<IntGauge> = Lazy::new(|| {
     try_create_int_gauge("unc_has_state_snapshot", "Whether a node has a state snapshot open")
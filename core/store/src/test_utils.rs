@@ -114,6 +114,7 @@ impl TestTriesBuilder {
             store,
             TrieConfig {
                 load_mem_tries_for_all_shards: self.enable_in_memory_tries,
+                serve_view_queries_from_mem_tries: self.enable_in_memory_tries,
                 ..Default::default()
             },
             &shard_uids,
@@ -26,6 +26,8 @@ pub struct TrieConfig {
     /// List of shards we will load into memory.
     pub load_mem_tries_for_shards: Vec<ShardUId>,
     pub load_mem_tries_for_all_shards: bool,
+    /// Whether view tries read from the in-memory tries when they hold the requested root.
+    pub serve_view_queries_from_mem_tries: bool,
 }
 
 impl TrieConfig {
@@ -39,6 +41,7 @@ impl TrieConfig {
         this.enable_receipt_prefetching = config.enable_receipt_prefetching;
         this.load_mem_tries_for_shards = config.load_mem_tries_for_shards.clone();
         this.load_mem_tries_for_all_shards = config.load_mem_tries_for_all_shards;
+        this.serve_view_queries_from_mem_tries = config.serve_view_queries_from_mem_tries;
 
         this
     }
@@ -105,6 +105,13 @@ impl<'a> TrieIterator<'a> {
         self.seek_nibble_slice(NibbleSlice::new(key.as_ref()), true).map(drop)
     }
 
+    /// Position the iterator on the first element with key >= `key`.
+    ///
+    /// Unlike [`Self::seek_prefix`], iteration does not stop after the keys starting with `key`.
+    pub fn seek<K: AsRef<[u8]>>(&mut self, key: K) -> Result<(), StorageError> {
+        self.seek_nibble_slice(NibbleSlice::new(key.as_ref()), false).map(drop)
+    }
+
     /// Configures whether the iterator should remember all the nodes its
     /// visiting.
     ///
@@ -481,6 +488,7 @@ mod tests {
                 let seek_key: Vec<u8> =
                     (0..key_length).map(|_| *alphabet.choose(&mut rng).unwrap()).collect();
                 test_seek_prefix(&trie, &map, &seek_key);
+                test_seek(&trie, &map, &seek_key);
             }
         }
     }
@@ -648,6 +656,15 @@ mod tests {
         assert_eq!(got, want);
     }
 
+    fn test_seek(trie: &Trie, map: &BTreeMap<Vec<u8>, Vec<u8>>, seek_key: &[u8]) {
+        let mut iterator = trie.iter().unwrap();
+        iterator.seek(&seek_key).unwrap();
+        let got: Vec<_> = iterator.map(Result::unwrap).collect();
+        let want: Vec<_> =
+            map.range(seek_key.to_vec()..).map(|(k, v)| (k.clone(), v.clone())).collect();
+        assert_eq!(got, want);
+    }
+
     #[test]
     fn test_has_value() {
         let mut rng = rand::thread_rng();
@@ -29,3 +29,12 @@ pub static MEM_TRIE_NUM_LOOKUPS: Lazy<IntCounter> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static MEM_TRIE_VIEW_FALLBACKS: Lazy<IntCounterVec> = Lazy::new(|| {
+    try_create_int_counter_vec(
+        "unc_mem_trie_view_fallbacks",
+        "Number of view lookups that fell back to disk because the root was garbage collected from the in-memory trie",
+        &["shard_uid"],
+    )
+    .unwrap()
+});
@@ -1,9 +1,10 @@
 use self::accounting_cache::TrieAccountingCache;
 use self::mem::lookup::memtrie_lookup;
+use self::mem::metrics::MEM_TRIE_VIEW_FALLBACKS;
 use self::mem::updating::{UpdatedMemTrieNode, UpdatedMemTrieNodeId};
 use self::mem::MemTries;
 use self::trie_recording::TrieRecorder;
-use self::trie_storage::TrieMemoryPartialStorage;
+use self::trie_storage::{TrieMemoryPartialStorage, TrieOverlayStorage};
 use crate::flat::{FlatStateChanges, FlatStorageChunkView};
 pub use crate::trie::config::TrieConfig;
 pub(crate) use crate::trie::config::{
@@ -667,6 +668,18 @@ impl Trie {
         trie
     }
 
+    /// Makes a new trie for the same root which finds the nodes and values in `overlay` as well
+    /// as in the storage of this trie.
+    ///
+    /// Neither flat storage nor in-memory tries know of the `overlay`, so the new trie uses
+    /// neither of them.
+    pub fn with_overlay(&self, overlay: Arc<HashMap<CryptoHash, Arc<[u8]>>>) -> Self {
+        let storage = Rc::new(TrieOverlayStorage { overlay, base: self.storage.clone() });
+        let mut trie = Self::new(storage, self.root, None);
+        trie.charge_gas_for_trie_node_access = self.charge_gas_for_trie_node_access;
+        trie
+    }
+
     /// Takes the recorded state proof out of the trie.
     pub fn recorded_storage(&self) -> Option<PartialStorage> {
         self.recorder.as_ref().map(|recorder| recorder.borrow_mut().recorded_storage())
@@ -701,6 +714,11 @@ impl Trie {
         self.flat_storage_chunk_view.is_some()
     }
 
+    /// Whether the lookups of this trie are served from in-memory tries.
+    pub fn has_memtries(&self) -> bool {
+        self.memtries.is_some()
+    }
+
     pub fn internal_get_storage_as_caching_storage(&self) -> Option<&TrieCachingStorage> {
         self.storage.as_caching_storage()
     }
@@ -1262,12 +1280,24 @@ impl Trie {
             return Ok(None);
         }
         let lock = self.memtries.as_ref().unwrap().read().unwrap();
-        let root = lock.get_root(&self.root).ok_or_else(|| {
-            StorageError::StorageInconsistentState(format!(
+        let Some(root) = lock.get_root(&self.root) else {
+            // A view trie may outlive its root in memory, which is garbage collected once the
+            // block gets final. The state is still on disk, so look it up there instead.
+            if let Some(storage) = self.storage.as_caching_storage().filter(|s| s.is_view) {
+                MEM_TRIE_VIEW_FALLBACKS.with_label_values(&[&storage.shard_uid.to_string()]).inc();
+                drop(lock);
+                return Ok(self
+                    .lookup_from_state_column(
+                        NibbleSlice::new(key),
+                        charge_gas_for_trie_node_access,
+                    )?
+                    .map(OptimizedValueRef::Ref));
+            }
+            return Err(StorageError::StorageInconsistentState(format!(
                 "Failed to find root node {} in memtrie",
                 self.root
-            ))
-        })?;
+            )));
+        };
 
         let mut accessed_nodes = Vec::new();
         let flat_value = memtrie_lookup(root, key, Some(&mut accessed_nodes));
@@ -1869,6 +1899,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_trie_overlay() {
+        let tries = TestTriesBuilder::new().build();
+        let shard_uid = ShardUId::single_shard();
+        let changes = vec![
+            (b"doge".to_vec(), Some(b"coin".to_vec())),
+            (b"horse".to_vec(), Some(b"stallion".to_vec())),
+        ];
+        let root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes);
+
+        // Change the trie without writing the new nodes anywhere.
+        let trie_changes = tries
+            .get_trie_for_shard(shard_uid, root)
+            .update(vec![(b"dog".to_vec(), Some(b"puppy".to_vec())), (b"doge".to_vec(), None)])
+            .unwrap();
+        let new_root = trie_changes.new_root;
+        let trie = tries.get_trie_for_shard(shard_uid, new_root);
+        assert_matches!(trie.get(b"dog"), Err(StorageError::MissingTrieValue(_, _)));
+
+        let overlay = trie_changes
+            .insertions()
+            .iter()
+            .map(|insertion| (*insertion.hash(), insertion.payload().into()))
+            .collect();
+        let trie = trie.with_overlay(Arc::new(overlay));
+        assert_eq!(trie.get(b"dog"), Ok(Some(b"puppy".to_vec())));
+        assert_eq!(trie.get(b"doge"), Ok(None));
+        // Nodes which did not change are still read from the store.
+        assert_eq!(trie.get(b"horse"), Ok(Some(b"stallion".to_vec())));
+    }
+
     #[test]
     fn test_trie_recording_reads_update() {
         let tries = TestTriesBuilder::new().build();
@@ -9,7 +9,7 @@ use unc_primitives::receipt::Receipt;
 use unc_primitives::shard_layout::ShardUId;
 use unc_primitives::state_part::PartId;
 use unc_primitives::trie_key::trie_key_parsers::parse_account_id_from_raw_key;
-use unc_primitives::trie_key::TrieKey;
+use unc_primitives::trie_key::{col, TrieKey};
 use unc_primitives::types::{
     ConsolidatedStateChange, StateChangeCause, StateChangesForResharding, StateRoot,
 };
@@ -55,6 +55,21 @@ impl ShardTries {
                     }
                     None => {}
                 },
+                // The timeout queue is not split by account, so every child gets a full copy.
+                // Entries whose yield lives in another child are skipped once they expire, as
+                // the corresponding `PromiseYieldReceipt` marker is not found there.
+                // Global contracts are resolved by hash from any account of the shard, so every
+                // child needs them as well.
+                TrieKey::PromiseYieldIndices
+                | TrieKey::PromiseYieldTimeout { .. }
+                | TrieKey::GlobalContractCode { .. } => {
+                    for trie_update in trie_updates.values_mut() {
+                        match &value {
+                            Some(value) => trie_update.set(trie_key.clone(), value.clone()),
+                            None => trie_update.remove(trie_key.clone()),
+                        }
+                    }
+                }
                 TrieKey::Account { account_id }
                 | TrieKey::ContractCode { account_id }
                 | TrieKey::AccessKey { account_id, .. }
@@ -62,6 +77,7 @@ impl ShardTries {
                 | TrieKey::PostponedReceiptId { receiver_id: account_id, .. }
                 | TrieKey::PendingDataCount { receiver_id: account_id, .. }
                 | TrieKey::PostponedReceipt { receiver_id: account_id, .. }
+                | TrieKey::PromiseYieldReceipt { receiver_id: account_id, .. }
                 | TrieKey::Rsa2048Keys { account_id, .. }
                 | TrieKey::ContractData { account_id, .. } => {
                     let new_shard_uid = account_id_to_shard_uid(account_id);
@@ -134,6 +150,24 @@ impl ShardTries {
     ) -> Result<(StoreUpdate, HashMap<ShardUId, StateRoot>), StorageError> {
         let mut changes_by_shard: HashMap<_, Vec<_>> = HashMap::new();
         for (raw_key, value) in values.into_iter() {
+            // The yielded receipts timeout queue and the global contract registry are copied to
+            // every child, see `apply_state_changes_to_children_states`.
+            if matches!(
+                raw_key.first(),
+                Some(
+                    &col::PROMISE_YIELD_INDICES
+                        | &col::PROMISE_YIELD_TIMEOUT
+                        | &col::GLOBAL_CONTRACT_CODE
+                )
+            ) {
+                for shard_uid in state_roots.keys() {
+                    changes_by_shard
+                        .entry(*shard_uid)
+                        .or_default()
+                        .push((raw_key.clone(), value.clone()));
+                }
+                continue;
+            }
             if let Some(new_shard_uid) = key_to_shard_id(&raw_key)? {
                 changes_by_shard.entry(new_shard_uid).or_default().push((raw_key, value));
             }
@@ -139,12 +139,30 @@ impl ShardTries {
         ));
         let flat_storage_chunk_view = block_hash
             .and_then(|block_hash| self.0.flat_storage_manager.chunk_view(shard_uid, block_hash));
-        Trie::new_with_memtries(
-            storage,
-            self.get_mem_tries(shard_uid),
-            state_root,
-            flat_storage_chunk_view,
-        )
+        let memtries = if is_view {
+            self.get_view_mem_tries(shard_uid, &state_root)
+        } else {
+            self.get_mem_tries(shard_uid)
+        };
+        Trie::new_with_memtries(storage, memtries, state_root, flat_storage_chunk_view)
+    }
+
+    /// Returns the in-memory tries a view trie for `state_root` should read from, if any.
+    ///
+    /// In-memory tries only keep the roots of blocks which are not yet final, so views of older
+    /// state are served from disk.
+    fn get_view_mem_tries(
+        &self,
+        shard_uid: ShardUId,
+        state_root: &StateRoot,
+    ) -> Option<Arc<RwLock<MemTries>>> {
+        if !self.0.trie_config.serve_view_queries_from_mem_tries {
+            return None;
+        }
+        let memtries = self.get_mem_tries(shard_uid)?;
+        let has_root = *state_root == Trie::EMPTY_ROOT
+            || memtries.read().unwrap().get_root(state_root).is_some();
+        has_root.then_some(memtries)
     }
 
     pub fn get_trie_for_shard(&self, shard_uid: ShardUId, state_root: StateRoot) -> Trie {
@@ -469,6 +487,10 @@ impl WrappedTrieChanges {
         }
     }
 
+    pub fn trie_changes(&self) -> &TrieChanges {
+        &self.trie_changes
+    }
+
     pub fn state_changes(&self) -> &[RawStateChangesWithTrieKey] {
         &self.state_changes
     }
@@ -698,6 +720,7 @@ mod test {
             enable_receipt_prefetching: false,
             load_mem_tries_for_shards: Vec::new(),
             load_mem_tries_for_all_shards: false,
+            serve_view_queries_from_mem_tries: false,
         };
         let shard_uids = Vec::from([ShardUId::single_shard()]);
         ShardTries::new(
@@ -816,6 +839,7 @@ mod test {
             enable_receipt_prefetching: false,
             load_mem_tries_for_shards: Vec::new(),
             load_mem_tries_for_all_shards: false,
+            serve_view_queries_from_mem_tries: false,
         };
         let shard_uids = Vec::from([ShardUId { shard_id: 0, version: 0 }]);
         let shard_uid = *shard_uids.first().unwrap();
@@ -876,4 +900,48 @@ mod test {
         let mut iter = store.iter_prefix(DBCol::State, &key_prefix);
         assert!(iter.next().is_none());
     }
+
+    #[test]
+    fn test_view_trie_from_mem_tries() {
+        let shard_uid = ShardUId::single_shard();
+        let store = create_test_store();
+        let trie_config =
+            TrieConfig { serve_view_queries_from_mem_tries: true, ..TrieConfig::default() };
+        let tries = ShardTries::new(
+            store.clone(),
+            trie_config,
+            &[shard_uid],
+            FlatStorageManager::new(store),
+            StateSnapshotConfig::default(),
+        );
+        tries
+            .0
+            .mem_tries
+            .write()
+            .unwrap()
+            .insert(shard_uid, Arc::new(RwLock::new(MemTries::new(shard_uid))));
+
+        let key = b"alice".to_vec();
+        let val: Vec<u8> = Vec::from([0, 1, 2, 3, 4]);
+        let trie = tries.get_trie_for_shard(shard_uid, Trie::EMPTY_ROOT);
+        let trie_changes = trie.update(vec![(key.clone(), Some(val.clone()))]).unwrap();
+        let mut store_update = tries.store_update();
+        let root = tries.apply_all(&trie_changes, shard_uid, &mut store_update);
+        store_update.commit().unwrap();
+
+        // The root is only on disk so far.
+        let view_trie = tries.get_view_trie_for_shard(shard_uid, root);
+        assert!(!view_trie.has_memtries());
+        assert_eq!(view_trie.get(&key), Ok(Some(val.clone())));
+
+        tries.apply_memtrie_changes(&trie_changes, shard_uid, 1);
+        let view_trie = tries.get_view_trie_for_shard(shard_uid, root);
+        assert!(view_trie.has_memtries());
+        assert_eq!(view_trie.get(&key), Ok(Some(val.clone())));
+
+        // Lookups of a view trie whose root got garbage collected in the meantime go to disk.
+        tries.delete_memtrie_roots_up_to_height(shard_uid, 2);
+        assert!(!tries.get_view_trie_for_shard(shard_uid, root).has_memtries());
+        assert_eq!(view_trie.get(&key), Ok(Some(val)));
+    }
 }
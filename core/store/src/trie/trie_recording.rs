@@ -88,8 +88,16 @@ mod trie_recording_tests {
         );
 
         // ChunkExtra is needed for in-memory trie loading code to query state roots.
-        let chunk_extra =
-            ChunkExtra::new(&state_root, CryptoHash::default(), Vec::new(), Vec::new(), 0, 0, 0);
+        let chunk_extra = ChunkExtra::new(
+            &state_root,
+            CryptoHash::default(),
+            Vec::new(),
+            Vec::new(),
+            0,
+            0,
+            0,
+            None,
+        );
         let mut update_for_chunk_extra = tries_for_building.store_update();
         update_for_chunk_extra
             .set_ser(
@@ -5,6 +5,7 @@ use crate::{metrics, DBCol, MissingTrieValueContext, PrefetchApi, StorageError,
 use lru::LruCache;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use unc_o11y::log_assert;
 use unc_o11y::metrics::prometheus;
@@ -346,6 +347,23 @@ impl TrieMemoryPartialStorage {
     }
 }
 
+/// Storage which looks trie nodes and values up in memory before reading them from another storage.
+///
+/// This lets a trie built on top of changes which were never written to disk be read as a whole.
+pub struct TrieOverlayStorage {
+    pub(crate) overlay: Arc<HashMap<CryptoHash, Arc<[u8]>>>,
+    pub(crate) base: Rc<dyn TrieStorage>,
+}
+
+impl TrieStorage for TrieOverlayStorage {
+    fn retrieve_raw_bytes(&self, hash: &CryptoHash) -> Result<Arc<[u8]>, StorageError> {
+        match self.overlay.get(hash) {
+            Some(value) => Ok(value.clone()),
+            None => self.base.retrieve_raw_bytes(hash),
+        }
+    }
+}
+
 /// Storage for reading State nodes and values from DB which caches reads.
 ///
 /// Important: The TrieCachingStorage contains the shard cache, which is
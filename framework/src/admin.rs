@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use unc_chain_configs::{config_diff, ConfigUpdateLog, MutableConfigValue};
+use unc_dyn_configs::UpdateableConfigLoader;
+use unc_jsonrpc_primitives::types::admin::{AdminHandler, RpcAdminError};
+use unc_primitives::types::BlockHeight;
+use unc_store::Store;
+
+pub(crate) struct AdminHandlerImpl {
+    pub home_dir: PathBuf,
+    /// Not available when the node is started without a SIGHUP handler, e.g. in tests.
+    pub config_loader: Option<UpdateableConfigLoader>,
+    pub expected_shutdown: MutableConfigValue<Option<BlockHeight>>,
+    pub config_updates: ConfigUpdateLog,
+    pub store: Store,
+}
+
+impl AdminHandler for AdminHandlerImpl {
+    fn reload_config(&self) -> Result<(), RpcAdminError> {
+        let Some(config_loader) = &self.config_loader else {
+            return Err(RpcAdminError::ActionFailed {
+                error_message: "Config reloading is not enabled on this node".to_string(),
+            });
+        };
+        // Errors are also passed to the loader, so that they're reported the same way as on SIGHUP.
+        let updateable_configs = crate::dyn_config::read_updateable_configs(&self.home_dir);
+        let result = updateable_configs
+            .as_ref()
+            .map(|_| ())
+            .map_err(|err| RpcAdminError::ActionFailed { error_message: err.to_string() });
+        config_loader.reload(updateable_configs);
+        result
+    }
+
+    fn set_expected_shutdown(&self, block_height: Option<BlockHeight>) {
+        // Note that a later config reload overrides this value with the one from `config.json`.
+        let old = self.expected_shutdown.get();
+        self.config_updates.record(config_diff(
+            "client_config.expected_shutdown",
+            &old,
+            &block_height,
+        ));
+        self.expected_shutdown.update(block_height);
+    }
+
+    fn compact_database(&self) -> Result<(), RpcAdminError> {
+        self.store
+            .compact()
+            .map_err(|err| RpcAdminError::InternalError { error_message: err.to_string() })
+    }
+}
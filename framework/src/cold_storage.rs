@@ -50,6 +50,7 @@ enum ColdStoreCopyResult {
 /// for the next available produced block after current cold store head.
 /// Updates cold store head after.
 fn cold_store_copy(
+    split_storage_config: &SplitStorageConfig,
     hot_store: &Store,
     cold_store: &Store,
     cold_db: &Arc<ColdDB>,
@@ -107,7 +108,8 @@ fn cold_store_copy(
     let shard_layout = epoch_manager.get_shard_layout(&epoch_id)?;
 
     let mut next_height = cold_head_height + 1;
-    while !update_cold_db(cold_db, hot_store, &shard_layout, &next_height)? {
+    let retained_accounts = split_storage_config.retained_accounts.as_ref();
+    while !update_cold_db(cold_db, hot_store, &shard_layout, &next_height, retained_accounts)? {
         next_height += 1;
         if next_height > hot_final_head_height {
             return Err(anyhow::anyhow!(
@@ -190,7 +192,14 @@ fn cold_store_initial_migration(
     let hot_final_head_height = hot_final_head.height;
 
     let batch_size = split_storage_config.cold_store_initial_migration_batch_size;
-    match copy_all_data_to_cold(cold_db.clone(), hot_store, batch_size, keep_going)? {
+    let retained_accounts = split_storage_config.retained_accounts.as_ref();
+    match copy_all_data_to_cold(
+        cold_db.clone(),
+        hot_store,
+        batch_size,
+        keep_going,
+        retained_accounts,
+    )? {
         CopyAllDataToColdStatus::EverythingCopied => {
             tracing::info!(target: "cold_store", "Initial population was successful, writing cold head of height {}", hot_final_head_height);
             update_cold_head(cold_db, hot_store, &hot_final_head_height)?;
@@ -266,8 +275,14 @@ fn cold_store_loop(
         }
 
         let instant = std::time::Instant::now();
-        let result =
-            cold_store_copy(&hot_store, &cold_store, &cold_db, genesis_height, epoch_manager);
+        let result = cold_store_copy(
+            split_storage_config,
+            &hot_store,
+            &cold_store,
+            &cold_db,
+            genesis_height,
+            epoch_manager,
+        );
         let duration = instant.elapsed();
 
         let result_string = cold_store_copy_result_to_string(&result);
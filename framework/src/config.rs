@@ -20,8 +20,9 @@ use unc_chain_configs::{
     default_sync_height_threshold, default_sync_step_period, default_transaction_pool_size_limit,
     default_trie_viewer_state_size_limit, default_tx_routing_height_horizon,
     default_view_client_threads, default_view_client_throttle_period, get_initial_supply,
-    ClientConfig, GCConfig, Genesis, GenesisConfig, GenesisValidationMode, LogSummaryStyle,
-    MutableConfigValue, ReshardingConfig, StateSyncConfig,
+    ClientConfig, ConfigUpdateLog, GCConfig, Genesis, GenesisConfig, GenesisValidationMode,
+    LogSummaryStyle, MutableConfigValue, ReshardingConfig, RetainedAccounts, StateSyncConfig,
+    TransactionPoolEvictionPolicy,
 };
 use unc_config_utils::{ValidationError, ValidationErrors};
 use unc_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer};
@@ -338,6 +339,9 @@ pub struct Config {
     /// Setting this value too low (<1MB) on the validator might lead to production of smaller
     /// chunks and underutilizing the capacity of the network.
     pub transaction_pool_size_limit: Option<u64>,
+    /// What to do when a new transaction doesn't fit into `transaction_pool_size_limit`:
+    /// `reject` it, evict transactions with a `lowest_priority`, or evict the `oldest` ones.
+    pub transaction_pool_eviction_policy: TransactionPoolEvictionPolicy,
     // Configuration for resharding.
     pub resharding_config: ReshardingConfig,
     /// If the node is not a chunk producer within that many blocks, then route
@@ -382,6 +386,7 @@ impl Default for Config {
             state_sync: default_state_sync(),
             state_sync_enabled: default_state_sync_enabled(),
             transaction_pool_size_limit: default_transaction_pool_size_limit(),
+            transaction_pool_eviction_policy: TransactionPoolEvictionPolicy::default(),
             enable_multiline_logging: default_enable_multiline_logging(),
             resharding_config: ReshardingConfig::default(),
             tx_routing_height_horizon: default_tx_routing_height_horizon(),
@@ -419,6 +424,12 @@ pub struct SplitStorageConfig {
 
     #[serde(default = "default_cold_store_loop_sleep_duration")]
     pub cold_store_loop_sleep_duration: Duration,
+
+    /// Turns the node into a partial archival node: cold storage keeps state,
+    /// state changes and outcomes only for accounts matching these patterns.
+    /// Patterns are exact account ids or prefixes ending with `*`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retained_accounts: Option<RetainedAccounts>,
 }
 
 impl Default for SplitStorageConfig {
@@ -430,6 +441,7 @@ impl Default for SplitStorageConfig {
             cold_store_initial_migration_loop_sleep_duration:
                 default_cold_store_initial_migration_loop_sleep_duration(),
             cold_store_loop_sleep_duration: default_cold_store_loop_sleep_duration(),
+            retained_accounts: None,
         }
     }
 }
@@ -646,8 +658,13 @@ impl UncConfig {
                 doosmslug_step_period: config.consensus.doomslug_step_period,
                 archive: config.archive,
                 save_trie_changes: config.save_trie_changes.unwrap_or(!config.archive),
+                archive_retained_accounts: config
+                    .split_storage
+                    .as_ref()
+                    .and_then(|split_storage| split_storage.retained_accounts.clone()),
                 log_summary_style: config.log_summary_style,
                 gc: config.gc,
+                tracked_shards: config.tracked_shards,
                 view_client_threads: config.view_client_threads,
                 view_client_throttle_period: config.view_client_throttle_period,
                 trie_viewer_state_size_limit: config.trie_viewer_state_size_limit,
@@ -659,6 +676,7 @@ impl UncConfig {
                 state_sync_enabled: config.state_sync_enabled,
                 state_sync: config.state_sync.unwrap_or_default(),
                 transaction_pool_size_limit: config.transaction_pool_size_limit,
+                transaction_pool_eviction_policy: config.transaction_pool_eviction_policy,
                 enable_multiline_logging: config.enable_multiline_logging.unwrap_or(true),
                 resharding_config: MutableConfigValue::new(
                     config.resharding_config,
@@ -669,6 +687,7 @@ impl UncConfig {
                     config.produce_chunk_add_transactions_time_limit,
                     "produce_chunk_add_transactions_time_limit",
                 ),
+                config_updates: ConfigUpdateLog::default(),
             },
             network_config: NetworkConfig::new(
                 config.network,
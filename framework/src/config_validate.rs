@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 use std::path::Path;
-use unc_chain_configs::{ExternalStorageLocation, SyncConfig};
+use unc_chain_configs::{ExternalStorageConfig, ExternalStorageLocation, SyncConfig};
 use unc_config_utils::{ValidationError, ValidationErrors};
 
 use crate::config::Config;
@@ -43,6 +43,16 @@ impl<'a> ConfigValidator<'a> {
             self.validation_errors.push_config_semantics_error(error_message);
         }
 
+        let retains_accounts = self
+            .config
+            .split_storage
+            .as_ref()
+            .is_some_and(|split_storage| split_storage.retained_accounts.is_some());
+        if retains_accounts && (!self.config.archive || self.config.cold_store.is_none()) {
+            let error_message = "'config.split_storage.retained_accounts' is set, but the node is not an archival node with cold_store configured.".to_string();
+            self.validation_errors.push_config_semantics_error(error_message);
+        }
+
         if self.config.consensus.min_block_production_delay
             > self.config.consensus.max_block_production_delay
         {
@@ -108,6 +118,9 @@ impl<'a> ConfigValidator<'a> {
                             self.validation_errors.push_config_semantics_error(error_message);
                         }
                     }
+                    ExternalStorageLocation::Http { base_url, .. } => {
+                        self.validate_http_base_url(base_url, "config.state_sync.dump.location");
+                    }
                 }
 
                 if let Some(credentials_file) = &dump_config.credentials_file {
@@ -120,28 +133,22 @@ impl<'a> ConfigValidator<'a> {
             match &state_sync.sync {
                 SyncConfig::Peers => {}
                 SyncConfig::ExternalStorage(config) => {
-                    match &config.location {
-                        ExternalStorageLocation::S3 { bucket, region } => {
-                            if bucket.is_empty() || region.is_empty() {
-                                let error_message = format!("'config.state_sync.sync.ExternalStorage.location.S3.bucket' and 'config.state_sync.sync.ExternalStorage.location.S3.region' need to be specified when 'config.state_sync.sync.ExternalStorage.location.S3' is present.");
-                                self.validation_errors.push_config_semantics_error(error_message);
-                            }
-                        }
-                        ExternalStorageLocation::Filesystem { root_dir } => {
-                            if root_dir.as_path() == Path::new("") {
-                                let error_message = format!("'config.state_sync.sync.ExternalStorage.location.Filesystem.root_dir' needs to be specified when 'config.state_sync.sync.ExternalStorage.location.Filesystem' is present.");
-                                self.validation_errors.push_config_semantics_error(error_message);
-                            }
-                        }
-                        ExternalStorageLocation::GCS { bucket } => {
-                            if bucket.is_empty() {
-                                let error_message = format!("'config.state_sync.sync.ExternalStorage.location.GCS.bucket' needs to be specified when 'config.state_sync.sync.ExternalStorage.location.GCS' is present.");
-                                self.validation_errors.push_config_semantics_error(error_message);
-                            }
-                        }
+                    self.validate_external_storage_config(
+                        config,
+                        "config.state_sync.sync.ExternalStorage",
+                    );
+                }
+                SyncConfig::Hybrid(config) => {
+                    self.validate_external_storage_config(
+                        &config.external,
+                        "config.state_sync.sync.Hybrid.external",
+                    );
+                    if config.sources.is_empty() {
+                        let error_message = format!("'config.state_sync.sync.Hybrid.sources' needs to contain at least one source");
+                        self.validation_errors.push_config_semantics_error(error_message);
                     }
-                    if config.num_concurrent_requests == 0 {
-                        let error_message = format!("'config.state_sync.sync.ExternalStorage.num_concurrent_requests' needs to be greater than 0");
+                    if config.attempts_per_source == 0 {
+                        let error_message = format!("'config.state_sync.sync.Hybrid.attempts_per_source' needs to be greater than 0");
                         self.validation_errors.push_config_semantics_error(error_message);
                     }
                 }
@@ -159,6 +166,46 @@ impl<'a> ConfigValidator<'a> {
         }
     }
 
+    /// Validates the external storage used to fetch state parts, `prefix` is the path of the
+    /// config in config.json.
+    fn validate_external_storage_config(&mut self, config: &ExternalStorageConfig, prefix: &str) {
+        match &config.location {
+            ExternalStorageLocation::S3 { bucket, region } => {
+                if bucket.is_empty() || region.is_empty() {
+                    let error_message = format!("'{prefix}.location.S3.bucket' and '{prefix}.location.S3.region' need to be specified when '{prefix}.location.S3' is present.");
+                    self.validation_errors.push_config_semantics_error(error_message);
+                }
+            }
+            ExternalStorageLocation::Filesystem { root_dir } => {
+                if root_dir.as_path() == Path::new("") {
+                    let error_message = format!("'{prefix}.location.Filesystem.root_dir' needs to be specified when '{prefix}.location.Filesystem' is present.");
+                    self.validation_errors.push_config_semantics_error(error_message);
+                }
+            }
+            ExternalStorageLocation::GCS { bucket } => {
+                if bucket.is_empty() {
+                    let error_message = format!("'{prefix}.location.GCS.bucket' needs to be specified when '{prefix}.location.GCS' is present.");
+                    self.validation_errors.push_config_semantics_error(error_message);
+                }
+            }
+            ExternalStorageLocation::Http { base_url, .. } => {
+                self.validate_http_base_url(base_url, &format!("{prefix}.location"));
+            }
+        }
+        if config.num_concurrent_requests == 0 {
+            let error_message =
+                format!("'{prefix}.num_concurrent_requests' needs to be greater than 0");
+            self.validation_errors.push_config_semantics_error(error_message);
+        }
+    }
+
+    fn validate_http_base_url(&mut self, base_url: &str, prefix: &str) {
+        if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            let error_message = format!("'{prefix}.Http.base_url' needs to be an http:// or https:// URL, got '{base_url}'.");
+            self.validation_errors.push_config_semantics_error(error_message);
+        }
+    }
+
     fn result_with_full_error(&self) -> Result<(), ValidationError> {
         if self.validation_errors.is_empty() {
             Ok(())
@@ -172,6 +219,7 @@ impl<'a> ConfigValidator<'a> {
 #[cfg(test)]
 mod test {
     use super::*;
+    use unc_chain_configs::StateSyncSource;
 
     #[test]
     #[should_panic(expected = "gc config values should all be greater than 0")]
@@ -231,6 +279,20 @@ mod test {
         validate_config(&config).unwrap();
     }
 
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: 'config.split_storage.retained_accounts' is set, but the node is not an archival node with cold_store configured."
+    )]
+    fn test_retained_accounts_without_cold_store() {
+        let mut config = Config::default();
+        config.archive = true;
+        config.split_storage = Some(crate::config::SplitStorageConfig {
+            retained_accounts: Some(vec!["miner-*".to_string()].try_into().unwrap()),
+            ..Default::default()
+        });
+        validate_config(&config).unwrap();
+    }
+
     #[test]
     #[should_panic(
         expected = "\\nconfig.json semantic issue: 'config.tx_routing_height_horizon' needs to be at least 2, got 1."
@@ -250,4 +312,62 @@ mod test {
         config.tx_routing_height_horizon = 1_000_000_000;
         validate_config(&config).unwrap();
     }
+
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: 'config.state_sync.sync.Hybrid.sources' needs to contain at least one source"
+    )]
+    fn test_hybrid_state_sync_without_sources() {
+        let config = hybrid_state_sync_config("state-parts", vec![], 1);
+        validate_config(&config).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: 'config.state_sync.sync.Hybrid.attempts_per_source' needs to be greater than 0"
+    )]
+    fn test_hybrid_state_sync_zero_attempts_per_source() {
+        let config = hybrid_state_sync_config("state-parts", vec![StateSyncSource::Peers], 0);
+        validate_config(&config).unwrap();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "\\nconfig.json semantic issue: 'config.state_sync.sync.Hybrid.external.location.GCS.bucket' needs to be specified when 'config.state_sync.sync.Hybrid.external.location.GCS' is present."
+    )]
+    fn test_hybrid_state_sync_invalid_external_storage() {
+        let config = hybrid_state_sync_config("", vec![StateSyncSource::ExternalStorage], 1);
+        validate_config(&config).unwrap();
+    }
+
+    #[test]
+    fn test_hybrid_state_sync_valid() {
+        let config = hybrid_state_sync_config(
+            "state-parts",
+            vec![StateSyncSource::ExternalStorage, StateSyncSource::Peers],
+            3,
+        );
+        validate_config(&config).unwrap();
+    }
+
+    fn hybrid_state_sync_config(
+        bucket: &str,
+        sources: Vec<StateSyncSource>,
+        attempts_per_source: u32,
+    ) -> Config {
+        let mut config = Config::default();
+        config.state_sync = Some(unc_chain_configs::StateSyncConfig {
+            dump: None,
+            sync: SyncConfig::Hybrid(unc_chain_configs::HybridSyncConfig {
+                external: ExternalStorageConfig {
+                    location: ExternalStorageLocation::GCS { bucket: bucket.to_string() },
+                    num_concurrent_requests: 1,
+                    num_concurrent_requests_during_catchup: 1,
+                },
+                sources,
+                attempts_per_source,
+            }),
+        });
+        config
+    }
 }
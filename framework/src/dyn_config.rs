@@ -1,9 +1,17 @@
 use crate::config::Config;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
-use unc_chain_configs::UpdateableClientConfig;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use unc_async::messaging::Sender;
+use unc_chain_configs::{
+    config_diff, ConfigUpdateLog, MutableConfigValue, UpdateableClientConfig,
+    UpdateableNetworkConfig, UpdateableRpcConfig, UpdateableTelemetryConfig,
+};
 use unc_dyn_configs::{UpdateableConfigLoaderError, UpdateableConfigs};
+use unc_network::types::UpdateNetworkConfig;
 use unc_o11y::log_config::LogConfig;
+use unc_telemetry::UpdateTelemetryEndpoints;
 
 pub const LOG_CONFIG_FILENAME: &str = "log_config.json";
 
@@ -19,22 +27,27 @@ pub fn read_updateable_configs(
             None
         }
     };
-    let updateable_client_config =
-        match Config::from_file(&home_dir.join(crate::config::CONFIG_FILENAME))
-            .map(get_updateable_client_config)
-        {
-            Ok(config) => Some(config),
-            Err(err) => {
-                errs.push(UpdateableConfigLoaderError::ConfigFileError {
-                    file: PathBuf::from(crate::config::CONFIG_FILENAME),
-                    err: err.into(),
-                });
-                None
-            }
-        };
+    let config = match Config::from_file(&home_dir.join(crate::config::CONFIG_FILENAME)) {
+        Ok(config) => Some(config),
+        Err(err) => {
+            errs.push(UpdateableConfigLoaderError::ConfigFileError {
+                file: PathBuf::from(crate::config::CONFIG_FILENAME),
+                err: err.into(),
+            });
+            None
+        }
+    };
     if errs.is_empty() {
         crate::metrics::CONFIG_CORRECT.set(1);
-        Ok(UpdateableConfigs { log_config, client_config: updateable_client_config })
+        Ok(UpdateableConfigs {
+            log_config,
+            client_config: config
+                .as_ref()
+                .map(|config| get_updateable_client_config(config.clone())),
+            network_config: config.as_ref().map(get_updateable_network_config),
+            rpc_config: config.as_ref().and_then(get_updateable_rpc_config),
+            telemetry_config: config.as_ref().map(get_updateable_telemetry_config),
+        })
     } else {
         tracing::warn!(target: "unc-node", "Dynamically updateable configs are not valid. Please fix this ASAP otherwise the node will be unable to restart: {:?}", &errs);
         crate::metrics::CONFIG_CORRECT.set(0);
@@ -49,9 +62,126 @@ pub fn get_updateable_client_config(config: Config) -> UpdateableClientConfig {
         expected_shutdown: config.expected_shutdown,
         resharding_config: config.resharding_config,
         produce_chunk_add_transactions_time_limit: config.produce_chunk_add_transactions_time_limit,
+        gc: config.gc,
+        tracked_shards: config.tracked_shards,
+        transaction_pool_size_limit: config.transaction_pool_size_limit,
     }
 }
 
+pub fn get_updateable_network_config(config: &Config) -> UpdateableNetworkConfig {
+    UpdateableNetworkConfig {
+        blacklist: config.network.blacklist.clone(),
+        max_num_peers: config.network.max_num_peers,
+    }
+}
+
+#[cfg(feature = "json_rpc")]
+pub fn get_updateable_rpc_config(config: &Config) -> Option<UpdateableRpcConfig> {
+    config.rpc.as_ref().map(|rpc| UpdateableRpcConfig {
+        json_payload_max_size: rpc.limits_config.json_payload_max_size,
+    })
+}
+
+#[cfg(not(feature = "json_rpc"))]
+pub fn get_updateable_rpc_config(_config: &Config) -> Option<UpdateableRpcConfig> {
+    None
+}
+
+pub fn get_updateable_telemetry_config(config: &Config) -> UpdateableTelemetryConfig {
+    UpdateableTelemetryConfig { endpoints: config.telemetry.endpoints.clone() }
+}
+
+/// Applies the reloaded network, RPC and telemetry configs to the running actors.
+/// `ClientConfig` updates are applied by `ClientActor` itself, see `ConfigUpdater`.
+/// The returned task finishes once the config updates channel is closed.
+pub(crate) fn spawn_config_update_handler(
+    mut rx: broadcast::Receiver<Result<UpdateableConfigs, Arc<UpdateableConfigLoaderError>>>,
+    config: &Config,
+    config_updates: ConfigUpdateLog,
+    network_sender: Sender<UpdateNetworkConfig>,
+    telemetry_sender: Sender<UpdateTelemetryEndpoints>,
+    json_payload_max_size: Option<MutableConfigValue<usize>>,
+) -> tokio::task::JoinHandle<()> {
+    let mut network_config = get_updateable_network_config(config);
+    let mut rpc_config = get_updateable_rpc_config(config);
+    let mut telemetry_config = get_updateable_telemetry_config(config);
+    tokio::spawn(async move {
+        loop {
+            let updateable_configs = match rx.recv().await {
+                Ok(Ok(updateable_configs)) => updateable_configs,
+                // Errors are reported by `ConfigUpdater`.
+                Ok(Err(_)) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            if let Some(new) = updateable_configs.network_config {
+                if accept_update(
+                    "network_config",
+                    &mut network_config,
+                    new,
+                    UpdateableNetworkConfig::validate,
+                    &config_updates,
+                ) {
+                    network_sender.send(UpdateNetworkConfig {
+                        blacklist: network_config
+                            .blacklist
+                            .iter()
+                            .filter_map(|entry| entry.parse().ok())
+                            .collect(),
+                        max_num_peers: network_config.max_num_peers,
+                    });
+                }
+            }
+            if let (Some(new), Some(current), Some(json_payload_max_size)) =
+                (updateable_configs.rpc_config, rpc_config.as_mut(), &json_payload_max_size)
+            {
+                if accept_update(
+                    "rpc_config",
+                    current,
+                    new,
+                    UpdateableRpcConfig::validate,
+                    &config_updates,
+                ) {
+                    json_payload_max_size.update(current.json_payload_max_size);
+                }
+            }
+            if let Some(new) = updateable_configs.telemetry_config {
+                if accept_update(
+                    "telemetry_config",
+                    &mut telemetry_config,
+                    new,
+                    UpdateableTelemetryConfig::validate,
+                    &config_updates,
+                ) {
+                    telemetry_sender.send(UpdateTelemetryEndpoints {
+                        endpoints: telemetry_config.endpoints.clone(),
+                    });
+                }
+            }
+        }
+    })
+}
+
+/// Validates the new config and records its diff. Returns whether the new
+/// config replaced the current one and needs to be applied.
+fn accept_update<T: Serialize + PartialEq>(
+    section: &str,
+    current: &mut T,
+    new: T,
+    validate: fn(&T) -> Result<(), String>,
+    config_updates: &ConfigUpdateLog,
+) -> bool {
+    if *current == new {
+        return false;
+    }
+    if let Err(err) = validate(&new) {
+        tracing::warn!(target: "config", section, %err, "Ignoring the invalid config update");
+        return false;
+    }
+    config_updates.record(config_diff(section, current, &new));
+    *current = new;
+    true
+}
+
 fn read_log_config(home_dir: &Path) -> Result<Option<LogConfig>, UpdateableConfigLoaderError> {
     read_json_config::<LogConfig>(&home_dir.join(LOG_CONFIG_FILENAME))
 }
@@ -89,3 +219,87 @@ where
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use unc_async::messaging::{CanSend, IntoSender};
+
+    /// Collects the messages sent to it.
+    struct Capture<M>(Arc<Mutex<Vec<M>>>);
+
+    impl<M: Send + 'static> CanSend<M> for Capture<M> {
+        fn send(&self, message: M) {
+            self.0.lock().unwrap().push(message);
+        }
+    }
+
+    fn capture<M: Send + 'static>() -> (Sender<M>, Arc<Mutex<Vec<M>>>) {
+        let messages = Arc::new(Mutex::new(vec![]));
+        (Capture(messages.clone()).into_sender(), messages)
+    }
+
+    /// Valid updates are applied and recorded, invalid and unchanged ones are ignored.
+    #[tokio::test]
+    async fn test_config_update_handler() {
+        let mut config = Config::default();
+        config.network.max_num_peers = 40;
+        config.telemetry.endpoints = vec!["https://telemetry.unc.org".to_string()];
+        let (tx, rx) = broadcast::channel(16);
+        let config_updates = ConfigUpdateLog::default();
+        let (network_sender, network_messages) = capture();
+        let (telemetry_sender, telemetry_messages) = capture();
+        let handler = spawn_config_update_handler(
+            rx,
+            &config,
+            config_updates.clone(),
+            network_sender,
+            telemetry_sender,
+            None,
+        );
+
+        let updateable_configs = |max_num_peers, endpoints: &[&str]| UpdateableConfigs {
+            network_config: Some(UpdateableNetworkConfig {
+                blacklist: vec!["127.0.0.1".to_string()],
+                max_num_peers,
+            }),
+            telemetry_config: Some(UpdateableTelemetryConfig {
+                endpoints: endpoints.iter().map(|endpoint| endpoint.to_string()).collect(),
+            }),
+            ..Default::default()
+        };
+        // The network config is updated, the invalid telemetry endpoint is rejected.
+        tx.send(Ok(updateable_configs(10, &["not a url"]))).unwrap();
+        // Nothing changed in the network config, the telemetry endpoint is updated.
+        tx.send(Ok(updateable_configs(10, &["https://telemetry2.unc.org"]))).unwrap();
+        // Invalid network config.
+        tx.send(Ok(updateable_configs(0, &["https://telemetry2.unc.org"]))).unwrap();
+        drop(tx);
+        handler.await.unwrap();
+
+        let network_messages = network_messages.lock().unwrap();
+        assert_eq!(network_messages.len(), 1);
+        assert_eq!(network_messages[0].max_num_peers, 10);
+        let telemetry_messages = telemetry_messages.lock().unwrap();
+        let endpoints: Vec<_> =
+            telemetry_messages.iter().map(|message| message.endpoints.clone()).collect();
+        assert_eq!(endpoints, vec![vec!["https://telemetry2.unc.org".to_string()]]);
+
+        let fields: Vec<Vec<String>> = config_updates
+            .get()
+            .into_iter()
+            .map(|update| update.changes.into_iter().map(|change| change.field).collect())
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                vec![
+                    "network_config.blacklist".to_string(),
+                    "network_config.max_num_peers".to_string()
+                ],
+                vec!["telemetry_config.endpoints".to_string()],
+            ]
+        );
+    }
+}
@@ -22,7 +22,6 @@ use unc_chain::state_snapshot_actor::{
 use unc_chain::types::RuntimeAdapter;
 use unc_chain::{Chain, ChainGenesis};
 use unc_chain_configs::ReshardingHandle;
-use unc_chain_configs::SyncConfig;
 use unc_chunks::shards_manager_actor::start_shards_manager;
 use unc_client::sync::adapter::SyncAdapter;
 use unc_client::{start_client, start_view_client, ClientActor, ConfigUpdater, ViewClientActor};
@@ -37,6 +36,8 @@ use unc_store::metrics::spawn_db_metrics_loop;
 use unc_store::{DBCol, Mode, NodeStorage, Store, StoreOpenerError};
 use unc_telemetry::TelemetryActor;
 
+#[cfg(feature = "json_rpc")]
+mod admin;
 pub mod append_only_map;
 pub mod cold_storage;
 pub mod config;
@@ -201,6 +202,20 @@ fn get_split_store(config: &UncConfig, storage: &NodeStorage) -> anyhow::Result<
     Ok(storage.get_split_store())
 }
 
+/// Reads the bearer token which authenticates the admin RPC requests.
+#[cfg(feature = "json_rpc")]
+fn read_admin_auth_token(path: &Path) -> anyhow::Result<String> {
+    let auth_token = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read admin RPC auth token from {}", path.display()))?;
+    let auth_token = auth_token.trim();
+    anyhow::ensure!(
+        !auth_token.is_empty(),
+        "admin RPC auth token file {} is empty",
+        path.display()
+    );
+    Ok(auth_token.to_string())
+}
+
 pub struct UncNode {
     pub client: Addr<ClientActor>,
     pub view_client: Addr<ViewClientActor>,
@@ -220,7 +235,7 @@ pub struct UncNode {
 }
 
 pub fn start_with_config(home_dir: &Path, config: UncConfig) -> anyhow::Result<UncNode> {
-    start_with_config_and_synchronization(home_dir, config, None, None)
+    start_with_config_and_synchronization(home_dir, config, None, None, None)
 }
 
 pub fn start_with_config_and_synchronization(
@@ -230,6 +245,8 @@ pub fn start_with_config_and_synchronization(
     // `ClientActor` gets dropped.
     shutdown_signal: Option<broadcast::Sender<()>>,
     config_updater: Option<ConfigUpdater>,
+    // Used by the admin RPC to reload the configs same as on SIGHUP.
+    config_loader: Option<unc_dyn_configs::UpdateableConfigLoader>,
 ) -> anyhow::Result<UncNode> {
     let storage = open_storage(home_dir, &mut config)?;
     let db_metrics_arbiter = if config.client_config.enable_statistics_export {
@@ -253,7 +270,10 @@ pub fn start_with_config_and_synchronization(
 
     let epoch_manager =
         EpochManager::new_arc_handle(storage.get_hot_store(), &config.genesis.config);
-    let shard_tracker = ShardTracker::new(epoch_manager.clone());
+    let shard_tracker = ShardTracker::new_with_tracked_shards(
+        epoch_manager.clone(),
+        &config.client_config.tracked_shards,
+    );
     let runtime = NightshadeRuntime::from_config(
         home_dir,
         storage.get_hot_store(),
@@ -268,7 +288,7 @@ pub fn start_with_config_and_synchronization(
         if let Some(split_store) = &split_store {
             let view_epoch_manager =
                 EpochManager::new_arc_handle(split_store.clone(), &config.genesis.config);
-            let view_shard_tracker = ShardTracker::new(epoch_manager.clone());
+            let view_shard_tracker = shard_tracker.clone();
             let view_runtime = NightshadeRuntime::from_config(
                 home_dir,
                 split_store.clone(),
@@ -329,6 +349,7 @@ pub fn start_with_config_and_synchronization(
         get_make_snapshot_callback(state_snapshot_actor, runtime.get_flat_storage_manager());
     let snapshot_callbacks = SnapshotCallbacks { make_snapshot_callback, delete_snapshot_callback };
 
+    let config_update_rx = config_updater.as_ref().map(ConfigUpdater::subscribe);
     let (client_actor, client_arbiter_handle, resharding_handle) = start_client(
         config.client_config.clone(),
         chain_genesis.clone(),
@@ -340,13 +361,13 @@ pub fn start_with_config_and_synchronization(
         network_adapter.clone().into(),
         shards_manager_adapter.as_sender(),
         config.validator_signer.clone(),
-        telemetry,
+        telemetry.clone(),
         Some(snapshot_callbacks),
         shutdown_signal,
         adv,
         config_updater,
     );
-    if let SyncConfig::Peers = config.client_config.state_sync.sync {
+    if config.client_config.state_sync.sync.uses_peers() {
         client_adapter_for_sync.bind(client_actor.clone().with_auto_span_context())
     };
     client_adapter_for_shards_manager.bind(client_actor.clone().with_auto_span_context());
@@ -390,11 +411,27 @@ pub fn start_with_config_and_synchronization(
     )
     .context("PeerManager::spawn()")?;
     network_adapter.bind(network_actor.clone().with_auto_span_context());
-    if let SyncConfig::Peers = config.client_config.state_sync.sync {
+    if config.client_config.state_sync.sync.uses_peers() {
         network_adapter_for_sync.bind(network_actor.clone().with_auto_span_context())
     }
     #[cfg(feature = "json_rpc")]
-    if let Some(rpc_config) = config.rpc_config {
+    let json_payload_max_size = config.rpc_config.as_ref().map(|rpc_config| {
+        unc_chain_configs::MutableConfigValue::new(
+            rpc_config.limits_config.json_payload_max_size,
+            "json_payload_max_size",
+        )
+    });
+    #[cfg(not(feature = "json_rpc"))]
+    let json_payload_max_size = None;
+    #[cfg(feature = "json_rpc")]
+    let admin_rpc_config =
+        config.rpc_config.as_mut().and_then(|rpc_config| rpc_config.admin.take());
+    #[cfg(feature = "json_rpc")]
+    let admin_store = hot_store.clone();
+    #[cfg(feature = "json_rpc")]
+    if let (Some(rpc_config), Some(json_payload_max_size)) =
+        (config.rpc_config, json_payload_max_size.clone())
+    {
         let entity_debug_handler = EntityDebugHandlerImpl {
             epoch_manager: view_epoch_manager,
             runtime: view_runtime,
@@ -405,10 +442,42 @@ pub fn start_with_config_and_synchronization(
             config.genesis.config.clone(),
             client_actor.clone(),
             view_client.clone(),
-            Some(network_actor),
+            Some(network_actor.clone()),
             Arc::new(entity_debug_handler),
+            json_payload_max_size,
         ));
     }
+    #[cfg(feature = "json_rpc")]
+    if let Some(admin_config) = admin_rpc_config {
+        let auth_token = read_admin_auth_token(&home_dir.join(&admin_config.auth_token_file))?;
+        let admin_handler = admin::AdminHandlerImpl {
+            home_dir: home_dir.to_path_buf(),
+            config_loader,
+            expected_shutdown: config.client_config.expected_shutdown.clone(),
+            config_updates: config.client_config.config_updates.clone(),
+            store: admin_store,
+        };
+        rpc_servers.extend(unc_jsonrpc::start_admin_http(
+            admin_config,
+            auth_token,
+            client_actor.clone(),
+            Some(network_actor.clone()),
+            Arc::new(admin_handler),
+        ));
+    }
+    #[cfg(not(feature = "json_rpc"))]
+    let _ = config_loader;
+
+    if let Some(config_update_rx) = config_update_rx {
+        dyn_config::spawn_config_update_handler(
+            config_update_rx,
+            &config.config,
+            config.client_config.config_updates.clone(),
+            network_actor.with_auto_span_context().into_sender(),
+            telemetry.with_auto_span_context().into_sender(),
+            json_payload_max_size,
+        );
+    }
 
     rpc_servers.shrink_to_fit();
 
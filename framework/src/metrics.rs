@@ -138,6 +138,16 @@ pub(crate) static STATE_SYNC_APPLY_PART_DELAY: Lazy<HistogramVec> = Lazy::new(||
     .unwrap()
 });
 
+pub(crate) static VIEW_QUERY_DELAY: Lazy<HistogramVec> = Lazy::new(|| {
+    try_create_histogram_vec(
+        "unc_view_query_delay_sec",
+        "Latency of answering a view query, by the kind of query and whether the state was read from in-memory tries or from disk",
+        &["query", "source"],
+        Some(exponential_buckets(0.0001, 2.0, 16).unwrap()),
+    )
+    .unwrap()
+});
+
 pub(crate) static STATE_SYNC_OBTAIN_PART_DELAY: Lazy<HistogramVec> = Lazy::new(|| {
     try_create_histogram_vec(
         "unc_state_sync_obtain_part_delay_sec",
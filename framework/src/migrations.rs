@@ -113,6 +113,11 @@ impl<'a> unc_store::StoreMigrator for Migrator<'a> {
             }
             36 => unc_store::migrations::migrate_36_to_37(store),
             37 => unc_store::migrations::migrate_37_to_38(store),
+            38 => {
+                // The PeerReputations column is created empty when the database is opened.
+                tracing::info!(target: "migrations", "Migrating DB version from 38 to 39. Peer reputations will be stored on disk as peers are observed.");
+                Ok(())
+            }
             DB_VERSION.. => unreachable!(),
         }
     }
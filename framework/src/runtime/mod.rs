@@ -4,7 +4,6 @@ use crate::UncConfig;
 
 use borsh::BorshDeserialize;
 use errors::FromStateViewerErrors;
-use node_runtime::adapter::ViewRuntimeAdapter;
 use node_runtime::state_viewer::TrieViewer;
 use unc_chain::types::{
     ApplyChunkBlockContext, ApplyChunkResult, ApplyChunkShardContext, ApplyResultForResharding,
@@ -19,6 +18,8 @@ use unc_epoch_manager::{EpochManagerAdapter, EpochManagerHandle};
 use unc_parameters::{ActionCosts, ExtCosts, RuntimeConfigStore};
 use unc_pool::types::PoolIterator;
 use unc_primitives::account::{AccessKey, Account};
+use unc_primitives::checked_feature;
+use unc_primitives::congestion_info::{BlockCongestionInfo, CongestionControl};
 use unc_primitives::errors::{InvalidTxError, RuntimeError, StorageError};
 use unc_primitives::hash::{hash, CryptoHash};
 use unc_primitives::receipt::{DelayedReceiptIndices, Receipt};
@@ -48,14 +49,15 @@ use unc_store::{
 };
 use unc_vm_runner::logic::CompiledContractCache;
 use unc_vm_runner::precompile_contract;
-use unc_vm_runner::ContractCode;
+use unc_vm_runner::{ContractCode, FunctionGasProfile};
 
 use node_runtime::{
-    validate_transaction, verify_and_charge_transaction, ApplyState, Runtime,
-    ValidatorAccountsUpdate,
+    validate_receiver_congestion, validate_transaction, verify_and_charge_transaction, ApplyState,
+    Runtime, ValidatorAccountsUpdate,
 };
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
@@ -77,7 +79,7 @@ pub struct NightshadeRuntime {
     pub runtime: Runtime,
     epoch_manager: Arc<EpochManagerHandle>,
     migration_data: Arc<MigrationData>,
-    gc_num_epochs_to_keep: u64,
+    gc_num_epochs_to_keep: AtomicU64,
 }
 
 impl NightshadeRuntime {
@@ -162,7 +164,9 @@ impl NightshadeRuntime {
             trie_viewer,
             epoch_manager,
             migration_data,
-            gc_num_epochs_to_keep: gc_num_epochs_to_keep.max(MIN_GC_NUM_EPOCHS_TO_KEEP),
+            gc_num_epochs_to_keep: AtomicU64::new(
+                gc_num_epochs_to_keep.max(MIN_GC_NUM_EPOCHS_TO_KEEP),
+            ),
         })
     }
 
@@ -290,6 +294,7 @@ impl NightshadeRuntime {
             gas_price,
             challenges_result,
             random_seed,
+            congestion_info,
         } = block;
         let ApplyChunkShardContext {
             shard_id,
@@ -301,9 +306,9 @@ impl NightshadeRuntime {
         } = chunk;
         let epoch_id = self.epoch_manager.get_epoch_id_from_prev_block(prev_block_hash)?;
 
+        let shard_layout = self.epoch_manager.get_shard_layout(&epoch_id)?;
         let validator_accounts_update = {
             let epoch_manager = self.epoch_manager.read();
-            let shard_layout = epoch_manager.get_shard_layout(&epoch_id)?;
             debug!(target: "runtime",
                    "is next_block_epoch_start {}",
                    epoch_manager.is_next_block_epoch_start(prev_block_hash).unwrap()
@@ -400,6 +405,17 @@ impl NightshadeRuntime {
 
         debug!(target: "runtime", ?epoch_height, ?epoch_id, ?current_protocol_version, ?is_first_block_of_version);
 
+        let congestion_control =
+            if checked_feature!("stable", CongestionControl, current_protocol_version) {
+                Some(CongestionControl {
+                    shard_id,
+                    shard_layout,
+                    block_congestion_info: congestion_info,
+                })
+            } else {
+                None
+            };
+
         let apply_state = ApplyState {
             block_height,
             prev_block_hash: *prev_block_hash,
@@ -419,6 +435,7 @@ impl NightshadeRuntime {
                 is_first_block_of_version,
                 is_first_block_with_chunk_of_version,
             },
+            congestion_control,
         };
 
         let instant = Instant::now();
@@ -493,6 +510,7 @@ impl NightshadeRuntime {
             total_balance_burnt,
             proof: apply_result.proof,
             processed_delayed_receipts: apply_result.processed_delayed_receipts,
+            congestion_info: apply_result.congestion_info,
         };
 
         Ok(result)
@@ -544,7 +562,7 @@ impl NightshadeRuntime {
         // maintain pointers to avoid cloning.
         let mut last_block_in_prev_epoch = *epoch_first_block_info.prev_hash();
         let mut epoch_start_height = epoch_first_block_info.height();
-        for _ in 0..self.gc_num_epochs_to_keep - 1 {
+        for _ in 0..self.gc_num_epochs_to_keep.load(Ordering::Relaxed) - 1 {
             let epoch_first_block =
                 *epoch_manager.get_block_info(&last_block_in_prev_epoch)?.epoch_first_block();
             let epoch_first_block_info = epoch_manager.get_block_info(&epoch_first_block)?;
@@ -730,6 +748,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         shard_id: ShardId,
         state_root: StateRoot,
         next_block_height: BlockHeight,
+        congestion_info: &BlockCongestionInfo,
         pool_iterator: &mut dyn PoolIterator,
         chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         current_protocol_version: ProtocolVersion,
@@ -752,6 +771,9 @@ impl RuntimeAdapter for NightshadeRuntime {
         let mut num_checked_transactions = 0;
 
         let runtime_config = self.runtime_config_store.get_config(current_protocol_version);
+        let shard_layout = checked_feature!("stable", CongestionControl, current_protocol_version)
+            .then(|| self.epoch_manager.get_shard_layout(epoch_id))
+            .transpose()?;
 
         // To avoid limiting the throughput of the network, we want to include enough receipts to
         // saturate the capacity of the chunk even in case when all of these receipts end up using
@@ -803,6 +825,25 @@ impl RuntimeAdapter for NightshadeRuntime {
                         continue;
                     }
 
+                    // Verifying that the receiver shard is not too congested.
+                    if let Some(shard_layout) = &shard_layout {
+                        let receiver_shard_id =
+                            account_id_to_shard_id(&tx.transaction.receiver_id, shard_layout);
+                        if receiver_shard_id != shard_id {
+                            if let Err(err) = validate_receiver_congestion(
+                                runtime_config,
+                                congestion_info,
+                                receiver_shard_id,
+                            ) {
+                                // Keep the transaction and the later nonces of the group in the
+                                // pool until the receiver shard accepts transactions again.
+                                tracing::trace!(target: "runtime", tx=?tx.get_hash(), ?err, "postponing transaction to a congested shard");
+                                iter.postpone(tx);
+                                break;
+                            }
+                        }
+                    }
+
                     // Verifying the validity of the transaction based on the current state.
                     match verify_and_charge_transaction(
                         runtime_config,
@@ -854,6 +895,11 @@ impl RuntimeAdapter for NightshadeRuntime {
         }
     }
 
+    fn set_gc_num_epochs_to_keep(&self, gc_num_epochs_to_keep: u64) {
+        self.gc_num_epochs_to_keep
+            .store(gc_num_epochs_to_keep.max(MIN_GC_NUM_EPOCHS_TO_KEEP), Ordering::Relaxed);
+    }
+
     fn apply_chunk(
         &self,
         storage_config: RuntimeStorageConfig,
@@ -885,6 +931,18 @@ impl RuntimeAdapter for NightshadeRuntime {
                 trie.dont_charge_gas_for_trie_node_access();
                 trie
             }
+            StorageDataSource::DbTrieWithOverlay(overlay) => {
+                let mut trie = self
+                    .get_trie_for_shard(
+                        shard_id,
+                        &block.prev_block_hash,
+                        storage_config.state_root,
+                        false,
+                    )?
+                    .with_overlay(overlay);
+                trie.dont_charge_gas_for_trie_node_access();
+                trie
+            }
             StorageDataSource::Recorded(storage) => Trie::from_recorded_storage(
                 storage,
                 storage_config.state_root,
@@ -925,10 +983,25 @@ impl RuntimeAdapter for NightshadeRuntime {
         epoch_id: &EpochId,
         request: &QueryRequest,
     ) -> Result<QueryResponse, unc_chain::unc_chain_primitives::error::QueryError> {
+        let query = match request {
+            QueryRequest::ViewAccount { .. } => "view_account",
+            QueryRequest::ViewCode { .. } => "view_code",
+            QueryRequest::ViewState { .. } => "view_state",
+            QueryRequest::ViewAccessKey { .. } => "view_access_key",
+            QueryRequest::ViewAccessKeyList { .. } => "view_access_key_list",
+            QueryRequest::ViewChipList { .. } => "view_chip_list",
+            QueryRequest::CallFunction { .. } => "call_function",
+            QueryRequest::DebugCallFunction { .. } => "debug_call_function",
+        };
+        // All the views below read the state through this update, so whether it is backed by the
+        // in-memory tries is already known here.
+        let state_update = self.tries.new_trie_update_view(shard_uid, *state_root);
+        let source = if state_update.trie().has_memtries() { "memtrie" } else { "disk" };
+        let _timer = metrics::VIEW_QUERY_DELAY.with_label_values(&[query, source]).start_timer();
         match request {
             QueryRequest::ViewAccount { account_id } => {
                 let account =
-                    self.view_account(&shard_uid, *state_root, account_id).map_err(|err| {
+                    self.trie_viewer.view_account(&state_update, account_id).map_err(|err| {
                         unc_chain::unc_chain_primitives::error::QueryError::from_view_account_error(
                             err,
                             block_height,
@@ -943,7 +1016,8 @@ impl RuntimeAdapter for NightshadeRuntime {
             }
             QueryRequest::ViewCode { account_id } => {
                 let contract_code = self
-                    .view_contract_code(&shard_uid,  *state_root, account_id)
+                    .trie_viewer
+                    .view_contract_code(&state_update, account_id)
                     .map_err(|err| unc_chain::unc_chain_primitives::error::QueryError::from_view_contract_code_error(err, block_height, *block_hash))?;
                 Ok(QueryResponse {
                     kind: QueryResponseKind::ViewCode(contract_code.into()),
@@ -951,8 +1025,9 @@ impl RuntimeAdapter for NightshadeRuntime {
                     block_hash: *block_hash,
                 })
             }
-            QueryRequest::CallFunction { account_id, method_name, args } => {
+            QueryRequest::CallFunction { account_id, method_name, args, profile } => {
                 let mut logs = vec![];
+                let mut function_profile = None;
                 let (epoch_height, current_protocol_version) = {
                     let epoch_manager = self.epoch_manager.read();
                     let epoch_info = epoch_manager.get_epoch_info(epoch_id).map_err(|err| {
@@ -965,42 +1040,93 @@ impl RuntimeAdapter for NightshadeRuntime {
                     (epoch_info.epoch_height(), epoch_info.protocol_version())
                 };
 
+                let view_state = ViewApplyState {
+                    block_height,
+                    prev_block_hash: *prev_block_hash,
+                    block_hash: *block_hash,
+                    epoch_id: epoch_id.clone(),
+                    epoch_height,
+                    block_timestamp,
+                    current_protocol_version,
+                    cache: Some(Box::new(StoreCompiledContractCache::new(&self.tries.get_store()))),
+                };
                 let call_function_result = self
+                    .trie_viewer
                     .call_function(
-                        &shard_uid,
-                        *state_root,
-                        block_height,
-                        block_timestamp,
-                        prev_block_hash,
-                        block_hash,
-                        epoch_height,
-                        epoch_id,
+                        state_update,
+                        view_state,
                         account_id,
                         method_name,
                         args.as_ref(),
                         &mut logs,
+                        profile.then_some(&mut function_profile),
                         self.epoch_manager.as_ref(),
-                        current_protocol_version,
                     )
                     .map_err(|err| unc_chain::unc_chain_primitives::error::QueryError::from_call_function_error(err, block_height, *block_hash))?;
                 Ok(QueryResponse {
                     kind: QueryResponseKind::CallResult(CallResult {
                         result: call_function_result,
                         logs,
+                        profile: function_profile.map(Into::into),
                     }),
                     block_height,
                     block_hash: *block_hash,
                 })
             }
-            QueryRequest::ViewState { account_id, prefix, include_proof } => {
-                let view_state_result = self
-                    .view_state(
-                        &shard_uid,
-                        *state_root,
+            #[cfg(feature = "sandbox")]
+            QueryRequest::DebugCallFunction { account_id, method_name, args, options } => {
+                let (epoch_height, current_protocol_version) = {
+                    let epoch_manager = self.epoch_manager.read();
+                    let epoch_info = epoch_manager.get_epoch_info(epoch_id).map_err(|err| {
+                        unc_chain::unc_chain_primitives::error::QueryError::from_epoch_error(
+                            err,
+                            block_height,
+                            *block_hash,
+                        )
+                    })?;
+                    (epoch_info.epoch_height(), epoch_info.protocol_version())
+                };
+                // The debugger instruments the contract itself, so there is no use for the cache.
+                let view_state = ViewApplyState {
+                    block_height,
+                    prev_block_hash: *prev_block_hash,
+                    block_hash: *block_hash,
+                    epoch_id: epoch_id.clone(),
+                    epoch_height,
+                    block_timestamp,
+                    current_protocol_version,
+                    cache: None,
+                };
+                let debug_call_result = self
+                    .trie_viewer
+                    .debug_function_call(
+                        state_update,
+                        view_state,
                         account_id,
-                        prefix.as_ref(),
-                        *include_proof,
+                        method_name,
+                        args.as_ref(),
+                        options.clone(),
+                        self.epoch_manager.as_ref(),
                     )
+                    .map_err(|err| unc_chain::unc_chain_primitives::error::QueryError::from_call_function_error(err, block_height, *block_hash))?;
+                Ok(QueryResponse {
+                    kind: QueryResponseKind::DebugCallResult(debug_call_result),
+                    block_height,
+                    block_hash: *block_hash,
+                })
+            }
+            #[cfg(not(feature = "sandbox"))]
+            QueryRequest::DebugCallFunction { .. } => {
+                Err(unc_chain::unc_chain_primitives::error::QueryError::InternalError {
+                    error_message: "debug calls are only supported by sandbox nodes".to_string(),
+                    block_height,
+                    block_hash: *block_hash,
+                })
+            }
+            QueryRequest::ViewState { account_id, prefix, include_proof } => {
+                let view_state_result = self
+                    .trie_viewer
+                    .view_state(&state_update, account_id, prefix.as_ref(), *include_proof)
                     .map_err(|err| {
                         unc_chain::unc_chain_primitives::error::QueryError::from_view_state_error(
                             err,
@@ -1016,7 +1142,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             }
             QueryRequest::ViewAccessKeyList { account_id } => {
                 let access_key_list =
-                    self.view_access_keys(&shard_uid, *state_root, account_id).map_err(|err| {
+                    self.trie_viewer.view_access_keys(&state_update, account_id).map_err(|err| {
                         unc_chain::unc_chain_primitives::error::QueryError::from_view_access_key_error(
                             err,
                             block_height,
@@ -1039,7 +1165,7 @@ impl RuntimeAdapter for NightshadeRuntime {
             }
             QueryRequest::ViewChipList { account_id } => {
                 let chip_list_result =
-                    self.view_chip_list(&shard_uid, *state_root, account_id).map_err(|err| {
+                    self.trie_viewer.view_chip_list(&state_update, account_id).map_err(|err| {
                         unc_chain::unc_chain_primitives::error::QueryError::from_view_chip_error(
                             err,
                             block_height,
@@ -1060,7 +1186,8 @@ impl RuntimeAdapter for NightshadeRuntime {
             }
             QueryRequest::ViewAccessKey { account_id, public_key } => {
                 let access_key = self
-                    .view_access_key(&shard_uid, *state_root, account_id, public_key)
+                    .trie_viewer
+                    .view_access_key(&state_update, account_id, public_key)
                     .map_err(|err| {
                         unc_chain::unc_chain_primitives::error::QueryError::from_view_access_key_error(
                             err,
@@ -1308,6 +1435,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        function_profile: Option<&mut Option<FunctionGasProfile>>,
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
     ) -> Result<Vec<u8>, node_runtime::state_viewer::errors::CallFunctionError> {
@@ -1329,6 +1457,7 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
             method_name,
             args,
             logs,
+            function_profile,
             epoch_info_provider,
         )
     }
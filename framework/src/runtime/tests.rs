@@ -1,5 +1,6 @@
 use std::collections::BTreeSet;
 
+use node_runtime::adapter::ViewRuntimeAdapter;
 use num_rational::Ratio;
 use unc_chain::types::RuntimeStorageConfig;
 use unc_chain::{Chain, ChainGenesis};
@@ -84,6 +85,7 @@ impl NightshadeRuntime {
                     gas_price,
                     challenges_result: challenges_result.clone(),
                     random_seed: CryptoHash::default(),
+                    congestion_info: Default::default(),
                 },
                 receipts,
                 transactions,
@@ -7,7 +7,7 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use unc_chain::types::RuntimeAdapter;
 use unc_chain::{Chain, ChainGenesis, ChainStoreAccess, DoomslugThresholdMode, Error};
-use unc_chain_configs::{ClientConfig, ExternalStorageLocation};
+use unc_chain_configs::{ClientConfig, ExternalStorageLocation, StatePartFormat};
 use unc_client::sync::external::{create_bucket_readwrite, external_storage_location};
 use unc_client::sync::external::{
     external_storage_location_directory, get_part_id_from_filename, is_part_filename,
@@ -17,7 +17,7 @@ use unc_client::sync::state::{StateSync, STATE_DUMP_ITERATION_TIME_LIMIT_SECS};
 use unc_epoch_manager::shard_tracker::ShardTracker;
 use unc_epoch_manager::EpochManagerAdapter;
 use unc_primitives::hash::CryptoHash;
-use unc_primitives::state_part::PartId;
+use unc_primitives::state_part::{PartId, StatePartContainer, StatePartContainerError};
 use unc_primitives::state_sync::{StatePartKey, StateSyncDumpProgress};
 use unc_primitives::types::{AccountId, EpochHeight, EpochId, ShardId, StateRoot};
 use unc_store::DBCol;
@@ -62,6 +62,15 @@ pub fn spawn_state_sync_dump(
                 bucket
             }
         },
+        ExternalStorageLocation::Http { base_url, headers, allow_missing_checksums } => {
+            ExternalConnection::new_http(
+                &base_url,
+                &headers,
+                allow_missing_checksums,
+                Duration::from_secs(30),
+            )
+            .expect("Failed to create an HTTP client for the state dump.")
+        }
     };
 
     // Determine how many threads to start.
@@ -108,6 +117,7 @@ pub fn spawn_state_sync_dump(
                 dump_config.restart_dump_for_shards.clone().unwrap_or_default(),
                 external.clone(),
                 dump_config.iteration_delay.unwrap_or(Duration::from_secs(10)),
+                dump_config.part_format,
                 account_id.clone(),
                 keep_running.clone(),
             )));
@@ -229,6 +239,7 @@ async fn state_sync_dump(
     restart_dump_for_shards: Vec<ShardId>,
     external: ExternalConnection,
     iteration_delay: Duration,
+    part_format: StatePartFormat,
     account_id: Option<AccountId>,
     keep_running: Arc<AtomicBool>,
 ) {
@@ -327,6 +338,22 @@ async fn state_sync_dump(
                                         }
                                     };
 
+                                    let state_part = match encode_state_part(
+                                        part_format,
+                                        shard_id,
+                                        &epoch_id,
+                                        PartId::new(part_id, num_parts),
+                                        state_root,
+                                        state_part,
+                                    ) {
+                                        Ok(state_part) => state_part,
+                                        Err(err) => {
+                                            tracing::warn!(target: "state_sync_dump", shard_id, epoch_height, part_id, ?err, "Failed to encode part. Will skip this part.");
+                                            failures_cnt += 1;
+                                            continue;
+                                        }
+                                    };
+
                                     let location = external_storage_location(
                                         &chain_id,
                                         &epoch_id,
@@ -449,6 +476,30 @@ fn update_dumped_size_and_cnt_metrics(
         .set(num_parts as i64);
 }
 
+/// Converts a raw state part into the configured format before it is uploaded.
+fn encode_state_part(
+    part_format: StatePartFormat,
+    shard_id: ShardId,
+    epoch_id: &EpochId,
+    part_id: PartId,
+    state_root: StateRoot,
+    state_part: Vec<u8>,
+) -> Result<Vec<u8>, StatePartContainerError> {
+    match part_format {
+        StatePartFormat::Raw => Ok(state_part),
+        StatePartFormat::Container => {
+            let container = StatePartContainer::new(
+                shard_id,
+                epoch_id.clone(),
+                part_id,
+                state_root,
+                &state_part,
+            )?;
+            Ok(container.to_bytes())
+        }
+    }
+}
+
 /// Obtains and then saves the part data.
 fn obtain_and_store_state_part(
     runtime: &dyn RuntimeAdapter,
@@ -2,12 +2,15 @@
 use chrono::DateTime;
 use chrono::Utc;
 use csv::ReaderBuilder;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
+use unc_chain_configs::genesis_validate::RSA2048_ROOT_ACCOUNT;
 use unc_crypto::{KeyType, PublicKey};
 use unc_network::types::PeerInfo;
 use unc_primitives::account::{AccessKey, AccessKeyPermission, Account, FunctionCallPermission};
+use unc_primitives::action::RegisterRsa2048KeysAction;
 use unc_primitives::hash::{hash, CryptoHash};
 use unc_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
 use unc_primitives::state_record::StateRecord;
@@ -91,6 +94,44 @@ impl Row {
     }
 }
 
+/// RSA2048 chip registered at genesis.
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct ChipRow {
+    public_key: PublicKey,
+    miner_id: String,
+    sn: String,
+    bus_id: String,
+    power: Power,
+    owner: AccountId,
+}
+
+impl ChipRow {
+    /// Returns the registry record of the chip, with `args` encoded the same way as in
+    /// the `RegisterRsa2048Keys` action.
+    fn to_state_record(&self) -> StateRecord {
+        let args = serde_json::json!({
+            "public_key": self.public_key.to_string(),
+            "miner_id": self.miner_id,
+            "sn": self.sn,
+            "bus_id": self.bus_id,
+            "power": self.power.to_string(),
+        });
+        StateRecord::Rsa2048Keys {
+            account_id: self.owner.clone(),
+            public_key: self.public_key.clone(),
+            rsa_key: RegisterRsa2048KeysAction {
+                public_key: self.public_key.clone(),
+                operation_type: 0,
+                args: serde_json::to_vec(&args).unwrap(),
+            },
+        }
+    }
+}
+
+fn invalid_input(message: String) -> Box<dyn std::error::Error> {
+    Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, message))
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug)]
 struct Row {
     genesis_time: Option<DateTime<Utc>>,
@@ -117,19 +158,56 @@ struct Row {
     vesting_cliff: Option<DateTime<Utc>>,
 }
 
+/// Given the csv file of the chips produces their registry records together with the total
+/// power of the chips owned by each account.
+fn chips_to_state_records<R>(reader: R) -> Result<(Vec<StateRecord>, HashMap<AccountId, Power>)>
+where
+    R: std::io::Read,
+{
+    let mut reader = ReaderBuilder::new().has_headers(true).from_reader(reader);
+
+    let mut state_records = vec![];
+    let mut public_keys = HashSet::new();
+    let mut powers = HashMap::<AccountId, Power>::new();
+    for row in reader.deserialize() {
+        let row: ChipRow = row?;
+        if !public_keys.insert(row.public_key.clone()) {
+            return Err(invalid_input(format!("Duplicate chip key {}.", row.public_key)));
+        }
+        let power = powers.entry(row.owner.clone()).or_default();
+        *power = power
+            .checked_add(row.power)
+            .ok_or_else(|| invalid_input(format!("Power of account {} overflows.", row.owner)))?;
+        state_records.push(row.to_state_record());
+    }
+    Ok((state_records, powers))
+}
+
 /// Given path to the csv file produces:
 /// * `StateRecord`s that represent the state of the token holders;
 /// * `AccountInfo`s that represent validators;
 /// * `PeerInfo`s that represent boot nodes;
 /// * `AccountId` of the treasury.
 /// *  Genesis time
-pub fn keys_to_state_records<R>(
+///
+/// If the csv file of the chips is given, their registry records are produced as well. The
+/// power of an account owning chips is the total power of its chips. It can be left as zero in
+/// the accounts file, otherwise it must match. Chips owned by the root account are not claimed
+/// yet and don't give it any power.
+pub fn keys_to_state_records<R, C>(
     reader: R,
+    chips_reader: Option<C>,
     gas_price: Balance,
 ) -> Result<(Vec<StateRecord>, Vec<AccountInfo>, Vec<PeerInfo>, AccountId, DateTime<Utc>)>
 where
     R: std::io::Read,
+    C: std::io::Read,
 {
+    let (chip_records, mut chip_powers) = match chips_reader {
+        Some(chips_reader) => chips_to_state_records(chips_reader)?,
+        None => (vec![], HashMap::new()),
+    };
+
     let mut reader = ReaderBuilder::new().has_headers(true).from_reader(reader);
 
     let mut state_records = vec![];
@@ -138,8 +216,20 @@ where
     let mut treasury = None;
     let mut genesis_time = None;
     for row in reader.deserialize() {
-        let row: Row = row?;
+        let mut row: Row = row?;
         row.verify()?;
+        if let Some(chip_power) = chip_powers.remove(&row.account_id) {
+            if row.account_id.as_str() != RSA2048_ROOT_ACCOUNT {
+                if row.validator_power == 0 {
+                    row.validator_power = chip_power;
+                } else if row.validator_power != chip_power {
+                    return Err(invalid_input(format!(
+                        "Account {} has power {} which doesn't match the power {} of its chips.",
+                        row.account_id, row.validator_power, chip_power
+                    )));
+                }
+            }
+        }
         if row.is_treasury {
             if treasury.is_none() {
                 treasury = Some(row.account_id.clone());
@@ -165,6 +255,10 @@ where
             boot_nodes.push(peer_info);
         }
     }
+    if let Some(owner) = chip_powers.keys().next() {
+        return Err(invalid_input(format!("Chip owner {} is not in the accounts file.", owner)));
+    }
+    state_records.extend(chip_records);
     let treasury = treasury.expect("At least one account should be marked as treasury");
     let genesis_time = genesis_time.expect("Genesis time must be set");
     Ok((state_records, initial_validators, boot_nodes, treasury, genesis_time))
@@ -317,6 +411,7 @@ mod tests {
                 vesting_end: timestamp(23, 30, 0),
                 vesting_cliff: timestamp(22, 30, 20),
                 validator_stake: 100,
+                validator_power: 10,
                 validator_key: Some(PublicKey::empty(KeyType::ED25519)),
                 peer_info: Some(PeerInfo {
                     id: PeerId::new(PublicKey::empty(KeyType::ED25519)),
@@ -341,6 +436,7 @@ mod tests {
                 vesting_end: None,
                 vesting_cliff: None,
                 validator_stake: 0,
+                validator_power: 0,
                 validator_key: None,
                 peer_info: None,
                 is_treasury: true,
@@ -348,13 +444,140 @@ mod tests {
             })
             .unwrap();
         writer.flush().unwrap();
-        keys_to_state_records(file.reopen().unwrap(), 1).unwrap();
+        keys_to_state_records(file.reopen().unwrap(), None::<File>, 1).unwrap();
     }
 
     #[test]
     fn test_res_file() {
         let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("res/test_accounts.csv");
         let res = std::fs::read(path).unwrap();
-        keys_to_state_records(&res[..], 1).unwrap();
+        keys_to_state_records(&res[..], None::<&[u8]>, 1).unwrap();
+    }
+
+    fn account_row(account_id: &str, validator_power: Power, is_treasury: bool) -> Row {
+        Row {
+            genesis_time: Some(Utc::now()),
+            account_id: account_id.parse().unwrap(),
+            regular_pks: vec![],
+            privileged_pks: vec![],
+            foundation_pks: vec![],
+            full_pks: vec![PublicKey::from_seed(KeyType::ED25519, account_id)],
+            amount: 1000,
+            is_treasury,
+            validator_stake: 0,
+            validator_power,
+            validator_key: None,
+            peer_info: None,
+            smart_contract: None,
+            lockup: None,
+            vesting_start: None,
+            vesting_end: None,
+            vesting_cliff: None,
+        }
+    }
+
+    fn chip_row(seed: &str, power: Power, owner: &str) -> ChipRow {
+        ChipRow {
+            public_key: PublicKey::from_seed(KeyType::ED25519, seed),
+            miner_id: format!("miner-{}", seed),
+            sn: format!("sn-{}", seed),
+            bus_id: format!("bus-{}", seed),
+            power,
+            owner: owner.parse().unwrap(),
+        }
+    }
+
+    fn write_csv<T: serde::Serialize>(rows: Vec<T>) -> NamedTempFile {
+        let file = NamedTempFile::new().unwrap();
+        let mut writer = WriterBuilder::new().has_headers(true).from_writer(file.reopen().unwrap());
+        for row in rows {
+            writer.serialize(row).unwrap();
+        }
+        writer.flush().unwrap();
+        file
+    }
+
+    fn records_with_chips(accounts: Vec<Row>, chips: Vec<ChipRow>) -> Result<Vec<StateRecord>> {
+        let accounts = write_csv(accounts);
+        let chips = write_csv(chips);
+        keys_to_state_records(accounts.reopen().unwrap(), Some(chips.reopen().unwrap()), 1)
+            .map(|(records, ..)| records)
+    }
+
+    #[test]
+    fn test_with_chips() {
+        let records = records_with_chips(
+            vec![
+                account_row("unc", 0, true),
+                account_row("alice_unc", 0, false),
+                account_row("bob_unc", 5, false),
+            ],
+            vec![
+                chip_row("chip0", 3, "alice_unc"),
+                chip_row("chip1", 4, "alice_unc"),
+                chip_row("chip2", 5, "bob_unc"),
+                chip_row("chip3", 6, "unc"),
+            ],
+        )
+        .unwrap();
+
+        let mut powers = HashMap::new();
+        let mut chips = vec![];
+        for record in records {
+            match record {
+                StateRecord::Account { account_id, account } => {
+                    powers.insert(account_id.to_string(), account.power());
+                }
+                StateRecord::Rsa2048Keys { account_id, rsa_key, .. } => {
+                    chips.push((account_id.to_string(), rsa_key.power().unwrap()));
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(powers["unc"], 0);
+        assert_eq!(powers["alice_unc"], 7);
+        assert_eq!(powers["bob_unc"], 5);
+        assert_eq!(
+            chips,
+            vec![
+                ("alice_unc".to_string(), 3),
+                ("alice_unc".to_string(), 4),
+                ("bob_unc".to_string(), 5),
+                ("unc".to_string(), 6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chips_duplicate_key() {
+        let err = records_with_chips(
+            vec![account_row("alice_unc", 0, true)],
+            vec![chip_row("chip0", 3, "alice_unc"), chip_row("chip0", 4, "alice_unc")],
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("Duplicate chip key"), "{}", err);
+    }
+
+    #[test]
+    fn test_chips_power_mismatch() {
+        let err = records_with_chips(
+            vec![account_row("alice_unc", 5, true)],
+            vec![chip_row("chip0", 3, "alice_unc")],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Account alice_unc has power 5 which doesn't match the power 3 of its chips."
+        );
+    }
+
+    #[test]
+    fn test_chips_unknown_owner() {
+        let err = records_with_chips(
+            vec![account_row("alice_unc", 0, true)],
+            vec![chip_row("chip0", 3, "bob_unc")],
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Chip owner bob_unc is not in the accounts file.");
     }
 }
@@ -15,6 +15,7 @@ use unc_primitives::utils::get_num_seats_per_shard;
 use unc_primitives::version::PROTOCOL_VERSION;
 
 const ACCOUNTS_FILE: &str = "accounts.csv";
+const CHIPS_FILE: &str = "chips.csv";
 const SHARDS: &'static [ShardId] = &[0, 1, 2, 3, 4, 5, 6, 7];
 
 fn verify_total_supply(total_supply: Balance, chain_id: &str) {
@@ -32,6 +33,7 @@ fn verify_total_supply(total_supply: Balance, chain_id: &str) {
 }
 
 /// Generates `config.json` and `genesis.config` from csv files.
+/// Chips are registered at genesis if `chips.csv` is present.
 /// Verifies that `validator_key.json`, and `node_key.json` are present.
 pub fn csv_to_json_configs(home: &Path, chain_id: String, tracked_shards: Vec<ShardId>) {
     // Verify that key files exist.
@@ -47,9 +49,13 @@ pub fn csv_to_json_configs(home: &Path, chain_id: String, tracked_shards: Vec<Sh
     config.tracked_shards = tracked_shards;
 
     // Construct genesis config.
+    let chips_path = home.join(CHIPS_FILE);
+    let chips_file =
+        chips_path.exists().then(|| File::open(chips_path).expect("Error opening chips file."));
     let (records, validators, peer_info, treasury, genesis_time) =
         crate::csv_parser::keys_to_state_records(
             File::open(home.join(ACCOUNTS_FILE)).expect("Error opening accounts file."),
+            chips_file,
             MIN_GAS_PRICE,
         )
         .expect("Error parsing accounts file.");
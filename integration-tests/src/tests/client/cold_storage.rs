@@ -1,22 +1,32 @@
+use actix::System;
+use assert_matches::assert_matches;
 use borsh::BorshDeserialize;
 use framework::config::GenesisExt;
 use framework::test_utils::TestEnvNightshadeSetupExt;
 use framework::{cold_storage::spawn_cold_store_loop, UncConfig};
 use std::collections::HashSet;
+use std::rc::Rc;
 use std::str::FromStr;
 use strum::IntoEnumIterator;
+use unc_actix_test_utils::run_actix;
 use unc_chain::{ChainGenesis, Provenance};
-use unc_chain_configs::Genesis;
+use unc_chain_configs::{Genesis, RetainedAccounts, DEFAULT_GC_NUM_EPOCHS_TO_KEEP};
+use unc_client::adversarial::Controls;
 use unc_client::test_utils::TestEnv;
-use unc_client::ProcessTxResponse;
+use unc_client::{start_view_client, GetStateChanges, ProcessTxResponse, Query, QueryError};
+use unc_client_primitives::types::GetStateChangesError;
 use unc_crypto::{InMemorySigner, KeyType};
 use unc_epoch_manager::EpochManager;
 use unc_o11y::testonly::init_test_logger;
+use unc_o11y::WithSpanContextExt;
 use unc_primitives::block::Tip;
+use unc_primitives::shard_layout::ShardUId;
 use unc_primitives::sharding::{PartialEncodedChunk, ShardChunk};
 use unc_primitives::transaction::{
     Action, DeployContractAction, FunctionCallAction, SignedTransaction,
 };
+use unc_primitives::types::{BlockId, BlockReference};
+use unc_primitives::views::{QueryRequest, StateChangesRequestView};
 use unc_primitives_core::types::AccountId;
 use unc_store::cold_storage::{
     copy_all_data_to_cold, test_cold_genesis_update, test_get_store_initial_writes,
@@ -25,7 +35,7 @@ use unc_store::cold_storage::{
 use unc_store::metadata::DbKind;
 use unc_store::metadata::DB_VERSION;
 use unc_store::test_utils::create_test_node_storage_with_cold;
-use unc_store::{DBCol, Store, COLD_HEAD_KEY, HEAD_KEY};
+use unc_store::{get_account, DBCol, Store, Trie, TrieDBStorage, COLD_HEAD_KEY, HEAD_KEY};
 
 fn check_key(first_store: &Store, second_store: &Store, col: DBCol, key: &[u8]) {
     let pretty_key = unc_fmt::StorageKey(key);
@@ -157,6 +167,7 @@ fn test_storage_after_commit_of_cold_update() {
                 )
                 .unwrap(),
             &h,
+            None,
         )
         .unwrap();
 
@@ -329,6 +340,7 @@ fn test_cold_db_copy_with_height_skips() {
                 )
                 .unwrap(),
             &h,
+            None,
         )
         .unwrap();
 
@@ -383,7 +395,9 @@ fn test_cold_db_copy_with_height_skips() {
 /// - If batch_size = 0, check that every value was copied in a separate batch.
 /// - If batch_size = usize::MAX, check that everything was copied in one batch.
 /// Most importantly, checking that everything from cold columns was indeed copied into cold storage.
-fn test_initial_copy_to_cold(batch_size: usize) {
+/// With `retained_accounts` the filtered columns are checked to be a strict subset of the hot ones
+/// that still serves the state of the retained accounts.
+fn test_initial_copy_to_cold(batch_size: usize, retained_accounts: Option<&RetainedAccounts>) {
     init_test_logger();
 
     let epoch_length = 5;
@@ -429,19 +443,25 @@ fn test_initial_copy_to_cold(batch_size: usize) {
         &env.clients[0].runtime_adapter.store(),
         batch_size,
         &keep_going,
+        retained_accounts,
     )
     .unwrap();
 
+    let hot_store = env.clients[0].runtime_adapter.store();
+    let cold_store = store.get_cold_store().unwrap();
     for col in DBCol::iter() {
         if !col.is_cold() {
             continue;
         }
-        let num_checks = check_iter(
-            &env.clients[0].runtime_adapter.store(),
-            &store.get_cold_store().unwrap(),
-            col,
-            &vec![],
-        );
+        let filtered = retained_accounts.is_some()
+            && matches!(col, DBCol::State | DBCol::StateChanges | DBCol::TransactionResultForBlock);
+        let num_checks = if filtered {
+            let num_checks = check_iter(&cold_store, &hot_store, col, &vec![]);
+            assert!(num_checks < hot_store.iter(col).count() as u64, "{col} is not filtered");
+            num_checks
+        } else {
+            check_iter(&hot_store, &cold_store, col, &vec![])
+        };
         // StateChangesForSplitStates and StateHeaders are empty
         if col == DBCol::StateChangesForSplitStates || col == DBCol::StateHeaders {
             continue;
@@ -454,21 +474,206 @@ fn test_initial_copy_to_cold(batch_size: usize) {
             assert_eq!(1, test_get_store_initial_writes(col));
         }
     }
+
+    if retained_accounts.is_some() {
+        let shard_uid = ShardUId::single_shard();
+        let state_root =
+            *env.clients[0].chain.get_chunk_extra(&last_hash, &shard_uid).unwrap().state_root();
+        let trie = Trie::new(Rc::new(TrieDBStorage::new(cold_store, shard_uid)), state_root, None);
+        assert!(get_account(&trie, &"test1".parse().unwrap()).unwrap().is_some());
+        assert!(get_account(&trie, &"test0".parse().unwrap()).is_err());
+    }
 }
 
 #[test]
 fn test_initial_copy_to_cold_small_batch() {
-    test_initial_copy_to_cold(0);
+    test_initial_copy_to_cold(0, None);
 }
 
 #[test]
 fn test_initial_copy_to_cold_huge_batch() {
-    test_initial_copy_to_cold(usize::MAX);
+    test_initial_copy_to_cold(usize::MAX, None);
 }
 
 #[test]
 fn test_initial_copy_to_cold_medium_batch() {
-    test_initial_copy_to_cold(5000);
+    test_initial_copy_to_cold(5000, None);
+}
+
+#[test]
+fn test_initial_copy_to_cold_retained_accounts() {
+    let retained_accounts = RetainedAccounts::try_from(vec!["test1".to_string()]).unwrap();
+    test_initial_copy_to_cold(0, Some(&retained_accounts));
+}
+
+/// Sending money from test0 to test1 every block for 4 epochs and updating cold storage block by
+/// block with only test1 retained.
+/// Checks that the filtered columns are a strict subset of the hot ones and that the latest state
+/// of test1, but not of test0, can be read from cold storage.
+#[test]
+fn test_cold_db_update_retained_accounts() {
+    init_test_logger();
+
+    let epoch_length = 5;
+    let max_height = epoch_length * 4;
+
+    let mut genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+
+    genesis.config.epoch_length = epoch_length;
+    let mut chain_genesis = ChainGenesis::test();
+    chain_genesis.epoch_length = epoch_length;
+    let mut env = TestEnv::builder(chain_genesis)
+        .real_epoch_managers(&genesis.config)
+        .nightshade_runtimes(&genesis)
+        .build();
+
+    let (storage, ..) = create_test_node_storage_with_cold(DB_VERSION, DbKind::Hot);
+    let retained_accounts = RetainedAccounts::try_from(vec!["test1".to_string()]).unwrap();
+
+    let mut last_hash = *env.clients[0].chain.genesis().hash();
+
+    test_cold_genesis_update(&*storage.cold_db().unwrap(), &env.clients[0].runtime_adapter.store())
+        .unwrap();
+
+    for h in 1..max_height {
+        let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+        for i in 0..5 {
+            let tx = SignedTransaction::send_money(
+                h * 10 + i,
+                "test0".parse().unwrap(),
+                "test1".parse().unwrap(),
+                &signer,
+                1,
+                last_hash,
+            );
+            assert_eq!(env.clients[0].process_tx(tx, false, false), ProcessTxResponse::ValidTx);
+        }
+
+        let block = env.clients[0].produce_block(h).unwrap().unwrap();
+        env.process_block(0, block.clone(), Provenance::PRODUCED);
+
+        update_cold_db(
+            &*storage.cold_db().unwrap(),
+            &env.clients[0].runtime_adapter.store(),
+            &env.clients[0]
+                .epoch_manager
+                .get_shard_layout(
+                    &env.clients[0].epoch_manager.get_epoch_id_from_prev_block(&last_hash).unwrap(),
+                )
+                .unwrap(),
+            &h,
+            Some(&retained_accounts),
+        )
+        .unwrap();
+
+        last_hash = *block.hash();
+    }
+
+    let hot_store = env.clients[0].runtime_adapter.store();
+    let cold_store = storage.get_cold_store().unwrap();
+    for col in [DBCol::State, DBCol::StateChanges, DBCol::TransactionResultForBlock] {
+        let num_checks = check_iter(&cold_store, &hot_store, col, &vec![]);
+        assert!(num_checks > 0, "nothing of {col} is retained");
+        assert!(num_checks < hot_store.iter(col).count() as u64, "{col} is not filtered");
+    }
+
+    let shard_uid = ShardUId::single_shard();
+    let state_root =
+        *env.clients[0].chain.get_chunk_extra(&last_hash, &shard_uid).unwrap().state_root();
+    let trie = Trie::new(Rc::new(TrieDBStorage::new(cold_store, shard_uid)), state_root, None);
+    assert!(get_account(&trie, &"test1".parse().unwrap()).unwrap().is_some());
+    assert!(get_account(&trie, &"test0".parse().unwrap()).is_err());
+}
+
+/// Producing enough blocks for the hot tail to move past the first epoch.
+/// Checks that a view client of a partial archival node refuses to look up history of accounts
+/// which aren't retained below the tail, and still looks up history of the retained ones.
+#[test]
+fn test_view_client_account_not_retained() {
+    init_test_logger();
+
+    let epoch_length = 5;
+
+    let mut genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+
+    genesis.config.epoch_length = epoch_length;
+    let mut chain_genesis = ChainGenesis::test();
+    chain_genesis.epoch_length = epoch_length;
+    let mut env = TestEnv::builder(chain_genesis)
+        .real_epoch_managers(&genesis.config)
+        .nightshade_runtimes(&genesis)
+        .build();
+
+    let mut blocks = vec![];
+    for h in 1..=epoch_length * (DEFAULT_GC_NUM_EPOCHS_TO_KEEP + 1) {
+        let block = env.clients[0].produce_block(h).unwrap().unwrap();
+        env.process_block(0, block.clone(), Provenance::PRODUCED);
+        blocks.push(block);
+    }
+    let old_block_hash = *blocks[0].hash();
+    let old_block_height = blocks[0].header().height();
+    assert!(old_block_height < env.clients[0].chain.tail().unwrap());
+
+    let client = &env.clients[0];
+    let mut config = client.config.clone();
+    config.archive_retained_accounts =
+        Some(RetainedAccounts::try_from(vec!["test1".to_string()]).unwrap());
+    let chain_genesis = env.chain_genesis.clone();
+    let epoch_manager = client.epoch_manager.clone();
+    let shard_tracker = client.shard_tracker.clone();
+    let runtime = client.runtime_adapter.clone();
+    let network_adapter = env.network_adapters[0].clone();
+
+    run_actix(async move {
+        let view_client = start_view_client(
+            None,
+            chain_genesis,
+            epoch_manager,
+            shard_tracker,
+            runtime,
+            network_adapter.into(),
+            config,
+            Controls::default(),
+        );
+        let view_account = |account_id: &str| {
+            Query::new(
+                BlockReference::BlockId(BlockId::Hash(old_block_hash)),
+                QueryRequest::ViewAccount { account_id: account_id.parse().unwrap() },
+            )
+            .with_span_context()
+        };
+
+        let res = view_client.send(view_account("test0")).await.unwrap();
+        assert_matches!(
+            res,
+            Err(QueryError::AccountNotRetained { requested_account_id, block_height, .. })
+                if requested_account_id == "test0" && block_height == old_block_height
+        );
+        // The retained account gets past the check, and only fails because this
+        // node doesn't have the cold storage.
+        let res = view_client.send(view_account("test1")).await.unwrap();
+        assert_matches!(res, Err(QueryError::GarbageCollectedBlock { .. }));
+
+        let res = view_client
+            .send(
+                GetStateChanges {
+                    block_hash: old_block_hash,
+                    state_changes_request: StateChangesRequestView::AccountChanges {
+                        account_ids: vec!["test0".parse().unwrap()],
+                    },
+                }
+                .with_span_context(),
+            )
+            .await
+            .unwrap();
+        assert_matches!(
+            res,
+            Err(GetStateChangesError::AccountNotRetained { requested_account_id, .. })
+                if requested_account_id == "test0"
+        );
+
+        System::current().stop();
+    });
 }
 
 /// This test checks that garbage collection does not remove data needed for cold storage migration prematurely.
@@ -527,8 +732,14 @@ fn test_cold_loop_on_gc_boundary() {
 
     let keep_going = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
 
-    copy_all_data_to_cold((*store.cold_db().unwrap()).clone(), &hot_store, 1000000, &keep_going)
-        .unwrap();
+    copy_all_data_to_cold(
+        (*store.cold_db().unwrap()).clone(),
+        &hot_store,
+        1000000,
+        &keep_going,
+        None,
+    )
+    .unwrap();
 
     update_cold_head(&*store.cold_db().unwrap(), &hot_store, &(height_delta - 1)).unwrap();
 
@@ -983,6 +983,7 @@ fn client_sync_headers() {
                     connection_established_time: unc_async::time::Instant::now(),
                     peer_type: PeerType::Outbound,
                     nonce: 1,
+                    reputation: 0,
                 }],
                 num_connected_peers: 1,
                 peer_max_count: 1,
@@ -993,6 +994,7 @@ fn client_sync_headers() {
                     highest_block_hash: hash(&[5]),
                     tracked_shards: vec![],
                     archival: false,
+                    reputation: 0,
                 }],
                 sent_bytes_per_sec: 0,
                 received_bytes_per_sec: 0,
@@ -1197,6 +1199,7 @@ fn test_bad_orphan() {
             match &mut chunk.inner {
                 ShardChunkHeaderInner::V1(inner) => inner.prev_outcome_root = CryptoHash([1; 32]),
                 ShardChunkHeaderInner::V2(inner) => inner.prev_outcome_root = CryptoHash([1; 32]),
+                ShardChunkHeaderInner::V3(inner) => inner.prev_outcome_root = CryptoHash([1; 32]),
             }
             chunk.hash = ShardChunkHeaderV3::compute_hash(&chunk.inner);
         }
@@ -1506,7 +1509,7 @@ fn test_archival_gc_common(
         blocks.push(block);
 
         if i <= max_cold_head_height {
-            update_cold_db(storage.cold_db().unwrap(), hot_store, &shard_layout, &i).unwrap();
+            update_cold_db(storage.cold_db().unwrap(), hot_store, &shard_layout, &i, None).unwrap();
             update_cold_head(storage.cold_db().unwrap(), &hot_store, &i).unwrap();
         }
     }
@@ -2612,7 +2615,9 @@ fn test_execution_metadata() {
         unc_vm_runner::logic::ContractPrepareVersion::V1 => 2,
         // We spend two wasm instructions (call & drop), plus 8 ops for initializing function
         // operand stack (8 bytes worth to hold the return value.)
-        unc_vm_runner::logic::ContractPrepareVersion::V2 => 10,
+        unc_vm_runner::logic::ContractPrepareVersion::V2
+        | unc_vm_runner::logic::ContractPrepareVersion::V3
+        | unc_vm_runner::logic::ContractPrepareVersion::V4 => 10,
     };
 
     // Profile for what's happening *inside* wasm vm during function call.
@@ -3456,6 +3461,7 @@ mod contract_precompilation_tests {
                 "log_something",
                 &[],
                 &mut logs,
+                None,
                 &MockEpochInfoProvider::default(),
             )
             .unwrap();
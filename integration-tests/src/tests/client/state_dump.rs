@@ -10,7 +10,7 @@ use std::time::Duration;
 use unc_chain::unc_chain_primitives::error::QueryError;
 use unc_chain::{ChainGenesis, ChainStoreAccess, Provenance};
 use unc_chain_configs::ExternalStorageLocation::Filesystem;
-use unc_chain_configs::{DumpConfig, Genesis};
+use unc_chain_configs::{DumpConfig, Genesis, StatePartFormat};
 use unc_client::sync::external::external_storage_location;
 use unc_client::test_utils::TestEnv;
 use unc_client::ProcessTxResponse;
@@ -20,7 +20,7 @@ use unc_o11y::testonly::init_test_logger;
 use unc_primitives::block::Tip;
 use unc_primitives::shard_layout::ShardUId;
 use unc_primitives::state::FlatStateValue;
-use unc_primitives::state_part::PartId;
+use unc_primitives::state_part::{PartId, StatePartContainer};
 use unc_primitives::state_sync::StatePartKey;
 use unc_primitives::transaction::SignedTransaction;
 use unc_primitives::types::BlockHeight;
@@ -59,6 +59,7 @@ fn test_state_dump() {
             restart_dump_for_shards: None,
             iteration_delay: Some(Duration::ZERO),
             credentials_file: None,
+            part_format: StatePartFormat::Container,
         });
 
         let _state_sync_dump_handle = spawn_state_sync_dump(
@@ -98,9 +99,12 @@ fn test_state_dump() {
                         part_id,
                         num_parts,
                     ));
-                    if std::fs::read(&path).is_err() {
-                        tracing::info!("Missing {:?}", path);
-                        all_parts_present = false;
+                    match std::fs::read(&path) {
+                        Ok(part) => assert!(StatePartContainer::is_container(&part)),
+                        Err(_) => {
+                            tracing::info!("Missing {:?}", path);
+                            all_parts_present = false;
+                        }
                     }
                 }
             }
@@ -164,6 +168,7 @@ fn run_state_sync_with_dumped_parts(
             restart_dump_for_shards: None,
             iteration_delay: Some(Duration::ZERO),
             credentials_file: None,
+            part_format: StatePartFormat::Raw,
         });
         let _state_sync_dump_handle = spawn_state_sync_dump(
             &config,
@@ -45,6 +45,7 @@ impl StateSnaptshotTestEnv {
             enable_receipt_prefetching: false,
             load_mem_tries_for_shards: Vec::new(),
             load_mem_tries_for_all_shards: false,
+            serve_view_queries_from_mem_tries: false,
         };
         let flat_storage_manager = FlatStorageManager::new(store.clone());
         let shard_uids = [ShardUId::single_shard()];
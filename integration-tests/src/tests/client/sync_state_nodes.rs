@@ -10,7 +10,7 @@ use unc_actix_test_utils::run_actix;
 use unc_chain::chain::ApplyStatePartsRequest;
 use unc_chain::{ChainGenesis, Provenance};
 use unc_chain_configs::ExternalStorageLocation::Filesystem;
-use unc_chain_configs::{DumpConfig, ExternalStorageConfig, Genesis, SyncConfig};
+use unc_chain_configs::{DumpConfig, ExternalStorageConfig, Genesis, StatePartFormat, SyncConfig};
 use unc_client::adapter::{StateRequestHeader, StateRequestPart, StateResponse};
 use unc_client::test_utils::TestEnv;
 use unc_client::{GetBlock, ProcessTxResponse};
@@ -441,6 +441,7 @@ fn sync_state_dump() {
                 restart_dump_for_shards: None,
                 iteration_delay: Some(Duration::from_millis(500)),
                 credentials_file: None,
+                part_format: StatePartFormat::Container,
             });
             unc1.config.store.state_snapshot_enabled = true;
             unc1.config.store.state_snapshot_compaction_enabled = false;
@@ -123,6 +123,7 @@ fn test_view_call() {
         "run_test",
         &[],
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
 
@@ -151,6 +152,7 @@ fn test_view_call_try_changing_storage() {
         "run_test_with_storage_change",
         &[],
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
     let err = result.unwrap_err();
@@ -183,11 +185,48 @@ fn test_view_call_with_args() {
         "sum_with_input",
         &args,
         &mut logs,
+        None,
         &MockEpochInfoProvider::default(),
     );
     assert_eq!(view_call_result.unwrap(), 3u64.to_le_bytes().to_vec());
 }
 
+#[cfg(feature = "sandbox")]
+#[test]
+fn test_view_call_function_profile() {
+    let (viewer, root) = get_test_trie_viewer();
+    let args: Vec<_> = [1u64, 2u64].iter().flat_map(|x| (*x).to_le_bytes().to_vec()).collect();
+    let mut logs = vec![];
+    let mut function_profile = None;
+    let view_state = ViewApplyState {
+        block_height: 1,
+        prev_block_hash: CryptoHash::default(),
+        block_hash: CryptoHash::default(),
+        epoch_id: EpochId::default(),
+        epoch_height: 0,
+        block_timestamp: 1,
+        current_protocol_version: PROTOCOL_VERSION,
+        cache: None,
+    };
+    let view_call_result = viewer.call_function(
+        root,
+        view_state,
+        &"test.contract".parse().unwrap(),
+        "sum_with_input",
+        &args,
+        &mut logs,
+        Some(&mut function_profile),
+        &MockEpochInfoProvider::default(),
+    );
+    assert_eq!(view_call_result.unwrap(), 3u64.to_le_bytes().to_vec());
+    let function_profile = function_profile.expect("function profile should be collected");
+    assert_eq!(function_profile.method_name(), "sum_with_input");
+    assert!(function_profile.functions().any(|(_, gas)| gas > 0));
+    for line in function_profile.to_folded_stacks().lines() {
+        assert!(line.starts_with("sum_with_input;"), "{line}");
+    }
+}
+
 fn assert_view_state(
     trie_viewer: &TrieViewer,
     state_update: &unc_store::TrieUpdate,
@@ -405,6 +444,7 @@ fn test_log_when_panic() {
             "panic_after_logging",
             &[],
             &mut logs,
+            None,
             &MockEpochInfoProvider::default(),
         )
         .unwrap_err();
@@ -167,6 +167,7 @@ impl RuntimeUser {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_control: None,
         }
     }
 
@@ -289,6 +290,7 @@ impl User for RuntimeUser {
                 method_name,
                 args,
                 &mut result.logs,
+                None,
                 &self.epoch_info_provider,
             )
             .map_err(|err| err.to_string())?;
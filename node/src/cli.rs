@@ -547,7 +547,7 @@ impl RunCmd {
 
             let updateable_configs = framework::dyn_config::read_updateable_configs(home_dir)
                 .unwrap_or_else(|e| panic!("Error reading dynamic configs: {:#}", e));
-            let mut updateable_config_loader =
+            let updateable_config_loader =
                 UpdateableConfigLoader::new(updateable_configs.clone(), tx_config_update);
             let config_updater = ConfigUpdater::new(rx_config_update);
 
@@ -563,6 +563,7 @@ impl RunCmd {
                 unc_config,
                 Some(tx_crash),
                 Some(config_updater),
+                Some(updateable_config_loader.clone()),
             )
             .expect("start_with_config");
 
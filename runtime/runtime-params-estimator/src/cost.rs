@@ -608,6 +608,24 @@ pub enum Cost {
     AltBn128PairingCheckElement,
     AltBn128G1SumBase,
     AltBn128G1SumElement,
+    Bls12381P1SumBase,
+    Bls12381P1SumElement,
+    Bls12381P2SumBase,
+    Bls12381P2SumElement,
+    Bls12381G1MultiexpBase,
+    Bls12381G1MultiexpElement,
+    Bls12381G2MultiexpBase,
+    Bls12381G2MultiexpElement,
+    Bls12381MapFpToG1Base,
+    Bls12381MapFpToG1Element,
+    Bls12381MapFp2ToG2Base,
+    Bls12381MapFp2ToG2Element,
+    Bls12381PairingBase,
+    Bls12381PairingElement,
+    Bls12381P1DecompressBase,
+    Bls12381P1DecompressElement,
+    Bls12381P2DecompressBase,
+    Bls12381P2DecompressElement,
 
     // Costs used only in estimator
     //
@@ -38,6 +38,7 @@ pub fn costs_to_runtime_config(cost_table: &CostTable) -> anyhow::Result<Runtime
             ..latest_runtime_config.wasm_config
         },
         account_creation_config: AccountCreationConfig::default(),
+        congestion_control_config: latest_runtime_config.congestion_control_config.clone(),
     };
     Ok(res)
 }
@@ -72,6 +73,11 @@ fn runtime_fees_config(cost_table: &CostTable) -> anyhow::Result<RuntimeFeesConf
             ActionCosts::new_data_receipt_byte => fee(Cost::DataReceiptCreationPerByte)?,
             ActionCosts::register_rsa2048_keys => fee(Cost::RegisterRsa2048Keys)?,
             ActionCosts::create_rsa2048_challenge => fee(Cost::CreateRsa2048Challenge)?,
+            // Publishing a global contract does the same work as a regular deployment, and
+            // switching an account to it is bounded by the base cost of one.
+            ActionCosts::deploy_global_contract_base => fee(Cost::ActionDeployContractBase)?,
+            ActionCosts::deploy_global_contract_byte => fee(Cost::ActionDeployContractPerByte)?,
+            ActionCosts::use_global_contract => fee(Cost::ActionDeployContractBase)?,
         },
         ..actual_fees_config.clone()
     };
@@ -153,6 +159,24 @@ fn estimation(cost: ExtCosts) -> Option<Cost> {
         ExtCosts::alt_bn128_g1_multiexp_element => Cost::AltBn128G1MultiexpElement,
         ExtCosts::alt_bn128_pairing_check_base => Cost::AltBn128PairingCheckBase,
         ExtCosts::alt_bn128_pairing_check_element => Cost::AltBn128PairingCheckElement,
+        ExtCosts::bls12381_p1_sum_base => Cost::Bls12381P1SumBase,
+        ExtCosts::bls12381_p1_sum_element => Cost::Bls12381P1SumElement,
+        ExtCosts::bls12381_p2_sum_base => Cost::Bls12381P2SumBase,
+        ExtCosts::bls12381_p2_sum_element => Cost::Bls12381P2SumElement,
+        ExtCosts::bls12381_g1_multiexp_base => Cost::Bls12381G1MultiexpBase,
+        ExtCosts::bls12381_g1_multiexp_element => Cost::Bls12381G1MultiexpElement,
+        ExtCosts::bls12381_g2_multiexp_base => Cost::Bls12381G2MultiexpBase,
+        ExtCosts::bls12381_g2_multiexp_element => Cost::Bls12381G2MultiexpElement,
+        ExtCosts::bls12381_map_fp_to_g1_base => Cost::Bls12381MapFpToG1Base,
+        ExtCosts::bls12381_map_fp_to_g1_element => Cost::Bls12381MapFpToG1Element,
+        ExtCosts::bls12381_map_fp2_to_g2_base => Cost::Bls12381MapFp2ToG2Base,
+        ExtCosts::bls12381_map_fp2_to_g2_element => Cost::Bls12381MapFp2ToG2Element,
+        ExtCosts::bls12381_pairing_base => Cost::Bls12381PairingBase,
+        ExtCosts::bls12381_pairing_element => Cost::Bls12381PairingElement,
+        ExtCosts::bls12381_p1_decompress_base => Cost::Bls12381P1DecompressBase,
+        ExtCosts::bls12381_p1_decompress_element => Cost::Bls12381P1DecompressElement,
+        ExtCosts::bls12381_p2_decompress_base => Cost::Bls12381P2DecompressBase,
+        ExtCosts::bls12381_p2_decompress_element => Cost::Bls12381P2DecompressElement,
         _ => return None,
     })
 }
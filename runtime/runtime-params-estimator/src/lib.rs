@@ -227,6 +227,24 @@ static ALL_COSTS: &[(Cost, fn(&mut EstimatorContext) -> GasCost)] = &[
     (Cost::AltBn128G1SumElement, alt_bn128g1_sum_element),
     (Cost::AltBn128PairingCheckBase, alt_bn128_pairing_check_base),
     (Cost::AltBn128PairingCheckElement, alt_bn128_pairing_check_element),
+    (Cost::Bls12381P1SumBase, bls12381_p1_sum_base),
+    (Cost::Bls12381P1SumElement, bls12381_p1_sum_element),
+    (Cost::Bls12381P2SumBase, bls12381_p2_sum_base),
+    (Cost::Bls12381P2SumElement, bls12381_p2_sum_element),
+    (Cost::Bls12381G1MultiexpBase, bls12381_g1_multiexp_base),
+    (Cost::Bls12381G1MultiexpElement, bls12381_g1_multiexp_element),
+    (Cost::Bls12381G2MultiexpBase, bls12381_g2_multiexp_base),
+    (Cost::Bls12381G2MultiexpElement, bls12381_g2_multiexp_element),
+    (Cost::Bls12381MapFpToG1Base, bls12381_map_fp_to_g1_base),
+    (Cost::Bls12381MapFpToG1Element, bls12381_map_fp_to_g1_element),
+    (Cost::Bls12381MapFp2ToG2Base, bls12381_map_fp2_to_g2_base),
+    (Cost::Bls12381MapFp2ToG2Element, bls12381_map_fp2_to_g2_element),
+    (Cost::Bls12381PairingBase, bls12381_pairing_base),
+    (Cost::Bls12381PairingElement, bls12381_pairing_element),
+    (Cost::Bls12381P1DecompressBase, bls12381_p1_decompress_base),
+    (Cost::Bls12381P1DecompressElement, bls12381_p1_decompress_element),
+    (Cost::Bls12381P2DecompressBase, bls12381_p2_decompress_base),
+    (Cost::Bls12381P2DecompressElement, bls12381_p2_decompress_element),
     (Cost::StorageHasKeyBase, storage_has_key_base),
     (Cost::StorageHasKeyByte, storage_has_key_byte),
     (Cost::StorageReadBase, storage_read_base),
@@ -1057,6 +1075,79 @@ fn alt_bn128_pairing_check_element(ctx: &mut EstimatorContext) -> GasCost {
     )
 }
 
+fn bls12381_p1_sum_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p1_sum_1_1k", ExtCosts::bls12381_p1_sum_base, 1000)
+}
+fn bls12381_p1_sum_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p1_sum_10_1k", ExtCosts::bls12381_p1_sum_element, 10 * 1000)
+}
+
+fn bls12381_p2_sum_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p2_sum_1_1k", ExtCosts::bls12381_p2_sum_base, 1000)
+}
+fn bls12381_p2_sum_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p2_sum_10_1k", ExtCosts::bls12381_p2_sum_element, 10 * 1000)
+}
+
+fn bls12381_g1_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g1_multiexp_1_10", ExtCosts::bls12381_g1_multiexp_base, 10)
+}
+fn bls12381_g1_multiexp_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g1_multiexp_10_10", ExtCosts::bls12381_g1_multiexp_element, 10 * 10)
+}
+
+fn bls12381_g2_multiexp_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g2_multiexp_1_10", ExtCosts::bls12381_g2_multiexp_base, 10)
+}
+fn bls12381_g2_multiexp_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_g2_multiexp_10_10", ExtCosts::bls12381_g2_multiexp_element, 10 * 10)
+}
+
+fn bls12381_map_fp_to_g1_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp_to_g1_1_10", ExtCosts::bls12381_map_fp_to_g1_base, 10)
+}
+fn bls12381_map_fp_to_g1_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp_to_g1_10_10", ExtCosts::bls12381_map_fp_to_g1_element, 10 * 10)
+}
+
+fn bls12381_map_fp2_to_g2_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp2_to_g2_1_10", ExtCosts::bls12381_map_fp2_to_g2_base, 10)
+}
+fn bls12381_map_fp2_to_g2_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_map_fp2_to_g2_10_10", ExtCosts::bls12381_map_fp2_to_g2_element, 10 * 10)
+}
+
+fn bls12381_pairing_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_pairing_check_1_10", ExtCosts::bls12381_pairing_base, 10)
+}
+fn bls12381_pairing_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_pairing_check_10_10", ExtCosts::bls12381_pairing_element, 10 * 10)
+}
+
+fn bls12381_p1_decompress_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p1_decompress_1_100", ExtCosts::bls12381_p1_decompress_base, 100)
+}
+fn bls12381_p1_decompress_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(
+        ctx,
+        "bls12381_p1_decompress_10_100",
+        ExtCosts::bls12381_p1_decompress_element,
+        10 * 100,
+    )
+}
+
+fn bls12381_p2_decompress_base(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(ctx, "bls12381_p2_decompress_1_100", ExtCosts::bls12381_p2_decompress_base, 100)
+}
+fn bls12381_p2_decompress_element(ctx: &mut EstimatorContext) -> GasCost {
+    fn_cost(
+        ctx,
+        "bls12381_p2_decompress_10_100",
+        ExtCosts::bls12381_p2_decompress_element,
+        10 * 100,
+    )
+}
+
 fn storage_has_key_base(ctx: &mut EstimatorContext) -> GasCost {
     fn_cost_with_setup(
         ctx,
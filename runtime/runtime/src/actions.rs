@@ -1,35 +1,47 @@
 use crate::config::{
-    safe_add_compute, safe_add_gas, total_prepaid_exec_fees, total_prepaid_gas,
-    total_prepaid_send_fees,
+    global_contract_storage_exec_fee, safe_add_compute, safe_add_gas, safe_gas_to_balance,
+    total_prepaid_exec_fees, total_prepaid_gas, total_prepaid_send_fees,
 };
 use crate::ext::{ExternalError, RuntimeExt};
 use crate::receipt_manager::ReceiptManager;
 use crate::{metrics, ActionResult, ApplyState};
 
+use unc_chain_configs::genesis_validate::RSA2048_ROOT_ACCOUNT;
 use unc_crypto::PublicKey;
 use unc_parameters::{ActionCosts, RuntimeConfig, RuntimeFeesConfig};
 use unc_primitives::account::{AccessKey, AccessKeyPermission, Account};
 use unc_primitives::action::delegate::{DelegateAction, SignedDelegateAction};
 use unc_primitives::checked_feature;
 use unc_primitives::config::ViewConfig;
-use unc_primitives::errors::{ActionError, ActionErrorKind, InvalidAccessKeyError, RuntimeError};
+use unc_primitives::errors::{
+    ActionError, ActionErrorKind, IntegerOverflowError, InvalidAccessKeyError, RuntimeError,
+};
 use unc_primitives::hash::CryptoHash;
-use unc_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
+use unc_primitives::receipt::{
+    ActionReceipt, DataReceipt, GlobalContractDistributionReceipt, PromiseYieldTimeout, Receipt,
+    ReceiptEnum,
+};
+use unc_primitives::shard_layout::shard_routing_account_id;
 use unc_primitives::transaction::{
     Action, AddKeyAction, CreateRsa2048ChallengeAction, DeleteAccountAction, DeleteKeyAction,
-    DeployContractAction, FunctionCallAction, PledgeAction, RegisterRsa2048KeysAction,
-    TransferAction,
+    DeployContractAction, DeployGlobalContractAction, FunctionCallAction, PledgeAction,
+    RegisterRsa2048KeysAction, TransferAction, UseGlobalContractAction,
 };
 use unc_primitives::types::validator_power::ValidatorPower;
-use unc_primitives::types::{AccountId, BlockHeight, EpochInfoProvider, Gas, TrieCacheMode};
+use unc_primitives::types::{
+    AccountId, Balance, BlockHeight, EpochInfoProvider, Gas, TrieCacheMode,
+};
 use unc_primitives::utils::{account_is_valid, create_random_seed};
 use unc_primitives::version::{
     ProtocolFeature, ProtocolVersion, DELETE_KEY_STORAGE_USAGE_PROTOCOL_VERSION,
 };
 use unc_primitives_core::account::id::AccountType;
 use unc_store::{
-    get_access_key, get_code, get_rsa2048_keys, remove_access_key, remove_account,
-    remove_rsa2048_keys, set_access_key, set_code, set_rsa2048_keys, StorageError, TrieUpdate,
+    enqueue_promise_yield_timeout, get_access_key, get_code, get_promise_yield_indices,
+    get_rsa2048_keys, has_global_contract_code, remove_access_key, remove_account, remove_code,
+    remove_rsa2048_keys, set_access_key, set_code, set_global_contract_code,
+    set_promise_yield_indices, set_promise_yield_receipt, set_rsa2048_keys, StorageError,
+    TrieUpdate,
 };
 use unc_vm_runner::logic::errors::{
     CompilationError, FunctionCallError, InconsistentStateError, VMRunnerError,
@@ -91,6 +103,111 @@ pub(crate) fn execute_function_call(
             return Err(RuntimeError::StorageError(e));
         }
     };
+    let context = function_call_context(
+        apply_state,
+        runtime_ext,
+        account,
+        predecessor_id,
+        action_receipt,
+        function_call,
+        action_hash,
+        is_last_action,
+        view_config.clone(),
+    );
+
+    // Enable caching chunk mode for the function call. This allows to charge for nodes touched in a chunk only once for
+    // the first access time. Although nodes are accessed for other actions as well, we do it only here because we
+    // charge only for trie nodes touched during function calls.
+    // TODO (#5920): Consider using RAII for switching the state back
+    let protocol_version = runtime_ext.protocol_version();
+    if checked_feature!("stable", ChunkNodesCache, protocol_version) {
+        runtime_ext.set_trie_cache_mode(TrieCacheMode::CachingChunk);
+    }
+    let result = unc_vm_runner::run(
+        &code,
+        &function_call.method_name,
+        runtime_ext,
+        context,
+        &config.wasm_config,
+        &config.fees,
+        promise_results,
+        apply_state.cache.as_deref(),
+    );
+
+    if checked_feature!("stable", ChunkNodesCache, protocol_version) {
+        runtime_ext.set_trie_cache_mode(TrieCacheMode::CachingShard);
+    }
+
+    let mut outcome = result.map_err(vm_runner_error)?;
+
+    if !view_config.is_some() {
+        let unused_gas = function_call.gas.saturating_sub(outcome.used_gas);
+        let distributed = runtime_ext.receipt_manager.distribute_gas(unused_gas)?;
+        outcome.used_gas = safe_add_gas(outcome.used_gas, distributed)?;
+    }
+    Ok(outcome)
+}
+
+/// Runs given function call like [`execute_function_call`] does, but records a debug trace as
+/// requested by `options`.
+///
+/// Used by the debug calls of sandbox nodes, which run on a copy of the state that is discarded
+/// afterwards.
+#[cfg(feature = "sandbox")]
+pub(crate) fn execute_debug_function_call(
+    apply_state: &ApplyState,
+    runtime_ext: &mut RuntimeExt,
+    account: &Account,
+    action_receipt: &ActionReceipt,
+    function_call: &FunctionCallAction,
+    config: &RuntimeConfig,
+    options: unc_vm_runner::debugger::DebugOptions,
+) -> Result<VMOutcome, RuntimeError> {
+    let code = match get_contract_code(runtime_ext, account, apply_state.current_protocol_version)?
+    {
+        Some(code) => code,
+        None => {
+            let error = FunctionCallError::CompilationError(CompilationError::CodeDoesNotExist {
+                account_id: runtime_ext.account_id().as_str().into(),
+            });
+            return Ok(VMOutcome::nop_outcome(error));
+        }
+    };
+    let context = function_call_context(
+        apply_state,
+        runtime_ext,
+        account,
+        &action_receipt.signer_id,
+        action_receipt,
+        function_call,
+        &CryptoHash::default(),
+        true,
+        None,
+    );
+    unc_vm_runner::debugger::run(
+        &code,
+        &function_call.method_name,
+        runtime_ext,
+        context,
+        &config.wasm_config,
+        &config.fees,
+        &[],
+        options,
+    )
+    .map_err(vm_runner_error)
+}
+
+fn function_call_context(
+    apply_state: &ApplyState,
+    runtime_ext: &RuntimeExt,
+    account: &Account,
+    predecessor_id: &AccountId,
+    action_receipt: &ActionReceipt,
+    function_call: &FunctionCallAction,
+    action_hash: &CryptoHash,
+    is_last_action: bool,
+    view_config: Option<ViewConfig>,
+) -> VMContext {
     // Output data receipts are ignored if the function call is not the last action in the batch.
     let output_data_receivers: Vec<_> = if is_last_action {
         action_receipt.output_data_receivers.iter().map(|r| r.receiver_id.clone()).collect()
@@ -102,7 +219,7 @@ pub(crate) fn execute_function_call(
         *action_hash,
         apply_state.random_seed,
     );
-    let context = VMContext {
+    VMContext {
         current_account_id: runtime_ext.account_id().clone(),
         signer_account_id: action_receipt.signer_id.clone(),
         signer_account_pk: borsh::to_vec(&action_receipt.signer_public_key)
@@ -118,40 +235,19 @@ pub(crate) fn execute_function_call(
         attached_deposit: function_call.deposit,
         prepaid_gas: function_call.gas,
         random_seed,
-        view_config: view_config.clone(),
+        view_config,
         output_data_receivers,
-    };
-
-    // Enable caching chunk mode for the function call. This allows to charge for nodes touched in a chunk only once for
-    // the first access time. Although nodes are accessed for other actions as well, we do it only here because we
-    // charge only for trie nodes touched during function calls.
-    // TODO (#5920): Consider using RAII for switching the state back
-    let protocol_version = runtime_ext.protocol_version();
-    if checked_feature!("stable", ChunkNodesCache, protocol_version) {
-        runtime_ext.set_trie_cache_mode(TrieCacheMode::CachingChunk);
-    }
-    let result = unc_vm_runner::run(
-        &code,
-        &function_call.method_name,
-        runtime_ext,
-        context,
-        &config.wasm_config,
-        &config.fees,
-        promise_results,
-        apply_state.cache.as_deref(),
-    );
-
-    if checked_feature!("stable", ChunkNodesCache, protocol_version) {
-        runtime_ext.set_trie_cache_mode(TrieCacheMode::CachingShard);
     }
+}
 
-    // There are many specific errors that the runtime can encounter.
-    // Some can be translated to the more general `RuntimeError`, which allows to pass
-    // the error up to the caller. For all other cases, panicking here is better
-    // than leaking the exact details further up.
-    // Note that this does not include errors caused by user code / input, those are
-    // stored in outcome.aborted.
-    let mut outcome = result.map_err(|e| match e {
+// There are many specific errors that the runtime can encounter.
+// Some can be translated to the more general `RuntimeError`, which allows to pass
+// the error up to the caller. For all other cases, panicking here is better
+// than leaking the exact details further up.
+// Note that this does not include errors caused by user code / input, those are
+// stored in outcome.aborted.
+fn vm_runner_error(error: VMRunnerError) -> RuntimeError {
+    match error {
         VMRunnerError::ExternalError(any_err) => {
             let err: ExternalError =
                 any_err.downcast().expect("Downcasting AnyError should not fail");
@@ -176,14 +272,7 @@ pub(crate) fn execute_function_call(
         VMRunnerError::WasmUnknownError { debug_message } => {
             panic!("Wasmer returned unknown message: {}", debug_message)
         }
-    })?;
-
-    if !view_config.is_some() {
-        let unused_gas = function_call.gas.saturating_sub(outcome.used_gas);
-        let distributed = runtime_ext.receipt_manager.distribute_gas(unused_gas)?;
-        outcome.used_gas = safe_add_gas(outcome.used_gas, distributed)?;
     }
-    Ok(outcome)
 }
 
 pub(crate) fn action_function_call(
@@ -308,10 +397,43 @@ pub(crate) fn action_function_call(
             })
             .collect();
 
+        // Yields that were not resumed within this same call wait for their data in the state
+        // until they are resumed or time out.
+        if !receipt_manager.promise_yields.is_empty() {
+            let expires_at = apply_state
+                .block_height
+                .saturating_add(config.wasm_config.limit_config.yield_timeout_length_in_blocks);
+            let mut promise_yield_indices = get_promise_yield_indices(state_update)?;
+            let initial_promise_yield_indices = promise_yield_indices.clone();
+            for data_id in receipt_manager.promise_yields {
+                set_promise_yield_receipt(state_update, account_id.clone(), data_id, expires_at);
+                enqueue_promise_yield_timeout(
+                    state_update,
+                    &mut promise_yield_indices,
+                    &PromiseYieldTimeout { account_id: account_id.clone(), data_id, expires_at },
+                );
+            }
+            if promise_yield_indices != initial_promise_yield_indices {
+                set_promise_yield_indices(state_update, &promise_yield_indices);
+            }
+        }
+        // Resumed yields receive their payload through a regular data receipt.
+        let resume_receipts: Vec<_> = receipt_manager
+            .promise_resumes
+            .into_iter()
+            .map(|(data_id, data)| Receipt {
+                predecessor_id: account_id.clone(),
+                receiver_id: account_id.clone(),
+                receipt_id: CryptoHash::default(),
+                receipt: ReceiptEnum::Data(DataReceipt { data_id, data: Some(data) }),
+            })
+            .collect();
+
         account.set_amount(outcome.balance);
         account.set_storage_usage(outcome.storage_usage);
         result.result = Ok(outcome.return_data);
         result.new_receipts.extend(new_receipts);
+        result.new_receipts.extend(resume_receipts);
     }
 
     Ok(())
@@ -535,6 +657,132 @@ pub(crate) fn action_deploy_contract(
     Ok(())
 }
 
+/// Publishes the code into the global contract registry of the shard and sends it to the
+/// registries of all the other shards with `GlobalContractDistribution` receipts.
+///
+/// The code is stored once per code hash and its storage is charged to the publishing account.
+/// Publishing a code that is already in the registry is a no-op. The action fee only covers the
+/// registry of the publisher's shard, storing the code on every other shard is paid from the
+/// publisher's balance at the current gas price and burnt when the distribution receipt is
+/// applied.
+pub(crate) fn action_deploy_global_contract(
+    state_update: &mut TrieUpdate,
+    account: &mut Account,
+    account_id: &AccountId,
+    deploy_global_contract: &DeployGlobalContractAction,
+    apply_state: &ApplyState,
+    result: &mut ActionResult,
+) -> Result<(), RuntimeError> {
+    let _span = tracing::debug_span!(target: "runtime", "action_deploy_global_contract").entered();
+    let code = ContractCode::new(deploy_global_contract.code.clone(), None);
+    if has_global_contract_code(state_update, *code.hash())? {
+        return Ok(());
+    }
+    // Without congestion control the chunk doesn't know the shard layout, this is only the case in
+    // tests which run a single shard.
+    let destination_shards: Vec<_> = match &apply_state.congestion_control {
+        Some(congestion_control) => congestion_control
+            .shard_layout
+            .shard_ids()
+            .filter(|&shard_id| shard_id != congestion_control.shard_id)
+            .collect(),
+        None => vec![],
+    };
+    let distribution_cost = safe_gas_to_balance(
+        apply_state.gas_price,
+        global_contract_storage_exec_fee(&apply_state.config, code.code().len() as u64),
+    )?
+    .checked_mul(destination_shards.len() as Balance)
+    .ok_or(IntegerOverflowError)?;
+    if account.amount() < distribution_cost {
+        result.result = Err(ActionErrorKind::LackBalanceForState {
+            account_id: account_id.clone(),
+            amount: distribution_cost,
+        }
+        .into());
+        return Ok(());
+    }
+    account.set_amount(account.amount() - distribution_cost);
+    let storage_config = &apply_state.config.fees.storage_usage_config;
+    account.set_storage_usage(
+        account
+            .storage_usage()
+            .checked_add(code.code().len() as u64 + storage_config.num_extra_bytes_record)
+            .ok_or_else(|| {
+                StorageError::StorageInconsistentState(format!(
+                    "Storage usage integer overflow for account {}",
+                    account_id
+                ))
+            })?,
+    );
+    store_global_contract(state_update, &code, apply_state);
+    if let Some(congestion_control) = &apply_state.congestion_control {
+        let shard_layout = &congestion_control.shard_layout;
+        result.new_receipts.extend(destination_shards.into_iter().map(|shard_id| Receipt {
+            predecessor_id: account_id.clone(),
+            receiver_id: shard_routing_account_id(shard_id, shard_layout),
+            receipt_id: CryptoHash::default(),
+            receipt: ReceiptEnum::GlobalContractDistribution(GlobalContractDistributionReceipt {
+                code: deploy_global_contract.code.clone(),
+                gas_price: apply_state.gas_price,
+            }),
+        }));
+    }
+    Ok(())
+}
+
+/// Stores the code received with a `GlobalContractDistribution` receipt in the global contract
+/// registry of the shard. The storage is paid by the publisher on the shard of its account.
+pub(crate) fn apply_global_contract_distribution(
+    state_update: &mut TrieUpdate,
+    distribution_receipt: &GlobalContractDistributionReceipt,
+    apply_state: &ApplyState,
+) -> Result<(), StorageError> {
+    let _span =
+        tracing::debug_span!(target: "runtime", "apply_global_contract_distribution").entered();
+    let code = ContractCode::new(distribution_receipt.code.clone(), None);
+    if !has_global_contract_code(state_update, *code.hash())? {
+        store_global_contract(state_update, &code, apply_state);
+    }
+    Ok(())
+}
+
+fn store_global_contract(
+    state_update: &mut TrieUpdate,
+    code: &ContractCode,
+    apply_state: &ApplyState,
+) {
+    set_global_contract_code(state_update, code);
+    // The compiled artifact is keyed by the code hash, so it is shared by all the accounts using
+    // this global contract.
+    precompile_contract(code, &apply_state.config.wasm_config, apply_state.cache.as_deref()).ok();
+}
+
+/// Points the account at a code from the global contract registry, dropping the code the account
+/// stored itself, if any.
+pub(crate) fn action_use_global_contract(
+    state_update: &mut TrieUpdate,
+    account: &mut Account,
+    account_id: &AccountId,
+    use_global_contract: &UseGlobalContractAction,
+    result: &mut ActionResult,
+) -> Result<(), StorageError> {
+    let code_hash = use_global_contract.code_hash;
+    if !has_global_contract_code(state_update, code_hash)? {
+        result.result = Err(ActionErrorKind::GlobalContractDoesNotExist { code_hash }.into());
+        return Ok(());
+    }
+    let prev_code = get_code(state_update, account_id, Some(account.code_hash()))?;
+    if let Some(prev_code) = prev_code {
+        account.set_storage_usage(
+            account.storage_usage().saturating_sub(prev_code.code().len() as u64),
+        );
+        remove_code(state_update, account_id.clone());
+    }
+    account.set_code_hash(code_hash);
+    Ok(())
+}
+
 pub(crate) fn action_delete_account(
     state_update: &mut TrieUpdate,
     account: &mut Option<Account>,
@@ -707,8 +955,7 @@ pub(crate) fn action_create_rsa2048_challenge(
     account_id: &AccountId,
     challenge: &CreateRsa2048ChallengeAction,
 ) -> Result<(), RuntimeError> {
-    // TODO: Obtain the actual value of root_id from the "unc" foundation
-    let root_id = "unc".parse::<AccountId>().unwrap();
+    let root_id = RSA2048_ROOT_ACCOUNT.parse::<AccountId>().unwrap();
     if get_rsa2048_keys(state_update, &root_id, &challenge.public_key)?.is_none() {
         result.result = Err(ActionErrorKind::RsaKeysNotFound {
             account_id: account_id.to_owned(),
@@ -1010,6 +1257,8 @@ pub(crate) fn check_actor_permissions(
 ) -> Result<(), ActionError> {
     match action {
         Action::DeployContract(_)
+        | Action::DeployGlobalContract(_)
+        | Action::UseGlobalContract(_)
         | Action::Pledge(_)
         | Action::AddKey(_)
         | Action::DeleteKey(_) => {
@@ -1040,8 +1289,8 @@ pub(crate) fn check_actor_permissions(
         Action::CreateAccount(_) | Action::FunctionCall(_) | Action::Transfer(_) => (),
         Action::Delegate(_) => (),
         Action::RegisterRsa2048Keys(_) => {
-            // FIXME: Hard-coded here, configuration should be added in RuntimeConfig later. Only the genesis consensus account and foundation account, similar to the registrar account.
-            let root_id = "unc".parse::<AccountId>().unwrap();
+            // FIXME: configuration should be added in RuntimeConfig later. Only the genesis consensus account and foundation account, similar to the registrar account.
+            let root_id = RSA2048_ROOT_ACCOUNT.parse::<AccountId>().unwrap();
             if account_id.clone() != root_id {
                 return Err(ActionErrorKind::ActorNoPermission {
                     account_id: account_id.clone(),
@@ -1114,6 +1363,8 @@ pub(crate) fn check_account_existence(
             }
         }
         Action::DeployContract(_)
+        | Action::DeployGlobalContract(_)
+        | Action::UseGlobalContract(_)
         | Action::FunctionCall(_)
         | Action::Pledge(_)
         | Action::AddKey(_)
@@ -1144,10 +1395,11 @@ pub(crate) fn check_account_existence(
 mod tests {
 
     use super::*;
-    use crate::unc_primitives::shard_layout::ShardUId;
+    use crate::unc_primitives::shard_layout::{account_id_to_shard_id, ShardLayout, ShardUId};
     use std::sync::Arc;
     use unc_primitives::account::FunctionCallPermission;
     use unc_primitives::action::delegate::NonDelegateAction;
+    use unc_primitives::congestion_info::{BlockCongestionInfo, CongestionControl};
     use unc_primitives::errors::InvalidAccessKeyError;
     use unc_primitives::hash::hash;
     use unc_primitives::runtime::migration_data::MigrationFlags;
@@ -1330,6 +1582,205 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_global_contract_stored_once() {
+        let tries = TestTriesBuilder::new().build();
+        let mut state_update =
+            tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default());
+        let apply_state = create_apply_state(1);
+        let num_extra_bytes_record =
+            apply_state.config.fees.storage_usage_config.num_extra_bytes_record;
+        let code = [0; 1_000].to_vec();
+        let code_hash = hash(&code);
+        let action = DeployGlobalContractAction { code: code.clone() };
+
+        let publisher_id = "alice".parse::<AccountId>().unwrap();
+        let mut publisher = Account::new(100, 0, 0, CryptoHash::default(), 100);
+        let mut action_result = ActionResult::default();
+        action_deploy_global_contract(
+            &mut state_update,
+            &mut publisher,
+            &publisher_id,
+            &action,
+            &apply_state,
+            &mut action_result,
+        )
+        .unwrap();
+        assert!(action_result.new_receipts.is_empty());
+        assert_eq!(publisher.storage_usage(), 100 + code.len() as u64 + num_extra_bytes_record);
+        assert_eq!(publisher.code_hash(), CryptoHash::default());
+        let stored = unc_store::get_global_contract_code(&state_update, code_hash).unwrap();
+        assert_eq!(stored.unwrap().code(), code.as_slice());
+
+        // Publishing the same code again is free of storage.
+        let other_id = "bob".parse::<AccountId>().unwrap();
+        let mut other = Account::new(100, 0, 0, CryptoHash::default(), 100);
+        action_deploy_global_contract(
+            &mut state_update,
+            &mut other,
+            &other_id,
+            &action,
+            &apply_state,
+            &mut ActionResult::default(),
+        )
+        .unwrap();
+        assert_eq!(other.storage_usage(), 100);
+    }
+
+    fn sharded_apply_state() -> (ApplyState, ShardLayout) {
+        let mut apply_state = create_apply_state(1);
+        let shard_layout =
+            ShardLayout::v1(vec!["bbb".parse().unwrap(), "ccc".parse().unwrap()], None, 1);
+        apply_state.congestion_control = Some(CongestionControl {
+            shard_id: 1,
+            shard_layout: shard_layout.clone(),
+            block_congestion_info: BlockCongestionInfo::default(),
+        });
+        (apply_state, shard_layout)
+    }
+
+    #[test]
+    fn test_global_contract_distributed_to_other_shards() {
+        let tries = TestTriesBuilder::new().build();
+        let (apply_state, shard_layout) = sharded_apply_state();
+        let code = [0; 1_000].to_vec();
+        let code_hash = hash(&code);
+        let storage_cost = global_contract_storage_exec_fee(&apply_state.config, 1_000) as Balance
+            * apply_state.gas_price;
+
+        let publisher_id = "bob".parse::<AccountId>().unwrap();
+        let initial_balance = 10 * storage_cost;
+        let mut publisher = Account::new(initial_balance, 0, 0, CryptoHash::default(), 100);
+        let mut action_result = ActionResult::default();
+        action_deploy_global_contract(
+            &mut tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default()),
+            &mut publisher,
+            &publisher_id,
+            &DeployGlobalContractAction { code: code.clone() },
+            &apply_state,
+            &mut action_result,
+        )
+        .unwrap();
+        assert!(action_result.result.is_ok());
+
+        // One receipt is sent to each of the other shards and the publisher prepays each of them.
+        let receiving_shards: Vec<_> = action_result
+            .new_receipts
+            .iter()
+            .map(|receipt| account_id_to_shard_id(&receipt.receiver_id, &shard_layout))
+            .collect();
+        assert_eq!(receiving_shards, vec![0, 2]);
+        assert_eq!(publisher.amount(), initial_balance - 2 * storage_cost);
+
+        // The receiving shard stores the code in its registry.
+        for receipt in &action_result.new_receipts {
+            assert_eq!(receipt.predecessor_id, publisher_id);
+            let ReceiptEnum::GlobalContractDistribution(distribution_receipt) = &receipt.receipt
+            else {
+                panic!("expected a global contract distribution receipt, got {receipt:?}");
+            };
+            assert_eq!(distribution_receipt.gas_price, apply_state.gas_price);
+            let mut state_update =
+                tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default());
+            apply_global_contract_distribution(
+                &mut state_update,
+                distribution_receipt,
+                &apply_state,
+            )
+            .unwrap();
+            let stored = unc_store::get_global_contract_code(&state_update, code_hash).unwrap();
+            assert_eq!(stored.unwrap().code(), code.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_global_contract_distribution_lacks_balance() {
+        let tries = TestTriesBuilder::new().build();
+        let (apply_state, _) = sharded_apply_state();
+        let code = [0; 1_000].to_vec();
+        let storage_cost = global_contract_storage_exec_fee(&apply_state.config, 1_000) as Balance
+            * apply_state.gas_price;
+
+        let publisher_id = "bob".parse::<AccountId>().unwrap();
+        let mut publisher = Account::new(storage_cost, 0, 0, CryptoHash::default(), 100);
+        let mut action_result = ActionResult::default();
+        action_deploy_global_contract(
+            &mut tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default()),
+            &mut publisher,
+            &publisher_id,
+            &DeployGlobalContractAction { code },
+            &apply_state,
+            &mut action_result,
+        )
+        .unwrap();
+        assert_eq!(
+            action_result.result,
+            Err(ActionError {
+                index: None,
+                kind: ActionErrorKind::LackBalanceForState {
+                    account_id: publisher_id,
+                    amount: 2 * storage_cost,
+                }
+            })
+        );
+        assert!(action_result.new_receipts.is_empty());
+        assert_eq!(publisher.amount(), storage_cost);
+    }
+
+    #[test]
+    fn test_use_global_contract() {
+        let tries = TestTriesBuilder::new().build();
+        let mut state_update =
+            tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default());
+        let global_code = ContractCode::new([1; 1_000].to_vec(), None);
+        set_global_contract_code(&mut state_update, &global_code);
+
+        let account_id = "alice".parse::<AccountId>().unwrap();
+        let own_code = [0; 500].to_vec();
+        let own_code_hash = hash(&own_code);
+        state_update.set(TrieKey::ContractCode { account_id: account_id.clone() }, own_code);
+        let mut account = Account::new(100, 0, 0, own_code_hash, 600);
+
+        let mut action_result = ActionResult::default();
+        action_use_global_contract(
+            &mut state_update,
+            &mut account,
+            &account_id,
+            &UseGlobalContractAction { code_hash: *global_code.hash() },
+            &mut action_result,
+        )
+        .unwrap();
+        assert!(action_result.result.is_ok());
+        assert_eq!(account.code_hash(), *global_code.hash());
+        assert_eq!(account.storage_usage(), 100);
+        assert!(get_code(&state_update, &account_id, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_use_global_contract_does_not_exist() {
+        let tries = TestTriesBuilder::new().build();
+        let mut state_update =
+            tries.new_trie_update(ShardUId::single_shard(), CryptoHash::default());
+        let account_id = "alice".parse::<AccountId>().unwrap();
+        let mut account = Account::new(100, 0, 0, CryptoHash::default(), 100);
+        let code_hash = hash(b"missing");
+
+        let mut action_result = ActionResult::default();
+        action_use_global_contract(
+            &mut state_update,
+            &mut account,
+            &account_id,
+            &UseGlobalContractAction { code_hash },
+            &mut action_result,
+        )
+        .unwrap();
+        assert_eq!(
+            action_result.result,
+            Err(ActionErrorKind::GlobalContractDoesNotExist { code_hash }.into())
+        );
+        assert_eq!(account.code_hash(), CryptoHash::default());
+    }
+
     fn create_delegate_action_receipt() -> (ActionReceipt, SignedDelegateAction) {
         let signed_delegate_action = SignedDelegateAction {
             delegate_action: DelegateAction {
@@ -1383,6 +1834,7 @@ mod tests {
             is_new_chunk: false,
             migration_data: Arc::default(),
             migration_flags: MigrationFlags::default(),
+            congestion_control: None,
         }
     }
 
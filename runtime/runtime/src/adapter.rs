@@ -7,7 +7,7 @@ use unc_primitives::types::{
 };
 use unc_primitives::version::ProtocolVersion;
 use unc_primitives::views::{ChipView, ViewStateResult};
-use unc_vm_runner::ContractCode;
+use unc_vm_runner::{ContractCode, FunctionGasProfile};
 
 /// Adapter for querying runtime.
 pub trait ViewRuntimeAdapter {
@@ -39,6 +39,7 @@ pub trait ViewRuntimeAdapter {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        function_profile: Option<&mut Option<FunctionGasProfile>>,
         epoch_info_provider: &dyn EpochInfoProvider,
         current_protocol_version: ProtocolVersion,
     ) -> Result<Vec<u8>, crate::state_viewer::errors::CallFunctionError>;
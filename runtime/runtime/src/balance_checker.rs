@@ -1,6 +1,6 @@
 use crate::config::{
-    safe_add_balance, safe_add_gas, safe_gas_to_balance, total_deposit, total_prepaid_exec_fees,
-    total_prepaid_gas, total_prepaid_send_fees,
+    global_contract_storage_exec_fee, safe_add_balance, safe_add_gas, safe_gas_to_balance,
+    total_deposit, total_prepaid_exec_fees, total_prepaid_gas, total_prepaid_send_fees,
 };
 use crate::safe_add_balance_apply;
 use crate::{ApplyStats, DelayedReceiptIndices, ValidatorAccountsUpdate};
@@ -55,6 +55,10 @@ fn receipt_cost(
             }
             total_cost
         }
+        ReceiptEnum::GlobalContractDistribution(distribution_receipt) => safe_gas_to_balance(
+            distribution_receipt.gas_price,
+            global_contract_storage_exec_fee(config, distribution_receipt.code.len() as u64),
+        )?,
         ReceiptEnum::Data(_) => 0,
     })
 }
@@ -190,6 +194,7 @@ pub(crate) fn check_balance(
                         Ok(Some(receipt_id)) => Some(Ok((account_id.clone(), receipt_id))),
                     }
                 }
+                ReceiptEnum::GlobalContractDistribution(_) => None,
             }
         })
         .collect::<Result<HashSet<_>, StorageError>>()?;
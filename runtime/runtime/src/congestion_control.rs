@@ -0,0 +1,175 @@
+//! Backpressure between the shards.
+//!
+//! Every shard keeps track of the gas and the size of its delayed receipts in
+//! the `CongestionInfo`, which ends up in the header of its next chunk. When
+//! applying a chunk, the runtime limits the gas of the receipts sent to each of
+//! the other shards according to their congestion. Once the limit of some shard
+//! is reached, the remaining receipts are delayed until the next chunk.
+
+use std::collections::HashMap;
+
+use unc_parameters::{CongestionControlConfig, RuntimeConfig};
+use unc_primitives::congestion_info::{CongestionControl, CongestionInfo};
+use unc_primitives::errors::{IntegerOverflowError, RuntimeError, StorageError};
+use unc_primitives::receipt::{DelayedReceiptIndices, Receipt, ReceiptEnum};
+use unc_primitives::shard_layout::{account_id_to_shard_id, ShardLayout};
+use unc_primitives::trie_key::TrieKey;
+use unc_primitives::types::{Gas, ShardId};
+use unc_store::{get, TrieUpdate};
+
+use crate::config::{
+    global_contract_storage_exec_fee, safe_add_gas, total_prepaid_exec_fees, total_prepaid_gas,
+};
+
+/// Gas accounted for the receipt in the congestion info: all the gas that may
+/// be spent while executing it.
+pub(crate) fn receipt_congestion_gas(
+    config: &RuntimeConfig,
+    receipt: &Receipt,
+) -> Result<Gas, IntegerOverflowError> {
+    match &receipt.receipt {
+        ReceiptEnum::Action(action_receipt) => safe_add_gas(
+            total_prepaid_gas(&action_receipt.actions)?,
+            total_prepaid_exec_fees(config, &action_receipt.actions, &receipt.receiver_id)?,
+        ),
+        ReceiptEnum::GlobalContractDistribution(distribution_receipt) => {
+            Ok(global_contract_storage_exec_fee(config, distribution_receipt.code.len() as u64))
+        }
+        ReceiptEnum::Data(_) => Ok(0),
+    }
+}
+
+/// Size of the receipt accounted in the congestion info.
+pub(crate) fn receipt_size(receipt: &Receipt) -> u64 {
+    borsh::object_length(receipt).expect("Receipt serialization must not fail") as u64
+}
+
+pub(crate) fn add_delayed_receipt(
+    config: &RuntimeConfig,
+    congestion_info: &mut CongestionInfo,
+    receipt: &Receipt,
+) -> Result<(), RuntimeError> {
+    congestion_info
+        .add_delayed_receipt(receipt_congestion_gas(config, receipt)?, receipt_size(receipt))?;
+    Ok(())
+}
+
+pub(crate) fn remove_delayed_receipt(
+    config: &RuntimeConfig,
+    congestion_info: &mut CongestionInfo,
+    receipt: &Receipt,
+) -> Result<(), RuntimeError> {
+    congestion_info
+        .remove_delayed_receipt(receipt_congestion_gas(config, receipt)?, receipt_size(receipt));
+    Ok(())
+}
+
+/// Maximum number of delayed receipts read by `bootstrap_congestion_info`.
+pub(crate) const MAX_BOOTSTRAP_DELAYED_RECEIPTS: u64 = 1000;
+
+/// Computes the congestion info from scratch by reading the delayed receipts
+/// queue. Used for the first chunk of a shard which does not have the
+/// congestion info in its header yet, e.g. right after resharding.
+///
+/// Only the first `MAX_BOOTSTRAP_DELAYED_RECEIPTS` receipts are read, so that
+/// the cost of the chunk stays bounded. The info of a longer queue is
+/// extrapolated from them; the estimate converges to the actual value as the
+/// queue drains and is reset once the queue is empty.
+pub(crate) fn bootstrap_congestion_info(
+    config: &RuntimeConfig,
+    state_update: &TrieUpdate,
+    delayed_receipts_indices: &DelayedReceiptIndices,
+) -> Result<CongestionInfo, RuntimeError> {
+    bootstrap_congestion_info_with_limit(
+        config,
+        state_update,
+        delayed_receipts_indices,
+        MAX_BOOTSTRAP_DELAYED_RECEIPTS,
+    )
+}
+
+pub(crate) fn bootstrap_congestion_info_with_limit(
+    config: &RuntimeConfig,
+    state_update: &TrieUpdate,
+    delayed_receipts_indices: &DelayedReceiptIndices,
+    max_receipts: u64,
+) -> Result<CongestionInfo, RuntimeError> {
+    let mut congestion_info = CongestionInfo::default();
+    let queue_len = delayed_receipts_indices.len();
+    let read_len = queue_len.min(max_receipts);
+    let first_index = delayed_receipts_indices.first_index;
+    for index in first_index..first_index + read_len {
+        let receipt: Receipt =
+            get(state_update, &TrieKey::DelayedReceipt { index })?.ok_or_else(|| {
+                StorageError::StorageInconsistentState(format!(
+                    "Delayed receipt #{} should be in the state",
+                    index
+                ))
+            })?;
+        add_delayed_receipt(config, &mut congestion_info, &receipt)?;
+    }
+    if read_len < queue_len {
+        let extrapolate = |value: u128| -> Result<u128, IntegerOverflowError> {
+            Ok(value.checked_mul(queue_len.into()).ok_or(IntegerOverflowError)?
+                / u128::from(read_len))
+        };
+        congestion_info.delayed_receipts_gas = extrapolate(congestion_info.delayed_receipts_gas)?;
+        congestion_info.delayed_receipts_bytes =
+            extrapolate(congestion_info.delayed_receipts_bytes.into())?
+                .try_into()
+                .map_err(|_| IntegerOverflowError)?;
+    }
+    Ok(congestion_info)
+}
+
+/// Tracks the gas of the receipts sent to the other shards while applying a
+/// chunk.
+pub(crate) struct OutgoingGasLimits<'a> {
+    own_shard_id: ShardId,
+    shard_layout: &'a ShardLayout,
+    runtime_config: &'a RuntimeConfig,
+    limits: HashMap<ShardId, Gas>,
+    sent: HashMap<ShardId, Gas>,
+}
+
+impl<'a> OutgoingGasLimits<'a> {
+    pub(crate) fn new(
+        congestion_control: &'a CongestionControl,
+        runtime_config: &'a RuntimeConfig,
+    ) -> Self {
+        let config: &CongestionControlConfig = &runtime_config.congestion_control_config;
+        let limits = congestion_control
+            .block_congestion_info
+            .iter()
+            .map(|(shard_id, congestion_info)| {
+                (*shard_id, congestion_info.outgoing_gas_limit(config))
+            })
+            .collect();
+        Self {
+            own_shard_id: congestion_control.shard_id,
+            shard_layout: &congestion_control.shard_layout,
+            runtime_config,
+            limits,
+            sent: HashMap::new(),
+        }
+    }
+
+    /// Accounts the receipts sent to the other shards. Returns whether the
+    /// limit of any of the receiving shards is reached.
+    pub(crate) fn add_receipts(&mut self, receipts: &[Receipt]) -> Result<bool, RuntimeError> {
+        let mut limit_reached = false;
+        for receipt in receipts {
+            let shard_id = account_id_to_shard_id(&receipt.receiver_id, self.shard_layout);
+            if shard_id == self.own_shard_id {
+                continue;
+            }
+            let sent = self.sent.entry(shard_id).or_default();
+            *sent = safe_add_gas(*sent, receipt_congestion_gas(self.runtime_config, receipt)?)?;
+            // Shards without the congestion info are not limited.
+            if self.limits.get(&shard_id).is_some_and(|limit| *sent >= *limit) {
+                limit_reached = true;
+            }
+        }
+        Ok(limit_reached)
+    }
+}
@@ -8,7 +8,10 @@ use unc_primitives::types::{
 use unc_primitives::utils::create_data_id;
 use unc_primitives::version::ProtocolVersion;
 use unc_primitives_core::types::Power;
-use unc_store::{get_code, KeyLookupMode, TrieUpdate, TrieUpdateValuePtr};
+use unc_store::{
+    get_code, get_global_contract_code, has_promise_yield_receipt, remove_promise_yield_receipt,
+    KeyLookupMode, TrieUpdate, TrieUpdateValuePtr,
+};
 use unc_vm_runner::logic::errors::{AnyError, VMLogicError};
 use unc_vm_runner::logic::types::ReceiptIndex;
 use unc_vm_runner::logic::{External, StorageGetMode, ValuePtr};
@@ -86,8 +89,14 @@ impl<'a> RuntimeExt<'a> {
         self.account_id
     }
 
+    /// Returns the code deployed on the account, falling back to the global contract registry
+    /// for accounts that were pointed at a global contract with `UseGlobalContract`.
     pub fn get_code(&self, code_hash: CryptoHash) -> Result<Option<ContractCode>, StorageError> {
-        get_code(self.trie_update, self.account_id, Some(code_hash))
+        let code = get_code(self.trie_update, self.account_id, Some(code_hash))?;
+        if code.is_some() || code_hash == CryptoHash::default() {
+            return Ok(code);
+        }
+        get_global_contract_code(self.trie_update, code_hash)
     }
 
     pub fn create_storage_key(&self, key: &[u8]) -> TrieKey {
@@ -225,6 +234,35 @@ impl<'a> External for RuntimeExt<'a> {
         self.receipt_manager.create_receipt(data_ids, receipt_indices, receiver_id)
     }
 
+    fn create_promise_yield_receipt(
+        &mut self,
+        receiver_id: AccountId,
+    ) -> Result<(ReceiptIndex, CryptoHash), VMLogicError> {
+        let data_id = self.generate_data_id();
+        let receipt_index = self.receipt_manager.create_promise_yield_receipt(data_id, receiver_id);
+        Ok((receipt_index, data_id))
+    }
+
+    fn submit_promise_resume_data(
+        &mut self,
+        data_id: CryptoHash,
+        data: Vec<u8>,
+    ) -> Result<bool, VMLogicError> {
+        // A yield created earlier in this same execution is not committed to the state yet.
+        if let Some(pos) = self.receipt_manager.promise_yields.iter().position(|id| *id == data_id)
+        {
+            self.receipt_manager.promise_yields.remove(pos);
+        } else if has_promise_yield_receipt(self.trie_update, self.account_id, data_id)
+            .map_err(wrap_storage_error)?
+        {
+            remove_promise_yield_receipt(self.trie_update, self.account_id, data_id);
+        } else {
+            return Ok(false);
+        }
+        self.receipt_manager.promise_resumes.push((data_id, data));
+        Ok(true)
+    }
+
     fn append_action_create_account(
         &mut self,
         receipt_index: ReceiptIndex,
@@ -1,24 +1,30 @@
 use crate::actions::*;
 use crate::balance_checker::check_balance;
 use crate::config::{
-    exec_fee, safe_add_balance, safe_add_compute, safe_add_gas, safe_gas_to_balance, total_deposit,
-    total_prepaid_exec_fees, total_prepaid_gas,
+    exec_fee, global_contract_storage_exec_fee, safe_add_balance, safe_add_compute, safe_add_gas,
+    safe_gas_to_balance, total_deposit, total_prepaid_exec_fees, total_prepaid_gas,
+};
+use crate::congestion_control::{
+    add_delayed_receipt, bootstrap_congestion_info, remove_delayed_receipt, OutgoingGasLimits,
 };
 use crate::prefetch::TriePrefetcher;
 use crate::verifier::{check_storage_pledge, validate_receipt, StorageStakingError};
 pub use crate::verifier::{
-    validate_transaction, verify_and_charge_transaction, ZERO_BALANCE_ACCOUNT_STORAGE_LIMIT,
+    validate_receiver_congestion, validate_transaction, verify_and_charge_transaction,
+    ZERO_BALANCE_ACCOUNT_STORAGE_LIMIT,
 };
 use config::total_prepaid_send_fees;
 pub use unc_crypto;
-use unc_parameters::{ActionCosts, RuntimeConfig};
+use unc_parameters::{ActionCosts, ExtCosts, RuntimeConfig};
 pub use unc_primitives;
 use unc_primitives::account::Account;
 use unc_primitives::checked_feature;
+use unc_primitives::congestion_info::CongestionInfo;
 use unc_primitives::errors::{ActionError, ActionErrorKind, RuntimeError, TxExecutionError};
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::receipt::{
-    ActionReceipt, DataReceipt, DelayedReceiptIndices, Receipt, ReceiptEnum, ReceivedData,
+    ActionReceipt, DataReceipt, DelayedReceiptIndices, GlobalContractDistributionReceipt,
+    PromiseYieldTimeout, Receipt, ReceiptEnum, ReceivedData,
 };
 pub use unc_primitives::runtime::apply_state::ApplyState;
 use unc_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
@@ -34,20 +40,23 @@ use unc_primitives::types::{
     EpochInfoProvider, Gas, Power, RawStateChangesWithTrieKey, StateChangeCause, StateRoot,
 };
 
+use std::cell::Cell;
 use std::cmp::max;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tracing::debug;
 use unc_primitives::utils::{
-    create_action_hash, create_receipt_id_from_receipt, create_receipt_id_from_transaction,
+    create_action_hash, create_receipt_id_from_data_id, create_receipt_id_from_receipt,
+    create_receipt_id_from_transaction,
 };
 use unc_primitives::version::{ProtocolFeature, ProtocolVersion};
 use unc_store::{
-    get, get_account, get_postponed_receipt, get_received_data, remove_postponed_receipt, set,
-    set_account, set_delayed_receipt, set_postponed_receipt, set_received_data, PartialStorage,
-    StorageError, Trie, TrieChanges, TrieUpdate,
+    get, get_account, get_postponed_receipt, get_promise_yield_indices, get_received_data,
+    has_promise_yield_receipt, remove_postponed_receipt, remove_promise_yield_receipt, set,
+    set_account, set_delayed_receipt, set_postponed_receipt, set_promise_yield_indices,
+    set_received_data, PartialStorage, StorageError, Trie, TrieChanges, TrieUpdate,
 };
-use unc_store::{set_access_key, set_code};
+use unc_store::{set_access_key, set_code, set_rsa2048_keys};
 use unc_vm_runner::logic::types::PromiseResult;
 use unc_vm_runner::logic::ReturnData;
 pub use unc_vm_runner::with_ext_cost_counter;
@@ -58,6 +67,7 @@ mod actions;
 pub mod adapter;
 mod balance_checker;
 pub mod config;
+mod congestion_control;
 pub mod ext;
 mod metrics;
 mod prefetch;
@@ -122,6 +132,9 @@ pub struct ApplyResult {
     pub proof: Option<PartialStorage>,
     pub delayed_receipts_count: u64,
     pub metrics: Option<metrics::ApplyMetrics>,
+    /// Congestion of the shard after applying the chunk, `None` if the
+    /// congestion control is not enabled.
+    pub congestion_info: Option<CongestionInfo>,
 }
 
 #[derive(Debug)]
@@ -473,6 +486,25 @@ impl Runtime {
                     create_rsa2048_challenge,
                 )?;
             }
+            Action::DeployGlobalContract(deploy_global_contract) => {
+                action_deploy_global_contract(
+                    state_update,
+                    account.as_mut().expect(EXPECT_ACCOUNT_EXISTS),
+                    account_id,
+                    deploy_global_contract,
+                    apply_state,
+                    &mut result,
+                )?;
+            }
+            Action::UseGlobalContract(use_global_contract) => {
+                action_use_global_contract(
+                    state_update,
+                    account.as_mut().expect(EXPECT_ACCOUNT_EXISTS),
+                    account_id,
+                    use_global_contract,
+                    &mut result,
+                )?;
+            }
         };
         Ok(result)
     }
@@ -784,6 +816,43 @@ impl Runtime {
         })
     }
 
+    /// Stores the distributed global contract on this shard. The gas of storing it was prepaid by
+    /// the publisher, it is burnt here and counted towards the gas limit of the chunk.
+    fn apply_global_contract_distribution_receipt(
+        &self,
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        receipt: &Receipt,
+        distribution_receipt: &GlobalContractDistributionReceipt,
+        stats: &mut ApplyStats,
+    ) -> Result<ExecutionOutcomeWithId, RuntimeError> {
+        apply_global_contract_distribution(state_update, distribution_receipt, apply_state)?;
+        state_update
+            .commit(StateChangeCause::ReceiptProcessing { receipt_hash: receipt.get_hash() });
+
+        let gas_burnt = global_contract_storage_exec_fee(
+            &apply_state.config,
+            distribution_receipt.code.len() as u64,
+        );
+        let tokens_burnt = safe_gas_to_balance(distribution_receipt.gas_price, gas_burnt)?;
+        stats.tx_burnt_amount = safe_add_balance(stats.tx_burnt_amount, tokens_burnt)?;
+
+        Ok(ExecutionOutcomeWithId {
+            id: receipt.receipt_id,
+            outcome: ExecutionOutcome {
+                status: ExecutionStatus::SuccessValue(vec![]),
+                logs: vec![],
+                receipt_ids: vec![],
+                gas_burnt,
+                // TODO(#8806): Support compute costs for actions. For now they match burnt gas.
+                compute_usage: Some(gas_burnt),
+                tokens_burnt,
+                executor_id: receipt.receiver_id.clone(),
+                metadata: ExecutionMetadata::V1,
+            },
+        })
+    }
+
     fn generate_refund_receipts(
         &self,
         current_gas_price: Balance,
@@ -1005,6 +1074,17 @@ impl Runtime {
                     set_postponed_receipt(state_update, receipt);
                 }
             }
+            ReceiptEnum::GlobalContractDistribution(ref distribution_receipt) => {
+                return self
+                    .apply_global_contract_distribution_receipt(
+                        state_update,
+                        apply_state,
+                        receipt,
+                        distribution_receipt,
+                        stats,
+                    )
+                    .map(Some);
+            }
         };
         // We didn't trigger execution, so we need to commit the state.
         state_update
@@ -1301,6 +1381,7 @@ impl Runtime {
                 proof,
                 delayed_receipts_count: delayed_receipts_indices.len(),
                 metrics: None,
+                congestion_info: None,
             });
         }
 
@@ -1317,6 +1398,25 @@ impl Runtime {
         let mut total_compute_usage = total_gas_burnt;
         let mut metrics = metrics::ApplyMetrics::default();
 
+        let mut own_congestion_info = match &apply_state.congestion_control {
+            Some(congestion_control) => Some(
+                match congestion_control.block_congestion_info.get(congestion_control.shard_id) {
+                    Some(congestion_info) => *congestion_info,
+                    None => bootstrap_congestion_info(
+                        &apply_state.config,
+                        &state_update,
+                        &delayed_receipts_indices,
+                    )?,
+                },
+            ),
+            None => None,
+        };
+        let mut outgoing_gas_limits =
+            apply_state.congestion_control.as_ref().map(|congestion_control| {
+                OutgoingGasLimits::new(congestion_control, &apply_state.config)
+            });
+        let outgoing_limit_reached = Cell::new(false);
+
         for signed_transaction in transactions {
             let (receipt, outcome_with_id) = self.process_transaction(
                 &mut state_update,
@@ -1348,6 +1448,9 @@ impl Runtime {
 
             outcomes.push(outcome_with_id);
         }
+        if let Some(outgoing_gas_limits) = &mut outgoing_gas_limits {
+            outgoing_limit_reached.set(outgoing_gas_limits.add_receipts(&outgoing_receipts)?);
+        }
         metrics.tx_processing_done(total_gas_burnt, total_compute_usage);
 
         let mut process_receipt = |receipt: &Receipt,
@@ -1365,6 +1468,7 @@ impl Runtime {
             )
             .entered();
             let node_counter_before = state_update.trie().get_trie_nodes_count();
+            let num_outgoing_receipts_before = outgoing_receipts.len();
             let result = self.process_receipt(
                 state_update,
                 apply_state,
@@ -1378,6 +1482,14 @@ impl Runtime {
             let node_counter_after = state_update.trie().get_trie_nodes_count();
             tracing::trace!(target: "runtime", ?node_counter_before, ?node_counter_after);
 
+            if let Some(outgoing_gas_limits) = &mut outgoing_gas_limits {
+                if outgoing_gas_limits
+                    .add_receipts(&outgoing_receipts[num_outgoing_receipts_before..])?
+                {
+                    outgoing_limit_reached.set(true);
+                }
+            }
+
             if let Some(outcome_with_id) = result? {
                 *total_gas_burnt =
                     safe_add_gas(*total_gas_burnt, outcome_with_id.outcome.gas_burnt)?;
@@ -1411,7 +1523,7 @@ impl Runtime {
             _ = prefetcher.prefetch_receipts_data(&local_receipts);
         }
         for receipt in local_receipts.iter() {
-            if total_compute_usage < compute_limit {
+            if total_compute_usage < compute_limit && !outgoing_limit_reached.get() {
                 // NOTE: We don't need to validate the local receipt, because it's just validated in
                 // the `verify_and_charge_transaction`.
                 process_receipt(
@@ -1422,13 +1534,16 @@ impl Runtime {
                 )?;
             } else {
                 set_delayed_receipt(&mut state_update, &mut delayed_receipts_indices, receipt);
+                if let Some(congestion_info) = &mut own_congestion_info {
+                    add_delayed_receipt(&apply_state.config, congestion_info, receipt)?;
+                }
             }
         }
         metrics.local_receipts_done(total_gas_burnt, total_compute_usage);
 
         // Then we process the delayed receipts. It's a backlog of receipts from the past blocks.
         while delayed_receipts_indices.first_index < delayed_receipts_indices.next_available_index {
-            if total_compute_usage >= compute_limit {
+            if total_compute_usage >= compute_limit || outgoing_limit_reached.get() {
                 break;
             }
             let key = TrieKey::DelayedReceipt { index: delayed_receipts_indices.first_index };
@@ -1461,6 +1576,9 @@ impl Runtime {
             state_update.remove(key);
             // Math checked above: first_index is less than next_available_index
             delayed_receipts_indices.first_index += 1;
+            if let Some(congestion_info) = &mut own_congestion_info {
+                remove_delayed_receipt(&apply_state.config, congestion_info, &receipt)?;
+            }
             process_receipt(
                 &receipt,
                 &mut state_update,
@@ -1486,7 +1604,7 @@ impl Runtime {
                 apply_state.current_protocol_version,
             )
             .map_err(RuntimeError::ReceiptValidationError)?;
-            if total_compute_usage < compute_limit {
+            if total_compute_usage < compute_limit && !outgoing_limit_reached.get() {
                 process_receipt(
                     receipt,
                     &mut state_update,
@@ -1495,6 +1613,9 @@ impl Runtime {
                 )?;
             } else {
                 set_delayed_receipt(&mut state_update, &mut delayed_receipts_indices, receipt);
+                if let Some(congestion_info) = &mut own_congestion_info {
+                    add_delayed_receipt(&apply_state.config, congestion_info, receipt)?;
+                }
             }
         }
         metrics.incoming_receipts_done(total_gas_burnt, total_compute_usage);
@@ -1504,6 +1625,26 @@ impl Runtime {
             prefetcher.clear();
         }
 
+        // Resolve the yields which timed out with a data receipt without payload, which the
+        // yielded receipt observes as `PromiseResult::Failed`.
+        if checked_feature!("stable", YieldExecution, apply_state.current_protocol_version) {
+            self.resolve_promise_yield_timeouts(
+                &mut state_update,
+                apply_state,
+                &mut outgoing_receipts,
+                &mut total_gas_burnt,
+                &mut total_compute_usage,
+                compute_limit,
+            )?;
+        }
+
+        if delayed_receipts_indices.first_index == delayed_receipts_indices.next_available_index {
+            // A bootstrapped congestion info may be an estimate, it is exact
+            // again once the delayed receipts queue is drained.
+            if let Some(congestion_info) = &mut own_congestion_info {
+                *congestion_info = CongestionInfo::default();
+            }
+        }
         if delayed_receipts_indices != initial_delayed_receipt_indices {
             set(&mut state_update, TrieKey::DelayedReceiptIndices, &delayed_receipts_indices);
         }
@@ -1558,9 +1699,90 @@ impl Runtime {
             proof,
             delayed_receipts_count: delayed_receipts_indices.len(),
             metrics: Some(metrics),
+            congestion_info: own_congestion_info,
         })
     }
 
+    /// Processes the yielded receipts timeout queue, sending a data receipt with no payload to
+    /// every yield that expired at or before the current block and was not resumed yet.
+    ///
+    /// Every processed entry costs `yield_resume_base`, the same as resuming the yield explicitly.
+    /// The gas is prepaid by `promise_yield_create`, here it is only counted towards the chunk.
+    /// Processing stops once the chunk compute limit is reached; the remaining expired entries are
+    /// resolved in the following chunks.
+    fn resolve_promise_yield_timeouts(
+        &self,
+        state_update: &mut TrieUpdate,
+        apply_state: &ApplyState,
+        outgoing_receipts: &mut Vec<Receipt>,
+        total_gas_burnt: &mut Gas,
+        total_compute_usage: &mut Compute,
+        compute_limit: Compute,
+    ) -> Result<(), RuntimeError> {
+        let ext_costs = &apply_state.config.wasm_config.ext_costs;
+        let timeout_gas_cost = ext_costs.gas_cost(ExtCosts::yield_resume_base);
+        let timeout_compute_cost = ext_costs.compute_cost(ExtCosts::yield_resume_base);
+        let mut promise_yield_indices = get_promise_yield_indices(state_update)?;
+        let initial_promise_yield_indices = promise_yield_indices.clone();
+        let mut timeout_receipts_count = 0;
+        while promise_yield_indices.first_index < promise_yield_indices.next_available_index {
+            if *total_compute_usage >= compute_limit {
+                break;
+            }
+            let key = TrieKey::PromiseYieldTimeout { index: promise_yield_indices.first_index };
+            let queue_entry: PromiseYieldTimeout = get(state_update, &key)?.ok_or_else(|| {
+                StorageError::StorageInconsistentState(format!(
+                    "Yield timeout #{} should be in the state",
+                    promise_yield_indices.first_index
+                ))
+            })?;
+            // Entries are enqueued in order of block height, so the rest are not expired either.
+            if queue_entry.expires_at > apply_state.block_height {
+                break;
+            }
+
+            // The yield may have been resumed already, in which case there is nothing to do.
+            if has_promise_yield_receipt(
+                state_update,
+                &queue_entry.account_id,
+                queue_entry.data_id,
+            )? {
+                remove_promise_yield_receipt(
+                    state_update,
+                    &queue_entry.account_id,
+                    queue_entry.data_id,
+                );
+                let receipt_id = create_receipt_id_from_data_id(
+                    apply_state.current_protocol_version,
+                    &queue_entry.data_id,
+                    &apply_state.prev_block_hash,
+                    &apply_state.block_hash,
+                    timeout_receipts_count,
+                );
+                timeout_receipts_count += 1;
+                outgoing_receipts.push(Receipt {
+                    predecessor_id: queue_entry.account_id.clone(),
+                    receiver_id: queue_entry.account_id,
+                    receipt_id,
+                    receipt: ReceiptEnum::Data(DataReceipt {
+                        data_id: queue_entry.data_id,
+                        data: None,
+                    }),
+                });
+            }
+
+            state_update.remove(key);
+            // Math checked above: first_index is less than next_available_index
+            promise_yield_indices.first_index += 1;
+            *total_gas_burnt = safe_add_gas(*total_gas_burnt, timeout_gas_cost)?;
+            *total_compute_usage = safe_add_compute(*total_compute_usage, timeout_compute_cost)?;
+        }
+        if promise_yield_indices != initial_promise_yield_indices {
+            set_promise_yield_indices(state_update, &promise_yield_indices);
+        }
+        Ok(())
+    }
+
     fn apply_state_patch(&self, state_update: &mut TrieUpdate, state_patch: SandboxStatePatch) {
         if state_patch.is_empty() {
             return;
@@ -1583,7 +1805,10 @@ impl Runtime {
                 StateRecord::AccessKey { account_id, public_key, access_key } => {
                     set_access_key(state_update, account_id, public_key, &access_key);
                 }
-                _ => unimplemented!("patch_state can only patch Account, AccessKey, Contract and Data kind of StateRecord")
+                StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                    set_rsa2048_keys(state_update, account_id, public_key, &rsa_key);
+                }
+                _ => unimplemented!("patch_state can only patch Account, AccessKey, Rsa2048Keys, Contract and Data kind of StateRecord")
             }
         }
         state_update.commit(StateChangeCause::Migration);
@@ -1592,16 +1817,19 @@ impl Runtime {
 
 #[cfg(test)]
 mod tests {
+    use crate::congestion_control::{bootstrap_congestion_info_with_limit, receipt_congestion_gas};
     use assert_matches::assert_matches;
     use testlib::runtime_utils::{alice_account, bob_account};
     use unc_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
     use unc_parameters::{ExtCosts, ParameterCost, RuntimeConfig};
     use unc_primitives::account::AccessKey;
+    use unc_primitives::congestion_info::{BlockCongestionInfo, CongestionControl};
     use unc_primitives::hash::hash;
-    use unc_primitives::shard_layout::ShardUId;
+    use unc_primitives::shard_layout::{account_id_to_shard_id, ShardLayout, ShardUId};
     use unc_primitives::test_utils::{account_new, MockEpochInfoProvider};
     use unc_primitives::transaction::{
-        AddKeyAction, DeleteKeyAction, DeployContractAction, FunctionCallAction, TransferAction,
+        AddKeyAction, DeleteKeyAction, DeployContractAction, DeployGlobalContractAction,
+        FunctionCallAction, TransferAction, UseGlobalContractAction,
     };
     use unc_primitives::types::MerkleHash;
     use unc_primitives::version::PROTOCOL_VERSION;
@@ -1726,6 +1954,7 @@ mod tests {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_control: None,
         };
 
         (runtime, tries, root, apply_state, signer, MockEpochInfoProvider::default())
@@ -1925,6 +2154,120 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_apply_delayed_receipts_congestion_info() {
+        let initial_balance = to_atto(1_000_000);
+        let initial_locked = to_atto(500_000);
+        let initial_power = to_tera(5);
+        let small_transfer = to_atto(10_000);
+        let (runtime, tries, mut root, mut apply_state, _, epoch_info_provider) =
+            setup_runtime(initial_balance, initial_locked, initial_power, 1);
+        let shard_layout = ShardLayout::v0_single_shard();
+
+        let n = 10;
+        let receipts = generate_receipts(small_transfer, n);
+
+        // The first chunk has no congestion info in the header, so it is bootstrapped from the
+        // state. Then the info is passed from one chunk to the next one.
+        let mut congestion_info = None;
+        for i in 1..=n + 1 {
+            apply_state.congestion_control = Some(CongestionControl {
+                shard_id: 0,
+                shard_layout: shard_layout.clone(),
+                block_congestion_info: BlockCongestionInfo::new(
+                    congestion_info.map(|info| (0, info)).into_iter().collect(),
+                ),
+            });
+            let prev_receipts: &[Receipt] = if i == 1 { &receipts } else { &[] };
+            let apply_result = runtime
+                .apply(
+                    tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                    &None,
+                    &apply_state,
+                    prev_receipts,
+                    &[],
+                    &epoch_info_provider,
+                    Default::default(),
+                )
+                .unwrap();
+            let mut store_update = tries.store_update();
+            root = tries.apply_all(
+                &apply_result.trie_changes,
+                ShardUId::single_shard(),
+                &mut store_update,
+            );
+            store_update.commit().unwrap();
+
+            let state = tries.new_trie_update(ShardUId::single_shard(), root);
+            let delayed_receipts_indices: DelayedReceiptIndices =
+                get(&state, &TrieKey::DelayedReceiptIndices).unwrap().unwrap_or_default();
+            let expected =
+                bootstrap_congestion_info(&apply_state.config, &state, &delayed_receipts_indices)
+                    .unwrap();
+            assert_eq!(apply_result.congestion_info, Some(expected));
+            assert_eq!(expected.delayed_receipts_bytes == 0, i >= n);
+            congestion_info = apply_result.congestion_info;
+        }
+        assert_eq!(congestion_info, Some(CongestionInfo::default()));
+    }
+
+    #[test]
+    fn test_bootstrap_congestion_info_with_limit() {
+        let initial_balance = to_atto(1_000_000);
+        let initial_locked = to_atto(500_000);
+        let initial_power = to_tera(5);
+        let small_transfer = to_atto(10_000);
+        let (runtime, tries, root, apply_state, _, epoch_info_provider) =
+            setup_runtime(initial_balance, initial_locked, initial_power, 1);
+
+        let n = 10;
+        let receipts = generate_receipts(small_transfer, n);
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                &None,
+                &apply_state,
+                &receipts,
+                &[],
+                &epoch_info_provider,
+                Default::default(),
+            )
+            .unwrap();
+        let mut store_update = tries.store_update();
+        let root = tries.apply_all(
+            &apply_result.trie_changes,
+            ShardUId::single_shard(),
+            &mut store_update,
+        );
+        store_update.commit().unwrap();
+
+        let state = tries.new_trie_update(ShardUId::single_shard(), root);
+        let delayed_receipts_indices: DelayedReceiptIndices =
+            get(&state, &TrieKey::DelayedReceiptIndices).unwrap().unwrap();
+        assert!(delayed_receipts_indices.len() > 2);
+        let exact =
+            bootstrap_congestion_info(&apply_state.config, &state, &delayed_receipts_indices)
+                .unwrap();
+        // The delayed receipts are all of the same gas and size, so extrapolating from the first
+        // two of them gives the exact value.
+        let estimate = bootstrap_congestion_info_with_limit(
+            &apply_state.config,
+            &state,
+            &delayed_receipts_indices,
+            2,
+        )
+        .unwrap();
+        assert_eq!(estimate, exact);
+        let empty = bootstrap_congestion_info_with_limit(
+            &apply_state.config,
+            &state,
+            &DelayedReceiptIndices::default(),
+            2,
+        )
+        .unwrap();
+        assert_eq!(empty, CongestionInfo::default());
+    }
+
     #[test]
     fn test_apply_delayed_receipts_adjustable_gas_limit() {
         let initial_balance = to_atto(1_000_000);
@@ -2702,6 +3045,129 @@ mod tests {
             assert_matches!(second.outcome.status, ExecutionStatus::Failure(_));
         });
     }
+
+    #[test]
+    fn test_global_contract_used_on_other_shard() {
+        let (runtime, tries, root, mut apply_state, alice_signer, epoch_info_provider) =
+            setup_runtime(to_atto(1_000_000), to_atto(500_000), to_tera(5), 10u64.pow(15));
+        // "alice" lives on shard 0 and "bob" on shard 1.
+        let shard_layout = ShardLayout::v1(vec!["bb".parse().unwrap()], None, 1);
+        let code = unc_test_contracts::rs_contract().to_vec();
+        let code_hash = hash(&code);
+        let storage_gas = global_contract_storage_exec_fee(&apply_state.config, code.len() as u64);
+        let call_sha256 = |args: &[u8]| {
+            Action::FunctionCall(Box::new(FunctionCallAction {
+                method_name: "ext_sha256".to_string(),
+                args: args.to_vec(),
+                gas: 10u64.pow(13),
+                deposit: 0,
+            }))
+        };
+
+        // Alice publishes the code and calls it on her own shard.
+        apply_state.congestion_control = Some(CongestionControl {
+            shard_id: 0,
+            shard_layout: shard_layout.clone(),
+            block_congestion_info: BlockCongestionInfo::default(),
+        });
+        let alice_receipts = vec![
+            create_receipt_with_actions(
+                alice_account(),
+                alice_signer.clone(),
+                vec![Action::DeployGlobalContract(DeployGlobalContractAction { code })],
+            ),
+            create_receipt_with_actions(
+                alice_account(),
+                alice_signer.clone(),
+                vec![Action::UseGlobalContract(UseGlobalContractAction { code_hash })],
+            ),
+            create_receipt_with_actions(alice_account(), alice_signer, vec![call_sha256(b"alice")]),
+        ];
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(ShardUId::single_shard(), root),
+                &None,
+                &apply_state,
+                &alice_receipts,
+                &[],
+                &epoch_info_provider,
+                Default::default(),
+            )
+            .unwrap();
+        assert_eq!(apply_result.outcomes.len(), 3);
+        for outcome_with_id in &apply_result.outcomes {
+            assert_matches!(outcome_with_id.outcome.status, ExecutionStatus::SuccessValue(_));
+        }
+        let distribution_receipts: Vec<_> = apply_result
+            .outgoing_receipts
+            .into_iter()
+            .filter(|receipt| matches!(receipt.receipt, ReceiptEnum::GlobalContractDistribution(_)))
+            .collect();
+        assert_matches!(&distribution_receipts[..], [distribution_receipt] => {
+            assert_eq!(account_id_to_shard_id(&distribution_receipt.receiver_id, &shard_layout), 1);
+            assert_eq!(
+                receipt_congestion_gas(&apply_state.config, distribution_receipt).unwrap(),
+                storage_gas
+            );
+        });
+
+        // Bob uses the distributed code on the other shard.
+        let bob_signer =
+            Arc::new(InMemorySigner::from_seed(bob_account(), KeyType::ED25519, "bob"));
+        let mut initial_state =
+            tries.new_trie_update(ShardUId::single_shard(), MerkleHash::default());
+        let mut bob = account_new(to_atto(1_000_000), hash(&[]));
+        bob.set_storage_usage(182);
+        set_account(&mut initial_state, bob_account(), &bob);
+        set_access_key(
+            &mut initial_state,
+            bob_account(),
+            bob_signer.public_key(),
+            &AccessKey::full_access(),
+        );
+        initial_state.commit(StateChangeCause::InitialState);
+        let trie_changes = initial_state.finalize().unwrap().1;
+        let mut store_update = tries.store_update();
+        let bob_root = tries.apply_all(&trie_changes, ShardUId::single_shard(), &mut store_update);
+        store_update.commit().unwrap();
+
+        apply_state.congestion_control = Some(CongestionControl {
+            shard_id: 1,
+            shard_layout,
+            block_congestion_info: BlockCongestionInfo::default(),
+        });
+        let use_receipt = create_receipt_with_actions(
+            bob_account(),
+            bob_signer.clone(),
+            vec![Action::UseGlobalContract(UseGlobalContractAction { code_hash })],
+        );
+        let call_receipt =
+            create_receipt_with_actions(bob_account(), bob_signer, vec![call_sha256(b"bob")]);
+        let bob_receipts =
+            [distribution_receipts[0].clone(), use_receipt.clone(), call_receipt.clone()];
+        let apply_result = runtime
+            .apply(
+                tries.get_trie_for_shard(ShardUId::single_shard(), bob_root),
+                &None,
+                &apply_state,
+                &bob_receipts,
+                &[],
+                &epoch_info_provider,
+                Default::default(),
+            )
+            .unwrap();
+        assert_matches!(&apply_result.outcomes[..], [distribution, use_code, call] => {
+            assert_eq!(distribution.id, distribution_receipts[0].receipt_id);
+            assert_eq!(distribution.outcome.gas_burnt, storage_gas);
+            assert_eq!(distribution.outcome.tokens_burnt, Balance::from(storage_gas) * GAS_PRICE);
+
+            assert_eq!(use_code.id, use_receipt.receipt_id);
+            assert_matches!(use_code.outcome.status, ExecutionStatus::SuccessValue(_));
+
+            assert_eq!(call.id, call_receipt.receipt_id);
+            assert_matches!(call.outcome.status, ExecutionStatus::SuccessValue(_));
+        });
+    }
 }
 
 /// Interface provided for gas cost estimations.
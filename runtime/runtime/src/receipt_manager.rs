@@ -36,6 +36,10 @@ pub struct ReceiptMetadata {
 pub struct ReceiptManager {
     pub(super) action_receipts: ActionReceipts,
     pub(super) gas_weights: Vec<(FunctionCallActionIndex, GasWeight)>,
+    /// Data ids of the yielded receipts created during this execution.
+    pub(super) promise_yields: Vec<CryptoHash>,
+    /// Payloads submitted to resume yielded receipts during this execution.
+    pub(super) promise_resumes: Vec<(CryptoHash, Vec<u8>)>,
 }
 
 /// Indexes the [`ReceiptManager`]'s action receipts and actions.
@@ -104,6 +108,29 @@ impl ReceiptManager {
         Ok(new_receipt_index)
     }
 
+    /// Create a receipt to `receiver_id` waiting on `data_id`, which is not produced by any
+    /// other receipt but is instead submitted when the yield is resumed or times out.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_id` - the data id to be submitted to resume the receipt
+    /// * `receiver_id` - account id of the receiver of the receipt created
+    pub(super) fn create_promise_yield_receipt(
+        &mut self,
+        data_id: CryptoHash,
+        receiver_id: AccountId,
+    ) -> ReceiptIndex {
+        let new_receipt = ReceiptMetadata {
+            output_data_receivers: vec![],
+            input_data_ids: vec![data_id],
+            actions: vec![],
+        };
+        let new_receipt_index = self.action_receipts.len() as ReceiptIndex;
+        self.action_receipts.push((receiver_id, new_receipt));
+        self.promise_yields.push(data_id);
+        new_receipt_index
+    }
+
     /// Attach the [`CreateAccountAction`] action to an existing receipt.
     ///
     /// # Arguments
@@ -355,7 +382,7 @@ impl ReceiptManager {
     ///
     /// Returns the amount of gas distributed (either `0` or `unused_gas`.)
     pub(super) fn distribute_gas(&mut self, unused_gas: Gas) -> Result<Gas, RuntimeError> {
-        let ReceiptManager { action_receipts, gas_weights } = self;
+        let ReceiptManager { action_receipts, gas_weights, .. } = self;
         let gas_weight_sum: u128 = gas_weights.iter().map(|(_, gv)| u128::from(gv.0)).sum();
         if gas_weight_sum == 0 || unused_gas == 0 {
             return Ok(0);
@@ -5,7 +5,7 @@ use crate::{actions::execute_function_call, ext::RuntimeExt};
 use std::{str, sync::Arc, time::Instant};
 use tracing::debug;
 use unc_crypto::{KeyType, PublicKey};
-use unc_parameters::RuntimeConfigStore;
+use unc_parameters::{RuntimeConfig, RuntimeConfigStore};
 use unc_primitives::account::{AccessKey, Account};
 use unc_primitives::borsh::BorshDeserialize;
 use unc_primitives::hash::CryptoHash;
@@ -17,9 +17,11 @@ use unc_primitives::trie_key::trie_key_parsers;
 use unc_primitives::types::{AccountId, EpochInfoProvider, Gas};
 use unc_primitives::views::{ChipView, StateItem, ViewApplyState, ViewStateResult};
 use unc_primitives_core::config::ViewConfig;
-use unc_store::{get_access_key, get_account, get_code, TrieUpdate};
+use unc_store::{get_access_key, get_account, get_code, get_global_contract_code, TrieUpdate};
 use unc_vm_runner::logic::ReturnData;
-use unc_vm_runner::ContractCode;
+use unc_vm_runner::{ContractCode, FunctionGasProfile};
+#[cfg(feature = "sandbox")]
+use {unc_primitives::views::DebugCallResult, unc_vm_runner::debugger::DebugOptions};
 
 pub mod errors;
 
@@ -64,10 +66,16 @@ impl TrieViewer {
         account_id: &AccountId,
     ) -> Result<ContractCode, errors::ViewContractCodeError> {
         let account = self.view_account(state_update, account_id)?;
-        get_code(state_update, account_id, Some(account.code_hash()))?.ok_or_else(|| {
-            errors::ViewContractCodeError::NoContractCode {
-                contract_account_id: account_id.clone(),
+        let code_hash = account.code_hash();
+        let code = match get_code(state_update, account_id, Some(code_hash))? {
+            Some(code) => Some(code),
+            None if code_hash != CryptoHash::default() => {
+                get_global_contract_code(state_update, code_hash)?
             }
+            None => None,
+        };
+        code.ok_or_else(|| errors::ViewContractCodeError::NoContractCode {
+            contract_account_id: account_id.clone(),
         })
     }
 
@@ -267,6 +275,10 @@ impl TrieViewer {
         Ok(ViewStateResult { values, proof })
     }
 
+    /// Execute the view method `method_name` of the contract `contract_id`.
+    ///
+    /// The logs of the call are appended to `logs`. When `function_profile` is given, the gas burnt
+    /// by each contract function is collected into it, or `None` if this node cannot profile.
     pub fn call_function(
         &self,
         mut state_update: TrieUpdate,
@@ -275,6 +287,7 @@ impl TrieViewer {
         method_name: &str,
         args: &[u8],
         logs: &mut Vec<String>,
+        function_profile: Option<&mut Option<FunctionGasProfile>>,
         epoch_info_provider: &dyn EpochInfoProvider,
     ) -> Result<Vec<u8>, errors::CallFunctionError> {
         let now = Instant::now();
@@ -286,55 +299,34 @@ impl TrieViewer {
         })?;
         // TODO(#1015): Add ability to pass public key and originator_id
         let originator_id = contract_id;
-        let public_key = PublicKey::empty(KeyType::ED25519);
         let empty_hash = CryptoHash::default();
+        let config_store = RuntimeConfigStore::new(None);
+        let config = config_store.get_config(PROTOCOL_VERSION);
+        let apply_state = view_apply_state(view_state, root, config);
         let mut receipt_manager = ReceiptManager::default();
         let mut runtime_ext = RuntimeExt::new(
             &mut state_update,
             &mut receipt_manager,
             contract_id,
             &empty_hash,
-            &view_state.epoch_id,
-            &view_state.prev_block_hash,
-            &view_state.block_hash,
+            &apply_state.epoch_id,
+            &apply_state.prev_block_hash,
+            &apply_state.block_hash,
             epoch_info_provider,
-            view_state.current_protocol_version,
+            apply_state.current_protocol_version,
         );
-        let config_store = RuntimeConfigStore::new(None);
-        let config = config_store.get_config(PROTOCOL_VERSION);
-        let apply_state = ApplyState {
-            block_height: view_state.block_height,
-            // Used for legacy reasons
-            prev_block_hash: view_state.prev_block_hash,
-            block_hash: view_state.block_hash,
-            epoch_id: view_state.epoch_id.clone(),
-            epoch_height: view_state.epoch_height,
-            gas_price: 0,
-            block_timestamp: view_state.block_timestamp,
-            gas_limit: None,
-            random_seed: root,
-            current_protocol_version: view_state.current_protocol_version,
-            config: config.clone(),
-            cache: view_state.cache,
-            is_new_chunk: false,
-            migration_data: Arc::new(MigrationData::default()),
-            migration_flags: MigrationFlags::default(),
-        };
-        let action_receipt = ActionReceipt {
-            signer_id: originator_id.clone(),
-            signer_public_key: public_key,
-            gas_price: 0,
-            output_data_receivers: vec![],
-            input_data_ids: vec![],
-            actions: vec![],
-        };
+        let action_receipt = view_action_receipt(originator_id);
         let function_call = FunctionCallAction {
             method_name: method_name.to_string(),
             args: args.to_vec(),
             gas: self.max_gas_burnt_view,
             deposit: 0,
         };
-        let outcome = execute_function_call(
+        let view_config = ViewConfig {
+            max_gas_burnt: self.max_gas_burnt_view,
+            profile_functions: function_profile.is_some(),
+        };
+        let mut outcome = execute_function_call(
             &apply_state,
             &mut runtime_ext,
             &mut account,
@@ -345,9 +337,12 @@ impl TrieViewer {
             &empty_hash,
             config,
             true,
-            Some(ViewConfig { max_gas_burnt: self.max_gas_burnt_view }),
+            Some(view_config),
         )
         .map_err(|e| errors::CallFunctionError::InternalError { error_message: e.to_string() })?;
+        if let Some(function_profile) = function_profile {
+            *function_profile = outcome.function_profile.take();
+        }
         let elapsed = now.elapsed();
         let time_ms =
             (elapsed.as_secs() as f64 / 1_000.0) + f64::from(elapsed.subsec_nanos()) / 1_000_000.0;
@@ -368,6 +363,112 @@ impl TrieViewer {
             Ok(result)
         }
     }
+
+    /// Run the method `method_name` of the contract `contract_id` with the debugger.
+    ///
+    /// Unlike [`Self::call_function`], the call may modify the state and a failed execution is
+    /// still reported as a result, as its trace is what is being asked for. All changes remain in
+    /// `state_update`, which the caller is expected to discard.
+    #[cfg(feature = "sandbox")]
+    pub fn debug_function_call(
+        &self,
+        mut state_update: TrieUpdate,
+        view_state: ViewApplyState,
+        contract_id: &AccountId,
+        method_name: &str,
+        args: &[u8],
+        options: DebugOptions,
+        epoch_info_provider: &dyn EpochInfoProvider,
+    ) -> Result<DebugCallResult, errors::CallFunctionError> {
+        let root = *state_update.get_root();
+        let account = get_account(&state_update, contract_id)?.ok_or_else(|| {
+            errors::CallFunctionError::AccountDoesNotExist {
+                requested_account_id: contract_id.clone(),
+            }
+        })?;
+        let empty_hash = CryptoHash::default();
+        let config_store = RuntimeConfigStore::new(None);
+        let config = config_store.get_config(PROTOCOL_VERSION);
+        let apply_state = view_apply_state(view_state, root, config);
+        let mut receipt_manager = ReceiptManager::default();
+        let mut runtime_ext = RuntimeExt::new(
+            &mut state_update,
+            &mut receipt_manager,
+            contract_id,
+            &empty_hash,
+            &apply_state.epoch_id,
+            &apply_state.prev_block_hash,
+            &apply_state.block_hash,
+            epoch_info_provider,
+            apply_state.current_protocol_version,
+        );
+        let function_call = FunctionCallAction {
+            method_name: method_name.to_string(),
+            args: args.to_vec(),
+            gas: self.max_gas_burnt_view,
+            deposit: 0,
+        };
+        let outcome = crate::actions::execute_debug_function_call(
+            &apply_state,
+            &mut runtime_ext,
+            &account,
+            &view_action_receipt(contract_id),
+            &function_call,
+            config,
+            options,
+        )
+        .map_err(|e| errors::CallFunctionError::InternalError { error_message: e.to_string() })?;
+        let result = match outcome.return_data {
+            ReturnData::Value(buf) => buf,
+            ReturnData::ReceiptIndex(_) | ReturnData::None => vec![],
+        };
+        Ok(DebugCallResult {
+            result,
+            logs: outcome.logs,
+            burnt_gas: outcome.burnt_gas,
+            error: outcome
+                .aborted
+                .map(|err| format!("wasm execution failed with error: {:?}", err)),
+            trace: outcome.debug_trace.unwrap_or_default(),
+        })
+    }
+}
+
+fn view_apply_state(
+    view_state: ViewApplyState,
+    root: CryptoHash,
+    config: &Arc<RuntimeConfig>,
+) -> ApplyState {
+    ApplyState {
+        block_height: view_state.block_height,
+        // Used for legacy reasons
+        prev_block_hash: view_state.prev_block_hash,
+        block_hash: view_state.block_hash,
+        epoch_id: view_state.epoch_id,
+        epoch_height: view_state.epoch_height,
+        gas_price: 0,
+        block_timestamp: view_state.block_timestamp,
+        gas_limit: None,
+        random_seed: root,
+        current_protocol_version: view_state.current_protocol_version,
+        config: config.clone(),
+        cache: view_state.cache,
+        is_new_chunk: false,
+        migration_data: Arc::new(MigrationData::default()),
+        migration_flags: MigrationFlags::default(),
+        congestion_control: None,
+    }
+}
+
+fn view_action_receipt(originator_id: &AccountId) -> ActionReceipt {
+    ActionReceipt {
+        signer_id: originator_id.clone(),
+        signer_public_key: PublicKey::empty(KeyType::ED25519),
+        gas_price: 0,
+        output_data_receivers: vec![],
+        input_data_ids: vec![],
+        actions: vec![],
+    }
 }
 
 // Helper function to deserialize ChipView from binary format
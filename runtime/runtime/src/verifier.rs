@@ -6,17 +6,21 @@ use unc_parameters::RuntimeConfig;
 use unc_primitives::account::AccessKeyPermission;
 use unc_primitives::action::delegate::SignedDelegateAction;
 use unc_primitives::checked_feature;
+use unc_primitives::congestion_info::BlockCongestionInfo;
 use unc_primitives::errors::{
     ActionsValidationError, InvalidAccessKeyError, InvalidTxError, ReceiptValidationError,
     RuntimeError,
 };
-use unc_primitives::receipt::{ActionReceipt, DataReceipt, Receipt, ReceiptEnum};
+use unc_primitives::receipt::{
+    ActionReceipt, DataReceipt, GlobalContractDistributionReceipt, Receipt, ReceiptEnum,
+};
 use unc_primitives::transaction::DeleteAccountAction;
 use unc_primitives::transaction::{
-    Action, AddKeyAction, CreateRsa2048ChallengeAction, DeployContractAction, FunctionCallAction,
-    PledgeAction, RegisterRsa2048KeysAction, SignedTransaction,
+    Action, AddKeyAction, CreateRsa2048ChallengeAction, DeployContractAction,
+    DeployGlobalContractAction, FunctionCallAction, PledgeAction, RegisterRsa2048KeysAction,
+    SignedTransaction,
 };
-use unc_primitives::types::{AccountId, Balance};
+use unc_primitives::types::{AccountId, Balance, ShardId};
 use unc_primitives::types::{BlockHeight, StorageUsage};
 use unc_primitives::version::ProtocolFeature;
 use unc_primitives::version::ProtocolVersion;
@@ -81,6 +85,23 @@ fn is_zero_balance_account(account: &Account) -> bool {
     account.storage_usage() <= ZERO_BALANCE_ACCOUNT_STORAGE_LIMIT
 }
 
+/// Checks that the shard of the transaction receiver is not too congested to accept new
+/// transactions. Shards without the congestion info are not limited.
+pub fn validate_receiver_congestion(
+    config: &RuntimeConfig,
+    block_congestion_info: &BlockCongestionInfo,
+    receiver_shard_id: ShardId,
+) -> Result<(), InvalidTxError> {
+    match block_congestion_info.get(receiver_shard_id) {
+        Some(congestion_info)
+            if !congestion_info.shard_accepts_transactions(&config.congestion_control_config) =>
+        {
+            Err(InvalidTxError::ShardCongested { shard_id: receiver_shard_id })
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Validates the transaction without using the state. It allows any node to validate a
 /// transaction before forwarding it to the node that tracks the `signer_id` account.
 pub fn validate_transaction(
@@ -298,6 +319,9 @@ pub(crate) fn validate_receipt(
             validate_action_receipt(limit_config, action_receipt, current_protocol_version)
         }
         ReceiptEnum::Data(data_receipt) => validate_data_receipt(limit_config, data_receipt),
+        ReceiptEnum::GlobalContractDistribution(distribution_receipt) => {
+            validate_global_contract_distribution_receipt(limit_config, distribution_receipt)
+        }
     }
 }
 
@@ -332,6 +356,23 @@ fn validate_data_receipt(
     Ok(())
 }
 
+/// Validates given global contract distribution receipt. Checks that the distributed contract
+/// size doesn't exceed the limit.
+fn validate_global_contract_distribution_receipt(
+    limit_config: &LimitConfig,
+    receipt: &GlobalContractDistributionReceipt,
+) -> Result<(), ReceiptValidationError> {
+    if receipt.code.len() as u64 > limit_config.max_contract_size {
+        return Err(ReceiptValidationError::ActionsValidation(
+            ActionsValidationError::ContractSizeExceeded {
+                size: receipt.code.len() as u64,
+                limit: limit_config.max_contract_size,
+            },
+        ));
+    }
+    Ok(())
+}
+
 /// Validates given actions:
 ///
 /// - Checks limits if applicable.
@@ -371,6 +412,14 @@ pub(crate) fn validate_actions(
                 }
                 found_delegate_action = true;
             }
+            if let Action::DeployGlobalContract(_) | Action::UseGlobalContract(_) = action {
+                if !checked_feature!("stable", GlobalContracts, current_protocol_version) {
+                    return Err(ActionsValidationError::UnsupportedProtocolFeature {
+                        protocol_feature: String::from("GlobalContracts"),
+                        version: ProtocolFeature::GlobalContracts.protocol_version(),
+                    });
+                }
+            }
         }
         validate_action(limit_config, action, current_protocol_version)?;
     }
@@ -405,6 +454,8 @@ pub fn validate_action(
         Action::Delegate(a) => validate_delegate_action(limit_config, a, current_protocol_version),
         Action::RegisterRsa2048Keys(a) => validate_register_rsa2048_keys_action(limit_config, a),
         Action::CreateRsa2048Challenge(a) => validate_create_rsa2048_challenge_action(a),
+        Action::DeployGlobalContract(a) => validate_deploy_global_contract_action(limit_config, a),
+        Action::UseGlobalContract(_) => Ok(()),
     }
 }
 
@@ -433,6 +484,22 @@ fn validate_deploy_contract_action(
     Ok(())
 }
 
+/// Validates `DeployGlobalContractAction`. Checks that the given contract size doesn't exceed the
+/// limit.
+fn validate_deploy_global_contract_action(
+    limit_config: &LimitConfig,
+    action: &DeployGlobalContractAction,
+) -> Result<(), ActionsValidationError> {
+    if action.code.len() as u64 > limit_config.max_contract_size {
+        return Err(ActionsValidationError::ContractSizeExceeded {
+            size: action.code.len() as u64,
+            limit: limit_config.max_contract_size,
+        });
+    }
+
+    Ok(())
+}
+
 /// Validates `FunctionCallAction`. Checks that the method name length doesn't exceed the limit and
 /// the length of the arguments doesn't exceed the limit.
 fn validate_function_call_action(
@@ -565,10 +632,12 @@ mod tests {
     use unc_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
     use unc_primitives::account::{AccessKey, FunctionCallPermission};
     use unc_primitives::action::delegate::{DelegateAction, NonDelegateAction};
+    use unc_primitives::congestion_info::CongestionInfo;
     use unc_primitives::hash::{hash, CryptoHash};
     use unc_primitives::test_utils::account_new;
     use unc_primitives::transaction::{
         CreateAccountAction, DeleteAccountAction, DeleteKeyAction, PledgeAction, TransferAction,
+        UseGlobalContractAction,
     };
     use unc_primitives::types::{AccountId, Balance, MerkleHash, StateChangeCause};
     use unc_primitives::version::PROTOCOL_VERSION;
@@ -818,6 +887,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_validate_receiver_congestion() {
+        let config = RuntimeConfig::test();
+        let mut congested = CongestionInfo::default();
+        congested
+            .add_delayed_receipt(config.congestion_control_config.max_congestion_incoming_gas, 0)
+            .unwrap();
+        let block_congestion_info = BlockCongestionInfo::new(
+            [(0, CongestionInfo::default()), (1, congested)].into_iter().collect(),
+        );
+
+        validate_receiver_congestion(&config, &block_congestion_info, 0).expect("not congested");
+        assert_eq!(
+            validate_receiver_congestion(&config, &block_congestion_info, 1),
+            Err(InvalidTxError::ShardCongested { shard_id: 1 })
+        );
+        // Shards without the congestion info accept all the transactions.
+        validate_receiver_congestion(&config, &block_congestion_info, 2).expect("unknown shard");
+    }
+
     // Transactions
 
     #[test]
@@ -1568,6 +1657,29 @@ mod tests {
         );
     }
 
+    // GlobalContractDistributionReceipt
+
+    #[test]
+    fn test_validate_global_contract_distribution_receipt_too_large() {
+        let mut limit_config = test_limit_config();
+        limit_config.max_contract_size = 5;
+        validate_global_contract_distribution_receipt(
+            &limit_config,
+            &GlobalContractDistributionReceipt { code: vec![1; 5], gas_price: 100 },
+        )
+        .expect("valid distribution receipt");
+        assert_eq!(
+            validate_global_contract_distribution_receipt(
+                &limit_config,
+                &GlobalContractDistributionReceipt { code: vec![1; 6], gas_price: 100 }
+            )
+            .expect_err("expected an error"),
+            ReceiptValidationError::ActionsValidation(
+                ActionsValidationError::ContractSizeExceeded { size: 6, limit: 5 }
+            )
+        );
+    }
+
     // Group of actions
 
     #[test]
@@ -1708,6 +1820,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_global_contract_actions_feature_gate() {
+        let limit_config = test_limit_config();
+        let actions = [
+            Action::DeployGlobalContract(DeployGlobalContractAction { code: vec![1; 10] }),
+            Action::UseGlobalContract(Box::new(UseGlobalContractAction {
+                code_hash: hash(&[1; 10]),
+            })),
+        ];
+        let enabled_version = ProtocolFeature::GlobalContracts.protocol_version();
+        assert_eq!(
+            validate_actions(&limit_config, &actions, enabled_version - 1)
+                .expect_err("Expected an error"),
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: String::from("GlobalContracts"),
+                version: enabled_version,
+            },
+        );
+        assert_eq!(validate_actions(&limit_config, &actions, enabled_version), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_deploy_global_contract_size_exceeded() {
+        let mut limit_config = test_limit_config();
+        limit_config.max_contract_size = 5;
+        assert_eq!(
+            validate_action(
+                &limit_config,
+                &Action::DeployGlobalContract(DeployGlobalContractAction { code: vec![1; 10] }),
+                PROTOCOL_VERSION,
+            )
+            .expect_err("Expected an error"),
+            ActionsValidationError::ContractSizeExceeded { size: 10, limit: 5 },
+        );
+    }
+
     // Individual actions
 
     #[test]
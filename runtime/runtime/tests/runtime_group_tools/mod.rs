@@ -107,6 +107,7 @@ impl StandaloneRuntime {
             is_new_chunk: true,
             migration_data: Arc::new(MigrationData::default()),
             migration_flags: MigrationFlags::default(),
+            congestion_control: None,
         };
 
         Self {
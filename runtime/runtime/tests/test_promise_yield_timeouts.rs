@@ -0,0 +1,358 @@
+use assert_matches::assert_matches;
+use node_runtime::{ApplyResult, ApplyState, Runtime};
+use std::sync::Arc;
+use testlib::runtime_utils::alice_account;
+use unc_crypto::{KeyType, PublicKey};
+use unc_parameters::{ExtCosts, RuntimeConfig};
+use unc_primitives::account::Account;
+use unc_primitives::hash::{hash, CryptoHash};
+use unc_primitives::receipt::{
+    ActionReceipt, DataReceipt, PromiseYieldTimeout, Receipt, ReceiptEnum,
+};
+use unc_primitives::runtime::migration_data::{MigrationData, MigrationFlags};
+use unc_primitives::shard_layout::ShardUId;
+use unc_primitives::test_utils::MockEpochInfoProvider;
+use unc_primitives::transaction::{Action, ExecutionStatus, FunctionCallAction};
+use unc_primitives::types::{BlockHeight, Gas, MerkleHash, StateChangeCause};
+use unc_primitives::version::ProtocolFeature;
+use unc_store::test_utils::TestTriesBuilder;
+use unc_store::{
+    enqueue_promise_yield_timeout, get_promise_yield_indices, has_promise_yield_receipt,
+    set_account, set_code, set_promise_yield_indices, set_promise_yield_receipt, ShardTries,
+};
+use unc_vm_runner::ContractCode;
+
+/// `yield` yields to `callback` and returns the data id, `resume` resumes the data id given as
+/// the input and `callback` returns the status of the promise result as a little-endian `u64`:
+/// 1 when resumed and 2 when timed out.
+const YIELD_CONTRACT: &str = r#"
+(module
+  (import "env" "input" (func $input (param i64)))
+  (import "env" "value_return" (func $value_return (param i64 i64)))
+  (import "env" "promise_yield_create"
+    (func $promise_yield_create (param i64 i64 i64 i64 i64 i64 i64) (result i64)))
+  (import "env" "promise_yield_resume"
+    (func $promise_yield_resume (param i64 i64 i64 i64) (result i32)))
+  (import "env" "promise_result" (func $promise_result (param i64 i64) (result i64)))
+  (memory 1)
+  (data (i32.const 0) "callback")
+  (data (i32.const 8) "ok")
+  (func (export "yield")
+    (drop (call $promise_yield_create
+      (i64.const 8) (i64.const 0) (i64.const 0) (i64.const 0)
+      (i64.const 0) (i64.const 1) (i64.const 0)))
+    (call $value_return (i64.const -1) (i64.const 0)))
+  (func (export "resume")
+    (call $input (i64.const 0))
+    (drop (call $promise_yield_resume
+      (i64.const -1) (i64.const 0) (i64.const 2) (i64.const 8))))
+  (func (export "callback")
+    (i64.store (i32.const 16) (call $promise_result (i64.const 0) (i64.const 1)))
+    (call $value_return (i64.const 8) (i64.const 16))))
+"#;
+
+fn apply_state(gas_limit: Gas) -> ApplyState {
+    ApplyState {
+        block_height: 1,
+        prev_block_hash: Default::default(),
+        block_hash: Default::default(),
+        epoch_id: Default::default(),
+        epoch_height: 0,
+        gas_price: 100,
+        block_timestamp: 0,
+        gas_limit: Some(gas_limit),
+        random_seed: Default::default(),
+        current_protocol_version: ProtocolFeature::YieldExecution.protocol_version(),
+        config: Arc::new(RuntimeConfig::test()),
+        cache: None,
+        is_new_chunk: true,
+        migration_data: Arc::new(MigrationData::default()),
+        migration_flags: MigrationFlags::default(),
+        congestion_control: None,
+    }
+}
+
+/// Stores pending yields for `alice` which expire at `expires_at` in the timeout queue,
+/// optionally together with the markers which are removed when the yields are resumed.
+fn setup_promise_yields(
+    tries: &ShardTries,
+    root: CryptoHash,
+    data_ids: &[CryptoHash],
+    expires_at: BlockHeight,
+    resumed: bool,
+) -> CryptoHash {
+    let mut state_update = tries.new_trie_update(ShardUId::single_shard(), root);
+    let mut promise_yield_indices = get_promise_yield_indices(&state_update).unwrap();
+    for &data_id in data_ids {
+        if !resumed {
+            set_promise_yield_receipt(&mut state_update, alice_account(), data_id, expires_at);
+        }
+        enqueue_promise_yield_timeout(
+            &mut state_update,
+            &mut promise_yield_indices,
+            &PromiseYieldTimeout { account_id: alice_account(), data_id, expires_at },
+        );
+    }
+    set_promise_yield_indices(&mut state_update, &promise_yield_indices);
+    state_update.commit(StateChangeCause::InitialState);
+    let trie_changes = state_update.finalize().unwrap().1;
+    let mut store_update = tries.store_update();
+    let root = tries.apply_all(&trie_changes, ShardUId::single_shard(), &mut store_update);
+    store_update.commit().unwrap();
+    root
+}
+
+/// Creates `alice` with the yield contract deployed.
+fn setup_yield_contract(tries: &ShardTries) -> CryptoHash {
+    let code = ContractCode::new(unc_test_contracts::wat_contract(YIELD_CONTRACT), None);
+    let mut state_update = tries.new_trie_update(ShardUId::single_shard(), MerkleHash::default());
+    let account = Account::new(10u128.pow(30), 0, 0, *code.hash(), code.code().len() as u64 + 100);
+    set_account(&mut state_update, alice_account(), &account);
+    set_code(&mut state_update, alice_account(), &code);
+    state_update.commit(StateChangeCause::InitialState);
+    let trie_changes = state_update.finalize().unwrap().1;
+    let mut store_update = tries.store_update();
+    let root = tries.apply_all(&trie_changes, ShardUId::single_shard(), &mut store_update);
+    store_update.commit().unwrap();
+    root
+}
+
+/// A receipt from `alice` calling `method_name` on its own contract.
+fn function_call_receipt(method_name: &str, args: Vec<u8>) -> Receipt {
+    Receipt {
+        predecessor_id: alice_account(),
+        receiver_id: alice_account(),
+        receipt_id: hash(method_name.as_bytes()),
+        receipt: ReceiptEnum::Action(ActionReceipt {
+            signer_id: alice_account(),
+            signer_public_key: PublicKey::empty(KeyType::ED25519),
+            gas_price: 100,
+            output_data_receivers: vec![],
+            input_data_ids: vec![],
+            actions: vec![Action::FunctionCall(Box::new(FunctionCallAction {
+                method_name: method_name.to_string(),
+                args,
+                gas: 10u64.pow(14),
+                deposit: 0,
+            }))],
+        }),
+    }
+}
+
+/// Applies a chunk with the given incoming receipts and commits its changes, returning the new
+/// state root.
+fn apply_chunk(
+    tries: &ShardTries,
+    root: CryptoHash,
+    apply_state: &ApplyState,
+    incoming_receipts: &[Receipt],
+) -> (CryptoHash, ApplyResult) {
+    let apply_result = Runtime::new()
+        .apply(
+            tries.get_trie_for_shard(ShardUId::single_shard(), root),
+            &None,
+            apply_state,
+            incoming_receipts,
+            &[],
+            &MockEpochInfoProvider::default(),
+            Default::default(),
+        )
+        .unwrap();
+    let mut store_update = tries.store_update();
+    let root =
+        tries.apply_all(&apply_result.trie_changes, ShardUId::single_shard(), &mut store_update);
+    store_update.commit().unwrap();
+    (root, apply_result)
+}
+
+/// Applies an empty chunk and commits its changes, returning the new state root.
+fn apply_empty_chunk(
+    tries: &ShardTries,
+    root: CryptoHash,
+    apply_state: &ApplyState,
+) -> (CryptoHash, ApplyResult) {
+    apply_chunk(tries, root, apply_state, &[])
+}
+
+/// Returns the value the contract returned for the receipt with the given method name.
+fn success_value(apply_result: &ApplyResult, method_name: &str) -> Vec<u8> {
+    let outcome = apply_result
+        .outcomes
+        .iter()
+        .find(|outcome| outcome.id == hash(method_name.as_bytes()))
+        .unwrap();
+    assert_matches!(&outcome.outcome.status, ExecutionStatus::SuccessValue(value) => value.clone())
+}
+
+/// Returns the value the yielded `callback` returned, if it ran in this chunk.
+fn callback_value(apply_result: &ApplyResult, callback_receipt_id: CryptoHash) -> Option<u64> {
+    let outcome = apply_result.outcomes.iter().find(|outcome| outcome.id == callback_receipt_id)?;
+    let value = assert_matches!(
+        &outcome.outcome.status,
+        ExecutionStatus::SuccessValue(value) => value.clone()
+    );
+    Some(u64::from_le_bytes(value.try_into().unwrap()))
+}
+
+/// Calls `yield` at `block_height` and returns the new state root, the data id of the yield and
+/// the receipts the call produced, the yielded `callback` among them.
+fn call_yield(
+    tries: &ShardTries,
+    root: CryptoHash,
+    apply_state: &ApplyState,
+) -> (CryptoHash, CryptoHash, Vec<Receipt>, CryptoHash) {
+    let (root, apply_result) =
+        apply_chunk(tries, root, apply_state, &[function_call_receipt("yield", vec![])]);
+    let data_id = CryptoHash::try_from(success_value(&apply_result, "yield").as_slice()).unwrap();
+    let callback_receipt_id = apply_result
+        .outgoing_receipts
+        .iter()
+        .find(|receipt| {
+            matches!(&receipt.receipt, ReceiptEnum::Action(action_receipt)
+                if action_receipt.input_data_ids == [data_id])
+        })
+        .unwrap()
+        .receipt_id;
+
+    let state = tries.new_trie_update(ShardUId::single_shard(), root);
+    assert!(has_promise_yield_receipt(&state, &alice_account(), data_id).unwrap());
+    assert_eq!(get_promise_yield_indices(&state).unwrap().len(), 1);
+    (root, data_id, apply_result.outgoing_receipts, callback_receipt_id)
+}
+
+#[test]
+fn test_promise_yield_timeout() {
+    let tries = TestTriesBuilder::new().build();
+    let mut apply_state = apply_state(10u64.pow(15));
+    let data_id = hash(b"yield");
+    let mut root = setup_promise_yields(&tries, MerkleHash::default(), &[data_id], 5, false);
+
+    for block_height in 4..=6 {
+        apply_state.block_height = block_height;
+        let (new_root, apply_result) = apply_empty_chunk(&tries, root, &apply_state);
+        root = new_root;
+
+        // The yield is resolved exactly once, at the block it expires.
+        if block_height == 5 {
+            assert_matches!(&apply_result.outgoing_receipts[..], [receipt] => {
+                assert_eq!(receipt.receiver_id, alice_account());
+                assert_eq!(receipt.receipt, ReceiptEnum::Data(DataReceipt { data_id, data: None }));
+            });
+        } else {
+            assert!(apply_result.outgoing_receipts.is_empty());
+        }
+    }
+
+    let state = tries.new_trie_update(ShardUId::single_shard(), root);
+    assert!(!has_promise_yield_receipt(&state, &alice_account(), data_id).unwrap());
+    assert_eq!(get_promise_yield_indices(&state).unwrap().len(), 0);
+}
+
+#[test]
+fn test_promise_yield_resumed_before_timeout() {
+    let tries = TestTriesBuilder::new().build();
+    let mut apply_state = apply_state(10u64.pow(15));
+    apply_state.block_height = 5;
+    let root = setup_promise_yields(&tries, MerkleHash::default(), &[hash(b"yield")], 5, true);
+
+    let (root, apply_result) = apply_empty_chunk(&tries, root, &apply_state);
+    assert!(apply_result.outgoing_receipts.is_empty());
+    let state = tries.new_trie_update(ShardUId::single_shard(), root);
+    assert_eq!(get_promise_yield_indices(&state).unwrap().len(), 0);
+}
+
+/// Every resolved timeout is charged, so a chunk only resolves as many as its compute limit
+/// allows and leaves the rest to the next chunks.
+#[test]
+fn test_promise_yield_timeouts_compute_limit() {
+    let tries = TestTriesBuilder::new().build();
+    let timeout_cost =
+        RuntimeConfig::test().wasm_config.ext_costs.compute_cost(ExtCosts::yield_resume_base);
+    let mut apply_state = apply_state(2 * timeout_cost);
+    apply_state.block_height = 5;
+    let data_ids: Vec<_> = (0..5u8).map(|i| hash(&[i])).collect();
+    let mut root = setup_promise_yields(&tries, MerkleHash::default(), &data_ids, 5, false);
+
+    let mut resolved = vec![];
+    for expected_receipts in [2, 2, 1, 0] {
+        let (new_root, apply_result) = apply_empty_chunk(&tries, root, &apply_state);
+        root = new_root;
+        assert_eq!(apply_result.outgoing_receipts.len(), expected_receipts);
+        for receipt in apply_result.outgoing_receipts {
+            let data_id = assert_matches!(
+                receipt.receipt,
+                ReceiptEnum::Data(DataReceipt { data_id, data: None }) => data_id
+            );
+            resolved.push(data_id);
+        }
+        apply_state.block_height += 1;
+    }
+
+    // The timeouts are resolved in the order of the queue.
+    assert_eq!(resolved, data_ids);
+    let state = tries.new_trie_update(ShardUId::single_shard(), root);
+    assert_eq!(get_promise_yield_indices(&state).unwrap().len(), 0);
+}
+
+#[test]
+fn test_promise_yield_resumed_through_contract() {
+    let tries = TestTriesBuilder::new().build();
+    let mut apply_state = apply_state(10u64.pow(15));
+    let root = setup_yield_contract(&tries);
+    let (root, data_id, mut receipts, callback_receipt_id) = call_yield(&tries, root, &apply_state);
+
+    // The yielded callback waits for its data while `resume` submits it.
+    apply_state.block_height += 1;
+    receipts.push(function_call_receipt("resume", data_id.as_ref().to_vec()));
+    let (root, apply_result) = apply_chunk(&tries, root, &apply_state, &receipts);
+    assert_eq!(callback_value(&apply_result, callback_receipt_id), None);
+    let state = tries.new_trie_update(ShardUId::single_shard(), root);
+    assert!(!has_promise_yield_receipt(&state, &alice_account(), data_id).unwrap());
+    assert_matches!(
+        apply_result.outgoing_receipts.iter().find(|receipt| receipt.receiver_id == alice_account()
+            && matches!(receipt.receipt, ReceiptEnum::Data(_))),
+        Some(Receipt { receipt: ReceiptEnum::Data(DataReceipt { data_id: id, data: Some(data) }), .. }) => {
+            assert_eq!(*id, data_id);
+            assert_eq!(data, b"ok");
+        }
+    );
+
+    apply_state.block_height += 1;
+    let (root, apply_result) =
+        apply_chunk(&tries, root, &apply_state, &apply_result.outgoing_receipts);
+    assert_eq!(callback_value(&apply_result, callback_receipt_id), Some(1));
+
+    // The timeout of a resumed yield is dropped from the queue without a receipt.
+    apply_state.block_height =
+        1 + RuntimeConfig::test().wasm_config.limit_config.yield_timeout_length_in_blocks;
+    let (root, apply_result) = apply_empty_chunk(&tries, root, &apply_state);
+    assert!(apply_result.outgoing_receipts.is_empty());
+    let state = tries.new_trie_update(ShardUId::single_shard(), root);
+    assert_eq!(get_promise_yield_indices(&state).unwrap().len(), 0);
+}
+
+#[test]
+fn test_promise_yield_timed_out_through_contract() {
+    let tries = TestTriesBuilder::new().build();
+    let mut apply_state = apply_state(10u64.pow(15));
+    let root = setup_yield_contract(&tries);
+    let (root, data_id, receipts, callback_receipt_id) = call_yield(&tries, root, &apply_state);
+
+    apply_state.block_height += 1;
+    let (root, apply_result) = apply_chunk(&tries, root, &apply_state, &receipts);
+    assert_eq!(callback_value(&apply_result, callback_receipt_id), None);
+
+    apply_state.block_height =
+        1 + RuntimeConfig::test().wasm_config.limit_config.yield_timeout_length_in_blocks;
+    let (root, apply_result) = apply_empty_chunk(&tries, root, &apply_state);
+    assert_matches!(&apply_result.outgoing_receipts[..], [receipt] => {
+        assert_eq!(receipt.receipt, ReceiptEnum::Data(DataReceipt { data_id, data: None }));
+    });
+    let state = tries.new_trie_update(ShardUId::single_shard(), root);
+    assert!(!has_promise_yield_receipt(&state, &alice_account(), data_id).unwrap());
+
+    apply_state.block_height += 1;
+    let (_, apply_result) =
+        apply_chunk(&tries, root, &apply_state, &apply_result.outgoing_receipts);
+    assert_eq!(callback_value(&apply_result, callback_receipt_id), Some(2));
+}
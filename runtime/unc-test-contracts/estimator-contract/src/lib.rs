@@ -40,6 +40,15 @@ extern "C" {
     fn alt_bn128_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
     fn alt_bn128_g1_sum(value_len: u64, value_ptr: u64, register_id: u64);
     fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    fn bls12381_p1_sum(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_p2_sum(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_g1_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_g2_multiexp(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_map_fp_to_g1(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_map_fp2_to_g2(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    fn bls12381_p1_decompress(value_len: u64, value_ptr: u64, register_id: u64);
+    fn bls12381_p2_decompress(value_len: u64, value_ptr: u64, register_id: u64);
     fn random_seed(register_id: u64);
     fn sha256(value_len: u64, value_ptr: u64, register_id: u64);
     fn keccak256(value_len: u64, value_ptr: u64, register_id: u64);
@@ -719,6 +728,328 @@ pub unsafe fn alt_bn128_pairing_check_10_10() {
     }
 }
 
+// BLS12-381 host functions are only available with the `nightly` feature until the
+// `Bls12381` protocol feature is stabilized.
+#[cfg(feature = "nightly")]
+mod bls12381 {
+    use super::*;
+
+    // Multiples `k * G` of the generators for `k` in `1..=10`, uncompressed.
+    #[rustfmt::skip]
+    const G1_POINTS: [[u8; 96]; 10] = [
+        [23, 241, 211, 167, 49, 151, 215, 148, 38, 149, 99, 140, 79, 169, 172, 15, 195, 104, 140, 79, 151, 116, 185, 5, 161, 78, 58, 63, 23, 27, 172, 88, 108, 85, 232, 63, 249, 122, 26, 239, 251, 58, 240, 10, 219, 34, 198, 187, 8, 179, 244, 129, 227, 170, 160, 241, 160, 158, 48, 237, 116, 29, 138, 228, 252, 245, 224, 149, 213, 208, 10, 246, 0, 219, 24, 203, 44, 4, 179, 237, 208, 60, 199, 68, 162, 136, 138, 228, 12, 170, 35, 41, 70, 197, 231, 225],
+        [5, 114, 203, 234, 144, 77, 103, 70, 136, 8, 200, 235, 80, 169, 69, 12, 151, 33, 219, 48, 145, 40, 1, 37, 67, 144, 45, 10, 195, 88, 166, 42, 226, 143, 117, 187, 143, 28, 124, 66, 195, 154, 140, 85, 41, 191, 15, 78, 22, 106, 157, 140, 171, 198, 115, 163, 34, 253, 166, 115, 119, 157, 142, 56, 34, 186, 62, 203, 134, 112, 228, 97, 247, 59, 185, 2, 29, 95, 215, 106, 76, 86, 217, 212, 205, 22, 189, 27, 186, 134, 136, 25, 121, 116, 157, 40],
+        [9, 236, 227, 8, 249, 209, 240, 19, 23, 101, 33, 45, 236, 169, 150, 151, 177, 18, 214, 31, 155, 233, 165, 241, 243, 120, 10, 81, 51, 91, 63, 249, 129, 116, 122, 11, 44, 162, 23, 155, 150, 210, 192, 201, 2, 78, 82, 36, 3, 43, 128, 211, 166, 245, 176, 159, 138, 132, 98, 51, 137, 197, 248, 12, 166, 154, 12, 221, 171, 195, 9, 127, 157, 156, 39, 49, 15, 212, 59, 230, 231, 69, 37, 108, 99, 74, 244, 92, 163, 71, 59, 5, 144, 174, 48, 209],
+        [12, 155, 96, 213, 175, 203, 213, 102, 58, 138, 68, 183, 197, 160, 47, 25, 233, 167, 122, 176, 163, 91, 214, 88, 9, 187, 92, 103, 236, 88, 44, 137, 127, 235, 4, 222, 204, 105, 75, 19, 224, 133, 135, 243, 255, 155, 91, 96, 20, 59, 230, 208, 120, 194, 183, 154, 125, 79, 29, 27, 33, 72, 106, 3, 14, 201, 63, 86, 170, 84, 225, 222, 136, 13, 181, 166, 109, 216, 51, 166, 82, 169, 91, 238, 39, 200, 36, 8, 64, 6, 203, 86, 68, 203, 212, 63],
+        [16, 231, 121, 31, 185, 114, 254, 1, 65, 89, 170, 51, 169, 134, 34, 218, 60, 220, 152, 255, 112, 121, 101, 229, 54, 216, 99, 107, 95, 204, 90, 199, 169, 26, 140, 70, 229, 154, 0, 220, 165, 117, 175, 15, 24, 251, 19, 220, 22, 186, 67, 126, 220, 198, 85, 30, 48, 193, 5, 18, 54, 116, 148, 191, 182, 176, 28, 198, 104, 30, 138, 76, 60, 210, 80, 24, 50, 171, 92, 74, 188, 64, 180, 87, 139, 133, 203, 175, 251, 240, 188, 215, 13, 103, 198, 226],
+        [6, 232, 47, 109, 164, 82, 15, 133, 197, 210, 125, 143, 50, 158, 204, 250, 5, 148, 79, 209, 9, 107, 32, 115, 76, 137, 73, 102, 209, 42, 158, 42, 154, 151, 68, 82, 157, 114, 18, 211, 56, 131, 17, 58, 12, 173, 185, 9, 23, 216, 16, 56, 247, 214, 11, 238, 145, 16, 217, 192, 214, 209, 16, 47, 226, 217, 152, 201, 87, 242, 142, 49, 236, 40, 76, 192, 65, 52, 223, 142, 71, 232, 248, 47, 243, 175, 46, 96, 166, 217, 104, 138, 69, 99, 71, 124],
+        [25, 40, 243, 190, 185, 53, 25, 238, 207, 1, 69, 218, 144, 59, 64, 164, 201, 125, 202, 0, 178, 31, 18, 172, 13, 243, 190, 145, 22, 239, 46, 242, 123, 42, 230, 188, 212, 197, 188, 45, 84, 239, 90, 112, 98, 126, 252, 183, 16, 141, 173, 186, 164, 182, 54, 68, 86, 57, 213, 174, 48, 137, 179, 196, 58, 138, 29, 71, 129, 142, 221, 24, 57, 215, 56, 57, 89, 164, 28, 16, 253, 198, 104, 73, 207, 161, 176, 140, 90, 17, 236, 126, 40, 152, 26, 28],
+        [8, 90, 231, 101, 88, 129, 38, 245, 232, 96, 208, 25, 192, 226, 98, 53, 245, 103, 169, 192, 192, 178, 216, 255, 48, 243, 232, 212, 54, 177, 8, 37, 150, 229, 231, 70, 45, 32, 245, 190, 55, 100, 253, 71, 62, 87, 249, 207, 25, 231, 223, 171, 138, 121, 75, 106, 187, 159, 132, 229, 119, 57, 222, 23, 42, 99, 65, 82, 115, 244, 96, 209, 96, 127, 166, 167, 79, 10, 205, 151, 217, 103, 27, 128, 29, 209, 253, 79, 24, 35, 45, 209, 37, 147, 89, 161],
+        [25, 205, 243, 128, 113, 70, 230, 142, 4, 19, 20, 202, 147, 225, 254, 224, 153, 18, 36, 236, 42, 116, 190, 178, 134, 104, 22, 253, 8, 38, 206, 123, 98, 99, 238, 49, 233, 83, 168, 109, 27, 114, 204, 34, 21, 165, 119, 147, 7, 72, 27, 31, 38, 26, 171, 172, 244, 92, 110, 79, 194, 120, 5, 84, 65, 191, 175, 153, 246, 4, 209, 248, 53, 192, 117, 42, 201, 116, 43, 69, 34, 201, 245, 199, 125, 180, 9, 137, 231, 218, 96, 133, 5, 212, 134, 22],
+        [15, 129, 218, 37, 236, 241, 200, 75, 87, 127, 239, 190, 221, 97, 7, 122, 129, 220, 67, 176, 3, 4, 1, 91, 43, 89, 106, 182, 127, 0, 228, 28, 134, 187, 0, 235, 208, 249, 13, 75, 18, 94, 176, 83, 152, 145, 174, 237, 17, 175, 98, 149, 145, 236, 134, 145, 109, 108, 227, 120, 119, 183, 67, 254, 32, 154, 58, 246, 17, 71, 153, 108, 29, 247, 253, 28, 71, 176, 49, 129, 205, 128, 111, 211, 28, 48, 113, 183, 57, 228, 222, 178, 52, 189, 158, 25],
+    ];
+    #[rustfmt::skip]
+    const G2_POINTS: [[u8; 192]; 10] = [
+        [19, 224, 43, 96, 82, 113, 159, 96, 125, 172, 211, 160, 136, 39, 79, 101, 89, 107, 208, 208, 153, 32, 182, 26, 181, 218, 97, 187, 220, 127, 80, 73, 51, 76, 241, 18, 19, 148, 93, 87, 229, 172, 125, 5, 93, 4, 43, 126, 2, 74, 162, 178, 240, 143, 10, 145, 38, 8, 5, 39, 45, 197, 16, 81, 198, 228, 122, 212, 250, 64, 59, 2, 180, 81, 11, 100, 122, 227, 209, 119, 11, 172, 3, 38, 168, 5, 187, 239, 212, 128, 86, 200, 193, 33, 189, 184, 6, 6, 196, 160, 46, 167, 52, 204, 50, 172, 210, 176, 43, 194, 139, 153, 203, 62, 40, 126, 133, 167, 99, 175, 38, 116, 146, 171, 87, 46, 153, 171, 63, 55, 13, 39, 92, 236, 29, 161, 170, 169, 7, 95, 240, 95, 121, 190, 12, 229, 213, 39, 114, 125, 110, 17, 140, 201, 205, 198, 218, 46, 53, 26, 173, 253, 155, 170, 140, 189, 211, 167, 109, 66, 154, 105, 81, 96, 209, 44, 146, 58, 201, 204, 59, 172, 162, 137, 225, 147, 84, 134, 8, 184, 40, 1],
+        [10, 78, 222, 249, 193, 237, 127, 114, 159, 82, 14, 71, 115, 10, 18, 79, 215, 6, 98, 169, 4, 186, 16, 116, 114, 129, 20, 209, 3, 30, 21, 114, 198, 200, 134, 246, 181, 126, 199, 42, 97, 120, 40, 140, 71, 195, 53, 119, 22, 56, 83, 57, 87, 213, 64, 169, 210, 55, 15, 23, 204, 126, 213, 134, 59, 192, 185, 149, 184, 130, 94, 14, 225, 234, 30, 30, 77, 0, 219, 174, 129, 241, 75, 11, 243, 97, 27, 120, 201, 82, 170, 202, 184, 39, 160, 83, 15, 109, 69, 82, 250, 101, 221, 38, 56, 179, 97, 84, 63, 136, 113, 54, 164, 50, 83, 217, 198, 108, 65, 22, 151, 0, 63, 122, 19, 195, 8, 245, 66, 46, 26, 160, 165, 156, 137, 103, 172, 222, 253, 139, 110, 54, 204, 243, 4, 104, 251, 68, 13, 130, 176, 99, 10, 235, 141, 202, 43, 82, 86, 120, 154, 102, 218, 105, 191, 145, 0, 156, 191, 230, 189, 34, 30, 71, 170, 138, 232, 141, 236, 233, 118, 75, 243, 189, 153, 157, 149, 215, 30, 76, 152, 153],
+        [9, 56, 2, 117, 187, 200, 229, 220, 234, 125, 196, 221, 126, 5, 80, 255, 42, 196, 128, 144, 83, 150, 237, 165, 80, 98, 101, 15, 141, 37, 28, 150, 235, 72, 6, 115, 147, 124, 198, 217, 214, 164, 74, 170, 86, 202, 102, 220, 18, 41, 21, 200, 36, 160, 133, 126, 46, 228, 20, 163, 220, 203, 35, 174, 105, 26, 229, 67, 41, 120, 19, 21, 160, 199, 93, 241, 192, 77, 109, 122, 80, 160, 48, 252, 134, 111, 9, 213, 22, 2, 14, 248, 35, 36, 175, 174, 8, 242, 57, 186, 50, 155, 57, 103, 254, 72, 215, 24, 163, 108, 254, 95, 98, 167, 228, 46, 11, 241, 193, 237, 113, 65, 80, 161, 102, 191, 189, 107, 207, 107, 59, 88, 185, 117, 185, 237, 234, 86, 213, 63, 35, 160, 232, 73, 11, 33, 218, 121, 85, 150, 158, 97, 1, 12, 122, 26, 188, 26, 111, 1, 54, 150, 29, 30, 59, 32, 177, 167, 50, 106, 199, 56, 254, 245, 199, 33, 71, 157, 253, 148, 139, 82, 253, 242, 69, 94, 68, 129, 62, 207, 216, 146],
+        [7, 2, 39, 211, 241, 54, 132, 253, 183, 206, 49, 184, 6, 91, 163, 172, 179, 95, 123, 222, 111, 226, 221, 254, 250, 53, 159, 139, 53, 208, 138, 154, 185, 83, 123, 67, 226, 79, 79, 251, 114, 11, 90, 11, 218, 42, 130, 242, 14, 122, 48, 151, 154, 136, 83, 160, 119, 69, 78, 182, 59, 141, 206, 231, 95, 16, 98, 33, 178, 98, 136, 107, 184, 224, 27, 10, 187, 4, 51, 104, 218, 130, 246, 8, 153, 204, 20, 18, 227, 62, 65, 32, 25, 95, 197, 87, 7, 130, 193, 78, 44, 78, 230, 28, 190, 123, 230, 228, 98, 166, 107, 46, 53, 9, 244, 45, 83, 255, 51, 62, 252, 155, 254, 154, 0, 48, 124, 210, 246, 139, 0, 118, 6, 68, 109, 152, 167, 95, 184, 8, 164, 5, 216, 185, 7, 1, 55, 124, 183, 218, 34, 120, 157, 3, 39, 55, 234, 188, 234, 43, 46, 238, 107, 180, 99, 76, 67, 101, 134, 69, 17, 164, 60, 44, 170, 213, 4, 34, 153, 60, 205, 62, 153, 99, 110, 184, 165, 241, 137, 69, 75, 24],
+        [0, 251, 131, 120, 4, 219, 168, 33, 51, 41, 219, 70, 96, 139, 108, 18, 29, 151, 51, 99, 193, 35, 74, 134, 221, 24, 59, 175, 241, 18, 112, 156, 249, 112, 150, 197, 233, 161, 167, 112, 238, 157, 125, 198, 65, 168, 148, 214, 4, 17, 165, 222, 103, 48, 255, 236, 230, 113, 169, 242, 29, 101, 2, 140, 192, 241, 16, 35, 120, 222, 18, 69, 98, 203, 31, 244, 157, 182, 240, 4, 252, 209, 77, 104, 48, 36, 176, 84, 142, 255, 61, 20, 104, 223, 38, 136, 9, 53, 103, 180, 34, 139, 225, 126, 230, 45, 17, 162, 84, 237, 208, 65, 238, 75, 149, 59, 255, 184, 184, 199, 249, 37, 189, 102, 98, 180, 41, 139, 172, 40, 34, 180, 70, 245, 181, 222, 59, 137, 62, 27, 229, 170, 73, 134, 25, 181, 232, 245, 212, 167, 47, 43, 117, 129, 26, 192, 132, 167, 248, 20, 49, 115, 96, 186, 197, 47, 106, 171, 21, 238, 212, 22, 180, 239, 153, 56, 224, 189, 196, 134, 92, 194, 196, 208, 253, 148, 126, 124, 105, 37, 253, 20],
+        [3, 244, 180, 231, 97, 147, 109, 144, 253, 95, 85, 249, 144, 135, 19, 138, 7, 166, 151, 85, 173, 74, 70, 228, 221, 28, 44, 254, 109, 17, 55, 30, 28, 192, 51, 17, 26, 5, 149, 227, 187, 169, 141, 15, 83, 141, 180, 81, 25, 227, 132, 18, 27, 125, 112, 146, 124, 73, 230, 208, 68, 253, 133, 23, 195, 107, 198, 237, 40, 19, 168, 149, 109, 214, 79, 4, 152, 105, 232, 167, 127, 126, 70, 147, 2, 64, 230, 152, 74, 190, 38, 250, 106, 137, 101, 143, 8, 139, 181, 131, 47, 74, 74, 69, 46, 221, 166, 70, 235, 170, 40, 83, 165, 66, 5, 213, 99, 41, 150, 11, 68, 178, 69, 0, 112, 115, 71, 36, 167, 77, 170, 164, 1, 135, 155, 173, 20, 33, 50, 49, 110, 155, 52, 1, 23, 163, 26, 79, 204, 251, 95, 118, 138, 33, 87, 81, 124, 119, 164, 248, 170, 240, 222, 232, 242, 96, 217, 110, 2, 225, 23, 90, 135, 84, 208, 150, 0, 146, 59, 234, 224, 42, 1, 154, 252, 50, 123, 101, 162, 253, 187, 252],
+        [13, 2, 115, 246, 191, 49, 237, 55, 195, 184, 214, 128, 131, 236, 61, 142, 32, 181, 242, 204, 23, 15, 162, 75, 155, 91, 227, 91, 52, 237, 1, 63, 154, 146, 31, 28, 173, 22, 68, 212, 189, 177, 70, 116, 36, 114, 52, 200, 4, 156, 209, 219, 178, 210, 195, 88, 30, 84, 192, 136, 19, 95, 239, 54, 80, 90, 104, 35, 214, 27, 133, 148, 55, 191, 199, 155, 97, 112, 48, 220, 139, 64, 227, 43, 173, 31, 168, 91, 156, 15, 54, 138, 246, 211, 141, 60, 5, 236, 249, 54, 84, 183, 161, 136, 86, 149, 170, 238, 183, 202, 244, 27, 2, 57, 220, 69, 225, 2, 43, 229, 93, 55, 17, 26, 242, 174, 206, 248, 119, 153, 99, 139, 236, 87, 45, 232, 106, 116, 55, 137, 142, 250, 112, 32, 8, 183, 174, 77, 191, 128, 44, 23, 166, 100, 136, 66, 146, 44, 148, 103, 228, 96, 167, 28, 136, 211, 147, 238, 122, 243, 86, 218, 18, 58, 47, 54, 25, 232, 12, 59, 220, 200, 226, 177, 218, 82, 248, 205, 153, 19, 204, 221],
+        [18, 190, 101, 26, 95, 166, 32, 52, 13, 65, 136, 52, 82, 109, 55, 168, 201, 50, 101, 35, 69, 64, 11, 76, 217, 212, 60, 143, 65, 192, 128, 244, 26, 109, 149, 88, 17, 142, 190, 171, 157, 66, 104, 187, 115, 232, 80, 225, 2, 20, 42, 88, 186, 226, 117, 86, 74, 109, 99, 203, 107, 214, 38, 108, 166, 107, 239, 7, 166, 171, 140, 163, 123, 157, 11, 162, 212, 239, 251, 204, 253, 137, 193, 105, 100, 159, 125, 14, 138, 62, 176, 6, 132, 101, 121, 173, 6, 252, 172, 179, 238, 102, 80, 161, 4, 72, 82, 214, 28, 156, 32, 190, 220, 142, 233, 10, 173, 151, 222, 142, 36, 103, 10, 158, 245, 116, 131, 230, 120, 219, 17, 221, 149, 66, 137, 21, 8, 141, 118, 227, 12, 176, 26, 55, 21, 244, 178, 53, 194, 9, 216, 156, 232, 51, 248, 242, 150, 228, 207, 183, 72, 232, 171, 206, 105, 144, 206, 26, 90, 145, 75, 148, 22, 192, 142, 13, 58, 38, 219, 137, 98, 89, 21, 200, 33, 165, 241, 82, 183, 250, 89, 46],
+        [12, 72, 224, 212, 249, 64, 74, 224, 167, 241, 7, 116, 197, 90, 158, 131, 139, 176, 157, 59, 174, 133, 181, 234, 166, 177, 107, 15, 77, 194, 53, 67, 104, 17, 127, 55, 153, 195, 127, 63, 113, 38, 216, 181, 77, 63, 131, 147, 1, 132, 5, 228, 182, 127, 149, 123, 100, 101, 234, 217, 245, 175, 196, 120, 50, 212, 86, 67, 220, 58, 160, 58, 247, 49, 76, 108, 249, 128, 250, 35, 221, 59, 184, 219, 51, 88, 105, 58, 208, 96, 17, 246, 166, 177, 165, 255, 17, 223, 175, 146, 129, 144, 29, 211, 86, 252, 93, 254, 206, 33, 137, 138, 147, 217, 173, 158, 78, 36, 109, 214, 225, 141, 62, 228, 109, 88, 171, 126, 119, 64, 26, 62, 141, 4, 5, 126, 86, 56, 237, 116, 251, 149, 104, 129, 7, 230, 31, 78, 197, 188, 158, 44, 200, 202, 71, 28, 228, 237, 64, 231, 41, 177, 121, 12, 210, 192, 217, 193, 203, 80, 230, 21, 236, 127, 52, 102, 54, 231, 126, 28, 246, 50, 200, 129, 192, 124, 83, 133, 137, 134, 7, 98],
+        [15, 182, 101, 245, 167, 85, 156, 176, 250, 19, 0, 4, 138, 14, 111, 26, 181, 84, 114, 38, 232, 111, 142, 117, 45, 209, 60, 40, 237, 164, 22, 132, 146, 227, 211, 191, 47, 138, 107, 35, 13, 213, 127, 121, 177, 175, 169, 145, 23, 150, 171, 224, 217, 228, 167, 3, 150, 43, 229, 40, 230, 165, 203, 101, 198, 7, 37, 136, 111, 146, 93, 176, 226, 168, 145, 7, 236, 36, 139, 179, 159, 163, 50, 188, 99, 189, 145, 210, 138, 230, 110, 13, 252, 232, 247, 84, 16, 170, 85, 4, 180, 105, 66, 126, 179, 88, 74, 40, 97, 145, 20, 159, 92, 60, 90, 116, 95, 51, 130, 120, 221, 149, 51, 124, 210, 51, 109, 60, 78, 117, 50, 217, 142, 177, 137, 250, 84, 56, 36, 149, 62, 124, 28, 23, 3, 66, 45, 187, 228, 160, 106, 76, 108, 159, 223, 53, 229, 79, 116, 180, 171, 21, 40, 171, 183, 36, 158, 153, 137, 142, 111, 215, 175, 254, 188, 122, 239, 149, 191, 130, 211, 40, 220, 1, 214, 60, 37, 246, 167, 53, 195, 93],
+    ];
+    #[rustfmt::skip]
+    const SCALARS: [[u8; 32]; 10] = [
+        [252, 245, 224, 149, 213, 208, 10, 246, 0, 219, 24, 203, 44, 4, 179, 237, 208, 60, 199, 68, 162, 136, 138, 228, 12, 170, 35, 41, 70, 197, 231, 225],
+        [34, 186, 62, 203, 134, 112, 228, 97, 247, 59, 185, 2, 29, 95, 215, 106, 76, 86, 217, 212, 205, 22, 189, 27, 186, 134, 136, 25, 121, 116, 157, 40],
+        [166, 154, 12, 221, 171, 195, 9, 127, 157, 156, 39, 49, 15, 212, 59, 230, 231, 69, 37, 108, 99, 74, 244, 92, 163, 71, 59, 5, 144, 174, 48, 209],
+        [14, 201, 63, 86, 170, 84, 225, 222, 136, 13, 181, 166, 109, 216, 51, 166, 82, 169, 91, 238, 39, 200, 36, 8, 64, 6, 203, 86, 68, 203, 212, 63],
+        [182, 176, 28, 198, 104, 30, 138, 76, 60, 210, 80, 24, 50, 171, 92, 74, 188, 64, 180, 87, 139, 133, 203, 175, 251, 240, 188, 215, 13, 103, 198, 226],
+        [226, 217, 152, 201, 87, 242, 142, 49, 236, 40, 76, 192, 65, 52, 223, 142, 71, 232, 248, 47, 243, 175, 46, 96, 166, 217, 104, 138, 69, 99, 71, 124],
+        [58, 138, 29, 71, 129, 142, 221, 24, 57, 215, 56, 57, 89, 164, 28, 16, 253, 198, 104, 73, 207, 161, 176, 140, 90, 17, 236, 126, 40, 152, 26, 28],
+        [42, 99, 65, 82, 115, 244, 96, 209, 96, 127, 166, 167, 79, 10, 205, 151, 217, 103, 27, 128, 29, 209, 253, 79, 24, 35, 45, 209, 37, 147, 89, 161],
+        [65, 191, 175, 153, 246, 4, 209, 248, 53, 192, 117, 42, 201, 116, 43, 69, 34, 201, 245, 199, 125, 180, 9, 137, 231, 218, 96, 133, 5, 212, 134, 22],
+        [32, 154, 58, 246, 17, 71, 153, 108, 29, 247, 253, 28, 71, 176, 49, 129, 205, 128, 111, 211, 28, 48, 113, 183, 57, 228, 222, 178, 52, 189, 158, 25],
+    ];
+
+    #[repr(C)]
+    struct P1SumElem(u8, [u8; 96]);
+    #[repr(C)]
+    struct P2SumElem(u8, [u8; 192]);
+    #[repr(C)]
+    struct G1MultiexpElem([u8; 96], [u8; 32]);
+    #[repr(C)]
+    struct G2MultiexpElem([u8; 192], [u8; 32]);
+    #[repr(C)]
+    struct PairingElem([u8; 96], [u8; 192]);
+
+    /// Takes the x coordinate of an uncompressed point and sets the compression flag on it.
+    fn compress<const N: usize>(point: &[u8]) -> [u8; N] {
+        let mut res = [0u8; N];
+        res.copy_from_slice(&point[..N]);
+        res[0] |= 0x80;
+        res
+    }
+
+    unsafe fn p1_sum<const N: usize>(iterations: usize) {
+        let buffer: [P1SumElem; N] =
+            core::array::from_fn(|i| P1SumElem((i % 2) as u8, G1_POINTS[i]));
+        for _ in 0..iterations {
+            bls12381_p1_sum(core::mem::size_of_val(&buffer) as u64, buffer.as_ptr() as u64, 0);
+        }
+    }
+
+    unsafe fn p2_sum<const N: usize>(iterations: usize) {
+        let buffer: [P2SumElem; N] =
+            core::array::from_fn(|i| P2SumElem((i % 2) as u8, G2_POINTS[i]));
+        for _ in 0..iterations {
+            bls12381_p2_sum(core::mem::size_of_val(&buffer) as u64, buffer.as_ptr() as u64, 0);
+        }
+    }
+
+    unsafe fn g1_multiexp<const N: usize>(iterations: usize) {
+        let buffer: [G1MultiexpElem; N] =
+            core::array::from_fn(|i| G1MultiexpElem(G1_POINTS[i], SCALARS[i]));
+        for _ in 0..iterations {
+            bls12381_g1_multiexp(core::mem::size_of_val(&buffer) as u64, buffer.as_ptr() as u64, 0);
+        }
+    }
+
+    unsafe fn g2_multiexp<const N: usize>(iterations: usize) {
+        let buffer: [G2MultiexpElem; N] =
+            core::array::from_fn(|i| G2MultiexpElem(G2_POINTS[i], SCALARS[i]));
+        for _ in 0..iterations {
+            bls12381_g2_multiexp(core::mem::size_of_val(&buffer) as u64, buffer.as_ptr() as u64, 0);
+        }
+    }
+
+    unsafe fn map_fp_to_g1<const N: usize>(iterations: usize) {
+        // The x coordinates of the points are valid field elements.
+        let buffer: [[u8; 48]; N] = core::array::from_fn(|i| {
+            let mut fp = [0u8; 48];
+            fp.copy_from_slice(&G1_POINTS[i][..48]);
+            fp
+        });
+        for _ in 0..iterations {
+            bls12381_map_fp_to_g1(
+                core::mem::size_of_val(&buffer) as u64,
+                buffer.as_ptr() as u64,
+                0,
+            );
+        }
+    }
+
+    unsafe fn map_fp2_to_g2<const N: usize>(iterations: usize) {
+        let buffer: [[u8; 96]; N] = core::array::from_fn(|i| {
+            let mut fp2 = [0u8; 96];
+            fp2.copy_from_slice(&G2_POINTS[i][..96]);
+            fp2
+        });
+        for _ in 0..iterations {
+            bls12381_map_fp2_to_g2(
+                core::mem::size_of_val(&buffer) as u64,
+                buffer.as_ptr() as u64,
+                0,
+            );
+        }
+    }
+
+    unsafe fn pairing_check<const N: usize>(iterations: usize) {
+        let buffer: [PairingElem; N] =
+            core::array::from_fn(|i| PairingElem(G1_POINTS[i], G2_POINTS[i]));
+        for _ in 0..iterations {
+            bls12381_pairing_check(core::mem::size_of_val(&buffer) as u64, buffer.as_ptr() as u64);
+        }
+    }
+
+    unsafe fn p1_decompress<const N: usize>(iterations: usize) {
+        let buffer: [[u8; 48]; N] = core::array::from_fn(|i| compress(&G1_POINTS[i]));
+        for _ in 0..iterations {
+            bls12381_p1_decompress(
+                core::mem::size_of_val(&buffer) as u64,
+                buffer.as_ptr() as u64,
+                0,
+            );
+        }
+    }
+
+    unsafe fn p2_decompress<const N: usize>(iterations: usize) {
+        let buffer: [[u8; 96]; N] = core::array::from_fn(|i| compress(&G2_POINTS[i]));
+        for _ in 0..iterations {
+            bls12381_p2_decompress(
+                core::mem::size_of_val(&buffer) as u64,
+                buffer.as_ptr() as u64,
+                0,
+            );
+        }
+    }
+
+    // Function to measure `bls12381_p1_sum_base` and `bls12381_p1_sum_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `p1_sum` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute p1_sum on 1 element 1k times.
+    #[no_mangle]
+    pub unsafe fn bls12381_p1_sum_1_1k() {
+        p1_sum::<1>(1_000);
+    }
+
+    // Function to measure `bls12381_p1_sum_base` and `bls12381_p1_sum_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `p1_sum` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute p1_sum on 10 elements 1k times.
+    #[no_mangle]
+    pub unsafe fn bls12381_p1_sum_10_1k() {
+        p1_sum::<10>(1_000);
+    }
+
+    // Function to measure `bls12381_p2_sum_base` and `bls12381_p2_sum_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `p2_sum` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute p2_sum on 1 element 1k times.
+    #[no_mangle]
+    pub unsafe fn bls12381_p2_sum_1_1k() {
+        p2_sum::<1>(1_000);
+    }
+
+    // Function to measure `bls12381_p2_sum_base` and `bls12381_p2_sum_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `p2_sum` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute p2_sum on 10 elements 1k times.
+    #[no_mangle]
+    pub unsafe fn bls12381_p2_sum_10_1k() {
+        p2_sum::<10>(1_000);
+    }
+
+    // Function to measure `bls12381_g1_multiexp_base` and `bls12381_g1_multiexp_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `g1_multiexp` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute g1_multiexp on 1 element 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_g1_multiexp_1_10() {
+        g1_multiexp::<1>(10);
+    }
+
+    // Function to measure `bls12381_g1_multiexp_base` and `bls12381_g1_multiexp_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `g1_multiexp` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute g1_multiexp on 10 elements 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_g1_multiexp_10_10() {
+        g1_multiexp::<10>(10);
+    }
+
+    // Function to measure `bls12381_g2_multiexp_base` and `bls12381_g2_multiexp_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `g2_multiexp` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute g2_multiexp on 1 element 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_g2_multiexp_1_10() {
+        g2_multiexp::<1>(10);
+    }
+
+    // Function to measure `bls12381_g2_multiexp_base` and `bls12381_g2_multiexp_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `g2_multiexp` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute g2_multiexp on 10 elements 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_g2_multiexp_10_10() {
+        g2_multiexp::<10>(10);
+    }
+
+    // Function to measure `bls12381_map_fp_to_g1_base` and `bls12381_map_fp_to_g1_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `map_fp_to_g1` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute map_fp_to_g1 on 1 element 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_map_fp_to_g1_1_10() {
+        map_fp_to_g1::<1>(10);
+    }
+
+    // Function to measure `bls12381_map_fp_to_g1_base` and `bls12381_map_fp_to_g1_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `map_fp_to_g1` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute map_fp_to_g1 on 10 elements 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_map_fp_to_g1_10_10() {
+        map_fp_to_g1::<10>(10);
+    }
+
+    // Function to measure `bls12381_map_fp2_to_g2_base` and `bls12381_map_fp2_to_g2_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `map_fp2_to_g2` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute map_fp2_to_g2 on 1 element 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_map_fp2_to_g2_1_10() {
+        map_fp2_to_g2::<1>(10);
+    }
+
+    // Function to measure `bls12381_map_fp2_to_g2_base` and `bls12381_map_fp2_to_g2_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `map_fp2_to_g2` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute map_fp2_to_g2 on 10 elements 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_map_fp2_to_g2_10_10() {
+        map_fp2_to_g2::<10>(10);
+    }
+
+    // Function to measure `bls12381_pairing_base` and `bls12381_pairing_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `pairing_check` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute pairing_check on 1 element 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_pairing_check_1_10() {
+        pairing_check::<1>(10);
+    }
+
+    // Function to measure `bls12381_pairing_base` and `bls12381_pairing_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `pairing_check` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute pairing_check on 10 elements 10 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_pairing_check_10_10() {
+        pairing_check::<10>(10);
+    }
+
+    // Function to measure `bls12381_p1_decompress_base` and `bls12381_p1_decompress_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `p1_decompress` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute p1_decompress on 1 element 100 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_p1_decompress_1_100() {
+        p1_decompress::<1>(100);
+    }
+
+    // Function to measure `bls12381_p1_decompress_base` and `bls12381_p1_decompress_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `p1_decompress` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute p1_decompress on 10 elements 100 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_p1_decompress_10_100() {
+        p1_decompress::<10>(100);
+    }
+
+    // Function to measure `bls12381_p2_decompress_base` and `bls12381_p2_decompress_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `p2_decompress` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute p2_decompress on 1 element 100 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_p2_decompress_1_100() {
+        p2_decompress::<1>(100);
+    }
+
+    // Function to measure `bls12381_p2_decompress_base` and `bls12381_p2_decompress_element`. Also measures
+    // `base`, `write_register_base`, and `write_register_byte`. However `p2_decompress` computation is
+    // more expensive than register writing so we are okay overcharging it.
+    // Compute p2_decompress on 10 elements 100 times.
+    #[no_mangle]
+    pub unsafe fn bls12381_p2_decompress_10_100() {
+        p2_decompress::<10>(100);
+    }
+}
+
 // ###############
 // # Storage API #
 // ###############
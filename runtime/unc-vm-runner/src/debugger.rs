@@ -0,0 +1,377 @@
+//! Step-through debugging of contracts on sandbox nodes.
+//!
+//! A debug call runs a contract method to completion exactly as it would run on chain, while
+//! recording a [`DebugStop`] every time execution reaches a breakpoint or, when single-stepping,
+//! any instruction of the debugged functions. Each stop captures the locals of the current
+//! function, the requested ranges of memory and the registers. Storage writes are recorded too,
+//! along with the position among the stops at which they happened.
+//!
+//! Execution is deterministic, so instead of suspending the node in the middle of a call, a
+//! debugger front-end navigates the recorded stops and re-runs the call with other options to
+//! look elsewhere.
+//!
+//! The types describing debug calls are always available, but only sandbox builds can run them.
+
+use serde_with::base64::Base64;
+use serde_with::serde_as;
+use std::collections::BTreeMap;
+use unc_primitives_core::serialize::dec_format;
+use unc_primitives_core::types::Gas;
+#[cfg(feature = "sandbox")]
+use {
+    crate::instrument::gas::{debug, profile},
+    crate::logic::errors::{FunctionCallError, PrepareError, VMRunnerError},
+    crate::logic::types::PromiseResult,
+    crate::logic::{ContractPrepareVersion, External, MemSlice, VMContext, VMOutcome},
+    crate::ContractCode,
+    std::collections::{BTreeSet, HashMap},
+    unc_parameters::vm::Config,
+    unc_parameters::RuntimeFeesConfig,
+};
+
+/// Number of stops recorded by default before the trace is truncated.
+pub const DEFAULT_MAX_STOPS: u32 = 1_000;
+
+/// Upper bound on [`DebugOptions::max_stops`], to keep the size of the trace in check.
+pub const MAX_STOPS: u32 = 100_000;
+
+/// Upper bound on the length of each captured memory range.
+pub const MAX_MEMORY_RANGE_LEN: u64 = 64 * 1024;
+
+/// What to record while running a debug call.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugOptions {
+    /// Names of the functions to stop at, as found in the `name` section of the contract. When
+    /// empty, all functions of the contract are debugged.
+    #[serde(default)]
+    pub breakpoints: Vec<String>,
+    /// Stop before every instruction of the debugged functions rather than only at their entry.
+    #[serde(default)]
+    pub single_step: bool,
+    /// Ranges of the guest memory to capture at every stop.
+    #[serde(default)]
+    pub memory: Vec<MemoryRange>,
+    /// Number of stops after which the trace is truncated, at most [`MAX_STOPS`].
+    #[serde(default = "default_max_stops")]
+    pub max_stops: u32,
+}
+
+fn default_max_stops() -> u32 {
+    DEFAULT_MAX_STOPS
+}
+
+impl Default for DebugOptions {
+    fn default() -> Self {
+        Self {
+            breakpoints: vec![],
+            single_step: false,
+            memory: vec![],
+            max_stops: DEFAULT_MAX_STOPS,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryRange {
+    pub offset: u64,
+    /// Length of the range, capped at [`MAX_MEMORY_RANGE_LEN`].
+    pub len: u64,
+}
+
+/// Everything recorded during a debug call.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugTrace {
+    pub stops: Vec<DebugStop>,
+    pub storage_changes: Vec<StorageChange>,
+    /// Breakpoints which do not name any function of the contract.
+    pub unresolved_breakpoints: Vec<String>,
+    /// Whether execution reached more stops than [`DebugOptions::max_stops`].
+    pub truncated: bool,
+}
+
+/// State of the execution right before an instruction is executed.
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DebugStop {
+    /// Index of the function in the contract as it was deployed.
+    pub function: u32,
+    pub function_name: Option<String>,
+    /// Index of the instruction within the function, starting from zero.
+    pub instruction: u32,
+    /// Byte offset of the instruction within the contract code.
+    pub code_offset: Option<u64>,
+    #[serde(with = "dec_format")]
+    pub burnt_gas: Gas,
+    /// Values of the locals of the function, parameters first.
+    pub locals: Vec<DebugValue>,
+    /// Contents of [`DebugOptions::memory`], in the same order.
+    pub memory: Vec<MemoryContents>,
+    #[serde_as(as = "BTreeMap<_, Base64>")]
+    pub registers: BTreeMap<u64, Vec<u8>>,
+}
+
+/// Value of a local. Floats are given as their bit patterns so that no NaN payload is lost.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum DebugValue {
+    I32(i32),
+    I64(i64),
+    F32(u32),
+    F64(u64),
+    /// The local has a type which cannot be captured, such as `v128`.
+    Unavailable,
+}
+
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MemoryContents {
+    pub offset: u64,
+    /// `None` if the range is not within the guest memory.
+    #[serde_as(as = "Option<Base64>")]
+    pub data: Option<Vec<u8>>,
+}
+
+#[serde_as]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StorageChange {
+    /// Number of stops recorded before the change.
+    pub stop: u32,
+    #[serde_as(as = "Base64")]
+    pub key: Vec<u8>,
+    /// The new value, `None` if the key was removed.
+    #[serde_as(as = "Option<Base64>")]
+    pub value: Option<Vec<u8>>,
+}
+
+/// Collects the [`DebugTrace`] from the events reported by the debug probes.
+#[cfg(feature = "sandbox")]
+pub(crate) struct Debugger {
+    options: DebugOptions,
+    functions: BTreeSet<u32>,
+    names: HashMap<u32, String>,
+    instruction_offsets: BTreeMap<u32, Vec<u64>>,
+    /// Locals reported by the probe currently executing.
+    locals: Vec<DebugValue>,
+    trace: DebugTrace,
+}
+
+#[cfg(feature = "sandbox")]
+impl Debugger {
+    /// Set up debugging of `code`, the contract as it was deployed.
+    pub(crate) fn new(code: &[u8], mut options: DebugOptions) -> Result<Self, PrepareError> {
+        options.max_stops = options.max_stops.min(MAX_STOPS);
+        let names = profile::function_names(code);
+        let mut instruction_offsets = debug::instruction_offsets(code)?;
+        let mut trace = DebugTrace::default();
+        let functions = if options.breakpoints.is_empty() {
+            instruction_offsets.keys().copied().collect()
+        } else {
+            let mut functions = BTreeSet::new();
+            for breakpoint in &options.breakpoints {
+                let matching: Vec<u32> = names
+                    .iter()
+                    .filter(|(function, name)| {
+                        *name == breakpoint && instruction_offsets.contains_key(*function)
+                    })
+                    .map(|(function, _)| *function)
+                    .collect();
+                if matching.is_empty() {
+                    trace.unresolved_breakpoints.push(breakpoint.clone());
+                }
+                functions.extend(matching);
+            }
+            functions
+        };
+        instruction_offsets.retain(|function, _| functions.contains(function));
+        Ok(Self { options, functions, names, instruction_offsets, locals: vec![], trace })
+    }
+
+    /// Indices of the functions to probe.
+    pub(crate) fn functions(&self) -> &BTreeSet<u32> {
+        &self.functions
+    }
+
+    pub(crate) fn single_step(&self) -> bool {
+        self.options.single_step
+    }
+
+    /// Record the value of a local reported by a probe with the given event code.
+    pub(crate) fn record_local(&mut self, event: u32, index: u32, value: u64) {
+        let value = match event {
+            debug::EVENT_LOCAL_I32 => DebugValue::I32(value as u32 as i32),
+            debug::EVENT_LOCAL_I64 => DebugValue::I64(value as i64),
+            debug::EVENT_LOCAL_F32 => DebugValue::F32(value as u32),
+            debug::EVENT_LOCAL_F64 => DebugValue::F64(value),
+            _ => DebugValue::Unavailable,
+        };
+        self.locals.resize(index as usize, DebugValue::Unavailable);
+        self.locals.push(value);
+    }
+
+    /// Record a stop before `instruction` of `function`, together with the locals reported right
+    /// before it.
+    pub(crate) fn record_stop<'r>(
+        &mut self,
+        function: u32,
+        instruction: u32,
+        burnt_gas: Gas,
+        memory: impl Fn(MemSlice) -> Option<Vec<u8>>,
+        registers: impl Iterator<Item = (u64, &'r [u8])>,
+    ) {
+        let locals = std::mem::take(&mut self.locals);
+        if self.trace.stops.len() >= self.options.max_stops as usize {
+            self.trace.truncated = true;
+            return;
+        }
+        let code_offset = self
+            .instruction_offsets
+            .get(&function)
+            .and_then(|offsets| offsets.get(instruction as usize))
+            .copied();
+        let memory = self
+            .options
+            .memory
+            .iter()
+            .map(|range| {
+                let len = range.len.min(MAX_MEMORY_RANGE_LEN);
+                let data = memory(MemSlice { ptr: range.offset, len });
+                MemoryContents { offset: range.offset, data }
+            })
+            .collect();
+        self.trace.stops.push(DebugStop {
+            function,
+            function_name: self.names.get(&function).cloned(),
+            instruction,
+            code_offset,
+            burnt_gas,
+            locals,
+            memory,
+            registers: registers.map(|(id, data)| (id, data.to_vec())).collect(),
+        });
+    }
+
+    /// Record a write to the storage, or a removal if there is no `value`.
+    pub(crate) fn record_storage_change(&mut self, key: &[u8], value: Option<&[u8]>) {
+        self.trace.storage_changes.push(StorageChange {
+            stop: self.trace.stops.len() as u32,
+            key: key.to_vec(),
+            value: value.map(<[u8]>::to_vec),
+        });
+    }
+
+    pub(crate) fn into_trace(self) -> DebugTrace {
+        self.trace
+    }
+}
+
+/// Run `method_name` of the contract like [`crate::run`] does, recording a [`DebugTrace`] into
+/// [`VMOutcome::debug_trace`] as requested by `options`.
+///
+/// Debug calls always run on Wasmtime and are never cached. They are only supported for contracts
+/// prepared with finite-wasm, i.e. since [`ContractPrepareVersion::V2`].
+#[cfg(feature = "sandbox")]
+#[allow(unused_variables)]
+pub fn run(
+    code: &ContractCode,
+    method_name: &str,
+    ext: &mut dyn External,
+    context: VMContext,
+    wasm_config: &Config,
+    fees_config: &RuntimeFeesConfig,
+    promise_results: &[PromiseResult],
+    options: DebugOptions,
+) -> Result<VMOutcome, VMRunnerError> {
+    if matches!(
+        wasm_config.limit_config.contract_prepare_version,
+        ContractPrepareVersion::V0 | ContractPrepareVersion::V1
+    ) {
+        let msg = "debugging requires contract prepare version V2 or newer".to_string();
+        return Ok(VMOutcome::nop_outcome(FunctionCallError::LinkError { msg }));
+    }
+    #[cfg(feature = "wasmtime_vm")]
+    {
+        let mut config = wasm_config.clone();
+        config.vm_kind = unc_parameters::vm::VMKind::Wasmtime;
+        crate::wasmtime_runner::WasmtimeVM::new(config).run_debug(
+            code,
+            method_name,
+            ext,
+            context,
+            fees_config,
+            promise_results,
+            options,
+        )
+    }
+    #[cfg(not(feature = "wasmtime_vm"))]
+    {
+        let msg = "debugging requires the wasmtime_vm feature".to_string();
+        Ok(VMOutcome::nop_outcome(FunctionCallError::LinkError { msg }))
+    }
+}
+
+#[cfg(all(test, feature = "sandbox"))]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = r#"(module
+        (import "env" "input" (func $input (param i64)))
+        (func $main (export "main") (nop))
+        (func $helper)
+        (func $other))"#;
+
+    #[test]
+    fn test_resolve_breakpoints() {
+        let wasm = wat::parse_str(CONTRACT).unwrap();
+        let options = DebugOptions {
+            breakpoints: vec!["helper".into(), "input".into(), "missing".into()],
+            ..DebugOptions::default()
+        };
+        let debugger = Debugger::new(&wasm, options).unwrap();
+        assert_eq!(debugger.functions().iter().copied().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(debugger.trace.unresolved_breakpoints, vec!["input", "missing"]);
+
+        let debugger = Debugger::new(&wasm, DebugOptions::default()).unwrap();
+        assert_eq!(debugger.functions().iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_record_stops() {
+        let wasm = wat::parse_str(CONTRACT).unwrap();
+        let options = DebugOptions {
+            memory: vec![MemoryRange { offset: 8, len: 2 }],
+            max_stops: 1,
+            ..DebugOptions::default()
+        };
+        let mut debugger = Debugger::new(&wasm, options).unwrap();
+        debugger.record_local(debug::EVENT_LOCAL_I32, 0, u64::from(u32::MAX));
+        debugger.record_local(debug::EVENT_LOCAL_UNAVAILABLE, 1, 0);
+        debugger.record_local(debug::EVENT_LOCAL_F64, 2, 1.5f64.to_bits());
+        let registers = [(1, &b"reg"[..])];
+        debugger.record_stop(
+            1,
+            1,
+            42,
+            |slice| Some(vec![0; slice.len as usize]),
+            registers.into_iter(),
+        );
+        debugger.record_storage_change(b"key", None);
+        debugger.record_stop(1, 2, 43, |_| None, std::iter::empty());
+
+        let trace = debugger.into_trace();
+        assert!(trace.truncated);
+        assert_eq!(trace.stops.len(), 1);
+        let stop = &trace.stops[0];
+        assert_eq!(stop.function_name.as_deref(), Some("main"));
+        assert_eq!(stop.code_offset.map(|offset| wasm[offset as usize]), Some(0x0b));
+        assert_eq!(
+            stop.locals,
+            vec![DebugValue::I32(-1), DebugValue::Unavailable, DebugValue::F64(1.5f64.to_bits())]
+        );
+        assert_eq!(stop.memory, vec![MemoryContents { offset: 8, data: Some(vec![0, 0]) }]);
+        assert_eq!(stop.registers, BTreeMap::from([(1, b"reg".to_vec())]));
+        assert_eq!(
+            trace.storage_changes,
+            vec![StorageChange { stop: 1, key: b"key".to_vec(), value: None }]
+        );
+    }
+}
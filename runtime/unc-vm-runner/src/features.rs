@@ -18,6 +18,11 @@ const MEMORY_CONTROL: bool = false;
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub(crate) struct WasmFeatures {
     sign_extension: bool,
+    bulk_memory: bool,
+    multi_value: bool,
+    reference_types: bool,
+    saturating_float_to_int: bool,
+    simd: bool,
 }
 
 impl From<crate::logic::ContractPrepareVersion> for WasmFeatures {
@@ -26,8 +31,32 @@ impl From<crate::logic::ContractPrepareVersion> for WasmFeatures {
             crate::logic::ContractPrepareVersion::V0 => false,
             crate::logic::ContractPrepareVersion::V1 => false,
             crate::logic::ContractPrepareVersion::V2 => true,
+            crate::logic::ContractPrepareVersion::V3 => true,
+            crate::logic::ContractPrepareVersion::V4 => true,
         };
-        WasmFeatures { sign_extension }
+        let proposals = match version {
+            crate::logic::ContractPrepareVersion::V0
+            | crate::logic::ContractPrepareVersion::V1
+            | crate::logic::ContractPrepareVersion::V2 => false,
+            crate::logic::ContractPrepareVersion::V3 | crate::logic::ContractPrepareVersion::V4 => {
+                true
+            }
+        };
+        let simd = match version {
+            crate::logic::ContractPrepareVersion::V0
+            | crate::logic::ContractPrepareVersion::V1
+            | crate::logic::ContractPrepareVersion::V2
+            | crate::logic::ContractPrepareVersion::V3 => false,
+            crate::logic::ContractPrepareVersion::V4 => true,
+        };
+        WasmFeatures {
+            sign_extension,
+            bulk_memory: proposals,
+            multi_value: proposals,
+            reference_types: proposals,
+            saturating_float_to_int: proposals,
+            simd,
+        }
     }
 }
 
@@ -37,18 +66,17 @@ impl From<WasmFeatures> for finite_wasm::wasmparser::WasmFeatures {
             floats: true,
             mutable_global: true,
             sign_extension: f.sign_extension,
+            reference_types: f.reference_types,
+            multi_value: f.multi_value,
+            bulk_memory: f.bulk_memory,
+            saturating_float_to_int: f.saturating_float_to_int,
+            simd: f.simd,
 
-            reference_types: REFERENCE_TYPES,
-            // wasmer singlepass compiler requires multi_value return values to be disabled.
-            multi_value: MULTI_VALUE,
-            bulk_memory: BULK_MEMORY,
-            simd: SIMD,
             threads: THREADS,
             tail_call: TAIL_CALL,
             multi_memory: MULTI_MEMORY,
             exceptions: EXCEPTIONS,
             memory64: MEMORY64,
-            saturating_float_to_int: SATURATING_FLOAT_TO_INT,
             relaxed_simd: RELAXED_SIMD,
             extended_const: EXTENDED_COST,
             component_model: COMPONENT_MODEL,
@@ -102,17 +130,17 @@ impl From<WasmFeatures> for unc_vm_types::Features {
         Self {
             mutable_global: true,
             sign_extension: f.sign_extension,
+            reference_types: f.reference_types,
+            bulk_memory: f.bulk_memory,
+            multi_value: f.multi_value,
+            saturating_float_to_int: f.saturating_float_to_int,
+            simd: f.simd,
 
             threads: THREADS,
-            reference_types: REFERENCE_TYPES,
-            simd: SIMD,
-            bulk_memory: BULK_MEMORY,
-            multi_value: MULTI_VALUE,
             tail_call: TAIL_CALL,
             multi_memory: MULTI_MEMORY,
             memory64: MEMORY64,
             exceptions: EXCEPTIONS,
-            saturating_float_to_int: SATURATING_FLOAT_TO_INT,
         }
     }
 }
@@ -150,13 +178,13 @@ impl From<WasmFeatures> for wasmer_types::Features {
 
 #[cfg(feature = "wasmtime_vm")]
 impl From<WasmFeatures> for wasmtime::Config {
-    fn from(_: WasmFeatures) -> Self {
+    fn from(f: WasmFeatures) -> Self {
         let mut config = wasmtime::Config::default();
         config.wasm_threads(THREADS);
-        config.wasm_reference_types(REFERENCE_TYPES);
-        config.wasm_simd(SIMD);
-        config.wasm_bulk_memory(BULK_MEMORY);
-        config.wasm_multi_value(MULTI_VALUE);
+        config.wasm_reference_types(f.reference_types);
+        config.wasm_simd(f.simd);
+        config.wasm_bulk_memory(f.bulk_memory);
+        config.wasm_multi_value(f.multi_value);
         config.wasm_multi_memory(MULTI_MEMORY);
         config.wasm_memory64(MEMORY64);
         config
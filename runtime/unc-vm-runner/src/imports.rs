@@ -84,6 +84,8 @@ imports! {
     // # Finite-wasm internals #
     // #########################
     @in internal: finite_wasm_gas<[gas: u64] -> []>,
+    @in internal: finite_wasm_gas_profiled<[gas: u64, function: u32] -> []>,
+    ##["sandbox"] @in internal: finite_wasm_gas_debug<[gas: u64, event: u32, index: u32, value: u64] -> []>,
     @in internal: finite_wasm_stack<[operand_size: u64, frame_size: u64] -> []>,
     @in internal: finite_wasm_unstack<[operand_size: u64, frame_size: u64] -> []>,
     // #############
@@ -222,6 +224,24 @@ imports! {
         beneficiary_id_len: u64,
         beneficiary_id_ptr: u64
     ] -> []>,
+    // ############################
+    // # Promise API yield/resume #
+    // ############################
+    #[yield_resume] promise_yield_create<[
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        gas: u64,
+        gas_weight: u64,
+        register_id: u64
+    ] -> [u64]>,
+    #[yield_resume] promise_yield_resume<[
+        data_id_len: u64,
+        data_id_ptr: u64,
+        payload_len: u64,
+        payload_ptr: u64
+    ] -> [u32]>,
     // #######################
     // # Promise API results #
     // #######################
@@ -254,6 +274,18 @@ imports! {
     #[alt_bn128] alt_bn128_g1_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     #[alt_bn128] alt_bn128_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
     // #############
+    // # BLS12-381 #
+    // #############
+    #[bls12381] bls12381_p1_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #[bls12381] bls12381_p2_sum<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #[bls12381] bls12381_g1_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #[bls12381] bls12381_g2_multiexp<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #[bls12381] bls12381_map_fp_to_g1<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #[bls12381] bls12381_map_fp2_to_g2<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #[bls12381] bls12381_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
+    #[bls12381] bls12381_p1_decompress<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #[bls12381] bls12381_p2_decompress<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    // #############
     // #  Sandbox  #
     // #############
     ##["sandbox"] sandbox_debug_log<[len: u64, ptr: u64] -> []>,
@@ -291,7 +323,7 @@ pub(crate) mod wasmer {
             ) => {
                 #[allow(unused_parens)]
                 fn $name( ctx: &mut wasmer_runtime::Ctx, $( $arg_name: $arg_type ),* ) -> Result<($( $returns ),*), VMLogicError> {
-                    const IS_GAS: bool = str_eq(stringify!($name), "gas") || str_eq(stringify!($name), "finite_wasm_gas");
+                    const IS_GAS: bool = str_eq(stringify!($name), "gas") || str_eq(stringify!($name), "finite_wasm_gas") || str_eq(stringify!($name), "finite_wasm_gas_profiled") || str_eq(stringify!($name), "finite_wasm_gas_debug");
                     let _span = if IS_GAS {
                         None
                     } else {
@@ -389,7 +421,7 @@ pub(crate) mod wasmer2 {
                     extern "C" fn $name(env: *mut VMLogic<'_>, $( $arg_name: $arg_type ),* )
                     -> Ret {
                         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                            const IS_GAS: bool = str_eq(stringify!($name), "gas") || str_eq(stringify!($name), "finite_wasm_gas");
+                            const IS_GAS: bool = str_eq(stringify!($name), "gas") || str_eq(stringify!($name), "finite_wasm_gas") || str_eq(stringify!($name), "finite_wasm_gas_profiled") || str_eq(stringify!($name), "finite_wasm_gas_debug");
                             let _span = if IS_GAS {
                                 None
                             } else {
@@ -542,7 +574,7 @@ pub(crate) mod unc_vm {
                     extern "C" fn $name(env: *mut VMLogic<'_>, $( $arg_name: $arg_type ),* )
                     -> Ret {
                         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                            const IS_GAS: bool = str_eq(stringify!($name), "gas") || str_eq(stringify!($name), "finite_wasm_gas");
+                            const IS_GAS: bool = str_eq(stringify!($name), "gas") || str_eq(stringify!($name), "finite_wasm_gas") || str_eq(stringify!($name), "finite_wasm_gas_profiled") || str_eq(stringify!($name), "finite_wasm_gas_debug");
                             let _span = if IS_GAS {
                                 None
                             } else {
@@ -672,7 +704,7 @@ pub(crate) mod wasmtime {
             ) => {
                 #[allow(unused_parens)]
                 fn $name(caller: wasmtime::Caller<'_, ()>, $( $arg_name: $arg_type ),* ) -> anyhow::Result<($( $returns ),*)> {
-                    const IS_GAS: bool = str_eq(stringify!($name), "gas") || str_eq(stringify!($name), "finite_wasm_gas");
+                    const IS_GAS: bool = str_eq(stringify!($name), "gas") || str_eq(stringify!($name), "finite_wasm_gas") || str_eq(stringify!($name), "finite_wasm_gas_profiled") || str_eq(stringify!($name), "finite_wasm_gas_debug");
                     let _span = if IS_GAS {
                         None
                     } else {
@@ -0,0 +1,399 @@
+//! Per-byte and per-element gas metering of the bulk memory and table instructions.
+//!
+//! `memory.copy`, `memory.fill`, `memory.init`, `table.copy`, `table.fill`, `table.init` and
+//! `table.grow` are single instructions whose cost depends on the number of bytes or table
+//! elements they touch, while finite-wasm only ever charges a constant amount per instruction. To
+//! account for the variable part, every such instruction in an already instrumented module is
+//! replaced with a call to a helper function appended to the module. The helper charges
+//! `len * byte_cost` or `len * element_cost` gas through the gas intrinsic before executing the
+//! original instruction.
+
+use crate::logic::errors::PrepareError;
+use finite_wasm::wasmparser as wp;
+use wasm_encoder::{Encode, Instruction, Section, SectionId, ValType};
+
+/// An instruction charged for the bytes or table elements it processes, with its immediates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BulkInstruction {
+    MemoryCopy,
+    MemoryFill,
+    MemoryInit { data_index: u32 },
+    TableCopy { dst_table: u32, src_table: u32 },
+    TableFill { table: u32 },
+    TableInit { elem_index: u32, table: u32 },
+    TableGrow { table: u32 },
+}
+
+impl BulkInstruction {
+    fn from_operator(op: &wp::Operator) -> Option<Self> {
+        Some(match *op {
+            wp::Operator::MemoryCopy { .. } => Self::MemoryCopy,
+            wp::Operator::MemoryFill { .. } => Self::MemoryFill,
+            wp::Operator::MemoryInit { data_index, .. } => Self::MemoryInit { data_index },
+            wp::Operator::TableCopy { dst_table, src_table } => {
+                Self::TableCopy { dst_table, src_table }
+            }
+            wp::Operator::TableFill { table } => Self::TableFill { table },
+            wp::Operator::TableInit { elem_index, table } => Self::TableInit { elem_index, table },
+            wp::Operator::TableGrow { table } => Self::TableGrow { table },
+            _ => return None,
+        })
+    }
+
+    fn processes_table_elements(&self) -> bool {
+        !matches!(self, Self::MemoryCopy | Self::MemoryFill | Self::MemoryInit { .. })
+    }
+
+    /// Encoded type of the helper function, which has the same signature as the instruction.
+    ///
+    /// The number of bytes or elements processed is the last parameter in all cases.
+    fn helper_type(&self, table_types: &[ValType]) -> Result<Vec<u8>, PrepareError> {
+        let table_type = |table: u32| {
+            table_types.get(table as usize).copied().ok_or(PrepareError::Deserialization)
+        };
+        let (params, results) = match *self {
+            Self::TableFill { table } => {
+                (vec![ValType::I32, table_type(table)?, ValType::I32], vec![])
+            }
+            Self::TableGrow { table } => {
+                (vec![table_type(table)?, ValType::I32], vec![ValType::I32])
+            }
+            _ => (vec![ValType::I32; 3], vec![]),
+        };
+        let mut ty = vec![0x60];
+        params.encode(&mut ty);
+        results.encode(&mut ty);
+        Ok(ty)
+    }
+
+    fn instruction(&self) -> Instruction<'static> {
+        match *self {
+            Self::MemoryCopy => Instruction::MemoryCopy { src_mem: 0, dst_mem: 0 },
+            Self::MemoryFill => Instruction::MemoryFill(0),
+            Self::MemoryInit { data_index } => Instruction::MemoryInit { mem: 0, data_index },
+            Self::TableCopy { dst_table, src_table } => {
+                Instruction::TableCopy { src_table, dst_table }
+            }
+            Self::TableFill { table } => Instruction::TableFill(table),
+            Self::TableInit { elem_index, table } => Instruction::TableInit { elem_index, table },
+            Self::TableGrow { table } => Instruction::TableGrow(table),
+        }
+    }
+}
+
+/// Replace the bulk memory and table instructions in `code` with calls that first charge
+/// `byte_cost` gas for every byte or `element_cost` gas for every table element processed.
+///
+/// `gas_module` and `gas_name` identify the imported gas intrinsic taking a single `i64` argument.
+/// Instructions with a zero cost are left as is, and modules not using any of the charged
+/// instructions are returned unchanged.
+pub(crate) fn inject_bulk_memory_gas(
+    code: &[u8],
+    gas_module: &str,
+    gas_name: &str,
+    byte_cost: u64,
+    element_cost: u64,
+) -> Result<Vec<u8>, PrepareError> {
+    let cost_of = |instruction: &BulkInstruction| {
+        if instruction.processes_table_elements() {
+            element_cost
+        } else {
+            byte_cost
+        }
+    };
+    let mut imported_functions = 0u32;
+    let mut defined_functions = 0u32;
+    let mut type_count = 0u32;
+    let mut gas_function = None;
+    let mut table_types = Vec::new();
+    // Every distinct instruction gets its own helper, in the order of their first use.
+    let mut helpers: Vec<BulkInstruction> = Vec::new();
+    for payload in wp::Parser::new(0).parse_all(code) {
+        match payload.map_err(|_| PrepareError::Deserialization)? {
+            wp::Payload::TypeSection(reader) => type_count = reader.count(),
+            wp::Payload::FunctionSection(reader) => defined_functions = reader.count(),
+            wp::Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(|_| PrepareError::Deserialization)?;
+                    if let wp::TypeRef::Func(_) = import.ty {
+                        if import.module == gas_module && import.name == gas_name {
+                            gas_function = Some(imported_functions);
+                        }
+                        imported_functions += 1;
+                    }
+                }
+            }
+            wp::Payload::TableSection(reader) => {
+                for table in reader {
+                    let table = table.map_err(|_| PrepareError::Deserialization)?;
+                    table_types.push(match table.ty.element_type {
+                        wp::RefType::FUNCREF => ValType::FUNCREF,
+                        wp::RefType::EXTERNREF => ValType::EXTERNREF,
+                        _ => return Err(PrepareError::Deserialization),
+                    });
+                }
+            }
+            wp::Payload::CodeSectionEntry(body) => {
+                for instruction in find_bulk_instructions(&body)? {
+                    let (_, _, instruction) = instruction?;
+                    if cost_of(&instruction) != 0 && !helpers.contains(&instruction) {
+                        helpers.push(instruction);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if helpers.is_empty() {
+        return Ok(code.to_vec());
+    }
+    let gas_function = gas_function.ok_or_else(|| {
+        tracing::error!("gas intrinsic is not imported by the instrumented module");
+        PrepareError::Serialization
+    })?;
+    let first_helper = imported_functions + defined_functions;
+    let helper_count = helpers.len() as u32;
+
+    let mut output = Vec::with_capacity(code.len() + 64 * helpers.len());
+    let mut code_section = wasm_encoder::CodeSection::new();
+    let mut remaining_bodies = 0u32;
+    for payload in wp::Parser::new(0).parse_all(code) {
+        let payload = payload.map_err(|_| PrepareError::Deserialization)?;
+        match payload {
+            wp::Payload::Version { range, .. } => output.extend(&code[range]),
+            wp::Payload::TypeSection(reader) => {
+                let mut types = Vec::new();
+                for helper in &helpers {
+                    types.extend(helper.helper_type(&table_types)?);
+                }
+                extend_section(
+                    &mut output,
+                    code,
+                    SectionId::Type,
+                    reader.range(),
+                    helper_count,
+                    &types,
+                )?
+            }
+            wp::Payload::FunctionSection(reader) => {
+                let mut functions = Vec::new();
+                for index in 0..helper_count {
+                    (type_count + index).encode(&mut functions);
+                }
+                extend_section(
+                    &mut output,
+                    code,
+                    SectionId::Function,
+                    reader.range(),
+                    helper_count,
+                    &functions,
+                )?
+            }
+            wp::Payload::CodeSectionStart { count, .. } => remaining_bodies = count,
+            wp::Payload::CodeSectionEntry(body) => {
+                let range = body.range();
+                let mut new_body = Vec::with_capacity(range.len());
+                let mut copied_up_to = range.start;
+                for instruction in find_bulk_instructions(&body)? {
+                    let (offset, end, instruction) = instruction?;
+                    let Some(helper) = helpers.iter().position(|h| *h == instruction) else {
+                        continue;
+                    };
+                    new_body.extend(&code[copied_up_to..offset]);
+                    Instruction::Call(first_helper + helper as u32).encode(&mut new_body);
+                    copied_up_to = end;
+                }
+                new_body.extend(&code[copied_up_to..range.end]);
+                code_section.raw(&new_body);
+                remaining_bodies -= 1;
+                if remaining_bodies == 0 {
+                    for helper in &helpers {
+                        code_section.function(&helper_function(
+                            gas_function,
+                            cost_of(helper),
+                            helper,
+                        ));
+                    }
+                    code_section.append_to(&mut output);
+                }
+            }
+            wp::Payload::End(_) => {}
+            payload => {
+                let (id, range) = payload.as_section().ok_or(PrepareError::Deserialization)?;
+                output.push(id);
+                range.len().encode(&mut output);
+                output.extend(&code[range]);
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Iterate over the charged instructions within the function body, yielding their start and end
+/// offsets and the instruction.
+fn find_bulk_instructions<'a>(
+    body: &wp::FunctionBody<'a>,
+) -> Result<
+    impl Iterator<Item = Result<(usize, usize, BulkInstruction), PrepareError>> + 'a,
+    PrepareError,
+> {
+    let mut reader = body.get_operators_reader().map_err(|_| PrepareError::Deserialization)?;
+    Ok(std::iter::from_fn(move || loop {
+        if reader.eof() {
+            return None;
+        }
+        let (op, offset) = match reader.read_with_offset() {
+            Ok(op) => op,
+            Err(_) => return Some(Err(PrepareError::Deserialization)),
+        };
+        if let Some(instruction) = BulkInstruction::from_operator(&op) {
+            return Some(Ok((offset, reader.original_position(), instruction)));
+        }
+    }))
+}
+
+/// Build a function that charges for the bytes or elements processed by `instruction` before
+/// executing it.
+fn helper_function(
+    gas_function: u32,
+    cost: u64,
+    instruction: &BulkInstruction,
+) -> wasm_encoder::Function {
+    let param_count = match instruction {
+        BulkInstruction::TableGrow { .. } => 2,
+        _ => 3,
+    };
+    let len = param_count - 1;
+    let mut function = wasm_encoder::Function::new([]);
+    function
+        .instruction(&Instruction::LocalGet(len))
+        .instruction(&Instruction::I64ExtendI32U)
+        .instruction(&Instruction::I64Const(cost as i64))
+        .instruction(&Instruction::I64Mul)
+        .instruction(&Instruction::Call(gas_function));
+    for param in 0..param_count {
+        function.instruction(&Instruction::LocalGet(param));
+    }
+    function.instruction(&instruction.instruction()).instruction(&Instruction::End);
+    function
+}
+
+/// Copy a vector-like section, appending `extra_count` already encoded entries to it.
+pub(super) fn extend_section(
+    output: &mut Vec<u8>,
+    code: &[u8],
+    id: SectionId,
+    range: std::ops::Range<usize>,
+    extra_count: u32,
+    extra: &[u8],
+) -> Result<(), PrepareError> {
+    let mut reader = wp::BinaryReader::new_with_offset(&code[range.clone()], range.start);
+    let count = reader.read_var_u32().map_err(|_| PrepareError::Deserialization)?;
+    let entries = &code[reader.original_position()..range.end];
+    let mut content = Vec::with_capacity(range.len() + extra.len() + 5);
+    (count + extra_count).encode(&mut content);
+    content.extend(entries);
+    content.extend(extra);
+    id.encode(output);
+    content.len().encode(output);
+    output.extend(content);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAS_IMPORT: &str = r#"(import "internal" "finite_wasm_gas" (func (param i64)))"#;
+
+    fn instrument(wat: &str) -> Vec<u8> {
+        let wasm = wat::parse_str(wat).unwrap();
+        let instrumented =
+            inject_bulk_memory_gas(&wasm, "internal", "finite_wasm_gas", 7, 11).unwrap();
+        let features =
+            crate::features::WasmFeatures::from(crate::logic::ContractPrepareVersion::V3);
+        wp::Validator::new_with_features(features.into()).validate_all(&instrumented).unwrap();
+        instrumented
+    }
+
+    #[test]
+    fn test_without_bulk_memory_unchanged() {
+        let wat = format!(
+            r#"(module {GAS_IMPORT} (memory 1) (func (export "main") (i64.store (i32.const 0) (i64.const 1))))"#
+        );
+        assert_eq!(instrument(&wat), wat::parse_str(&wat).unwrap());
+    }
+
+    #[test]
+    fn test_bulk_memory_calls_helpers() {
+        let wat = format!(
+            r#"(module
+                {GAS_IMPORT}
+                (memory 1)
+                (func (export "main")
+                    (memory.copy (i32.const 0) (i32.const 10) (i32.const 20))
+                    (memory.fill (i32.const 0) (i32.const 42) (i32.const 20))))"#
+        );
+        let instrumented = instrument(&wat);
+        let printed = wasmprinter::print_bytes(&instrumented).unwrap();
+        // Both instructions are only left in the appended helpers, each preceded by the charge.
+        assert_eq!(printed.matches("memory.copy").count(), 1);
+        assert_eq!(printed.matches("memory.fill").count(), 1);
+        assert_eq!(printed.matches("call 0").count(), 2);
+        assert!(printed.contains("call 2"));
+        assert!(printed.contains("call 3"));
+        assert!(printed.contains("i64.const 7"));
+    }
+
+    #[test]
+    fn test_memory_init_and_table_instructions_call_helpers() {
+        let wat = format!(
+            r#"(module
+                {GAS_IMPORT}
+                (memory 1)
+                (table 2 funcref)
+                (func $f)
+                (elem func $f)
+                (data "abcd")
+                (func (export "main")
+                    (memory.init 0 (i32.const 0) (i32.const 0) (i32.const 4))
+                    (memory.init 0 (i32.const 4) (i32.const 0) (i32.const 4))
+                    (table.init 0 (i32.const 0) (i32.const 0) (i32.const 1))
+                    (table.copy (i32.const 1) (i32.const 0) (i32.const 1))
+                    (table.fill (i32.const 0) (ref.null func) (i32.const 2))
+                    (drop (table.grow (ref.null func) (i32.const 3)))))"#
+        );
+        let instrumented = instrument(&wat);
+        let printed = wasmprinter::print_bytes(&instrumented).unwrap();
+        // Every distinct instruction gets a single helper, called in place of the instruction.
+        for instruction in ["memory.init", "table.init", "table.copy", "table.fill", "table.grow"] {
+            assert_eq!(printed.matches(instruction).count(), 1, "{instruction}");
+        }
+        for helper in 3..8 {
+            assert!(printed.contains(&format!("call {helper}")), "{helper}");
+        }
+        assert_eq!(printed.matches("i64.const 7").count(), 1);
+        assert_eq!(printed.matches("i64.const 11").count(), 4);
+    }
+
+    #[test]
+    fn test_zero_cost_instructions_unchanged() {
+        let wat = format!(
+            r#"(module
+                {GAS_IMPORT}
+                (memory 1)
+                (table 2 funcref)
+                (func (export "main")
+                    (memory.fill (i32.const 0) (i32.const 42) (i32.const 20))
+                    (drop (table.grow (ref.null func) (i32.const 3)))))"#
+        );
+        let wasm = wat::parse_str(&wat).unwrap();
+        let instrumented =
+            inject_bulk_memory_gas(&wasm, "internal", "finite_wasm_gas", 7, 0).unwrap();
+        let printed = wasmprinter::print_bytes(&instrumented).unwrap();
+        assert_eq!(printed.matches("memory.fill").count(), 1);
+        assert_eq!(printed.matches("table.grow").count(), 1);
+        assert!(printed.contains("call 2"));
+        assert!(!printed.contains("call 3"));
+    }
+}
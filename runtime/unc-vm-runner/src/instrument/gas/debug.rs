@@ -0,0 +1,394 @@
+//! Probes for the sandbox contract debugger.
+//!
+//! The debugger observes an execution through the calls a module makes to the host, so the
+//! probes are piggybacked onto the gas intrinsic finite-wasm already imports. Its import is
+//! redirected to a debug intrinsic which, besides the gas to charge, receives an event code, an
+//! index and a value. The existing gas calls report [`EVENT_GAS`] and the probes inserted before
+//! the instructions of interest charge no gas at all, so the instrumented module burns exactly as
+//! much gas as the original one.
+//!
+//! A probe first reports the value of each local of the function, then reports a stop at the
+//! instruction. Instructions are numbered within their function in the contract as it was
+//! deployed, skipping the code added by earlier instrumentation.
+
+use crate::logic::errors::PrepareError;
+use finite_wasm::wasmparser as wp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use wasm_encoder::{Encode, Instruction, Section, SectionId};
+
+/// Name of the debug intrinsic, imported from the same module as the regular gas intrinsic.
+pub(crate) const DEBUG_GAS_NAME: &str = "finite_wasm_gas_debug";
+
+/// Encoded `(func (param i64 i32 i32 i64))`, the signature of the debug intrinsic.
+const DEBUG_GAS_TYPE: [u8; 7] = [0x60, 4, 0x7e, 0x7f, 0x7f, 0x7e, 0];
+
+/// Only charges gas.
+pub(crate) const EVENT_GAS: u32 = 0;
+/// Reports an `i32` local, zero-extended to 64 bits.
+pub(crate) const EVENT_LOCAL_I32: u32 = 1;
+/// Reports an `i64` local.
+pub(crate) const EVENT_LOCAL_I64: u32 = 2;
+/// Reports the bits of an `f32` local, zero-extended to 64 bits.
+pub(crate) const EVENT_LOCAL_F32: u32 = 3;
+/// Reports the bits of an `f64` local.
+pub(crate) const EVENT_LOCAL_F64: u32 = 4;
+/// Reports a local whose value cannot be passed as an `i64`, such as a `v128` or a reference.
+pub(crate) const EVENT_LOCAL_UNAVAILABLE: u32 = 5;
+/// Reports reaching the instruction passed as the value, in the function passed as the index.
+pub(crate) const EVENT_STOP: u32 = 6;
+
+/// Number of locals reported by each probe, the remaining ones are left out.
+pub(crate) const MAX_REPORTED_LOCALS: u32 = 256;
+
+/// Insert debugger probes into `code`, an instrumented module calling the gas intrinsic
+/// `gas_module.gas_name`.
+///
+/// `functions` are the indices of the functions to probe in the contract as it was deployed. The
+/// entry of each such function is probed and, with `single_step`, every other instruction too.
+/// Modules not importing the gas intrinsic are returned unchanged.
+pub(crate) fn inject_debug_probes(
+    code: &[u8],
+    gas_module: &str,
+    gas_name: &str,
+    functions: &BTreeSet<u32>,
+    single_step: bool,
+) -> Result<Vec<u8>, PrepareError> {
+    let mut types = Vec::new();
+    let mut function_types = Vec::new();
+    let mut imported_functions = 0u32;
+    // Number of arguments of each function imported from `gas_module`, keyed by function index.
+    let mut instrumentation_functions = HashMap::new();
+    let mut gas_function = None;
+    for payload in wp::Parser::new(0).parse_all(code) {
+        match payload.map_err(|_| PrepareError::Deserialization)? {
+            wp::Payload::TypeSection(reader) => {
+                for ty in reader {
+                    match ty.map_err(|_| PrepareError::Deserialization)? {
+                        wp::Type::Func(ty) => types.push(ty.params().to_vec()),
+                        // Types other than functions cannot be used by a valid contract.
+                        #[allow(unreachable_patterns)]
+                        _ => types.push(Vec::new()),
+                    }
+                }
+            }
+            wp::Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(|_| PrepareError::Deserialization)?;
+                    if let wp::TypeRef::Func(ty) = import.ty {
+                        if import.module == gas_module {
+                            if import.name == gas_name {
+                                gas_function = Some(imported_functions);
+                            }
+                            let params = types.get(ty as usize).map_or(0, Vec::len);
+                            instrumentation_functions.insert(imported_functions, params);
+                        }
+                        imported_functions += 1;
+                    }
+                }
+            }
+            wp::Payload::FunctionSection(reader) => {
+                for ty in reader {
+                    function_types.push(ty.map_err(|_| PrepareError::Deserialization)?);
+                }
+            }
+            _ => {}
+        }
+    }
+    let Some(gas_function) = gas_function else { return Ok(code.to_vec()) };
+    let first_function = imported_functions - instrumentation_functions.len() as u32;
+    let debug_type = types.len() as u32;
+
+    let mut output = Vec::with_capacity(code.len() * 2);
+    let mut code_section = wasm_encoder::CodeSection::new();
+    let mut remaining_bodies = 0u32;
+    let mut defined = 0usize;
+    for payload in wp::Parser::new(0).parse_all(code) {
+        let payload = payload.map_err(|_| PrepareError::Deserialization)?;
+        match payload {
+            wp::Payload::Version { range, .. } => output.extend(&code[range]),
+            wp::Payload::TypeSection(reader) => super::bulk_memory::extend_section(
+                &mut output,
+                code,
+                SectionId::Type,
+                reader.range(),
+                1,
+                &DEBUG_GAS_TYPE,
+            )?,
+            wp::Payload::ImportSection(reader) => super::profile::redirect_import(
+                &mut output,
+                code,
+                reader,
+                gas_module,
+                gas_name,
+                DEBUG_GAS_NAME,
+                debug_type,
+            )?,
+            wp::Payload::CodeSectionStart { count, .. } => remaining_bodies = count,
+            wp::Payload::CodeSectionEntry(body) => {
+                let function = first_function + defined as u32;
+                let probed = functions.contains(&function);
+                let locals = if probed {
+                    let ty = function_types.get(defined).ok_or(PrepareError::Deserialization)?;
+                    let params = types.get(*ty as usize).ok_or(PrepareError::Deserialization)?;
+                    reported_locals(params, &body)?
+                } else {
+                    Vec::new()
+                };
+                let operators = read_operators(&body)?;
+                let instrumentation = instrumentation_operators(&operators, |index| {
+                    instrumentation_functions.get(&index).copied()
+                });
+
+                let range = body.range();
+                let mut new_body = Vec::with_capacity(range.len() * 2);
+                let mut copied_up_to = range.start;
+                let mut instruction = 0u32;
+                for (index, (op, offset)) in operators.iter().enumerate() {
+                    let offset = *offset;
+                    if instrumentation.contains(&index) {
+                        if let wp::Operator::Call { function_index } = op {
+                            if *function_index == gas_function {
+                                new_body.extend(&code[copied_up_to..offset]);
+                                copied_up_to = offset;
+                                Instruction::I32Const(EVENT_GAS as i32).encode(&mut new_body);
+                                Instruction::I32Const(0).encode(&mut new_body);
+                                Instruction::I64Const(0).encode(&mut new_body);
+                            }
+                        }
+                        continue;
+                    }
+                    if probed && (single_step || instruction == 0) {
+                        new_body.extend(&code[copied_up_to..offset]);
+                        copied_up_to = offset;
+                        encode_probe(&mut new_body, gas_function, &locals, function, instruction);
+                    }
+                    instruction += 1;
+                }
+                new_body.extend(&code[copied_up_to..range.end]);
+                code_section.raw(&new_body);
+                defined += 1;
+                remaining_bodies -= 1;
+                if remaining_bodies == 0 {
+                    code_section.append_to(&mut output);
+                }
+            }
+            wp::Payload::End(_) => {}
+            payload => {
+                let (id, range) = payload.as_section().ok_or(PrepareError::Deserialization)?;
+                output.push(id);
+                range.len().encode(&mut output);
+                output.extend(&code[range]);
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Byte offsets within `code` of the instructions of each defined function.
+///
+/// The offsets are listed in the order the instructions are numbered by the probes of
+/// [`inject_debug_probes`] when `code` is the contract as it was deployed.
+pub(crate) fn instruction_offsets(code: &[u8]) -> Result<BTreeMap<u32, Vec<u64>>, PrepareError> {
+    let mut offsets = BTreeMap::new();
+    let mut function = 0u32;
+    for payload in wp::Parser::new(0).parse_all(code) {
+        match payload.map_err(|_| PrepareError::Deserialization)? {
+            wp::Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(|_| PrepareError::Deserialization)?;
+                    if let wp::TypeRef::Func(_) = import.ty {
+                        function += 1;
+                    }
+                }
+            }
+            wp::Payload::CodeSectionEntry(body) => {
+                let operators = read_operators(&body)?;
+                offsets
+                    .insert(function, operators.iter().map(|(_, offset)| *offset as u64).collect());
+                function += 1;
+            }
+            _ => {}
+        }
+    }
+    Ok(offsets)
+}
+
+fn read_operators<'a>(
+    body: &wp::FunctionBody<'a>,
+) -> Result<Vec<(wp::Operator<'a>, usize)>, PrepareError> {
+    let mut reader = body.get_operators_reader().map_err(|_| PrepareError::Deserialization)?;
+    let mut operators = Vec::new();
+    while !reader.eof() {
+        operators.push(reader.read_with_offset().map_err(|_| PrepareError::Deserialization)?);
+    }
+    Ok(operators)
+}
+
+/// Positions of the operators added by instrumentation: the calls to its intrinsics together with
+/// the constants pushing their arguments right before them.
+///
+/// `arguments` returns the number of arguments of the given function if it is an intrinsic.
+fn instrumentation_operators(
+    operators: &[(wp::Operator<'_>, usize)],
+    arguments: impl Fn(u32) -> Option<usize>,
+) -> BTreeSet<usize> {
+    let mut positions = BTreeSet::new();
+    for (index, (op, _)) in operators.iter().enumerate() {
+        if let wp::Operator::Call { function_index } = op {
+            if let Some(arguments) = arguments(*function_index) {
+                positions.extend(index.saturating_sub(arguments)..=index);
+            }
+        }
+    }
+    positions
+}
+
+/// Types of the locals reported by the probes of a function with the given parameters and body.
+fn reported_locals(
+    params: &[wp::ValType],
+    body: &wp::FunctionBody<'_>,
+) -> Result<Vec<wp::ValType>, PrepareError> {
+    let limit = MAX_REPORTED_LOCALS as usize;
+    let mut locals: Vec<_> = params.iter().copied().take(limit).collect();
+    let reader = body.get_locals_reader().map_err(|_| PrepareError::Deserialization)?;
+    for local in reader {
+        let (count, ty) = local.map_err(|_| PrepareError::Deserialization)?;
+        let count = (count as usize).min(limit - locals.len());
+        locals.extend(std::iter::repeat(ty).take(count));
+    }
+    Ok(locals)
+}
+
+fn encode_probe(
+    sink: &mut Vec<u8>,
+    debug_function: u32,
+    locals: &[wp::ValType],
+    function: u32,
+    instruction: u32,
+) {
+    for (index, ty) in locals.iter().enumerate() {
+        let index = index as u32;
+        let mut value = Vec::with_capacity(8);
+        let event = match ty {
+            wp::ValType::I32 => {
+                Instruction::LocalGet(index).encode(&mut value);
+                Instruction::I64ExtendI32U.encode(&mut value);
+                EVENT_LOCAL_I32
+            }
+            wp::ValType::I64 => {
+                Instruction::LocalGet(index).encode(&mut value);
+                EVENT_LOCAL_I64
+            }
+            wp::ValType::F32 => {
+                Instruction::LocalGet(index).encode(&mut value);
+                Instruction::I32ReinterpretF32.encode(&mut value);
+                Instruction::I64ExtendI32U.encode(&mut value);
+                EVENT_LOCAL_F32
+            }
+            wp::ValType::F64 => {
+                Instruction::LocalGet(index).encode(&mut value);
+                Instruction::I64ReinterpretF64.encode(&mut value);
+                EVENT_LOCAL_F64
+            }
+            wp::ValType::V128 | wp::ValType::Ref(_) => {
+                Instruction::I64Const(0).encode(&mut value);
+                EVENT_LOCAL_UNAVAILABLE
+            }
+        };
+        Instruction::I64Const(0).encode(sink);
+        Instruction::I32Const(event as i32).encode(sink);
+        Instruction::I32Const(index as i32).encode(sink);
+        sink.extend(value);
+        Instruction::Call(debug_function).encode(sink);
+    }
+    Instruction::I64Const(0).encode(sink);
+    Instruction::I32Const(EVENT_STOP as i32).encode(sink);
+    Instruction::I32Const(function as i32).encode(sink);
+    Instruction::I64Const(i64::from(instruction)).encode(sink);
+    Instruction::Call(debug_function).encode(sink);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTRACT: &str = r#"(module
+        (import "env" "input" (func (param i64)))
+        (import "internal" "finite_wasm_stack" (func (param i64 i64)))
+        (import "internal" "finite_wasm_gas" (func (param i64)))
+        (func (export "main") (local f32)
+            (call 1 (i64.const 1) (i64.const 8))
+            (call 2 (i64.const 3))
+            (call 0 (i64.const 7))
+            (drop (i32.const 5)))
+        (func (param i32)
+            (call 2 (i64.const 1))
+            (nop)))"#;
+
+    fn instrument(functions: &[u32], single_step: bool) -> String {
+        let wasm = wat::parse_str(CONTRACT).unwrap();
+        let functions = functions.iter().copied().collect();
+        let instrumented =
+            inject_debug_probes(&wasm, "internal", "finite_wasm_gas", &functions, single_step)
+                .unwrap();
+        let features =
+            crate::features::WasmFeatures::from(crate::logic::ContractPrepareVersion::V3);
+        wp::Validator::new_with_features(features.into()).validate_all(&instrumented).unwrap();
+        let printed = wasmprinter::print_bytes(&instrumented).unwrap();
+        printed.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn test_gas_calls_are_redirected() {
+        let printed = instrument(&[], false);
+        assert!(printed.contains(r#"(import "internal" "finite_wasm_gas_debug""#));
+        assert!(printed.contains("(type (;4;) (func (param i64 i32 i32 i64)))"));
+        assert!(printed.contains("i64.const 3 i32.const 0 i32.const 0 i64.const 0 call 2"));
+        assert!(printed.contains("i64.const 1 i32.const 0 i32.const 0 i64.const 0 call 2"));
+        assert_eq!(printed.matches("call 2").count(), 2);
+    }
+
+    #[test]
+    fn test_function_entry_probe() {
+        let printed = instrument(&[1], false);
+        // The first instruction of the contract comes after the instrumentation prologue.
+        assert!(printed.contains(
+            "call 2 \
+             i64.const 0 i32.const 3 i32.const 0 local.get 0 i32.reinterpret_f32 \
+             i64.extend_i32_u call 2 \
+             i64.const 0 i32.const 6 i32.const 1 i64.const 0 call 2 \
+             i64.const 7 call 0"
+        ));
+        assert_eq!(printed.matches("i32.const 6").count(), 1);
+    }
+
+    #[test]
+    fn test_single_step_probes() {
+        let printed = instrument(&[2], true);
+        assert!(printed.contains(
+            "i64.const 0 i32.const 1 i32.const 0 local.get 0 i64.extend_i32_u call 2 \
+             i64.const 0 i32.const 6 i32.const 2 i64.const 1 call 2 )"
+        ));
+        // Before `nop` and before the `end` closing the function.
+        assert_eq!(printed.matches("i32.const 6").count(), 2);
+    }
+
+    #[test]
+    fn test_instruction_offsets() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (import "env" "input" (func (param i64)))
+                (func (export "main") (call 0 (i64.const 7)))
+                (func (nop)))"#,
+        )
+        .unwrap();
+        let offsets = instruction_offsets(&wasm).unwrap();
+        let main = &offsets[&1];
+        assert_eq!(main.len(), 3);
+        assert_eq!(wasm[main[0] as usize], 0x42);
+        assert_eq!(wasm[main[1] as usize], 0x10);
+        assert_eq!(wasm[main[2] as usize], 0x0b);
+        assert_eq!(offsets[&2].len(), 2);
+        assert!(!offsets.contains_key(&0));
+    }
+}
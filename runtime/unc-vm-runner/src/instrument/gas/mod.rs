@@ -4,6 +4,11 @@
 //! module into one that charges gas for code to be executed. See function documentation for usage
 //! and details.
 
+pub(crate) mod bulk_memory;
+#[cfg(feature = "sandbox")]
+pub(crate) mod debug;
+pub(crate) mod profile;
+pub(crate) mod simd;
 #[cfg(test)]
 mod validation;
 
@@ -0,0 +1,234 @@
+//! Attribution of the gas spent on Wasm instructions to the functions executing them.
+//!
+//! finite-wasm charges for instructions by calling the gas intrinsic at the start of every
+//! straight-line block. To find out how much gas each function burns, these calls in an already
+//! instrumented module are redirected to a profiling intrinsic which additionally receives the
+//! index of the calling function. Only view calls that explicitly ask for a profile are ever
+//! instrumented this way.
+
+use crate::logic::errors::PrepareError;
+use finite_wasm::wasmparser as wp;
+use std::collections::HashMap;
+use wasm_encoder::{Encode, Instruction, Section, SectionId};
+
+/// Name of the profiling gas intrinsic, imported from the same module as the regular one.
+pub(crate) const PROFILED_GAS_NAME: &str = "finite_wasm_gas_profiled";
+
+/// Encoded `(func (param i64 i32))`, the signature of the profiling intrinsic.
+const PROFILED_GAS_TYPE: [u8; 5] = [0x60, 2, 0x7e, 0x7f, 0];
+
+/// Redirect the calls to the gas intrinsic `gas_module.gas_name` within `code` to the profiling
+/// intrinsic.
+///
+/// The function index passed along is the one in the contract as it was deployed, so it can be
+/// symbolized with [`function_names`] of the original code. Functions added by instrumentation
+/// are numbered after the ones defined by the contract. Modules not importing the gas intrinsic
+/// are returned unchanged.
+pub(crate) fn inject_function_profiling(
+    code: &[u8],
+    gas_module: &str,
+    gas_name: &str,
+) -> Result<Vec<u8>, PrepareError> {
+    let mut type_count = 0u32;
+    let mut imported_functions = 0u32;
+    let mut instrumentation_functions = 0u32;
+    let mut gas_function = None;
+    for payload in wp::Parser::new(0).parse_all(code) {
+        match payload.map_err(|_| PrepareError::Deserialization)? {
+            wp::Payload::TypeSection(reader) => type_count = reader.count(),
+            wp::Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(|_| PrepareError::Deserialization)?;
+                    if let wp::TypeRef::Func(_) = import.ty {
+                        if import.module == gas_module {
+                            if import.name == gas_name {
+                                gas_function = Some(imported_functions);
+                            }
+                            instrumentation_functions += 1;
+                        }
+                        imported_functions += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    // A module that never charges gas has nothing to profile.
+    let Some(gas_function) = gas_function else { return Ok(code.to_vec()) };
+    let mut function = imported_functions - instrumentation_functions;
+
+    let mut output = Vec::with_capacity(code.len() + code.len() / 8);
+    let mut code_section = wasm_encoder::CodeSection::new();
+    let mut remaining_bodies = 0u32;
+    for payload in wp::Parser::new(0).parse_all(code) {
+        let payload = payload.map_err(|_| PrepareError::Deserialization)?;
+        match payload {
+            wp::Payload::Version { range, .. } => output.extend(&code[range]),
+            wp::Payload::TypeSection(reader) => super::bulk_memory::extend_section(
+                &mut output,
+                code,
+                SectionId::Type,
+                reader.range(),
+                1,
+                &PROFILED_GAS_TYPE,
+            )?,
+            wp::Payload::ImportSection(reader) => redirect_import(
+                &mut output,
+                code,
+                reader,
+                gas_module,
+                gas_name,
+                PROFILED_GAS_NAME,
+                type_count,
+            )?,
+            wp::Payload::CodeSectionStart { count, .. } => remaining_bodies = count,
+            wp::Payload::CodeSectionEntry(body) => {
+                let range = body.range();
+                let mut new_body = Vec::with_capacity(range.len() + 16);
+                let mut copied_up_to = range.start;
+                let mut reader =
+                    body.get_operators_reader().map_err(|_| PrepareError::Deserialization)?;
+                while !reader.eof() {
+                    let (op, offset) =
+                        reader.read_with_offset().map_err(|_| PrepareError::Deserialization)?;
+                    if let wp::Operator::Call { function_index } = op {
+                        if function_index == gas_function {
+                            new_body.extend(&code[copied_up_to..offset]);
+                            Instruction::I32Const(function as i32).encode(&mut new_body);
+                            copied_up_to = offset;
+                        }
+                    }
+                }
+                new_body.extend(&code[copied_up_to..range.end]);
+                code_section.raw(&new_body);
+                function += 1;
+                remaining_bodies -= 1;
+                if remaining_bodies == 0 {
+                    code_section.append_to(&mut output);
+                }
+            }
+            wp::Payload::End(_) => {}
+            payload => {
+                let (id, range) = payload.as_section().ok_or(PrepareError::Deserialization)?;
+                output.push(id);
+                range.len().encode(&mut output);
+                output.extend(&code[range]);
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Copy the import section, replacing the function import `module.name` with an import of the
+/// function `new_name` of type `new_type` from the same module.
+///
+/// The import keeps its position, so no function indices change.
+pub(super) fn redirect_import(
+    output: &mut Vec<u8>,
+    code: &[u8],
+    reader: wp::ImportSectionReader<'_>,
+    module: &str,
+    name: &str,
+    new_name: &str,
+    new_type: u32,
+) -> Result<(), PrepareError> {
+    let range = reader.range();
+    let mut content = Vec::with_capacity(range.len() + 16);
+    reader.count().encode(&mut content);
+    let mut imports = reader.into_iter_with_offsets().peekable();
+    while let Some(import) = imports.next() {
+        let (offset, import) = import.map_err(|_| PrepareError::Deserialization)?;
+        let end = match imports.peek() {
+            Some(Ok((next, _))) => *next,
+            Some(Err(_)) => return Err(PrepareError::Deserialization),
+            None => range.end,
+        };
+        if import.module == module && import.name == name {
+            import.module.encode(&mut content);
+            new_name.encode(&mut content);
+            content.push(0x00);
+            new_type.encode(&mut content);
+        } else {
+            content.extend(&code[offset..end]);
+        }
+    }
+    SectionId::Import.encode(output);
+    content.len().encode(output);
+    output.extend(content);
+    Ok(())
+}
+
+/// Function names from the `name` custom section of `code`, keyed by the function index.
+///
+/// The section is optional and purely informational, so a missing or malformed one simply results
+/// in fewer names.
+pub(crate) fn function_names(code: &[u8]) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for payload in wp::Parser::new(0).parse_all(code) {
+        let reader = match payload {
+            Ok(wp::Payload::CustomSection(reader)) if reader.name() == "name" => reader,
+            Ok(_) => continue,
+            Err(_) => break,
+        };
+        let subsections = wp::NameSectionReader::new(reader.data(), reader.data_offset());
+        for subsection in subsections {
+            if let Ok(wp::Name::Function(map)) = subsection {
+                for naming in map.into_iter().flatten() {
+                    names.insert(naming.index, naming.name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instrument(wat: &str) -> String {
+        let wasm = wat::parse_str(wat).unwrap();
+        let instrumented = inject_function_profiling(&wasm, "internal", "finite_wasm_gas").unwrap();
+        let features =
+            crate::features::WasmFeatures::from(crate::logic::ContractPrepareVersion::V3);
+        wp::Validator::new_with_features(features.into()).validate_all(&instrumented).unwrap();
+        let printed = wasmprinter::print_bytes(&instrumented).unwrap();
+        printed.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn test_gas_calls_pass_function_index() {
+        let printed = instrument(
+            r#"(module
+                (import "env" "input" (func (param i64)))
+                (import "internal" "finite_wasm_gas" (func (param i64)))
+                (func (export "main")
+                    (call 1 (i64.const 3))
+                    (call 3))
+                (func
+                    (call 1 (i64.const 5))
+                    (call 0 (i64.const 0))))"#,
+        );
+        assert!(printed.contains(r#"(import "internal" "finite_wasm_gas_profiled""#));
+        assert!(printed.contains("(type (;2;) (func (param i64 i32)))"));
+        // The instrumentation import does not count towards the contract function indices.
+        assert!(printed.contains("i64.const 3 i32.const 1 call 1"));
+        assert!(printed.contains("i64.const 5 i32.const 2 call 1"));
+        assert_eq!(printed.matches("i32.const").count(), 2);
+    }
+
+    #[test]
+    fn test_function_names() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (import "env" "input" (func $input (param i64)))
+                (func $main (export "main"))
+                (func))"#,
+        )
+        .unwrap();
+        let names = function_names(&wasm);
+        assert_eq!(names.get(&0).map(String::as_str), Some("input"));
+        assert_eq!(names.get(&1).map(String::as_str), Some("main"));
+        assert_eq!(names.get(&2), None);
+    }
+}
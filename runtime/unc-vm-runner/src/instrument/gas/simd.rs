@@ -0,0 +1,137 @@
+//! Gas costs of the fixed-width SIMD instructions.
+//!
+//! finite-wasm charges every instruction a constant amount, which is the regular op cost for
+//! everything but the SIMD instructions. Those are instead grouped in families by how expensive
+//! they are to execute, each with its own cost.
+
+use crate::logic::Config;
+use finite_wasm::wasmparser::Operator;
+
+/// Gas cost of the SIMD instructions of each family.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SimdOpCosts {
+    /// Loads, stores, bitwise operations, comparisons, lane accesses and integer arithmetic not
+    /// covered by the other families.
+    pub(crate) basic: u64,
+    /// Integer multiplications, including the widening and dot product ones.
+    pub(crate) mul: u64,
+    /// Floating point arithmetic, rounding and conversions.
+    pub(crate) float: u64,
+    /// `i8x16.shuffle` and `i8x16.swizzle`.
+    pub(crate) shuffle: u64,
+}
+
+impl SimdOpCosts {
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            basic: u64::from(config.simd_op_cost),
+            mul: u64::from(config.simd_mul_op_cost),
+            float: u64::from(config.simd_float_op_cost),
+            shuffle: u64::from(config.simd_shuffle_op_cost),
+        }
+    }
+
+    /// Cost of the SIMD instruction `op`.
+    ///
+    /// Instructions not listed in any of the other families are basic ones.
+    pub(crate) fn cost(&self, op: &Operator) -> u64 {
+        match op {
+            Operator::I8x16Shuffle { .. } | Operator::I8x16Swizzle => self.shuffle,
+            Operator::I16x8Mul
+            | Operator::I32x4Mul
+            | Operator::I64x2Mul
+            | Operator::I16x8Q15MulrSatS
+            | Operator::I16x8ExtMulLowI8x16S
+            | Operator::I16x8ExtMulHighI8x16S
+            | Operator::I16x8ExtMulLowI8x16U
+            | Operator::I16x8ExtMulHighI8x16U
+            | Operator::I32x4ExtMulLowI16x8S
+            | Operator::I32x4ExtMulHighI16x8S
+            | Operator::I32x4ExtMulLowI16x8U
+            | Operator::I32x4ExtMulHighI16x8U
+            | Operator::I64x2ExtMulLowI32x4S
+            | Operator::I64x2ExtMulHighI32x4S
+            | Operator::I64x2ExtMulLowI32x4U
+            | Operator::I64x2ExtMulHighI32x4U
+            | Operator::I32x4DotI16x8S
+            | Operator::I16x8ExtAddPairwiseI8x16S
+            | Operator::I16x8ExtAddPairwiseI8x16U
+            | Operator::I32x4ExtAddPairwiseI16x8S
+            | Operator::I32x4ExtAddPairwiseI16x8U => self.mul,
+            Operator::F32x4Add
+            | Operator::F32x4Sub
+            | Operator::F32x4Mul
+            | Operator::F32x4Div
+            | Operator::F32x4Min
+            | Operator::F32x4Max
+            | Operator::F32x4PMin
+            | Operator::F32x4PMax
+            | Operator::F32x4Sqrt
+            | Operator::F32x4Ceil
+            | Operator::F32x4Floor
+            | Operator::F32x4Trunc
+            | Operator::F32x4Nearest
+            | Operator::F64x2Add
+            | Operator::F64x2Sub
+            | Operator::F64x2Mul
+            | Operator::F64x2Div
+            | Operator::F64x2Min
+            | Operator::F64x2Max
+            | Operator::F64x2PMin
+            | Operator::F64x2PMax
+            | Operator::F64x2Sqrt
+            | Operator::F64x2Ceil
+            | Operator::F64x2Floor
+            | Operator::F64x2Trunc
+            | Operator::F64x2Nearest
+            | Operator::I32x4TruncSatF32x4S
+            | Operator::I32x4TruncSatF32x4U
+            | Operator::I32x4TruncSatF64x2SZero
+            | Operator::I32x4TruncSatF64x2UZero
+            | Operator::F32x4ConvertI32x4S
+            | Operator::F32x4ConvertI32x4U
+            | Operator::F64x2ConvertLowI32x4S
+            | Operator::F64x2ConvertLowI32x4U
+            | Operator::F32x4DemoteF64x2Zero
+            | Operator::F64x2PromoteLowF32x4 => self.float,
+            _ => self.basic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SimdOpCosts;
+    use finite_wasm::wasmparser::{MemArg, Operator};
+
+    const COSTS: SimdOpCosts = SimdOpCosts { basic: 1, mul: 2, float: 3, shuffle: 4 };
+
+    #[test]
+    fn test_families() {
+        let memarg = MemArg { align: 4, max_align: 4, offset: 0, memory: 0 };
+        for (op, cost) in [
+            (Operator::V128Load { memarg }, COSTS.basic),
+            (Operator::V128Bitselect, COSTS.basic),
+            (Operator::I8x16AddSatU, COSTS.basic),
+            (Operator::I32x4ExtractLane { lane: 0 }, COSTS.basic),
+            (Operator::F32x4Abs, COSTS.basic),
+            (Operator::F64x2Lt, COSTS.basic),
+            (Operator::F32x4Splat, COSTS.basic),
+            (Operator::I16x8Mul, COSTS.mul),
+            (Operator::I64x2ExtMulHighI32x4U, COSTS.mul),
+            (Operator::I32x4DotI16x8S, COSTS.mul),
+            (Operator::I16x8Q15MulrSatS, COSTS.mul),
+            (Operator::I32x4ExtAddPairwiseI16x8U, COSTS.mul),
+            (Operator::F32x4Mul, COSTS.float),
+            (Operator::F64x2PMin, COSTS.float),
+            (Operator::F64x2Nearest, COSTS.float),
+            (Operator::F32x4ConvertI32x4U, COSTS.float),
+            (Operator::I32x4TruncSatF64x2SZero, COSTS.float),
+            (Operator::F32x4DemoteF64x2Zero, COSTS.float),
+            (Operator::I8x16Shuffle { lanes: [0; 16] }, COSTS.shuffle),
+            (Operator::I8x16Swizzle, COSTS.shuffle),
+        ] {
+            assert_eq!(COSTS.cost(&op), cost, "{op:?}");
+        }
+    }
+}
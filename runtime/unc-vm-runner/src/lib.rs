@@ -2,6 +2,7 @@
 
 mod cache;
 mod code;
+pub mod debugger;
 mod errors;
 mod features;
 mod imports;
@@ -27,6 +28,7 @@ mod wasmtime_runner;
 pub use crate::logic::with_ext_cost_counter;
 pub use cache::{get_contract_cache_key, precompile_contract, MockCompiledContractCache};
 pub use code::ContractCode;
+pub use profile::FunctionGasProfile;
 pub use profile::ProfileDataV2;
 pub use profile::ProfileDataV3;
 pub use runner::{run, VM};
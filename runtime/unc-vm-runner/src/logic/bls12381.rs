@@ -0,0 +1,358 @@
+//! BLS12-381 operations exposed to contracts.
+//!
+//! Points are encoded as in the ZCash serialization format used by
+//! [`blst`](https://github.com/supranational/blst): field elements are
+//! big-endian, an `Fp2` element `c0 + c1 * u` is encoded as `c1 || c0`, and the
+//! three most significant bits of the first byte are flags. Uncompressed points
+//! must have the compression flag unset, the point at infinity is encoded as
+//! `0x40` followed by zeros. Scalars are 32-byte little-endian integers.
+
+use super::{HostError, VMLogicError};
+use blst::{
+    blst_bendian_from_fp, blst_final_exp, blst_fp, blst_fp12, blst_fp12_is_one, blst_fp12_mul,
+    blst_fp12_one, blst_fp2, blst_fp_from_bendian, blst_map_to_g1, blst_map_to_g2,
+    blst_miller_loop, blst_p1, blst_p1_add_or_double, blst_p1_affine, blst_p1_affine_in_g1,
+    blst_p1_affine_is_inf, blst_p1_affine_serialize, blst_p1_cneg, blst_p1_deserialize,
+    blst_p1_from_affine, blst_p1_mult, blst_p1_to_affine, blst_p1_uncompress, blst_p2,
+    blst_p2_add_or_double, blst_p2_affine, blst_p2_affine_in_g2, blst_p2_affine_is_inf,
+    blst_p2_affine_serialize, blst_p2_cneg, blst_p2_deserialize, blst_p2_from_affine, blst_p2_mult,
+    blst_p2_to_affine, blst_p2_uncompress, BLST_ERROR,
+};
+
+const BOOL_SIZE: usize = 1;
+const SCALAR_SIZE: usize = 256 / 8;
+const FP_SIZE: usize = 384 / 8;
+const FP2_SIZE: usize = FP_SIZE * 2;
+const G1_SIZE: usize = FP_SIZE * 2;
+const G2_SIZE: usize = FP2_SIZE * 2;
+const G1_COMPRESSED_SIZE: usize = FP_SIZE;
+const G2_COMPRESSED_SIZE: usize = FP2_SIZE;
+
+/// Flag set in the first byte of a compressed point.
+const COMPRESSION_FLAG: u8 = 0x80;
+
+pub(super) struct InvalidInput {
+    pub(super) msg: String,
+}
+
+impl InvalidInput {
+    fn new(msg: &str, bad_value: &[u8]) -> InvalidInput {
+        let msg = format!("{msg}: {bad_value:X?}");
+        InvalidInput { msg }
+    }
+}
+
+impl From<InvalidInput> for VMLogicError {
+    fn from(err: InvalidInput) -> Self {
+        HostError::Bls12381InvalidInput { msg: err.msg }.into()
+    }
+}
+
+pub(super) fn split_elements<const ELEMENT_SIZE: usize>(
+    data: &[u8],
+) -> Result<&[[u8; ELEMENT_SIZE]], InvalidInput> {
+    stdx::as_chunks_exact(data).map_err(|e| InvalidInput { msg: e.to_string() })
+}
+
+const P1_SUM_ELEMENT_SIZE: usize = BOOL_SIZE + G1_SIZE;
+
+pub(super) fn p1_sum(
+    elements: &[[u8; P1_SUM_ELEMENT_SIZE]],
+) -> Result<[u8; G1_SIZE], InvalidInput> {
+    let mut res = blst_p1::default();
+    for chunk in elements {
+        let (sign, point) = stdx::split_array(chunk);
+        let sign = decode_bool(sign)?;
+        let mut point = g1_from_affine(&decode_g1(point)?);
+        let mut sum = blst_p1::default();
+        // SAFETY: all pointers come from references to initialized values.
+        unsafe {
+            blst_p1_cneg(&mut point, sign);
+            blst_p1_add_or_double(&mut sum, &res, &point);
+        }
+        res = sum;
+    }
+    Ok(encode_g1(&res))
+}
+
+const P2_SUM_ELEMENT_SIZE: usize = BOOL_SIZE + G2_SIZE;
+
+pub(super) fn p2_sum(
+    elements: &[[u8; P2_SUM_ELEMENT_SIZE]],
+) -> Result<[u8; G2_SIZE], InvalidInput> {
+    let mut res = blst_p2::default();
+    for chunk in elements {
+        let (sign, point) = stdx::split_array(chunk);
+        let sign = decode_bool(sign)?;
+        let mut point = g2_from_affine(&decode_g2(point)?);
+        let mut sum = blst_p2::default();
+        // SAFETY: all pointers come from references to initialized values.
+        unsafe {
+            blst_p2_cneg(&mut point, sign);
+            blst_p2_add_or_double(&mut sum, &res, &point);
+        }
+        res = sum;
+    }
+    Ok(encode_g2(&res))
+}
+
+const G1_MULTIEXP_ELEMENT_SIZE: usize = G1_SIZE + SCALAR_SIZE;
+
+pub(super) fn g1_multiexp(
+    elements: &[[u8; G1_MULTIEXP_ELEMENT_SIZE]],
+) -> Result<[u8; G1_SIZE], InvalidInput> {
+    let mut res = blst_p1::default();
+    for chunk in elements {
+        let (point, scalar): (_, &[u8; SCALAR_SIZE]) = stdx::split_array(chunk);
+        let point = g1_from_affine(&decode_g1_in_subgroup(point)?);
+        let mut product = blst_p1::default();
+        let mut sum = blst_p1::default();
+        // SAFETY: all pointers come from references to initialized values and
+        // `scalar` is exactly `SCALAR_SIZE * 8` bits long.
+        unsafe {
+            blst_p1_mult(&mut product, &point, scalar.as_ptr(), SCALAR_SIZE * 8);
+            blst_p1_add_or_double(&mut sum, &res, &product);
+        }
+        res = sum;
+    }
+    Ok(encode_g1(&res))
+}
+
+const G2_MULTIEXP_ELEMENT_SIZE: usize = G2_SIZE + SCALAR_SIZE;
+
+pub(super) fn g2_multiexp(
+    elements: &[[u8; G2_MULTIEXP_ELEMENT_SIZE]],
+) -> Result<[u8; G2_SIZE], InvalidInput> {
+    let mut res = blst_p2::default();
+    for chunk in elements {
+        let (point, scalar): (_, &[u8; SCALAR_SIZE]) = stdx::split_array(chunk);
+        let point = g2_from_affine(&decode_g2_in_subgroup(point)?);
+        let mut product = blst_p2::default();
+        let mut sum = blst_p2::default();
+        // SAFETY: all pointers come from references to initialized values and
+        // `scalar` is exactly `SCALAR_SIZE * 8` bits long.
+        unsafe {
+            blst_p2_mult(&mut product, &point, scalar.as_ptr(), SCALAR_SIZE * 8);
+            blst_p2_add_or_double(&mut sum, &res, &product);
+        }
+        res = sum;
+    }
+    Ok(encode_g2(&res))
+}
+
+pub(super) fn map_fp_to_g1(elements: &[[u8; FP_SIZE]]) -> Result<Vec<u8>, InvalidInput> {
+    let mut res = Vec::with_capacity(elements.len() * G1_SIZE);
+    for element in elements {
+        let fp = decode_fp(element)?;
+        let mut point = blst_p1::default();
+        // SAFETY: `fp` is a valid field element and a null `v` is allowed by
+        // blst, in which case only `u` is mapped.
+        unsafe { blst_map_to_g1(&mut point, &fp, std::ptr::null()) };
+        res.extend_from_slice(&encode_g1(&point));
+    }
+    Ok(res)
+}
+
+pub(super) fn map_fp2_to_g2(elements: &[[u8; FP2_SIZE]]) -> Result<Vec<u8>, InvalidInput> {
+    let mut res = Vec::with_capacity(elements.len() * G2_SIZE);
+    for element in elements {
+        let fp2 = decode_fp2(element)?;
+        let mut point = blst_p2::default();
+        // SAFETY: `fp2` is a valid field element and a null `v` is allowed by
+        // blst, in which case only `u` is mapped.
+        unsafe { blst_map_to_g2(&mut point, &fp2, std::ptr::null()) };
+        res.extend_from_slice(&encode_g2(&point));
+    }
+    Ok(res)
+}
+
+const PAIRING_CHECK_ELEMENT_SIZE: usize = G1_SIZE + G2_SIZE;
+
+pub(super) fn pairing_check(
+    elements: &[[u8; PAIRING_CHECK_ELEMENT_SIZE]],
+) -> Result<bool, InvalidInput> {
+    // SAFETY: blst returns a pointer to a static constant.
+    let mut acc: blst_fp12 = unsafe { *blst_fp12_one() };
+    for chunk in elements {
+        let (g1, g2) = stdx::split_array(chunk);
+        let g1 = decode_g1_in_subgroup(g1)?;
+        let g2 = decode_g2_in_subgroup(g2)?;
+        // SAFETY: all pointers come from references to initialized values.
+        unsafe {
+            // The pairing with the point at infinity is the identity, skip it.
+            if blst_p1_affine_is_inf(&g1) || blst_p2_affine_is_inf(&g2) {
+                continue;
+            }
+            let mut pairing = acc;
+            blst_miller_loop(&mut pairing, &g2, &g1);
+            let prev = acc;
+            blst_fp12_mul(&mut acc, &prev, &pairing);
+        }
+    }
+    let mut res = acc;
+    // SAFETY: all pointers come from references to initialized values.
+    Ok(unsafe {
+        blst_final_exp(&mut res, &acc);
+        blst_fp12_is_one(&res)
+    })
+}
+
+pub(super) fn p1_decompress(
+    elements: &[[u8; G1_COMPRESSED_SIZE]],
+) -> Result<Vec<u8>, InvalidInput> {
+    let mut res = Vec::with_capacity(elements.len() * G1_SIZE);
+    for element in elements {
+        let mut point = blst_p1_affine::default();
+        // SAFETY: `element` is exactly as long as blst expects.
+        let err = unsafe { blst_p1_uncompress(&mut point, element.as_ptr()) };
+        check_blst_error(err, "G1 point", element)?;
+        let mut serialized = [0u8; G1_SIZE];
+        // SAFETY: `serialized` is exactly as long as blst expects.
+        unsafe { blst_p1_affine_serialize(serialized.as_mut_ptr(), &point) };
+        res.extend_from_slice(&serialized);
+    }
+    Ok(res)
+}
+
+pub(super) fn p2_decompress(
+    elements: &[[u8; G2_COMPRESSED_SIZE]],
+) -> Result<Vec<u8>, InvalidInput> {
+    let mut res = Vec::with_capacity(elements.len() * G2_SIZE);
+    for element in elements {
+        let mut point = blst_p2_affine::default();
+        // SAFETY: `element` is exactly as long as blst expects.
+        let err = unsafe { blst_p2_uncompress(&mut point, element.as_ptr()) };
+        check_blst_error(err, "G2 point", element)?;
+        let mut serialized = [0u8; G2_SIZE];
+        // SAFETY: `serialized` is exactly as long as blst expects.
+        unsafe { blst_p2_affine_serialize(serialized.as_mut_ptr(), &point) };
+        res.extend_from_slice(&serialized);
+    }
+    Ok(res)
+}
+
+fn encode_g1(point: &blst_p1) -> [u8; G1_SIZE] {
+    let mut affine = blst_p1_affine::default();
+    let mut res = [0u8; G1_SIZE];
+    // SAFETY: `res` is exactly as long as blst expects.
+    unsafe {
+        blst_p1_to_affine(&mut affine, point);
+        blst_p1_affine_serialize(res.as_mut_ptr(), &affine);
+    }
+    res
+}
+
+fn encode_g2(point: &blst_p2) -> [u8; G2_SIZE] {
+    let mut affine = blst_p2_affine::default();
+    let mut res = [0u8; G2_SIZE];
+    // SAFETY: `res` is exactly as long as blst expects.
+    unsafe {
+        blst_p2_to_affine(&mut affine, point);
+        blst_p2_affine_serialize(res.as_mut_ptr(), &affine);
+    }
+    res
+}
+
+fn g1_from_affine(point: &blst_p1_affine) -> blst_p1 {
+    let mut res = blst_p1::default();
+    // SAFETY: all pointers come from references to initialized values.
+    unsafe { blst_p1_from_affine(&mut res, point) };
+    res
+}
+
+fn g2_from_affine(point: &blst_p2_affine) -> blst_p2 {
+    let mut res = blst_p2::default();
+    // SAFETY: all pointers come from references to initialized values.
+    unsafe { blst_p2_from_affine(&mut res, point) };
+    res
+}
+
+/// Decodes an uncompressed G1 point, checking that it lies on the curve.
+fn decode_g1(raw: &[u8; G1_SIZE]) -> Result<blst_p1_affine, InvalidInput> {
+    if raw[0] & COMPRESSION_FLAG != 0 {
+        return Err(InvalidInput::new("compression flag is set for uncompressed G1 point", raw));
+    }
+    let mut point = blst_p1_affine::default();
+    // SAFETY: `raw` is exactly as long as blst expects.
+    let err = unsafe { blst_p1_deserialize(&mut point, raw.as_ptr()) };
+    check_blst_error(err, "G1 point", raw)?;
+    Ok(point)
+}
+
+/// Decodes an uncompressed G2 point, checking that it lies on the curve.
+fn decode_g2(raw: &[u8; G2_SIZE]) -> Result<blst_p2_affine, InvalidInput> {
+    if raw[0] & COMPRESSION_FLAG != 0 {
+        return Err(InvalidInput::new("compression flag is set for uncompressed G2 point", raw));
+    }
+    let mut point = blst_p2_affine::default();
+    // SAFETY: `raw` is exactly as long as blst expects.
+    let err = unsafe { blst_p2_deserialize(&mut point, raw.as_ptr()) };
+    check_blst_error(err, "G2 point", raw)?;
+    Ok(point)
+}
+
+/// Like [`decode_g1`] but also checks that the point is in the prime order
+/// subgroup.
+fn decode_g1_in_subgroup(raw: &[u8; G1_SIZE]) -> Result<blst_p1_affine, InvalidInput> {
+    let point = decode_g1(raw)?;
+    // SAFETY: `point` is a valid point on the curve.
+    if !unsafe { blst_p1_affine_in_g1(&point) } {
+        return Err(InvalidInput::new("G1 point is not in the subgroup", raw));
+    }
+    Ok(point)
+}
+
+/// Like [`decode_g2`] but also checks that the point is in the prime order
+/// subgroup.
+fn decode_g2_in_subgroup(raw: &[u8; G2_SIZE]) -> Result<blst_p2_affine, InvalidInput> {
+    let point = decode_g2(raw)?;
+    // SAFETY: `point` is a valid point on the curve.
+    if !unsafe { blst_p2_affine_in_g2(&point) } {
+        return Err(InvalidInput::new("G2 point is not in the subgroup", raw));
+    }
+    Ok(point)
+}
+
+fn decode_fp(raw: &[u8; FP_SIZE]) -> Result<blst_fp, InvalidInput> {
+    let mut fp = blst_fp::default();
+    let mut canonical = [0u8; FP_SIZE];
+    // SAFETY: `raw` and `canonical` are exactly as long as blst expects.
+    unsafe {
+        blst_fp_from_bendian(&mut fp, raw.as_ptr());
+        blst_bendian_from_fp(canonical.as_mut_ptr(), &fp);
+    }
+    // `blst_fp_from_bendian` silently reduces its input modulo p, so values
+    // which are not in the canonical form don't survive the round trip.
+    if &canonical != raw {
+        return Err(InvalidInput::new("invalid fp", raw));
+    }
+    Ok(fp)
+}
+
+fn decode_fp2(raw: &[u8; FP2_SIZE]) -> Result<blst_fp2, InvalidInput> {
+    let (c1, c0) = stdx::split_array(raw);
+    let c0 = decode_fp(c0)?;
+    let c1 = decode_fp(c1)?;
+    Ok(blst_fp2 { fp: [c0, c1] })
+}
+
+fn decode_bool(raw: &[u8; BOOL_SIZE]) -> Result<bool, InvalidInput> {
+    match raw {
+        [0] => Ok(false),
+        [1] => Ok(true),
+        _ => Err(InvalidInput::new("invalid bool", raw)),
+    }
+}
+
+fn check_blst_error(err: BLST_ERROR, what: &str, raw: &[u8]) -> Result<(), InvalidInput> {
+    match err {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        BLST_ERROR::BLST_POINT_NOT_ON_CURVE => {
+            Err(InvalidInput::new(&format!("{what} is not on the curve"), raw))
+        }
+        BLST_ERROR::BLST_POINT_NOT_IN_GROUP => {
+            Err(InvalidInput::new(&format!("{what} is not in the subgroup"), raw))
+        }
+        _ => Err(InvalidInput::new(&format!("invalid {what} encoding"), raw)),
+    }
+}
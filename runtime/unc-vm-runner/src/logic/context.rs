@@ -55,4 +55,9 @@ impl VMContext {
     pub fn is_view(&self) -> bool {
         self.view_config.is_some()
     }
+
+    /// Whether this is a view call asking for the gas burnt by each contract function.
+    pub fn profiles_functions(&self) -> bool {
+        self.view_config.as_ref().map_or(false, |config| config.profile_functions)
+    }
 }
@@ -270,6 +270,35 @@ pub trait External {
         receiver_id: AccountId,
     ) -> Result<ReceiptIndex, VMLogicError>;
 
+    /// Create a receipt to `receiver_id` which will wait for a single data dependency that is
+    /// only delivered once [`submit_promise_resume_data`](Self::submit_promise_resume_data) is
+    /// called with the returned data id, or the yield times out.
+    ///
+    /// Returns the index of the new receipt together with the data id it waits on.
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver_id` - account id of the receiver of the yielded receipt
+    fn create_promise_yield_receipt(
+        &mut self,
+        receiver_id: AccountId,
+    ) -> Result<(ReceiptIndex, CryptoHash), VMLogicError>;
+
+    /// Deliver `data` to the receipt yielded with the given `data_id`.
+    ///
+    /// Returns `false` if there is no pending yield for the current account with this data id,
+    /// e.g. because it has already been resumed or has timed out.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_id` - the data id returned when the yield was created
+    /// * `data` - the payload to pass to the yielded receipt
+    fn submit_promise_resume_data(
+        &mut self,
+        data_id: CryptoHash,
+        data: Vec<u8>,
+    ) -> Result<bool, VMLogicError>;
+
     /// Attach the [`CreateAccountAction`] action to an existing receipt.
     ///
     /// # Arguments
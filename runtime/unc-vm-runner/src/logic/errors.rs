@@ -136,6 +136,10 @@ pub enum PrepareError {
     TooManyFunctions,
     /// Contract contains too many locals.
     TooManyLocals,
+    /// Contract contains too many tables.
+    TooManyTables,
+    /// Contract contains a table which may have too many elements.
+    TooManyTableElements,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, strum::IntoStaticStr)]
@@ -206,6 +210,13 @@ pub enum HostError {
     /// Invalid input to ed25519 signature verification function (e.g. signature cannot be
     /// derived from bytes).
     Ed25519VerifyInvalidInput { msg: String },
+    /// Yield payload length exceeds the maximum permitted.
+    YieldPayloadLength { length: u64, limit: u64 },
+    /// Yield resumption data id is malformed.
+    DataIdMalformed,
+    /// Invalid input to bls12381 family of functions (e.g., point which isn't
+    /// on the curve or in the subgroup).
+    Bls12381InvalidInput { msg: String },
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -286,6 +297,8 @@ impl fmt::Display for PrepareError {
             Memory => "Error creating memory.",
             TooManyFunctions => "Too many functions in contract.",
             TooManyLocals => "Too many locals declared in the contract.",
+            TooManyTables => "Too many tables declared in the contract.",
+            TooManyTableElements => "Too many elements in a table of the contract.",
         })
     }
 }
@@ -435,6 +448,11 @@ impl std::fmt::Display for HostError {
             Ed25519VerifyInvalidInput { msg } => {
                 write!(f, "ED25519 signature verification error: {}", msg)
             }
+            YieldPayloadLength { length, limit } => {
+                write!(f, "Yield resume payload length {} exceeds the limit {}", length, limit)
+            }
+            DataIdMalformed => write!(f, "Yield resume data id is malformed"),
+            Bls12381InvalidInput { msg } => write!(f, "Bls12381 invalid input: {}", msg),
         }
     }
 }
@@ -6,7 +6,7 @@ use super::types::{PromiseIndex, PromiseResult, ReceiptIndex, ReturnData};
 use super::utils::split_method_names;
 use super::ValuePtr;
 use super::{HostError, VMLogicError};
-use crate::ProfileDataV3;
+use crate::{FunctionGasProfile, ProfileDataV3};
 use std::mem::size_of;
 use unc_crypto::Secp256K1Signature;
 use unc_parameters::vm::{Config, StorageGetMode};
@@ -14,6 +14,7 @@ use unc_parameters::{
     transfer_exec_fee, transfer_send_fee, ActionCosts, ExtCosts, RuntimeFeesConfig,
 };
 use unc_primitives_core::config::ViewConfig;
+use unc_primitives_core::hash::CryptoHash;
 use unc_primitives_core::types::{
     AccountId, Balance, Compute, EpochHeight, Gas, GasWeight, StorageUsage,
 };
@@ -67,6 +68,13 @@ pub struct VMLogic<'a> {
 
     /// Stores the amount of stack space remaining
     remaining_stack: u64,
+
+    /// Gas burnt by each function, collected only when the module is instrumented for profiling.
+    function_profile: Option<FunctionGasProfile>,
+
+    /// Records the debug trace, set only when the module is instrumented with debug probes.
+    #[cfg(feature = "sandbox")]
+    debugger: Option<crate::debugger::Debugger>,
 }
 
 /// Promises API allows to create a DAG-structure that defines dependencies between smart contract
@@ -137,7 +145,7 @@ impl<'a> VMLogic<'a> {
         let current_account_balance = context.account_balance + context.attached_deposit;
         let current_storage_usage = context.storage_usage;
         let max_gas_burnt = match context.view_config {
-            Some(ViewConfig { max_gas_burnt: max_gas_burnt_view }) => max_gas_burnt_view,
+            Some(ViewConfig { max_gas_burnt: max_gas_burnt_view, .. }) => max_gas_burnt_view,
             None => config.limit_config.max_gas_burnt,
         };
 
@@ -166,6 +174,9 @@ impl<'a> VMLogic<'a> {
             promises: vec![],
             total_log_length: 0,
             remaining_stack: u64::from(config.limit_config.max_stack_height),
+            function_profile: None,
+            #[cfg(feature = "sandbox")]
+            debugger: None,
         }
     }
 
@@ -201,6 +212,62 @@ impl<'a> VMLogic<'a> {
         self.gas(gas)
     }
 
+    /// Like [`Self::finite_wasm_gas`], but also attributes the burnt gas to the calling function.
+    ///
+    /// Only called by modules instrumented for per-function profiling, which must be enabled with
+    /// [`Self::enable_function_profile`] beforehand.
+    pub fn finite_wasm_gas_profiled(&mut self, gas: u64, function: u32) -> Result<()> {
+        let burnt_before = self.gas_counter.burnt_gas();
+        let result = self.gas(gas);
+        if let Some(profile) = &mut self.function_profile {
+            let burnt = self.gas_counter.burnt_gas().saturating_sub(burnt_before);
+            profile.add_function_gas(function, burnt);
+        }
+        result
+    }
+
+    /// Start collecting the gas burnt by each function into `profile`.
+    pub fn enable_function_profile(&mut self, profile: FunctionGasProfile) {
+        self.function_profile = Some(profile);
+    }
+
+    /// Like [`Self::finite_wasm_gas`], but also reports an event of the debug probes.
+    ///
+    /// Only called by modules instrumented with debug probes, see [`crate::debugger`].
+    #[cfg(feature = "sandbox")]
+    pub fn finite_wasm_gas_debug(
+        &mut self,
+        gas: u64,
+        event: u32,
+        index: u32,
+        value: u64,
+    ) -> Result<()> {
+        use crate::instrument::gas::debug;
+
+        self.gas(gas)?;
+        let Some(debugger) = &mut self.debugger else { return Ok(()) };
+        match event {
+            debug::EVENT_GAS => {}
+            debug::EVENT_STOP => {
+                let memory = &self.memory;
+                debugger.record_stop(
+                    index,
+                    value as u32,
+                    self.gas_counter.burnt_gas(),
+                    |slice| memory.view_for_free(slice).ok().map(|data| data.into_owned()),
+                    self.registers.iter(),
+                );
+            }
+            _ => debugger.record_local(event, index, value),
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "sandbox")]
+    pub(crate) fn enable_debugger(&mut self, debugger: crate::debugger::Debugger) {
+        self.debugger = Some(debugger);
+    }
+
     pub fn finite_wasm_stack(&mut self, operand_size: u64, frame_size: u64) -> Result<()> {
         self.remaining_stack =
             match self.remaining_stack.checked_sub(operand_size.saturating_add(frame_size)) {
@@ -902,6 +969,386 @@ impl<'a> VMLogic<'a> {
         Ok(res as u64)
     }
 
+    /// Computes the sum of signed G1 points on the BLS12-381 curve \sum_i
+    /// (-1)^{sign_i} p_{1 i}.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (sign:bool, p1:G1), where
+    ///   G1 is point (x:Fp, y:Fp) on BLS12-381,
+    ///   BLS12-381 is Y^2 = X^3 + 4 curve over Fp.
+    ///
+    ///   `value` is encoded as packed `[(u8, [u8; 96])]` slice. `0u8` is
+    ///   positive sign, `1u8` -- negative. Points are encoded uncompressed in
+    ///   the ZCash format: big-endian `x || y` with the three most significant
+    ///   bits of `x` used as flags, the point at infinity is `0x40` followed by
+    ///   zeros.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If point coordinates are not on the curve, the compression flag is set,
+    /// sign is not 0 or 1, or `value.len()%97!=0`, the function returns
+    /// `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_p1_sum_base + bls12381_p1_sum_element * num_elements`
+    pub fn bls12381_p1_sum(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_p1_sum_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p1_sum_element, elements.len() as u64)?;
+
+        let res = super::bls12381::p1_sum(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Computes the sum of signed G2 points on the BLS12-381 curve \sum_i
+    /// (-1)^{sign_i} p_{2 i}.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (sign:bool, p2:G2), where
+    ///   G2 is point (x:Fp2, y:Fp2) on BLS12-381 twist,
+    ///   BLS12-381 twist is Y^2 = X^3 + 4(u + 1) curve over Fp2,
+    ///   Fp2 is element c0 + c1 * u of the quadratic extension of Fp.
+    ///
+    ///   `value` is encoded as packed `[(u8, [u8; 192])]` slice. `0u8` is
+    ///   positive sign, `1u8` -- negative. Points are encoded uncompressed in
+    ///   the ZCash format: big-endian `x.c1 || x.c0 || y.c1 || y.c0` with the
+    ///   three most significant bits of `x.c1` used as flags.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If point coordinates are not on the curve, the compression flag is set,
+    /// sign is not 0 or 1, or `value.len()%193!=0`, the function returns
+    /// `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_p2_sum_base + bls12381_p2_sum_element * num_elements`
+    pub fn bls12381_p2_sum(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_p2_sum_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p2_sum_element, elements.len() as u64)?;
+
+        let res = super::bls12381::p2_sum(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Computes multiexp on the BLS12-381 curve \sum_i s_i p_{1 i}.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (p1:G1, s:u256), where G1 is encoded as for
+    ///   [`Self::bls12381_p1_sum`] and the scalar is a little-endian `u256`.
+    ///
+    ///   `value` is encoded as packed `[([u8; 96], [u8; 32])]` slice.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If point coordinates are not on the curve, point is not in the
+    /// subgroup, the compression flag is set or `value.len()%128!=0`, the
+    /// function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_g1_multiexp_base + bls12381_g1_multiexp_element * num_elements`
+    pub fn bls12381_g1_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_g1_multiexp_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_g1_multiexp_element, elements.len() as u64)?;
+
+        let res = super::bls12381::g1_multiexp(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Computes multiexp on the BLS12-381 curve twist \sum_i s_i p_{2 i}.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (p2:G2, s:u256), where G2 is encoded as for
+    ///   [`Self::bls12381_p2_sum`] and the scalar is a little-endian `u256`.
+    ///
+    ///   `value` is encoded as packed `[([u8; 192], [u8; 32])]` slice.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If point coordinates are not on the curve, point is not in the
+    /// subgroup, the compression flag is set or `value.len()%224!=0`, the
+    /// function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_g2_multiexp_base + bls12381_g2_multiexp_element * num_elements`
+    pub fn bls12381_g2_multiexp(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_g2_multiexp_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_g2_multiexp_element, elements.len() as u64)?;
+
+        let res = super::bls12381::g2_multiexp(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Maps each Fp element to a G1 point on the BLS12-381 curve using the
+    /// simplified SWU map followed by cofactor clearing, as specified by
+    /// RFC 9380 for the `BLS12381G1_XMD:SHA-256_SSWU_RO_` suite.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of big-endian Fp elements, encoded as packed
+    ///   `[[u8; 48]]` slice.
+    ///
+    /// The resulting points are written to the register as packed
+    /// `[[u8; 96]]` slice, in the same encoding as for
+    /// [`Self::bls12381_p1_sum`].
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If an element is not less than the field modulus or
+    /// `value.len()%48!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_map_fp_to_g1_base + bls12381_map_fp_to_g1_element * num_elements`
+    pub fn bls12381_map_fp_to_g1(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_map_fp_to_g1_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_map_fp_to_g1_element, elements.len() as u64)?;
+
+        let res = super::bls12381::map_fp_to_g1(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Maps each Fp2 element to a G2 point on the BLS12-381 curve twist using
+    /// the simplified SWU map followed by cofactor clearing, as specified by
+    /// RFC 9380 for the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of Fp2 elements encoded as big-endian `c1 || c0`,
+    ///   packed as `[[u8; 96]]` slice.
+    ///
+    /// The resulting points are written to the register as packed
+    /// `[[u8; 192]]` slice, in the same encoding as for
+    /// [`Self::bls12381_p2_sum`].
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If a component of an element is not less than the field modulus or
+    /// `value.len()%96!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_map_fp2_to_g2_base + bls12381_map_fp2_to_g2_element * num_elements`
+    pub fn bls12381_map_fp2_to_g2(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_map_fp2_to_g2_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_map_fp2_to_g2_element, elements.len() as u64)?;
+
+        let res = super::bls12381::map_fp2_to_g2(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Computes pairing check on the BLS12-381 curve.
+    /// \prod_i e(p_{1 i}, p_{2 i}) should be equal one, e(p1, p2) is the
+    /// optimal Ate pairing.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of (p1:G1, p2:G2), encoded as packed
+    ///   `[([u8; 96], [u8; 192])]` slice with points encoded as for
+    ///   [`Self::bls12381_p1_sum`] and [`Self::bls12381_p2_sum`].
+    ///
+    /// Returns `1` if the check succeeds and `0` otherwise. The check succeeds
+    /// for an empty sequence.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If point coordinates are not on the curve, point is not in the
+    /// subgroup, the compression flag is set or `value.len()%288!=0`, the
+    /// function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + bls12381_pairing_base + bls12381_pairing_element * num_elements`
+    pub fn bls12381_pairing_check(&mut self, value_len: u64, value_ptr: u64) -> Result<u64> {
+        self.gas_counter.pay_base(bls12381_pairing_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_pairing_element, elements.len() as u64)?;
+
+        let res = super::bls12381::pairing_check(elements)?;
+
+        Ok(res as u64)
+    }
+
+    /// Decompresses G1 points on the BLS12-381 curve.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of compressed G1 points in the ZCash format,
+    ///   packed as `[[u8; 48]]` slice. The compression flag must be set.
+    ///
+    /// The resulting points are written to the register as packed
+    /// `[[u8; 96]]` slice, in the same encoding as for
+    /// [`Self::bls12381_p1_sum`]. Decompression doesn't check that the points
+    /// are in the subgroup.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If a point is not on the curve, the compression flag is not set or
+    /// `value.len()%48!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_p1_decompress_base + bls12381_p1_decompress_element * num_elements`
+    pub fn bls12381_p1_decompress(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_p1_decompress_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p1_decompress_element, elements.len() as u64)?;
+
+        let res = super::bls12381::p1_decompress(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
+    /// Decompresses G2 points on the BLS12-381 curve twist.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - sequence of compressed G2 points in the ZCash format,
+    ///   packed as `[[u8; 96]]` slice. The compression flag must be set.
+    ///
+    /// The resulting points are written to the register as packed
+    /// `[[u8; 192]]` slice, in the same encoding as for
+    /// [`Self::bls12381_p2_sum`]. Decompression doesn't check that the points
+    /// are in the subgroup.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers
+    /// use more memory than the limit, the function returns
+    /// `MemoryAccessViolation`.
+    ///
+    /// If a point is not on the curve, the compression flag is not set or
+    /// `value.len()%96!=0`, the function returns `Bls12381InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes +
+    /// bls12381_p2_decompress_base + bls12381_p2_decompress_element * num_elements`
+    pub fn bls12381_p2_decompress(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(bls12381_p2_decompress_base)?;
+        let data = get_memory_or_register!(self, value_ptr, value_len)?;
+
+        let elements = super::bls12381::split_elements(&data)?;
+        self.gas_counter.pay_per(bls12381_p2_decompress_element, elements.len() as u64)?;
+
+        let res = super::bls12381::p2_decompress(elements)?;
+
+        self.registers.set(&mut self.gas_counter, &self.config.limit_config, register_id, res)
+    }
+
     /// Writes random seed into the register.
     ///
     /// # Errors
@@ -2035,6 +2482,147 @@ impl<'a> VMLogic<'a> {
         Ok(())
     }
 
+    /// Creates a promise that calls `method_name` on the current account once data is submitted
+    /// for it with `promise_yield_resume`, or once the yield times out after
+    /// `yield_timeout_length_in_blocks` blocks. The yielded call observes the submitted payload
+    /// as its single promise result, or `PromiseResult::Failed` on timeout.
+    ///
+    /// The data id that identifies the yield is written into `register_id`; it has to be passed
+    /// to `promise_yield_resume` to wake the call up.
+    ///
+    /// # Errors
+    ///
+    /// * If `method_name_len + method_name_ptr` or `arguments_len + arguments_ptr` points outside
+    ///   the memory of the guest or host returns `MemoryAccessViolation`.
+    /// * If `method_name` is empty returns `EmptyMethodName`.
+    /// * If called as view function returns `ProhibitedInView`.
+    /// * If the total number of promises exceeds `max_promises_per_function_call_action` limit
+    ///   returns `NumPromisesExceeded`.
+    ///
+    /// # Returns
+    ///
+    /// Index of the new promise that uniquely identifies it within the current execution of the
+    /// method.
+    ///
+    /// # Cost
+    ///
+    /// `burnt_gas := base + yield_create_base + yield_resume_base + yield_create_byte * num_bytes +
+    ///  dispatch cost of the receipt and its data dependency + dispatch function call fee +
+    ///  write_register_base + write_register_byte * 32`
+    ///
+    /// `yield_resume_base` prepays resolving the timeout of the yield, which the runtime does
+    /// whether the yield gets resumed or not.
+    /// `used_gas := burnt_gas + exec cost of the receipt + exec function call fee + prepaid gas`
+    pub fn promise_yield_create(
+        &mut self,
+        method_name_len: u64,
+        method_name_ptr: u64,
+        arguments_len: u64,
+        arguments_ptr: u64,
+        gas: Gas,
+        gas_weight: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(base)?;
+        if self.context.is_view() {
+            return Err(HostError::ProhibitedInView {
+                method_name: "promise_yield_create".to_string(),
+            }
+            .into());
+        }
+        self.gas_counter.pay_base(yield_create_base)?;
+        self.gas_counter.pay_base(yield_resume_base)?;
+
+        let method_name = get_memory_or_register!(self, method_name_ptr, method_name_len)?;
+        if method_name.is_empty() {
+            return Err(HostError::EmptyMethodName.into());
+        }
+        let arguments = get_memory_or_register!(self, arguments_ptr, arguments_len)?;
+        let method_name = method_name.into_owned();
+        let arguments = arguments.into_owned();
+        // Input can't be large enough to overflow, WebAssembly address space is 32-bits.
+        let num_bytes = method_name.len() as u64 + arguments.len() as u64;
+        self.gas_counter.pay_per(yield_create_byte, num_bytes)?;
+
+        // The yielded receipt is always addressed to the current account and waits on a single
+        // data dependency which is also produced by the current account.
+        self.pay_gas_for_new_receipt(true, &[true])?;
+        self.pay_action_base(ActionCosts::function_call_base, true)?;
+        self.pay_action_per_byte(ActionCosts::function_call_byte, num_bytes, true)?;
+        self.gas_counter.prepay_gas(gas)?;
+
+        let (new_receipt_idx, data_id) =
+            self.ext.create_promise_yield_receipt(self.context.current_account_id.clone())?;
+        self.ext.append_action_function_call_weight(
+            new_receipt_idx,
+            method_name,
+            arguments,
+            0,
+            gas,
+            GasWeight(gas_weight),
+        )?;
+
+        self.registers.set(
+            &mut self.gas_counter,
+            &self.config.limit_config,
+            register_id,
+            *data_id.as_bytes(),
+        )?;
+        self.checked_push_promise(Promise::Receipt(new_receipt_idx))
+    }
+
+    /// Submits the payload that resumes the call yielded with `promise_yield_create` under the
+    /// given data id. Only the account that created the yield can resume it, and only once.
+    ///
+    /// # Errors
+    ///
+    /// * If `data_id_len + data_id_ptr` or `payload_len + payload_ptr` points outside the memory
+    ///   of the guest or host returns `MemoryAccessViolation`.
+    /// * If `payload_len` exceeds `max_yield_payload_size` returns `YieldPayloadLength`.
+    /// * If the data id is not 32 bytes long returns `DataIdMalformed`.
+    /// * If called as view function returns `ProhibitedInView`.
+    ///
+    /// # Returns
+    ///
+    /// `1` if the payload was submitted to a pending yield, `0` if there is no pending yield with
+    /// this data id for the current account, e.g. because it was already resumed or timed out.
+    ///
+    /// # Cost
+    ///
+    /// `base + yield_resume_base + yield_resume_byte * num_bytes`
+    pub fn promise_yield_resume(
+        &mut self,
+        data_id_len: u64,
+        data_id_ptr: u64,
+        payload_len: u64,
+        payload_ptr: u64,
+    ) -> Result<u32> {
+        self.gas_counter.pay_base(base)?;
+        if self.context.is_view() {
+            return Err(HostError::ProhibitedInView {
+                method_name: "promise_yield_resume".to_string(),
+            }
+            .into());
+        }
+        self.gas_counter.pay_base(yield_resume_base)?;
+        self.gas_counter.pay_per(yield_resume_byte, payload_len)?;
+
+        let payload_len_limit = self.config.limit_config.max_yield_payload_size;
+        if payload_len > payload_len_limit {
+            return Err(HostError::YieldPayloadLength {
+                length: payload_len,
+                limit: payload_len_limit,
+            }
+            .into());
+        }
+
+        let data_id = get_memory_or_register!(self, data_id_ptr, data_id_len)?;
+        let data_id = CryptoHash::try_from(&*data_id).map_err(|_| HostError::DataIdMalformed)?;
+        let payload = get_memory_or_register!(self, payload_ptr, payload_len)?.into_owned();
+        let resumed = self.ext.submit_promise_resume_data(data_id, payload)?;
+        Ok(resumed as u32)
+    }
+
     /// If the current function is invoked by a callback we can access the execution results of the
     /// promises that caused the callback. This function returns the number of complete and
     /// incomplete callbacks.
@@ -2424,6 +3012,10 @@ impl<'a> VMLogic<'a> {
 
         self.gas_counter.add_trie_fees(&nodes_delta)?;
         self.ext.storage_set(&key, &value)?;
+        #[cfg(feature = "sandbox")]
+        if let Some(debugger) = &mut self.debugger {
+            debugger.record_storage_change(&key, Some(&value[..]));
+        }
         let storage_config = &self.fees_config.storage_usage_config;
         match evicted {
             Some(old_value) => {
@@ -2581,6 +3173,10 @@ impl<'a> VMLogic<'a> {
             Self::deref_value(&mut self.gas_counter, storage_remove_ret_value_byte, removed_ptr)?;
 
         self.ext.storage_remove(&key)?;
+        #[cfg(feature = "sandbox")]
+        if let Some(debugger) = &mut self.debugger {
+            debugger.record_storage_change(&key, None);
+        }
         let nodes_delta = self
             .ext
             .get_trie_nodes_count()
@@ -2797,6 +3393,9 @@ impl<'a> VMLogic<'a> {
             compute_usage,
             logs: self.logs,
             profile,
+            function_profile: self.function_profile,
+            #[cfg(feature = "sandbox")]
+            debug_trace: self.debugger.map(crate::debugger::Debugger::into_trace),
             aborted: None,
         }
     }
@@ -2905,6 +3504,12 @@ pub struct VMOutcome {
     pub logs: Vec<String>,
     /// Data collected from making a contract call
     pub profile: ProfileDataV3,
+    /// Per-function breakdown of the Wasm instruction gas, only present for view calls that
+    /// asked for it.
+    pub function_profile: Option<FunctionGasProfile>,
+    /// Recorded by debug calls on sandbox nodes.
+    #[cfg(feature = "sandbox")]
+    pub debug_trace: Option<crate::debugger::DebugTrace>,
     pub aborted: Option<FunctionCallError>,
 }
 
@@ -2937,6 +3542,9 @@ impl VMOutcome {
             compute_usage: 0,
             logs: Vec::new(),
             profile: ProfileDataV3::default(),
+            function_profile: None,
+            #[cfg(feature = "sandbox")]
+            debug_trace: None,
             aborted: Some(error),
         }
     }
@@ -16,6 +16,14 @@ pub enum MockAction {
         receipt_indices: Vec<ReceiptIndex>,
         receiver_id: AccountId,
     },
+    YieldCreate {
+        data_id: CryptoHash,
+        receiver_id: AccountId,
+    },
+    YieldResume {
+        data_id: CryptoHash,
+        data: Vec<u8>,
+    },
     CreateAccount {
         receipt_index: ReceiptIndex,
     },
@@ -179,6 +187,34 @@ impl External for MockedExternal {
         Ok(index as u64)
     }
 
+    fn create_promise_yield_receipt(
+        &mut self,
+        receiver_id: AccountId,
+    ) -> Result<(ReceiptIndex, CryptoHash), crate::logic::VMLogicError> {
+        let data_id = self.generate_data_id();
+        let index = self.action_log.len();
+        self.action_log.push(MockAction::YieldCreate { data_id, receiver_id });
+        Ok((index as u64, data_id))
+    }
+
+    fn submit_promise_resume_data(
+        &mut self,
+        data_id: CryptoHash,
+        data: Vec<u8>,
+    ) -> Result<bool, crate::logic::VMLogicError> {
+        let yielded = self.action_log.iter().any(
+            |action| matches!(action, MockAction::YieldCreate { data_id: id, .. } if *id == data_id),
+        );
+        let resumed = self.action_log.iter().any(
+            |action| matches!(action, MockAction::YieldResume { data_id: id, .. } if *id == data_id),
+        );
+        if !yielded || resumed {
+            return Ok(false);
+        }
+        self.action_log.push(MockAction::YieldResume { data_id, data });
+        Ok(true)
+    }
+
     fn append_action_create_account(
         &mut self,
         receipt_index: ReceiptIndex,
@@ -283,6 +319,7 @@ impl External for MockedExternal {
     fn get_receipt_receiver(&self, receipt_index: ReceiptIndex) -> &AccountId {
         match &self.action_log[receipt_index as usize] {
             MockAction::CreateReceipt { receiver_id, .. } => receiver_id,
+            MockAction::YieldCreate { receiver_id, .. } => receiver_id,
             _ => panic!("not a valid receipt index!"),
         }
     }
@@ -4,6 +4,7 @@ use types::AccountId;
 use unc_primitives_core::hash::CryptoHash;
 
 mod alt_bn128;
+mod bls12381;
 mod context;
 mod dependencies;
 pub mod errors;
@@ -0,0 +1,232 @@
+use crate::logic::tests::vm_logic_builder::{TestVMLogic, VMLogicBuilder};
+use crate::logic::{HostError, VMLogicError};
+
+const G1: &str = "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1";
+const G1_DOUBLE: &str = "0572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28";
+const G1_NEG: &str = "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb114d1d6855d545a8aa7d76c8cf2e21f267816aef1db507c96655b9d5caac42364e6f38ba0ecb751bad54dcd6b939c2ca";
+const G1_COMPRESSED: &str = "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+const G2: &str = "13e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb80606c4a02ea734cc32acd2b02bc28b99cb3e287e85a763af267492ab572e99ab3f370d275cec1da1aaa9075ff05f79be0ce5d527727d6e118cc9cdc6da2e351aadfd9baa8cbdd3a76d429a695160d12c923ac9cc3baca289e193548608b82801";
+const G2_DOUBLE: &str = "0a4edef9c1ed7f729f520e47730a124fd70662a904ba1074728114d1031e1572c6c886f6b57ec72a6178288c47c335771638533957d540a9d2370f17cc7ed5863bc0b995b8825e0ee1ea1e1e4d00dbae81f14b0bf3611b78c952aacab827a0530f6d4552fa65dd2638b361543f887136a43253d9c66c411697003f7a13c308f5422e1aa0a59c8967acdefd8b6e36ccf30468fb440d82b0630aeb8dca2b5256789a66da69bf91009cbfe6bd221e47aa8ae88dece9764bf3bd999d95d71e4c9899";
+const G2_COMPRESSED: &str = "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8";
+
+/// Calls a bls12381 host function on `input` and returns the content of
+/// register `0`, or the message of the `Bls12381InvalidInput` error.
+#[track_caller]
+fn call<T>(
+    input: &[u8],
+    f: impl FnOnce(&mut TestVMLogic<'_>, u64, u64) -> Result<T, VMLogicError>,
+) -> Result<Vec<u8>, String> {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+    let input = logic.internal_mem_write(input);
+    match f(&mut logic, input.len, input.ptr) {
+        Ok(_) => Ok(logic.registers().get_for_free(0).unwrap().to_vec()),
+        Err(VMLogicError::HostError(HostError::Bls12381InvalidInput { msg })) => Err(msg),
+        Err(err) => panic!("unexpected error: `{err}`"),
+    }
+}
+
+#[track_caller]
+fn check_err(res: Result<Vec<u8>, String>, expected_err: &str) {
+    let err = res.expect_err("expected an error");
+    assert!(err.contains(expected_err), "expected `{expected_err}` error, got {err}");
+}
+
+fn p1_sum(input: &[u8]) -> Result<Vec<u8>, String> {
+    call(input, |logic, len, ptr| logic.bls12381_p1_sum(len, ptr, 0))
+}
+
+fn p2_sum(input: &[u8]) -> Result<Vec<u8>, String> {
+    call(input, |logic, len, ptr| logic.bls12381_p2_sum(len, ptr, 0))
+}
+
+fn g1_multiexp(input: &[u8]) -> Result<Vec<u8>, String> {
+    call(input, |logic, len, ptr| logic.bls12381_g1_multiexp(len, ptr, 0))
+}
+
+fn g2_multiexp(input: &[u8]) -> Result<Vec<u8>, String> {
+    call(input, |logic, len, ptr| logic.bls12381_g2_multiexp(len, ptr, 0))
+}
+
+fn pairing_check(input: &[u8]) -> Result<u64, String> {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+    let input = logic.internal_mem_write(input);
+    match logic.bls12381_pairing_check(input.len, input.ptr) {
+        Ok(res) => Ok(res),
+        Err(VMLogicError::HostError(HostError::Bls12381InvalidInput { msg })) => Err(msg),
+        Err(err) => panic!("unexpected error: `{err}`"),
+    }
+}
+
+fn scalar(n: u8) -> [u8; 32] {
+    let mut res = [0; 32];
+    res[0] = n;
+    res
+}
+
+fn infinity(len: usize) -> Vec<u8> {
+    let mut res = vec![0; len];
+    res[0] = 0x40;
+    res
+}
+
+fn concat(parts: &[&[u8]]) -> Vec<u8> {
+    parts.concat()
+}
+
+#[test]
+fn test_bls12381_p1_sum() {
+    let g1 = hex::decode(G1).unwrap();
+
+    assert_eq!(p1_sum(&[]).unwrap(), infinity(96));
+    assert_eq!(p1_sum(&concat(&[&[0], &g1])).unwrap(), g1);
+    assert_eq!(p1_sum(&concat(&[&[1], &g1])).unwrap(), hex::decode(G1_NEG).unwrap());
+    assert_eq!(p1_sum(&concat(&[&[0], &g1, &[0], &g1])).unwrap(), hex::decode(G1_DOUBLE).unwrap());
+    assert_eq!(p1_sum(&concat(&[&[0], &g1, &[1], &g1])).unwrap(), infinity(96));
+    assert_eq!(p1_sum(&concat(&[&[0], &infinity(96), &[0], &g1])).unwrap(), g1);
+
+    check_err(
+        p1_sum(&[0; 10]),
+        "slice of size 10 cannot be precisely split into chunks of size 97",
+    );
+    check_err(p1_sum(&concat(&[&[2], &g1])), "invalid bool");
+    let mut point = g1.clone();
+    point[95] ^= 1;
+    check_err(p1_sum(&concat(&[&[0], &point])), "G1 point is not on the curve");
+    let mut point = g1.clone();
+    point[0] |= 0x80;
+    check_err(
+        p1_sum(&concat(&[&[0], &point])),
+        "compression flag is set for uncompressed G1 point",
+    );
+    check_err(p1_sum(&concat(&[&[0], &[0x1f; 96]])), "invalid G1 point encoding");
+}
+
+#[test]
+fn test_bls12381_p2_sum() {
+    let g2 = hex::decode(G2).unwrap();
+
+    assert_eq!(p2_sum(&[]).unwrap(), infinity(192));
+    assert_eq!(p2_sum(&concat(&[&[0], &g2])).unwrap(), g2);
+    assert_eq!(p2_sum(&concat(&[&[0], &g2, &[0], &g2])).unwrap(), hex::decode(G2_DOUBLE).unwrap());
+    assert_eq!(p2_sum(&concat(&[&[1], &g2, &[0], &g2])).unwrap(), infinity(192));
+
+    check_err(
+        p2_sum(&[0; 97]),
+        "slice of size 97 cannot be precisely split into chunks of size 193",
+    );
+    let mut point = g2.clone();
+    point[191] ^= 1;
+    check_err(p2_sum(&concat(&[&[0], &point])), "G2 point is not on the curve");
+}
+
+#[test]
+fn test_bls12381_multiexp() {
+    let g1 = hex::decode(G1).unwrap();
+    let g2 = hex::decode(G2).unwrap();
+
+    assert_eq!(g1_multiexp(&[]).unwrap(), infinity(96));
+    assert_eq!(g1_multiexp(&concat(&[&g1, &scalar(1)])).unwrap(), g1);
+    assert_eq!(g1_multiexp(&concat(&[&g1, &scalar(2)])).unwrap(), hex::decode(G1_DOUBLE).unwrap());
+    assert_eq!(
+        g1_multiexp(&concat(&[&g1, &scalar(1), &g1, &scalar(1)])).unwrap(),
+        hex::decode(G1_DOUBLE).unwrap()
+    );
+    assert_eq!(g1_multiexp(&concat(&[&g1, &scalar(0)])).unwrap(), infinity(96));
+
+    assert_eq!(g2_multiexp(&[]).unwrap(), infinity(192));
+    assert_eq!(g2_multiexp(&concat(&[&g2, &scalar(2)])).unwrap(), hex::decode(G2_DOUBLE).unwrap());
+
+    check_err(
+        g1_multiexp(&g1),
+        "slice of size 96 cannot be precisely split into chunks of size 128",
+    );
+
+    // Points with `x = 4` on E1 and `x = 2` on E2 are on the curve but not in
+    // the prime order subgroup.
+    let mut compressed = [0; 48];
+    compressed[0] = 0x80;
+    compressed[47] = 4;
+    let point = call(&compressed, |logic, len, ptr| logic.bls12381_p1_decompress(len, ptr, 0));
+    check_err(
+        g1_multiexp(&concat(&[&point.unwrap(), &scalar(1)])),
+        "G1 point is not in the subgroup",
+    );
+    let mut compressed = [0; 96];
+    compressed[0] = 0x80;
+    compressed[95] = 2;
+    let point = call(&compressed, |logic, len, ptr| logic.bls12381_p2_decompress(len, ptr, 0));
+    check_err(
+        g2_multiexp(&concat(&[&point.unwrap(), &scalar(1)])),
+        "G2 point is not in the subgroup",
+    );
+}
+
+#[test]
+fn test_bls12381_map_to_curve() {
+    // Test vectors for `BLS12381G1_XMD:SHA-256_SSWU_RO_` and
+    // `BLS12381G2_XMD:SHA-256_SSWU_RO_` with an empty message from RFC 9380.
+    // The hash of the message is the sum of the images of `u0` and `u1`.
+    let u = hex::decode("0ba14bd907ad64a016293ee7c2d276b8eae71f25a4b941eece7b0d89f17f75cb3ae5438a614fb61d6835ad59f29c564f019b9bd7979f12657976de2884c7cce192b82c177c80e0ec604436a7f538d231552f0d96d9f7babe5fa3b19b3ff25ac9").unwrap();
+    let mapped = call(&u, |logic, len, ptr| logic.bls12381_map_fp_to_g1(len, ptr, 0)).unwrap();
+    assert_eq!(
+        p1_sum(&concat(&[&[0], &mapped[..96], &[0], &mapped[96..]])).unwrap(),
+        hex::decode("052926add2207b76ca4fa57a8734416c8dc95e24501772c814278700eed6d1e4e8cf62d9c09db0fac349612b759e79a108ba738453bfed09cb546dbb0783dbb3a5f1f566ed67bb6be0e8c67e2e81a4cc68ee29813bb7994998f3eae0c9c6a265").unwrap()
+    );
+
+    let u = hex::decode("05a2acec64114845711a54199ea339abd125ba38253b70a92c876df10598bd1986b739cad67961eb94f7076511b3b39a03dbc2cce174e91ba93cbb08f26b917f98194a2ea08d1cce75b2b9cc9f21689d80bd79b594a613d0a68eb807dfdc1cf8145a81e418d4010cc027a68f14391b30074e89e60ee7a22f87217b2f6eb0c4b94c9115b436e6fa4607e95a98de30a43502f99798e8a5acdeed60d7e18e9120521ba1f47ec090984662846bc825de191b5b7641148c0dbc237726a334473eee94").unwrap();
+    let mapped = call(&u, |logic, len, ptr| logic.bls12381_map_fp2_to_g2(len, ptr, 0)).unwrap();
+    assert_eq!(
+        p2_sum(&concat(&[&[0], &mapped[..192], &[0], &mapped[192..]])).unwrap(),
+        hex::decode("05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d60503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92").unwrap()
+    );
+
+    check_err(
+        call(&[0xff; 48], |logic, len, ptr| logic.bls12381_map_fp_to_g1(len, ptr, 0)),
+        "invalid fp",
+    );
+    check_err(
+        call(&[0xff; 96], |logic, len, ptr| logic.bls12381_map_fp2_to_g2(len, ptr, 0)),
+        "invalid fp",
+    );
+}
+
+#[test]
+fn test_bls12381_pairing_check() {
+    let g1 = hex::decode(G1).unwrap();
+    let g1_neg = hex::decode(G1_NEG).unwrap();
+    let g2 = hex::decode(G2).unwrap();
+
+    assert_eq!(pairing_check(&[]), Ok(1));
+    assert_eq!(pairing_check(&concat(&[&g1, &g2])), Ok(0));
+    assert_eq!(pairing_check(&concat(&[&g1, &g2, &g1_neg, &g2])), Ok(1));
+    assert_eq!(pairing_check(&concat(&[&infinity(96), &g2, &g1, &infinity(192)])), Ok(1));
+
+    check_err(
+        pairing_check(&g1).map(|res| vec![res as u8]),
+        "slice of size 96 cannot be precisely split into chunks of size 288",
+    );
+}
+
+#[test]
+fn test_bls12381_decompress() {
+    let p1_decompress =
+        |input: &[u8]| call(input, |logic, len, ptr| logic.bls12381_p1_decompress(len, ptr, 0));
+    let p2_decompress =
+        |input: &[u8]| call(input, |logic, len, ptr| logic.bls12381_p2_decompress(len, ptr, 0));
+    let g1_compressed = hex::decode(G1_COMPRESSED).unwrap();
+    let g2_compressed = hex::decode(G2_COMPRESSED).unwrap();
+
+    assert_eq!(p1_decompress(&[]).unwrap(), Vec::<u8>::new());
+    assert_eq!(p1_decompress(&g1_compressed).unwrap(), hex::decode(G1).unwrap());
+    assert_eq!(
+        p1_decompress(&concat(&[&g1_compressed, &g1_compressed])).unwrap(),
+        concat(&[&hex::decode(G1).unwrap(), &hex::decode(G1).unwrap()])
+    );
+    assert_eq!(p2_decompress(&g2_compressed).unwrap(), hex::decode(G2).unwrap());
+
+    check_err(p1_decompress(&[0; 48]), "invalid G1 point encoding");
+    check_err(p1_decompress(&g1_compressed[..47]), "slice of size 47 cannot be precisely split");
+    check_err(p2_decompress(&[0; 96]), "invalid G2 point encoding");
+}
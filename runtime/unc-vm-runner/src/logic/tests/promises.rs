@@ -2,9 +2,12 @@ use crate::logic::mocks::mock_external::MockedExternal;
 use crate::logic::tests::helpers::*;
 use crate::logic::tests::vm_logic_builder::VMLogicBuilder;
 use crate::logic::types::PromiseResult;
+use crate::logic::HostError;
+use crate::map;
 
 use serde_json;
 use unc_crypto::PublicKey;
+use unc_parameters::ExtCosts;
 
 fn vm_receipts<'a>(ext: &'a MockedExternal) -> Vec<impl serde::Serialize + 'a> {
     ext.action_log.clone()
@@ -630,3 +633,89 @@ fn test_promise_batch_then() {
         ]"#]]
     .assert_eq(&serde_json::to_string_pretty(&vm_receipts(&logic_builder.ext)).unwrap());
 }
+
+#[test]
+fn test_promise_yield_create_and_resume() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+
+    let method_name = logic.internal_mem_write(b"callback");
+    let args = logic.internal_mem_write(b"args");
+    let index = logic
+        .promise_yield_create(method_name.len, method_name.ptr, args.len, args.ptr, 0, 1, 0)
+        .expect("should create a yield");
+    assert_eq!(index, 0);
+    assert_eq!(logic.register_len(0), Ok(32));
+
+    // The data id is passed straight from the register.
+    let payload = logic.internal_mem_write(b"payload");
+    assert_eq!(logic.promise_yield_resume(u64::MAX, 0, payload.len, payload.ptr), Ok(1));
+    // A yield can only be resumed once.
+    assert_eq!(logic.promise_yield_resume(u64::MAX, 0, payload.len, payload.ptr), Ok(0));
+
+    // Unknown data ids are not resumed.
+    let unknown_data_id = logic.internal_mem_write(&[0; 32]);
+    assert_eq!(
+        logic.promise_yield_resume(
+            unknown_data_id.len,
+            unknown_data_id.ptr,
+            payload.len,
+            payload.ptr
+        ),
+        Ok(0)
+    );
+}
+
+#[test]
+fn test_promise_yield_create_prepays_timeout() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+
+    let method_name = logic.internal_mem_write(b"callback");
+    let args = logic.internal_mem_write(b"args");
+    logic
+        .promise_yield_create(method_name.len, method_name.ptr, args.len, args.ptr, 0, 1, 0)
+        .expect("should create a yield");
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::yield_create_base: 1,
+        ExtCosts::yield_create_byte: method_name.len + args.len,
+        ExtCosts::yield_resume_base: 1,
+        ExtCosts::read_memory_base: 2,
+        ExtCosts::read_memory_byte: method_name.len + args.len,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 32,
+    });
+}
+
+#[test]
+fn test_promise_yield_resume_invalid_input() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let limit = logic_builder.config.limit_config.max_yield_payload_size;
+    let mut logic = logic_builder.build();
+
+    let data_id = logic.internal_mem_write(&[0; 31]);
+    let payload = logic.internal_mem_write(b"payload");
+    assert_eq!(
+        logic.promise_yield_resume(data_id.len, data_id.ptr, payload.len, payload.ptr),
+        Err(HostError::DataIdMalformed.into())
+    );
+
+    let data_id = logic.internal_mem_write(&[0; 32]);
+    assert_eq!(
+        logic.promise_yield_resume(data_id.len, data_id.ptr, limit + 1, 0),
+        Err(HostError::YieldPayloadLength { length: limit + 1, limit }.into())
+    );
+}
+
+#[test]
+fn test_promise_yield_create_empty_method_name() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build();
+
+    let args = logic.internal_mem_write(b"args");
+    assert_eq!(
+        logic.promise_yield_create(0, 0, args.len, args.ptr, 0, 1, 0),
+        Err(HostError::EmptyMethodName.into())
+    );
+}
@@ -31,8 +31,10 @@ impl VMLogicBuilder {
     pub fn view() -> Self {
         let mut builder = Self::default();
         let max_gas_burnt = builder.config.limit_config.max_gas_burnt;
-        builder.context.view_config =
-            Some(unc_primitives_core::config::ViewConfig { max_gas_burnt });
+        builder.context.view_config = Some(unc_primitives_core::config::ViewConfig {
+            max_gas_burnt,
+            profile_functions: false,
+        });
         builder
     }
 
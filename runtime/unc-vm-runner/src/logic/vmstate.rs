@@ -150,6 +150,12 @@ impl Registers {
         self.registers.get(&register_id).map(|data| &data[..])
     }
 
+    /// Iterates over all registers and their values, in no particular order.
+    #[cfg(feature = "sandbox")]
+    pub(super) fn iter(&self) -> impl Iterator<Item = (u64, &[u8])> {
+        self.registers.iter().map(|(id, data)| (*id, &data[..]))
+    }
+
     /// Returns length of register with given index or None if no such register.
     pub(super) fn get_len(&self, register_id: u64) -> Option<u64> {
         self.registers.get(&register_id).map(|data| data.len() as u64)
@@ -25,12 +25,26 @@ pub fn prepare_contract(
     kind: VMKind,
 ) -> Result<Vec<u8>, PrepareError> {
     let prepare = config.limit_config.contract_prepare_version;
-    // UncVM => ContractPrepareVersion::V2
+    // UncVM => ContractPrepareVersion::V2, V3 or V4
     assert!(
-        (kind != VMKind::UncVm) || (prepare == crate::logic::ContractPrepareVersion::V2),
-        "UncVM only works with contract prepare version V2",
+        (kind != VMKind::UncVm)
+            || matches!(
+                prepare,
+                crate::logic::ContractPrepareVersion::V2
+                    | crate::logic::ContractPrepareVersion::V3
+                    | crate::logic::ContractPrepareVersion::V4
+            ),
+        "UncVM only works with contract prepare version V2, V3 or V4",
     );
-    let features = crate::features::WasmFeatures::from(prepare);
+    let features = crate::features::WasmFeatures::from(match (kind, prepare) {
+        // Wasmer2 supports none of the proposals enabled since V3, so contracts using them are
+        // rejected here rather than handed over to a compiler unable to deal with them.
+        (
+            VMKind::Wasmer2,
+            crate::logic::ContractPrepareVersion::V3 | crate::logic::ContractPrepareVersion::V4,
+        ) => crate::logic::ContractPrepareVersion::V2,
+        _ => prepare,
+    });
     match prepare {
         crate::logic::ContractPrepareVersion::V0 => {
             // NB: v1 here is not a bug, we are reusing the code.
@@ -41,7 +55,9 @@ pub fn prepare_contract(
             prepare_v1::validate_contract(original_code, features, config)?;
             prepare_v1::prepare_contract(original_code, config)
         }
-        crate::logic::ContractPrepareVersion::V2 => {
+        crate::logic::ContractPrepareVersion::V2
+        | crate::logic::ContractPrepareVersion::V3
+        | crate::logic::ContractPrepareVersion::V4 => {
             prepare_v2::prepare_contract(original_code, features, config, kind)
         }
     }
@@ -83,7 +83,7 @@ impl<'a> PrepareContext<'a> {
                     self.validator
                         .table_section(&reader)
                         .map_err(|_| PrepareError::Deserialization)?;
-                    self.copy_section(SectionId::Table, reader.range())?;
+                    self.transform_table_section(&reader)?;
                 }
                 wp::Payload::MemorySection(reader) => {
                     // We do not want to include the implicit memory anymore as we normalized it by
@@ -229,6 +229,49 @@ impl<'a> PrepareContext<'a> {
         Ok(())
     }
 
+    /// Apply the limits on the number of tables and their elements.
+    ///
+    /// The maximum of every table is lowered to the limit on the elements, so that `table.grow`
+    /// can't go beyond it in any of the VMs.
+    fn transform_table_section(
+        &mut self,
+        reader: &wp::TableSectionReader,
+    ) -> Result<(), PrepareError> {
+        let limits = &self.config.limit_config;
+        if let Some(max_tables) = limits.max_tables_per_contract {
+            if reader.count() > max_tables {
+                return Err(PrepareError::TooManyTables);
+            }
+        }
+        let Some(max_elements) = limits.max_elements_per_contract_table else {
+            return self.copy_section(SectionId::Table, reader.range());
+        };
+        let mut new_section = wasm_encoder::TableSection::new();
+        for table in reader.clone() {
+            let table = table.map_err(|_| PrepareError::Deserialization)?;
+            if table.ty.initial > max_elements {
+                return Err(PrepareError::TooManyTableElements);
+            }
+            let element_type = match table.ty.element_type {
+                wp::RefType::FUNCREF => wasm_encoder::RefType::FUNCREF,
+                wp::RefType::EXTERNREF => wasm_encoder::RefType::EXTERNREF,
+                // Other reference types and initializer expressions need the function
+                // references proposal, which is not enabled.
+                _ => return Err(PrepareError::Deserialization),
+            };
+            if !matches!(table.init, wp::TableInit::RefNull) {
+                return Err(PrepareError::Deserialization);
+            }
+            new_section.table(wasm_encoder::TableType {
+                element_type,
+                minimum: table.ty.initial,
+                maximum: Some(table.ty.maximum.map_or(max_elements, |max| max.min(max_elements))),
+            });
+        }
+        new_section.append_to(&mut self.output_code);
+        Ok(())
+    }
+
     fn ensure_import_section(&mut self) {
         if self.before_import_section {
             self.before_import_section = false;
@@ -273,13 +316,17 @@ pub(crate) fn prepare_contract(
     let lightly_steamed = PrepareContext::new(original_code, features, config).run()?;
 
     if kind == VMKind::UncVm {
-        // Built-in unc-vm code instruments code for itself.
+        // Built-in unc-vm code instruments code for itself, including the per-byte gas of bulk
+        // memory instructions, so nothing below may be applied to it.
         return Ok(lightly_steamed);
     }
 
     let res = finite_wasm::Analysis::new()
         .with_stack(Box::new(SimpleMaxStackCfg))
-        .with_gas(Box::new(SimpleGasCostCfg(u64::from(config.regular_op_cost))))
+        .with_gas(Box::new(SimpleGasCostCfg(
+            u64::from(config.regular_op_cost),
+            crate::instrument::gas::simd::SimdOpCosts::new(config),
+        )))
         .analyze(&lightly_steamed)
         .map_err(|err| {
             tracing::error!(?err, ?kind, "Analysis failed");
@@ -291,7 +338,19 @@ pub(crate) fn prepare_contract(
             tracing::error!(?err, ?kind, "Instrumentation failed");
             PrepareError::Serialization
         })?;
-    Ok(res)
+    if config.bulk_memory_byte_cost == 0 && config.bulk_table_element_cost == 0 {
+        return Ok(res);
+    }
+    // The only place the per-byte and per-element gas of bulk memory and table instructions is
+    // charged for VMs other than UncVM, which charges it in `emit_bulk_gas` of the singlepass
+    // compiler instead.
+    crate::instrument::gas::bulk_memory::inject_bulk_memory_gas(
+        &res,
+        "internal",
+        "finite_wasm_gas",
+        u64::from(config.bulk_memory_byte_cost),
+        u64::from(config.bulk_table_element_cost),
+    )
 }
 
 // TODO: refactor to avoid copy-paste with the ones currently defined in unc_vm_runner
@@ -329,7 +388,8 @@ impl finite_wasm::max_stack::SizeConfig for SimpleMaxStackCfg {
     }
 }
 
-struct SimpleGasCostCfg(u64);
+/// Gas cost of the regular instructions and of the SIMD ones.
+struct SimpleGasCostCfg(u64, crate::instrument::gas::simd::SimdOpCosts);
 
 macro_rules! gas_cost {
     ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
@@ -349,6 +409,9 @@ macro_rules! gas_cost {
     (@@mvp $_op:ident $_self:ident $({ $($_arg:ident: $_argty:ty),* })? => visit_else) => {
         0
     };
+    (@@simd $op:ident $self:ident $({ $($arg:ident: $_argty:ty),* })? => $_visit:ident) => {
+        $self.1.cost(&wp::Operator::$op $({ $($arg),* })?)
+    };
     (@@$_proposal:ident $_op:ident $self:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident) => {
         $self.0
     };
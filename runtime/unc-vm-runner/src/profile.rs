@@ -2,6 +2,7 @@ pub use profile_v2::ProfileDataV2;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use enum_map::{enum_map, Enum, EnumMap};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use strum::IntoEnumIterator;
 use unc_parameters::{ActionCosts, ExtCosts, ExtCostsConfig};
@@ -235,6 +236,65 @@ impl fmt::Debug for ProfileDataV3 {
     }
 }
 
+/// Gas spent on Wasm instructions, broken down by the contract function executing them.
+///
+/// Only collected for view calls that ask for it. Unlike [`ProfileDataV3`], this never ends up in
+/// the chain data.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FunctionGasProfile {
+    /// The contract method that was called.
+    method_name: String,
+    /// Gas burnt by each function, keyed by the function index within the contract.
+    functions: BTreeMap<u32, Gas>,
+    /// Function names from the `name` section of the contract, if it has one.
+    names: HashMap<u32, String>,
+}
+
+impl FunctionGasProfile {
+    pub fn new(method_name: String, names: HashMap<u32, String>) -> Self {
+        Self { method_name, functions: BTreeMap::new(), names }
+    }
+
+    pub fn method_name(&self) -> &str {
+        &self.method_name
+    }
+
+    #[inline]
+    pub fn add_function_gas(&mut self, function: u32, gas: Gas) {
+        let function_gas = self.functions.entry(function).or_default();
+        *function_gas = function_gas.saturating_add(gas);
+    }
+
+    /// Name of the function from the `name` section, or a placeholder with its index.
+    pub fn function_name(&self, function: u32) -> String {
+        match self.names.get(&function) {
+            Some(name) => name.clone(),
+            None => format!("<function {function}>"),
+        }
+    }
+
+    /// Functions that burnt any gas along with that gas, ordered by the function index.
+    pub fn functions(&self) -> impl Iterator<Item = (u32, Gas)> + '_ {
+        self.functions.iter().map(|(function, gas)| (*function, *gas))
+    }
+
+    /// Render the profile in the folded stacks format consumed by `flamegraph.pl`, `inferno` and
+    /// similar tools.
+    ///
+    /// Contract functions cannot be attributed to their callers, so every stack consists of the
+    /// called method and the function that burnt the gas.
+    pub fn to_folded_stacks(&self) -> String {
+        let frame = |name: &str| name.replace(|c: char| c == ';' || c.is_whitespace(), "_");
+        let method = frame(&self.method_name);
+        let mut folded = String::new();
+        for (function, gas) in self.functions() {
+            let name = frame(&self.function_name(function));
+            folded.push_str(&format!("{method};{name} {gas}\n"));
+        }
+        folded
+    }
+}
+
 /// Tests for ProfileDataV3
 #[cfg(test)]
 mod test {
@@ -454,4 +514,21 @@ mod test {
         BorshSerialize::serialize(&wasm_cost, &mut input).unwrap();
         input
     }
+
+    #[test]
+    fn test_function_gas_profile_folded_stacks() {
+        let names = HashMap::from([(3, "main".to_string()), (7, "core::fmt::write".to_string())]);
+        let mut profile = FunctionGasProfile::new("view method".to_string(), names);
+        profile.add_function_gas(7, 100);
+        profile.add_function_gas(3, 20);
+        profile.add_function_gas(9, 5);
+        profile.add_function_gas(7, 1);
+        assert_eq!(profile.functions().collect::<Vec<_>>(), vec![(3, 20), (7, 101), (9, 5)]);
+        expect_test::expect![[r#"
+            view_method;main 20
+            view_method;core::fmt::write 101
+            view_method;<function_9> 5
+        "#]]
+        .assert_eq(&profile.to_folded_stacks());
+    }
 }
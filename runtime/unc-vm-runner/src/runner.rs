@@ -60,8 +60,19 @@ pub fn run(
     )
     .entered();
 
-    let runtime = vm_kind
-        .runtime(wasm_config.clone())
+    // Per-function profiles are collected by instrumenting the whole module, which is only done by
+    // the Wasmtime runner. It compiles without the contract cache and is not the VM the chain agrees
+    // on, so only sandbox nodes switch to it. Elsewhere, or without Wasmtime compiled in, the call
+    // runs on the configured VM, just without the profile.
+    let profiling_runtime = if cfg!(feature = "sandbox") && context.profiles_functions() {
+        let mut config = wasm_config.clone();
+        config.vm_kind = VMKind::Wasmtime;
+        VMKind::Wasmtime.runtime(config)
+    } else {
+        None
+    };
+    let runtime = profiling_runtime
+        .or_else(|| vm_kind.runtime(wasm_config.clone()))
         .unwrap_or_else(|| panic!("the {vm_kind:?} runtime has not been enabled at compile time"));
 
     let outcome =
@@ -1,11 +1,15 @@
 mod cache;
 mod compile_errors;
+#[cfg(all(feature = "sandbox", feature = "wasmtime_vm"))]
+mod debugger;
+mod function_profile;
 mod fuzzers;
 mod regression_tests;
 mod rs_contract;
 mod runtime_errors;
 pub(crate) mod test_builder;
 mod ts_contract;
+mod wasm_proposals;
 mod wasm_validation;
 
 use crate::logic::VMContext;
@@ -41,7 +45,12 @@ pub(crate) fn with_vm_variants(
     runner(VMKind::Wasmer2);
 
     #[cfg(all(feature = "unc_vm", target_arch = "x86_64"))]
-    if cfg.limit_config.contract_prepare_version == unc_parameters::vm::ContractPrepareVersion::V2 {
+    if matches!(
+        cfg.limit_config.contract_prepare_version,
+        unc_parameters::vm::ContractPrepareVersion::V2
+            | unc_parameters::vm::ContractPrepareVersion::V3
+            | unc_parameters::vm::ContractPrepareVersion::V4
+    ) {
         runner(VMKind::UncVm);
     }
 }
@@ -0,0 +1,106 @@
+use crate::debugger::{DebugOptions, DebugValue, MemoryRange, StorageChange};
+use crate::logic::mocks::mock_external::MockedExternal;
+use crate::logic::VMOutcome;
+use crate::tests::{create_context, test_vm_config};
+use crate::ContractCode;
+use unc_parameters::vm::VMKind;
+use unc_parameters::RuntimeFeesConfig;
+
+static DEBUGGED_CONTRACT: &str = r#"
+(module
+  (import "env" "storage_write"
+    (func $storage_write (param i64 i64 i64 i64 i64) (result i64)))
+  (memory 1)
+  (data (i32.const 0) "keyvalue")
+  (func $add (param i32 i32) (result i32)
+    (i32.add (local.get 0) (local.get 1)))
+  (func $main (export "main")
+    (drop (call $add (i32.const 2) (i32.const 3)))
+    (drop (call $storage_write (i64.const 3) (i64.const 0) (i64.const 5) (i64.const 3) (i64.const 0)))))
+"#;
+
+fn run_debug(options: DebugOptions) -> VMOutcome {
+    let code = ContractCode::new(wat::parse_str(DEBUGGED_CONTRACT).unwrap(), None);
+    crate::debugger::run(
+        &code,
+        "main",
+        &mut MockedExternal::new(),
+        create_context(vec![]),
+        &test_vm_config(),
+        &RuntimeFeesConfig::test(),
+        &[],
+        options,
+    )
+    .expect("execution failed")
+}
+
+#[test]
+fn test_breakpoint() {
+    let options = DebugOptions {
+        breakpoints: vec!["add".to_string()],
+        memory: vec![MemoryRange { offset: 0, len: 8 }],
+        ..DebugOptions::default()
+    };
+    let outcome = run_debug(options);
+    assert_eq!(outcome.aborted, None);
+
+    let mut config = test_vm_config();
+    config.vm_kind = VMKind::Wasmtime;
+    let code = ContractCode::new(wat::parse_str(DEBUGGED_CONTRACT).unwrap(), None);
+    let undebugged = crate::run(
+        &code,
+        "main",
+        &mut MockedExternal::new(),
+        create_context(vec![]),
+        &config,
+        &RuntimeFeesConfig::test(),
+        &[],
+        None,
+    )
+    .expect("execution failed");
+    // The probes must not change the gas charged for the call.
+    assert_eq!(outcome.burnt_gas, undebugged.burnt_gas);
+
+    let trace = outcome.debug_trace.expect("debug trace should be recorded");
+    assert!(!trace.truncated);
+    assert!(trace.unresolved_breakpoints.is_empty());
+    assert_eq!(trace.stops.len(), 1);
+    let stop = &trace.stops[0];
+    assert_eq!((stop.function, stop.function_name.as_deref()), (1, Some("add")));
+    assert_eq!(stop.instruction, 0);
+    assert_eq!(stop.locals, vec![DebugValue::I32(2), DebugValue::I32(3)]);
+    assert_eq!(stop.memory[0].data.as_deref(), Some(&b"keyvalue"[..]));
+    assert!(stop.burnt_gas > 0 && stop.burnt_gas < undebugged.burnt_gas);
+    assert_eq!(
+        trace.storage_changes,
+        vec![StorageChange { stop: 1, key: b"key".to_vec(), value: Some(b"value".to_vec()) }]
+    );
+}
+
+#[test]
+fn test_single_step() {
+    let options = DebugOptions {
+        breakpoints: vec!["add".to_string(), "missing".to_string()],
+        single_step: true,
+        ..DebugOptions::default()
+    };
+    let trace = run_debug(options).debug_trace.unwrap();
+    assert_eq!(trace.unresolved_breakpoints, vec!["missing"]);
+    // `local.get 0`, `local.get 1`, `i32.add` and `end`.
+    let instructions: Vec<_> = trace.stops.iter().map(|stop| stop.instruction).collect();
+    assert_eq!(instructions, vec![0, 1, 2, 3]);
+    let gas: Vec<_> = trace.stops.iter().map(|stop| stop.burnt_gas).collect();
+    assert!(gas.windows(2).all(|pair| pair[0] <= pair[1]), "{gas:?}");
+}
+
+#[test]
+fn test_max_stops() {
+    let options = DebugOptions { single_step: true, max_stops: 3, ..DebugOptions::default() };
+    let outcome = run_debug(options);
+    assert_eq!(outcome.aborted, None);
+    let trace = outcome.debug_trace.unwrap();
+    assert!(trace.truncated);
+    assert_eq!(trace.stops.len(), 3);
+    // Execution goes on past the last recorded stop.
+    assert_eq!(trace.storage_changes[0].stop, 3);
+}
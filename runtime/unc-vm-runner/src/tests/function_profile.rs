@@ -0,0 +1,74 @@
+use crate::logic::mocks::mock_external::MockedExternal;
+use crate::logic::VMOutcome;
+use crate::tests::{create_context, test_vm_config};
+use crate::ContractCode;
+use unc_parameters::RuntimeFeesConfig;
+use unc_primitives_core::config::ViewConfig;
+
+static PROFILED_CONTRACT: &str = r#"
+(module
+  (func $spin (param i32)
+    (loop $again
+      (local.set 0 (i32.sub (local.get 0) (i32.const 1)))
+      (br_if $again (local.get 0))))
+  (func $main (export "main")
+    (call $spin (i32.const 100))
+    (call $spin (i32.const 10))))
+"#;
+
+fn run_view(profile_functions: bool) -> VMOutcome {
+    let code = ContractCode::new(wat::parse_str(PROFILED_CONTRACT).unwrap(), None);
+    let config = test_vm_config();
+    let mut context = create_context(vec![]);
+    context.view_config =
+        Some(ViewConfig { max_gas_burnt: config.limit_config.max_gas_burnt, profile_functions });
+    crate::run(
+        &code,
+        "main",
+        &mut MockedExternal::new(),
+        context,
+        &config,
+        &RuntimeFeesConfig::test(),
+        &[],
+        None,
+    )
+    .expect("execution failed")
+}
+
+#[test]
+fn test_view_without_function_profile() {
+    let outcome = run_view(false);
+    assert_eq!(outcome.aborted, None);
+    assert_eq!(outcome.function_profile, None);
+}
+
+#[cfg(all(feature = "sandbox", feature = "wasmtime_vm"))]
+#[test]
+fn test_function_profile() {
+    let unprofiled = run_view(false);
+    let outcome = run_view(true);
+    assert_eq!(outcome.aborted, None);
+    // Profiling must not change the gas charged for the call.
+    assert_eq!(outcome.burnt_gas, unprofiled.burnt_gas);
+
+    let profile = outcome.function_profile.expect("function profile should be collected");
+    assert_eq!(profile.method_name(), "main");
+    assert_eq!(profile.function_name(0), "spin");
+    assert_eq!(profile.function_name(1), "main");
+    let functions: Vec<_> = profile.functions().collect();
+    assert_eq!(functions.iter().map(|(function, _)| *function).collect::<Vec<_>>(), vec![0, 1]);
+    let (spin_gas, main_gas) = (functions[0].1, functions[1].1);
+    assert!(spin_gas > 10 * main_gas, "{functions:?}");
+    assert!(spin_gas + main_gas < outcome.burnt_gas);
+    assert_eq!(profile.to_folded_stacks(), format!("main;spin {spin_gas}\nmain;main {main_gas}\n"));
+}
+
+#[cfg(not(feature = "sandbox"))]
+#[test]
+fn test_function_profile_needs_sandbox() {
+    let unprofiled = run_view(false);
+    let outcome = run_view(true);
+    assert_eq!(outcome.aborted, None);
+    assert_eq!(outcome.burnt_gas, unprofiled.burnt_gas);
+    assert_eq!(outcome.function_profile, None);
+}
@@ -167,7 +167,9 @@ pub fn ext_used_gas() {
         let expected = match config.limit_config.contract_prepare_version {
             crate::logic::ContractPrepareVersion::V0 => [111, 10, 200, 15, 0, 0, 0, 0],
             crate::logic::ContractPrepareVersion::V1 => [111, 10, 200, 15, 0, 0, 0, 0],
-            crate::logic::ContractPrepareVersion::V2 => [27, 180, 237, 15, 0, 0, 0, 0],
+            crate::logic::ContractPrepareVersion::V2
+            | crate::logic::ContractPrepareVersion::V3
+            | crate::logic::ContractPrepareVersion::V4 => [27, 180, 237, 15, 0, 0, 0, 0],
         };
         run_test_ext(&config, "ext_used_gas", &expected, &[], vec![], vm_kind)
     })
@@ -206,8 +206,12 @@ impl TestBuilder {
 
                 // UncVM includes a different contract preparation algorithm, that is not supported on old protocol versions
                 if vm_kind == VMKind::UncVm
-                    && runtime_config.wasm_config.limit_config.contract_prepare_version
-                        != ContractPrepareVersion::V2
+                    && !matches!(
+                        runtime_config.wasm_config.limit_config.contract_prepare_version,
+                        ContractPrepareVersion::V2
+                            | ContractPrepareVersion::V3
+                            | ContractPrepareVersion::V4
+                    )
                 {
                     continue;
                 }
@@ -0,0 +1,464 @@
+use crate::logic::errors::{CompilationError, FunctionCallError, PrepareError};
+use crate::logic::mocks::mock_external::MockedExternal;
+use crate::logic::{Config, VMOutcome};
+use crate::runner::VMKindExt;
+use crate::tests::{create_context, with_vm_variants};
+use crate::ContractCode;
+use assert_matches::assert_matches;
+use unc_parameters::vm::{ContractPrepareVersion, VMKind};
+use unc_parameters::{RuntimeConfigStore, RuntimeFeesConfig};
+use unc_primitives_core::version::ProtocolFeature;
+
+/// Exercises multi-value blocks, branches and calls along with bulk memory, trapping on any
+/// unexpected result.
+static PROPOSALS_CONTRACT: &str = r#"
+(module
+  (type $swap_t (func (param i64 i64) (result i64 i64)))
+  (memory 1)
+  (table funcref (elem $swap))
+  (func $check (param i32 i32)
+    (if (i32.ne (local.get 0) (local.get 1)) (then unreachable)))
+  (func $swap (param i64 i64) (result i64 i64)
+    local.get 1
+    local.get 0)
+  (func $divmod (param i32 i32) (result i32 i32)
+    (i32.div_u (local.get 0) (local.get 1))
+    (i32.rem_u (local.get 0) (local.get 1)))
+  (func $floats (result f64 f32)
+    f64.const 1.5
+    f32.const 2.5)
+  (func $many (result i32 i32 i32 i32 i32 i32 i32 i32 i32)
+    i32.const 1 i32.const 2 i32.const 3 i32.const 4 i32.const 5
+    i32.const 6 i32.const 7 i32.const 8 i32.const 9)
+  (func $pair (param i32) (result i32 i32)
+    block (result i32 i32)
+      i32.const 1
+      i32.const 2
+      local.get 0
+      br_if 0
+      drop
+      drop
+      i32.const 3
+      i32.const 4
+    end)
+  (func $pick (param i32) (result i32 i32)
+    block (result i32 i32)
+      block (result i32 i32)
+        i32.const 10
+        i32.const 20
+        local.get 0
+        br_table 0 1
+      end
+      i32.add
+      i32.const 0
+    end)
+  (func $sum_to (param i32) (result i32)
+    (local i32)
+    i32.const 0
+    local.get 0
+    loop (param i32 i32) (result i32)
+      local.tee 1
+      i32.add
+      local.get 1
+      i32.const 1
+      i32.sub
+      local.tee 1
+      local.get 1
+      br_if 0
+      drop
+    end)
+  (func $abs_diff (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    (i32.ge_u (local.get 0) (local.get 1))
+    if (param i32 i32) (result i32)
+      i32.sub
+    else
+      drop
+      drop
+      (i32.sub (local.get 1) (local.get 0))
+    end)
+  (func $maybe_inc (param i32 i32) (result i32)
+    local.get 0
+    local.get 1
+    if (param i32) (result i32)
+      i32.const 1
+      i32.add
+    end)
+  (func (export "main")
+    (call $swap (i64.const 1) (i64.const 2))
+    (if (i64.ne (i64.const 1)) (then unreachable))
+    (if (i64.ne (i64.const 2)) (then unreachable))
+    (call_indirect (type $swap_t) (i64.const 3) (i64.const 4) (i32.const 0))
+    (if (i64.ne (i64.const 3)) (then unreachable))
+    (if (i64.ne (i64.const 4)) (then unreachable))
+    (call $divmod (i32.const 17) (i32.const 5))
+    (call $check (i32.const 2))
+    (call $check (i32.const 3))
+    (call $floats)
+    (if (f32.ne (f32.const 2.5)) (then unreachable))
+    (if (f64.ne (f64.const 1.5)) (then unreachable))
+    (call $many)
+    i32.add i32.add i32.add i32.add i32.add i32.add i32.add i32.add
+    (call $check (i32.const 45))
+    (call $pair (i32.const 1))
+    (call $check (i32.const 2))
+    (call $check (i32.const 1))
+    (call $pair (i32.const 0))
+    (call $check (i32.const 4))
+    (call $check (i32.const 3))
+    (call $pick (i32.const 0))
+    (call $check (i32.const 0))
+    (call $check (i32.const 30))
+    (call $pick (i32.const 1))
+    (call $check (i32.const 20))
+    (call $check (i32.const 10))
+    (call $check (call $sum_to (i32.const 10)) (i32.const 55))
+    (call $check (call $abs_diff (i32.const 3) (i32.const 10)) (i32.const 7))
+    (call $check (call $abs_diff (i32.const 10) (i32.const 3)) (i32.const 7))
+    (call $check (call $maybe_inc (i32.const 5) (i32.const 1)) (i32.const 6))
+    (call $check (call $maybe_inc (i32.const 5) (i32.const 0)) (i32.const 5))
+    (memory.fill (i32.const 0) (i32.const 7) (i32.const 100))
+    (memory.copy (i32.const 200) (i32.const 0) (i32.const 100))
+    (call $check (i32.load8_u (i32.const 299)) (i32.const 7))
+    (call $check (i32.load8_u (i32.const 300)) (i32.const 0)))
+)
+"#;
+
+fn proposals_config() -> Config {
+    let store = RuntimeConfigStore::new(None);
+    let config =
+        store.get_config(ProtocolFeature::WasmProposals.protocol_version()).wasm_config.clone();
+    assert_eq!(config.limit_config.contract_prepare_version, ContractPrepareVersion::V3);
+    config
+}
+
+/// Run `method` of `wat` on every VM supporting the proposals.
+fn run(config: &Config, wat: &str, method: &str, check: impl Fn(VMKind, VMOutcome)) {
+    let code = ContractCode::new(wat::parse_str(wat).unwrap(), None);
+    with_vm_variants(config, |vm_kind: VMKind| {
+        // The legacy VMs are stuck with the proposals they were shipped with.
+        if matches!(vm_kind, VMKind::Wasmer0 | VMKind::Wasmer2) {
+            return;
+        }
+        check(vm_kind, run_on(vm_kind, config, &code, method));
+    });
+}
+
+fn run_on(vm_kind: VMKind, config: &Config, code: &ContractCode, method: &str) -> VMOutcome {
+    let runtime = vm_kind.runtime(config.clone()).expect("runtime has not been compiled");
+    runtime
+        .run(
+            code,
+            method,
+            &mut MockedExternal::new(),
+            create_context(vec![]),
+            &RuntimeFeesConfig::test(),
+            &[],
+            None,
+        )
+        .expect("execution failed")
+}
+
+#[test]
+fn test_wasm_proposals() {
+    run(&proposals_config(), PROPOSALS_CONTRACT, "main", |vm_kind, outcome| {
+        assert_eq!(outcome.aborted, None, "{vm_kind:?}");
+    });
+}
+
+/// Every VM charges the per-byte cost exactly once, whether it is injected into the contract
+/// or emitted by the compiler.
+#[test]
+fn test_bulk_memory_charged_per_byte() {
+    let config = proposals_config();
+    let fill = |len: u32| {
+        let wat = format!(
+            r#"(module
+                (memory 1)
+                (func (export "main")
+                    (memory.fill (i32.const 0) (i32.const 1) (i32.const {len}))))"#
+        );
+        ContractCode::new(wat::parse_str(wat).unwrap(), None)
+    };
+    // Both lengths take up a single byte, so the contracts only differ by the bytes filled.
+    let (short, long) = (fill(10), fill(60));
+    let expected = 50 * u64::from(config.bulk_memory_byte_cost);
+    with_vm_variants(&config, |vm_kind: VMKind| {
+        let short = run_on(vm_kind, &config, &short, "main");
+        let long = run_on(vm_kind, &config, &long, "main");
+        // The legacy VMs are stuck with the proposals they were shipped with, so they have to
+        // reject the contract rather than run it with any other charge.
+        if matches!(vm_kind, VMKind::Wasmer0 | VMKind::Wasmer2) {
+            assert_matches!(
+                short.aborted,
+                Some(FunctionCallError::CompilationError(_)),
+                "{vm_kind:?}"
+            );
+            assert_matches!(
+                long.aborted,
+                Some(FunctionCallError::CompilationError(_)),
+                "{vm_kind:?}"
+            );
+            return;
+        }
+        assert_eq!(short.aborted, None, "{vm_kind:?}");
+        assert_eq!(long.aborted, None, "{vm_kind:?}");
+        assert_eq!(long.burnt_gas - short.burnt_gas, expected, "{vm_kind:?}");
+    });
+}
+
+/// The other bulk memory and table instructions are charged per byte or element too, on every VM
+/// supporting them.
+#[test]
+fn test_bulk_instructions_charged_per_unit() {
+    let config = proposals_config();
+    let byte_cost = u64::from(config.bulk_memory_byte_cost);
+    let element_cost = u64::from(config.bulk_table_element_cost);
+    assert!(element_cost > 0);
+    let cases = [
+        ("(memory.init 0 (i32.const 0) (i32.const 0) (i32.const {len}))", byte_cost),
+        ("(drop (table.grow (ref.null func) (i32.const {len})))", element_cost),
+        ("(table.fill (i32.const 0) (ref.null func) (i32.const {len}))", element_cost),
+        ("(table.copy (i32.const 0) (i32.const 1) (i32.const {len}))", element_cost),
+        ("(table.init 0 (i32.const 0) (i32.const 0) (i32.const {len}))", element_cost),
+    ];
+    for (instruction, unit_cost) in cases {
+        let contract = |len: u32| {
+            format!(
+                r#"(module
+                    (memory 1)
+                    (table 100 funcref)
+                    (func $f)
+                    (elem func {elems})
+                    (data "{data}")
+                    (func (export "main") {instruction}))"#,
+                elems = "$f ".repeat(64),
+                data = "a".repeat(64),
+                instruction = instruction.replace("{len}", &len.to_string()),
+            )
+        };
+        // Both lengths take up a single byte, so the contracts only differ by the units processed.
+        let (short, long) = (contract(10), contract(60));
+        let short = ContractCode::new(wat::parse_str(short).unwrap(), None);
+        let long = ContractCode::new(wat::parse_str(long).unwrap(), None);
+        with_vm_variants(&config, |vm_kind: VMKind| {
+            if matches!(vm_kind, VMKind::Wasmer0 | VMKind::Wasmer2) {
+                return;
+            }
+            let short = run_on(vm_kind, &config, &short, "main");
+            let long = run_on(vm_kind, &config, &long, "main");
+            assert_eq!(short.aborted, None, "{vm_kind:?} {instruction}");
+            assert_eq!(long.aborted, None, "{vm_kind:?} {instruction}");
+            assert_eq!(
+                long.burnt_gas - short.burnt_gas,
+                50 * unit_cost,
+                "{vm_kind:?} {instruction}"
+            );
+        });
+    }
+}
+
+/// Contracts may only declare a bounded number of tables, which can't grow beyond the limit on
+/// their elements.
+#[test]
+fn test_table_limits() {
+    let config = proposals_config();
+    let max_tables = config.limit_config.max_tables_per_contract.unwrap();
+    let max_elements = config.limit_config.max_elements_per_contract_table.unwrap();
+    let prepare_error =
+        |error| Some(FunctionCallError::CompilationError(CompilationError::PrepareError(error)));
+
+    let tables = "(table 1 funcref)".repeat(max_tables as usize + 1);
+    let too_many_tables = format!(r#"(module {tables} (func (export "main")))"#);
+    run(&config, &too_many_tables, "main", |vm_kind, outcome| {
+        assert_eq!(outcome.aborted, prepare_error(PrepareError::TooManyTables), "{vm_kind:?}");
+    });
+
+    let too_big_table =
+        format!(r#"(module (table {} funcref) (func (export "main")))"#, max_elements + 1);
+    run(&config, &too_big_table, "main", |vm_kind, outcome| {
+        assert_eq!(
+            outcome.aborted,
+            prepare_error(PrepareError::TooManyTableElements),
+            "{vm_kind:?}"
+        );
+    });
+
+    // A table without a declared maximum can grow up to the limit and no further.
+    let grow = format!(
+        r#"(module
+            (table 0 funcref)
+            (func (export "main")
+                (if (i32.ne (table.grow (ref.null func) (i32.const {max_elements})) (i32.const 0))
+                    (then unreachable))
+                (if (i32.ne (table.grow (ref.null func) (i32.const 1)) (i32.const -1))
+                    (then unreachable))))"#
+    );
+    run(&config, &grow, "main", |vm_kind, outcome| {
+        assert_eq!(outcome.aborted, None, "{vm_kind:?}");
+    });
+}
+
+/// Exercises `v128` values through locals, globals, memory, blocks and calls along with a sample
+/// of each family of SIMD instructions, trapping on any unexpected result.
+static SIMD_CONTRACT: &str = r#"
+(module
+  (memory 1)
+  (global $g (mut v128) (v128.const i64x2 0 0))
+  (func $check (param i64 i64)
+    (if (i64.ne (local.get 0) (local.get 1)) (then unreachable)))
+  (func $check_v128 (param v128 i64 i64)
+    (call $check (i64x2.extract_lane 0 (local.get 0)) (local.get 1))
+    (call $check (i64x2.extract_lane 1 (local.get 0)) (local.get 2)))
+  (func $swap_halves (param v128) (result v128)
+    (i8x16.shuffle 8 9 10 11 12 13 14 15 0 1 2 3 4 5 6 7 (local.get 0) (local.get 0)))
+  (func $pick (param v128 v128 i32) (result v128)
+    (local v128)
+    (local.set 3 (select (local.get 0) (local.get 1) (local.get 2)))
+    block (result v128)
+      (local.get 3)
+    end)
+  (func (export "main")
+    (local v128 i32)
+    (local.set 0 (v128.const i32x4 1 2 3 4))
+    (call $check_v128 (local.get 0) (i64.const 0x2_0000_0001) (i64.const 0x4_0000_0003))
+    (call $check_v128 (call $swap_halves (local.get 0))
+      (i64.const 0x4_0000_0003) (i64.const 0x2_0000_0001))
+    (call $check_v128 (call $pick (local.get 0) (v128.const i64x2 7 8) (i32.const 0))
+      (i64.const 7) (i64.const 8))
+    (call $check_v128 (i32x4.add (local.get 0) (i32x4.splat (i32.const 10)))
+      (i64.const 0xc_0000_000b) (i64.const 0xe_0000_000d))
+    (call $check_v128 (i64x2.mul (v128.const i64x2 3 -4) (v128.const i64x2 5 6))
+      (i64.const 15) (i64.const -24))
+    (call $check_v128 (i16x8.shr_s (v128.const i16x8 -8 8 0 0 0 0 0 0) (i32.const 2))
+      (i64.const 0x2_fffe) (i64.const 0))
+    (call $check_v128 (i8x16.popcnt (v128.const i8x16 0 1 3 7 15 31 63 127 255 0 0 0 0 0 0 0))
+      (i64.const 0x0706_0504_0302_0100) (i64.const 8))
+    (call $check_v128 (i32x4.lt_u (local.get 0) (v128.const i32x4 2 2 -1 0))
+      (i64.const 0xffff_ffff) (i64.const 0xffff_ffff))
+    (call $check_v128 (f64x2.add (v128.const f64x2 1.5 -2) (v128.const f64x2 0.5 0.5))
+      (i64.reinterpret_f64 (f64.const 2)) (i64.reinterpret_f64 (f64.const -1.5)))
+    (call $check_v128 (i32x4.trunc_sat_f32x4_u (v128.const f32x4 -1 nan 1e10 7.9))
+      (i64.const 0) (i64.const 0x7_ffff_ffff))
+    (call $check
+      (i64.extend_i32_u (i8x16.bitmask (v128.const i8x16 -1 0 -1 0 0 0 0 0 0 0 0 0 0 0 0 -1)))
+      (i64.const 0x8005))
+    (call $check (i64.extend_i32_u (i32x4.all_true (local.get 0))) (i64.const 1))
+    (global.set $g (i32x4.replace_lane 2 (local.get 0) (i32.const 9)))
+    (v128.store offset=16 (i32.const 0) (global.get $g))
+    (call $check (i64.load offset=24 (i32.const 0)) (i64.const 0x4_0000_0009))
+    (call $check_v128 (v128.load32_zero offset=20 (i32.const 0)) (i64.const 2) (i64.const 0))
+    (call $check_v128 (v128.load8_lane 15 (i32.const 16) (v128.const i64x2 0 0))
+      (i64.const 0) (i64.const 0x0100_0000_0000_0000)))
+)
+"#;
+
+fn simd_config() -> Config {
+    let store = RuntimeConfigStore::new(None);
+    let config = store.get_config(ProtocolFeature::WasmSimd.protocol_version()).wasm_config.clone();
+    assert_eq!(config.limit_config.contract_prepare_version, ContractPrepareVersion::V4);
+    config
+}
+
+#[test]
+fn test_wasm_simd() {
+    run(&simd_config(), SIMD_CONTRACT, "main", |vm_kind, outcome| {
+        assert_eq!(outcome.aborted, None, "{vm_kind:?}");
+    });
+}
+
+#[test]
+fn test_simd_charged_per_family() {
+    let config = simd_config();
+    let contract = |op: &str| {
+        format!(
+            r#"(module
+                (func (export "main")
+                    (drop ({op} (v128.const i64x2 1 2) (v128.const i64x2 3 4)))))"#
+        )
+    };
+    let burnt_gas = |op: &str| {
+        let gas = std::cell::RefCell::new(vec![]);
+        run(&config, &contract(op), "main", |vm_kind, outcome| {
+            assert_eq!(outcome.aborted, None, "{vm_kind:?}");
+            gas.borrow_mut().push(outcome.burnt_gas);
+        });
+        gas.into_inner()
+    };
+    // Each operation is compared to a basic one with an opcode of the same encoded length, so
+    // that the contracts only differ by the cost of the operation.
+    for (basic_op, op, cost) in [
+        ("i32x4.add", "i32x4.mul", config.simd_mul_op_cost),
+        ("i32x4.add", "f32x4.add", config.simd_float_op_cost),
+        ("v128.and", "i8x16.swizzle", config.simd_shuffle_op_cost),
+    ] {
+        for (basic, gas) in burnt_gas(basic_op).into_iter().zip(burnt_gas(op)) {
+            assert_eq!(gas - basic, u64::from(cost - config.simd_op_cost), "{op}");
+        }
+    }
+}
+
+/// Floating point SIMD operations only ever produce the canonical NaN, whatever NaNs they are
+/// given, and never the negative one x86 computes by default. Wasmtime does not canonicalize
+/// NaNs, so only UncVM is checked.
+#[test]
+fn test_simd_nan_canonicalization() {
+    let config = simd_config();
+    // NaNs with the sign bit set, payloads or both, including signaling ones.
+    let f32_nans = "(v128.const i32x4 0xffc00000 0x7f800001 0xffffffff 0x7fc00001)";
+    let f64_nans = "(v128.const i64x2 0xfff8000000000000 0x7ff0000000000001)";
+    let f32_one = "(f32x4.splat (f32.const 1))";
+    let f64_one = "(f64x2.splat (f64.const 1))";
+    let f32_canonical = "0x7fc0_0000_7fc0_0000";
+    let f64_canonical = "0x7ff8_0000_0000_0000";
+
+    let mut cases = vec![];
+    for (shape, nans, one, canonical) in
+        [("f32x4", f32_nans, f32_one, f32_canonical), ("f64x2", f64_nans, f64_one, f64_canonical)]
+    {
+        for op in ["add", "sub", "mul", "div", "min", "max"] {
+            cases.push((format!("({shape}.{op} {nans} {one})"), canonical, canonical));
+            cases.push((format!("({shape}.{op} {one} {nans})"), canonical, canonical));
+        }
+        for op in ["sqrt", "ceil", "floor", "trunc", "nearest"] {
+            cases.push((format!("({shape}.{op} {nans})"), canonical, canonical));
+        }
+    }
+    cases.extend([
+        (format!("(f32x4.demote_f64x2_zero {f64_nans})"), f32_canonical, "0"),
+        (format!("(f64x2.promote_low_f32x4 {f32_nans})"), f64_canonical, f64_canonical),
+        ("(f32x4.sqrt (f32x4.splat (f32.const -1)))".to_string(), f32_canonical, f32_canonical),
+        (
+            "(f64x2.div (f64x2.splat (f64.const 0)) (f64x2.splat (f64.const 0)))".to_string(),
+            f64_canonical,
+            f64_canonical,
+        ),
+        (
+            "(f32x4.sub (f32x4.splat (f32.const inf)) (f32x4.splat (f32.const inf)))".to_string(),
+            f32_canonical,
+            f32_canonical,
+        ),
+    ]);
+
+    for (expr, low, high) in cases {
+        let wat = format!(
+            r#"(module
+                (func $check (param i64 i64)
+                    (if (i64.ne (local.get 0) (local.get 1)) (then unreachable)))
+                (func (export "main")
+                    (local v128)
+                    (local.set 0 {expr})
+                    (call $check (i64x2.extract_lane 0 (local.get 0)) (i64.const {low}))
+                    (call $check (i64x2.extract_lane 1 (local.get 0)) (i64.const {high}))))"#
+        );
+        let code = ContractCode::new(wat::parse_str(wat).unwrap(), None);
+        with_vm_variants(&config, |vm_kind: VMKind| {
+            if vm_kind != VMKind::UncVm {
+                return;
+            }
+            let outcome = run_on(vm_kind, &config, &code, "main");
+            assert_eq!(outcome.aborted, None, "{expr}");
+        });
+    }
+}
@@ -3,6 +3,7 @@ use super::test_vm_config;
 use crate::prepare::prepare_contract;
 use crate::tests::with_vm_variants;
 use expect_test::expect;
+use unc_parameters::vm::ContractPrepareVersion;
 
 static SIMD: &str = r#"
 (module
@@ -92,21 +93,74 @@ static EXPECTED_UNSUPPORTED: &[(&str, &str)] = &[
     ("multi_memory", MULTI_MEMORY),
     // ("module_linking", MODULE_LINKING),
     ("tail_call", TAIL_CALL),
+    ("threads", THREADS),
+];
+
+/// Proposals only supported starting with `ContractPrepareVersion::V3`.
+static V3_PROPOSALS: &[(&str, &str)] = &[
     ("multi_value", MULTI_VALUE),
     ("bulk_memory", BULK_MEMORY),
     ("reference_types", REFERENCE_TYPES),
-    ("threads", THREADS),
-    ("simd", SIMD),
 ];
 
+/// Proposals only supported starting with `ContractPrepareVersion::V4`.
+static V4_PROPOSALS: &[(&str, &str)] = &[("simd", SIMD)];
+
 #[test]
 fn ensure_fails_verification() {
-    let config = test_vm_config();
+    for prepare_version in
+        [ContractPrepareVersion::V2, ContractPrepareVersion::V3, ContractPrepareVersion::V4]
+    {
+        let mut config = test_vm_config();
+        config.limit_config.contract_prepare_version = prepare_version;
+        let unsupported = match prepare_version {
+            ContractPrepareVersion::V4 => EXPECTED_UNSUPPORTED.to_vec(),
+            ContractPrepareVersion::V3 => [EXPECTED_UNSUPPORTED, V4_PROPOSALS].concat(),
+            _ => [EXPECTED_UNSUPPORTED, V3_PROPOSALS, V4_PROPOSALS].concat(),
+        };
+        with_vm_variants(&config, |kind| {
+            for (feature_name, wat) in &unsupported {
+                let wasm = wat::parse_str(wat).expect("parsing test wat should succeed");
+                if let Ok(_) = prepare_contract(&wasm, &config, kind) {
+                    panic!(
+                        "wasm containing use of {} feature did not fail to prepare",
+                        feature_name
+                    );
+                }
+            }
+        });
+    }
+}
+
+#[test]
+fn ensure_v3_proposals_pass_verification() {
+    let mut config = test_vm_config();
+    config.limit_config.contract_prepare_version = ContractPrepareVersion::V3;
+    with_vm_variants(&config, |kind| {
+        for (feature_name, wat) in V3_PROPOSALS {
+            let wasm = wat::parse_str(wat).expect("parsing test wat should succeed");
+            if let Err(err) = prepare_contract(&wasm, &config, kind) {
+                panic!(
+                    "wasm containing use of {} feature failed to prepare: {}",
+                    feature_name, err
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn ensure_v4_proposals_pass_verification() {
+    let mut config = test_vm_config();
+    config.limit_config.contract_prepare_version = ContractPrepareVersion::V4;
     with_vm_variants(&config, |kind| {
-        for (feature_name, wat) in EXPECTED_UNSUPPORTED {
+        for (feature_name, wat) in V3_PROPOSALS.iter().chain(V4_PROPOSALS) {
             let wasm = wat::parse_str(wat).expect("parsing test wat should succeed");
-            if let Ok(_) = prepare_contract(&wasm, &config, kind) {
-                panic!("wasm containing use of {} feature did not fail to prepare", feature_name);
+            if let Err(err) = prepare_contract(&wasm, &config, kind) {
+                panic!(
+                    "wasm containing use of {} feature failed to prepare: {}",
+                    feature_name, err
+                );
             }
         }
     });
@@ -114,7 +168,8 @@ fn ensure_fails_verification() {
 
 #[test]
 fn ensure_fails_execution() {
-    for (_feature_name, wat) in EXPECTED_UNSUPPORTED {
+    for (_feature_name, wat) in EXPECTED_UNSUPPORTED.iter().chain(V3_PROPOSALS).chain(V4_PROPOSALS)
+    {
         test_builder().wat(wat).opaque_error().opaque_outcome().expect(&expect![[r#"
             Err: ...
         "#]]);
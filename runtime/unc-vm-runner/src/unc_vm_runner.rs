@@ -239,6 +239,8 @@ impl UncVM {
         assert_eq!(VM_CONFIG.compiler, UncVmCompiler::Singlepass);
         let mut compiler = Singlepass::new();
         compiler.set_9393_fix(!config.disable_9393_fix);
+        compiler.set_bulk_memory_byte_cost(config.bulk_memory_byte_cost);
+        compiler.set_bulk_table_element_cost(config.bulk_table_element_cost);
         // We only support universal engine at the moment.
         assert_eq!(VM_CONFIG.engine, UncVmEngine::Universal);
 
@@ -581,7 +583,10 @@ impl unc_vm_vm::Tunables for &UncVM {
 
     /// Instrumentation configuration: gas accounting config
     fn gas_cfg(&self) -> Box<dyn finite_wasm::wasmparser::VisitOperator<Output = u64>> {
-        Box::new(GasCostCfg(u64::from(self.config.regular_op_cost)))
+        Box::new(GasCostCfg(
+            u64::from(self.config.regular_op_cost),
+            crate::instrument::gas::simd::SimdOpCosts::new(&self.config),
+        ))
     }
 }
 
@@ -619,7 +624,8 @@ impl finite_wasm::max_stack::SizeConfig for MaxStackCfg {
     }
 }
 
-struct GasCostCfg(u64);
+/// Gas cost of the regular instructions and of the SIMD ones.
+struct GasCostCfg(u64, crate::instrument::gas::simd::SimdOpCosts);
 
 macro_rules! gas_cost {
     ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
@@ -639,6 +645,9 @@ macro_rules! gas_cost {
     (@@mvp $_op:ident $_self:ident $({ $($_arg:ident: $_argty:ty),* })? => visit_else) => {
         0
     };
+    (@@simd $op:ident $self:ident $({ $($arg:ident: $_argty:ty),* })? => $_visit:ident) => {
+        $self.1.cost(&finite_wasm::wasmparser::Operator::$op $({ $($arg),* })?)
+    };
     (@@$_proposal:ident $_op:ident $self:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident) => {
         $self.0
     };
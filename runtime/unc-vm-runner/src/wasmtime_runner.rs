@@ -1,14 +1,17 @@
+#[cfg(feature = "sandbox")]
+use crate::debugger::{DebugOptions, Debugger};
 use crate::errors::{ContractPrecompilatonResult, IntoVMError};
+use crate::instrument::gas;
 use crate::logic::errors::{
     CompilationError, FunctionCallError, MethodResolveError, PrepareError, VMLogicError,
     VMRunnerError, WasmTrap,
 };
 use crate::logic::types::PromiseResult;
-use crate::logic::Config;
 use crate::logic::{
     CompiledContractCache, External, MemSlice, MemoryLike, VMContext, VMLogic, VMOutcome,
 };
-use crate::{imports, prepare, ContractCode};
+use crate::logic::{Config, ContractPrepareVersion};
+use crate::{imports, prepare, ContractCode, FunctionGasProfile};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use unc_parameters::vm::VMKind;
@@ -149,10 +152,10 @@ impl WasmtimeVM {
         config.max_wasm_stack(1024 * 1024 * 1024); // wasm stack metering is implemented by instrumentation, we don't want wasmtime to trap before that
         config
     }
-}
 
-impl crate::runner::VM for WasmtimeVM {
-    fn run(
+    /// Run `method_name` of the contract, passing the prepared code through `instrument` before
+    /// it is compiled.
+    fn run_with(
         &self,
         code: &ContractCode,
         method_name: &str,
@@ -160,7 +163,7 @@ impl crate::runner::VM for WasmtimeVM {
         context: VMContext,
         fees_config: &RuntimeFeesConfig,
         promise_results: &[PromiseResult],
-        _cache: Option<&dyn CompiledContractCache>,
+        instrument: impl FnOnce(&mut VMLogic<'_>, Vec<u8>) -> Result<Vec<u8>, PrepareError>,
     ) -> Result<VMOutcome, VMRunnerError> {
         let mut config = self.default_wasmtime_config();
         let engine = get_engine(&mut config);
@@ -185,6 +188,10 @@ impl crate::runner::VM for WasmtimeVM {
                 Ok(code) => code,
                 Err(err) => return Ok(VMOutcome::abort(logic, FunctionCallError::from(err))),
             };
+        let prepared_code = match instrument(&mut logic, prepared_code) {
+            Ok(code) => code,
+            Err(err) => return Ok(VMOutcome::abort(logic, FunctionCallError::from(err))),
+        };
         let module = match Module::new(&engine, prepared_code) {
             Ok(module) => module,
             Err(err) => return Ok(VMOutcome::abort(logic, err.into_vm_error()?)),
@@ -241,6 +248,86 @@ impl crate::runner::VM for WasmtimeVM {
         }
     }
 
+    /// Run `method_name` of the contract with the debug probes requested by `options`.
+    #[cfg(feature = "sandbox")]
+    pub(crate) fn run_debug(
+        &self,
+        code: &ContractCode,
+        method_name: &str,
+        ext: &mut dyn External,
+        context: VMContext,
+        fees_config: &RuntimeFeesConfig,
+        promise_results: &[PromiseResult],
+        options: DebugOptions,
+    ) -> Result<VMOutcome, VMRunnerError> {
+        self.run_with(
+            code,
+            method_name,
+            ext,
+            context,
+            fees_config,
+            promise_results,
+            |logic, prepared_code| {
+                let debugger = Debugger::new(code.code(), options)?;
+                let prepared_code = gas::debug::inject_debug_probes(
+                    &prepared_code,
+                    "internal",
+                    "finite_wasm_gas",
+                    debugger.functions(),
+                    debugger.single_step(),
+                )?;
+                logic.enable_debugger(debugger);
+                Ok(prepared_code)
+            },
+        )
+    }
+}
+
+impl crate::runner::VM for WasmtimeVM {
+    fn run(
+        &self,
+        code: &ContractCode,
+        method_name: &str,
+        ext: &mut dyn External,
+        context: VMContext,
+        fees_config: &RuntimeFeesConfig,
+        promise_results: &[PromiseResult],
+        _cache: Option<&dyn CompiledContractCache>,
+    ) -> Result<VMOutcome, VMRunnerError> {
+        // Only the finite-wasm instrumentation used since prepare version V2 can be profiled, and
+        // only sandbox nodes profile at all, as in `crate::run`.
+        let profile_functions = cfg!(feature = "sandbox")
+            && context.profiles_functions()
+            && !matches!(
+                self.config.limit_config.contract_prepare_version,
+                ContractPrepareVersion::V0 | ContractPrepareVersion::V1
+            );
+        self.run_with(
+            code,
+            method_name,
+            ext,
+            context,
+            fees_config,
+            promise_results,
+            |logic, prepared_code| {
+                if !profile_functions {
+                    return Ok(prepared_code);
+                }
+                let prepared_code = gas::profile::inject_function_profiling(
+                    &prepared_code,
+                    "internal",
+                    "finite_wasm_gas",
+                )?;
+                let names = gas::profile::function_names(code.code());
+                logic.enable_function_profile(FunctionGasProfile::new(
+                    method_name.to_string(),
+                    names,
+                ));
+                Ok(prepared_code)
+            },
+        )
+    }
+
     fn precompile(
         &self,
         _code: &ContractCode,
@@ -58,18 +58,32 @@ pub(crate) struct FuncGen<'a> {
     /// Types of the local variables, including arguments.
     local_types: unc_vm_types::partial_sum_map::PartialSumMap<u32, WpType>,
 
+    /// Runs of `v128` locals, as `(index of the first one, count, v128 locals before the run)`.
+    ///
+    /// A `v128` local takes up two consecutive machine locals, its low half first.
+    v128_local_runs: Vec<(u32, u32, u32)>,
+
     /// Value stack.
     value_stack: Vec<Location>,
 
     /// Metadata about floating point values on the stack.
     fp_stack: Vec<FloatValue>,
 
+    /// Depths in the main value stack of the `v128` values on it.
+    ///
+    /// A `v128` takes up two consecutive entries of the value stack, its low half first, and
+    /// its depth is that of the low half.
+    v128_stack: Vec<usize>,
+
     /// A list of frames describing the current control stack.
     control_stack: Vec<ControlFrame>,
 
     /// Low-level machine state.
     machine: Machine,
 
+    /// Number of scratch slots needed to pass multiple values across branches and calls.
+    scratch_slots: usize,
+
     /// Nesting level of unreachable code.
     unreachable_depth: usize,
 
@@ -227,8 +241,18 @@ pub(crate) struct ControlFrame {
     pub(crate) loop_like: bool,
     pub(crate) if_else: IfElseState,
     pub(crate) returns: SmallVec<[WpType; 1]>,
+    /// Number of values the block takes as parameters.
+    ///
+    /// For `if` and `loop` blocks, these are duplicated on entry, so the parameters visible to
+    /// the block body sit right above the original values.
+    pub(crate) param_count: usize,
+    /// For `loop` blocks, the locations `br` opcodes should place the parameters at.
+    pub(crate) param_locations: SmallVec<[Location; 1]>,
+    /// For `loop` blocks, the machine stack offset at the start of the loop.
+    pub(crate) loop_stack_offset: usize,
     pub(crate) value_stack_depth: usize,
     pub(crate) fp_stack_depth: usize,
+    pub(crate) v128_stack_depth: usize,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -279,10 +303,8 @@ impl<'a> FuncGen<'a> {
     fn emit_call(&mut self, function: FunctionIndex) -> Result<(), CodegenError> {
         let sig_index = *self.module.functions.get(function).unwrap();
         let sig = self.module.signatures.get(sig_index).unwrap();
-        let param_types: SmallVec<[WpType; 8]> =
-            sig.params().iter().cloned().map(type_to_wp_type).collect();
-        let return_types: SmallVec<[WpType; 1]> =
-            sig.results().iter().cloned().map(type_to_wp_type).collect();
+        let param_types: SmallVec<[WpType; 8]> = machine_types(sig.params());
+        let return_types: SmallVec<[WpType; 1]> = machine_types(sig.results());
 
         let params: SmallVec<[_; 8]> =
             self.value_stack.drain(self.value_stack.len() - param_types.len()..).collect();
@@ -307,6 +329,7 @@ impl<'a> FuncGen<'a> {
                 break;
             }
         }
+        self.v128_stack.truncate(self.v128_stack_depth_at(self.value_stack.len()));
 
         let reloc_at = self.assembler.get_offset().0 + self.assembler.arch_mov64_imm_offset();
         // Imported functions are called through trampolines placed as custom sections.
@@ -325,29 +348,261 @@ impl<'a> FuncGen<'a> {
         // The Imm64 value is relocated by the JIT linker.
         self.assembler.emit_mov(Size::S64, Location::Imm64(std::u64::MAX), Location::GPR(GPR::RAX));
 
+        let return_area = self.call_return_area(&return_types);
         self.emit_call_native(
             |this| {
+                if let Some(return_area) = return_area {
+                    this.assembler.emit_lea(Size::S64, return_area, Location::GPR(GPR::R11));
+                }
                 this.assembler.emit_call_location(Location::GPR(GPR::RAX));
             },
             params.iter().copied(),
         )?;
 
         self.machine.release_locations_only_stack(self.assembler, &params);
+        self.push_call_results(&return_types);
+        Ok(())
+    }
+
+    /// Location of the area the callee writes all but its first return value to, if it has more
+    /// than one.
+    ///
+    /// The callee expects the address of this area in `R11`.
+    fn call_return_area(&self, return_types: &[WpType]) -> Option<Location> {
+        (return_types.len() > 1).then(|| self.machine.get_scratch_location(0))
+    }
 
-        if !return_types.is_empty() {
-            let ret =
-                self.machine.acquire_locations(self.assembler, &[(return_types[0])], false)[0];
+    /// Push the values returned by a call that has just completed onto the value stack.
+    fn push_call_results(&mut self, return_types: &[WpType]) {
+        if return_types.is_empty() {
+            return;
+        }
+        let rets = self.machine.acquire_locations(self.assembler, return_types, false);
+        for (i, (&ty, &ret)) in return_types.iter().zip(rets.iter()).enumerate() {
             self.value_stack.push(ret);
-            if return_types[0].is_float() {
-                self.assembler.emit_mov(Size::S64, Location::XMM(XMM::XMM0), ret);
+            if i == 0 {
+                if ty.is_float() {
+                    self.assembler.emit_mov(Size::S64, Location::XMM(XMM::XMM0), ret);
+                } else {
+                    self.assembler.emit_mov(Size::S64, Location::GPR(GPR::RAX), ret);
+                }
+            } else {
+                let src = self.machine.get_scratch_location(i - 1);
+                self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, src, ret);
+            }
+            if ty.is_float() {
                 self.fp_stack.push(FloatValue::new(self.value_stack.len() - 1));
+            }
+        }
+        self.track_v128_values(return_types);
+    }
+
+    /// Record the `v128` values among the values of the given types, which have just been
+    /// pushed onto the value stack.
+    fn track_v128_values(&mut self, types: &[WpType]) {
+        let base = self.value_stack.len() - types.len();
+        let mut i = 0;
+        while i < types.len() {
+            if types[i] == WpType::V128 {
+                self.v128_stack.push(base + i);
+                i += 2;
             } else {
-                self.assembler.emit_mov(Size::S64, Location::GPR(GPR::RAX), ret);
+                i += 1;
+            }
+        }
+    }
+
+    /// Copy the `count` values at the top of the value stack to the scratch area, in order.
+    ///
+    /// This is how more than one value is carried across a branch, as the value stack locations
+    /// on either side of it need not match.
+    fn emit_stash_values(&mut self, count: usize) {
+        let base = self.value_stack.len() - count;
+        for i in 0..count {
+            let loc = self.value_stack[base + i];
+            let dst = self.machine.get_scratch_location(i);
+            let canonicalization = self
+                .fp_stack
+                .iter()
+                .rev()
+                .take_while(|fp| fp.depth >= base)
+                .find(|fp| fp.depth == base + i)
+                .and_then(|fp| fp.canonicalization);
+            match canonicalization {
+                Some(canonicalization)
+                    if self.assembler.arch_supports_canonicalize_nan()
+                        && self.config.enable_nan_canonicalization =>
+                {
+                    self.canonicalize_nan(canonicalization.to_size(), loc, dst);
+                }
+                _ => self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, loc, dst),
+            }
+        }
+    }
+
+    /// Push values of the given types, previously stored with
+    /// [`emit_stash_values`](Self::emit_stash_values), onto the value stack.
+    fn emit_unstash_values(&mut self, types: &[WpType]) {
+        let locs = self.machine.acquire_locations(self.assembler, types, false);
+        for (i, (&ty, &loc)) in types.iter().zip(locs.iter()).enumerate() {
+            let src = self.machine.get_scratch_location(i);
+            self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, src, loc);
+            self.value_stack.push(loc);
+            if ty.is_float() {
+                self.fp_stack.push(FloatValue::new(self.value_stack.len() - 1));
+                // we already canonicalized when stashing.
+            }
+        }
+        self.track_v128_values(types);
+    }
+
+    /// Push a copy of the `count` values at the top of the value stack.
+    ///
+    /// Used to give `if` and `loop` blocks their own locations for their parameters.
+    fn emit_duplicate_values(&mut self, count: usize) -> SmallVec<[Location; 1]> {
+        let base = self.value_stack.len() - count;
+        let fp_values: SmallVec<[FloatValue; 1]> = self
+            .fp_stack
+            .iter()
+            .filter(|fp| fp.depth >= base)
+            .map(|fp| FloatValue { depth: fp.depth + count, ..*fp })
+            .collect();
+        let v128_values: SmallVec<[usize; 1]> =
+            self.v128_stack.iter().filter(|&&depth| depth >= base).map(|d| d + count).collect();
+        let types: SmallVec<[WpType; 1]> = (base..base + count)
+            .map(|i| {
+                if fp_values.iter().any(|fp| fp.depth == i + count) {
+                    WpType::F64
+                } else {
+                    WpType::I64
+                }
+            })
+            .collect();
+        let locs = self.machine.acquire_locations(self.assembler, &types, false);
+        for (i, &loc) in locs.iter().enumerate() {
+            let src = self.value_stack[base + i];
+            self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, src, loc);
+        }
+        self.value_stack.extend(locs.iter().copied());
+        self.fp_stack.extend(fp_values);
+        self.v128_stack.extend(v128_values);
+        locs
+    }
+
+    /// Move the value at the top of the value stack to `RAX`, where a single value is passed
+    /// across a branch.
+    fn emit_return_value(&mut self, ty: WpType) -> Result<(), CodegenError> {
+        let loc = *self.value_stack.last().unwrap();
+        if ty.is_float() {
+            let fp = self.fp_stack.peek1()?;
+            if self.assembler.arch_supports_canonicalize_nan()
+                && self.config.enable_nan_canonicalization
+                && fp.canonicalization.is_some()
+            {
+                self.canonicalize_nan(
+                    match ty {
+                        WpType::F32 => Size::S32,
+                        WpType::F64 => Size::S64,
+                        _ => unreachable!(),
+                    },
+                    loc,
+                    Location::GPR(GPR::RAX),
+                );
+                return Ok(());
+            }
+        }
+        self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, loc, Location::GPR(GPR::RAX));
+        Ok(())
+    }
+
+    /// Pass the values expected by the target block on to it, ahead of the block being exited
+    /// via its label or by falling through its end.
+    fn emit_block_results(&mut self, returns: &[WpType]) -> Result<(), CodegenError> {
+        match returns {
+            [] => Ok(()),
+            [ty] => self.emit_return_value(*ty),
+            _ => {
+                self.emit_stash_values(returns.len());
+                Ok(())
+            }
+        }
+    }
+
+    /// Emit a branch to the control frame `relative_depth` levels up, carrying along the values
+    /// it expects.
+    ///
+    /// The machine state is left untouched, so that `br_if` and `br_table` can keep generating
+    /// code for the other paths.
+    fn emit_br(&mut self, relative_depth: u32) -> Result<(), CodegenError> {
+        let frame = &self.control_stack[self.control_stack.len() - 1 - (relative_depth as usize)];
+        let br_label = frame.br_label;
+        let value_stack_depth = frame.value_stack_depth;
+        if frame.loop_like && frame.param_count > 0 {
+            let param_locations = frame.param_locations.clone();
+            let loop_stack_offset = frame.loop_stack_offset;
+            self.emit_stash_values(param_locations.len());
+            // The loop body may have consumed the parameters' locations, so the stack height at
+            // the start of the loop can't be derived from the value stack.
+            let stack_offset = self.machine.get_stack_offset();
+            if stack_offset > loop_stack_offset {
+                self.assembler.emit_add(
+                    Size::S64,
+                    Location::Imm32((stack_offset - loop_stack_offset) as u32),
+                    Location::GPR(GPR::RSP),
+                );
+            } else if stack_offset < loop_stack_offset {
+                self.assembler.emit_sub(
+                    Size::S64,
+                    Location::Imm32((loop_stack_offset - stack_offset) as u32),
+                    Location::GPR(GPR::RSP),
+                );
+            }
+            for (i, &loc) in param_locations.iter().enumerate() {
+                let src = self.machine.get_scratch_location(i);
+                self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, src, loc);
             }
+        } else {
+            if !frame.loop_like {
+                let returns = frame.returns.clone();
+                self.emit_block_results(&returns)?;
+            }
+            let released = &self.value_stack[value_stack_depth..];
+            self.machine.release_locations_keep_state(self.assembler, released);
         }
+        self.assembler.emit_jmp(Condition::None, br_label);
         Ok(())
     }
 
+    /// Finish the `then` branch of the innermost `if` block and start its `else` branch.
+    fn emit_else(&mut self, was_unreachable: bool) -> Result<(), CodegenError> {
+        let frame = self.control_stack.last().unwrap();
+        let returns = frame.returns.clone();
+        if !was_unreachable {
+            self.emit_block_results(&returns)?;
+        }
+
+        let frame = self.control_stack.last_mut().unwrap();
+        // Only the copies of the parameters belong to the `then` branch.
+        let keep = frame.value_stack_depth + frame.param_count;
+        let released: &[Location] = &self.value_stack[keep..];
+        self.machine.release_locations(self.assembler, released);
+        self.value_stack.truncate(keep);
+        self.fp_stack.retain(|fp| fp.depth < keep);
+        self.v128_stack.retain(|&depth| depth < keep);
+
+        match frame.if_else {
+            IfElseState::If(label) => {
+                let params = &self.value_stack[frame.value_stack_depth..];
+                self.machine.release_locations_keep_state(self.assembler, params);
+                self.assembler.emit_jmp(Condition::None, frame.br_label);
+                self.assembler.emit_label(label);
+                frame.if_else = IfElseState::Else;
+                Ok(())
+            }
+            _ => Err(CodegenError { message: "Else: frame.if_else unreachable code".to_string() }),
+        }
+    }
+
     fn emit_gas_const(&mut self, cost: u64) {
         if self.config.disable_9393_fix {
             // emit_gas only supports Imm32 with an argument up-to i32::MAX, but we made *this*
@@ -429,6 +684,31 @@ impl<'a> FuncGen<'a> {
         self.machine.release_temp_gpr(current_burnt_reg);
     }
 
+    /// Charge the per-byte or per-element gas of a bulk memory or table instruction processing
+    /// `len` bytes or elements, at `unit_cost` gas each.
+    ///
+    /// This is the only place UncVM charges it: contracts prepared for UncVM are not
+    /// instrumented with this gas like they are for the other VMs.
+    fn emit_bulk_gas(&mut self, len: Location, unit_cost: u32) {
+        if unit_cost == 0 {
+            return;
+        }
+        // Both factors fit into 32 bits, so the 64-bit product can never overflow.
+        let cost_reg = self.machine.acquire_temp_gpr().unwrap();
+        let unit_cost_reg = self.machine.acquire_temp_gpr().unwrap();
+        // A 32-bit move zero-extends the unsigned length into the whole register.
+        self.assembler.emit_mov(Size::S32, len, Location::GPR(cost_reg));
+        self.assembler.emit_mov(
+            Size::S64,
+            Location::Imm64(u64::from(unit_cost)),
+            Location::GPR(unit_cost_reg),
+        );
+        self.assembler.emit_imul(Size::S64, Location::GPR(unit_cost_reg), Location::GPR(cost_reg));
+        self.machine.release_temp_gpr(unit_cost_reg);
+        self.emit_gas(Location::GPR(cost_reg));
+        self.machine.release_temp_gpr(cost_reg);
+    }
+
     fn emit_trap(&mut self, code: TrapCode) {
         let label = self.assembler.get_label();
         self.assembler.emit_label(label);
@@ -771,6 +1051,824 @@ impl<'a> FuncGen<'a> {
         Ok(())
     }
 
+    /// Whether the value right below the `above` topmost values of the value stack is a `v128`.
+    fn has_v128_below(&self, above: usize) -> bool {
+        self.v128_stack.last().map_or(false, |&depth| depth + 2 + above == self.value_stack.len())
+    }
+
+    /// Pop a `v128` off the value stack, returning the locations of its low and high halves.
+    fn pop_v128_released(&mut self) -> (Location, Location) {
+        let depth = self.v128_stack.pop().expect("pop_v128_released: no v128 on the value stack");
+        debug_assert_eq!(depth + 2, self.value_stack.len());
+        let hi = self.pop_value_released();
+        let lo = self.pop_value_released();
+        (lo, hi)
+    }
+
+    /// Locations of the low and high halves of the `v128` at the top of the value stack.
+    fn peek_v128(&self) -> (Location, Location) {
+        debug_assert!(self.has_v128_below(0));
+        let len = self.value_stack.len();
+        (self.value_stack[len - 2], self.value_stack[len - 1])
+    }
+
+    /// Push a new `v128` onto the value stack, returning the locations of its low and high halves.
+    fn push_v128(&mut self) -> (Location, Location) {
+        let locs = self.machine.acquire_locations(self.assembler, &[WpType::V128; 2], false);
+        self.v128_stack.push(self.value_stack.len());
+        self.value_stack.extend(locs.iter().copied());
+        (locs[0], locs[1])
+    }
+
+    /// Copy the `v128` with halves at `src` to the halves at `dst`, which may overlap them.
+    fn emit_v128_move(&mut self, src: (Location, Location), dst: (Location, Location)) {
+        let lo = self.machine.acquire_temp_gpr().unwrap();
+        let hi = self.machine.acquire_temp_gpr().unwrap();
+        self.assembler.emit_mov(Size::S64, src.0, Location::GPR(lo));
+        self.assembler.emit_mov(Size::S64, src.1, Location::GPR(hi));
+        self.assembler.emit_mov(Size::S64, Location::GPR(lo), dst.0);
+        self.assembler.emit_mov(Size::S64, Location::GPR(hi), dst.1);
+        self.machine.release_temp_gpr(hi);
+        self.machine.release_temp_gpr(lo);
+    }
+
+    /// Materialize the `v128` constant `value` in `dst`.
+    fn emit_v128_const(&mut self, value: u128, dst: XMM) {
+        match value {
+            0 => self.assembler.emit_vpxor(dst, XMMOrMemory::XMM(dst), dst),
+            u128::MAX => self.assembler.emit_vpcmpeqd(dst, XMMOrMemory::XMM(dst), dst),
+            _ => {
+                let tmp = self.machine.acquire_temp_gpr().unwrap();
+                let (lo, hi) = (value as u64, (value >> 64) as u64);
+                self.assembler.emit_mov(Size::S64, Location::Imm64(lo), Location::GPR(tmp));
+                self.assembler.emit_mov(Size::S64, Location::GPR(tmp), Location::XMM(dst));
+                if hi != lo {
+                    self.assembler.emit_mov(Size::S64, Location::Imm64(hi), Location::GPR(tmp));
+                }
+                self.assembler.emit_vpinsrq(dst, GPROrMemory::GPR(tmp), 1, dst);
+                self.machine.release_temp_gpr(tmp);
+            }
+        }
+    }
+
+    /// Load the `v128` with halves at `src` into `dst`.
+    fn emit_v128_load(&mut self, (lo, hi): (Location, Location), dst: XMM) {
+        if let (Location::Imm64(lo), Location::Imm64(hi)) = (lo, hi) {
+            return self.emit_v128_const(u128::from(hi) << 64 | u128::from(lo), dst);
+        }
+        self.assembler.emit_mov(Size::S64, lo, Location::XMM(dst));
+        self.assembler.emit_vpinsrq(dst, to_gpr_or_memory(hi), 1, dst);
+    }
+
+    /// Store `src` to the halves of the `v128` at `dst`.
+    fn emit_v128_store(&mut self, src: XMM, (lo, hi): (Location, Location)) {
+        self.assembler.emit_mov(Size::S64, Location::XMM(src), lo);
+        self.assembler.emit_vpextrq(src, 1, to_gpr_or_memory(hi));
+    }
+
+    /// Canonicalize the NaNs among the floating point lanes of `x`, if configured to.
+    ///
+    /// Unlike scalars, `v128` values are canonicalized right away, as there is no telling which
+    /// of their lanes a later operation is going to observe.
+    fn emit_simd_canonicalize_nan(&mut self, sz: Size, x: XMM) {
+        if !(self.assembler.arch_supports_canonicalize_nan()
+            && self.config.enable_nan_canonicalization)
+        {
+            return;
+        }
+        let mask = self.machine.acquire_temp_xmm().unwrap();
+        let nan = self.machine.acquire_temp_xmm().unwrap();
+        match sz {
+            Size::S32 => {
+                self.assembler.emit_vcmpunordps(x, XMMOrMemory::XMM(x), mask);
+                self.emit_v128_const(splat(0x7FC0_0000, 32), nan); // Canonical NaN
+                self.assembler.emit_vblendvps(mask, XMMOrMemory::XMM(nan), x, x);
+            }
+            Size::S64 => {
+                self.assembler.emit_vcmpunordpd(x, XMMOrMemory::XMM(x), mask);
+                self.emit_v128_const(splat(0x7FF8_0000_0000_0000, 64), nan); // Canonical NaN
+                self.assembler.emit_vblendvpd(mask, XMMOrMemory::XMM(nan), x, x);
+            }
+            _ => unreachable!(),
+        }
+        self.machine.release_temp_xmm(nan);
+        self.machine.release_temp_xmm(mask);
+    }
+
+    /// SIMD operation on a `v128` popped from the value stack.
+    ///
+    /// `op` is passed the register holding the operand and the one to compute the result into.
+    fn emit_simd_unop<F: FnOnce(&mut Self, XMM, XMM)>(&mut self, op: F) {
+        let a = self.pop_v128_released();
+        let ret = self.push_v128();
+        let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+        let tmp_out = self.machine.acquire_temp_xmm().unwrap();
+        self.emit_v128_load(a, tmp_a);
+        op(self, tmp_a, tmp_out);
+        self.emit_v128_store(tmp_out, ret);
+        self.machine.release_temp_xmm(tmp_out);
+        self.machine.release_temp_xmm(tmp_a);
+    }
+
+    /// SIMD operation on two `v128` popped from the value stack.
+    ///
+    /// `op` is passed the registers holding the operands and the one to compute the result into.
+    fn emit_simd_binop<F: FnOnce(&mut Self, XMM, XMM, XMM)>(&mut self, op: F) {
+        let b = self.pop_v128_released();
+        let a = self.pop_v128_released();
+        let ret = self.push_v128();
+        let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+        let tmp_b = self.machine.acquire_temp_xmm().unwrap();
+        let tmp_out = self.machine.acquire_temp_xmm().unwrap();
+        self.emit_v128_load(a, tmp_a);
+        self.emit_v128_load(b, tmp_b);
+        op(self, tmp_a, tmp_b, tmp_out);
+        self.emit_v128_store(tmp_out, ret);
+        self.machine.release_temp_xmm(tmp_out);
+        self.machine.release_temp_xmm(tmp_b);
+        self.machine.release_temp_xmm(tmp_a);
+    }
+
+    /// SIMD (AVX) unary operation on a `v128` popped from the value stack.
+    fn emit_simd_unop_avx(&mut self, f: fn(&mut Assembler, XMM, XMM)) {
+        self.emit_simd_unop(|this, a, out| f(this.assembler, a, out));
+    }
+
+    /// SIMD (AVX) binary operation on two `v128` popped from the value stack.
+    fn emit_simd_binop_avx(&mut self, f: fn(&mut Assembler, XMM, XMMOrMemory, XMM)) {
+        self.emit_simd_binop(|this, a, b, out| f(this.assembler, a, XMMOrMemory::XMM(b), out));
+    }
+
+    /// SIMD (AVX) binary operation on two `v128` popped from the value stack, with the operands
+    /// of the instruction swapped.
+    fn emit_simd_binop_avx_swapped(&mut self, f: fn(&mut Assembler, XMM, XMMOrMemory, XMM)) {
+        self.emit_simd_binop(|this, a, b, out| f(this.assembler, b, XMMOrMemory::XMM(a), out));
+    }
+
+    /// SIMD (AVX) comparison of two `v128` popped from the value stack, negated, and with the
+    /// operands of the instruction swapped if `swapped`.
+    fn emit_simd_cmp_not(&mut self, f: fn(&mut Assembler, XMM, XMMOrMemory, XMM), swapped: bool) {
+        self.emit_simd_binop(|this, a, b, out| {
+            let (a, b) = if swapped { (b, a) } else { (a, b) };
+            f(this.assembler, a, XMMOrMemory::XMM(b), out);
+            this.assembler.emit_vpcmpeqd(b, XMMOrMemory::XMM(b), b);
+            this.assembler.emit_vpxor(out, XMMOrMemory::XMM(b), out);
+        });
+    }
+
+    /// Unsigned SIMD comparison of two `v128` popped from the value stack, as whether `a` is
+    /// `min_max(a, b)`, negated if `not`.
+    fn emit_simd_cmp_unsigned(
+        &mut self,
+        min_max: fn(&mut Assembler, XMM, XMMOrMemory, XMM),
+        cmpeq: fn(&mut Assembler, XMM, XMMOrMemory, XMM),
+        not: bool,
+    ) {
+        self.emit_simd_binop(|this, a, b, out| {
+            min_max(this.assembler, a, XMMOrMemory::XMM(b), out);
+            cmpeq(this.assembler, a, XMMOrMemory::XMM(out), out);
+            if not {
+                this.assembler.emit_vpcmpeqd(b, XMMOrMemory::XMM(b), b);
+                this.assembler.emit_vpxor(out, XMMOrMemory::XMM(b), out);
+            }
+        });
+    }
+
+    /// Negation of the lanes of a `v128` popped from the value stack, by subtracting them from
+    /// zero with `sub`.
+    fn emit_simd_neg(&mut self, sub: fn(&mut Assembler, XMM, XMMOrMemory, XMM)) {
+        self.emit_simd_unop(|this, a, out| {
+            this.assembler.emit_vpxor(out, XMMOrMemory::XMM(out), out);
+            sub(this.assembler, out, XMMOrMemory::XMM(a), out);
+        });
+    }
+
+    /// Bitwise operation `f` between a `v128` popped from the value stack and a constant.
+    fn emit_simd_bitwise_const(
+        &mut self,
+        f: fn(&mut Assembler, XMM, XMMOrMemory, XMM),
+        value: u128,
+    ) {
+        self.emit_simd_unop(|this, a, out| {
+            this.emit_v128_const(value, out);
+            f(this.assembler, a, XMMOrMemory::XMM(out), out);
+        });
+    }
+
+    /// Multiplication of the 64-bit lanes of two `v128` popped from the value stack.
+    ///
+    /// There is no instruction for that before AVX-512, so each lane is multiplied separately.
+    fn emit_simd_mul_i64x2(&mut self) {
+        self.emit_simd_binop(|this, a, b, out| {
+            let tmp_a = this.machine.acquire_temp_gpr().unwrap();
+            let tmp_b = this.machine.acquire_temp_gpr().unwrap();
+            this.assembler.emit_mov(Size::S64, Location::XMM(a), Location::GPR(tmp_a));
+            this.assembler.emit_mov(Size::S64, Location::XMM(b), Location::GPR(tmp_b));
+            this.assembler.emit_imul(Size::S64, Location::GPR(tmp_b), Location::GPR(tmp_a));
+            this.assembler.emit_mov(Size::S64, Location::GPR(tmp_a), Location::XMM(out));
+            this.assembler.emit_vpextrq(a, 1, GPROrMemory::GPR(tmp_a));
+            this.assembler.emit_vpextrq(b, 1, GPROrMemory::GPR(tmp_b));
+            this.assembler.emit_imul(Size::S64, Location::GPR(tmp_b), Location::GPR(tmp_a));
+            this.assembler.emit_vpinsrq(out, GPROrMemory::GPR(tmp_a), 1, out);
+            this.machine.release_temp_gpr(tmp_b);
+            this.machine.release_temp_gpr(tmp_a);
+        });
+    }
+
+    /// Floating point SIMD (AVX) unary operation on a `v128` popped from the value stack.
+    fn emit_simd_fp_unop_avx(&mut self, sz: Size, f: fn(&mut Assembler, XMM, XMM)) {
+        self.emit_simd_unop(|this, a, out| {
+            f(this.assembler, a, out);
+            this.emit_simd_canonicalize_nan(sz, out);
+        });
+    }
+
+    /// Floating point SIMD (AVX) binary operation on two `v128` popped from the value stack.
+    fn emit_simd_fp_binop_avx(&mut self, sz: Size, f: fn(&mut Assembler, XMM, XMMOrMemory, XMM)) {
+        self.emit_simd_binop(|this, a, b, out| {
+            f(this.assembler, a, XMMOrMemory::XMM(b), out);
+            this.emit_simd_canonicalize_nan(sz, out);
+        });
+    }
+
+    /// Floating point SIMD `min` (or `max`, if `max`) of two `v128` popped from the value stack.
+    ///
+    /// `minps`/`maxps` return their second operand if either is a NaN and don't order zeros of
+    /// different signs, so these are computed both ways round and merged.
+    fn emit_simd_fp_min_max(&mut self, sz: Size, max: bool) {
+        let (min_max, cmpunord, sub, srl): (
+            fn(&mut Assembler, XMM, XMMOrMemory, XMM),
+            fn(&mut Assembler, XMM, XMMOrMemory, XMM),
+            fn(&mut Assembler, XMM, XMMOrMemory, XMM),
+            fn(&mut Assembler, XMM, u8, XMM),
+        ) = match sz {
+            Size::S32 => (
+                if max { Assembler::emit_vmaxps } else { Assembler::emit_vminps },
+                Assembler::emit_vcmpunordps,
+                Assembler::emit_vsubps,
+                Assembler::emit_vpsrld_imm,
+            ),
+            Size::S64 => (
+                if max { Assembler::emit_vmaxpd } else { Assembler::emit_vminpd },
+                Assembler::emit_vcmpunordpd,
+                Assembler::emit_vsubpd,
+                Assembler::emit_vpsrlq_imm,
+            ),
+            _ => unreachable!(),
+        };
+        // Shifting an all-ones mask right by this many bits leaves all the fraction bits but the
+        // quiet one set.
+        let fraction_shift = if sz == Size::S32 { 10 } else { 13 };
+        self.emit_simd_binop(|this, a, b, out| {
+            let tmp = this.machine.acquire_temp_xmm().unwrap();
+            min_max(this.assembler, a, XMMOrMemory::XMM(b), tmp);
+            min_max(this.assembler, b, XMMOrMemory::XMM(a), out);
+            if max {
+                // Where the lanes differ only by their sign, `xor` leaves just that bit set in
+                // `a`, `or` gives the negative zero and the `sub` turns it into a positive zero.
+                // NaNs propagate through all three.
+                this.assembler.emit_vpxor(tmp, XMMOrMemory::XMM(out), a);
+                this.assembler.emit_vpor(tmp, XMMOrMemory::XMM(a), tmp);
+                sub(this.assembler, tmp, XMMOrMemory::XMM(a), out);
+                cmpunord(this.assembler, tmp, XMMOrMemory::XMM(tmp), a);
+            } else {
+                // Where the lanes differ only by their sign, `or` gives the negative zero. NaNs
+                // are propagated by setting all the bits of the lane.
+                this.assembler.emit_vpor(tmp, XMMOrMemory::XMM(out), tmp);
+                cmpunord(this.assembler, tmp, XMMOrMemory::XMM(out), a);
+                this.assembler.emit_vpor(tmp, XMMOrMemory::XMM(a), out);
+            }
+            // Clear the fraction bits of NaNs, making them quiet ones with no payload.
+            srl(this.assembler, a, fraction_shift, a);
+            this.assembler.emit_vpandn(a, XMMOrMemory::XMM(out), out);
+            this.machine.release_temp_xmm(tmp);
+            this.emit_simd_canonicalize_nan(sz, out);
+        });
+    }
+
+    /// SIMD shift of a `v128` by a count, both popped from the value stack.
+    ///
+    /// `op` is passed the register holding the `v128` and the one to compute the result into,
+    /// with `RCX` holding the count reduced modulo the lane width of `bits` bits.
+    fn emit_simd_shift<F: FnOnce(&mut Self, XMM, XMM)>(&mut self, bits: u32, op: F) {
+        let count = self.pop_value_released();
+        let a = self.pop_v128_released();
+        let ret = self.push_v128();
+        let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+        let tmp_out = self.machine.acquire_temp_xmm().unwrap();
+        self.emit_v128_load(a, tmp_a);
+        let rcx = self.machine.reserve_unused_temp_gpr(GPR::RCX);
+        self.assembler.emit_mov(Size::S32, count, Location::GPR(rcx));
+        self.assembler.emit_and(Size::S32, Location::Imm32(bits - 1), Location::GPR(rcx));
+        op(self, tmp_a, tmp_out);
+        self.machine.release_temp_gpr(rcx);
+        self.emit_v128_store(tmp_out, ret);
+        self.machine.release_temp_xmm(tmp_out);
+        self.machine.release_temp_xmm(tmp_a);
+    }
+
+    /// SIMD (AVX) shift of a `v128` by a count, both popped from the value stack, for lanes of
+    /// `bits` bits.
+    fn emit_simd_shift_avx(&mut self, bits: u32, f: fn(&mut Assembler, XMM, XMMOrMemory, XMM)) {
+        self.emit_simd_shift(bits, |this, a, out| {
+            let count = this.machine.acquire_temp_xmm().unwrap();
+            this.assembler.emit_mov(Size::S32, Location::GPR(GPR::RCX), Location::XMM(count));
+            f(this.assembler, a, XMMOrMemory::XMM(count), out);
+            this.machine.release_temp_xmm(count);
+        });
+    }
+
+    /// Shift of the 8-bit lanes of a `v128` left (or logically right, if `right`) by a count,
+    /// both popped from the value stack.
+    ///
+    /// The 16-bit lanes are shifted instead, then the bits shifted in from the neighbouring byte
+    /// are masked off.
+    fn emit_simd_shift_i8x16(&mut self, right: bool) {
+        self.emit_simd_shift(8, |this, a, out| {
+            let count = this.machine.acquire_temp_xmm().unwrap();
+            let mask = this.machine.acquire_temp_xmm().unwrap();
+            let zero = this.machine.acquire_temp_xmm().unwrap();
+            let tmp = this.machine.acquire_temp_gpr().unwrap();
+            this.assembler.emit_mov(Size::S32, Location::GPR(GPR::RCX), Location::XMM(count));
+            this.assembler.emit_mov(Size::S32, Location::Imm32(0xFF), Location::GPR(tmp));
+            if right {
+                this.assembler.emit_vpsrlw(a, XMMOrMemory::XMM(count), out);
+                this.assembler.emit_shr(Size::S32, Location::GPR(GPR::RCX), Location::GPR(tmp));
+            } else {
+                this.assembler.emit_vpsllw(a, XMMOrMemory::XMM(count), out);
+                this.assembler.emit_shl(Size::S32, Location::GPR(GPR::RCX), Location::GPR(tmp));
+            }
+            this.assembler.emit_mov(Size::S32, Location::GPR(tmp), Location::XMM(mask));
+            this.assembler.emit_vpxor(zero, XMMOrMemory::XMM(zero), zero);
+            this.assembler.emit_vpshufb(mask, XMMOrMemory::XMM(zero), mask);
+            this.assembler.emit_vpand(out, XMMOrMemory::XMM(mask), out);
+            this.machine.release_temp_gpr(tmp);
+            this.machine.release_temp_xmm(zero);
+            this.machine.release_temp_xmm(mask);
+            this.machine.release_temp_xmm(count);
+        });
+    }
+
+    /// Shift of the 8-bit lanes of a `v128` arithmetically right by a count, both popped from the
+    /// value stack.
+    ///
+    /// The bytes are duplicated into 16-bit lanes, so that shifting these by 8 more bits leaves
+    /// the results in range to be narrowed back.
+    fn emit_simd_sar_i8x16(&mut self) {
+        self.emit_simd_shift(8, |this, a, out| {
+            let count = this.machine.acquire_temp_xmm().unwrap();
+            let hi = this.machine.acquire_temp_xmm().unwrap();
+            this.assembler.emit_add(Size::S32, Location::Imm32(8), Location::GPR(GPR::RCX));
+            this.assembler.emit_mov(Size::S32, Location::GPR(GPR::RCX), Location::XMM(count));
+            this.assembler.emit_vpunpckhbw(a, XMMOrMemory::XMM(a), hi);
+            this.assembler.emit_vpsraw(hi, XMMOrMemory::XMM(count), hi);
+            this.assembler.emit_vpunpcklbw(a, XMMOrMemory::XMM(a), out);
+            this.assembler.emit_vpsraw(out, XMMOrMemory::XMM(count), out);
+            this.assembler.emit_vpacksswb(out, XMMOrMemory::XMM(hi), out);
+            this.machine.release_temp_xmm(hi);
+            this.machine.release_temp_xmm(count);
+        });
+    }
+
+    /// Shift of the 64-bit lanes of a `v128` arithmetically right by a count, both popped from
+    /// the value stack.
+    ///
+    /// There is no instruction for that before AVX-512, so each lane is shifted separately.
+    fn emit_simd_sar_i64x2(&mut self) {
+        self.emit_simd_shift(64, |this, a, out| {
+            let tmp = this.machine.acquire_temp_gpr().unwrap();
+            this.assembler.emit_mov(Size::S64, Location::XMM(a), Location::GPR(tmp));
+            this.assembler.emit_sar(Size::S64, Location::GPR(GPR::RCX), Location::GPR(tmp));
+            this.assembler.emit_mov(Size::S64, Location::GPR(tmp), Location::XMM(out));
+            this.assembler.emit_vpextrq(a, 1, GPROrMemory::GPR(tmp));
+            this.assembler.emit_sar(Size::S64, Location::GPR(GPR::RCX), Location::GPR(tmp));
+            this.assembler.emit_vpinsrq(out, GPROrMemory::GPR(tmp), 1, out);
+            this.machine.release_temp_gpr(tmp);
+        });
+    }
+
+    /// SIMD operation on one half of a `v128` popped from the value stack: the high one if
+    /// `high`, the low one otherwise.
+    ///
+    /// `op` is passed the register holding the half in its low 64 bits, and the one to compute
+    /// the result into.
+    fn emit_simd_half_unop<F: FnOnce(&mut Self, XMM, XMM)>(&mut self, high: bool, op: F) {
+        let (lo, hi) = self.pop_v128_released();
+        let ret = self.push_v128();
+        let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+        let tmp_out = self.machine.acquire_temp_xmm().unwrap();
+        self.emit_v128_half_load(if high { hi } else { lo }, tmp_a);
+        op(self, tmp_a, tmp_out);
+        self.emit_v128_store(tmp_out, ret);
+        self.machine.release_temp_xmm(tmp_out);
+        self.machine.release_temp_xmm(tmp_a);
+    }
+
+    /// SIMD operation on one half of each of two `v128` popped from the value stack: the high
+    /// ones if `high`, the low ones otherwise.
+    ///
+    /// `op` is passed the registers holding the halves in their low 64 bits, and the one to
+    /// compute the result into.
+    fn emit_simd_half_binop<F: FnOnce(&mut Self, XMM, XMM, XMM)>(&mut self, high: bool, op: F) {
+        let (b_lo, b_hi) = self.pop_v128_released();
+        let (a_lo, a_hi) = self.pop_v128_released();
+        let ret = self.push_v128();
+        let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+        let tmp_b = self.machine.acquire_temp_xmm().unwrap();
+        let tmp_out = self.machine.acquire_temp_xmm().unwrap();
+        self.emit_v128_half_load(if high { a_hi } else { a_lo }, tmp_a);
+        self.emit_v128_half_load(if high { b_hi } else { b_lo }, tmp_b);
+        op(self, tmp_a, tmp_b, tmp_out);
+        self.emit_v128_store(tmp_out, ret);
+        self.machine.release_temp_xmm(tmp_out);
+        self.machine.release_temp_xmm(tmp_b);
+        self.machine.release_temp_xmm(tmp_a);
+    }
+
+    /// Load the half of a `v128` at `src` into the low 64 bits of `dst`.
+    fn emit_v128_half_load(&mut self, src: Location, dst: XMM) {
+        match src {
+            Location::Imm64(_) => {
+                let tmp = self.machine.acquire_temp_gpr().unwrap();
+                self.assembler.emit_mov(Size::S64, src, Location::GPR(tmp));
+                self.assembler.emit_mov(Size::S64, Location::GPR(tmp), Location::XMM(dst));
+                self.machine.release_temp_gpr(tmp);
+            }
+            _ => self.assembler.emit_mov(Size::S64, src, Location::XMM(dst)),
+        }
+    }
+
+    /// Widening of the lanes of one half of a `v128` popped from the value stack with `extend`.
+    fn emit_simd_extend(&mut self, high: bool, extend: fn(&mut Assembler, XMM, XMM)) {
+        self.emit_simd_half_unop(high, |this, a, out| extend(this.assembler, a, out));
+    }
+
+    /// Widening multiplication of the lanes of one half of each of two `v128` popped from the
+    /// value stack, with `extend` widening the lanes and `mul` multiplying them.
+    fn emit_simd_extmul(
+        &mut self,
+        high: bool,
+        extend: fn(&mut Assembler, XMM, XMM),
+        mul: fn(&mut Assembler, XMM, XMMOrMemory, XMM),
+    ) {
+        self.emit_simd_half_binop(high, |this, a, b, out| {
+            extend(this.assembler, a, a);
+            extend(this.assembler, b, b);
+            mul(this.assembler, a, XMMOrMemory::XMM(b), out);
+        });
+    }
+
+    /// Whether any (or all, if `all`) of the lanes of `bits` bits of a `v128` popped from the
+    /// value stack are non-zero.
+    fn emit_simd_true(&mut self, bits: u32, all: bool) -> Result<(), CodegenError> {
+        let a = self.pop_v128_released();
+        let ret = self.machine.acquire_locations(self.assembler, &[WpType::I32], false)[0];
+        self.value_stack.push(ret);
+        let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+        self.emit_v128_load(a, tmp_a);
+        let condition = if all {
+            // Set all bits of the zero lanes, then check that there are none.
+            let zero = self.machine.acquire_temp_xmm().unwrap();
+            self.assembler.emit_vpxor(zero, XMMOrMemory::XMM(zero), zero);
+            let cmpeq = match bits {
+                8 => Assembler::emit_vpcmpeqb,
+                16 => Assembler::emit_vpcmpeqw,
+                32 => Assembler::emit_vpcmpeqd,
+                64 => Assembler::emit_vpcmpeqq,
+                _ => unreachable!(),
+            };
+            cmpeq(self.assembler, tmp_a, XMMOrMemory::XMM(zero), tmp_a);
+            self.machine.release_temp_xmm(zero);
+            Condition::Equal
+        } else {
+            Condition::NotEqual
+        };
+        self.assembler.emit_vptest(tmp_a, tmp_a);
+        self.machine.release_temp_xmm(tmp_a);
+        self.emit_set_i32(condition, ret)
+    }
+
+    /// Set the `i32` at `ret` to 1 if `condition` holds on the flags, to 0 otherwise.
+    fn emit_set_i32(&mut self, condition: Condition, ret: Location) -> Result<(), CodegenError> {
+        match ret {
+            Location::GPR(x) => {
+                self.assembler.emit_set(condition, x);
+                self.assembler.emit_and(Size::S32, Location::Imm32(0xff), Location::GPR(x));
+            }
+            Location::Memory(_, _) => {
+                let tmp = self.machine.acquire_temp_gpr().unwrap();
+                self.assembler.emit_set(condition, tmp);
+                self.assembler.emit_and(Size::S32, Location::Imm32(0xff), Location::GPR(tmp));
+                self.assembler.emit_mov(Size::S32, Location::GPR(tmp), ret);
+                self.machine.release_temp_gpr(tmp);
+            }
+            _ => {
+                return Err(CodegenError {
+                    message: "emit_set_i32 ret: unreachable code".to_string(),
+                })
+            }
+        }
+        Ok(())
+    }
+
+    /// Gather the sign bits of the lanes of `bits` bits of a `v128` popped from the value stack
+    /// into an `i32`.
+    fn emit_simd_bitmask(&mut self, bits: u32) {
+        let a = self.pop_v128_released();
+        let ret = self.machine.acquire_locations(self.assembler, &[WpType::I32], false)[0];
+        self.value_stack.push(ret);
+        let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+        let tmp = self.machine.acquire_temp_gpr().unwrap();
+        self.emit_v128_load(a, tmp_a);
+        match bits {
+            8 => self.assembler.emit_pmovmskb(tmp_a, tmp),
+            16 => {
+                // Narrowing with signed saturation keeps the signs.
+                self.assembler.emit_vpacksswb(tmp_a, XMMOrMemory::XMM(tmp_a), tmp_a);
+                self.assembler.emit_pmovmskb(tmp_a, tmp);
+                self.assembler.emit_and(Size::S32, Location::Imm32(0xff), Location::GPR(tmp));
+            }
+            32 => self.assembler.emit_movmskps(tmp_a, tmp),
+            64 => self.assembler.emit_movmskpd(tmp_a, tmp),
+            _ => unreachable!(),
+        }
+        self.assembler.emit_mov(Size::S32, Location::GPR(tmp), ret);
+        self.machine.release_temp_gpr(tmp);
+        self.machine.release_temp_xmm(tmp_a);
+    }
+
+    /// Pop a scalar of type `ty` off the value stack into `dst`, zero-extended to 64 bits.
+    ///
+    /// The bits of floating point values become observable in a `v128`, so any pending
+    /// canonicalization is applied.
+    fn emit_pop_scalar(&mut self, ty: WpType, dst: GPR) -> Result<(), CodegenError> {
+        let loc = self.pop_value_released();
+        let sz = match ty {
+            WpType::I32 | WpType::F32 => Size::S32,
+            _ => Size::S64,
+        };
+        let canonicalization =
+            if ty.is_float() { self.fp_stack.pop1()?.canonicalization } else { None };
+        match canonicalization {
+            Some(_)
+                if self.assembler.arch_supports_canonicalize_nan()
+                    && self.config.enable_nan_canonicalization =>
+            {
+                self.canonicalize_nan(sz, loc, Location::GPR(dst));
+            }
+            _ => self.assembler.emit_mov(sz, loc, Location::GPR(dst)),
+        }
+        Ok(())
+    }
+
+    /// Set all the lanes of `bits` bits of a new `v128` to a scalar of type `ty`, both popped
+    /// from the value stack.
+    fn emit_simd_splat(&mut self, ty: WpType, bits: u32) -> Result<(), CodegenError> {
+        let tmp = self.machine.acquire_temp_gpr().unwrap();
+        self.emit_pop_scalar(ty, tmp)?;
+        match bits {
+            8 => self.assembler.emit_movzx(
+                Size::S8,
+                Location::GPR(tmp),
+                Size::S32,
+                Location::GPR(tmp),
+            ),
+            16 => self.assembler.emit_movzx(
+                Size::S16,
+                Location::GPR(tmp),
+                Size::S32,
+                Location::GPR(tmp),
+            ),
+            _ => {}
+        }
+        if bits < 64 {
+            self.emit_splat_lane(bits, tmp);
+        }
+        let (lo, hi) = self.push_v128();
+        self.assembler.emit_mov(Size::S64, Location::GPR(tmp), lo);
+        self.assembler.emit_mov(Size::S64, Location::GPR(tmp), hi);
+        self.machine.release_temp_gpr(tmp);
+        Ok(())
+    }
+
+    /// Repeat the zero-extended lane of `bits` bits in `x` across all 64 bits of it.
+    fn emit_splat_lane(&mut self, bits: u32, x: GPR) {
+        let ones = self.machine.acquire_temp_gpr().unwrap();
+        let ones_value = splat(1, bits) as u64;
+        self.assembler.emit_mov(Size::S64, Location::Imm64(ones_value), Location::GPR(ones));
+        self.assembler.emit_imul(Size::S64, Location::GPR(ones), Location::GPR(x));
+        self.machine.release_temp_gpr(ones);
+    }
+
+    /// Extract the lane of `bits` bits at index `lane` of a `v128` popped from the value stack as
+    /// a value of type `ty`, sign-extending it if `signed`.
+    fn emit_simd_extract_lane(&mut self, ty: WpType, bits: u32, lane: u8, signed: bool) {
+        let (lo, hi) = self.pop_v128_released();
+        let ret = self.machine.acquire_locations(self.assembler, &[ty], false)[0];
+        self.value_stack.push(ret);
+        if ty.is_float() {
+            self.fp_stack.push(FloatValue::new(self.value_stack.len() - 1));
+        }
+        let offset = u32::from(lane) * bits;
+        let tmp = self.machine.acquire_temp_gpr().unwrap();
+        self.assembler.emit_mov(Size::S64, if offset < 64 { lo } else { hi }, Location::GPR(tmp));
+        if offset % 64 != 0 {
+            let shift = Location::Imm8((offset % 64) as u8);
+            self.assembler.emit_shr(Size::S64, shift, Location::GPR(tmp));
+        }
+        let extend = if signed { Assembler::emit_movsx } else { Assembler::emit_movzx };
+        match bits {
+            8 => {
+                extend(self.assembler, Size::S8, Location::GPR(tmp), Size::S32, Location::GPR(tmp))
+            }
+            16 => {
+                extend(self.assembler, Size::S16, Location::GPR(tmp), Size::S32, Location::GPR(tmp))
+            }
+            _ => {}
+        }
+        let sz = if bits == 64 { Size::S64 } else { Size::S32 };
+        self.assembler.emit_mov(sz, Location::GPR(tmp), ret);
+        self.machine.release_temp_gpr(tmp);
+    }
+
+    /// Replace the lane of `bits` bits at index `lane` of a `v128` with a scalar of type `ty`,
+    /// both popped from the value stack.
+    fn emit_simd_replace_lane(
+        &mut self,
+        ty: WpType,
+        bits: u32,
+        lane: u8,
+    ) -> Result<(), CodegenError> {
+        let tmp = self.machine.acquire_temp_gpr().unwrap();
+        self.emit_pop_scalar(ty, tmp)?;
+        let (lo, hi) = self.pop_v128_released();
+        let ret = self.push_v128();
+        if bits == 64 {
+            let (kept, ret_kept, ret_replaced) =
+                if lane == 0 { (hi, ret.1, ret.0) } else { (lo, ret.0, ret.1) };
+            let tmp_kept = self.machine.acquire_temp_gpr().unwrap();
+            self.assembler.emit_mov(Size::S64, kept, Location::GPR(tmp_kept));
+            self.assembler.emit_mov(Size::S64, Location::GPR(tmp), ret_replaced);
+            self.assembler.emit_mov(Size::S64, Location::GPR(tmp_kept), ret_kept);
+            self.machine.release_temp_gpr(tmp_kept);
+        } else {
+            let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+            self.emit_v128_load((lo, hi), tmp_a);
+            let insert = match bits {
+                8 => Assembler::emit_vpinsrb,
+                16 => Assembler::emit_vpinsrw,
+                32 => Assembler::emit_vpinsrd,
+                _ => unreachable!(),
+            };
+            insert(self.assembler, tmp_a, tmp, lane, tmp_a);
+            self.emit_v128_store(tmp_a, ret);
+            self.machine.release_temp_xmm(tmp_a);
+        }
+        self.machine.release_temp_gpr(tmp);
+        Ok(())
+    }
+
+    /// Load a `v128` from memory, at an address popped from the value stack, with `load` putting
+    /// the `value_size` bytes in memory into the register it is passed.
+    fn emit_simd_load<F: FnOnce(&mut Self, GPR, XMM)>(
+        &mut self,
+        memarg: &MemArg,
+        value_size: usize,
+        load: F,
+    ) -> Result<(), CodegenError> {
+        let target = self.pop_value_released();
+        let ret = self.push_v128();
+        let tmp_out = self.machine.acquire_temp_xmm().unwrap();
+        self.emit_memory_op(target, memarg, false, value_size, |this, addr| {
+            load(this, addr, tmp_out);
+            this.emit_v128_store(tmp_out, ret);
+            Ok(())
+        })?;
+        self.machine.release_temp_xmm(tmp_out);
+        Ok(())
+    }
+
+    /// Load 8 bytes from memory and widen their lanes into a `v128` with `extend`, at an address
+    /// popped from the value stack.
+    fn emit_simd_load_extend(
+        &mut self,
+        memarg: &MemArg,
+        extend: fn(&mut Assembler, XMM, XMM),
+    ) -> Result<(), CodegenError> {
+        self.emit_simd_load(memarg, 8, |this, addr, out| {
+            this.assembler.emit_mov(Size::S64, Location::Memory(addr, 0), Location::XMM(out));
+            extend(this.assembler, out, out);
+        })
+    }
+
+    /// Load a lane of `bits` bits from memory into all the lanes of a `v128`, at an address
+    /// popped from the value stack.
+    fn emit_simd_load_splat(&mut self, memarg: &MemArg, bits: u32) -> Result<(), CodegenError> {
+        self.emit_simd_load(memarg, bits as usize / 8, |this, addr, out| {
+            let src = Location::Memory(addr, 0);
+            let tmp = this.machine.acquire_temp_gpr().unwrap();
+            match bits {
+                8 => this.assembler.emit_movzx(Size::S8, src, Size::S32, Location::GPR(tmp)),
+                16 => this.assembler.emit_movzx(Size::S16, src, Size::S32, Location::GPR(tmp)),
+                32 => this.assembler.emit_mov(Size::S32, src, Location::GPR(tmp)),
+                64 => this.assembler.emit_mov(Size::S64, src, Location::GPR(tmp)),
+                _ => unreachable!(),
+            }
+            if bits < 64 {
+                this.emit_splat_lane(bits, tmp);
+            }
+            this.assembler.emit_mov(Size::S64, Location::GPR(tmp), Location::XMM(out));
+            this.assembler.emit_vpinsrq(out, GPROrMemory::GPR(tmp), 1, out);
+            this.machine.release_temp_gpr(tmp);
+        })
+    }
+
+    /// Load a lane of `bits` bits from memory into the first lane of a `v128`, with the other
+    /// lanes zeroed, at an address popped from the value stack.
+    fn emit_simd_load_zero(&mut self, memarg: &MemArg, bits: u32) -> Result<(), CodegenError> {
+        let sz = if bits == 64 { Size::S64 } else { Size::S32 };
+        self.emit_simd_load(memarg, bits as usize / 8, |this, addr, out| {
+            this.assembler.emit_mov(sz, Location::Memory(addr, 0), Location::XMM(out));
+        })
+    }
+
+    /// Load a lane of `bits` bits at index `lane` of a `v128` from memory, the `v128` and the
+    /// address being popped from the value stack.
+    fn emit_simd_load_lane(
+        &mut self,
+        memarg: &MemArg,
+        bits: u32,
+        lane: u8,
+    ) -> Result<(), CodegenError> {
+        let a = self.pop_v128_released();
+        let target = self.pop_value_released();
+        let ret = self.push_v128();
+        let tmp_a = self.machine.acquire_temp_xmm().unwrap();
+        self.emit_v128_load(a, tmp_a);
+        self.emit_memory_op(target, memarg, false, bits as usize / 8, |this, addr| {
+            let src = Location::Memory(addr, 0);
+            if bits == 64 {
+                let src = GPROrMemory::Memory(addr, 0);
+                this.assembler.emit_vpinsrq(tmp_a, src, lane, tmp_a);
+            } else {
+                let tmp = this.machine.acquire_temp_gpr().unwrap();
+                let insert = match bits {
+                    8 => {
+                        this.assembler.emit_movzx(Size::S8, src, Size::S32, Location::GPR(tmp));
+                        Assembler::emit_vpinsrb
+                    }
+                    16 => {
+                        this.assembler.emit_movzx(Size::S16, src, Size::S32, Location::GPR(tmp));
+                        Assembler::emit_vpinsrw
+                    }
+                    32 => {
+                        this.assembler.emit_mov(Size::S32, src, Location::GPR(tmp));
+                        Assembler::emit_vpinsrd
+                    }
+                    _ => unreachable!(),
+                };
+                insert(this.assembler, tmp_a, tmp, lane, tmp_a);
+                this.machine.release_temp_gpr(tmp);
+            }
+            this.emit_v128_store(tmp_a, ret);
+            Ok(())
+        })?;
+        self.machine.release_temp_xmm(tmp_a);
+        Ok(())
+    }
+
+    /// Store the lane of `bits` bits at index `lane` of a `v128` to memory, the `v128` and the
+    /// address being popped from the value stack.
+    fn emit_simd_store_lane(
+        &mut self,
+        memarg: &MemArg,
+        bits: u32,
+        lane: u8,
+    ) -> Result<(), CodegenError> {
+        let (lo, hi) = self.pop_v128_released();
+        let target = self.pop_value_released();
+        let offset = u32::from(lane) * bits;
+        let half = if offset < 64 { lo } else { hi };
+        self.emit_memory_op(target, memarg, false, bits as usize / 8, |this, addr| {
+            let tmp = this.machine.acquire_temp_gpr().unwrap();
+            this.assembler.emit_mov(Size::S64, half, Location::GPR(tmp));
+            if offset % 64 != 0 {
+                let shift = Location::Imm8((offset % 64) as u8);
+                this.assembler.emit_shr(Size::S64, shift, Location::GPR(tmp));
+            }
+            let sz = match bits {
+                8 => Size::S8,
+                16 => Size::S16,
+                32 => Size::S32,
+                64 => Size::S64,
+                _ => unreachable!(),
+            };
+            this.assembler.emit_mov(sz, Location::GPR(tmp), Location::Memory(addr, 0));
+            this.machine.release_temp_gpr(tmp);
+            Ok(())
+        })
+    }
+
     /// I32 binary operation with both operands popped from the virtual stack.
     fn emit_binop_i32(&mut self, f: fn(&mut Assembler, Size, Location, Location)) {
         // Using Red Zone here.
@@ -1633,14 +2731,30 @@ impl<'a> FuncGen<'a> {
         self.assembler.emit_push(Size::S64, Location::GPR(GPR::RBP));
         self.assembler.emit_mov(Size::S64, Location::GPR(GPR::RSP), Location::GPR(GPR::RBP));
 
+        // Reserve the scratch area, with an extra slot to save the return area pointer to.
+        let has_return_area = machine_value_count(self.signature.results()) > 1;
+        let scratch_slots = self.scratch_slots + usize::from(has_return_area);
+        self.stack_size = u32::try_from(scratch_slots * 8)
+            .ok()
+            .and_then(|size| self.stack_size.checked_add(size))
+            .ok_or_else(|| CodegenError {
+                message: "one function has a stack more than u32::MAX deep".to_string(),
+            })?;
+
         // Setup the registers (incl. defining the vmctx register)
         let local_count = self.local_count();
+        let param_count = machine_value_count(self.signature.params()) as u32;
         self.machine.setup_registers(
             self.assembler,
             local_count,
-            self.signature.params().len() as u32,
+            param_count,
+            scratch_slots,
             self.calling_convention,
         );
+        if has_return_area {
+            let return_area = self.return_area_location();
+            self.assembler.emit_mov(Size::S64, Location::GPR(GPR::R11), return_area);
+        }
 
         // Verify stack height
         self.assembler.emit_sub(
@@ -1658,12 +2772,7 @@ impl<'a> FuncGen<'a> {
 
         // Initialize the locals
         let local_count = self.local_count();
-        self.machine.init_locals(
-            self.assembler,
-            local_count,
-            self.signature.params().len() as u32,
-            self.calling_convention,
-        );
+        self.machine.init_locals(self.assembler, local_count, param_count, self.calling_convention);
 
         self.assembler.emit_sub(Size::S64, Location::Imm32(32), Location::GPR(GPR::RSP)); // simulate "red zone" if not supported by the platform
 
@@ -1671,9 +2780,13 @@ impl<'a> FuncGen<'a> {
             br_label: self.assembler.get_label(),
             loop_like: false,
             if_else: IfElseState::None,
-            returns: self.signature.results().iter().map(|&x| type_to_wp_type(x)).collect(),
+            returns: machine_types(self.signature.results()),
+            param_count: 0,
+            param_locations: smallvec![],
+            loop_stack_offset: 0,
             value_stack_depth: 0,
             fp_stack_depth: 0,
+            v128_stack_depth: 0,
         });
 
         Ok(())
@@ -1727,11 +2840,17 @@ impl<'a> FuncGen<'a> {
             target,
             vmoffsets,
             local_types: unc_vm_types::partial_sum_map::PartialSumMap::new(),
+            v128_local_runs: vec![],
             assembler,
             value_stack: vec![],
             fp_stack: vec![],
+            v128_stack: vec![],
             control_stack: vec![],
             machine: Machine::new(),
+            scratch_slots: match machine_value_count(signature.results()) {
+                n if n > 1 => n,
+                _ => 0,
+            },
             unreachable_depth: 0,
             relocations: vec![],
             special_labels,
@@ -1761,16 +2880,54 @@ impl<'a> FuncGen<'a> {
     pub(crate) fn feed_local(&mut self, local_count: u32, local_type: WpType) {
         // FIXME: somehow verify that we haven't invoked `emit_head` yet? Doing so could lead us to
         // generate code that accesses the stack buffer out of bounds.
+        if local_type == WpType::V128 && local_count > 0 {
+            let first = *self.local_types.size();
+            self.v128_local_runs.push((first, local_count, self.v128_local_count()));
+        }
         self.local_types
             .push(local_count, local_type)
             .expect("module cannot have more than u32::MAX locals");
+        assert!(
+            self.local_types.size().checked_add(self.v128_local_count()).is_some(),
+            "module cannot have more than u32::MAX locals"
+        );
     }
 
-    /// Total number of locals and arguments so far.
+    /// Total number of machine locals taken up by the locals and arguments so far.
     ///
     /// More can be introduced with the [`feed_local`](Self::feed_local) method.
     pub(crate) fn local_count(&self) -> u32 {
-        *self.local_types.size()
+        *self.local_types.size() + self.v128_local_count()
+    }
+
+    /// Number of `v128` locals and arguments so far.
+    fn v128_local_count(&self) -> u32 {
+        self.v128_local_runs.last().map_or(0, |&(_, count, before)| before + count)
+    }
+
+    /// Index of the machine local holding the local or argument at the specified index, or its
+    /// low half for a `v128`.
+    fn machine_local_index(&self, index: u32) -> u32 {
+        let runs = self.v128_local_runs.partition_point(|&(first, _, _)| first <= index);
+        match runs.checked_sub(1).map(|run| self.v128_local_runs[run]) {
+            None => index,
+            Some((first, count, before)) => index + before + std::cmp::min(index - first, count),
+        }
+    }
+
+    /// Location of the local or argument at the specified index.
+    fn local_location(&self, index: u32) -> Location {
+        self.machine.get_local_location(self.machine_local_index(index))
+    }
+
+    /// Locations of the low and high halves of the `v128` local or argument at the specified
+    /// index.
+    fn v128_local_locations(&self, index: u32) -> (Location, Location) {
+        let machine_index = self.machine_local_index(index);
+        (
+            self.machine.get_local_location(machine_index),
+            self.machine.get_local_location(machine_index + 1),
+        )
     }
 
     /// Obtain the type of the local or argument at the specified index.
@@ -1784,6 +2941,67 @@ impl<'a> FuncGen<'a> {
         *self.local_types.find(index).expect("local index out of bounds")
     }
 
+    /// Parameter and result types of a block.
+    fn block_type(&self, blockty: WpBlockType) -> (SmallVec<[WpType; 1]>, SmallVec<[WpType; 1]>) {
+        match blockty {
+            WpBlockType::Empty => (smallvec![], smallvec![]),
+            WpBlockType::Type(WpType::V128) => (smallvec![], smallvec![WpType::V128; 2]),
+            WpBlockType::Type(inner_ty) => (smallvec![], smallvec![inner_ty]),
+            WpBlockType::FuncType(index) => {
+                let sig = &self.module.signatures[SignatureIndex::new(index as usize)];
+                (machine_types(sig.params()), machine_types(sig.results()))
+            }
+        }
+    }
+
+    /// Number of entries of the FP stack that describe values below `value_stack_depth`.
+    fn fp_stack_depth_at(&self, value_stack_depth: usize) -> usize {
+        self.fp_stack.iter().take_while(|fp| fp.depth < value_stack_depth).count()
+    }
+
+    /// Number of entries of the `v128` stack that describe values below `value_stack_depth`.
+    fn v128_stack_depth_at(&self, value_stack_depth: usize) -> usize {
+        self.v128_stack.partition_point(|&depth| depth < value_stack_depth)
+    }
+
+    /// Reserve the scratch slots `op` needs to pass multiple values around.
+    ///
+    /// With the multi-value proposal enabled, all the operators of the function must be fed
+    /// through this method before [`emit_head`](Self::emit_head) is invoked.
+    pub(crate) fn reserve_scratch_slots(&mut self, op: &Operator) {
+        let slots = match *op {
+            Operator::Block { blockty } | Operator::If { blockty } => {
+                match self.block_type(blockty).1.len() {
+                    n if n > 1 => n,
+                    _ => 0,
+                }
+            }
+            Operator::Loop { blockty } => {
+                let (params, returns) = self.block_type(blockty);
+                match returns.len() {
+                    n if n > 1 => std::cmp::max(n, params.len()),
+                    _ => params.len(),
+                }
+            }
+            Operator::Call { function_index } => {
+                let sig_index = self.module.functions[FunctionIndex::from_u32(function_index)];
+                machine_value_count(self.module.signatures[sig_index].results()).saturating_sub(1)
+            }
+            Operator::CallIndirect { type_index, .. } => machine_value_count(
+                self.module.signatures[SignatureIndex::new(type_index as usize)].results(),
+            )
+            .saturating_sub(1),
+            _ => 0,
+        };
+        self.scratch_slots = std::cmp::max(self.scratch_slots, slots);
+    }
+
+    /// Location of the saved pointer to the area that receives all but the first return value,
+    /// for functions returning more than one value.
+    fn return_area_location(&self) -> Location {
+        self.machine.get_scratch_location(self.scratch_slots)
+    }
+
     /// Consume offset self.src_loc, return Some(cost) iff there must be an instrumentation point here
     fn consume_gas_offset(&mut self /*, should_be_unreachable: bool */) -> Option<u64> {
         if let Some(&(&offset, &cost /* (&cost, &kind) */)) = self.gas_iter.peek() {
@@ -1837,11 +3055,17 @@ impl<'a> FuncGen<'a> {
                 let global_index = GlobalIndex::from_u32(global_index);
 
                 let ty = type_to_wp_type(self.module.globals[global_index].ty);
-                if ty.is_float() {
-                    self.fp_stack.push(FloatValue::new(self.value_stack.len()));
-                }
-                let loc = self.machine.acquire_locations(self.assembler, &[(ty)], false)[0];
-                self.value_stack.push(loc);
+                let (loc, v128_hi) = if ty == WpType::V128 {
+                    let (lo, hi) = self.push_v128();
+                    (lo, Some(hi))
+                } else {
+                    if ty.is_float() {
+                        self.fp_stack.push(FloatValue::new(self.value_stack.len()));
+                    }
+                    let loc = self.machine.acquire_locations(self.assembler, &[(ty)], false)[0];
+                    self.value_stack.push(loc);
+                    (loc, None)
+                };
 
                 let tmp = self.machine.acquire_temp_gpr().unwrap();
 
@@ -1869,6 +3093,10 @@ impl<'a> FuncGen<'a> {
                 };
 
                 self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, src, loc);
+                if let Some(hi) = v128_hi {
+                    let src = Location::Memory(tmp, 8);
+                    self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, src, hi);
+                }
 
                 self.machine.release_temp_gpr(tmp);
             }
@@ -1898,30 +3126,55 @@ impl<'a> FuncGen<'a> {
                     Location::Memory(tmp, 0)
                 };
                 let ty = type_to_wp_type(self.module.globals[global_index].ty);
-                let loc = self.pop_value_released();
-                if ty.is_float() {
-                    let fp = self.fp_stack.pop1()?;
-                    if self.assembler.arch_supports_canonicalize_nan()
-                        && self.config.enable_nan_canonicalization
-                        && fp.canonicalization.is_some()
-                    {
-                        self.canonicalize_nan(
-                            match ty {
-                                WpType::F32 => Size::S32,
-                                WpType::F64 => Size::S64,
-                                _ => unreachable!(),
-                            },
-                            loc,
-                            dst,
-                        );
+                if ty == WpType::V128 {
+                    let (lo, hi) = self.pop_v128_released();
+                    self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, lo, dst);
+                    let dst = Location::Memory(tmp, 8);
+                    self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, hi, dst);
+                } else {
+                    let loc = self.pop_value_released();
+                    if ty.is_float() {
+                        let fp = self.fp_stack.pop1()?;
+                        if self.assembler.arch_supports_canonicalize_nan()
+                            && self.config.enable_nan_canonicalization
+                            && fp.canonicalization.is_some()
+                        {
+                            self.canonicalize_nan(
+                                match ty {
+                                    WpType::F32 => Size::S32,
+                                    WpType::F64 => Size::S64,
+                                    _ => unreachable!(),
+                                },
+                                loc,
+                                dst,
+                            );
+                        } else {
+                            self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, loc, dst);
+                        }
                     } else {
                         self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, loc, dst);
                     }
-                } else {
-                    self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, loc, dst);
                 }
                 self.machine.release_temp_gpr(tmp);
             }
+            Operator::LocalGet { local_index } if self.local_type(local_index) == WpType::V128 => {
+                let (local_lo, local_hi) = self.v128_local_locations(local_index);
+                let (lo, hi) = self.push_v128();
+                self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, local_lo, lo);
+                self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, local_hi, hi);
+            }
+            Operator::LocalSet { local_index } if self.local_type(local_index) == WpType::V128 => {
+                let (local_lo, local_hi) = self.v128_local_locations(local_index);
+                let (lo, hi) = self.pop_v128_released();
+                self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, lo, local_lo);
+                self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, hi, local_hi);
+            }
+            Operator::LocalTee { local_index } if self.local_type(local_index) == WpType::V128 => {
+                let (local_lo, local_hi) = self.v128_local_locations(local_index);
+                let (lo, hi) = self.peek_v128();
+                self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, lo, local_lo);
+                self.emit_relaxed_binop(Assembler::emit_mov, Size::S64, hi, local_hi);
+            }
             Operator::LocalGet { local_index } => {
                 let local_type = self.local_type(local_index);
                 let ret =
@@ -1929,7 +3182,7 @@ impl<'a> FuncGen<'a> {
                 self.emit_relaxed_binop(
                     Assembler::emit_mov,
                     Size::S64,
-                    self.machine.get_local_location(local_index),
+                    self.local_location(local_index),
                     ret,
                 );
                 self.value_stack.push(ret);
@@ -1954,14 +3207,14 @@ impl<'a> FuncGen<'a> {
                                 _ => unreachable!(),
                             },
                             loc,
-                            self.machine.get_local_location(local_index),
+                            self.local_location(local_index),
                         );
                     } else {
                         self.emit_relaxed_binop(
                             Assembler::emit_mov,
                             Size::S64,
                             loc,
-                            self.machine.get_local_location(local_index),
+                            self.local_location(local_index),
                         );
                     }
                 } else {
@@ -1969,7 +3222,7 @@ impl<'a> FuncGen<'a> {
                         Assembler::emit_mov,
                         Size::S64,
                         loc,
-                        self.machine.get_local_location(local_index),
+                        self.local_location(local_index),
                     );
                 }
             }
@@ -1989,14 +3242,14 @@ impl<'a> FuncGen<'a> {
                                 _ => unreachable!(),
                             },
                             loc,
-                            self.machine.get_local_location(local_index),
+                            self.local_location(local_index),
                         );
                     } else {
                         self.emit_relaxed_binop(
                             Assembler::emit_mov,
                             Size::S64,
                             loc,
-                            self.machine.get_local_location(local_index),
+                            self.local_location(local_index),
                         );
                     }
                 } else {
@@ -2004,7 +3257,7 @@ impl<'a> FuncGen<'a> {
                         Assembler::emit_mov,
                         Size::S64,
                         loc,
-                        self.machine.get_local_location(local_index),
+                        self.local_location(local_index),
                     );
                 }
             }
@@ -4690,10 +5943,8 @@ impl<'a> FuncGen<'a> {
                 let table_index = TableIndex::new(table_index as _);
                 let index = SignatureIndex::new(type_index as usize);
                 let sig = self.module.signatures.get(index).unwrap();
-                let param_types: SmallVec<[WpType; 8]> =
-                    sig.params().iter().cloned().map(type_to_wp_type).collect();
-                let return_types: SmallVec<[WpType; 1]> =
-                    sig.results().iter().cloned().map(type_to_wp_type).collect();
+                let param_types: SmallVec<[WpType; 8]> = machine_types(sig.params());
+                let return_types: SmallVec<[WpType; 1]> = machine_types(sig.results());
 
                 let func_index = self.pop_value_released();
 
@@ -4720,6 +5971,7 @@ impl<'a> FuncGen<'a> {
                         break;
                     }
                 }
+                self.v128_stack.truncate(self.v128_stack_depth_at(self.value_stack.len()));
 
                 let table_base = self.machine.acquire_temp_gpr().unwrap();
                 let table_count = self.machine.acquire_temp_gpr().unwrap();
@@ -4825,9 +6077,17 @@ impl<'a> FuncGen<'a> {
                 let vmcaller_checked_anyfunc_vmctx =
                     self.vmoffsets.vmcaller_checked_anyfunc_vmctx() as usize;
                 let calling_convention = self.calling_convention;
+                let return_area = self.call_return_area(&return_types);
 
                 self.emit_call_native(
                     |this| {
+                        if let Some(return_area) = return_area {
+                            this.assembler.emit_lea(
+                                Size::S64,
+                                return_area,
+                                Location::GPR(GPR::R11),
+                            );
+                        }
                         if this.assembler.arch_requires_indirect_call_trampoline() {
                             this.assembler.arch_emit_indirect_call_with_trampoline(
                                 Location::Memory(
@@ -4846,114 +6106,63 @@ impl<'a> FuncGen<'a> {
                             this.assembler.emit_call_location(Location::Memory(
                                 GPR::RAX,
                                 vmcaller_checked_anyfunc_func_ptr as i32,
-                            ));
-                        }
-                    },
-                    params.iter().copied(),
-                )?;
-
-                self.machine.release_locations_only_stack(self.assembler, &params);
-
-                if !return_types.is_empty() {
-                    let ret =
-                        self.machine.acquire_locations(self.assembler, &[return_types[0]], false)
-                            [0];
-                    self.value_stack.push(ret);
-                    if return_types[0].is_float() {
-                        self.assembler.emit_mov(Size::S64, Location::XMM(XMM::XMM0), ret);
-                        self.fp_stack.push(FloatValue::new(self.value_stack.len() - 1));
-                    } else {
-                        self.assembler.emit_mov(Size::S64, Location::GPR(GPR::RAX), ret);
-                    }
-                }
-            }
-            Operator::If { blockty } => {
-                let label_end = self.assembler.get_label();
-                let label_else = self.assembler.get_label();
-
-                let cond = self.pop_value_released();
-
-                let frame = ControlFrame {
-                    br_label: label_end,
-                    loop_like: false,
-                    if_else: IfElseState::If(label_else),
-                    returns: match blockty {
-                        WpBlockType::Empty => smallvec![],
-                        WpBlockType::Type(inner_ty) => smallvec![inner_ty],
-                        WpBlockType::FuncType(_) => {
-                            return Err(CodegenError {
-                                message: "If: multi-value returns not yet implemented".to_string(),
-                            })
-                        }
-                    },
-                    value_stack_depth: self.value_stack.len(),
-                    fp_stack_depth: self.fp_stack.len(),
-                };
-                self.control_stack.push(frame);
-                self.emit_relaxed_binop(Assembler::emit_cmp, Size::S32, Location::Imm32(0), cond);
-                self.assembler.emit_jmp(Condition::Equal, label_else);
-            }
-            Operator::Else => {
-                let frame = self.control_stack.last_mut().unwrap();
-
-                if !was_unreachable && !frame.returns.is_empty() {
-                    let first_return = frame.returns[0];
-                    let loc = *self.value_stack.last().unwrap();
-                    if first_return.is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        if self.assembler.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                        {
-                            self.canonicalize_nan(
-                                match first_return {
-                                    WpType::F32 => Size::S32,
-                                    WpType::F64 => Size::S64,
-                                    _ => unreachable!(),
-                                },
-                                loc,
-                                Location::GPR(GPR::RAX),
-                            );
-                        } else {
-                            self.emit_relaxed_binop(
-                                Assembler::emit_mov,
-                                Size::S64,
-                                loc,
-                                Location::GPR(GPR::RAX),
-                            );
-                        }
-                    } else {
-                        self.emit_relaxed_binop(
-                            Assembler::emit_mov,
-                            Size::S64,
-                            loc,
-                            Location::GPR(GPR::RAX),
-                        );
-                    }
-                }
+                            ));
+                        }
+                    },
+                    params.iter().copied(),
+                )?;
 
-                let frame = self.control_stack.last_mut().unwrap();
+                self.machine.release_locations_only_stack(self.assembler, &params);
+                self.push_call_results(&return_types);
+            }
+            Operator::If { blockty } => {
+                let label_end = self.assembler.get_label();
+                let label_else = self.assembler.get_label();
 
-                let released: &[Location] = &self.value_stack[frame.value_stack_depth..];
-                self.machine.release_locations(self.assembler, released);
-                self.value_stack.truncate(frame.value_stack_depth);
-                self.fp_stack.truncate(frame.fp_stack_depth);
+                let cond = self.pop_value_released();
+                let (params, returns) = self.block_type(blockty);
+                let value_stack_depth = self.value_stack.len() - params.len();
 
-                match frame.if_else {
-                    IfElseState::If(label) => {
-                        self.assembler.emit_jmp(Condition::None, frame.br_label);
-                        self.assembler.emit_label(label);
-                        frame.if_else = IfElseState::Else;
-                    }
-                    _ => {
-                        return Err(CodegenError {
-                            message: "Else: frame.if_else unreachable code".to_string(),
-                        })
-                    }
-                }
+                let frame = ControlFrame {
+                    br_label: label_end,
+                    loop_like: false,
+                    if_else: IfElseState::If(label_else),
+                    returns,
+                    param_count: params.len(),
+                    param_locations: smallvec![],
+                    loop_stack_offset: 0,
+                    value_stack_depth,
+                    fp_stack_depth: self.fp_stack_depth_at(value_stack_depth),
+                    v128_stack_depth: self.v128_stack_depth_at(value_stack_depth),
+                };
+                self.control_stack.push(frame);
+                self.emit_relaxed_binop(Assembler::emit_cmp, Size::S32, Location::Imm32(0), cond);
+                self.assembler.emit_jmp(Condition::Equal, label_else);
+                // The `else` branch gets to consume the original parameters.
+                self.emit_duplicate_values(params.len());
+            }
+            Operator::Else => {
+                self.emit_else(was_unreachable)?;
             }
             // `TypedSelect` must be used for extern refs so ref counting should
             // be done with TypedSelect. But otherwise they're the same.
+            Operator::TypedSelect { .. } | Operator::Select if self.has_v128_below(1) => {
+                let cond = self.pop_value_released();
+                let v_b = self.pop_v128_released();
+                let v_a = self.pop_v128_released();
+                let ret = self.push_v128();
+
+                let end_label = self.assembler.get_label();
+                let zero_label = self.assembler.get_label();
+
+                self.emit_relaxed_binop(Assembler::emit_cmp, Size::S32, Location::Imm32(0), cond);
+                self.assembler.emit_jmp(Condition::Equal, zero_label);
+                self.emit_v128_move(v_a, ret);
+                self.assembler.emit_jmp(Condition::None, end_label);
+                self.assembler.emit_label(zero_label);
+                self.emit_v128_move(v_b, ret);
+                self.assembler.emit_label(end_label);
+            }
             Operator::TypedSelect { .. } | Operator::Select => {
                 let cond = self.pop_value_released();
                 let v_b = self.pop_value_released();
@@ -5010,26 +6219,29 @@ impl<'a> FuncGen<'a> {
                 self.assembler.emit_label(end_label);
             }
             Operator::Block { blockty } => {
+                let (params, returns) = self.block_type(blockty);
+                let value_stack_depth = self.value_stack.len() - params.len();
                 let frame = ControlFrame {
                     br_label: self.assembler.get_label(),
                     loop_like: false,
                     if_else: IfElseState::None,
-                    returns: match blockty {
-                        WpBlockType::Empty => smallvec![],
-                        WpBlockType::Type(inner_ty) => smallvec![inner_ty],
-                        WpBlockType::FuncType(_) => {
-                            return Err(CodegenError {
-                                message: "Block: multi-value returns not yet implemented"
-                                    .to_string(),
-                            })
-                        }
-                    },
-                    value_stack_depth: self.value_stack.len(),
-                    fp_stack_depth: self.fp_stack.len(),
+                    returns,
+                    param_count: params.len(),
+                    param_locations: smallvec![],
+                    loop_stack_offset: 0,
+                    value_stack_depth,
+                    fp_stack_depth: self.fp_stack_depth_at(value_stack_depth),
+                    v128_stack_depth: self.v128_stack_depth_at(value_stack_depth),
                 };
                 self.control_stack.push(frame);
             }
             Operator::Loop { blockty } => {
+                // `br` opcodes targeting the loop overwrite its parameters, so they need their own
+                // locations.
+                let (params, returns) = self.block_type(blockty);
+                let value_stack_depth = self.value_stack.len() - params.len();
+                let param_locations = self.emit_duplicate_values(params.len());
+
                 // Pad with NOPs to the next 16-byte boundary.
                 // Here we don't use the dynasm `.align 16` attribute because it pads the alignment with single-byte nops
                 // which may lead to efficiency problems.
@@ -5048,18 +6260,13 @@ impl<'a> FuncGen<'a> {
                     br_label,
                     loop_like: true,
                     if_else: IfElseState::None,
-                    returns: match blockty {
-                        WpBlockType::Empty => smallvec![],
-                        WpBlockType::Type(inner_ty) => smallvec![inner_ty],
-                        WpBlockType::FuncType(_) => {
-                            return Err(CodegenError {
-                                message: "Loop: multi-value returns not yet implemented"
-                                    .to_string(),
-                            })
-                        }
-                    },
-                    value_stack_depth: self.value_stack.len(),
-                    fp_stack_depth: self.fp_stack.len(),
+                    returns,
+                    param_count: params.len(),
+                    param_locations,
+                    loop_stack_offset: self.machine.get_stack_offset(),
+                    value_stack_depth,
+                    fp_stack_depth: self.fp_stack_depth_at(value_stack_depth),
+                    v128_stack_depth: self.v128_stack_depth_at(value_stack_depth),
                 });
                 self.assembler.emit_label(br_label);
 
@@ -5098,6 +6305,7 @@ impl<'a> FuncGen<'a> {
                 let len = self.value_stack.pop().unwrap();
                 let src = self.value_stack.pop().unwrap();
                 let dst = self.value_stack.pop().unwrap();
+                self.emit_bulk_gas(len, self.config.bulk_memory_byte_cost);
                 self.machine.release_locations_only_regs(&[len, src, dst]);
 
                 self.assembler.emit_mov(
@@ -5148,6 +6356,7 @@ impl<'a> FuncGen<'a> {
                 let len = self.value_stack.pop().unwrap();
                 let src_pos = self.value_stack.pop().unwrap();
                 let dst_pos = self.value_stack.pop().unwrap();
+                self.emit_bulk_gas(len, self.config.bulk_memory_byte_cost);
                 self.machine.release_locations_only_regs(&[len, src_pos, dst_pos]);
 
                 let memory_index = MemoryIndex::new(src_mem as usize);
@@ -5182,6 +6391,7 @@ impl<'a> FuncGen<'a> {
                 let len = self.value_stack.pop().unwrap();
                 let val = self.value_stack.pop().unwrap();
                 let dst = self.value_stack.pop().unwrap();
+                self.emit_bulk_gas(len, self.config.bulk_memory_byte_cost);
                 self.machine.release_locations_only_regs(&[len, val, dst]);
 
                 let memory_index = MemoryIndex::new(mem as usize);
@@ -5649,98 +6859,11 @@ impl<'a> FuncGen<'a> {
                 self.unreachable_depth = 1;
             }
             Operator::Return => {
-                let frame = &self.control_stack[0];
-                if !frame.returns.is_empty() {
-                    if frame.returns.len() != 1 {
-                        return Err(CodegenError {
-                            message: "Return: incorrect frame.returns".to_string(),
-                        });
-                    }
-                    let first_return = frame.returns[0];
-                    let loc = *self.value_stack.last().unwrap();
-                    if first_return.is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        if self.assembler.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                        {
-                            self.canonicalize_nan(
-                                match first_return {
-                                    WpType::F32 => Size::S32,
-                                    WpType::F64 => Size::S64,
-                                    _ => unreachable!(),
-                                },
-                                loc,
-                                Location::GPR(GPR::RAX),
-                            );
-                        } else {
-                            self.emit_relaxed_binop(
-                                Assembler::emit_mov,
-                                Size::S64,
-                                loc,
-                                Location::GPR(GPR::RAX),
-                            );
-                        }
-                    } else {
-                        self.emit_relaxed_binop(
-                            Assembler::emit_mov,
-                            Size::S64,
-                            loc,
-                            Location::GPR(GPR::RAX),
-                        );
-                    }
-                }
-                let frame = &self.control_stack[0];
-                let released = &self.value_stack[frame.value_stack_depth..];
-                self.machine.release_locations_keep_state(self.assembler, released);
-                self.assembler.emit_jmp(Condition::None, frame.br_label);
+                self.emit_br(self.control_stack.len() as u32 - 1)?;
                 self.unreachable_depth = 1;
             }
             Operator::Br { relative_depth } => {
-                let frame =
-                    &self.control_stack[self.control_stack.len() - 1 - (relative_depth as usize)];
-                if !frame.loop_like && !frame.returns.is_empty() {
-                    if frame.returns.len() != 1 {
-                        return Err(CodegenError {
-                            message: "Br: incorrect frame.returns".to_string(),
-                        });
-                    }
-                    let first_return = frame.returns[0];
-                    let loc = *self.value_stack.last().unwrap();
-
-                    if first_return.is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        if self.assembler.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                        {
-                            self.canonicalize_nan(
-                                match first_return {
-                                    WpType::F32 => Size::S32,
-                                    WpType::F64 => Size::S64,
-                                    _ => unreachable!(),
-                                },
-                                loc,
-                                Location::GPR(GPR::RAX),
-                            );
-                        } else {
-                            self.emit_relaxed_binop(
-                                Assembler::emit_mov,
-                                Size::S64,
-                                loc,
-                                Location::GPR(GPR::RAX),
-                            );
-                        }
-                    } else {
-                        self.assembler.emit_mov(Size::S64, loc, Location::GPR(GPR::RAX));
-                    }
-                }
-                let frame =
-                    &self.control_stack[self.control_stack.len() - 1 - (relative_depth as usize)];
-
-                let released = &self.value_stack[frame.value_stack_depth..];
-                self.machine.release_locations_keep_state(self.assembler, released);
-                self.assembler.emit_jmp(Condition::None, frame.br_label);
+                self.emit_br(relative_depth)?;
                 self.unreachable_depth = 1;
             }
             Operator::BrIf { relative_depth } => {
@@ -5748,51 +6871,7 @@ impl<'a> FuncGen<'a> {
                 let cond = self.pop_value_released();
                 self.emit_relaxed_binop(Assembler::emit_cmp, Size::S32, Location::Imm32(0), cond);
                 self.assembler.emit_jmp(Condition::Equal, after);
-
-                let frame =
-                    &self.control_stack[self.control_stack.len() - 1 - (relative_depth as usize)];
-                if !frame.loop_like && !frame.returns.is_empty() {
-                    if frame.returns.len() != 1 {
-                        return Err(CodegenError {
-                            message: "BrIf: incorrect frame.returns".to_string(),
-                        });
-                    }
-
-                    let first_return = frame.returns[0];
-                    let loc = *self.value_stack.last().unwrap();
-                    if first_return.is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        if self.assembler.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                        {
-                            self.canonicalize_nan(
-                                match first_return {
-                                    WpType::F32 => Size::S32,
-                                    WpType::F64 => Size::S64,
-                                    _ => unreachable!(),
-                                },
-                                loc,
-                                Location::GPR(GPR::RAX),
-                            );
-                        } else {
-                            self.emit_relaxed_binop(
-                                Assembler::emit_mov,
-                                Size::S64,
-                                loc,
-                                Location::GPR(GPR::RAX),
-                            );
-                        }
-                    } else {
-                        self.assembler.emit_mov(Size::S64, loc, Location::GPR(GPR::RAX));
-                    }
-                }
-                let frame =
-                    &self.control_stack[self.control_stack.len() - 1 - (relative_depth as usize)];
-                let released = &self.value_stack[frame.value_stack_depth..];
-                self.machine.release_locations_keep_state(self.assembler, released);
-                self.assembler.emit_jmp(Condition::None, frame.br_label);
-
+                self.emit_br(relative_depth)?;
                 self.assembler.emit_label(after);
             }
             Operator::BrTable { ref targets } => {
@@ -5828,100 +6907,10 @@ impl<'a> FuncGen<'a> {
                     let label = self.assembler.get_label();
                     self.assembler.emit_label(label);
                     table.push(label);
-                    let frame =
-                        &self.control_stack[self.control_stack.len() - 1 - (*target as usize)];
-                    if !frame.loop_like && !frame.returns.is_empty() {
-                        if frame.returns.len() != 1 {
-                            return Err(CodegenError {
-                                message: format!(
-                                    "BrTable: incorrect frame.returns for {:?}",
-                                    target
-                                ),
-                            });
-                        }
-
-                        let first_return = frame.returns[0];
-                        let loc = *self.value_stack.last().unwrap();
-                        if first_return.is_float() {
-                            let fp = self.fp_stack.peek1()?;
-                            if self.assembler.arch_supports_canonicalize_nan()
-                                && self.config.enable_nan_canonicalization
-                                && fp.canonicalization.is_some()
-                            {
-                                self.canonicalize_nan(
-                                    match first_return {
-                                        WpType::F32 => Size::S32,
-                                        WpType::F64 => Size::S64,
-                                        _ => unreachable!(),
-                                    },
-                                    loc,
-                                    Location::GPR(GPR::RAX),
-                                );
-                            } else {
-                                self.emit_relaxed_binop(
-                                    Assembler::emit_mov,
-                                    Size::S64,
-                                    loc,
-                                    Location::GPR(GPR::RAX),
-                                );
-                            }
-                        } else {
-                            self.assembler.emit_mov(Size::S64, loc, Location::GPR(GPR::RAX));
-                        }
-                    }
-                    let frame =
-                        &self.control_stack[self.control_stack.len() - 1 - (*target as usize)];
-                    let released = &self.value_stack[frame.value_stack_depth..];
-                    self.machine.release_locations_keep_state(self.assembler, released);
-                    self.assembler.emit_jmp(Condition::None, frame.br_label);
+                    self.emit_br(*target)?;
                 }
                 self.assembler.emit_label(default_br);
-
-                {
-                    let frame = &self.control_stack
-                        [self.control_stack.len() - 1 - (default_target as usize)];
-                    if !frame.loop_like && !frame.returns.is_empty() {
-                        if frame.returns.len() != 1 {
-                            return Err(CodegenError {
-                                message: "BrTable: incorrect frame.returns".to_string(),
-                            });
-                        }
-
-                        let first_return = frame.returns[0];
-                        let loc = *self.value_stack.last().unwrap();
-                        if first_return.is_float() {
-                            let fp = self.fp_stack.peek1()?;
-                            if self.assembler.arch_supports_canonicalize_nan()
-                                && self.config.enable_nan_canonicalization
-                                && fp.canonicalization.is_some()
-                            {
-                                self.canonicalize_nan(
-                                    match first_return {
-                                        WpType::F32 => Size::S32,
-                                        WpType::F64 => Size::S64,
-                                        _ => unreachable!(),
-                                    },
-                                    loc,
-                                    Location::GPR(GPR::RAX),
-                                );
-                            } else {
-                                self.emit_relaxed_binop(
-                                    Assembler::emit_mov,
-                                    Size::S64,
-                                    loc,
-                                    Location::GPR(GPR::RAX),
-                                );
-                            }
-                        } else {
-                            self.assembler.emit_mov(Size::S64, loc, Location::GPR(GPR::RAX));
-                        }
-                    }
-                    let frame = &self.control_stack
-                        [self.control_stack.len() - 1 - (default_target as usize)];
-                    let released = &self.value_stack[frame.value_stack_depth..];
-                    self.machine.release_locations_keep_state(self.assembler, released);
-                    self.assembler.emit_jmp(Condition::None, frame.br_label);
-                }
+                self.emit_br(default_target)?;
 
                 self.assembler.emit_label(table_label);
                 for x in table {
@@ -5929,6 +6918,9 @@ impl<'a> FuncGen<'a> {
                 }
                 self.unreachable_depth = 1;
             }
+            Operator::Drop if self.has_v128_below(0) => {
+                self.pop_v128_released();
+            }
             Operator::Drop => {
                 self.pop_value_released();
                 if let Some(x) = self.fp_stack.last() {
@@ -5938,45 +6930,46 @@ impl<'a> FuncGen<'a> {
                 }
             }
             Operator::End => {
+                let mut was_unreachable = was_unreachable;
+                if let Some(frame) = self.control_stack.last() {
+                    // Without an `else` branch, the parameters are the results of the false branch.
+                    if let IfElseState::If(_) = frame.if_else {
+                        if frame.param_count > 0 {
+                            self.emit_else(was_unreachable)?;
+                            was_unreachable = false;
+                        }
+                    }
+                }
                 let frame = self.control_stack.pop().unwrap();
 
-                if !was_unreachable && !frame.returns.is_empty() {
-                    let loc = *self.value_stack.last().unwrap();
-                    if frame.returns[0].is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        if self.assembler.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                        {
-                            self.canonicalize_nan(
-                                match frame.returns[0] {
-                                    WpType::F32 => Size::S32,
-                                    WpType::F64 => Size::S64,
-                                    _ => unreachable!(),
-                                },
-                                loc,
-                                Location::GPR(GPR::RAX),
+                if !was_unreachable {
+                    self.emit_block_results(&frame.returns)?;
+                }
+
+                if self.control_stack.is_empty() {
+                    self.assembler.emit_label(frame.br_label);
+                    if frame.returns.len() > 1 {
+                        // Write all but the first return value to the area provided by the caller.
+                        let return_area = self.return_area_location();
+                        self.assembler.emit_mov(Size::S64, return_area, Location::GPR(GPR::RCX));
+                        for i in 1..frame.returns.len() {
+                            self.assembler.emit_mov(
+                                Size::S64,
+                                self.machine.get_scratch_location(i),
+                                Location::GPR(GPR::RDX),
                             );
-                        } else {
-                            self.emit_relaxed_binop(
-                                Assembler::emit_mov,
+                            self.assembler.emit_mov(
                                 Size::S64,
-                                loc,
-                                Location::GPR(GPR::RAX),
+                                Location::GPR(GPR::RDX),
+                                Location::Memory(GPR::RCX, (i as i32 - 1) * 8),
                             );
                         }
-                    } else {
-                        self.emit_relaxed_binop(
-                            Assembler::emit_mov,
+                        self.assembler.emit_mov(
                             Size::S64,
-                            loc,
+                            self.machine.get_scratch_location(0),
                             Location::GPR(GPR::RAX),
                         );
                     }
-                }
-
-                if self.control_stack.is_empty() {
-                    self.assembler.emit_label(frame.br_label);
                     let local_count = self.local_count();
                     self.machine.finalize_locals(self.assembler);
 
@@ -6002,9 +6995,9 @@ impl<'a> FuncGen<'a> {
                     );
                     self.assembler.emit_pop(Size::S64, Location::GPR(GPR::RBP));
 
-                    // Make a copy of the return value in XMM0, as required by the SysV CC.
-                    match self.signature.results() {
-                        [x] if *x == Type::F32 || *x == Type::F64 => {
+                    // Make a copy of the first return value in XMM0, as required by the SysV CC.
+                    match self.signature.results().first() {
+                        Some(x) if *x == Type::F32 || *x == Type::F64 => {
                             self.assembler.emit_mov(
                                 Size::S64,
                                 Location::GPR(GPR::RAX),
@@ -6019,6 +7012,7 @@ impl<'a> FuncGen<'a> {
                     self.machine.release_locations(self.assembler, released);
                     self.value_stack.truncate(frame.value_stack_depth);
                     self.fp_stack.truncate(frame.fp_stack_depth);
+                    self.v128_stack.truncate(frame.v128_stack_depth);
 
                     if !frame.loop_like {
                         self.assembler.emit_label(frame.br_label);
@@ -6028,12 +7022,9 @@ impl<'a> FuncGen<'a> {
                         self.assembler.emit_label(label);
                     }
 
-                    if !frame.returns.is_empty() {
-                        if frame.returns.len() != 1 {
-                            return Err(CodegenError {
-                                message: "End: incorrect frame.returns".to_string(),
-                            });
-                        }
+                    if frame.returns.len() > 1 {
+                        self.emit_unstash_values(&frame.returns);
+                    } else if !frame.returns.is_empty() {
                         let loc = self.machine.acquire_locations(
                             self.assembler,
                             &[(frame.returns[0])],
@@ -7505,6 +8496,7 @@ impl<'a> FuncGen<'a> {
                 let table_index = TableIndex::new(index as _);
                 let delta = self.value_stack.pop().unwrap();
                 let init_value = self.value_stack.pop().unwrap();
+                self.emit_bulk_gas(delta, self.config.bulk_table_element_cost);
                 self.machine.release_locations_only_regs(&[delta, init_value]);
 
                 self.assembler.emit_mov(
@@ -7544,6 +8536,7 @@ impl<'a> FuncGen<'a> {
                 let len = self.value_stack.pop().unwrap();
                 let src = self.value_stack.pop().unwrap();
                 let dest = self.value_stack.pop().unwrap();
+                self.emit_bulk_gas(len, self.config.bulk_table_element_cost);
                 self.machine.release_locations_only_regs(&[len, src, dest]);
 
                 self.assembler.emit_mov(
@@ -7575,6 +8568,7 @@ impl<'a> FuncGen<'a> {
                 let len = self.value_stack.pop().unwrap();
                 let val = self.value_stack.pop().unwrap();
                 let dest = self.value_stack.pop().unwrap();
+                self.emit_bulk_gas(len, self.config.bulk_table_element_cost);
                 self.machine.release_locations_only_regs(&[len, val, dest]);
 
                 self.assembler.emit_mov(
@@ -7603,6 +8597,7 @@ impl<'a> FuncGen<'a> {
                 let len = self.value_stack.pop().unwrap();
                 let src = self.value_stack.pop().unwrap();
                 let dest = self.value_stack.pop().unwrap();
+                self.emit_bulk_gas(len, self.config.bulk_table_element_cost);
                 self.machine.release_locations_only_regs(&[len, src, dest]);
 
                 self.assembler.emit_mov(
@@ -7649,6 +8644,600 @@ impl<'a> FuncGen<'a> {
                     [Location::Imm32(elem_index)].iter().cloned(),
                 )?;
             }
+            Operator::V128Const { value } => {
+                let value = u128::from_le_bytes(*value.bytes());
+                self.v128_stack.push(self.value_stack.len());
+                self.value_stack.push(Location::Imm64(value as u64));
+                self.value_stack.push(Location::Imm64((value >> 64) as u64));
+            }
+            Operator::V128Load { ref memarg } => {
+                let target = self.pop_value_released();
+                let (lo, hi) = self.push_v128();
+                self.emit_memory_op(target, memarg, false, 16, |this, addr| {
+                    let src = Location::Memory(addr, 0);
+                    this.emit_relaxed_binop(Assembler::emit_mov, Size::S64, src, lo);
+                    let src = Location::Memory(addr, 8);
+                    this.emit_relaxed_binop(Assembler::emit_mov, Size::S64, src, hi);
+                    Ok(())
+                })?;
+            }
+            Operator::V128Load8x8S { ref memarg } => {
+                self.emit_simd_load_extend(memarg, Assembler::emit_vpmovsxbw)?
+            }
+            Operator::V128Load8x8U { ref memarg } => {
+                self.emit_simd_load_extend(memarg, Assembler::emit_vpmovzxbw)?
+            }
+            Operator::V128Load16x4S { ref memarg } => {
+                self.emit_simd_load_extend(memarg, Assembler::emit_vpmovsxwd)?
+            }
+            Operator::V128Load16x4U { ref memarg } => {
+                self.emit_simd_load_extend(memarg, Assembler::emit_vpmovzxwd)?
+            }
+            Operator::V128Load32x2S { ref memarg } => {
+                self.emit_simd_load_extend(memarg, Assembler::emit_vpmovsxdq)?
+            }
+            Operator::V128Load32x2U { ref memarg } => {
+                self.emit_simd_load_extend(memarg, Assembler::emit_vpmovzxdq)?
+            }
+            Operator::V128Load8Splat { ref memarg } => self.emit_simd_load_splat(memarg, 8)?,
+            Operator::V128Load16Splat { ref memarg } => self.emit_simd_load_splat(memarg, 16)?,
+            Operator::V128Load32Splat { ref memarg } => self.emit_simd_load_splat(memarg, 32)?,
+            Operator::V128Load64Splat { ref memarg } => self.emit_simd_load_splat(memarg, 64)?,
+            Operator::V128Load32Zero { ref memarg } => self.emit_simd_load_zero(memarg, 32)?,
+            Operator::V128Load64Zero { ref memarg } => self.emit_simd_load_zero(memarg, 64)?,
+            Operator::V128Load8Lane { ref memarg, lane } => {
+                self.emit_simd_load_lane(memarg, 8, lane)?
+            }
+            Operator::V128Load16Lane { ref memarg, lane } => {
+                self.emit_simd_load_lane(memarg, 16, lane)?
+            }
+            Operator::V128Load32Lane { ref memarg, lane } => {
+                self.emit_simd_load_lane(memarg, 32, lane)?
+            }
+            Operator::V128Load64Lane { ref memarg, lane } => {
+                self.emit_simd_load_lane(memarg, 64, lane)?
+            }
+            Operator::V128Store { ref memarg } => {
+                let (lo, hi) = self.pop_v128_released();
+                let target_addr = self.pop_value_released();
+                self.emit_memory_op(target_addr, memarg, false, 16, |this, addr| {
+                    let dst = Location::Memory(addr, 0);
+                    this.emit_relaxed_binop(Assembler::emit_mov, Size::S64, lo, dst);
+                    let dst = Location::Memory(addr, 8);
+                    this.emit_relaxed_binop(Assembler::emit_mov, Size::S64, hi, dst);
+                    Ok(())
+                })?;
+            }
+            Operator::V128Store8Lane { ref memarg, lane } => {
+                self.emit_simd_store_lane(memarg, 8, lane)?
+            }
+            Operator::V128Store16Lane { ref memarg, lane } => {
+                self.emit_simd_store_lane(memarg, 16, lane)?
+            }
+            Operator::V128Store32Lane { ref memarg, lane } => {
+                self.emit_simd_store_lane(memarg, 32, lane)?
+            }
+            Operator::V128Store64Lane { ref memarg, lane } => {
+                self.emit_simd_store_lane(memarg, 64, lane)?
+            }
+            Operator::I8x16Shuffle { lanes } => {
+                // Pick the lanes of each operand separately, zeroing those taken from the other
+                // one by setting the top bit of their index.
+                let indices = |first: u8| {
+                    u128::from_le_bytes(lanes.map(|lane| {
+                        lane.checked_sub(first).filter(|&lane| lane < 16).unwrap_or(0x80)
+                    }))
+                };
+                let (indices_a, indices_b) = (indices(0), indices(16));
+                self.emit_simd_binop(|this, a, b, out| {
+                    let tmp = this.machine.acquire_temp_xmm().unwrap();
+                    this.emit_v128_const(indices_a, tmp);
+                    this.assembler.emit_vpshufb(a, XMMOrMemory::XMM(tmp), out);
+                    this.emit_v128_const(indices_b, tmp);
+                    this.assembler.emit_vpshufb(b, XMMOrMemory::XMM(tmp), tmp);
+                    this.assembler.emit_vpor(out, XMMOrMemory::XMM(tmp), out);
+                    this.machine.release_temp_xmm(tmp);
+                });
+            }
+            Operator::I8x16Swizzle => self.emit_simd_binop(|this, a, b, out| {
+                // Saturating addition sets the top bit of out of range indices, so that they
+                // select a zero.
+                let tmp = this.machine.acquire_temp_xmm().unwrap();
+                this.emit_v128_const(splat(0x70, 8), tmp);
+                this.assembler.emit_vpaddusb(b, XMMOrMemory::XMM(tmp), tmp);
+                this.assembler.emit_vpshufb(a, XMMOrMemory::XMM(tmp), out);
+                this.machine.release_temp_xmm(tmp);
+            }),
+            Operator::I8x16ExtractLaneS { lane } => {
+                self.emit_simd_extract_lane(WpType::I32, 8, lane, true)
+            }
+            Operator::I8x16ExtractLaneU { lane } => {
+                self.emit_simd_extract_lane(WpType::I32, 8, lane, false)
+            }
+            Operator::I16x8ExtractLaneS { lane } => {
+                self.emit_simd_extract_lane(WpType::I32, 16, lane, true)
+            }
+            Operator::I16x8ExtractLaneU { lane } => {
+                self.emit_simd_extract_lane(WpType::I32, 16, lane, false)
+            }
+            Operator::I32x4ExtractLane { lane } => {
+                self.emit_simd_extract_lane(WpType::I32, 32, lane, false)
+            }
+            Operator::I64x2ExtractLane { lane } => {
+                self.emit_simd_extract_lane(WpType::I64, 64, lane, false)
+            }
+            Operator::F32x4ExtractLane { lane } => {
+                self.emit_simd_extract_lane(WpType::F32, 32, lane, false)
+            }
+            Operator::F64x2ExtractLane { lane } => {
+                self.emit_simd_extract_lane(WpType::F64, 64, lane, false)
+            }
+            Operator::I8x16ReplaceLane { lane } => {
+                self.emit_simd_replace_lane(WpType::I32, 8, lane)?
+            }
+            Operator::I16x8ReplaceLane { lane } => {
+                self.emit_simd_replace_lane(WpType::I32, 16, lane)?
+            }
+            Operator::I32x4ReplaceLane { lane } => {
+                self.emit_simd_replace_lane(WpType::I32, 32, lane)?
+            }
+            Operator::I64x2ReplaceLane { lane } => {
+                self.emit_simd_replace_lane(WpType::I64, 64, lane)?
+            }
+            Operator::F32x4ReplaceLane { lane } => {
+                self.emit_simd_replace_lane(WpType::F32, 32, lane)?
+            }
+            Operator::F64x2ReplaceLane { lane } => {
+                self.emit_simd_replace_lane(WpType::F64, 64, lane)?
+            }
+            Operator::I8x16Splat => self.emit_simd_splat(WpType::I32, 8)?,
+            Operator::I16x8Splat => self.emit_simd_splat(WpType::I32, 16)?,
+            Operator::I32x4Splat => self.emit_simd_splat(WpType::I32, 32)?,
+            Operator::I64x2Splat => self.emit_simd_splat(WpType::I64, 64)?,
+            Operator::F32x4Splat => self.emit_simd_splat(WpType::F32, 32)?,
+            Operator::F64x2Splat => self.emit_simd_splat(WpType::F64, 64)?,
+            Operator::I8x16Eq => self.emit_simd_binop_avx(Assembler::emit_vpcmpeqb),
+            Operator::I8x16Ne => self.emit_simd_cmp_not(Assembler::emit_vpcmpeqb, false),
+            Operator::I8x16LtS => self.emit_simd_binop_avx_swapped(Assembler::emit_vpcmpgtb),
+            Operator::I8x16LtU => {
+                self.emit_simd_cmp_unsigned(Assembler::emit_vpmaxub, Assembler::emit_vpcmpeqb, true)
+            }
+            Operator::I8x16GtS => self.emit_simd_binop_avx(Assembler::emit_vpcmpgtb),
+            Operator::I8x16GtU => {
+                self.emit_simd_cmp_unsigned(Assembler::emit_vpminub, Assembler::emit_vpcmpeqb, true)
+            }
+            Operator::I8x16LeS => self.emit_simd_cmp_not(Assembler::emit_vpcmpgtb, false),
+            Operator::I8x16LeU => self.emit_simd_cmp_unsigned(
+                Assembler::emit_vpminub,
+                Assembler::emit_vpcmpeqb,
+                false,
+            ),
+            Operator::I8x16GeS => self.emit_simd_cmp_not(Assembler::emit_vpcmpgtb, true),
+            Operator::I8x16GeU => self.emit_simd_cmp_unsigned(
+                Assembler::emit_vpmaxub,
+                Assembler::emit_vpcmpeqb,
+                false,
+            ),
+            Operator::I16x8Eq => self.emit_simd_binop_avx(Assembler::emit_vpcmpeqw),
+            Operator::I16x8Ne => self.emit_simd_cmp_not(Assembler::emit_vpcmpeqw, false),
+            Operator::I16x8LtS => self.emit_simd_binop_avx_swapped(Assembler::emit_vpcmpgtw),
+            Operator::I16x8LtU => {
+                self.emit_simd_cmp_unsigned(Assembler::emit_vpmaxuw, Assembler::emit_vpcmpeqw, true)
+            }
+            Operator::I16x8GtS => self.emit_simd_binop_avx(Assembler::emit_vpcmpgtw),
+            Operator::I16x8GtU => {
+                self.emit_simd_cmp_unsigned(Assembler::emit_vpminuw, Assembler::emit_vpcmpeqw, true)
+            }
+            Operator::I16x8LeS => self.emit_simd_cmp_not(Assembler::emit_vpcmpgtw, false),
+            Operator::I16x8LeU => self.emit_simd_cmp_unsigned(
+                Assembler::emit_vpminuw,
+                Assembler::emit_vpcmpeqw,
+                false,
+            ),
+            Operator::I16x8GeS => self.emit_simd_cmp_not(Assembler::emit_vpcmpgtw, true),
+            Operator::I16x8GeU => self.emit_simd_cmp_unsigned(
+                Assembler::emit_vpmaxuw,
+                Assembler::emit_vpcmpeqw,
+                false,
+            ),
+            Operator::I32x4Eq => self.emit_simd_binop_avx(Assembler::emit_vpcmpeqd),
+            Operator::I32x4Ne => self.emit_simd_cmp_not(Assembler::emit_vpcmpeqd, false),
+            Operator::I32x4LtS => self.emit_simd_binop_avx_swapped(Assembler::emit_vpcmpgtd),
+            Operator::I32x4LtU => {
+                self.emit_simd_cmp_unsigned(Assembler::emit_vpmaxud, Assembler::emit_vpcmpeqd, true)
+            }
+            Operator::I32x4GtS => self.emit_simd_binop_avx(Assembler::emit_vpcmpgtd),
+            Operator::I32x4GtU => {
+                self.emit_simd_cmp_unsigned(Assembler::emit_vpminud, Assembler::emit_vpcmpeqd, true)
+            }
+            Operator::I32x4LeS => self.emit_simd_cmp_not(Assembler::emit_vpcmpgtd, false),
+            Operator::I32x4LeU => self.emit_simd_cmp_unsigned(
+                Assembler::emit_vpminud,
+                Assembler::emit_vpcmpeqd,
+                false,
+            ),
+            Operator::I32x4GeS => self.emit_simd_cmp_not(Assembler::emit_vpcmpgtd, true),
+            Operator::I32x4GeU => self.emit_simd_cmp_unsigned(
+                Assembler::emit_vpmaxud,
+                Assembler::emit_vpcmpeqd,
+                false,
+            ),
+            Operator::I64x2Eq => self.emit_simd_binop_avx(Assembler::emit_vpcmpeqq),
+            Operator::I64x2Ne => self.emit_simd_cmp_not(Assembler::emit_vpcmpeqq, false),
+            Operator::I64x2LtS => self.emit_simd_binop_avx_swapped(Assembler::emit_vpcmpgtq),
+            Operator::I64x2GtS => self.emit_simd_binop_avx(Assembler::emit_vpcmpgtq),
+            Operator::I64x2LeS => self.emit_simd_cmp_not(Assembler::emit_vpcmpgtq, false),
+            Operator::I64x2GeS => self.emit_simd_cmp_not(Assembler::emit_vpcmpgtq, true),
+            Operator::F32x4Eq => self.emit_simd_binop_avx(Assembler::emit_vcmpeqps),
+            Operator::F32x4Ne => self.emit_simd_binop_avx(Assembler::emit_vcmpneqps),
+            Operator::F32x4Lt => self.emit_simd_binop_avx(Assembler::emit_vcmpltps),
+            Operator::F32x4Gt => self.emit_simd_binop_avx_swapped(Assembler::emit_vcmpltps),
+            Operator::F32x4Le => self.emit_simd_binop_avx(Assembler::emit_vcmpleps),
+            Operator::F32x4Ge => self.emit_simd_binop_avx_swapped(Assembler::emit_vcmpleps),
+            Operator::F64x2Eq => self.emit_simd_binop_avx(Assembler::emit_vcmpeqpd),
+            Operator::F64x2Ne => self.emit_simd_binop_avx(Assembler::emit_vcmpneqpd),
+            Operator::F64x2Lt => self.emit_simd_binop_avx(Assembler::emit_vcmpltpd),
+            Operator::F64x2Gt => self.emit_simd_binop_avx_swapped(Assembler::emit_vcmpltpd),
+            Operator::F64x2Le => self.emit_simd_binop_avx(Assembler::emit_vcmplepd),
+            Operator::F64x2Ge => self.emit_simd_binop_avx_swapped(Assembler::emit_vcmplepd),
+            Operator::V128Not => self.emit_simd_bitwise_const(Assembler::emit_vpxor, u128::MAX),
+            Operator::V128And => self.emit_simd_binop_avx(Assembler::emit_vpand),
+            Operator::V128AndNot => self.emit_simd_binop_avx_swapped(Assembler::emit_vpandn),
+            Operator::V128Or => self.emit_simd_binop_avx(Assembler::emit_vpor),
+            Operator::V128Xor => self.emit_simd_binop_avx(Assembler::emit_vpxor),
+            Operator::V128Bitselect => {
+                let c = self.pop_v128_released();
+                self.emit_simd_binop(|this, a, b, out| {
+                    let mask = this.machine.acquire_temp_xmm().unwrap();
+                    this.emit_v128_load(c, mask);
+                    this.assembler.emit_vpand(a, XMMOrMemory::XMM(mask), a);
+                    this.assembler.emit_vpandn(mask, XMMOrMemory::XMM(b), out);
+                    this.assembler.emit_vpor(out, XMMOrMemory::XMM(a), out);
+                    this.machine.release_temp_xmm(mask);
+                });
+            }
+            Operator::V128AnyTrue => self.emit_simd_true(8, false)?,
+            Operator::I8x16Abs => self.emit_simd_unop_avx(Assembler::emit_vpabsb),
+            Operator::I8x16Neg => self.emit_simd_neg(Assembler::emit_vpsubb),
+            Operator::I8x16Popcnt => self.emit_simd_unop(|this, a, out| {
+                // Look the bit counts of both nibbles of each byte up in a table.
+                let low_nibbles = this.machine.acquire_temp_xmm().unwrap();
+                let table = this.machine.acquire_temp_xmm().unwrap();
+                this.emit_v128_const(splat(0x0F, 8), low_nibbles);
+                this.assembler.emit_vpand(a, XMMOrMemory::XMM(low_nibbles), out);
+                this.assembler.emit_vpsrlw_imm(a, 4, a);
+                this.assembler.emit_vpand(a, XMMOrMemory::XMM(low_nibbles), a);
+                this.emit_v128_const(0x0403_0302_0302_0201_0302_0201_0201_0100, table);
+                this.assembler.emit_vpshufb(table, XMMOrMemory::XMM(out), out);
+                this.assembler.emit_vpshufb(table, XMMOrMemory::XMM(a), a);
+                this.assembler.emit_vpaddb(out, XMMOrMemory::XMM(a), out);
+                this.machine.release_temp_xmm(table);
+                this.machine.release_temp_xmm(low_nibbles);
+            }),
+            Operator::I8x16AllTrue => self.emit_simd_true(8, true)?,
+            Operator::I8x16Bitmask => self.emit_simd_bitmask(8),
+            Operator::I8x16NarrowI16x8S => self.emit_simd_binop_avx(Assembler::emit_vpacksswb),
+            Operator::I8x16NarrowI16x8U => self.emit_simd_binop_avx(Assembler::emit_vpackuswb),
+            Operator::I8x16Shl => self.emit_simd_shift_i8x16(false),
+            Operator::I8x16ShrS => self.emit_simd_sar_i8x16(),
+            Operator::I8x16ShrU => self.emit_simd_shift_i8x16(true),
+            Operator::I8x16Add => self.emit_simd_binop_avx(Assembler::emit_vpaddb),
+            Operator::I8x16AddSatS => self.emit_simd_binop_avx(Assembler::emit_vpaddsb),
+            Operator::I8x16AddSatU => self.emit_simd_binop_avx(Assembler::emit_vpaddusb),
+            Operator::I8x16Sub => self.emit_simd_binop_avx(Assembler::emit_vpsubb),
+            Operator::I8x16SubSatS => self.emit_simd_binop_avx(Assembler::emit_vpsubsb),
+            Operator::I8x16SubSatU => self.emit_simd_binop_avx(Assembler::emit_vpsubusb),
+            Operator::I8x16MinS => self.emit_simd_binop_avx(Assembler::emit_vpminsb),
+            Operator::I8x16MinU => self.emit_simd_binop_avx(Assembler::emit_vpminub),
+            Operator::I8x16MaxS => self.emit_simd_binop_avx(Assembler::emit_vpmaxsb),
+            Operator::I8x16MaxU => self.emit_simd_binop_avx(Assembler::emit_vpmaxub),
+            Operator::I8x16AvgrU => self.emit_simd_binop_avx(Assembler::emit_vpavgb),
+            Operator::I16x8ExtAddPairwiseI8x16S => self.emit_simd_unop(|this, a, out| {
+                // Multiply the signed bytes by an unsigned 1 and add the adjacent products.
+                this.emit_v128_const(splat(1, 8), out);
+                this.assembler.emit_vpmaddubsw(out, XMMOrMemory::XMM(a), out);
+            }),
+            Operator::I16x8ExtAddPairwiseI8x16U => self.emit_simd_unop(|this, a, out| {
+                // Multiply the unsigned bytes by a signed 1 and add the adjacent products.
+                this.emit_v128_const(splat(1, 8), out);
+                this.assembler.emit_vpmaddubsw(a, XMMOrMemory::XMM(out), out);
+            }),
+            Operator::I16x8Abs => self.emit_simd_unop_avx(Assembler::emit_vpabsw),
+            Operator::I16x8Neg => self.emit_simd_neg(Assembler::emit_vpsubw),
+            Operator::I16x8Q15MulrSatS => self.emit_simd_binop(|this, a, b, out| {
+                // The only overflow, of -1 times -1, yields 0x8000, which needs to saturate.
+                this.assembler.emit_vpmulhrsw(a, XMMOrMemory::XMM(b), out);
+                this.emit_v128_const(splat(0x8000, 16), a);
+                this.assembler.emit_vpcmpeqw(out, XMMOrMemory::XMM(a), a);
+                this.assembler.emit_vpxor(out, XMMOrMemory::XMM(a), out);
+            }),
+            Operator::I16x8AllTrue => self.emit_simd_true(16, true)?,
+            Operator::I16x8Bitmask => self.emit_simd_bitmask(16),
+            Operator::I16x8NarrowI32x4S => self.emit_simd_binop_avx(Assembler::emit_vpackssdw),
+            Operator::I16x8NarrowI32x4U => self.emit_simd_binop_avx(Assembler::emit_vpackusdw),
+            Operator::I16x8ExtendLowI8x16S => {
+                self.emit_simd_extend(false, Assembler::emit_vpmovsxbw)
+            }
+            Operator::I16x8ExtendHighI8x16S => {
+                self.emit_simd_extend(true, Assembler::emit_vpmovsxbw)
+            }
+            Operator::I16x8ExtendLowI8x16U => {
+                self.emit_simd_extend(false, Assembler::emit_vpmovzxbw)
+            }
+            Operator::I16x8ExtendHighI8x16U => {
+                self.emit_simd_extend(true, Assembler::emit_vpmovzxbw)
+            }
+            Operator::I16x8Shl => self.emit_simd_shift_avx(16, Assembler::emit_vpsllw),
+            Operator::I16x8ShrS => self.emit_simd_shift_avx(16, Assembler::emit_vpsraw),
+            Operator::I16x8ShrU => self.emit_simd_shift_avx(16, Assembler::emit_vpsrlw),
+            Operator::I16x8Add => self.emit_simd_binop_avx(Assembler::emit_vpaddw),
+            Operator::I16x8AddSatS => self.emit_simd_binop_avx(Assembler::emit_vpaddsw),
+            Operator::I16x8AddSatU => self.emit_simd_binop_avx(Assembler::emit_vpaddusw),
+            Operator::I16x8Sub => self.emit_simd_binop_avx(Assembler::emit_vpsubw),
+            Operator::I16x8SubSatS => self.emit_simd_binop_avx(Assembler::emit_vpsubsw),
+            Operator::I16x8SubSatU => self.emit_simd_binop_avx(Assembler::emit_vpsubusw),
+            Operator::I16x8Mul => self.emit_simd_binop_avx(Assembler::emit_vpmullw),
+            Operator::I16x8MinS => self.emit_simd_binop_avx(Assembler::emit_vpminsw),
+            Operator::I16x8MinU => self.emit_simd_binop_avx(Assembler::emit_vpminuw),
+            Operator::I16x8MaxS => self.emit_simd_binop_avx(Assembler::emit_vpmaxsw),
+            Operator::I16x8MaxU => self.emit_simd_binop_avx(Assembler::emit_vpmaxuw),
+            Operator::I16x8AvgrU => self.emit_simd_binop_avx(Assembler::emit_vpavgw),
+            Operator::I16x8ExtMulLowI8x16S => {
+                self.emit_simd_extmul(false, Assembler::emit_vpmovsxbw, Assembler::emit_vpmullw)
+            }
+            Operator::I16x8ExtMulHighI8x16S => {
+                self.emit_simd_extmul(true, Assembler::emit_vpmovsxbw, Assembler::emit_vpmullw)
+            }
+            Operator::I16x8ExtMulLowI8x16U => {
+                self.emit_simd_extmul(false, Assembler::emit_vpmovzxbw, Assembler::emit_vpmullw)
+            }
+            Operator::I16x8ExtMulHighI8x16U => {
+                self.emit_simd_extmul(true, Assembler::emit_vpmovzxbw, Assembler::emit_vpmullw)
+            }
+            Operator::I32x4ExtAddPairwiseI16x8S => self.emit_simd_unop(|this, a, out| {
+                this.emit_v128_const(splat(1, 16), out);
+                this.assembler.emit_vpmaddwd(a, XMMOrMemory::XMM(out), out);
+            }),
+            Operator::I32x4ExtAddPairwiseI16x8U => self.emit_simd_unop(|this, a, out| {
+                // Flip the sign bits to make the lanes signed, offset by -0x8000 each, then make
+                // up for the offsets in the sums.
+                let tmp = this.machine.acquire_temp_xmm().unwrap();
+                this.emit_v128_const(splat(0x8000, 16), tmp);
+                this.assembler.emit_vpxor(a, XMMOrMemory::XMM(tmp), a);
+                this.emit_v128_const(splat(1, 16), tmp);
+                this.assembler.emit_vpmaddwd(a, XMMOrMemory::XMM(tmp), out);
+                this.emit_v128_const(splat(0x1_0000, 32), tmp);
+                this.assembler.emit_vpaddd(out, XMMOrMemory::XMM(tmp), out);
+                this.machine.release_temp_xmm(tmp);
+            }),
+            Operator::I32x4Abs => self.emit_simd_unop_avx(Assembler::emit_vpabsd),
+            Operator::I32x4Neg => self.emit_simd_neg(Assembler::emit_vpsubd),
+            Operator::I32x4AllTrue => self.emit_simd_true(32, true)?,
+            Operator::I32x4Bitmask => self.emit_simd_bitmask(32),
+            Operator::I32x4ExtendLowI16x8S => {
+                self.emit_simd_extend(false, Assembler::emit_vpmovsxwd)
+            }
+            Operator::I32x4ExtendHighI16x8S => {
+                self.emit_simd_extend(true, Assembler::emit_vpmovsxwd)
+            }
+            Operator::I32x4ExtendLowI16x8U => {
+                self.emit_simd_extend(false, Assembler::emit_vpmovzxwd)
+            }
+            Operator::I32x4ExtendHighI16x8U => {
+                self.emit_simd_extend(true, Assembler::emit_vpmovzxwd)
+            }
+            Operator::I32x4Shl => self.emit_simd_shift_avx(32, Assembler::emit_vpslld),
+            Operator::I32x4ShrS => self.emit_simd_shift_avx(32, Assembler::emit_vpsrad),
+            Operator::I32x4ShrU => self.emit_simd_shift_avx(32, Assembler::emit_vpsrld),
+            Operator::I32x4Add => self.emit_simd_binop_avx(Assembler::emit_vpaddd),
+            Operator::I32x4Sub => self.emit_simd_binop_avx(Assembler::emit_vpsubd),
+            Operator::I32x4Mul => self.emit_simd_binop_avx(Assembler::emit_vpmulld),
+            Operator::I32x4MinS => self.emit_simd_binop_avx(Assembler::emit_vpminsd),
+            Operator::I32x4MinU => self.emit_simd_binop_avx(Assembler::emit_vpminud),
+            Operator::I32x4MaxS => self.emit_simd_binop_avx(Assembler::emit_vpmaxsd),
+            Operator::I32x4MaxU => self.emit_simd_binop_avx(Assembler::emit_vpmaxud),
+            Operator::I32x4DotI16x8S => self.emit_simd_binop_avx(Assembler::emit_vpmaddwd),
+            Operator::I32x4ExtMulLowI16x8S => {
+                self.emit_simd_extmul(false, Assembler::emit_vpmovsxwd, Assembler::emit_vpmulld)
+            }
+            Operator::I32x4ExtMulHighI16x8S => {
+                self.emit_simd_extmul(true, Assembler::emit_vpmovsxwd, Assembler::emit_vpmulld)
+            }
+            Operator::I32x4ExtMulLowI16x8U => {
+                self.emit_simd_extmul(false, Assembler::emit_vpmovzxwd, Assembler::emit_vpmulld)
+            }
+            Operator::I32x4ExtMulHighI16x8U => {
+                self.emit_simd_extmul(true, Assembler::emit_vpmovzxwd, Assembler::emit_vpmulld)
+            }
+            Operator::I64x2Abs => self.emit_simd_unop(|this, a, out| {
+                // Flip the bits of negative lanes and add one, by subtracting their all-ones sign.
+                let sign = this.machine.acquire_temp_xmm().unwrap();
+                this.assembler.emit_vpxor(sign, XMMOrMemory::XMM(sign), sign);
+                this.assembler.emit_vpcmpgtq(sign, XMMOrMemory::XMM(a), sign);
+                this.assembler.emit_vpxor(a, XMMOrMemory::XMM(sign), out);
+                this.assembler.emit_vpsubq(out, XMMOrMemory::XMM(sign), out);
+                this.machine.release_temp_xmm(sign);
+            }),
+            Operator::I64x2Neg => self.emit_simd_neg(Assembler::emit_vpsubq),
+            Operator::I64x2AllTrue => self.emit_simd_true(64, true)?,
+            Operator::I64x2Bitmask => self.emit_simd_bitmask(64),
+            Operator::I64x2ExtendLowI32x4S => {
+                self.emit_simd_extend(false, Assembler::emit_vpmovsxdq)
+            }
+            Operator::I64x2ExtendHighI32x4S => {
+                self.emit_simd_extend(true, Assembler::emit_vpmovsxdq)
+            }
+            Operator::I64x2ExtendLowI32x4U => {
+                self.emit_simd_extend(false, Assembler::emit_vpmovzxdq)
+            }
+            Operator::I64x2ExtendHighI32x4U => {
+                self.emit_simd_extend(true, Assembler::emit_vpmovzxdq)
+            }
+            Operator::I64x2Shl => self.emit_simd_shift_avx(64, Assembler::emit_vpsllq),
+            Operator::I64x2ShrS => self.emit_simd_sar_i64x2(),
+            Operator::I64x2ShrU => self.emit_simd_shift_avx(64, Assembler::emit_vpsrlq),
+            Operator::I64x2Add => self.emit_simd_binop_avx(Assembler::emit_vpaddq),
+            Operator::I64x2Sub => self.emit_simd_binop_avx(Assembler::emit_vpsubq),
+            Operator::I64x2Mul => self.emit_simd_mul_i64x2(),
+            Operator::I64x2ExtMulLowI32x4S => {
+                self.emit_simd_extmul(false, Assembler::emit_vpmovsxdq, Assembler::emit_vpmuldq)
+            }
+            Operator::I64x2ExtMulHighI32x4S => {
+                self.emit_simd_extmul(true, Assembler::emit_vpmovsxdq, Assembler::emit_vpmuldq)
+            }
+            Operator::I64x2ExtMulLowI32x4U => {
+                self.emit_simd_extmul(false, Assembler::emit_vpmovzxdq, Assembler::emit_vpmuludq)
+            }
+            Operator::I64x2ExtMulHighI32x4U => {
+                self.emit_simd_extmul(true, Assembler::emit_vpmovzxdq, Assembler::emit_vpmuludq)
+            }
+            Operator::F32x4Ceil => {
+                self.emit_simd_fp_unop_avx(Size::S32, Assembler::emit_vroundps_ceil)
+            }
+            Operator::F32x4Floor => {
+                self.emit_simd_fp_unop_avx(Size::S32, Assembler::emit_vroundps_floor)
+            }
+            Operator::F32x4Trunc => {
+                self.emit_simd_fp_unop_avx(Size::S32, Assembler::emit_vroundps_trunc)
+            }
+            Operator::F32x4Nearest => {
+                self.emit_simd_fp_unop_avx(Size::S32, Assembler::emit_vroundps_uncest)
+            }
+            Operator::F32x4Abs => {
+                self.emit_simd_bitwise_const(Assembler::emit_vpand, splat(0x7FFF_FFFF, 32))
+            }
+            Operator::F32x4Neg => {
+                self.emit_simd_bitwise_const(Assembler::emit_vpxor, splat(0x8000_0000, 32))
+            }
+            Operator::F32x4Sqrt => self.emit_simd_fp_unop_avx(Size::S32, Assembler::emit_vsqrtps),
+            Operator::F32x4Add => self.emit_simd_fp_binop_avx(Size::S32, Assembler::emit_vaddps),
+            Operator::F32x4Sub => self.emit_simd_fp_binop_avx(Size::S32, Assembler::emit_vsubps),
+            Operator::F32x4Mul => self.emit_simd_fp_binop_avx(Size::S32, Assembler::emit_vmulps),
+            Operator::F32x4Div => self.emit_simd_fp_binop_avx(Size::S32, Assembler::emit_vdivps),
+            Operator::F32x4Min => self.emit_simd_fp_min_max(Size::S32, false),
+            Operator::F32x4Max => self.emit_simd_fp_min_max(Size::S32, true),
+            // `minps` returns its second operand unless the first is less, as `pmin` does with its
+            // operands swapped. Likewise for `maxps` and `pmax`.
+            Operator::F32x4PMin => self.emit_simd_binop_avx_swapped(Assembler::emit_vminps),
+            Operator::F32x4PMax => self.emit_simd_binop_avx_swapped(Assembler::emit_vmaxps),
+            Operator::F64x2Ceil => {
+                self.emit_simd_fp_unop_avx(Size::S64, Assembler::emit_vroundpd_ceil)
+            }
+            Operator::F64x2Floor => {
+                self.emit_simd_fp_unop_avx(Size::S64, Assembler::emit_vroundpd_floor)
+            }
+            Operator::F64x2Trunc => {
+                self.emit_simd_fp_unop_avx(Size::S64, Assembler::emit_vroundpd_trunc)
+            }
+            Operator::F64x2Nearest => {
+                self.emit_simd_fp_unop_avx(Size::S64, Assembler::emit_vroundpd_uncest)
+            }
+            Operator::F64x2Abs => self
+                .emit_simd_bitwise_const(Assembler::emit_vpand, splat(0x7FFF_FFFF_FFFF_FFFF, 64)),
+            Operator::F64x2Neg => self
+                .emit_simd_bitwise_const(Assembler::emit_vpxor, splat(0x8000_0000_0000_0000, 64)),
+            Operator::F64x2Sqrt => self.emit_simd_fp_unop_avx(Size::S64, Assembler::emit_vsqrtpd),
+            Operator::F64x2Add => self.emit_simd_fp_binop_avx(Size::S64, Assembler::emit_vaddpd),
+            Operator::F64x2Sub => self.emit_simd_fp_binop_avx(Size::S64, Assembler::emit_vsubpd),
+            Operator::F64x2Mul => self.emit_simd_fp_binop_avx(Size::S64, Assembler::emit_vmulpd),
+            Operator::F64x2Div => self.emit_simd_fp_binop_avx(Size::S64, Assembler::emit_vdivpd),
+            Operator::F64x2Min => self.emit_simd_fp_min_max(Size::S64, false),
+            Operator::F64x2Max => self.emit_simd_fp_min_max(Size::S64, true),
+            Operator::F64x2PMin => self.emit_simd_binop_avx_swapped(Assembler::emit_vminpd),
+            Operator::F64x2PMax => self.emit_simd_binop_avx_swapped(Assembler::emit_vmaxpd),
+            Operator::I32x4TruncSatF32x4S => self.emit_simd_unop(|this, a, out| {
+                let tmp = this.machine.acquire_temp_xmm().unwrap();
+                // Zero the NaNs.
+                this.assembler.emit_vcmpeqps(a, XMMOrMemory::XMM(a), tmp);
+                this.assembler.emit_vpand(a, XMMOrMemory::XMM(tmp), a);
+                // Out of range lanes convert to 0x8000_0000, which is right for the negative ones
+                // only, so flip the bits of the positive ones.
+                this.emit_v128_const(splat(0x4F00_0000, 32), tmp); // 2^31
+                this.assembler.emit_vcmpleps(tmp, XMMOrMemory::XMM(a), tmp);
+                this.assembler.emit_vcvttps2dq(a, out);
+                this.assembler.emit_vpxor(out, XMMOrMemory::XMM(tmp), out);
+                this.machine.release_temp_xmm(tmp);
+            }),
+            Operator::I32x4TruncSatF32x4U => self.emit_simd_unop(|this, a, out| {
+                let zero = this.machine.acquire_temp_xmm().unwrap();
+                let tmp = this.machine.acquire_temp_xmm().unwrap();
+                // Zero the NaNs and negative lanes, as `maxps` returns its second operand if
+                // either is a NaN.
+                this.assembler.emit_vpxor(zero, XMMOrMemory::XMM(zero), zero);
+                this.assembler.emit_vmaxps(a, XMMOrMemory::XMM(zero), a);
+                // Lanes below 2^31 convert as they are, the others to 0x8000_0000.
+                this.assembler.emit_vcvttps2dq(a, out);
+                // The others get what is left above 2^31 converted, saturating at 0x7FFF_FFFF,
+                // and added in. Lanes below 2^31 have it clamped to zero.
+                this.emit_v128_const(splat(0x4F00_0000, 32), tmp); // 2^31
+                this.assembler.emit_vsubps(a, XMMOrMemory::XMM(tmp), a);
+                this.assembler.emit_vcmpleps(tmp, XMMOrMemory::XMM(a), tmp);
+                this.assembler.emit_vcvttps2dq(a, a);
+                this.assembler.emit_vpxor(a, XMMOrMemory::XMM(tmp), a);
+                this.assembler.emit_vpmaxsd(a, XMMOrMemory::XMM(zero), a);
+                this.assembler.emit_vpaddd(out, XMMOrMemory::XMM(a), out);
+                this.machine.release_temp_xmm(tmp);
+                this.machine.release_temp_xmm(zero);
+            }),
+            Operator::F32x4ConvertI32x4S => self.emit_simd_unop_avx(Assembler::emit_vcvtdq2ps),
+            Operator::F32x4ConvertI32x4U => self.emit_simd_unop(|this, a, out| {
+                // Convert the low and high 16 bits of the lanes separately, which is exact, and
+                // sum them up, rounding only once.
+                let tmp = this.machine.acquire_temp_xmm().unwrap();
+                this.emit_v128_const(splat(0xFFFF, 32), tmp);
+                this.assembler.emit_vpand(a, XMMOrMemory::XMM(tmp), out);
+                this.assembler.emit_vpsrld_imm(a, 16, a);
+                this.assembler.emit_vcvtdq2ps(out, out);
+                this.assembler.emit_vcvtdq2ps(a, a);
+                this.emit_v128_const(splat(0x4780_0000, 32), tmp); // 2^16
+                this.assembler.emit_vmulps(a, XMMOrMemory::XMM(tmp), a);
+                this.assembler.emit_vaddps(a, XMMOrMemory::XMM(out), out);
+                this.machine.release_temp_xmm(tmp);
+            }),
+            Operator::I32x4TruncSatF64x2SZero => self.emit_simd_unop(|this, a, out| {
+                let tmp = this.machine.acquire_temp_xmm().unwrap();
+                // Zero the NaNs and clamp the lanes to the largest `i32`. Those below the smallest
+                // one convert to it.
+                this.assembler.emit_vcmpeqpd(a, XMMOrMemory::XMM(a), tmp);
+                this.assembler.emit_vpand(a, XMMOrMemory::XMM(tmp), a);
+                this.emit_v128_const(splat(0x41DF_FFFF_FFC0_0000, 64), tmp); // 2^31 - 1
+                this.assembler.emit_vminpd(a, XMMOrMemory::XMM(tmp), a);
+                this.assembler.emit_vcvttpd2dq(a, out);
+                this.machine.release_temp_xmm(tmp);
+            }),
+            Operator::I32x4TruncSatF64x2UZero => self.emit_simd_unop(|this, a, out| {
+                let zero = this.machine.acquire_temp_xmm().unwrap();
+                let tmp = this.machine.acquire_temp_xmm().unwrap();
+                // Zero the NaNs and negative lanes, as `maxpd` returns its second operand if
+                // either is a NaN, and clamp the lanes to the largest `u32`.
+                this.assembler.emit_vpxor(zero, XMMOrMemory::XMM(zero), zero);
+                this.assembler.emit_vmaxpd(a, XMMOrMemory::XMM(zero), a);
+                this.emit_v128_const(splat(0x41EF_FFFF_FFE0_0000, 64), tmp); // 2^32 - 1
+                this.assembler.emit_vminpd(a, XMMOrMemory::XMM(tmp), a);
+                // Adding 2^52 to the truncated lanes leaves them in the low 32 bits.
+                this.assembler.emit_vroundpd_trunc(a, a);
+                this.emit_v128_const(splat(0x4330_0000_0000_0000, 64), tmp); // 2^52
+                this.assembler.emit_vaddpd(a, XMMOrMemory::XMM(tmp), a);
+                this.assembler.emit_vshufps(a, zero, 0b00_00_10_00, out);
+                this.machine.release_temp_xmm(tmp);
+                this.machine.release_temp_xmm(zero);
+            }),
+            Operator::F64x2ConvertLowI32x4S => self.emit_simd_unop_avx(Assembler::emit_vcvtdq2pd),
+            Operator::F64x2ConvertLowI32x4U => self.emit_simd_unop(|this, a, out| {
+                // Put the lanes in the low 32 bits of 2^52, then subtract 2^52 back out.
+                let tmp = this.machine.acquire_temp_xmm().unwrap();
+                this.emit_v128_const(splat(0x4330_0000, 32), tmp);
+                this.assembler.emit_vunpcklps(a, XMMOrMemory::XMM(tmp), out);
+                this.emit_v128_const(splat(0x4330_0000_0000_0000, 64), tmp); // 2^52
+                this.assembler.emit_vsubpd(out, XMMOrMemory::XMM(tmp), out);
+                this.machine.release_temp_xmm(tmp);
+            }),
+            Operator::F32x4DemoteF64x2Zero => {
+                self.emit_simd_fp_unop_avx(Size::S32, Assembler::emit_vcvtpd2ps)
+            }
+            Operator::F64x2PromoteLowF32x4 => {
+                self.emit_simd_fp_unop_avx(Size::S64, Assembler::emit_vcvtps2pd)
+            }
             _ => {
                 return Err(CodegenError { message: format!("not yet implemented: {:?}", op) });
             }
@@ -7720,6 +9309,53 @@ fn type_to_wp_type(ty: Type) -> WpType {
     }
 }
 
+/// The `v128` with all its lanes of `bits` bits set to `lane`.
+fn splat(lane: u64, bits: u32) -> u128 {
+    u128::MAX / (u128::MAX >> (128 - bits)) * u128::from(lane)
+}
+
+/// The location of one half of a `v128`, as an operand for instructions inserting into or
+/// extracting from an XMM register.
+fn to_gpr_or_memory(loc: Location) -> GPROrMemory {
+    match loc {
+        Location::GPR(x) => GPROrMemory::GPR(x),
+        Location::Memory(base, disp) => GPROrMemory::Memory(base, disp),
+        _ => unreachable!("v128 half at {:?}", loc),
+    }
+}
+
+/// Number of 64-bit machine values taken up by values of the given types.
+///
+/// A `v128` takes up two of them, its low half first.
+fn machine_value_count(types: &[Type]) -> usize {
+    types.iter().map(|&ty| if ty == Type::V128 { 2 } else { 1 }).sum()
+}
+
+/// Types of the 64-bit machine values taken up by values of the given types.
+///
+/// Both halves of a `v128` are typed `V128`.
+fn machine_types<A: smallvec::Array<Item = WpType>>(types: &[Type]) -> SmallVec<A> {
+    types
+        .iter()
+        .flat_map(|&ty| {
+            iter::repeat(type_to_wp_type(ty)).take(if ty == Type::V128 { 2 } else { 1 })
+        })
+        .collect()
+}
+
+/// Offsets in the `args_rets` buffer of a trampoline of the 64-bit machine values taken up by
+/// values of the given types.
+fn args_rets_offsets(types: &[Type]) -> SmallVec<[i32; 8]> {
+    types
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &ty)| {
+            let halves = if ty == Type::V128 { 2 } else { 1 };
+            (0..halves).map(move |half| (i * 16 + half * 8) as i32)
+        })
+        .collect()
+}
+
 // FIXME: This implementation seems to be not enough to resolve all kinds of register dependencies
 // at call place.
 fn sort_call_movs(movs: &mut [(Location, GPR)]) {
@@ -7776,9 +9412,12 @@ pub(crate) fn gen_std_trampoline(
     calling_convention: CallingConvention,
     a: &mut Assembler,
 ) -> FunctionBody {
+    let param_offsets = args_rets_offsets(sig.params());
+    let result_offsets = args_rets_offsets(sig.results());
+
     // Calculate stack offset.
     let mut stack_offset: u32 = 0;
-    for (i, _param) in sig.params().iter().enumerate() {
+    for i in 0..param_offsets.len() {
         if let Location::Memory(_, _) = Machine::get_param_location(1 + i, calling_convention) {
             stack_offset += 8;
         }
@@ -7793,6 +9432,11 @@ pub(crate) fn gen_std_trampoline(
         stack_offset += 8;
     }
 
+    // Area the callee writes all but its first return value to, placed above the arguments.
+    let return_area_offset = stack_offset + stack_padding;
+    let return_area_size = (result_offsets.len().saturating_sub(1) as u32 * 8 + 15) / 16 * 16;
+    stack_offset += return_area_size;
+
     // Used callee-saved registers
     a.emit_push(Size::S64, Location::GPR(GPR::R15));
     a.emit_push(Size::S64, Location::GPR(GPR::R14));
@@ -7816,8 +9460,8 @@ pub(crate) fn gen_std_trampoline(
     // `callee_vmctx` is already in the first argument register, so no need to move.
     {
         let mut n_stack_args: usize = 0;
-        for (i, _param) in sig.params().iter().enumerate() {
-            let src_loc = Location::Memory(GPR::R14, (i * 16) as _); // args_rets[i]
+        for (i, &offset) in param_offsets.iter().enumerate() {
+            let src_loc = Location::Memory(GPR::R14, offset); // args_rets[i]
             let dst_loc = Machine::get_param_location(1 + i, calling_convention);
 
             match dst_loc {
@@ -7843,16 +9487,32 @@ pub(crate) fn gen_std_trampoline(
         }
     }
 
+    if return_area_size > 0 {
+        a.emit_lea(
+            Size::S64,
+            Location::Memory(GPR::RSP, return_area_offset as i32),
+            Location::GPR(GPR::R11),
+        );
+    }
+
     // Call.
     a.emit_call_location(Location::GPR(GPR::R15));
 
-    // Restore stack.
-    a.emit_add(Size::S64, Location::Imm32(stack_offset + stack_padding), Location::GPR(GPR::RSP));
-
-    // Write return value.
-    if !sig.results().is_empty() {
+    // Write return values.
+    if !result_offsets.is_empty() {
         a.emit_mov(Size::S64, Location::GPR(GPR::RAX), Location::Memory(GPR::R14, 0));
     }
+    for (i, &offset) in result_offsets.iter().enumerate().skip(1) {
+        a.emit_mov(
+            Size::S64,
+            Location::Memory(GPR::RSP, (return_area_offset as usize + (i - 1) * 8) as i32),
+            Location::GPR(GPR::RAX),
+        );
+        a.emit_mov(Size::S64, Location::GPR(GPR::RAX), Location::Memory(GPR::R14, offset));
+    }
+
+    // Restore stack.
+    a.emit_add(Size::S64, Location::Imm32(stack_offset + stack_padding), Location::GPR(GPR::RSP));
 
     // Restore callee-saved registers.
     a.emit_pop(Size::S64, Location::GPR(GPR::R14));
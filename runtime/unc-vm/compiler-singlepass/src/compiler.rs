@@ -16,7 +16,7 @@ use unc_vm_compiler::{
 };
 use unc_vm_types::entity::{EntityRef, PrimaryMap};
 use unc_vm_types::{
-    FunctionIndex, FunctionType, LocalFunctionIndex, MemoryIndex, ModuleInfo, TableIndex,
+    FunctionIndex, FunctionType, LocalFunctionIndex, MemoryIndex, ModuleInfo, TableIndex, Type,
 };
 use unc_vm_vm::{TrapCode, VMOffsets};
 
@@ -61,8 +61,17 @@ impl Compiler for SinglepassCompiler {
         if !target.cpu_features().contains(CpuFeature::AVX) {
             return Err(CompileError::UnsupportedTarget("x86_64 without AVX".to_string()));
         }
-        if compile_info.features.multi_value {
-            return Err(CompileError::UnsupportedFeature("multivalue".to_string()));
+        // Host functions only ever return a single value in `RAX`.
+        if compile_info.module.imported_function_types().any(|ty| ty.results().len() > 1) {
+            return Err(CompileError::UnsupportedFeature("multi-value imports".to_string()));
+        }
+        // Nor do they take or return `v128` values, which are split into two 64-bit halves.
+        if compile_info
+            .module
+            .imported_function_types()
+            .any(|ty| ty.params().iter().chain(ty.results()).any(|&ty| ty == Type::V128))
+        {
+            return Err(CompileError::UnsupportedFeature("v128 imports".to_string()));
         }
         let calling_convention = match target.triple().default_calling_convention() {
             Ok(CallingConvention::WindowsFastcall) => CallingConvention::WindowsFastcall,
@@ -149,6 +158,12 @@ impl Compiler for SinglepassCompiler {
                         generator.feed_local(count, ty);
                     }
 
+                    if compile_info.features.multi_value || compile_info.features.simd {
+                        let mut operator_reader = reader.get_operators_reader()?;
+                        while !operator_reader.eof() {
+                            generator.reserve_scratch_slots(&operator_reader.read()?);
+                        }
+                    }
                     generator.emit_head().map_err(to_compile_error)?;
 
                     let mut operator_reader =
@@ -25,6 +25,11 @@ pub struct Singlepass {
     pub(crate) enable_nan_canonicalization: bool,
     pub(crate) enable_stack_check: bool,
     pub(crate) disable_9393_fix: bool,
+    /// Gas charged per byte processed by `memory.copy`, `memory.fill` and `memory.init`.
+    pub(crate) bulk_memory_byte_cost: u32,
+    /// Gas charged per element processed by `table.copy`, `table.fill`, `table.init` and
+    /// `table.grow`.
+    pub(crate) bulk_table_element_cost: u32,
     /// Compiler intrinsics.
     pub(crate) intrinsics: Vec<Intrinsic>,
 }
@@ -37,6 +42,8 @@ impl Singlepass {
             enable_nan_canonicalization: true,
             enable_stack_check: false,
             disable_9393_fix: false,
+            bulk_memory_byte_cost: 0,
+            bulk_table_element_cost: 0,
             intrinsics: vec![Intrinsic {
                 kind: IntrinsicKind::Gas,
                 name: "gas".to_string(),
@@ -65,6 +72,18 @@ impl Singlepass {
         self.disable_9393_fix = !enable;
     }
 
+    /// Set the gas charged for every byte processed by `memory.copy`, `memory.fill` and
+    /// `memory.init`, on top of the regular cost of the instruction itself.
+    pub fn set_bulk_memory_byte_cost(&mut self, cost: u32) {
+        self.bulk_memory_byte_cost = cost;
+    }
+
+    /// Set the gas charged for every element processed by `table.copy`, `table.fill`,
+    /// `table.init` and `table.grow`, on top of the regular cost of the instruction itself.
+    pub fn set_bulk_table_element_cost(&mut self, cost: u32) {
+        self.bulk_table_element_cost = cost;
+    }
+
     pub fn canonicalize_nans(&mut self, enable: bool) -> &mut Self {
         self.enable_nan_canonicalization = enable;
         self
@@ -84,9 +103,7 @@ impl CompilerConfig for Singlepass {
 
     /// Gets the default features for this compiler in the given target
     fn default_features_for_target(&self, _target: &Target) -> Features {
-        let mut features = Features::default();
-        features.multi_value(false);
-        features
+        Features::default()
     }
 }
 
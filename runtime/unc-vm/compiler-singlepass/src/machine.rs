@@ -15,6 +15,10 @@ pub(crate) struct Machine {
     used_xmms: u32, // Bitset for the used XMMs, 1 means used
     stack_offset: MachineStackOffset,
     save_area_offset: Option<MachineStackOffset>,
+    /// Memory location at which the scratch area used to pass multiple values begins.
+    ///
+    /// Populated in `setup_registers`.
+    scratch_offset: MachineStackOffset,
     /// Memory location at which local variables begin.
     ///
     /// Populated in `init_locals`.
@@ -42,6 +46,7 @@ impl Machine {
             used_xmms: 0,
             stack_offset: MachineStackOffset(0),
             save_area_offset: None,
+            scratch_offset: MachineStackOffset(0),
             locals_offset: MachineStackOffset(0),
         }
     }
@@ -162,10 +167,14 @@ impl Machine {
 
     /// Picks an unused XMM register for internal temporary use.
     ///
+    /// `XMM8` to `XMM15` are never used for values, and only handed out once the first three
+    /// are taken, which only the lowering of SIMD instructions ever needs.
+    ///
     /// This method does not mark the register as used.
     pub(crate) fn pick_temp_xmm(&self) -> Option<XMM> {
         use XMM::*;
-        const REGS: u32 = bitset_of_regs!(XMM0, XMM1, XMM2);
+        const REGS: u32 =
+            bitset_of_regs!(XMM0, XMM1, XMM2, XMM8, XMM9, XMM10, XMM11, XMM12, XMM13, XMM14, XMM15);
         Self::pick_one_in(!self.used_xmms & REGS).map(|r| XMM::from_repr(r).unwrap())
     }
 
@@ -211,8 +220,9 @@ impl Machine {
             let loc = match *ty {
                 WpType::F32 | WpType::F64 => self.pick_xmm().map(Location::XMM),
                 WpType::I32 | WpType::I64 => self.pick_gpr().map(Location::GPR),
+                // A `v128` is made of two consecutive values, each holding one half like an `i64`.
+                WpType::V128 => self.pick_gpr().map(Location::GPR),
                 WpType::FuncRef | WpType::ExternRef => self.pick_gpr().map(Location::GPR),
-                _ => unreachable!("can't acquire location for type {:?}", ty),
             };
 
             let loc = if let Some(x) = loc {
@@ -362,6 +372,14 @@ impl Machine {
         })
     }
 
+    /// Location of the `idx`-th slot of the scratch area reserved by `setup_registers`.
+    ///
+    /// Slots are laid out at increasing addresses, so that the area can also be handed out to
+    /// callees as an array to write their extra return values into.
+    pub(crate) fn get_scratch_location(&self, idx: usize) -> Location {
+        Location::Memory(GPR::RBP, -((self.scratch_offset.0 - idx * 8) as i32))
+    }
+
     // `setup_registers`, `init_locals`, `finalize_locals` and `restore_registers` work together,
     // the first two making up the function prologue (with a stack check and gas charge in-between),
     // and the second two making up the function epilogue (with the stack height reset in-between).
@@ -376,6 +394,9 @@ impl Machine {
     // |     Saved R15     |   8B   |
     // |  (Win FastC) RDI  |   8B   |
     // |  (Win FastC) RSI  |   8B   |  <-  save_area_offset
+    // |     Scratch s     |   8B   |
+    // |        ...        |        |
+    // |     Scratch 0     |   8B   |  <-  scratch_offset
     // |      Local 0      |   8B   |  <-  locals_offset
     // |        ...        |        |
     // |      Local n      |   8B   |  <-  RSP, stack_offset (at end of init_locals, will keep moving during fn codegen)
@@ -385,6 +406,7 @@ impl Machine {
         a: &mut E,
         n: u32,
         n_params: u32,
+        scratch_slots: usize,
         calling_convention: CallingConvention,
     ) {
         // Total size (in bytes) of the pre-allocated "static area" for this function's
@@ -427,6 +449,13 @@ impl Machine {
         // Save the offset of register save area.
         self.save_area_offset = Some(MachineStackOffset(self.stack_offset.0));
 
+        // Reserve the scratch area. It is always written before being read, so it is not
+        // initialized.
+        if scratch_slots > 0 {
+            self.decrease_rsp(a, scratch_slots.checked_mul(8).unwrap());
+        }
+        self.scratch_offset = MachineStackOffset(self.stack_offset.0);
+
         // Load in-register parameters into the allocated locations for register parameters.
         // Locals are allocated on the stack from higher address to lower address,
         // so we won't skip the stack guard page here.
@@ -6,8 +6,10 @@ use std::collections::{hash_map, HashMap};
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
 use std::path::Path;
+use unc_chain_configs::genesis_validate::RSA2048_ROOT_ACCOUNT;
 use unc_chain_configs::{Genesis, GenesisValidationMode};
 use unc_crypto::PublicKey;
+use unc_primitives::action::RegisterRsa2048KeysAction;
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::shard_layout::ShardLayout;
 use unc_primitives::state_record::StateRecord;
@@ -34,6 +36,12 @@ struct AccountRecords {
     // given there
     amount_needed: bool,
     keys: HashMap<PublicKey, AccessKey>,
+    rsa2048_keys: HashMap<PublicKey, RegisterRsa2048KeysAction>,
+    // the power of the rsa2048 keys in the input records that were replaced by keys from
+    // --extra-records, and the power of the keys that replaced them. The account's power is
+    // adjusted by the difference when it's written out
+    replaced_rsa2048_power: Power,
+    replacing_rsa2048_power: Power,
     // code state records must appear after the account state record. So for accounts we're
     // modifying/adding keys for, we will remember any code records (there really should only be one),
     // and add them to the output only after we write the account record
@@ -94,16 +102,31 @@ impl AccountRecords {
                 }
             }
             None => {
+                // The power is kept as is, since it comes from the rsa2048 keys claimed by the
+                // account, and those are carried over to the output.
                 let mut account = existing.clone();
                 account.set_amount(account.amount() + account.pledging());
                 account.set_pledging(0);
-                account.set_power(0);
                 self.account = Some(account);
             }
         }
         self.amount_needed = false;
     }
 
+    fn replace_rsa2048_keys(
+        &mut self,
+        public_key: &PublicKey,
+        existing: &mut RegisterRsa2048KeysAction,
+    ) {
+        if let Some(rsa_key) = self.rsa2048_keys.remove(public_key) {
+            self.replaced_rsa2048_power =
+                self.replaced_rsa2048_power.saturating_add(existing.power().unwrap_or_default());
+            self.replacing_rsa2048_power =
+                self.replacing_rsa2048_power.saturating_add(rsa_key.power().unwrap_or_default());
+            *existing = rsa_key;
+        }
+    }
+
     fn push_extra_record(&mut self, record: StateRecord) {
         self.extra_records.push(record);
     }
@@ -120,6 +143,14 @@ impl AccountRecords {
     {
         match self.account {
             Some(mut account) => {
+                // chips held by the root account are not claimed yet, so they give it no power
+                let claims_power = account_id.as_str() != RSA2048_ROOT_ACCOUNT;
+                let mut power = account.power();
+                if claims_power {
+                    power = power
+                        .saturating_add(self.replacing_rsa2048_power)
+                        .saturating_sub(self.replaced_rsa2048_power);
+                }
                 for (public_key, access_key) in self.keys {
                     let storage_usage = account.storage_usage()
                         + public_key.len() as u64
@@ -133,6 +164,22 @@ impl AccountRecords {
                         access_key,
                     })?;
                 }
+                for (public_key, rsa_key) in self.rsa2048_keys {
+                    let storage_usage = account.storage_usage()
+                        + borsh::object_length(&rsa_key).unwrap() as u64
+                        + num_extra_bytes_record;
+                    account.set_storage_usage(storage_usage);
+                    if claims_power {
+                        power = power.saturating_add(rsa_key.power().unwrap_or_default());
+                    }
+
+                    seq.serialize_element(&StateRecord::Rsa2048Keys {
+                        account_id: account_id.clone(),
+                        public_key,
+                        rsa_key,
+                    })?;
+                }
+                account.set_power(power);
                 if self.amount_needed {
                     account.set_amount(10_000 * framework::config::UNC_BASE);
                 }
@@ -224,9 +271,19 @@ fn parse_extra_records(
             StateRecord::AccessKey { account_id, public_key, access_key } => {
                 records.entry(account_id).or_default().keys.insert(public_key, access_key);
             }
+            StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                if rsa_key.power().is_none() {
+                    result = Err(anyhow::anyhow!(
+                        "rsa2048 key {} of account {} doesn't specify a valid power",
+                        &public_key,
+                        &account_id
+                    ));
+                }
+                records.entry(account_id).or_default().rsa2048_keys.insert(public_key, rsa_key);
+            }
             _ => {
                 result = Err(anyhow::anyhow!(
-                    "FIXME: only Account and AccessKey records are supported in --extra-records"
+                    "FIXME: only Account, AccessKey and Rsa2048Keys records are supported in --extra-records"
                 ));
             }
         };
@@ -256,6 +313,7 @@ fn wanted_records(
                         validator_records.amount_needed = false;
                     }
                     validator_records.keys.extend(account_records.keys);
+                    validator_records.rsa2048_keys.extend(account_records.rsa2048_keys);
                 }
                 hash_map::Entry::Vacant(e) => {
                     e.insert(account_records);
@@ -330,6 +388,12 @@ pub fn amend_genesis(
                 }
                 records_seq.serialize_element(&r).unwrap();
             }
+            StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                if let Some(a) = wanted.get_mut(account_id) {
+                    a.replace_rsa2048_keys(public_key, rsa_key);
+                }
+                records_seq.serialize_element(&r).unwrap();
+            }
             StateRecord::Account { account_id, account } => {
                 if let Some(acc) = wanted.get_mut(account_id) {
                     acc.update_from_existing(account);
@@ -421,6 +485,8 @@ mod test {
     use std::str::FromStr;
     use tempfile::NamedTempFile;
     use unc_chain_configs::{get_initial_supply, Genesis, GenesisConfig};
+    use unc_crypto::PublicKey;
+    use unc_primitives::action::RegisterRsa2048KeysAction;
     use unc_primitives::hash::CryptoHash;
     use unc_primitives::shard_layout::ShardLayout;
     use unc_primitives::state_record::StateRecord;
@@ -466,6 +532,11 @@ mod test {
         Contract {
             account_id: &'static str,
         },
+        Rsa2048Keys {
+            account_id: &'static str,
+            public_key: &'static str,
+            power: Power,
+        },
     }
 
     impl TestStateRecord {
@@ -475,7 +546,7 @@ mod test {
                     let account = Account::new(
                         *amount,
                         *pledging,
-                        power,
+                        *power,
                         CryptoHash::default(),
                         *storage_usage,
                     );
@@ -490,6 +561,19 @@ mod test {
                     account_id: account_id.parse().unwrap(),
                     code: vec![123],
                 },
+                Self::Rsa2048Keys { account_id, public_key, power } => {
+                    let public_key: PublicKey = public_key.parse().unwrap();
+                    let args = serde_json::json!({ "power": power.to_string() });
+                    StateRecord::Rsa2048Keys {
+                        account_id: account_id.parse().unwrap(),
+                        public_key: public_key.clone(),
+                        rsa_key: RegisterRsa2048KeysAction {
+                            public_key,
+                            operation_type: 0,
+                            args: serde_json::to_vec(&args).unwrap(),
+                        },
+                    }
+                }
             }
         }
     }
@@ -523,9 +607,11 @@ mod test {
         let mut got_accounts = HashMap::new();
         let mut got_keys = HashSet::new();
         let mut got_contracts = HashMap::<AccountId, usize>::new();
+        let mut got_rsa2048_keys = HashSet::new();
         let mut wanted_accounts = HashMap::new();
         let mut wanted_keys = HashSet::new();
         let mut wanted_contracts = HashMap::<AccountId, usize>::new();
+        let mut wanted_rsa2048_keys = HashSet::new();
 
         for r in got_records {
             match r {
@@ -536,6 +622,7 @@ mod test {
                             (
                                 account.amount(),
                                 account.pledging(),
+                                account.power(),
                                 account.code_hash(),
                                 account.storage_usage(),
                             ),
@@ -563,6 +650,19 @@ mod test {
                     }
                     *got_contracts.entry(account_id).or_default() += 1;
                 }
+                StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                    if !got_rsa2048_keys.insert((
+                        account_id.clone(),
+                        public_key.clone(),
+                        rsa_key.args,
+                    )) {
+                        anyhow::bail!(
+                            "two rsa2048 key records in the output for {}, {}",
+                            &account_id,
+                            &public_key
+                        );
+                    }
+                }
                 _ => anyhow::bail!("got an unexpected record in the output: {}", r),
             };
         }
@@ -574,6 +674,7 @@ mod test {
                         (
                             account.amount(),
                             account.pledging(),
+                            account.power(),
                             account.code_hash(),
                             account.storage_usage(),
                         ),
@@ -585,6 +686,9 @@ mod test {
                 StateRecord::Contract { account_id, .. } => {
                     *wanted_contracts.entry(account_id).or_default() += 1;
                 }
+                StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                    wanted_rsa2048_keys.insert((account_id, public_key, rsa_key.args));
+                }
                 _ => anyhow::bail!("got an unexpected record in the output: {}", r),
             };
         }
@@ -592,6 +696,7 @@ mod test {
         assert_eq!(got_accounts, wanted_accounts);
         assert_eq!(got_keys, wanted_keys);
         assert_eq!(got_contracts, wanted_contracts);
+        assert_eq!(got_rsa2048_keys, wanted_rsa2048_keys);
         Ok(())
     }
 
@@ -736,6 +841,7 @@ mod test {
                     account_id: "foo0",
                     amount: 1_000_000,
                     pledging: 1_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -746,6 +852,7 @@ mod test {
                     account_id: "foo1",
                     amount: 1_000_000,
                     pledging: 2_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -756,6 +863,7 @@ mod test {
                     account_id: "asdf.unc",
                     amount: 1_234_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -785,12 +893,14 @@ mod test {
                     account_id: "foo0",
                     amount: 100_000_000,
                     pledging: 50_000_000,
+                    power: 0,
                     storage_usage: 0,
                 },
                 TestStateRecord::Account {
                     account_id: "extra-account.unc",
                     amount: 9_000_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 0,
                 },
                 TestStateRecord::AccessKey {
@@ -803,6 +913,7 @@ mod test {
                     account_id: "foo0",
                     amount: 149_000_000,
                     pledging: 1_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -813,6 +924,7 @@ mod test {
                     account_id: "foo1",
                     amount: 1_000_000,
                     pledging: 2_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -823,6 +935,7 @@ mod test {
                     account_id: "foo2",
                     amount: 10_000 * framework::config::UNC_BASE,
                     pledging: 3_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -833,6 +946,7 @@ mod test {
                     account_id: "asdf.unc",
                     amount: 1_234_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -843,6 +957,7 @@ mod test {
                     account_id: "extra-account.unc",
                     amount: 9_000_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -882,6 +997,7 @@ mod test {
                     account_id: "foo0",
                     amount: 1_000_000,
                     pledging: 1_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -892,6 +1008,7 @@ mod test {
                     account_id: "foo1",
                     amount: 1_000_000,
                     pledging: 2_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -902,6 +1019,7 @@ mod test {
                     account_id: "asdf.unc",
                     amount: 1_234_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -914,12 +1032,14 @@ mod test {
                     account_id: "foo0",
                     amount: 100_000_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 0,
                 },
                 TestStateRecord::Account {
                     account_id: "foo2",
                     amount: 300_000_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 0,
                 },
                 TestStateRecord::AccessKey {
@@ -934,6 +1054,7 @@ mod test {
                     account_id: "extra-account.unc",
                     amount: 9_000_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 0,
                 },
                 TestStateRecord::AccessKey {
@@ -946,6 +1067,7 @@ mod test {
                     account_id: "foo0",
                     amount: 100_000_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 264,
                 },
                 TestStateRecord::AccessKey {
@@ -960,6 +1082,7 @@ mod test {
                     account_id: "foo1",
                     amount: 3_000_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 264,
                 },
                 TestStateRecord::AccessKey {
@@ -974,6 +1097,7 @@ mod test {
                     account_id: "foo2",
                     amount: 299_000_000,
                     pledging: 1_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -984,6 +1108,7 @@ mod test {
                     account_id: "foo3",
                     amount: 10_000 * framework::config::UNC_BASE,
                     pledging: 2_000_000,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -994,6 +1119,7 @@ mod test {
                     account_id: "asdf.unc",
                     amount: 1_234_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -1004,6 +1130,7 @@ mod test {
                     account_id: "extra-account.unc",
                     amount: 9_000_000,
                     pledging: 0,
+                    power: 0,
                     storage_usage: 182,
                 },
                 TestStateRecord::AccessKey {
@@ -1029,6 +1156,7 @@ mod test {
                     account_id: "foo0",
                     amount: 1_000_000,
                     pledging: 1_000_000,
+                    power: 0,
                     storage_usage: 183,
                 },
                 TestStateRecord::AccessKey {
@@ -1041,6 +1169,7 @@ mod test {
                 account_id: "foo0",
                 amount: 100_000_000,
                 pledging: 0,
+                power: 0,
                 storage_usage: 0,
             }],
             wanted_records: &[
@@ -1048,6 +1177,7 @@ mod test {
                     account_id: "foo0",
                     amount: 99_000_000,
                     pledging: 1_000_000,
+                    power: 0,
                     storage_usage: 183,
                 },
                 TestStateRecord::AccessKey {
@@ -1057,6 +1187,147 @@ mod test {
                 TestStateRecord::Contract { account_id: "foo0" },
             ],
         },
+        // this one replaces and adds rsa2048 keys, which changes the power of the accounts
+        // claiming them, but not of the root account
+        TestCase {
+            initial_validators: &[TestAccountInfo {
+                account_id: "foo0",
+                public_key: "ed25519:He7QeRuwizNEhBioYG3u4DZ8jWXyETiyNzFD3MkTjDMf",
+                amount: 1_000_000,
+            }],
+            validators_in: &[TestAccountInfo {
+                account_id: "foo0",
+                public_key: "ed25519:He7QeRuwizNEhBioYG3u4DZ8jWXyETiyNzFD3MkTjDMf",
+                amount: 1_000_000,
+            }],
+            records_in: &[
+                TestStateRecord::Account {
+                    account_id: "foo0",
+                    amount: 1_000_000,
+                    pledging: 1_000_000,
+                    power: 0,
+                    storage_usage: 182,
+                },
+                TestStateRecord::AccessKey {
+                    account_id: "foo0",
+                    public_key: "ed25519:He7QeRuwizNEhBioYG3u4DZ8jWXyETiyNzFD3MkTjDMf",
+                },
+                TestStateRecord::Account {
+                    account_id: "miner.unc",
+                    amount: 1_000,
+                    pledging: 0,
+                    power: 5,
+                    storage_usage: 182,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "miner.unc",
+                    public_key: "ed25519:FXXrTXiKWpXj1R6r5fBvMLpstd8gPyrBq3qMByqKVzKF",
+                    power: 5,
+                },
+                TestStateRecord::Account {
+                    account_id: "unc",
+                    amount: 1_000,
+                    pledging: 0,
+                    power: 0,
+                    storage_usage: 200,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "unc",
+                    public_key: "ed25519:5C66RSJgwK17Yb6VtTbgBCFHDRPzGUd6AAhFdXNvmJuo",
+                    power: 3,
+                },
+            ],
+            extra_records: &[
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "miner.unc",
+                    public_key: "ed25519:FXXrTXiKWpXj1R6r5fBvMLpstd8gPyrBq3qMByqKVzKF",
+                    power: 6,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "miner.unc",
+                    public_key: "ed25519:BhnQV3oJa8iSQDKDc8gy36TsenaMFmv7qHvcnutuXj33",
+                    power: 4,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "unc",
+                    public_key: "ed25519:Eo9W44tRMwcYcoua11yM7Xfr1DjgR4EWQFM3RU27MEX8",
+                    power: 7,
+                },
+                TestStateRecord::Account {
+                    account_id: "new-miner.unc",
+                    amount: 9_000_000,
+                    pledging: 0,
+                    power: 0,
+                    storage_usage: 0,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "new-miner.unc",
+                    public_key: "ed25519:546XB2oHhj7PzUKHiH9Xve3Ze5q1JiW2WTh6abXFED3c",
+                    power: 2,
+                },
+            ],
+            // each new key record takes 33 bytes of public key, 1 of operation type, 4 + 13 of
+            // args and 40 extra bytes
+            wanted_records: &[
+                TestStateRecord::Account {
+                    account_id: "foo0",
+                    amount: 1_000_000,
+                    pledging: 1_000_000,
+                    power: 0,
+                    storage_usage: 182,
+                },
+                TestStateRecord::AccessKey {
+                    account_id: "foo0",
+                    public_key: "ed25519:He7QeRuwizNEhBioYG3u4DZ8jWXyETiyNzFD3MkTjDMf",
+                },
+                TestStateRecord::Account {
+                    account_id: "miner.unc",
+                    amount: 1_000,
+                    pledging: 0,
+                    power: 10,
+                    storage_usage: 273,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "miner.unc",
+                    public_key: "ed25519:FXXrTXiKWpXj1R6r5fBvMLpstd8gPyrBq3qMByqKVzKF",
+                    power: 6,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "miner.unc",
+                    public_key: "ed25519:BhnQV3oJa8iSQDKDc8gy36TsenaMFmv7qHvcnutuXj33",
+                    power: 4,
+                },
+                TestStateRecord::Account {
+                    account_id: "unc",
+                    amount: 1_000,
+                    pledging: 0,
+                    power: 0,
+                    storage_usage: 291,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "unc",
+                    public_key: "ed25519:5C66RSJgwK17Yb6VtTbgBCFHDRPzGUd6AAhFdXNvmJuo",
+                    power: 3,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "unc",
+                    public_key: "ed25519:Eo9W44tRMwcYcoua11yM7Xfr1DjgR4EWQFM3RU27MEX8",
+                    power: 7,
+                },
+                TestStateRecord::Account {
+                    account_id: "new-miner.unc",
+                    amount: 9_000_000,
+                    pledging: 0,
+                    power: 2,
+                    storage_usage: 191,
+                },
+                TestStateRecord::Rsa2048Keys {
+                    account_id: "new-miner.unc",
+                    public_key: "ed25519:546XB2oHhj7PzUKHiH9Xve3Ze5q1JiW2WTh6abXFED3c",
+                    power: 2,
+                },
+            ],
+        },
     ];
 
     #[test]
@@ -8,6 +8,7 @@ use rand::seq::SliceRandom;
 use std::io::Result;
 use std::path::Path;
 use strum::IntoEnumIterator;
+use unc_chain_configs::RetainedAccounts;
 use unc_epoch_manager::{EpochManager, EpochManagerAdapter, EpochManagerHandle};
 use unc_primitives::block::Tip;
 use unc_primitives::epoch_manager::block_info::BlockInfo;
@@ -82,7 +83,12 @@ impl ColdStoreCommand {
                 Ok(())
             }
             SubCommand::CopyAllBlocks(cmd) => {
-                copy_all_blocks(&storage, cmd.batch_size, !cmd.no_check_after);
+                copy_all_blocks(
+                    &storage,
+                    cmd.batch_size,
+                    !cmd.no_check_after,
+                    unc_config.client_config.archive_retained_accounts.as_ref(),
+                );
                 Ok(())
             }
             SubCommand::PrepareHot(cmd) => cmd.run(&storage, &home_dir, &unc_config),
@@ -226,6 +232,7 @@ fn copy_next_block(store: &NodeStorage, config: &UncConfig, epoch_manager: &Epoc
             .get_shard_layout(&epoch_manager.get_epoch_id_from_prev_block(&cold_head_hash).unwrap())
             .unwrap(),
         &next_height,
+        config.client_config.archive_retained_accounts.as_ref(),
     )
     .unwrap_or_else(|_| panic!("Failed to copy block at height {} to cold db", next_height));
 
@@ -233,7 +240,12 @@ fn copy_next_block(store: &NodeStorage, config: &UncConfig, epoch_manager: &Epoc
         .unwrap_or_else(|_| panic!("Failed to update cold HEAD to {}", next_height));
 }
 
-fn copy_all_blocks(storage: &NodeStorage, batch_size: usize, check: bool) {
+fn copy_all_blocks(
+    storage: &NodeStorage,
+    batch_size: usize,
+    check: bool,
+    retained_accounts: Option<&RetainedAccounts>,
+) {
     // If FINAL_HEAD is not set for hot storage we default it to 0
     // not genesis_height, because hot db needs to contain genesis block for that
     let hot_final_head = storage
@@ -250,6 +262,7 @@ fn copy_all_blocks(storage: &NodeStorage, batch_size: usize, check: bool) {
         &storage.get_hot_store(),
         batch_size,
         &keep_going,
+        retained_accounts,
     )
     .expect("Failed to do migration to cold db");
 
@@ -7,6 +7,7 @@ use crate::make_snapshot::MakeSnapshotCommand;
 use crate::memtrie::LoadMemTrieCommand;
 use crate::run_migrations::RunMigrationsCommand;
 use crate::state_perf::StatePerfCommand;
+use crate::verify::VerifyCommand;
 use clap::Parser;
 use std::path::PathBuf;
 
@@ -46,6 +47,9 @@ enum SubCommand {
 
     /// Loads an in-memory trie for research purposes.
     LoadMemTrie(LoadMemTrieCommand),
+
+    /// Incrementally check the integrity of the database, resuming the previous run.
+    Verify(VerifyCommand),
 }
 
 impl DatabaseCommand {
@@ -74,6 +78,14 @@ impl DatabaseCommand {
                 .unwrap_or_else(|e| panic!("Error loading config: {:#}", e));
                 cmd.run(unc_config, home)
             }
+            SubCommand::Verify(cmd) => {
+                let unc_config = framework::config::load_config(
+                    &home,
+                    unc_chain_configs::GenesisValidationMode::UnsafeFast,
+                )
+                .unwrap_or_else(|e| panic!("Error loading config: {:#}", e));
+                cmd.run(unc_config, home)
+            }
         }
     }
 }
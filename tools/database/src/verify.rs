@@ -0,0 +1,750 @@
+//! Incremental integrity checks of the hot database.
+//!
+//! The checks are split into tasks, one per column or shard, which run in parallel. Every task
+//! walks its keys in order and regularly records the last checked key in a cursor file, so that an
+//! interrupted or limited run continues where it stopped. Anomalies are appended to a report with
+//! one JSON object per line.
+
+use crate::utils::open_rocksdb;
+use borsh::BorshDeserialize;
+use clap::ValueEnum;
+use framework::UncConfig;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use strum::IntoEnumIterator;
+use unc_epoch_manager::EpochManager;
+use unc_primitives::block::{Block, Tip};
+use unc_primitives::block_header::BlockHeader;
+use unc_primitives::hash::{hash, CryptoHash};
+use unc_primitives::shard_layout::get_block_shard_uid;
+use unc_primitives::sharding::ShardChunk;
+use unc_primitives::state::ValueRef;
+use unc_primitives::types::chunk_extra::ChunkExtra;
+use unc_primitives::types::StateRoot;
+use unc_store::db::refcount::decode_value_with_rc;
+use unc_store::flat::{store_helper, FlatStorageStatus};
+use unc_store::{DBCol, ShardUId, Store, Trie, TrieDBStorage, HEAD_KEY};
+
+/// Number of checked keys after which a task records its progress.
+const CHECKPOINT_INTERVAL: u64 = 10_000;
+
+/// Number of parts the trie check splits each trie into, by the first byte of the keys.
+const TRIE_PARTS: usize = 256;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Check {
+    /// Reference counts of the reference counted columns and hashes of the trie nodes.
+    Refcounts,
+    /// Consistency of the stored blocks, headers and chunks.
+    Blocks,
+    /// Reachability of all trie nodes and values from the state roots at the head.
+    Tries,
+    /// Agreement of flat storage with the tries at the flat storage head.
+    Flat,
+}
+
+/// Checks the integrity of the hot database, continuing the previous run if there is one.
+#[derive(clap::Parser)]
+pub(crate) struct VerifyCommand {
+    /// Comma separated checks to run, all of them if not given.
+    #[clap(long, value_enum, value_delimiter = ',')]
+    checks: Vec<Check>,
+
+    /// File recording the progress. Defaults to `verify-cursor.json` in the home directory.
+    #[clap(long)]
+    cursor: Option<PathBuf>,
+
+    /// File the anomalies are appended to. Defaults to `verify-report.jsonl` in the home
+    /// directory.
+    #[clap(long)]
+    report: Option<PathBuf>,
+
+    /// Maximum number of keys each task checks in this run.
+    #[clap(long)]
+    limit: Option<u64>,
+
+    /// Discard the cursor and the report of previous runs and start over.
+    #[clap(long)]
+    restart: bool,
+}
+
+/// Progress of all the tasks, as persisted in the cursor file.
+#[derive(serde::Serialize, serde::Deserialize, Default)]
+struct Cursor {
+    tasks: BTreeMap<String, TaskProgress>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Default, Clone)]
+struct TaskProgress {
+    /// Hex encoded last checked key, `None` if nothing was checked yet.
+    last_key: Option<String>,
+    /// Root of the trie checked by the trie and flat storage tasks. The tasks start over once the
+    /// root they check changes.
+    state_root: Option<StateRoot>,
+    checked: u64,
+    anomalies: u64,
+    done: bool,
+}
+
+#[derive(serde::Serialize)]
+struct Anomaly<'a> {
+    task: &'a str,
+    key: String,
+    message: String,
+}
+
+enum Task {
+    Refcounts(DBCol),
+    Blocks,
+    Headers,
+    BlockHeights,
+    Chunks,
+    Trie(ShardUId, StateRoot),
+    Flat(ShardUId, StateRoot),
+}
+
+impl Task {
+    fn name(&self) -> String {
+        match self {
+            Task::Refcounts(col) => format!("refcounts:{col}"),
+            Task::Blocks => "blocks".to_string(),
+            Task::Headers => "headers".to_string(),
+            Task::BlockHeights => "block_heights".to_string(),
+            Task::Chunks => "chunks".to_string(),
+            Task::Trie(shard_uid, _) => format!("trie:{shard_uid}"),
+            Task::Flat(shard_uid, _) => format!("flat:{shard_uid}"),
+        }
+    }
+
+    fn state_root(&self) -> Option<StateRoot> {
+        match self {
+            Task::Trie(_, state_root) | Task::Flat(_, state_root) => Some(*state_root),
+            _ => None,
+        }
+    }
+}
+
+/// State shared by all the tasks.
+struct Progress {
+    cursor: Mutex<Cursor>,
+    cursor_path: PathBuf,
+    report: Mutex<BufWriter<File>>,
+}
+
+impl Progress {
+    /// Loads the progress of the previous runs, if any, and appends new anomalies to the report.
+    fn open(cursor_path: PathBuf, report_path: &Path) -> anyhow::Result<Self> {
+        let cursor = if cursor_path.exists() {
+            serde_json::from_slice(&std::fs::read(&cursor_path)?)?
+        } else {
+            Cursor::default()
+        };
+        let report = OpenOptions::new().create(true).append(true).open(report_path)?;
+        Ok(Self {
+            cursor: Mutex::new(cursor),
+            cursor_path,
+            report: Mutex::new(BufWriter::new(report)),
+        })
+    }
+
+    fn report(&self, task: &str, key: &[u8], message: String) -> anyhow::Result<()> {
+        let anomaly = Anomaly { task, key: hex::encode(key), message };
+        let mut report = self.report.lock().unwrap();
+        serde_json::to_writer(&mut *report, &anomaly)?;
+        report.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn save(&self, task: &str, progress: &TaskProgress) -> anyhow::Result<()> {
+        // The anomalies found so far must not get lost when resuming from this point.
+        self.report.lock().unwrap().flush()?;
+        let mut cursor = self.cursor.lock().unwrap();
+        cursor.tasks.insert(task.to_string(), progress.clone());
+        let tmp_path = self.cursor_path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_vec_pretty(&*cursor)?)?;
+        std::fs::rename(&tmp_path, &self.cursor_path)?;
+        Ok(())
+    }
+}
+
+/// A task being run, keeping track of its progress.
+struct TaskRun<'a> {
+    name: String,
+    progress: &'a Progress,
+    state: TaskProgress,
+    limit: Option<u64>,
+    checkpoint_interval: u64,
+    checked_in_run: u64,
+}
+
+impl TaskRun<'_> {
+    fn last_key(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.state.last_key.as_deref().map(hex::decode).transpose()?)
+    }
+
+    /// The smallest key after the last checked one.
+    fn next_key(&self) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.last_key()?.map(|mut key| {
+            key.push(0);
+            key
+        }))
+    }
+
+    /// Records that `key` has been checked. Returns false once the task should stop.
+    fn checked(&mut self, key: &[u8]) -> anyhow::Result<bool> {
+        self.state.last_key = Some(hex::encode(key));
+        self.state.checked += 1;
+        self.checked_in_run += 1;
+        if self.checked_in_run % self.checkpoint_interval == 0 {
+            self.progress.save(&self.name, &self.state)?;
+        }
+        Ok(self.limit.map_or(true, |limit| self.checked_in_run < limit))
+    }
+
+    fn anomaly(&mut self, key: &[u8], message: impl Into<String>) -> anyhow::Result<()> {
+        self.state.anomalies += 1;
+        self.progress.report(&self.name, key, message.into())
+    }
+}
+
+impl VerifyCommand {
+    pub(crate) fn run(&self, unc_config: UncConfig, home: &Path) -> anyhow::Result<()> {
+        let rocksdb = Arc::new(open_rocksdb(home, unc_store::Mode::ReadOnly)?);
+        let store = unc_store::NodeStorage::new(rocksdb).get_hot_store();
+        let cursor_path = self.cursor.clone().unwrap_or_else(|| home.join("verify-cursor.json"));
+        let report_path = self.report.clone().unwrap_or_else(|| home.join("verify-report.jsonl"));
+        if self.restart {
+            for path in [&cursor_path, &report_path] {
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+            }
+        }
+        let progress = Progress::open(cursor_path, &report_path)?;
+        let checks = if self.checks.is_empty() {
+            Check::value_variants().to_vec()
+        } else {
+            self.checks.clone()
+        };
+        let tasks = tasks(&store, &unc_config, &checks)?;
+
+        let results: Vec<_> =
+            tasks.par_iter().map(|task| run_task(&store, task, &progress, self.limit)).collect();
+        for (task, result) in tasks.iter().zip(results) {
+            let progress = result?;
+            println!(
+                "{}: {} keys checked, {} anomalies{}",
+                task.name(),
+                progress.checked,
+                progress.anomalies,
+                if progress.done { "" } else { ", not done yet" }
+            );
+        }
+        println!("Anomalies are reported in {}", report_path.display());
+        Ok(())
+    }
+}
+
+fn tasks(store: &Store, unc_config: &UncConfig, checks: &[Check]) -> anyhow::Result<Vec<Task>> {
+    let mut tasks = vec![];
+    if checks.contains(&Check::Refcounts) {
+        tasks.extend(DBCol::iter().filter(|col| col.is_rc()).map(Task::Refcounts));
+    }
+    if checks.contains(&Check::Blocks) {
+        tasks.extend([Task::Blocks, Task::Headers, Task::BlockHeights, Task::Chunks]);
+    }
+    if !checks.contains(&Check::Tries) && !checks.contains(&Check::Flat) {
+        return Ok(tasks);
+    }
+
+    let head = store
+        .get_ser::<Tip>(DBCol::BlockMisc, HEAD_KEY)?
+        .ok_or_else(|| anyhow::anyhow!("head not found"))?;
+    let epoch_manager =
+        EpochManager::new_from_genesis_config(store.clone(), &unc_config.genesis.config)?;
+    let shard_layout = epoch_manager.get_shard_layout(&head.epoch_id)?;
+    for shard_uid in shard_layout.shard_uids() {
+        if checks.contains(&Check::Tries) {
+            let state_root = state_root(store, &head.last_block_hash, shard_uid)?;
+            tasks.push(Task::Trie(shard_uid, state_root));
+        }
+        if checks.contains(&Check::Flat) {
+            match store_helper::get_flat_storage_status(store, shard_uid)? {
+                FlatStorageStatus::Ready(status) => {
+                    let state_root = state_root(store, &status.flat_head.hash, shard_uid)?;
+                    tasks.push(Task::Flat(shard_uid, state_root));
+                }
+                status => println!("flat:{shard_uid}: skipped, flat storage is {status:?}"),
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+fn state_root(
+    store: &Store,
+    block_hash: &CryptoHash,
+    shard_uid: ShardUId,
+) -> anyhow::Result<StateRoot> {
+    let chunk_extra = store
+        .get_ser::<ChunkExtra>(DBCol::ChunkExtra, &get_block_shard_uid(block_hash, &shard_uid))?
+        .ok_or_else(|| anyhow::anyhow!("no chunk extra for {shard_uid} at {block_hash}"))?;
+    Ok(*chunk_extra.state_root())
+}
+
+fn run_task(
+    store: &Store,
+    task: &Task,
+    progress: &Progress,
+    limit: Option<u64>,
+) -> anyhow::Result<TaskProgress> {
+    let name = task.name();
+    let mut state = progress.cursor.lock().unwrap().tasks.get(&name).cloned().unwrap_or_default();
+    if state.state_root != task.state_root() {
+        state = TaskProgress { state_root: task.state_root(), ..TaskProgress::default() };
+    }
+    if state.done {
+        return Ok(state);
+    }
+    let checkpoint_interval = if let Task::Trie(..) = task { 1 } else { CHECKPOINT_INTERVAL };
+    let mut run = TaskRun { name, progress, state, limit, checkpoint_interval, checked_in_run: 0 };
+    let result = match task {
+        Task::Refcounts(col) => check_refcounts(store, *col, &mut run),
+        Task::Blocks => check_blocks(store, &mut run),
+        Task::Headers => check_headers(store, &mut run),
+        Task::BlockHeights => check_block_heights(store, &mut run),
+        Task::Chunks => check_chunks(store, &mut run),
+        Task::Trie(shard_uid, state_root) => check_trie(store, *shard_uid, *state_root, &mut run),
+        Task::Flat(shard_uid, state_root) => check_flat(store, *shard_uid, *state_root, &mut run),
+    };
+    // A failing task is retried from its last checkpoint by the next run.
+    run.state.done = match result {
+        Ok(done) => done,
+        Err(err) => {
+            let key = run.last_key()?.unwrap_or_default();
+            run.anomaly(&key, format!("check aborted after this key: {err:#}"))?;
+            false
+        }
+    };
+    progress.save(&run.name, &run.state)?;
+    Ok(run.state)
+}
+
+/// Checks that no reference count is negative and that trie nodes are stored under their hash.
+fn check_refcounts(store: &Store, col: DBCol, run: &mut TaskRun) -> anyhow::Result<bool> {
+    let next_key = run.next_key()?;
+    for item in store.iter_raw_bytes_range(col, next_key.as_deref(), None) {
+        let (key, value) = item?;
+        // Zero reference counts are fine, the entries get removed by the next compaction.
+        let (data, rc) = decode_value_with_rc(&value);
+        if rc < 0 {
+            run.anomaly(&key, format!("negative reference count {rc}"))?;
+        }
+        if let (DBCol::State, Some(data)) = (col, data) {
+            // Trie nodes and values are keyed by the shard uid followed by their hash.
+            if key.len() != 40 || hash(data).as_ref() != &key[8..] {
+                run.anomaly(&key, "key does not match the hash of the value")?;
+            }
+        }
+        if !run.checked(&key)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Checks that blocks are stored under their hash along with their header and new chunks.
+fn check_blocks(store: &Store, run: &mut TaskRun) -> anyhow::Result<bool> {
+    let next_key = run.next_key()?;
+    for item in store.iter_range(DBCol::Block, next_key.as_deref(), None) {
+        let (key, value) = item?;
+        match Block::try_from_slice(&value) {
+            Err(err) => run.anomaly(&key, format!("cannot decode block: {err}"))?,
+            Ok(block) => {
+                if block.hash().as_ref() != &*key {
+                    run.anomaly(&key, format!("block hash is {}", block.hash()))?;
+                }
+                if !store.exists(DBCol::BlockHeader, &key)? {
+                    run.anomaly(&key, "block header is missing")?;
+                }
+                let chunks = block.chunks();
+                for chunk in chunks.iter() {
+                    if chunk.height_included() != block.header().height() {
+                        continue;
+                    }
+                    let chunk_hash = chunk.chunk_hash();
+                    if !store.exists(DBCol::PartialChunks, chunk_hash.as_ref())?
+                        && !store.exists(DBCol::Chunks, chunk_hash.as_ref())?
+                    {
+                        let message = format!(
+                            "chunk {} of shard {} is missing",
+                            chunk_hash.0,
+                            chunk.shard_id()
+                        );
+                        run.anomaly(&key, message)?;
+                    }
+                }
+            }
+        }
+        if !run.checked(&key)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Checks that headers are stored under their hash.
+fn check_headers(store: &Store, run: &mut TaskRun) -> anyhow::Result<bool> {
+    let next_key = run.next_key()?;
+    for item in store.iter_range(DBCol::BlockHeader, next_key.as_deref(), None) {
+        let (key, value) = item?;
+        match BlockHeader::try_from_slice(&value) {
+            Err(err) => run.anomaly(&key, format!("cannot decode header: {err}"))?,
+            Ok(header) if header.hash().as_ref() != &*key => {
+                run.anomaly(&key, format!("header hash is {}", header.hash()))?
+            }
+            Ok(_) => {}
+        }
+        if !run.checked(&key)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Checks that the canonical chain index points at headers of the right height.
+fn check_block_heights(store: &Store, run: &mut TaskRun) -> anyhow::Result<bool> {
+    let next_key = run.next_key()?;
+    for item in store.iter_range(DBCol::BlockHeight, next_key.as_deref(), None) {
+        let (key, value) = item?;
+        let height = <[u8; 8]>::try_from(&*key).map(u64::from_le_bytes);
+        let block_hash = CryptoHash::try_from_slice(&value);
+        match (height, block_hash) {
+            (Ok(height), Ok(block_hash)) => {
+                match store.get_ser::<BlockHeader>(DBCol::BlockHeader, block_hash.as_ref())? {
+                    None => {
+                        run.anomaly(&key, format!("header of block {block_hash} is missing"))?
+                    }
+                    Some(header) if header.height() != height => {
+                        let message = format!(
+                            "block {block_hash} at height {height} has height {}",
+                            header.height()
+                        );
+                        run.anomaly(&key, message)?;
+                    }
+                    Some(_) => {}
+                }
+            }
+            _ => run.anomaly(&key, "cannot decode height index entry")?,
+        }
+        if !run.checked(&key)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Checks that chunks are stored under their hash.
+fn check_chunks(store: &Store, run: &mut TaskRun) -> anyhow::Result<bool> {
+    let next_key = run.next_key()?;
+    for item in store.iter_range(DBCol::Chunks, next_key.as_deref(), None) {
+        let (key, value) = item?;
+        match ShardChunk::try_from_slice(&value) {
+            Err(err) => run.anomaly(&key, format!("cannot decode chunk: {err}"))?,
+            Ok(chunk) => {
+                let header_hash = chunk.cloned_header().chunk_hash();
+                if chunk.chunk_hash() != header_hash || header_hash.as_ref() != &*key {
+                    run.anomaly(&key, format!("chunk hash is {}", header_hash.0))?;
+                }
+            }
+        }
+        if !run.checked(&key)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Checks that all nodes and values of the trie are present, one part of the trie at a time.
+///
+/// The keys recorded as checked are the first bytes of the trie keys in the finished parts.
+fn check_trie(
+    store: &Store,
+    shard_uid: ShardUId,
+    state_root: StateRoot,
+    run: &mut TaskRun,
+) -> anyhow::Result<bool> {
+    let trie = Trie::new(Rc::new(TrieDBStorage::new(store.clone(), shard_uid)), state_root, None);
+    let first_part = match run.last_key()?.as_deref() {
+        Some([last_part]) => *last_part as usize + 1,
+        _ => 0,
+    };
+    let nibbles = |part: usize| vec![(part >> 4) as u8, (part & 0xf) as u8];
+    for part in first_part..TRIE_PARTS {
+        // Nibbles never exceed 15, so `[16]` is past all the keys.
+        let path_end = if part + 1 == TRIE_PARTS { vec![16] } else { nibbles(part + 1) };
+        if let Err(err) = trie.iter()?.visit_nodes_interval(&nibbles(part), &path_end) {
+            run.anomaly(&[part as u8], format!("part of the trie is not reachable: {err}"))?;
+        }
+        if !run.checked(&[part as u8])? {
+            return Ok(part + 1 == TRIE_PARTS);
+        }
+    }
+    Ok(true)
+}
+
+/// Checks that flat storage holds exactly the same key-value pairs as the trie, by walking both
+/// of them in key order.
+fn check_flat(
+    store: &Store,
+    shard_uid: ShardUId,
+    state_root: StateRoot,
+    run: &mut TaskRun,
+) -> anyhow::Result<bool> {
+    let trie = Trie::new(Rc::new(TrieDBStorage::new(store.clone(), shard_uid)), state_root, None);
+    let next_key = run.next_key()?;
+    let mut flat_items =
+        store_helper::iter_flat_state_entries(shard_uid, store, next_key.as_deref(), None);
+    let mut trie_items = trie.iter()?;
+    if let Some(next_key) = &next_key {
+        trie_items.seek(next_key)?;
+    }
+
+    let mut next_flat = flat_items.next().transpose()?;
+    let mut next_trie = trie_items.next().transpose()?;
+    loop {
+        let key = match (&next_flat, &next_trie) {
+            (None, None) => return Ok(true),
+            (Some((flat_key, flat_value)), Some((trie_key, trie_value)))
+                if flat_key == trie_key =>
+            {
+                if flat_value.to_value_ref() != ValueRef::new(trie_value) {
+                    run.anomaly(flat_key, "flat state value differs from the trie")?;
+                }
+                let key = flat_key.clone();
+                next_flat = flat_items.next().transpose()?;
+                next_trie = trie_items.next().transpose()?;
+                key
+            }
+            (Some((flat_key, _)), Some((trie_key, _))) if flat_key > trie_key => {
+                let key = trie_key.clone();
+                run.anomaly(&key, "key is missing in flat state")?;
+                next_trie = trie_items.next().transpose()?;
+                key
+            }
+            (None, Some((trie_key, _))) => {
+                let key = trie_key.clone();
+                run.anomaly(&key, "key is missing in flat state")?;
+                next_trie = trie_items.next().transpose()?;
+                key
+            }
+            (Some((flat_key, _)), _) => {
+                let key = flat_key.clone();
+                run.anomaly(&key, "key is missing in the trie")?;
+                next_flat = flat_items.next().transpose()?;
+                key
+            }
+        };
+        if !run.checked(&key)? {
+            return Ok(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unc_store::test_utils::{
+        create_test_store, test_populate_flat_storage, test_populate_trie, TestTriesBuilder,
+    };
+
+    fn read_report(path: &Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    /// A limited run records its progress and the next run continues after the last checked key
+    /// without reporting the same anomalies again.
+    #[test]
+    fn test_resume_from_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let cursor_path = dir.path().join("cursor.json");
+        let report_path = dir.path().join("report.jsonl");
+        let store = create_test_store();
+        let mut store_update = store.store_update();
+        // None of the blocks the height index points at are stored.
+        for height in 0..5u64 {
+            store_update
+                .set_ser(DBCol::BlockHeight, &height.to_le_bytes(), &hash(&[height as u8]))
+                .unwrap();
+        }
+        store_update.commit().unwrap();
+
+        let progress = Progress::open(cursor_path.clone(), &report_path).unwrap();
+        let state = run_task(&store, &Task::BlockHeights, &progress, Some(2)).unwrap();
+        assert_eq!((state.checked, state.anomalies, state.done), (2, 2, false));
+        drop(progress);
+
+        let progress = Progress::open(cursor_path.clone(), &report_path).unwrap();
+        let state = run_task(&store, &Task::BlockHeights, &progress, None).unwrap();
+        assert_eq!((state.checked, state.anomalies, state.done), (5, 5, true));
+        drop(progress);
+
+        let keys: Vec<_> = read_report(&report_path)
+            .iter()
+            .map(|anomaly| anomaly["key"].as_str().unwrap().to_string())
+            .collect();
+        let expected: Vec<_> = (0..5u64).map(|height| hex::encode(height.to_le_bytes())).collect();
+        assert_eq!(keys, expected);
+
+        // A finished task is not run again.
+        let progress = Progress::open(cursor_path, &report_path).unwrap();
+        let state = run_task(&store, &Task::BlockHeights, &progress, None).unwrap();
+        assert_eq!((state.checked, state.done), (5, true));
+        assert_eq!(read_report(&report_path).len(), 5);
+    }
+
+    #[test]
+    fn test_corrupted_trie_node_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let report_path = dir.path().join("report.jsonl");
+        let store = create_test_store();
+        let shard_uid = ShardUId::single_shard();
+        let node_key = |data: &[u8]| [&shard_uid.to_bytes()[..], hash(data).as_ref()].concat();
+        let corrupted_key = node_key(b"original");
+        let mut store_update = store.store_update();
+        store_update.increment_refcount(DBCol::State, &node_key(b"node"), b"node");
+        store_update.increment_refcount(DBCol::State, &corrupted_key, b"corrupted");
+        store_update.commit().unwrap();
+
+        let progress = Progress::open(dir.path().join("cursor.json"), &report_path).unwrap();
+        let state = run_task(&store, &Task::Refcounts(DBCol::State), &progress, None).unwrap();
+        assert_eq!((state.checked, state.anomalies, state.done), (2, 1, true));
+        drop(progress);
+
+        let report = read_report(&report_path);
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0]["task"], "refcounts:State");
+        assert_eq!(report[0]["key"], hex::encode(&corrupted_key));
+        assert_eq!(report[0]["message"], "key does not match the hash of the value");
+    }
+
+    /// Resuming the refcount check starts right after the last checked key.
+    #[test]
+    fn test_refcounts_resume_after_last_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let cursor_path = dir.path().join("cursor.json");
+        let report_path = dir.path().join("report.jsonl");
+        let store = create_test_store();
+        let shard_uid = ShardUId::single_shard();
+        let mut store_update = store.store_update();
+        for data in [b"a", b"b", b"c"] {
+            let key = [&shard_uid.to_bytes()[..], hash(data).as_ref()].concat();
+            store_update.increment_refcount(DBCol::State, &key, data);
+        }
+        store_update.commit().unwrap();
+
+        let progress = Progress::open(cursor_path.clone(), &report_path).unwrap();
+        let state = run_task(&store, &Task::Refcounts(DBCol::State), &progress, Some(2)).unwrap();
+        assert_eq!((state.checked, state.done), (2, false));
+        drop(progress);
+
+        let progress = Progress::open(cursor_path, &report_path).unwrap();
+        let state = run_task(&store, &Task::Refcounts(DBCol::State), &progress, Some(2)).unwrap();
+        assert_eq!((state.checked, state.anomalies, state.done), (3, 0, true));
+    }
+
+    #[test]
+    fn test_missing_trie_node_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let report_path = dir.path().join("report.jsonl");
+        let store = create_test_store();
+        let shard_uid = ShardUId::single_shard();
+        let tries = TestTriesBuilder::new().with_store(store.clone()).build();
+        let changes = vec![
+            (vec![1, 1], Some(b"a".to_vec())),
+            (vec![1, 2], Some(b"b".to_vec())),
+            (vec![200, 1], Some(b"c".to_vec())),
+            (vec![201, 1], Some(b"d".to_vec())),
+        ];
+        let state_root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, changes);
+
+        // Drop the leaf of the only key in the part of the trie starting with byte 200. The
+        // branch above it is shared with the part starting with byte 201, which stays readable.
+        let trie = tries.get_trie_for_shard(shard_uid, state_root);
+        let nodes = trie.iter().unwrap().visit_nodes_interval(&[12, 8], &[12, 9]).unwrap();
+        let leaf = nodes.last().unwrap();
+        assert_eq!(leaf.key.as_deref(), Some(&[200, 1][..]));
+        let node_key = [&shard_uid.to_bytes()[..], leaf.hash.as_ref()].concat();
+        let mut store_update = store.store_update();
+        store_update.decrement_refcount(DBCol::State, &node_key);
+        store_update.commit().unwrap();
+
+        let progress = Progress::open(dir.path().join("cursor.json"), &report_path).unwrap();
+        let state = run_task(&store, &Task::Trie(shard_uid, state_root), &progress, None).unwrap();
+        assert_eq!((state.checked, state.anomalies, state.done), (TRIE_PARTS as u64, 1, true));
+        drop(progress);
+
+        let report = read_report(&report_path);
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0]["task"], format!("trie:{shard_uid}"));
+        assert_eq!(report[0]["key"], hex::encode([200]));
+        assert!(report[0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("part of the trie is not reachable"));
+    }
+
+    #[test]
+    fn test_flat_state_mismatch_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let report_path = dir.path().join("report.jsonl");
+        let store = create_test_store();
+        let shard_uid = ShardUId::single_shard();
+        let tries = TestTriesBuilder::new().with_store(store.clone()).build();
+        let trie_changes = vec![
+            (vec![1], Some(b"a".to_vec())),
+            (vec![2], Some(b"b".to_vec())),
+            (vec![3], Some(b"c".to_vec())),
+        ];
+        let state_root = test_populate_trie(&tries, &Trie::EMPTY_ROOT, shard_uid, trie_changes);
+        // [2] differs, [3] is missing and [4] is extra in flat storage.
+        let flat_changes = vec![
+            (vec![1], Some(b"a".to_vec())),
+            (vec![2], Some(b"x".to_vec())),
+            (vec![4], Some(b"d".to_vec())),
+        ];
+        let block_hash = hash(b"block");
+        test_populate_flat_storage(&tries, shard_uid, &block_hash, &block_hash, &flat_changes);
+
+        let progress = Progress::open(dir.path().join("cursor.json"), &report_path).unwrap();
+        let state = run_task(&store, &Task::Flat(shard_uid, state_root), &progress, None).unwrap();
+        assert_eq!((state.checked, state.anomalies, state.done), (4, 3, true));
+        drop(progress);
+
+        let anomalies: Vec<_> = read_report(&report_path)
+            .iter()
+            .map(|anomaly| {
+                (
+                    anomaly["key"].as_str().unwrap().to_string(),
+                    anomaly["message"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        let expected = [
+            ([2], "flat state value differs from the trie"),
+            ([3], "key is missing in flat state"),
+            ([4], "key is missing in the trie"),
+        ]
+        .map(|(key, message)| (hex::encode(key), message.to_string()));
+        assert_eq!(anomalies, expected);
+    }
+}
@@ -495,6 +495,8 @@ impl ForkNetworkCommand {
         let mut postponed_receipts_updated = 0;
         let mut delayed_receipts_updated = 0;
         let mut received_data_updated = 0;
+        let mut rsa2048_keys_updated = 0;
+        let mut promise_yields_updated = 0;
         let mut fake_block_height = block_height + 1;
         for item in store_helper::iter_flat_state_entries(shard_uid, &store, None, None) {
             let (key, value) = match item {
@@ -595,6 +597,34 @@ impl ForkNetworkCommand {
                         }
                         index_delayed_receipt += 1;
                     }
+                    StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                        // TODO(eth-implicit) Change back to is_implicit() when ETH-implicit accounts are supported.
+                        if account_id.get_account_type() == AccountType::UtilityAccount {
+                            let new_account_id = map_account(&account_id, None);
+                            storage_mutator.delete_rsa2048_keys(account_id, public_key.clone())?;
+                            storage_mutator.set_rsa2048_keys(
+                                new_account_id,
+                                public_key,
+                                &rsa_key,
+                            )?;
+                            rsa2048_keys_updated += 1;
+                        }
+                    }
+                    StateRecord::PromiseYieldReceipt { account_id, data_id, expires_at } => {
+                        // TODO(eth-implicit) Change back to is_implicit() when ETH-implicit accounts are supported.
+                        if account_id.get_account_type() == AccountType::UtilityAccount {
+                            // The timeout queue entry keeps the old account id and resolves
+                            // nothing, so the remapped yield can only be resumed.
+                            let new_account_id = map_account(&account_id, None);
+                            storage_mutator.delete_promise_yield_receipt(account_id, data_id)?;
+                            storage_mutator.set_promise_yield_receipt(
+                                new_account_id,
+                                data_id,
+                                expires_at,
+                            )?;
+                            promise_yields_updated += 1;
+                        }
+                    }
                 }
                 records_parsed += 1;
             } else {
@@ -611,7 +641,9 @@ impl ForkNetworkCommand {
                         + contract_code_updated
                         + postponed_receipts_updated
                         + delayed_receipts_updated
-                        + received_data_updated,
+                        + received_data_updated
+                        + rsa2048_keys_updated
+                        + promise_yields_updated,
                 );
                 let state_root = storage_mutator.commit(&shard_uid, fake_block_height)?;
                 fake_block_height += 1;
@@ -631,6 +663,8 @@ impl ForkNetworkCommand {
             postponed_receipts_updated,
             delayed_receipts_updated,
             received_data_updated,
+            rsa2048_keys_updated,
+            promise_yields_updated,
             num_has_full_key = has_full_key.len(),
             "Pass 1 done"
         );
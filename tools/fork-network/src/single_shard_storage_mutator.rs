@@ -2,12 +2,13 @@ use framework::NightshadeRuntime;
 use unc_chain::types::RuntimeAdapter;
 use unc_crypto::PublicKey;
 use unc_primitives::account::{AccessKey, Account};
+use unc_primitives::action::RegisterRsa2048KeysAction;
 use unc_primitives::borsh;
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::receipt::Receipt;
 use unc_primitives::shard_layout::ShardUId;
 use unc_primitives::trie_key::TrieKey;
-use unc_primitives::types::{AccountId, StateRoot};
+use unc_primitives::types::{AccountId, BlockHeight, StateRoot};
 use unc_primitives::types::{StoreKey, StoreValue};
 use unc_store::{flat::FlatStateChanges, DBCol, ShardTries};
 
@@ -63,6 +64,23 @@ impl SingleShardStorageMutator {
         self.remove(TrieKey::AccessKey { account_id, public_key })
     }
 
+    pub(crate) fn set_rsa2048_keys(
+        &mut self,
+        account_id: AccountId,
+        public_key: PublicKey,
+        rsa_key: &RegisterRsa2048KeysAction,
+    ) -> anyhow::Result<()> {
+        self.set(TrieKey::Rsa2048Keys { account_id, public_key }, borsh::to_vec(rsa_key)?)
+    }
+
+    pub(crate) fn delete_rsa2048_keys(
+        &mut self,
+        account_id: AccountId,
+        public_key: PublicKey,
+    ) -> anyhow::Result<()> {
+        self.remove(TrieKey::Rsa2048Keys { account_id, public_key })
+    }
+
     pub(crate) fn set_data(
         &mut self,
         account_id: AccountId,
@@ -137,6 +155,26 @@ impl SingleShardStorageMutator {
         self.remove(TrieKey::DelayedReceipt { index })
     }
 
+    pub(crate) fn set_promise_yield_receipt(
+        &mut self,
+        account_id: AccountId,
+        data_id: CryptoHash,
+        expires_at: BlockHeight,
+    ) -> anyhow::Result<()> {
+        self.set(
+            TrieKey::PromiseYieldReceipt { receiver_id: account_id, data_id },
+            borsh::to_vec(&expires_at)?,
+        )
+    }
+
+    pub(crate) fn delete_promise_yield_receipt(
+        &mut self,
+        account_id: AccountId,
+        data_id: CryptoHash,
+    ) -> anyhow::Result<()> {
+        self.remove(TrieKey::PromiseYieldReceipt { receiver_id: account_id, data_id })
+    }
+
     pub(crate) fn should_commit(&self, batch_size: u64) -> bool {
         self.updates.len() >= batch_size as usize
     }
@@ -276,6 +276,8 @@ fn main() -> Result<()> {
                 sync_mode: unc_indexer::SyncModeEnum::FromInterruption,
                 await_for_node_synced: unc_indexer::AwaitForNodeSyncedEnum::WaitForFullSync,
                 validate_genesis: true,
+                filter: unc_indexer::IndexerFilter::default(),
+                backfill: unc_indexer::BackfillConfig::default(),
             };
             let system = actix::System::new();
             system.block_on(async move {
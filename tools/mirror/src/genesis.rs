@@ -91,6 +91,14 @@ pub fn map_records<P: AsRef<Path>>(
                 }
                 records_seq.serialize_element(&r).unwrap();
             }
+            StateRecord::Rsa2048Keys { account_id, .. }
+            | StateRecord::PromiseYieldReceipt { account_id, .. } => {
+                // TODO(eth-implicit) Change back to is_implicit() when ETH-implicit accounts are supported.
+                if account_id.get_account_type() == AccountType::UtilityAccount {
+                    *account_id = crate::key_mapping::map_account(&account_id, secret.as_ref());
+                }
+                records_seq.serialize_element(&r).unwrap();
+            }
             StateRecord::DelayedReceipt(receipt) => {
                 // TODO(eth-implicit) Change back to is_implicit() when ETH-implicit accounts are supported.
                 if receipt.predecessor_id.get_account_type() == AccountType::UtilityAccount
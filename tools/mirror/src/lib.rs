@@ -374,6 +374,7 @@ impl From<QueryError> for ChainError {
             | QueryError::NoContractCode { .. }
             | QueryError::UnknownAccessKey { .. }
             | QueryError::GarbageCollectedBlock { .. }
+            | QueryError::AccountNotRetained { .. }
             | QueryError::UnknownBlock { .. } => Self::Unknown,
             _ => Self::other(err),
         }
@@ -878,6 +879,8 @@ impl<T: ChainAccess> TxMirror<T> {
             sync_mode: unc_indexer::SyncModeEnum::FromInterruption,
             await_for_node_synced: unc_indexer::AwaitForNodeSyncedEnum::StreamWhileSyncing,
             validate_genesis: false,
+            filter: unc_indexer::IndexerFilter::default(),
+            backfill: unc_indexer::BackfillConfig::default(),
         })
         .context("failed to start target chain indexer")?;
         let (target_view_client, target_client) = target_indexer.client_actors();
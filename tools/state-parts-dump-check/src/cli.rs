@@ -14,7 +14,7 @@ use unc_client::sync::external::{
 };
 use unc_jsonrpc::client::{new_client, JsonRpcClient};
 use unc_primitives::hash::CryptoHash;
-use unc_primitives::state_part::PartId;
+use unc_primitives::state_part::{unpack_state_part, PartId, StatePartContainer};
 use unc_primitives::types::{
     BlockId, BlockReference, EpochId, EpochReference, Finality, ShardId, StateRoot,
 };
@@ -51,6 +51,8 @@ pub enum StatePartsDumpCheckSubCommand {
     SingleCheck(SingleCheckCommand),
     /// Runs an infinite loop to download and validate state parts of all 4 shards for each epoch when it becomes available
     LoopCheck(LoopCheckCommand),
+    /// Verifies a local state part file stored in the container format, using only the information in its header
+    VerifyFile(VerifyFileCommand),
 }
 
 #[derive(clap::Parser)]
@@ -63,6 +65,13 @@ pub struct LoopCheckCommand {
     rpc_server_addr: Option<String>,
 }
 
+#[derive(clap::Parser)]
+pub struct VerifyFileCommand {
+    /// Path to the state part file.
+    #[clap(long)]
+    path: PathBuf,
+}
+
 #[derive(clap::Parser)]
 pub struct SingleCheckCommand {
     #[clap(long)]
@@ -103,6 +112,7 @@ impl StatePartsDumpCheckSubCommand {
             StatePartsDumpCheckSubCommand::LoopCheck(cmd) => {
                 cmd.run(chain_id, root_dir, s3_bucket, s3_region, gcs_bucket)
             }
+            StatePartsDumpCheckSubCommand::VerifyFile(cmd) => cmd.run(),
         }
     }
 }
@@ -136,6 +146,34 @@ impl SingleCheckCommand {
     }
 }
 
+impl VerifyFileCommand {
+    // Checks the header and the content hash of the container, then validates the
+    // trie nodes against the state root and part id recorded in the header.
+    fn run(&self) -> anyhow::Result<()> {
+        let bytes = std::fs::read(&self.path)?;
+        if !StatePartContainer::is_container(&bytes) {
+            return Err(anyhow!(
+                "{} is a raw state part, use single-check to validate it against a state root",
+                self.path.display()
+            ));
+        }
+        let container = StatePartContainer::from_bytes(&bytes)?;
+        let header = container.header().clone();
+        println!("{header:#?}");
+        let part = container.into_data()?;
+        let part_id = PartId::new(header.part_id, header.num_parts);
+        if !validate_state_part(&header.state_root, part_id, &part) {
+            return Err(anyhow!(
+                "part {} doesn't match state root {}",
+                part_id.idx,
+                header.state_root
+            ));
+        }
+        println!("part {} of shard {} is valid", part_id.idx, header.shard_id);
+        Ok(())
+    }
+}
+
 impl LoopCheckCommand {
     // Connect to an RPC server to request latest epoch information.
     // Whenever an epoch is complete, use the location specified by root_dir/s3_bucket&s3_location/gcs_bucket to download parts and validate them.
@@ -625,7 +663,15 @@ async fn process_part(
     let location =
         external_storage_location(&chain_id, &epoch_id, epoch_height, shard_id, part_id, num_parts);
     let part = external.get_part(shard_id, &location).await?;
-    let is_part_valid = validate_state_part(&state_root, PartId::new(part_id, num_parts), &part);
+    let part_id = PartId::new(part_id, num_parts);
+    let is_part_valid = match unpack_state_part(part, shard_id, &epoch_id, part_id, &state_root) {
+        Ok(part) => validate_state_part(&state_root, part_id, &part),
+        Err(err) => {
+            tracing::error!(target: "state-parts", ?err, "State part container error");
+            false
+        }
+    };
+    let part_id = part_id.idx;
     if is_part_valid {
         crate::metrics::STATE_SYNC_DUMP_CHECK_NUM_PARTS_VALID
             .with_label_values(&[&shard_id.to_string(), &chain_id.to_string()])
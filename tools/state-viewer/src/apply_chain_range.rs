@@ -13,6 +13,7 @@ use unc_chain::types::{
 use unc_chain::{ChainStore, ChainStoreAccess, ChainStoreUpdate};
 use unc_chain_configs::Genesis;
 use unc_epoch_manager::{EpochManagerAdapter, EpochManagerHandle};
+use unc_primitives::congestion_info::BlockCongestionInfo;
 use unc_primitives::hash::CryptoHash;
 use unc_primitives::receipt::DelayedReceiptIndices;
 use unc_primitives::transaction::{Action, ExecutionOutcomeWithId, ExecutionOutcomeWithProof};
@@ -243,6 +244,7 @@ fn apply_block_from_range(
                 ApplyChunkBlockContext::from_header(
                     block.header(),
                     prev_block.header().next_gas_price(),
+                    block.block_congestion_info(),
                 ),
                 &receipts,
                 chunk.transactions(),
@@ -268,6 +270,7 @@ fn apply_block_from_range(
                 ApplyChunkBlockContext::from_header(
                     block.header(),
                     block.header().next_gas_price(),
+                    BlockCongestionInfo::default(),
                 ),
                 &[],
                 &[],
@@ -284,6 +287,7 @@ fn apply_block_from_range(
         apply_result.total_gas_burnt,
         genesis.config.gas_limit,
         apply_result.total_balance_burnt,
+        apply_result.congestion_info,
     );
 
     let state_update =
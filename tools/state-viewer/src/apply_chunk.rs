@@ -152,6 +152,7 @@ pub(crate) fn apply_chunk(
                 ),
                 gas_price,
                 random_seed: hash("random seed".as_ref()),
+                congestion_info: Default::default(),
             },
             &receipts,
             transactions,
@@ -1,5 +1,6 @@
 use crate::commands::*;
 use crate::contract_accounts::ContractAccountFilter;
+use crate::replay_patched::StatePatch;
 use crate::rocksdb_stats::get_rocksdb_stats;
 use crate::trie_iteration_benchmark::TrieIterationBenchmarkCmd;
 
@@ -71,6 +72,10 @@ pub enum StateViewerSubCommand {
     Receipts(ReceiptsCmd),
     /// Replay headers from chain.
     Replay(ReplayCmd),
+    /// Replay a receipt or a range of blocks for a single shard on patched state, and print where
+    /// the results differ from the ones on the original state. Nothing is written to the DB.
+    #[clap(alias = "replay_patched")]
+    ReplayPatched(ReplayPatchedCmd),
     /// Dump stats for the RocksDB storage.
     #[clap(name = "rocksdb-stats", alias = "rocksdb_stats")]
     RocksDBStats(RocksDBStatsCmd),
@@ -142,6 +147,7 @@ impl StateViewerSubCommand {
             StateViewerSubCommand::PartialChunks(cmd) => cmd.run(unc_config, store),
             StateViewerSubCommand::Receipts(cmd) => cmd.run(unc_config, store),
             StateViewerSubCommand::Replay(cmd) => cmd.run(unc_config, store),
+            StateViewerSubCommand::ReplayPatched(cmd) => cmd.run(home_dir, unc_config, store),
             StateViewerSubCommand::RocksDBStats(cmd) => cmd.run(store_opener.path()),
             StateViewerSubCommand::ScanDbColumn(cmd) => cmd.run(store),
             StateViewerSubCommand::State => state(home_dir, unc_config, store),
@@ -502,6 +508,45 @@ impl ReplayCmd {
     }
 }
 
+#[derive(clap::Parser)]
+pub struct ReplayPatchedCmd {
+    /// Replay the block in which this receipt or transaction was executed and only compare
+    /// its outcome, instead of a range of blocks.
+    #[clap(long, conflicts_with_all = &["start_height", "end_height", "shard_id"])]
+    hash: Option<String>,
+    #[clap(long)]
+    start_height: Option<BlockHeight>,
+    #[clap(long)]
+    end_height: Option<BlockHeight>,
+    #[clap(long, default_value = "0")]
+    shard_id: ShardId,
+    /// Replace the contract of an account for the patched run, given as `ACCOUNT_ID=WASM_FILE`.
+    #[clap(long)]
+    code: Vec<String>,
+    /// JSON file with a list of state records to write for the patched run, in the same format
+    /// as taken by `sandbox_patch_state`.
+    #[clap(long, value_parser)]
+    state_patch: Option<PathBuf>,
+}
+
+impl ReplayPatchedCmd {
+    pub fn run(self, home_dir: &Path, unc_config: UncConfig, store: Store) {
+        let patch = StatePatch::load(&self.code, self.state_patch.as_deref()).unwrap();
+        let hash = self.hash.map(|hash| CryptoHash::from_str(&hash).unwrap());
+        replay_patched(
+            hash,
+            self.start_height,
+            self.end_height,
+            self.shard_id,
+            patch,
+            home_dir,
+            unc_config,
+            store,
+        )
+        .unwrap();
+    }
+}
+
 #[derive(clap::Parser)]
 pub struct RocksDBStatsCmd {
     /// Location of the dumped Rocks DB stats.
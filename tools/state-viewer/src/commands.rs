@@ -2,6 +2,7 @@ use crate::apply_chain_range::apply_chain_range;
 use crate::contract_accounts::ContractAccount;
 use crate::contract_accounts::ContractAccountFilter;
 use crate::contract_accounts::Summary;
+use crate::replay_patched::StatePatch;
 use crate::state_dump::state_dump;
 use crate::state_dump::state_dump_redis;
 use crate::tx_dump::dump_tx_from_block;
@@ -33,7 +34,9 @@ use unc_epoch_manager::EpochManagerHandle;
 use unc_epoch_manager::{EpochManager, EpochManagerAdapter};
 use unc_primitives::account::id::AccountId;
 use unc_primitives::block::{Block, BlockHeader};
+use unc_primitives::congestion_info::BlockCongestionInfo;
 use unc_primitives::hash::CryptoHash;
+use unc_primitives::receipt::Receipt;
 use unc_primitives::shard_layout::ShardLayout;
 use unc_primitives::shard_layout::ShardUId;
 use unc_primitives::sharding::ChunkHash;
@@ -59,12 +62,39 @@ pub(crate) fn apply_block(
     chain_store: &mut ChainStore,
     use_flat_storage: bool,
 ) -> (Block, ApplyChunkResult) {
-    let block = chain_store.get_block(&block_hash).unwrap();
-    let height = block.header().height();
-    let shard_uid = epoch_manager.shard_id_to_uid(shard_id, block.header().epoch_id()).unwrap();
     if use_flat_storage {
+        let epoch_id = chain_store.get_block_header(&block_hash).unwrap().epoch_id().clone();
+        let shard_uid = epoch_manager.shard_id_to_uid(shard_id, &epoch_id).unwrap();
         runtime.get_flat_storage_manager().create_flat_storage_for_shard(shard_uid).unwrap();
     }
+    apply_block_with_storage(
+        block_hash,
+        shard_id,
+        epoch_manager,
+        runtime,
+        chain_store,
+        |root| RuntimeStorageConfig::new(root, use_flat_storage),
+        |receipts| receipts,
+    )
+}
+
+/// Applies the chunk of `shard_id` in the block, reading the state as configured by
+/// `storage_config` given the state root the chunk was originally applied to.
+///
+/// If the block has a new chunk, the receipts it originally received are passed through
+/// `incoming_receipts` before being applied.
+pub(crate) fn apply_block_with_storage(
+    block_hash: CryptoHash,
+    shard_id: ShardId,
+    epoch_manager: &dyn EpochManagerAdapter,
+    runtime: &dyn RuntimeAdapter,
+    chain_store: &mut ChainStore,
+    storage_config: impl FnOnce(StateRoot) -> RuntimeStorageConfig,
+    incoming_receipts: impl FnOnce(Vec<Receipt>) -> Vec<Receipt>,
+) -> (Block, ApplyChunkResult) {
+    let block = chain_store.get_block(&block_hash).unwrap();
+    let height = block.header().height();
+    let shard_uid = epoch_manager.shard_id_to_uid(shard_id, block.header().epoch_id()).unwrap();
     let apply_result = if block.chunks()[shard_id as usize].height_included() == height {
         let chunk = chain_store.get_chunk(&block.chunks()[shard_id as usize].chunk_hash()).unwrap();
         let prev_block = chain_store.get_block(block.header().prev_hash()).unwrap();
@@ -77,7 +107,7 @@ pub(crate) fn apply_block(
                 prev_block.chunks()[shard_id as usize].height_included(),
             )
             .unwrap();
-        let receipts = collect_receipts_from_response(&receipt_proof_response);
+        let receipts = incoming_receipts(collect_receipts_from_response(&receipt_proof_response));
 
         let chunk_inner = chunk.cloned_header().take_inner();
         let is_first_block_with_chunk_of_version = check_if_block_is_first_with_chunk_of_version(
@@ -90,7 +120,7 @@ pub(crate) fn apply_block(
 
         runtime
             .apply_chunk(
-                RuntimeStorageConfig::new(*chunk_inner.prev_state_root(), use_flat_storage),
+                storage_config(*chunk_inner.prev_state_root()),
                 ApplyChunkShardContext {
                     shard_id,
                     last_validator_power_proposals: chunk_inner.prev_validator_power_proposals(),
@@ -102,6 +132,7 @@ pub(crate) fn apply_block(
                 ApplyChunkBlockContext::from_header(
                     block.header(),
                     prev_block.header().next_gas_price(),
+                    block.block_congestion_info(),
                 ),
                 &receipts,
                 chunk.transactions(),
@@ -113,7 +144,7 @@ pub(crate) fn apply_block(
 
         runtime
             .apply_chunk(
-                RuntimeStorageConfig::new(*chunk_extra.state_root(), use_flat_storage),
+                storage_config(*chunk_extra.state_root()),
                 ApplyChunkShardContext {
                     shard_id,
                     last_validator_power_proposals: chunk_extra.validator_power_proposals(),
@@ -125,6 +156,7 @@ pub(crate) fn apply_block(
                 ApplyChunkBlockContext::from_header(
                     block.header(),
                     block.header().next_gas_price(),
+                    BlockCongestionInfo::default(),
                 ),
                 &[],
                 &[],
@@ -487,7 +519,10 @@ fn chunk_extras_equal(l: &ChunkExtra, r: &ChunkExtra) -> bool {
     match (l, r) {
         (ChunkExtra::V1(l), ChunkExtra::V1(r)) => return l == r,
         (ChunkExtra::V2(l), ChunkExtra::V2(r)) => return l == r,
-        (ChunkExtra::V1(_), ChunkExtra::V2(_)) | (ChunkExtra::V2(_), ChunkExtra::V1(_)) => {}
+        (ChunkExtra::V3(l), ChunkExtra::V3(r)) => return l == r,
+        (ChunkExtra::V1(_), ChunkExtra::V2(_) | ChunkExtra::V3(_))
+        | (ChunkExtra::V2(_), ChunkExtra::V1(_) | ChunkExtra::V3(_))
+        | (ChunkExtra::V3(_), ChunkExtra::V1(_) | ChunkExtra::V2(_)) => {}
     };
     if l.state_root() != r.state_root() {
         return false;
@@ -504,6 +539,9 @@ fn chunk_extras_equal(l: &ChunkExtra, r: &ChunkExtra) -> bool {
     if l.balance_burnt() != r.balance_burnt() {
         return false;
     }
+    if l.congestion_info() != r.congestion_info() {
+        return false;
+    }
     l.validator_power_proposals().collect::<Vec<_>>()
         == r.validator_power_proposals().collect::<Vec<_>>()
         && l.validator_pledge_proposals().collect::<Vec<_>>()
@@ -701,6 +739,56 @@ pub(crate) fn replay_chain(
     }
 }
 
+pub(crate) fn replay_patched(
+    hash: Option<CryptoHash>,
+    start_height: Option<BlockHeight>,
+    end_height: Option<BlockHeight>,
+    shard_id: ShardId,
+    patch: StatePatch,
+    home_dir: &Path,
+    unc_config: UncConfig,
+    store: Store,
+) -> anyhow::Result<()> {
+    let mut chain_store = ChainStore::new(
+        store.clone(),
+        unc_config.genesis.config.genesis_height,
+        unc_config.client_config.save_trie_changes,
+    );
+    let epoch_manager = EpochManager::new_arc_handle(store.clone(), &unc_config.genesis.config);
+    let runtime =
+        NightshadeRuntime::from_config(home_dir, store, &unc_config, epoch_manager.clone());
+    let (start_height, end_height, shard_id) = match &hash {
+        Some(hash) => {
+            let (height, shard_id) =
+                crate::replay_patched::find_outcome(hash, epoch_manager.as_ref(), &chain_store)?;
+            (height, height, shard_id)
+        }
+        None => {
+            let start_height = start_height
+                .ok_or_else(|| anyhow::anyhow!("either --hash or --start-height is needed"))?;
+            (start_height, end_height.unwrap_or(start_height), shard_id)
+        }
+    };
+    let diffs = crate::replay_patched::replay_patched(
+        start_height,
+        end_height,
+        shard_id,
+        hash.as_ref(),
+        &patch,
+        epoch_manager.as_ref(),
+        runtime.as_ref(),
+        &mut chain_store,
+    )?;
+    for diff in diffs {
+        if diff.is_empty() {
+            println!("#{}: no differences", diff.height);
+        } else {
+            print!("{}", diff);
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn resulting_chunk_extra(result: &ApplyChunkResult, gas_limit: Gas) -> ChunkExtra {
     let (outcome_root, _) = ApplyChunkResult::compute_outcomes_proof(&result.outcomes);
     ChunkExtra::new(
@@ -711,6 +799,7 @@ pub(crate) fn resulting_chunk_extra(result: &ApplyChunkResult, gas_limit: Gas) -
         result.total_gas_burnt,
         gas_limit,
         result.total_balance_burnt,
+        result.congestion_info,
     )
 }
 
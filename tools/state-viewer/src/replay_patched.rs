@@ -0,0 +1,670 @@
+//! Replaying historical chunks on top of patched state.
+//!
+//! Every chunk is applied twice: once on the state it was originally applied to, and once on the
+//! same state with a patch on top. The state produced by the patched run is only kept in memory,
+//! so the next chunk in the range is applied on top of it without anything being written to the
+//! database.
+//!
+//! The receipts the patched run sends to its own shard are received by the next chunk of the
+//! patched run in place of the ones the original run sent. Receipts to other shards cannot be
+//! followed without replaying those shards too, so the replay stops at the first chunk whose runs
+//! send different receipts to other shards.
+
+use crate::commands::apply_block_with_storage;
+use anyhow::{anyhow, Context};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use unc_chain::types::{ApplyChunkResult, RuntimeAdapter, RuntimeStorageConfig, StorageDataSource};
+use unc_chain::{ChainStore, ChainStoreAccess};
+use unc_epoch_manager::EpochManagerAdapter;
+use unc_primitives::hash::{hash, CryptoHash};
+use unc_primitives::receipt::Receipt;
+use unc_primitives::serialize::to_base64;
+use unc_primitives::state_record::StateRecord;
+use unc_primitives::transaction::ExecutionOutcome;
+use unc_primitives::trie_key::TrieKey;
+use unc_primitives::types::{AccountId, BlockHeight, EpochId, Gas, ShardId, StateChangeCause};
+use unc_store::{
+    get_account, get_code, set_access_key, set_account, set_rsa2048_keys, TrieChanges, TrieUpdate,
+};
+
+/// Changes made to the state before the first chunk is replayed.
+#[derive(Default)]
+pub(crate) struct StatePatch {
+    /// Records written as they are, in the format accepted by `sandbox_patch_state`.
+    pub records: Vec<StateRecord>,
+    /// Contracts to deploy in place of the current code of the accounts.
+    pub code: Vec<(AccountId, Vec<u8>)>,
+}
+
+impl StatePatch {
+    /// Reads the patch from the `ACCOUNT_ID=PATH` pairs of contracts to replace and a JSON file
+    /// with a list of state records.
+    pub(crate) fn load(code: &[String], state_patch: Option<&Path>) -> anyhow::Result<Self> {
+        let records = match state_patch {
+            Some(path) => {
+                let file = std::fs::File::open(path)
+                    .with_context(|| format!("failed to open {}", path.display()))?;
+                serde_json::from_reader(std::io::BufReader::new(file)).with_context(|| {
+                    format!("failed to parse state records in {}", path.display())
+                })?
+            }
+            None => vec![],
+        };
+        let code = code
+            .iter()
+            .map(|arg| {
+                let (account_id, path) = arg
+                    .split_once('=')
+                    .ok_or_else(|| anyhow!("expected ACCOUNT_ID=PATH, got {arg}"))?;
+                let account_id = account_id.parse()?;
+                let code = std::fs::read(path).with_context(|| format!("failed to read {path}"))?;
+                Ok((account_id, code))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { records, code })
+    }
+
+    fn apply(&self, state_update: &mut TrieUpdate) -> anyhow::Result<()> {
+        for record in &self.records {
+            match record.clone() {
+                StateRecord::Account { account_id, account } => {
+                    set_account(state_update, account_id, &account)
+                }
+                StateRecord::Data { account_id, data_key, value } => state_update
+                    .set(TrieKey::ContractData { account_id, key: data_key.into() }, value.into()),
+                StateRecord::Contract { account_id, code } => {
+                    deploy(state_update, account_id, code)?
+                }
+                StateRecord::AccessKey { account_id, public_key, access_key } => {
+                    set_access_key(state_update, account_id, public_key, &access_key)
+                }
+                StateRecord::Rsa2048Keys { account_id, public_key, rsa_key } => {
+                    set_rsa2048_keys(state_update, account_id, public_key, &rsa_key)
+                }
+                record => return Err(anyhow!("cannot patch the state with {record}")),
+            }
+        }
+        for (account_id, code) in &self.code {
+            deploy(state_update, account_id.clone(), code.clone())?;
+        }
+        state_update.commit(StateChangeCause::Migration);
+        Ok(())
+    }
+}
+
+/// Replaces the code of the account like a deployment does, keeping its code hash and storage
+/// usage in sync so the new code gets run and storage staking is checked as on chain.
+fn deploy(
+    state_update: &mut TrieUpdate,
+    account_id: AccountId,
+    code: Vec<u8>,
+) -> anyhow::Result<()> {
+    let mut account = get_account(state_update, &account_id)?
+        .ok_or_else(|| anyhow!("cannot deploy a contract to missing account {account_id}"))?;
+    let prev_code = get_code(state_update, &account_id, Some(account.code_hash()))?;
+    let prev_code_length = prev_code.map_or(0, |code| code.code().len() as u64);
+    let storage_usage = account
+        .storage_usage()
+        .saturating_sub(prev_code_length)
+        .checked_add(code.len() as u64)
+        .ok_or_else(|| anyhow!("storage usage of {account_id} overflows"))?;
+    account.set_storage_usage(storage_usage);
+    account.set_code_hash(hash(&code));
+    set_account(state_update, account_id.clone(), &account);
+    state_update.set(TrieKey::ContractCode { account_id }, code);
+    Ok(())
+}
+
+/// Where the results of one chunk differ between the original and the patched run.
+pub(crate) struct ReplayDiff {
+    pub height: BlockHeight,
+    pub gas_burnt: (Gas, Gas),
+    pub outcomes: Vec<OutcomeDiff>,
+    pub state_changes: Vec<StateChangeDiff>,
+    /// Whether the runs sent different receipts to other shards, which ends the replay.
+    pub other_shard_receipts_differ: bool,
+}
+
+/// Outcome of a receipt or transaction, `None` if the run did not execute it.
+pub(crate) struct OutcomeDiff {
+    pub id: CryptoHash,
+    pub original: Option<ExecutionOutcome>,
+    pub patched: Option<ExecutionOutcome>,
+}
+
+/// Final value a run left at the key, `None` if the run did not write to the key and
+/// `Some(None)` if it removed the value.
+pub(crate) struct StateChangeDiff {
+    pub key: TrieKey,
+    pub original: Option<Option<Vec<u8>>>,
+    pub patched: Option<Option<Vec<u8>>>,
+}
+
+impl ReplayDiff {
+    fn new(
+        height: BlockHeight,
+        original: &ApplyChunkResult,
+        patched: &ApplyChunkResult,
+        other_shard_receipts_differ: bool,
+        only_outcome: Option<&CryptoHash>,
+    ) -> Self {
+        let is_selected = |id: &CryptoHash| only_outcome.map_or(true, |only| only == id);
+        let mut patched_outcomes: HashMap<_, _> =
+            patched.outcomes.iter().map(|outcome| (outcome.id, &outcome.outcome)).collect();
+        let mut outcomes = vec![];
+        for outcome in &original.outcomes {
+            let patched = patched_outcomes.remove(&outcome.id);
+            if is_selected(&outcome.id) && patched != Some(&outcome.outcome) {
+                outcomes.push(OutcomeDiff {
+                    id: outcome.id,
+                    original: Some(outcome.outcome.clone()),
+                    patched: patched.cloned(),
+                });
+            }
+        }
+        for outcome in &patched.outcomes {
+            if is_selected(&outcome.id) && patched_outcomes.contains_key(&outcome.id) {
+                outcomes.push(OutcomeDiff {
+                    id: outcome.id,
+                    original: None,
+                    patched: Some(outcome.outcome.clone()),
+                });
+            }
+        }
+
+        let mut original_values = final_values(original);
+        let mut state_changes = vec![];
+        for (raw_key, (key, patched)) in final_values(patched) {
+            let original = original_values.remove(&raw_key).map(|(_, value)| value);
+            if original.as_ref() != Some(&patched) {
+                state_changes.push(StateChangeDiff { key, original, patched: Some(patched) });
+            }
+        }
+        state_changes.extend(original_values.into_values().map(|(key, original)| {
+            StateChangeDiff { key, original: Some(original), patched: None }
+        }));
+
+        Self {
+            height,
+            gas_burnt: (original.total_gas_burnt, patched.total_gas_burnt),
+            outcomes,
+            state_changes,
+            other_shard_receipts_differ,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.gas_burnt.0 == self.gas_burnt.1
+            && self.outcomes.is_empty()
+            && self.state_changes.is_empty()
+            && !self.other_shard_receipts_differ
+    }
+}
+
+impl std::fmt::Display for ReplayDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "#{}: gas burnt {} -> {}", self.height, self.gas_burnt.0, self.gas_burnt.1)?;
+        for outcome in &self.outcomes {
+            writeln!(f, "  outcome of {}", outcome.id)?;
+            writeln!(f, "    original: {:?}", outcome.original)?;
+            writeln!(f, "    patched:  {:?}", outcome.patched)?;
+        }
+        for change in &self.state_changes {
+            writeln!(f, "  {:?}", change.key)?;
+            writeln!(f, "    original: {}", FinalValue(&change.original))?;
+            writeln!(f, "    patched:  {}", FinalValue(&change.patched))?;
+        }
+        if self.other_shard_receipts_differ {
+            writeln!(f, "  receipts sent to other shards differ, the replay cannot go further")?;
+        }
+        Ok(())
+    }
+}
+
+struct FinalValue<'a>(&'a Option<Option<Vec<u8>>>);
+
+impl std::fmt::Display for FinalValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            None => write!(f, "untouched"),
+            Some(None) => write!(f, "removed"),
+            Some(Some(value)) if value.len() <= 64 => write!(f, "{}", to_base64(value)),
+            Some(Some(value)) => write!(f, "{} bytes with hash {}", value.len(), hash(value)),
+        }
+    }
+}
+
+/// The value each key touched by the chunk was left with.
+fn final_values(result: &ApplyChunkResult) -> BTreeMap<Vec<u8>, (TrieKey, Option<Vec<u8>>)> {
+    result
+        .trie_changes
+        .state_changes()
+        .iter()
+        .filter_map(|change| {
+            let value = change.changes.last()?.data.clone();
+            Some((change.trie_key.to_vec(), (change.trie_key.clone(), value)))
+        })
+        .collect()
+}
+
+/// Splits the receipts sent by the chunk into the ones to `shard_id` itself and the ones to other
+/// shards.
+fn split_outgoing_receipts(
+    result: &ApplyChunkResult,
+    shard_id: ShardId,
+    epoch_id: &EpochId,
+    epoch_manager: &dyn EpochManagerAdapter,
+) -> anyhow::Result<(Vec<Receipt>, Vec<Receipt>)> {
+    let mut own_shard = vec![];
+    let mut other_shards = vec![];
+    for receipt in &result.outgoing_receipts {
+        if epoch_manager.account_id_to_shard_id(&receipt.receiver_id, epoch_id)? == shard_id {
+            own_shard.push(receipt.clone());
+        } else {
+            other_shards.push(receipt.clone());
+        }
+    }
+    Ok((own_shard, other_shards))
+}
+
+/// Replaces the receipts the original run sent to its own shard with the ones the patched run sent,
+/// keeping the receipts received from other shards as they are.
+fn replace_own_shard_receipts(
+    received: Vec<Receipt>,
+    original: &[Receipt],
+    patched: &[Receipt],
+) -> Vec<Receipt> {
+    let original_ids: HashSet<_> = original.iter().map(|receipt| receipt.receipt_id).collect();
+    let mut patched = Some(patched);
+    let mut receipts = vec![];
+    for receipt in received {
+        if !original_ids.contains(&receipt.receipt_id) {
+            receipts.push(receipt);
+        } else if let Some(patched) = patched.take() {
+            // The patched receipts take the place of the original ones in the order of receipts.
+            receipts.extend_from_slice(patched);
+        }
+    }
+    if let Some(patched) = patched {
+        receipts.extend_from_slice(patched);
+    }
+    receipts
+}
+
+fn extend_overlay(overlay: &mut Arc<HashMap<CryptoHash, Arc<[u8]>>>, trie_changes: &TrieChanges) {
+    // The tries reading the overlay are gone by now, so this does not copy it.
+    Arc::make_mut(overlay).extend(
+        trie_changes
+            .insertions()
+            .iter()
+            .map(|insertion| (*insertion.hash(), insertion.payload().into())),
+    );
+}
+
+/// Finds the height of the block in which the receipt or transaction was executed and the shard
+/// it was executed on.
+pub(crate) fn find_outcome(
+    hash: &CryptoHash,
+    epoch_manager: &dyn EpochManagerAdapter,
+    chain_store: &ChainStore,
+) -> anyhow::Result<(BlockHeight, ShardId)> {
+    let outcome = chain_store
+        .get_outcomes_by_id(hash)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("{hash} was not executed in any known block"))?;
+    let header = chain_store.get_block_header(&outcome.block_hash)?;
+    let shard_id = epoch_manager
+        .account_id_to_shard_id(&outcome.outcome_with_id.outcome.executor_id, header.epoch_id())?;
+    Ok((header.height(), shard_id))
+}
+
+/// Replays the chunks of `shard_id` in the blocks from `start_height` to `end_height` on both the
+/// original and the patched state, returning the differences for each of them.
+///
+/// If `only_outcome` is given, outcomes of other receipts and transactions are not compared.
+pub(crate) fn replay_patched(
+    start_height: BlockHeight,
+    end_height: BlockHeight,
+    shard_id: ShardId,
+    only_outcome: Option<&CryptoHash>,
+    patch: &StatePatch,
+    epoch_manager: &dyn EpochManagerAdapter,
+    runtime: &dyn RuntimeAdapter,
+    chain_store: &mut ChainStore,
+) -> anyhow::Result<Vec<ReplayDiff>> {
+    let mut overlay = Arc::new(HashMap::new());
+    let mut patched_root = None;
+    // Receipts the runs sent to the shard itself, which the next new chunk of the shard receives.
+    let mut original_pending = vec![];
+    let mut patched_pending = vec![];
+    let mut diffs = vec![];
+    for height in start_height..=end_height {
+        let Ok(block_hash) = chain_store.get_block_hash_by_height(height) else { continue };
+        let patched_prev_root = match patched_root {
+            Some(root) => root,
+            None => {
+                let header = chain_store.get_block_header(&block_hash)?;
+                let shard_uid = epoch_manager.shard_id_to_uid(shard_id, header.epoch_id())?;
+                let prev_hash = header.prev_hash();
+                let prev_root = *chain_store.get_chunk_extra(prev_hash, &shard_uid)?.state_root();
+                let trie = runtime.get_trie_for_shard(shard_id, prev_hash, prev_root, false)?;
+                let mut state_update = TrieUpdate::new(trie);
+                patch.apply(&mut state_update)?;
+                let (_, trie_changes, _) = state_update.finalize()?;
+                extend_overlay(&mut overlay, &trie_changes);
+                trie_changes.new_root
+            }
+        };
+
+        // Neither run can use flat storage, as it does not know the patched state. Both runs
+        // charge gas as if they did, so that the gas they burn is comparable.
+        let (block, original) = apply_block_with_storage(
+            block_hash,
+            shard_id,
+            epoch_manager,
+            runtime,
+            chain_store,
+            |root| RuntimeStorageConfig {
+                source: StorageDataSource::DbTrieOnly,
+                ..RuntimeStorageConfig::new(root, false)
+            },
+            |receipts| receipts,
+        );
+        let (_, patched) = apply_block_with_storage(
+            block_hash,
+            shard_id,
+            epoch_manager,
+            runtime,
+            chain_store,
+            |_| RuntimeStorageConfig {
+                source: StorageDataSource::DbTrieWithOverlay(overlay.clone()),
+                ..RuntimeStorageConfig::new(patched_prev_root, false)
+            },
+            |receipts| replace_own_shard_receipts(receipts, &original_pending, &patched_pending),
+        );
+        extend_overlay(&mut overlay, patched.trie_changes.trie_changes());
+        patched_root = Some(patched.new_root);
+
+        if block.chunks()[shard_id as usize].height_included() == height {
+            original_pending.clear();
+            patched_pending.clear();
+        }
+        let epoch_id = block.header().epoch_id();
+        let (original_own, original_other) =
+            split_outgoing_receipts(&original, shard_id, epoch_id, epoch_manager)?;
+        let (patched_own, patched_other) =
+            split_outgoing_receipts(&patched, shard_id, epoch_id, epoch_manager)?;
+        original_pending.extend(original_own);
+        patched_pending.extend(patched_own);
+
+        let other_shard_receipts_differ = original_other != patched_other;
+        diffs.push(ReplayDiff::new(
+            height,
+            &original,
+            &patched,
+            other_shard_receipts_differ,
+            only_outcome,
+        ));
+        if other_shard_receipts_differ {
+            break;
+        }
+    }
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_outcome, replay_patched, StatePatch};
+    use framework::config::GenesisExt;
+    use framework::NightshadeRuntime;
+    use std::path::Path;
+    use std::sync::Arc;
+    use unc_chain::types::RuntimeAdapter;
+    use unc_chain::{ChainGenesis, ChainStore, ChainStoreAccess, Provenance};
+    use unc_chain_configs::Genesis;
+    use unc_client::test_utils::TestEnv;
+    use unc_client::ProcessTxResponse;
+    use unc_crypto::{InMemorySigner, KeyType};
+    use unc_epoch_manager::{EpochManager, EpochManagerHandle};
+    use unc_primitives::shard_layout::ShardUId;
+    use unc_primitives::state_record::StateRecord;
+    use unc_primitives::transaction::{
+        Action, DeployContractAction, FunctionCallAction, SignedTransaction,
+    };
+    use unc_primitives::trie_key::TrieKey;
+    use unc_primitives::types::{AccountId, BlockHeight};
+    use unc_store::genesis::initialize_genesis_state;
+    use unc_store::test_utils::create_test_store;
+    use unc_store::{get_account, TrieUpdate};
+
+    fn setup_env() -> (TestEnv, ChainStore, Arc<EpochManagerHandle>, Arc<NightshadeRuntime>) {
+        let genesis = Genesis::test(vec!["test0".parse().unwrap(), "test1".parse().unwrap()], 1);
+        let store = create_test_store();
+        initialize_genesis_state(store.clone(), &genesis, None);
+        let epoch_manager = EpochManager::new_arc_handle(store.clone(), &genesis.config);
+        let runtime = NightshadeRuntime::test(
+            Path::new("."),
+            store.clone(),
+            &genesis.config,
+            epoch_manager.clone(),
+        );
+        let env = TestEnv::builder(ChainGenesis::test())
+            .stores(vec![store.clone()])
+            .epoch_managers(vec![epoch_manager.clone()])
+            .track_all_shards()
+            .runtimes(vec![runtime.clone()])
+            .build();
+        let chain_store = ChainStore::new(store, genesis.config.genesis_height, false);
+        (env, chain_store, epoch_manager, runtime)
+    }
+
+    fn produce_block(env: &mut TestEnv, height: BlockHeight) {
+        let block = env.clients[0].produce_block(height).unwrap().unwrap();
+        env.process_block(0, block, Provenance::PRODUCED);
+    }
+
+    #[test]
+    fn test_replay_patched() {
+        let (mut env, mut chain_store, epoch_manager, runtime) = setup_env();
+        let genesis_hash = *env.clients[0].chain.genesis().hash();
+        let signer = InMemorySigner::from_seed("test0".parse().unwrap(), KeyType::ED25519, "test0");
+        for height in 1..5 {
+            let tx = SignedTransaction::send_money(
+                height,
+                "test0".parse().unwrap(),
+                "test1".parse().unwrap(),
+                &signer,
+                100,
+                genesis_hash,
+            );
+            assert_eq!(env.clients[0].process_tx(tx, false, false), ProcessTxResponse::ValidTx);
+            produce_block(&mut env, height);
+        }
+
+        // Without a patch, both runs must agree.
+        let diffs = replay_patched(
+            2,
+            4,
+            0,
+            None,
+            &StatePatch::default(),
+            epoch_manager.as_ref(),
+            runtime.as_ref(),
+            &mut chain_store,
+        )
+        .unwrap();
+        assert_eq!(diffs.len(), 3);
+        assert!(diffs.iter().all(|diff| diff.is_empty()));
+
+        let account_id: AccountId = "test1".parse().unwrap();
+        let prev_hash = chain_store.get_block_hash_by_height(1).unwrap();
+        let prev_root = *chain_store
+            .get_chunk_extra(&prev_hash, &ShardUId::single_shard())
+            .unwrap()
+            .state_root();
+        let trie = runtime.get_trie_for_shard(0, &prev_hash, prev_root, false).unwrap();
+        let mut account = get_account(&trie, &account_id).unwrap().unwrap();
+        account.set_amount(account.amount() * 2);
+        let patch = StatePatch {
+            records: vec![StateRecord::Account { account_id: account_id.clone(), account }],
+            code: vec![],
+        };
+        let diffs = replay_patched(
+            2,
+            4,
+            0,
+            None,
+            &patch,
+            epoch_manager.as_ref(),
+            runtime.as_ref(),
+            &mut chain_store,
+        )
+        .unwrap();
+        for diff in &diffs {
+            assert_eq!(diff.gas_burnt.0, diff.gas_burnt.1);
+            assert!(diff.outcomes.is_empty());
+        }
+        // The transfers leave the receiver with a different balance than in the original run.
+        let account_key = TrieKey::Account { account_id };
+        assert!(diffs.iter().any(|diff| diff.state_changes.iter().any(|change| {
+            change.key == account_key && change.original.is_some() && change.patched.is_some()
+        })));
+    }
+
+    const ORIGINAL_CONTRACT: &str = r#"
+(module
+  (import "env" "log_utf8" (func $log_utf8 (param i64 i64)))
+  (memory 1)
+  (data (i32.const 0) "original")
+  (func (export "main")
+    (call $log_utf8 (i64.const 8) (i64.const 0))))
+"#;
+
+    /// Logs something else and calls `test1`, which the next chunk of the patched run receives.
+    const PATCHED_CONTRACT: &str = r#"
+(module
+  (import "env" "log_utf8" (func $log_utf8 (param i64 i64)))
+  (import "env" "promise_create"
+    (func $promise_create (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+  (memory 1)
+  (data (i32.const 0) "patched")
+  (data (i32.const 16) "test1")
+  (data (i32.const 32) "main")
+  (func (export "main")
+    (call $log_utf8 (i64.const 7) (i64.const 0))
+    (drop (call $promise_create (i64.const 5) (i64.const 16) (i64.const 4) (i64.const 32)
+      (i64.const 0) (i64.const 0) (i64.const 48) (i64.const 10000000000000)))))
+"#;
+
+    #[test]
+    fn test_replay_patched_code() {
+        let (mut env, mut chain_store, epoch_manager, runtime) = setup_env();
+        let contract_id: AccountId = "test0".parse().unwrap();
+        let caller_id: AccountId = "test1".parse().unwrap();
+        let original_code = unc_test_contracts::wat_contract(ORIGINAL_CONTRACT);
+        let patched_code = unc_test_contracts::wat_contract(PATCHED_CONTRACT);
+
+        let deployer = InMemorySigner::from_seed(contract_id.clone(), KeyType::ED25519, "test0");
+        let deploy = env.tx_from_actions(
+            vec![Action::DeployContract(DeployContractAction { code: original_code.clone() })],
+            &deployer,
+            contract_id.clone(),
+        );
+        assert_eq!(env.clients[0].process_tx(deploy, false, false), ProcessTxResponse::ValidTx);
+        for height in 1..4 {
+            produce_block(&mut env, height);
+        }
+        let caller = InMemorySigner::from_seed(caller_id.clone(), KeyType::ED25519, "test1");
+        let call = env.tx_from_actions(
+            vec![Action::FunctionCall(Box::new(FunctionCallAction {
+                method_name: "main".to_string(),
+                args: vec![],
+                gas: 100_000_000_000_000,
+                deposit: 0,
+            }))],
+            &caller,
+            contract_id.clone(),
+        );
+        let call_hash = call.get_hash();
+        assert_eq!(env.clients[0].process_tx(call, false, false), ProcessTxResponse::ValidTx);
+        for height in 4..9 {
+            produce_block(&mut env, height);
+        }
+
+        // The patch replaces the code and its share of the storage usage of the account.
+        let prev_hash = chain_store.get_block_hash_by_height(3).unwrap();
+        let prev_root = *chain_store
+            .get_chunk_extra(&prev_hash, &ShardUId::single_shard())
+            .unwrap()
+            .state_root();
+        let trie = runtime.get_trie_for_shard(0, &prev_hash, prev_root, false).unwrap();
+        let mut state_update = TrieUpdate::new(trie);
+        let storage_usage =
+            get_account(&state_update, &contract_id).unwrap().unwrap().storage_usage();
+        let patch =
+            StatePatch { records: vec![], code: vec![(contract_id.clone(), patched_code.clone())] };
+        patch.apply(&mut state_update).unwrap();
+        let account = get_account(&state_update, &contract_id).unwrap().unwrap();
+        assert_eq!(
+            account.storage_usage() + original_code.len() as u64,
+            storage_usage + patched_code.len() as u64
+        );
+
+        let diffs = replay_patched(
+            4,
+            8,
+            0,
+            None,
+            &patch,
+            epoch_manager.as_ref(),
+            runtime.as_ref(),
+            &mut chain_store,
+        )
+        .unwrap();
+        assert_eq!(diffs.len(), 5);
+        assert!(diffs.iter().all(|diff| !diff.other_shard_receipts_differ));
+        let outcomes: Vec<_> = diffs.iter().flat_map(|diff| &diff.outcomes).collect();
+        let call_outcome = outcomes
+            .iter()
+            .find(|outcome| outcome.original.is_some() && outcome.patched.is_some())
+            .expect("the call should have a different outcome");
+        assert_eq!(call_outcome.original.as_ref().unwrap().logs, vec!["original".to_string()]);
+        assert_eq!(call_outcome.patched.as_ref().unwrap().logs, vec!["patched".to_string()]);
+        // The receipt sent by the patched contract is received and executed by the patched run.
+        assert!(outcomes.iter().any(|outcome| outcome.original.is_none()
+            && outcome.patched.as_ref().is_some_and(|patched| patched.executor_id == caller_id)));
+
+        // With the hash of the call receipt, only its block is replayed and only its outcome is
+        // compared.
+        let (tx_height, shard_id) =
+            find_outcome(&call_hash, epoch_manager.as_ref(), &chain_store).unwrap();
+        let tx_outcome = chain_store.get_outcomes_by_id(&call_hash).unwrap().remove(0);
+        let receipt_id = tx_outcome.outcome_with_id.outcome.receipt_ids[0];
+        let (height, shard_id_of_receipt) =
+            find_outcome(&receipt_id, epoch_manager.as_ref(), &chain_store).unwrap();
+        assert_eq!((shard_id, shard_id_of_receipt), (0, 0));
+        assert!(height > tx_height);
+        let diffs = replay_patched(
+            height,
+            height,
+            shard_id,
+            Some(&receipt_id),
+            &patch,
+            epoch_manager.as_ref(),
+            runtime.as_ref(),
+            &mut chain_store,
+        )
+        .unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].height, height);
+        assert_eq!(diffs[0].outcomes.len(), 1);
+        assert_eq!(diffs[0].outcomes[0].id, receipt_id);
+        assert_eq!(
+            diffs[0].outcomes[0].patched.as_ref().unwrap().logs,
+            vec!["patched".to_string()]
+        );
+    }
+}